@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[command(name = "rustic-ai", about = "Rustic-AI CLI")]
@@ -63,6 +63,65 @@ pub enum Command {
         #[command(subcommand)]
         command: RoutingCommand,
     },
+    Workflow {
+        #[command(subcommand)]
+        command: WorkflowCommand,
+    },
+    /// Serve tools, agents, code retrieval and workflows to MCP clients.
+    McpServe {
+        /// Serve Streamable HTTP instead of stdio, on the given address or
+        /// `mcp.serve.http_bind`.
+        #[arg(long)]
+        http: Option<Option<String>>,
+    },
+    /// Install and manage skill packages.
+    Skills {
+        #[command(subcommand)]
+        command: PackageCommand,
+    },
+    /// Install and manage plugin packages.
+    Plugins {
+        #[command(subcommand)]
+        command: PackageCommand,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum PackageCommand {
+    /// Install archives, package directories or `name[@requirement]` index
+    /// entries. Without packages, installs everything in the lockfile.
+    Install {
+        packages: Vec<String>,
+        #[arg(long, default_value_t = false)]
+        global: bool,
+        /// Reinstall even when the installed copy matches.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    List {
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// Update to the newest versions the lockfile requirements allow.
+    Update {
+        name: Option<String>,
+        #[arg(long, default_value_t = false)]
+        global: bool,
+    },
+    Remove {
+        name: String,
+        #[arg(long, default_value_t = false)]
+        global: bool,
+    },
+    /// Build `<name>-<version>.tar.gz` from a package directory.
+    Pack {
+        dir: String,
+        #[arg(long, default_value = ".")]
+        output: String,
+        /// Index file to add the archive to.
+        #[arg(long)]
+        index: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -342,6 +401,107 @@ pub enum IndexCommand {
     },
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct WorkflowInputArgs {
+    /// Workflow input as inline JSON, or `-` to read JSON from stdin.
+    #[arg(long)]
+    pub input: Option<String>,
+    /// Path to a JSON file with the workflow input, or `-` for stdin.
+    #[arg(long, conflicts_with = "input")]
+    pub input_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum WorkflowCommand {
+    List {
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Show {
+        name: String,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Run {
+        name: String,
+        #[arg(long, default_value = "start")]
+        entrypoint: String,
+        #[arg(long)]
+        agent: Option<String>,
+        #[command(flatten)]
+        input: WorkflowInputArgs,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Trigger {
+        #[arg(long, required_unless_present = "cron")]
+        event: Option<String>,
+        #[arg(long, default_value_t = false, conflicts_with = "event")]
+        cron: bool,
+        /// Look-back window for due cron schedules, for invocation from an external scheduler.
+        #[arg(long, default_value_t = 60)]
+        cron_window_secs: u64,
+        #[arg(long)]
+        agent: Option<String>,
+        #[command(flatten)]
+        input: WorkflowInputArgs,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    /// Watch the workspace and run entrypoints whose `file_changes` globs match, until interrupted.
+    Watch {
+        #[arg(long)]
+        agent: Option<String>,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Runs {
+        #[arg(long)]
+        workflow: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Resume {
+        run_id: String,
+        #[arg(long)]
+        agent: Option<String>,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Validate {
+        /// Loaded workflow name or path to a definition file.
+        target: String,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    DryRun {
+        name: String,
+        #[arg(long, default_value = "start")]
+        entrypoint: String,
+        #[arg(long)]
+        fixtures: Option<String>,
+        #[command(flatten)]
+        input: WorkflowInputArgs,
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+    Graph {
+        /// Loaded workflow name or path to a definition file.
+        target: String,
+        #[arg(long, value_enum, default_value = "mermaid")]
+        format: WorkflowGraphFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum WorkflowGraphFormat {
+    Json,
+    Dot,
+    Mermaid,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum GraphFormat {
     Summary,
@@ -751,6 +751,29 @@ fn run() -> rustic_ai_core::Result<()> {
                 handle_auth_command(&config_path, command)?;
                 return Ok(());
             }
+            cli::Command::Workflow { command } => {
+                if !handle_workflow_command(&config_path, args.session_id.clone(), command)? {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            cli::Command::McpServe { http } => {
+                return serve_mcp(&config_path, http);
+            }
+            cli::Command::Skills { command } => {
+                return handle_package_command(
+                    &config_path,
+                    rustic_ai_core::packages::PackageKind::Skill,
+                    command,
+                );
+            }
+            cli::Command::Plugins { command } => {
+                return handle_package_command(
+                    &config_path,
+                    rustic_ai_core::packages::PackageKind::Plugin,
+                    command,
+                );
+            }
             other => {
                 let app = rustic_ai_core::RusticAI::from_config_path(&config_path)?;
                 match other {
@@ -769,6 +792,69 @@ fn run() -> rustic_ai_core::Result<()> {
                             }
                             println!("  always_apply: {}", rule.always_apply);
                         }
+
+                        if app.config().features.mcp_enabled && !app.config().mcp.servers.is_empty()
+                        {
+                            let runtime = tokio::runtime::Runtime::new().map_err(|err| {
+                                rustic_ai_core::Error::Config(format!(
+                                    "failed to create tokio runtime: {err}"
+                                ))
+                            })?;
+                            let tools = app.runtime().tools.clone();
+                            runtime.block_on(async {
+                                println!("Discovered MCP tools:");
+                                for (server, discovered) in tools.refresh_mcp_tools().await {
+                                    if let Err(err) = discovered {
+                                        println!("- server '{server}' unavailable: {err}");
+                                    }
+                                }
+                                for name in tools.list_tools().await {
+                                    if rustic_ai_core::tools::mcp::split_tool_name(&name).is_none()
+                                    {
+                                        continue;
+                                    }
+                                    let mode = tools
+                                        .get_tool_config(&name)
+                                        .await
+                                        .map(|config| {
+                                            format!("{:?}", config.permission_mode)
+                                                .to_ascii_lowercase()
+                                        })
+                                        .unwrap_or_default();
+                                    println!("- {name} [{mode}]");
+                                    if let Some((description, schema)) =
+                                        tools.get_tool_schema(&name).await
+                                    {
+                                        println!("  description: {description}");
+                                        println!("  input_schema: {schema}");
+                                    }
+                                }
+                                tools.shutdown().await;
+                            });
+                        }
+
+                        let tools = app.runtime().tools.clone();
+                        if !tools.plugin_connections().config().servers.is_empty() {
+                            let runtime = tokio::runtime::Runtime::new().map_err(|err| {
+                                rustic_ai_core::Error::Config(format!(
+                                    "failed to create tokio runtime: {err}"
+                                ))
+                            })?;
+                            runtime.block_on(async {
+                                println!("Discovered plugin tools:");
+                                for (plugin, discovered) in tools.refresh_plugin_tools().await {
+                                    match discovered {
+                                        Ok(count) => {
+                                            println!("- plugin '{plugin}': {count} tools")
+                                        }
+                                        Err(err) => {
+                                            println!("- plugin '{plugin}' unavailable: {err}")
+                                        }
+                                    }
+                                }
+                                tools.shutdown().await;
+                            });
+                        }
                         return Ok(());
                     }
                     cli::Command::Topics => {
@@ -1590,6 +1676,687 @@ fn handle_routing_command(
     Ok(())
 }
 
+fn read_workflow_input(args: &cli::WorkflowInputArgs) -> rustic_ai_core::Result<serde_json::Value> {
+    let read_stdin = || -> rustic_ai_core::Result<String> {
+        let mut raw = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut raw)?;
+        Ok(raw)
+    };
+
+    let raw = match (args.input.as_deref(), args.input_file.as_deref()) {
+        (Some("-"), _) | (_, Some("-")) => read_stdin()?,
+        (Some(inline), _) => inline.to_owned(),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(|err| {
+            rustic_ai_core::Error::Config(format!("failed to read input file '{path}': {err}"))
+        })?,
+        (None, None) => return Ok(serde_json::Value::Object(serde_json::Map::new())),
+    };
+
+    if raw.trim().is_empty() {
+        return Ok(serde_json::Value::Object(serde_json::Map::new()));
+    }
+    serde_json::from_str(&raw).map_err(|err| {
+        rustic_ai_core::Error::Validation(format!("workflow input is not valid JSON: {err}"))
+    })
+}
+
+struct HeadlessWorkflowRun<'a> {
+    session_id: uuid::Uuid,
+    agent: Option<String>,
+    record: rustic_ai_core::workflows::WorkflowRunRecord,
+    resume_from: Option<rustic_ai_core::workflows::WorkflowResumePoint>,
+    dry_run: Option<rustic_ai_core::workflows::WorkflowDryRunFixtures>,
+    output: cli::OutputFormat,
+    store: Option<&'a rustic_ai_core::workflows::WorkflowRunStore>,
+}
+
+/// Runs `mcp-serve`. The app is built with the runtime entered because
+/// storage setup needs one.
+fn serve_mcp(config_path: &Path, http: Option<Option<String>>) -> rustic_ai_core::Result<()> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|err| {
+        rustic_ai_core::Error::Config(format!("failed to create tokio runtime: {err}"))
+    })?;
+    let app = {
+        let _guard = runtime.enter();
+        std::sync::Arc::new(rustic_ai_core::RusticAI::from_config_path(config_path)?)
+    };
+    let bind = http.map(|bind| bind.unwrap_or_else(|| app.config().mcp.serve.http_bind.clone()));
+    runtime.block_on(async {
+        let server = std::sync::Arc::new(rustic_ai_core::mcp::McpServer::new(app).await?);
+        match bind {
+            Some(bind) => server.serve_http(&bind).await,
+            None => server.serve_stdio().await,
+        }
+    })
+}
+
+fn handle_package_command(
+    config_path: &Path,
+    kind: rustic_ai_core::packages::PackageKind,
+    command: cli::PackageCommand,
+) -> rustic_ai_core::Result<()> {
+    use rustic_ai_core::packages::{PackageManager, PackageScope};
+
+    let work_dir = std::env::current_dir().map_err(|err| {
+        rustic_ai_core::Error::Config(format!("failed to resolve current dir: {err}"))
+    })?;
+    let config = if config_path.exists() {
+        rustic_ai_core::config::load_from_file(config_path)?
+    } else {
+        rustic_ai_core::Config::default()
+    };
+    let manager = PackageManager::new(&config, &work_dir);
+    let scope_of = |global: bool| {
+        if global {
+            PackageScope::Global
+        } else {
+            PackageScope::Project
+        }
+    };
+    let kind_name = kind.as_str();
+
+    match command {
+        cli::PackageCommand::Install {
+            packages,
+            global,
+            force,
+        } => {
+            let scope = scope_of(global);
+            let installed = if packages.is_empty() {
+                manager.sync(scope, kind, force)?
+            } else {
+                manager.install(scope, kind, &packages, force)?
+            };
+            if installed.is_empty() {
+                println!("Nothing to install.");
+            }
+            for package in installed {
+                println!(
+                    "Installed {} {} {} ({}) into {}",
+                    package.kind.as_str(),
+                    package.name,
+                    package.version,
+                    scope.as_str(),
+                    manager
+                        .install_dir(scope, package.kind, &package.name)
+                        .display()
+                );
+            }
+        }
+        cli::PackageCommand::List { output } => {
+            let listed = manager.list(kind)?;
+            match output {
+                cli::OutputFormat::Json => {
+                    let rows = listed
+                        .iter()
+                        .map(|installed| {
+                            serde_json::json!({
+                                "scope": installed.scope.as_str(),
+                                "path": installed.path.display().to_string(),
+                                "present": installed.present,
+                                "package": installed.package,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    println!("{}", serde_json::to_string(&rows)?);
+                }
+                cli::OutputFormat::Text if listed.is_empty() => {
+                    println!("No {kind_name} packages installed.");
+                }
+                cli::OutputFormat::Text => {
+                    println!("Installed {kind_name} packages ({}):", listed.len());
+                    for installed in listed {
+                        let package = &installed.package;
+                        let missing = if installed.present { "" } else { " [missing]" };
+                        println!(
+                            "- {} {} ({}, requirement {}){missing}",
+                            package.name,
+                            package.version,
+                            installed.scope.as_str(),
+                            package.requirement
+                        );
+                    }
+                }
+            }
+        }
+        cli::PackageCommand::Update { name, global } => {
+            let updates = manager.update(scope_of(global), kind, name.as_deref())?;
+            if updates.is_empty() {
+                println!("All {kind_name} packages are up to date.");
+            }
+            for update in updates {
+                println!("Updated {} {} -> {}", update.name, update.from, update.to);
+            }
+        }
+        cli::PackageCommand::Remove { name, global } => {
+            let removed = manager.remove(scope_of(global), kind, &name)?;
+            println!("Removed {kind_name} {} {}", removed.name, removed.version);
+        }
+        cli::PackageCommand::Pack { dir, output, index } => {
+            let packed = manager.pack(
+                Path::new(&dir),
+                Path::new(&output),
+                index.as_deref().map(Path::new),
+            )?;
+            if packed.manifest.kind != kind {
+                eprintln!(
+                    "note: '{}' is a {} package",
+                    packed.manifest.name,
+                    packed.manifest.kind.as_str()
+                );
+            }
+            println!("Packed {}", packed.archive.display());
+            println!("sha256 {}", packed.sha256);
+        }
+    }
+    Ok(())
+}
+
+async fn execute_headless_workflow(
+    app: &rustic_ai_core::RusticAI,
+    run: HeadlessWorkflowRun<'_>,
+) -> rustic_ai_core::Result<rustic_ai_core::workflows::WorkflowRunRecord> {
+    use rustic_ai_core::workflows::{WorkflowCheckpoint, WorkflowRunRequest, WorkflowRunStatus};
+
+    let HeadlessWorkflowRun {
+        session_id,
+        agent,
+        mut record,
+        resume_from,
+        dry_run,
+        output,
+        store,
+    } = run;
+    if let Some(store) = store {
+        store.save(&record)?;
+    }
+
+    let checkpoint = std::sync::Arc::new(std::sync::Mutex::new(WorkflowCheckpoint::default()));
+    let mut executor = app.workflow_executor().with_checkpoint(checkpoint.clone());
+    if let Some(fixtures) = dry_run {
+        executor = executor.with_dry_run(fixtures);
+    }
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(256);
+    let render_task = tokio::spawn(async move {
+        let renderer = renderer::Renderer::new(output);
+        while let Some(event) = event_rx.recv().await {
+            renderer.render_event(&event);
+        }
+    });
+
+    let result = executor
+        .run(
+            WorkflowRunRequest {
+                workflow_name: record.workflow.clone(),
+                entrypoint: record.entrypoint.clone(),
+                session_id: session_id.to_string(),
+                agent_name: agent,
+                input: record.input.clone(),
+                params: serde_json::Value::Null,
+                recursion_depth: 0,
+                workflow_stack: Vec::new(),
+                resume_from,
+            },
+            app.runtime().tools.as_ref(),
+            event_tx,
+        )
+        .await;
+    let _ = render_task.await;
+
+    let checkpoint = checkpoint
+        .lock()
+        .map(|guard| guard.clone())
+        .unwrap_or_default();
+    record.finished_at = Some(chrono::Utc::now());
+    record.last_step = checkpoint.step_id;
+    match result {
+        Ok(result) if result.success => {
+            record.status = WorkflowRunStatus::Succeeded;
+            record.steps_executed = result.steps_executed;
+            record.outputs = result.outputs;
+        }
+        Ok(result) => {
+            record.status = WorkflowRunStatus::Failed;
+            record.steps_executed = result.steps_executed;
+            record.outputs = checkpoint.outputs;
+            record.error = Some("workflow finished on a failed step".to_owned());
+        }
+        Err(err) => {
+            record.status = WorkflowRunStatus::Failed;
+            record.outputs = checkpoint.outputs;
+            record.error = Some(err.to_string());
+        }
+    }
+
+    if let Some(store) = store {
+        store.save(&record)?;
+    }
+    Ok(record)
+}
+
+fn print_workflow_run(
+    record: &rustic_ai_core::workflows::WorkflowRunRecord,
+    output: cli::OutputFormat,
+) {
+    match output {
+        cli::OutputFormat::Text => {
+            println!(
+                "Workflow '{}' run {}: status={:?}, steps={}, last_step={}",
+                record.workflow,
+                record.id,
+                record.status,
+                record.steps_executed,
+                record.last_step.as_deref().unwrap_or("<none>")
+            );
+            if let Some(error) = &record.error {
+                println!("  error: {error}");
+            }
+        }
+        cli::OutputFormat::Json => {
+            let mut value = serde_json::to_value(record).unwrap_or_default();
+            if let Some(map) = value.as_object_mut() {
+                map.insert("type".to_owned(), serde_json::json!("workflow_run"));
+            }
+            println!("{}", serde_json::to_string(&value).unwrap_or_default());
+        }
+    }
+}
+
+/// Returns `Ok(false)` when a workflow ran but failed, so callers can exit non-zero.
+fn handle_workflow_command(
+    config_path: &Path,
+    session_id: Option<String>,
+    command: cli::WorkflowCommand,
+) -> rustic_ai_core::Result<bool> {
+    use rustic_ai_core::workflows::{
+        WorkflowDryRunFixtures, WorkflowIssueSeverity, WorkflowRunRecord, WorkflowRunStatus,
+        WorkflowTriggerEngine,
+    };
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|err| {
+        rustic_ai_core::Error::Config(format!("failed to create tokio runtime: {err}"))
+    })?;
+    // Storage pools spawn maintenance tasks on creation, so build the app inside the runtime.
+    let _runtime_guard = runtime.enter();
+    let app = rustic_ai_core::RusticAI::from_config_path(config_path)?;
+    let app = &app;
+    let resolve_session = || -> rustic_ai_core::Result<uuid::Uuid> {
+        match session_id.as_deref() {
+            Some(value) => uuid::Uuid::parse_str(value).map_err(|err| {
+                rustic_ai_core::Error::Config(format!("invalid --session-id '{value}': {err}"))
+            }),
+            None => runtime.block_on(app.session_manager().create_session("workflow")),
+        }
+    };
+    let workflows = app.runtime().workflows.clone();
+    let store = app.workflow_run_store();
+
+    match command {
+        cli::WorkflowCommand::List { output } => {
+            let names = workflows.list();
+            match output {
+                cli::OutputFormat::Json => println!("{}", serde_json::to_string(&names)?),
+                cli::OutputFormat::Text if names.is_empty() => println!("No workflows loaded."),
+                cli::OutputFormat::Text => {
+                    println!("Loaded workflows ({}):", names.len());
+                    for name in names {
+                        let Some(workflow) = workflows.get(&name) else {
+                            continue;
+                        };
+                        let kind = if workflow.template { " [template]" } else { "" };
+                        println!(
+                            "- {name} (versions: {}){kind}: {}",
+                            workflows.versions(&name).join(", "),
+                            workflow.description
+                        );
+                    }
+                }
+            }
+        }
+        cli::WorkflowCommand::Show { name, output } => {
+            let workflow = workflows
+                .get(&name)
+                .ok_or_else(|| rustic_ai_core::Error::NotFound(format!("workflow '{name}'")))?;
+            match output {
+                cli::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(workflow)?),
+                cli::OutputFormat::Text => {
+                    println!("Workflow: {} (v{})", workflow.name, workflow.version);
+                    println!("Description: {}", workflow.description);
+                    for (entry, cfg) in &workflow.entrypoints {
+                        println!(
+                            "Entrypoint '{}' -> step '{}' (cron={}, events={}, webhooks={})",
+                            entry,
+                            cfg.step,
+                            cfg.triggers.cron.len(),
+                            cfg.triggers.events.len(),
+                            cfg.triggers.webhooks.len()
+                        );
+                    }
+                    for (name, parameter) in &workflow.parameters {
+                        println!(
+                            "Parameter '{}'{}: {}",
+                            name,
+                            if parameter.required {
+                                " (required)"
+                            } else {
+                                ""
+                            },
+                            parameter.description
+                        );
+                    }
+                    for (name, contract) in &workflow.outputs {
+                        println!("Output '{}' <- {}", name, contract.from);
+                    }
+                    println!("Steps ({}):", workflow.steps.len());
+                    for step in &workflow.steps {
+                        println!(
+                            "- {} [{:?}] next={} on_success={} on_failure={}",
+                            step.id,
+                            step.kind,
+                            step.next.as_deref().unwrap_or("-"),
+                            step.on_success.as_deref().unwrap_or("-"),
+                            step.on_failure.as_deref().unwrap_or("-")
+                        );
+                    }
+                }
+            }
+        }
+        cli::WorkflowCommand::Run {
+            name,
+            entrypoint,
+            agent,
+            input,
+            output,
+        } => {
+            if workflows.get(&name).is_none() {
+                return Err(rustic_ai_core::Error::NotFound(format!(
+                    "workflow '{name}'"
+                )));
+            }
+            let input = read_workflow_input(&input)?;
+            let session_id = resolve_session()?;
+            let record = runtime.block_on(execute_headless_workflow(
+                app,
+                HeadlessWorkflowRun {
+                    session_id,
+                    agent,
+                    record: WorkflowRunRecord::new(&name, &entrypoint, input),
+                    resume_from: None,
+                    dry_run: None,
+                    output,
+                    store: Some(&store),
+                },
+            ))?;
+            print_workflow_run(&record, output);
+            return Ok(record.status == WorkflowRunStatus::Succeeded);
+        }
+        cli::WorkflowCommand::Trigger {
+            event,
+            cron,
+            cron_window_secs,
+            agent,
+            input,
+            output,
+        } => {
+            let matches = if cron {
+                let now = chrono::Utc::now();
+                let window = chrono::Duration::seconds(cron_window_secs as i64);
+                WorkflowTriggerEngine::new(now - window).due_cron(&workflows, now)
+            } else {
+                WorkflowTriggerEngine::for_event(&workflows, event.as_deref().unwrap_or_default())
+            };
+            if matches.is_empty() {
+                if let cli::OutputFormat::Text = output {
+                    println!("No workflows matched.");
+                }
+                return Ok(true);
+            }
+
+            let input = read_workflow_input(&input)?;
+            let session_id = resolve_session()?;
+            let mut all_succeeded = true;
+            for matched in matches {
+                let record = runtime.block_on(execute_headless_workflow(
+                    app,
+                    HeadlessWorkflowRun {
+                        session_id,
+                        agent: agent.clone(),
+                        record: WorkflowRunRecord::new(
+                            &matched.workflow_name,
+                            &matched.entrypoint,
+                            input.clone(),
+                        ),
+                        resume_from: None,
+                        dry_run: None,
+                        output,
+                        store: Some(&store),
+                    },
+                ))?;
+                print_workflow_run(&record, output);
+                all_succeeded &= record.status == WorkflowRunStatus::Succeeded;
+            }
+            return Ok(all_succeeded);
+        }
+        cli::WorkflowCommand::Watch { agent, output } => {
+            use rustic_ai_core::events::Event;
+            use rustic_ai_core::workflows::WorkflowTriggerReason;
+            use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+            let session_id = resolve_session()?;
+            let mut events = app.runtime().event_bus.subscribe();
+            let watcher = app.start_file_watcher()?;
+            if let cli::OutputFormat::Text = output {
+                println!(
+                    "Watching {} ({}) for file_changes triggers; press Ctrl-C to stop.",
+                    watcher.root().display(),
+                    watcher.backend()
+                );
+            }
+            let settle = std::time::Duration::from_millis(app.config().watcher.debounce_ms + 100);
+            runtime.block_on(async {
+                loop {
+                    let event = match events.recv().await {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return Ok::<(), rustic_ai_core::Error>(()),
+                    };
+                    let Event::FilesChanged {
+                        root,
+                        created,
+                        modified,
+                        deleted,
+                    } = event
+                    else {
+                        continue;
+                    };
+                    let changes = rustic_ai_core::watcher::FileChangeSet {
+                        root: root.into(),
+                        created,
+                        modified,
+                        deleted,
+                    };
+                    let matches = WorkflowTriggerEngine::for_file_changes(&workflows, &changes);
+                    if matches.is_empty() {
+                        continue;
+                    }
+                    for matched in matches {
+                        let WorkflowTriggerReason::FileChange { paths } = &matched.reason else {
+                            continue;
+                        };
+                        let record = execute_headless_workflow(
+                            app,
+                            HeadlessWorkflowRun {
+                                session_id,
+                                agent: agent.clone(),
+                                record: WorkflowRunRecord::new(
+                                    &matched.workflow_name,
+                                    &matched.entrypoint,
+                                    serde_json::json!({ "changed_paths": paths }),
+                                ),
+                                resume_from: None,
+                                dry_run: None,
+                                output,
+                                store: Some(&store),
+                            },
+                        )
+                        .await?;
+                        print_workflow_run(&record, output);
+                    }
+                    // Drop batches caused by the runs themselves so a workflow that
+                    // writes files it watches does not retrigger forever.
+                    tokio::time::sleep(settle).await;
+                    while !matches!(
+                        events.try_recv(),
+                        Err(TryRecvError::Empty | TryRecvError::Closed)
+                    ) {}
+                }
+            })?;
+            return Ok(true);
+        }
+        cli::WorkflowCommand::Runs {
+            workflow,
+            limit,
+            output,
+        } => {
+            let records = store.list(workflow.as_deref(), limit)?;
+            match output {
+                cli::OutputFormat::Json => println!("{}", serde_json::to_string(&records)?),
+                cli::OutputFormat::Text if records.is_empty() => {
+                    println!("No workflow runs recorded.")
+                }
+                cli::OutputFormat::Text => {
+                    for record in records {
+                        println!(
+                            "- {} {} [{:?}] started={} steps={} last_step={}",
+                            record.id,
+                            record.workflow,
+                            record.status,
+                            record.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                            record.steps_executed,
+                            record.last_step.as_deref().unwrap_or("-")
+                        );
+                    }
+                }
+            }
+        }
+        cli::WorkflowCommand::Resume {
+            run_id,
+            agent,
+            output,
+        } => {
+            let previous = store.load(&run_id)?;
+            let resume_from = previous.resume_point().ok_or_else(|| {
+                rustic_ai_core::Error::Validation(format!(
+                    "workflow run '{run_id}' is {:?} and cannot be resumed; only failed runs with a recorded step can",
+                    previous.status
+                ))
+            })?;
+            let session_id = resolve_session()?;
+            let record = runtime.block_on(execute_headless_workflow(
+                app,
+                HeadlessWorkflowRun {
+                    session_id,
+                    agent,
+                    record: WorkflowRunRecord::resumed(&previous),
+                    resume_from: Some(resume_from),
+                    dry_run: None,
+                    output,
+                    store: Some(&store),
+                },
+            ))?;
+            print_workflow_run(&record, output);
+            return Ok(record.status == WorkflowRunStatus::Succeeded);
+        }
+        cli::WorkflowCommand::Validate { target, output } => {
+            let report = runtime.block_on(app.validate_workflow(&target))?;
+            match output {
+                cli::OutputFormat::Json => println!("{}", serde_json::to_string(&report)?),
+                cli::OutputFormat::Text => {
+                    println!(
+                        "Workflow '{}': valid={}, errors={}, warnings={}",
+                        report.workflow,
+                        report.is_valid(),
+                        report.error_count(),
+                        report.warning_count()
+                    );
+                    for issue in &report.issues {
+                        let severity = match issue.severity {
+                            WorkflowIssueSeverity::Error => "error",
+                            WorkflowIssueSeverity::Warning => "warning",
+                        };
+                        match &issue.step_id {
+                            Some(step_id) => {
+                                println!("  [{severity}] step '{step_id}': {}", issue.message)
+                            }
+                            None => println!("  [{severity}] {}", issue.message),
+                        }
+                    }
+                }
+            }
+            return Ok(report.is_valid());
+        }
+        cli::WorkflowCommand::DryRun {
+            name,
+            entrypoint,
+            fixtures,
+            input,
+            output,
+        } => {
+            let fixtures = match fixtures {
+                Some(path) => {
+                    let raw = std::fs::read_to_string(&path).map_err(|err| {
+                        rustic_ai_core::Error::Config(format!(
+                            "failed to read fixtures '{path}': {err}"
+                        ))
+                    })?;
+                    serde_json::from_str::<WorkflowDryRunFixtures>(&raw)?
+                }
+                None => WorkflowDryRunFixtures::default(),
+            };
+            let input = read_workflow_input(&input)?;
+            let session_id = resolve_session()?;
+            let record = runtime.block_on(execute_headless_workflow(
+                app,
+                HeadlessWorkflowRun {
+                    session_id,
+                    agent: None,
+                    record: WorkflowRunRecord::new(&name, &entrypoint, input),
+                    resume_from: None,
+                    dry_run: Some(fixtures),
+                    output,
+                    store: None,
+                },
+            ))?;
+            print_workflow_run(&record, output);
+            if let cli::OutputFormat::Text = output {
+                println!("{}", serde_json::to_string_pretty(&record.outputs)?);
+            }
+            return Ok(record.status == WorkflowRunStatus::Succeeded);
+        }
+        cli::WorkflowCommand::Graph { target, format } => {
+            let workflow = app.resolve_workflow(&target)?;
+            let graph = rustic_ai_core::workflows::build_workflow_graph(&workflow);
+            match format {
+                cli::WorkflowGraphFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&graph)?)
+                }
+                cli::WorkflowGraphFormat::Dot => {
+                    println!("{}", rustic_ai_core::workflows::graph::render_dot(&graph))
+                }
+                cli::WorkflowGraphFormat::Mermaid => {
+                    println!(
+                        "{}",
+                        rustic_ai_core::workflows::graph::render_mermaid(&graph)
+                    )
+                }
+            }
+        }
+    }
+
+    Ok(true)
+}
+
 fn print_value_by_output(
     path: &ConfigPath,
     scope: ConfigScope,
@@ -1673,6 +2440,7 @@ fn error_code(error: &rustic_ai_core::Error) -> &'static str {
         rustic_ai_core::Error::Provider(_) => "provider_error",
         rustic_ai_core::Error::Tool(_) => "tool_error",
         rustic_ai_core::Error::Timeout(_) => "timeout_error",
+        rustic_ai_core::Error::Cancelled(_) => "cancelled",
         rustic_ai_core::Error::Storage(_) => "storage_error",
         rustic_ai_core::Error::Io(_) => "io_error",
         rustic_ai_core::Error::Sqlx(_) => "sqlx_error",
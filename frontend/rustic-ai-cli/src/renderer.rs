@@ -123,12 +123,20 @@ impl Renderer {
                     "[workflow:{workflow}] {status} (steps: {steps_executed}, retries: {retries}, timeouts: {timeouts})"
                 );
             }
-            Event::PermissionRequest { tool, args, .. } => {
+            Event::PermissionRequest {
+                tool, args, grants, ..
+            } => {
                 println!();
                 println!(
                     "[permission] Allow tool '{tool}' with args: {}? (y/n/a/d)",
                     serde_json::to_string(args).unwrap_or_else(|_| "<invalid args>".to_string())
                 );
+                if !grants.is_empty() {
+                    println!(
+                        "  this skill pre-approves for the rest of the turn: {}",
+                        grants.join(", ")
+                    );
+                }
                 println!("  y = allow once");
                 println!("  n = deny");
                 println!("  a = allow in session");
@@ -205,6 +213,44 @@ impl Renderer {
                     "[retrieval] context injected: snippets={snippets}, keyword_hits={keyword_hits}, vector_hits={vector_hits}"
                 );
             }
+            Event::FilesChanged {
+                created,
+                modified,
+                deleted,
+                ..
+            } => {
+                println!(
+                    "[watch] files changed: created={}, modified={}, deleted={}",
+                    created.len(),
+                    modified.len(),
+                    deleted.len()
+                );
+            }
+            Event::McpServerLog { server, line } => {
+                println!("[mcp:{server}] {line}");
+            }
+            Event::McpServerStateChanged {
+                server,
+                state,
+                detail,
+            } => match detail {
+                Some(detail) => println!("[mcp] server '{server}' {state}: {detail}"),
+                None => println!("[mcp] server '{server}' {state}"),
+            },
+            Event::McpSamplingRequested {
+                id,
+                server,
+                agent,
+                summary,
+                max_tokens,
+            } => {
+                println!();
+                println!(
+                    "[mcp] server '{server}' requests a completion from agent '{}' (max {max_tokens} tokens): {summary}",
+                    agent.as_deref().unwrap_or("default")
+                );
+                println!("[mcp] /mcp sampling approve {id}  |  /mcp sampling deny {id}");
+            }
             Event::SessionUpdated(_) => {
                 // Silent for now, useful for debugging
             }
@@ -405,11 +451,13 @@ impl Renderer {
                 session_id,
                 tool,
                 args,
+                grants,
             } => serde_json::json!({
                 "type": "permission_request",
                 "session_id": session_id,
                 "tool": tool,
-                "args": args
+                "args": args,
+                "grants": grants
             }),
             Event::PermissionDecision {
                 session_id,
@@ -521,6 +569,47 @@ impl Renderer {
                 "keyword_hits": keyword_hits,
                 "vector_hits": vector_hits,
             }),
+            Event::FilesChanged {
+                root,
+                created,
+                modified,
+                deleted,
+            } => serde_json::json!({
+                "type": "files_changed",
+                "root": root,
+                "created": created,
+                "modified": modified,
+                "deleted": deleted,
+            }),
+            Event::McpServerLog { server, line } => serde_json::json!({
+                "type": "mcp_server_log",
+                "server": server,
+                "line": line,
+            }),
+            Event::McpServerStateChanged {
+                server,
+                state,
+                detail,
+            } => serde_json::json!({
+                "type": "mcp_server_state_changed",
+                "server": server,
+                "state": state,
+                "detail": detail,
+            }),
+            Event::McpSamplingRequested {
+                id,
+                server,
+                agent,
+                summary,
+                max_tokens,
+            } => serde_json::json!({
+                "type": "mcp_sampling_requested",
+                "id": id,
+                "server": server,
+                "agent": agent,
+                "summary": summary,
+                "max_tokens": max_tokens,
+            }),
             Event::SessionUpdated(id) => serde_json::json!({
                 "type": "session_updated",
                 "session_id": id
@@ -5,16 +5,19 @@ use rustic_ai_core::error::Result;
 use rustic_ai_core::events::Event;
 use rustic_ai_core::permissions::{AskResolution, CommandPatternBucket};
 use rustic_ai_core::rules::TopicTracker;
-use rustic_ai_core::workflows::{WorkflowExecutor, WorkflowExecutorConfig, WorkflowRunRequest};
+use rustic_ai_core::workflows::{WorkflowDryRunFixtures, WorkflowRunRequest};
 use rustic_ai_core::{FeedbackContext, FeedbackType, PreferenceValue, RusticAI};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tokio_util::sync::CancellationToken;
 
+/// Timeout for MCP resource and prompt requests made by REPL commands.
+const MCP_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 #[derive(Debug, Clone)]
 struct PendingPermissionRequest {
     session_id: String,
@@ -338,59 +341,30 @@ impl Repl {
         Ok(fragment_path)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_workflow(
         &self,
         session_id: uuid::Uuid,
         agent_name: &str,
         workflow_name: &str,
         entrypoint: &str,
+        input: Value,
+        dry_run: Option<WorkflowDryRunFixtures>,
         event_tx: mpsc::Sender<Event>,
     ) -> Result<rustic_ai_core::workflows::WorkflowExecutionResult> {
-        let wf_cfg = &self.app.config().workflows;
-        let executor = WorkflowExecutor::new(
-            self.app.runtime().workflows.clone(),
-            self.app.runtime().skills.clone(),
-            std::sync::Arc::new(self.app.runtime().agents.clone()),
-            self.app.session_manager().clone(),
-            WorkflowExecutorConfig {
-                max_recursion_depth: wf_cfg.max_recursion_depth,
-                max_steps_per_run: wf_cfg.max_steps_per_run,
-                working_directory: self.app.work_dir().to_path_buf(),
-                default_timeout_seconds: wf_cfg.default_timeout_seconds,
-                compatibility_preset: wf_cfg.compatibility_preset,
-                switch_case_sensitive_default: wf_cfg.switch_case_sensitive_default,
-                switch_pattern_priority: wf_cfg.switch_pattern_priority.clone(),
-                loop_continue_on_iteration_error_default: wf_cfg
-                    .loop_continue_on_iteration_error_default,
-                wait_timeout_succeeds: wf_cfg.wait_timeout_succeeds,
-                condition_missing_path_as_false: wf_cfg.condition_missing_path_as_false,
-                default_continue_on_error: wf_cfg.default_continue_on_error,
-                continue_on_error_routing: wf_cfg.continue_on_error_routing.clone(),
-                execution_error_policy: wf_cfg.execution_error_policy.clone(),
-                timeout_error_policy: wf_cfg.timeout_error_policy.clone(),
-                default_retry_count: wf_cfg.default_retry_count,
-                default_retry_backoff_ms: wf_cfg.default_retry_backoff_ms,
-                default_retry_backoff_multiplier: wf_cfg.default_retry_backoff_multiplier,
-                default_retry_backoff_max_ms: wf_cfg.default_retry_backoff_max_ms,
-                condition_group_max_depth: wf_cfg.condition_group_max_depth,
-                expression_max_length: wf_cfg.expression_max_length,
-                expression_max_depth: wf_cfg.expression_max_depth,
-                loop_default_max_iterations: wf_cfg.loop_default_max_iterations,
-                loop_default_max_parallelism: wf_cfg.loop_default_max_parallelism,
-                loop_hard_max_parallelism: wf_cfg.loop_hard_max_parallelism,
-                wait_default_poll_interval_ms: wf_cfg.wait_default_poll_interval_ms,
-                wait_default_timeout_seconds: wf_cfg.wait_default_timeout_seconds,
-                dynamic_routing_enabled: self.app.config().features.dynamic_routing_enabled,
-                dynamic_routing: self.app.config().dynamic_routing.clone(),
-                todo_tracking_enabled: self.app.config().features.todo_tracking_enabled,
-                project_id: self
-                    .app
-                    .config()
-                    .project
-                    .as_ref()
-                    .map(|project| project.name.clone()),
-            },
-        );
+        let mut executor = self.app.workflow_executor();
+        if let Some(agent) = self
+            .app
+            .config()
+            .agents
+            .iter()
+            .find(|agent| agent.name == agent_name)
+        {
+            executor = executor.with_agent_permission_mode(agent.permission_mode);
+        }
+        if let Some(fixtures) = dry_run {
+            executor = executor.with_dry_run(fixtures);
+        }
 
         executor
             .run(
@@ -399,9 +373,11 @@ impl Repl {
                     entrypoint: entrypoint.to_owned(),
                     session_id: session_id.to_string(),
                     agent_name: Some(agent_name.to_owned()),
-                    input: Value::Object(serde_json::Map::new()),
+                    input,
+                    params: Value::Null,
                     recursion_depth: 0,
                     workflow_stack: Vec::new(),
+                    resume_from: None,
                 },
                 self.app.runtime().tools.as_ref(),
                 event_tx,
@@ -424,12 +400,20 @@ impl Repl {
         event_tx: mpsc::Sender<Event>,
     ) -> Result<()> {
         for matched in matches {
+            let input = match &matched.reason {
+                rustic_ai_core::workflows::WorkflowTriggerReason::FileChange { paths } => {
+                    serde_json::json!({ "changed_paths": paths })
+                }
+                _ => Value::Object(serde_json::Map::new()),
+            };
             let result = self
                 .run_workflow(
                     session_id,
                     agent_name,
                     &matched.workflow_name,
                     &matched.entrypoint,
+                    input,
+                    None,
                     event_tx.clone(),
                 )
                 .await;
@@ -469,6 +453,19 @@ impl Repl {
                         timeouts
                     );
                 }
+                (
+                    rustic_ai_core::workflows::WorkflowTriggerReason::FileChange { paths },
+                    Ok(run),
+                ) => {
+                    println!(
+                        "Triggered by {} changed file(s) -> workflow '{}' (entrypoint '{}'): success={}, steps={}",
+                        paths.len(),
+                        matched.workflow_name,
+                        matched.entrypoint,
+                        run.success,
+                        run.steps_executed
+                    );
+                }
                 (_, Err(err)) => {
                     println!(
                         "Triggered workflow '{}' (entrypoint '{}') failed: {}",
@@ -481,6 +478,303 @@ impl Repl {
         Ok(())
     }
 
+    /// Merges the watcher batches published since the last call.
+    fn drain_file_changes(
+        events: &mut tokio::sync::broadcast::Receiver<Event>,
+    ) -> rustic_ai_core::watcher::FileChangeSet {
+        use tokio::sync::broadcast::error::TryRecvError;
+
+        let mut merged = rustic_ai_core::watcher::FileChangeSet::default();
+        loop {
+            match events.try_recv() {
+                Ok(Event::FilesChanged {
+                    root,
+                    created,
+                    modified,
+                    deleted,
+                }) => {
+                    merged.root = PathBuf::from(root);
+                    merged.created.extend(created);
+                    merged.modified.extend(modified);
+                    merged.deleted.extend(deleted);
+                }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        for paths in [
+            &mut merged.created,
+            &mut merged.modified,
+            &mut merged.deleted,
+        ] {
+            paths.sort();
+            paths.dedup();
+        }
+        merged
+    }
+
+    /// Reads an MCP resource and formats it as a context message.
+    async fn read_mcp_resource(&self, server: &str, uri: &str, agent_name: &str) -> Result<String> {
+        let client = self
+            .app
+            .runtime()
+            .tools
+            .mcp_client(server, Some(agent_name))
+            .await?;
+        let read = client
+            .request(
+                "resources/read",
+                serde_json::json!({"uri": uri}),
+                MCP_COMMAND_TIMEOUT,
+            )
+            .await?;
+        Ok(format!(
+            "Context from MCP resource {uri} (server '{server}'):\n{}",
+            rustic_ai_core::tools::mcp::render_resource_contents(&read)
+        ))
+    }
+
+    /// Renders `/mcp.<server>.<prompt> key=value ...`. Earlier prompt messages
+    /// are appended to the session; the last one is returned as the turn input.
+    /// Words without `=` continue the previous value.
+    async fn render_mcp_prompt(
+        &self,
+        session_id: uuid::Uuid,
+        agent_name: &str,
+        spec: &str,
+    ) -> Result<String> {
+        let usage = || {
+            rustic_ai_core::Error::Validation(
+                "usage: /mcp.<server>.<prompt> [key=value ...]".to_owned(),
+            )
+        };
+        let mut words = spec.split_whitespace();
+        let (server, prompt) = words
+            .next()
+            .and_then(|name| name.split_once('.'))
+            .filter(|(server, prompt)| !server.is_empty() && !prompt.is_empty())
+            .ok_or_else(usage)?;
+        let mut arguments = serde_json::Map::new();
+        let mut last_key: Option<String> = None;
+        for word in words {
+            match (word.split_once('='), &last_key) {
+                (Some((key, value)), _) if !key.is_empty() => {
+                    arguments.insert(key.to_owned(), Value::String(value.to_owned()));
+                    last_key = Some(key.to_owned());
+                }
+                (_, Some(key)) => {
+                    if let Some(Value::String(value)) = arguments.get_mut(key) {
+                        value.push(' ');
+                        value.push_str(word);
+                    }
+                }
+                _ => return Err(usage()),
+            }
+        }
+
+        let client = self
+            .app
+            .runtime()
+            .tools
+            .mcp_client(server, Some(agent_name))
+            .await?;
+        let rendered = client
+            .request(
+                "prompts/get",
+                serde_json::json!({"name": prompt, "arguments": arguments}),
+                MCP_COMMAND_TIMEOUT,
+            )
+            .await?;
+        let messages = rendered
+            .get("messages")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let Some((last, earlier)) = messages.split_last() else {
+            return Err(rustic_ai_core::Error::Tool(format!(
+                "mcp prompt '{prompt}' on server '{server}' returned no messages"
+            )));
+        };
+        for message in earlier {
+            let role = match message.get("role").and_then(Value::as_str) {
+                Some("assistant") => "assistant",
+                _ => "user",
+            };
+            self.app
+                .session_manager()
+                .append_message(session_id, role, &Self::prompt_message_text(message))
+                .await?;
+        }
+        Ok(Self::prompt_message_text(last))
+    }
+
+    fn prompt_message_text(message: &Value) -> String {
+        let content = message.get("content").cloned().unwrap_or(Value::Null);
+        match content.get("type").and_then(Value::as_str) {
+            Some("text") => content
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned(),
+            Some("resource") => content
+                .get("resource")
+                .and_then(|resource| resource.get("text"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned(),
+            Some(other) => format!("[{other} content omitted]"),
+            None => String::new(),
+        }
+    }
+
+    /// Handles `/mcp ...` commands.
+    async fn handle_mcp_command(
+        &self,
+        session_id: uuid::Uuid,
+        agent_name: &str,
+        args: &[&str],
+        subscriptions: &mut BTreeSet<(String, String)>,
+    ) -> Result<()> {
+        let tools = &self.app.runtime().tools;
+        let servers = |filter: Option<&&str>| {
+            tools
+                .mcp_connections()
+                .config()
+                .servers
+                .iter()
+                .map(|server| server.name.clone())
+                .filter(|name| filter.is_none_or(|filter| name == filter))
+                .collect::<Vec<_>>()
+        };
+        match args {
+            ["resources", rest @ ..] | ["prompts", rest @ ..] if rest.len() <= 1 => {
+                let (method, field) = if args[0] == "resources" {
+                    ("resources/list", "resources")
+                } else {
+                    ("prompts/list", "prompts")
+                };
+                for server in servers(rest.first()) {
+                    let listed = match tools.mcp_client(&server, Some(agent_name)).await {
+                        Ok(client) => client.list_all(method, field, MCP_COMMAND_TIMEOUT).await,
+                        Err(err) => Err(err),
+                    };
+                    match listed {
+                        Ok(items) if items.is_empty() => println!("[mcp] {server}: no {field}"),
+                        Ok(items) => {
+                            println!("[mcp] {server}:");
+                            for item in items {
+                                let key = if field == "resources" { "uri" } else { "name" };
+                                let id = item.get(key).and_then(Value::as_str).unwrap_or("?");
+                                let description = item
+                                    .get("description")
+                                    .or_else(|| item.get("name"))
+                                    .and_then(Value::as_str)
+                                    .filter(|text| *text != id)
+                                    .unwrap_or_default();
+                                if field == "prompts" {
+                                    println!("  /mcp.{server}.{id}  {description}");
+                                } else {
+                                    println!("  {id}  {description}");
+                                }
+                            }
+                        }
+                        Err(err) => println!("[mcp] {server}: {err}"),
+                    }
+                }
+            }
+            ["resource", server, uri] => {
+                let text = self.read_mcp_resource(server, uri, agent_name).await?;
+                self.app
+                    .session_manager()
+                    .append_message(session_id, "user", &text)
+                    .await?;
+                println!("[mcp] added {uri} to the conversation ({} chars)", text.len());
+            }
+            [action @ ("subscribe" | "unsubscribe"), server, uri] => {
+                let client = tools.mcp_client(server, Some(agent_name)).await?;
+                let method = format!("resources/{action}");
+                client
+                    .request(
+                        &method,
+                        serde_json::json!({"uri": uri}),
+                        MCP_COMMAND_TIMEOUT,
+                    )
+                    .await?;
+                let key = ((*server).to_owned(), (*uri).to_owned());
+                if *action == "subscribe" {
+                    subscriptions.insert(key);
+                    println!("[mcp] subscribed to {uri}; updates are added before your next message");
+                } else {
+                    subscriptions.remove(&key);
+                    println!("[mcp] unsubscribed from {uri}");
+                }
+            }
+            ["sampling", decision @ ("approve" | "deny"), id] => {
+                let approved = *decision == "approve";
+                if tools.mcp_connections().sampling().resolve(id, approved) {
+                    println!("[mcp] sampling request {id} {}", if approved { "approved" } else { "denied" });
+                } else {
+                    println!("[mcp] no pending sampling request '{id}'");
+                }
+            }
+            ["sampling"] => {
+                let pending = tools.mcp_connections().sampling().pending();
+                if pending.is_empty() {
+                    println!("[mcp] no pending sampling requests");
+                } else {
+                    println!("[mcp] pending sampling requests: {}", pending.join(", "));
+                }
+            }
+            _ => println!(
+                "Usage: /mcp resources [server] | /mcp resource <server> <uri> | /mcp subscribe|unsubscribe <server> <uri> | /mcp prompts [server] | /mcp sampling [approve|deny <id>]"
+            ),
+        }
+        Ok(())
+    }
+
+    /// Re-reads subscribed MCP resources that changed since the last call and
+    /// adds them to the conversation.
+    async fn drain_mcp_resource_updates(
+        &self,
+        session_id: uuid::Uuid,
+        agent_name: &str,
+        notifications: &mut tokio::sync::broadcast::Receiver<rustic_ai_core::mcp::McpNotification>,
+        subscriptions: &BTreeSet<(String, String)>,
+    ) {
+        use tokio::sync::broadcast::error::TryRecvError;
+
+        let mut updated = BTreeSet::new();
+        loop {
+            match notifications.try_recv() {
+                Ok(notification) if notification.method == "notifications/resources/updated" => {
+                    if let Some(uri) = notification.params.get("uri").and_then(Value::as_str) {
+                        let key = (notification.server, uri.to_owned());
+                        if subscriptions.contains(&key) {
+                            updated.insert(key);
+                        }
+                    }
+                }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        for (server, uri) in updated {
+            let added = match self.read_mcp_resource(&server, &uri, agent_name).await {
+                Ok(text) => self
+                    .app
+                    .session_manager()
+                    .append_message(session_id, "user", &text)
+                    .await
+                    .map(|_| ()),
+                Err(err) => Err(err),
+            };
+            match added {
+                Ok(()) => println!("[mcp] resource {uri} changed; added the new content"),
+                Err(err) => println!("[mcp] failed to refresh {uri}: {err}"),
+            }
+        }
+    }
+
     pub async fn run(&self) -> Result<()> {
         let runtime = tokio::runtime::Runtime::new().map_err(|err| {
             rustic_ai_core::Error::Config(format!("failed to create tokio runtime: {err}"))
@@ -503,6 +797,21 @@ impl Repl {
 
         println!("Session: {session_id}");
 
+        // Subscribe before starting so the first batches are not missed.
+        let mut file_changes = self.app.runtime().event_bus.subscribe();
+        let _file_watcher = if self.app.config().watcher.enabled {
+            let _guard = runtime.enter();
+            match self.app.start_file_watcher() {
+                Ok(watcher) => Some(watcher),
+                Err(err) => {
+                    println!("[watch] disabled: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let (event_tx, mut event_rx) = mpsc::channel(100);
 
         let renderer = Renderer::new(self.output_format);
@@ -537,6 +846,7 @@ impl Repl {
                     session_id,
                     tool,
                     args,
+                    ..
                 } = &event
                 {
                     let mut guard = pending_for_listener.lock().await;
@@ -604,10 +914,52 @@ impl Repl {
             }
         });
 
+        // MCP server stderr and lifecycle changes arrive on the runtime event bus.
+        let mut mcp_events = self.app.runtime().event_bus.subscribe();
+        let mcp_event_tx = event_tx.clone();
+        let mcp_forwarder = tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                match mcp_events.recv().await {
+                    Ok(
+                        event @ (Event::McpServerLog { .. }
+                        | Event::McpServerStateChanged { .. }
+                        | Event::McpSamplingRequested { .. }),
+                    ) => {
+                        if mcp_event_tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        for (server, discovered) in self.app.runtime().tools.refresh_mcp_tools().await {
+            match discovered {
+                Ok(count) => println!("[mcp] server '{server}': {count} tools as mcp.{server}.*"),
+                Err(err) => println!("[mcp] server '{server}' unavailable: {err}"),
+            }
+        }
+        for (plugin, discovered) in self.app.runtime().tools.refresh_plugin_tools().await {
+            match discovered {
+                Ok(count) => println!("[plugin] '{plugin}': {count} tools"),
+                Err(err) => println!("[plugin] '{plugin}' unavailable: {err}"),
+            }
+        }
+        // Sampling requests are approved with `/mcp sampling approve <id>`.
+        let mcp_connections = self.app.runtime().tools.mcp_connections();
+        mcp_connections.sampling().set_approvals_enabled(true);
+        let mut mcp_notifications = mcp_connections.subscribe();
+        let mut mcp_subscriptions = BTreeSet::new();
+
         println!();
         println!("Rustic-AI Interactive Chat");
         println!("Type 'exit' or press Ctrl-C to quit");
         println!("Use /interrupt to cancel an active turn");
+        println!("File changes: /undo [--force] | /redo [--force] | /undo list");
+        println!("Checkpoints: /checkpoints | /diff <checkpoint> | /restore <checkpoint>");
         println!(
             "Use /feedback --type <explicit|success|error> --rating <-1..1> [--comment <text>]"
         );
@@ -616,6 +968,12 @@ impl Repl {
             "Permission shortcuts: /perm path add [global|project|session] <path>, /perm cmd <allow|ask|deny> [global|project|session] <pattern>"
         );
         println!("Workflow triggers: /workflow trigger event <name> | /workflow trigger cron");
+        println!(
+            "MCP: /mcp resources | /mcp resource <server> <uri> | /mcp prompts | /mcp.<server>.<prompt> [key=value ...]"
+        );
+        println!(
+            "Workflow checks: /workflow validate <name|path> | /workflow dry-run <name> [entrypoint] [--fixtures <file>] | /workflow graph <name|path> [dot|mermaid]"
+        );
         println!();
 
         loop {
@@ -759,6 +1117,26 @@ impl Repl {
                     self.run_trigger_matches(session_id, &agent_name, due, event_tx.clone())
                         .await?;
                 }
+
+                let changes = Self::drain_file_changes(&mut file_changes);
+                let due = rustic_ai_core::workflows::WorkflowTriggerEngine::for_file_changes(
+                    self.app.runtime().workflows.as_ref(),
+                    &changes,
+                );
+                if !due.is_empty() {
+                    self.run_trigger_matches(session_id, &agent_name, due, event_tx.clone())
+                        .await?;
+                }
+            }
+
+            if !mcp_subscriptions.is_empty() {
+                self.drain_mcp_resource_updates(
+                    session_id,
+                    &agent_name,
+                    &mut mcp_notifications,
+                    &mcp_subscriptions,
+                )
+                .await;
             }
 
             print!("> ");
@@ -870,6 +1248,143 @@ impl Repl {
                 continue;
             }
 
+            if input == "/undo list" {
+                let history = self
+                    .app
+                    .runtime()
+                    .tools
+                    .file_change_history(&session_id.to_string());
+                if history.is_empty() {
+                    println!("No journaled file changes in this session.");
+                } else {
+                    println!("Journaled file changes (newest first):");
+                    for entry in history {
+                        println!(
+                            "  #{} {} at {}: {}",
+                            entry.id,
+                            entry.operation,
+                            entry.recorded_at.format("%H:%M:%S"),
+                            entry.paths.join(", ")
+                        );
+                    }
+                }
+                continue;
+            }
+
+            if input == "/checkpoints" {
+                let checkpoints = self.app.checkpoint_store();
+                let session_key = session_id.to_string();
+                match tokio::task::spawn_blocking(move || checkpoints.list(&session_key)).await {
+                    Ok(Ok(list)) if list.is_empty() => {
+                        println!("No checkpoints for this session.");
+                    }
+                    Ok(Ok(list)) => {
+                        println!("Checkpoints (newest first):");
+                        for checkpoint in list {
+                            println!(
+                                "  {}  {}  {}",
+                                checkpoint.id,
+                                checkpoint
+                                    .created_at
+                                    .with_timezone(&chrono::Local)
+                                    .format("%Y-%m-%d %H:%M:%S"),
+                                checkpoint.label
+                            );
+                        }
+                    }
+                    Ok(Err(err)) => println!("Failed to list checkpoints: {err}"),
+                    Err(err) => println!("Failed to list checkpoints: {err}"),
+                }
+                continue;
+            }
+
+            if let Some(id) = input.strip_prefix("/diff ") {
+                let checkpoints = self.app.checkpoint_store();
+                let id = id.trim().to_owned();
+                match tokio::task::spawn_blocking(move || checkpoints.diff(&id)).await {
+                    Ok(Ok(diff)) if diff.files.is_empty() => {
+                        println!(
+                            "Workspace matches checkpoint {} ({}).",
+                            diff.checkpoint.id, diff.checkpoint.label
+                        );
+                    }
+                    Ok(Ok(diff)) => {
+                        println!(
+                            "Changes since checkpoint {} ({}): {} file(s), +{} -{}",
+                            diff.checkpoint.id,
+                            diff.checkpoint.label,
+                            diff.files.len(),
+                            diff.insertions,
+                            diff.deletions
+                        );
+                        for file in &diff.files {
+                            println!("  {:<8} {}", file.status, file.path);
+                        }
+                        println!();
+                        print!("{}", diff.patch);
+                        if diff.truncated {
+                            println!("[diff truncated]");
+                        }
+                    }
+                    Ok(Err(err)) => println!("Failed to diff checkpoint: {err}"),
+                    Err(err) => println!("Failed to diff checkpoint: {err}"),
+                }
+                continue;
+            }
+
+            if let Some(id) = input.strip_prefix("/restore ") {
+                let checkpoints = self.app.checkpoint_store();
+                let session_key = session_id.to_string();
+                let id = id.trim().to_owned();
+                match tokio::task::spawn_blocking(move || checkpoints.restore(&session_key, &id))
+                    .await
+                {
+                    Ok(Ok(report)) => {
+                        println!(
+                            "Restored checkpoint {} ({}): {} file(s) written, {} removed.",
+                            report.checkpoint.id,
+                            report.checkpoint.label,
+                            report.written,
+                            report.removed
+                        );
+                        if let Some(previous) = report.previous_state {
+                            println!("Previous state saved as checkpoint {}.", previous.id);
+                        }
+                    }
+                    Ok(Err(err)) => println!("Failed to restore checkpoint: {err}"),
+                    Err(err) => println!("Failed to restore checkpoint: {err}"),
+                }
+                continue;
+            }
+
+            let journal_command = match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["/undo"] => Some((true, false)),
+                ["/undo", "--force"] => Some((true, true)),
+                ["/redo"] => Some((false, false)),
+                ["/redo", "--force"] => Some((false, true)),
+                _ => None,
+            };
+            if let Some((undo, force)) = journal_command {
+                let tools = &self.app.runtime().tools;
+                let session_key = session_id.to_string();
+                let result = if undo {
+                    tools.undo_file_change(&session_key, force)
+                } else {
+                    tools.redo_file_change(&session_key, force)
+                };
+                let verb = if undo { "Undid" } else { "Redid" };
+                match result {
+                    Ok(entry) => println!(
+                        "{verb} {} (#{}): {}",
+                        entry.operation,
+                        entry.id,
+                        entry.paths.join(", ")
+                    ),
+                    Err(err) => println!("{err}"),
+                }
+                continue;
+            }
+
             if input.starts_with("/feedback") {
                 let parsed = match Self::parse_feedback_command(input) {
                     Ok(parsed) => parsed,
@@ -1157,6 +1672,139 @@ impl Repl {
                 continue;
             }
 
+            if let Some(rest) = input
+                .strip_prefix("/workflow validate ")
+                .or_else(|| input.strip_prefix("/workflows validate "))
+            {
+                let target = rest.trim();
+                if target.is_empty() {
+                    println!("Usage: /workflow validate <workflow_name|path>");
+                    continue;
+                }
+                match self.app.validate_workflow(target).await {
+                    Ok(report) => {
+                        println!(
+                            "Workflow '{}': valid={}, errors={}, warnings={}",
+                            report.workflow,
+                            report.is_valid(),
+                            report.error_count(),
+                            report.warning_count()
+                        );
+                        for issue in &report.issues {
+                            let severity = match issue.severity {
+                                rustic_ai_core::workflows::WorkflowIssueSeverity::Error => "error",
+                                rustic_ai_core::workflows::WorkflowIssueSeverity::Warning => {
+                                    "warning"
+                                }
+                            };
+                            match &issue.step_id {
+                                Some(step_id) => {
+                                    println!("  [{severity}] step '{step_id}': {}", issue.message)
+                                }
+                                None => println!("  [{severity}] {}", issue.message),
+                            }
+                        }
+                    }
+                    Err(err) => println!("Workflow validation failed: {err}"),
+                }
+                continue;
+            }
+
+            if let Some(rest) = input
+                .strip_prefix("/workflow graph ")
+                .or_else(|| input.strip_prefix("/workflows graph "))
+            {
+                let parts = rest.split_whitespace().collect::<Vec<_>>();
+                if parts.is_empty() {
+                    println!("Usage: /workflow graph <workflow_name|path> [dot|mermaid]");
+                    continue;
+                }
+                let workflow = match self.app.resolve_workflow(parts[0]) {
+                    Ok(workflow) => workflow,
+                    Err(err) => {
+                        println!("{err}");
+                        continue;
+                    }
+                };
+                let graph = rustic_ai_core::workflows::build_workflow_graph(&workflow);
+                match parts.get(1).copied().unwrap_or("mermaid") {
+                    "dot" => println!("{}", rustic_ai_core::workflows::graph::render_dot(&graph)),
+                    "mermaid" => println!(
+                        "{}",
+                        rustic_ai_core::workflows::graph::render_mermaid(&graph)
+                    ),
+                    other => println!("Unknown graph format '{other}'; expected dot|mermaid"),
+                }
+                continue;
+            }
+
+            if let Some(rest) = input
+                .strip_prefix("/workflow dry-run ")
+                .or_else(|| input.strip_prefix("/workflows dry-run "))
+            {
+                let mut parts = rest.split_whitespace().collect::<Vec<_>>();
+                let fixtures_path =
+                    parts
+                        .iter()
+                        .position(|part| *part == "--fixtures")
+                        .map(|index| {
+                            let path = parts.get(index + 1).copied();
+                            parts.drain(index..(index + 2).min(parts.len()));
+                            path
+                        });
+                if parts.is_empty() || matches!(fixtures_path, Some(None)) {
+                    println!(
+                        "Usage: /workflow dry-run <workflow_name> [entrypoint] [--fixtures <file>]"
+                    );
+                    continue;
+                }
+                let fixtures = match fixtures_path.flatten() {
+                    Some(path) => match std::fs::read_to_string(path)
+                        .map_err(|err| err.to_string())
+                        .and_then(|raw| {
+                            serde_json::from_str::<WorkflowDryRunFixtures>(&raw)
+                                .map_err(|err| err.to_string())
+                        }) {
+                        Ok(fixtures) => fixtures,
+                        Err(err) => {
+                            println!("Failed to read fixtures '{path}': {err}");
+                            continue;
+                        }
+                    },
+                    None => WorkflowDryRunFixtures::default(),
+                };
+                let workflow_name = parts[0];
+                let entrypoint = parts.get(1).copied().unwrap_or("start");
+
+                match self
+                    .run_workflow(
+                        session_id,
+                        &agent_name,
+                        workflow_name,
+                        entrypoint,
+                        Value::Object(serde_json::Map::new()),
+                        Some(fixtures),
+                        event_tx.clone(),
+                    )
+                    .await
+                {
+                    Ok(result) => {
+                        println!(
+                            "Dry run of '{}' completed: success={}, steps={}",
+                            workflow_name, result.success, result.steps_executed
+                        );
+                        println!(
+                            "{}",
+                            serde_json::to_string_pretty(&result.outputs).unwrap_or_default()
+                        );
+                    }
+                    Err(err) => {
+                        println!("Dry run of '{}' failed: {}", workflow_name, err);
+                    }
+                }
+                continue;
+            }
+
             if let Some(rest) = input
                 .strip_prefix("/workflow run ")
                 .or_else(|| input.strip_prefix("/workflows run "))
@@ -1175,6 +1823,8 @@ impl Repl {
                         &agent_name,
                         workflow_name,
                         entrypoint,
+                        Value::Object(serde_json::Map::new()),
+                        None,
                         event_tx.clone(),
                     )
                     .await
@@ -1249,6 +1899,7 @@ impl Repl {
                             rustic_ai_core::workflows::WorkflowStepKind::Loop => "loop",
                             rustic_ai_core::workflows::WorkflowStepKind::Merge => "merge",
                             rustic_ai_core::workflows::WorkflowStepKind::Switch => "switch",
+                            rustic_ai_core::workflows::WorkflowStepKind::Transform => "transform",
                         };
 
                         let next = step.next.as_deref().unwrap_or("<end>");
@@ -1338,6 +1989,24 @@ impl Repl {
                                     .unwrap_or(0);
                                 println!("      mode={}, inputs={}", mode, input_count);
                             }
+                            rustic_ai_core::workflows::WorkflowStepKind::Transform => {
+                                let operations = step
+                                    .config
+                                    .get("operations")
+                                    .and_then(Value::as_array)
+                                    .map(|ops| {
+                                        ops.iter()
+                                            .filter_map(|op| op.get("op").and_then(Value::as_str))
+                                            .collect::<Vec<_>>()
+                                            .join(",")
+                                    })
+                                    .unwrap_or_default();
+                                let has_template = step.config.get("template").is_some();
+                                println!(
+                                    "      operations=[{}], template={}",
+                                    operations, has_template
+                                );
+                            }
                             rustic_ai_core::workflows::WorkflowStepKind::Switch => {
                                 let case_count = step
                                     .config
@@ -1428,6 +2097,35 @@ impl Repl {
                 continue;
             }
 
+            if let Some(rest) = input
+                .strip_prefix("/mcp ")
+                .or((input == "/mcp").then_some(""))
+            {
+                let args = rest.split_whitespace().collect::<Vec<_>>();
+                if let Err(err) = self
+                    .handle_mcp_command(session_id, &agent_name, &args, &mut mcp_subscriptions)
+                    .await
+                {
+                    println!("[mcp] {err}");
+                }
+                continue;
+            }
+
+            let prompt_input: String;
+            let input = match input.strip_prefix("/mcp.") {
+                Some(spec) => match self.render_mcp_prompt(session_id, &agent_name, spec).await {
+                    Ok(rendered) => {
+                        prompt_input = rendered;
+                        prompt_input.as_str()
+                    }
+                    Err(err) => {
+                        println!("[mcp] {err}");
+                        continue;
+                    }
+                },
+                None => input,
+            };
+
             let topic_updated = self
                 .app
                 .session_manager()
@@ -1472,6 +2170,20 @@ impl Repl {
 
             let agent = self.app.runtime().agents.get_agent(Some(&agent_for_turn))?;
 
+            let checkpoints = self.app.checkpoint_store();
+            if checkpoints.is_enabled() {
+                let session_key = session_id.to_string();
+                let label = input.to_owned();
+                let created =
+                    tokio::task::spawn_blocking(move || checkpoints.create(&session_key, &label))
+                        .await;
+                match created {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(err)) => println!("[checkpoint] skipped: {err}"),
+                    Err(err) => println!("[checkpoint] skipped: {err}"),
+                }
+            }
+
             let agent_clone = agent.clone();
             let session_id_clone = session_id;
             let input_clone = input.to_string();
@@ -1503,6 +2215,18 @@ impl Repl {
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
+        let stopped = self
+            .app
+            .runtime()
+            .tools
+            .end_session(&session_id.to_string())
+            .await;
+        if !stopped.is_empty() {
+            println!("Stopped: {}", stopped.join(", "));
+        }
+        self.app.runtime().shutdown().await;
+        mcp_forwarder.abort();
+
         renderer_handle.await.ok();
         Ok(())
     }
@@ -8,10 +8,10 @@ use crate::learning::{LearningManager, MistakeType};
 use crate::providers::types::{ChatMessage, GenerateOptions, ModelProvider};
 use crate::rag::HybridRetriever;
 use crate::storage::PendingToolState;
+use crate::tools::mcp::{split_tool_name, tool_list_allows};
 use crate::ToolManager;
 use chrono::Utc;
 use serde::Deserialize;
-use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration, Instant};
@@ -25,6 +25,7 @@ const DEFAULT_TOOL_SHORTLIST_ITEMS: usize = 12;
 const DEFAULT_TOOL_SHORTLIST_CHAR_BUDGET: usize = 1200;
 const DEFAULT_SUB_AGENT_TARGET_SHORTLIST_ITEMS: usize = 6;
 const DEFAULT_SUB_AGENT_TARGET_SHORTLIST_CHAR_BUDGET: usize = 600;
+const SKILL_LISTING_CHAR_BUDGET: usize = 2000;
 const HARD_MAX_TOOL_ROUNDS: usize = 32;
 const HARD_MAX_TOOLS_PER_ROUND: usize = 64;
 const HARD_MAX_TOTAL_TOOL_CALLS_PER_TURN: usize = 256;
@@ -243,18 +244,22 @@ impl Agent {
             return base_prompt;
         }
 
-        let tools = self.config.tools.join(", ");
-        let configured = self
-            .config
-            .tools
-            .iter()
-            .map(|tool| tool.as_str())
-            .collect::<HashSet<_>>();
+        // Discovered MCP tools admitted by an `mcp` or `mcp.<server>` entry.
+        let mut listed_tools = self.config.tools.clone();
+        for name in self.tool_manager.list_tools().await {
+            if split_tool_name(&name).is_some()
+                && !listed_tools.contains(&name)
+                && self.allows_tool(&name)
+            {
+                listed_tools.push(name);
+            }
+        }
+        let tools = listed_tools.join(", ");
         let shortlist_items = self
             .config
             .tool_shortlist_max_items
             .unwrap_or(DEFAULT_TOOL_SHORTLIST_ITEMS);
-        let shortlist_cap = usize::min(self.config.tools.len(), shortlist_items);
+        let shortlist_cap = usize::min(listed_tools.len(), shortlist_items);
         let shortlist_char_budget = self
             .config
             .tool_shortlist_char_budget
@@ -268,14 +273,14 @@ impl Agent {
                 .get_tool_descriptions(None, Some(shortlist_cap))
                 .await
                 .into_iter()
-                .filter(|(name, _)| configured.contains(name.as_str()))
+                .filter(|(name, _)| self.allows_tool(name))
                 .collect::<Vec<_>>(),
             ToolShortlistMode::TaskFocused => self
                 .tool_manager
                 .get_tool_descriptions(focus_hint, Some(shortlist_cap))
                 .await
                 .into_iter()
-                .filter(|(name, _)| configured.contains(name.as_str()))
+                .filter(|(name, _)| self.allows_tool(name))
                 .collect::<Vec<_>>(),
         };
 
@@ -285,7 +290,7 @@ impl Agent {
                 .get_tool_descriptions(None, Some(shortlist_cap))
                 .await
                 .into_iter()
-                .filter(|(name, _)| configured.contains(name.as_str()))
+                .filter(|(name, _)| self.allows_tool(name))
                 .collect();
         }
 
@@ -351,11 +356,52 @@ impl Agent {
             String::new()
         };
 
+        let skill_hint = self.skill_listing();
+
         format!(
-            "{base_prompt}\n\nWhen you need a tool, emit a single-line JSON object only with shape: {{\"tool\":\"<tool_name>\",\"args\":{{...}}}}. Configured tools: {tools}. Prioritize these tools for this task: {prioritized}.{sub_agent_target_hint}"
+            "{base_prompt}\n\nWhen you need a tool, emit a single-line JSON object only with shape: {{\"tool\":\"<tool_name>\",\"args\":{{...}}}}. Configured tools: {tools}. Prioritize these tools for this task: {prioritized}.{sub_agent_target_hint}{skill_hint}"
         )
     }
 
+    /// Names and short descriptions of the skills this agent may invoke.
+    /// Instructions and resources are only loaded through the `skill` tool.
+    fn skill_listing(&self) -> String {
+        if !self.allows_tool("skill") {
+            return String::new();
+        }
+        let registry = self.tool_manager.skills();
+        let mut chunks = Vec::new();
+        let mut used_chars = 0usize;
+        for name in registry.list() {
+            if !self.config.skills.is_empty() && !self.config.skills.contains(&name) {
+                continue;
+            }
+            let Some(skill) = registry.get(&name) else {
+                continue;
+            };
+            let description = skill
+                .spec()
+                .description
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let chunk = format!("{name}: {description}");
+            if used_chars + chunk.len() > SKILL_LISTING_CHAR_BUDGET {
+                break;
+            }
+            used_chars += chunk.len();
+            chunks.push(chunk);
+        }
+        if chunks.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " Available skills (invoke with the skill tool to load full instructions; pass 'resource' or 'script' for bundled files): {}.",
+                chunks.join("; ")
+            )
+        }
+    }
+
     fn extract_tool_calls(&self, response: &str) -> Vec<ParsedToolCall> {
         let trimmed = response.trim();
         if let Ok(call) = serde_json::from_str::<ParsedToolCall>(trimmed) {
@@ -401,6 +447,10 @@ impl Agent {
             .map(|message| message.content.clone())
     }
 
+    fn allows_tool(&self, tool: &str) -> bool {
+        tool_list_allows(self.config.tools.iter().map(String::as_str), tool)
+    }
+
     fn tools_used_from_context(context: &[ChatMessage]) -> Vec<String> {
         let mut tools = Vec::new();
         for message in context {
@@ -581,7 +631,7 @@ impl Agent {
         event_tx: mpsc::Sender<Event>,
         cancellation_token: Option<CancellationToken>,
     ) -> Result<ToolExecutionResult> {
-        if !self.config.tools.iter().any(|name| name == &call.tool) {
+        if !self.allows_tool(&call.tool) {
             let message = format!(
                 "tool '{}' is not allowed for agent '{}'",
                 call.tool, self.config.name
@@ -949,7 +999,7 @@ impl Agent {
                 }
                 total_tool_calls_executed += 1;
 
-                if !self.config.tools.iter().any(|name| name == &call.tool) {
+                if !self.allows_tool(&call.tool) {
                     let disallowed = self
                         .handle_disallowed_tool_call(
                             session_id,
@@ -1436,6 +1486,41 @@ impl Agent {
             agent: agent_name.clone(),
         });
 
+        let continued = self
+            .continue_pending_turn(
+                session_id,
+                &session_id_str,
+                &agent_name,
+                event_tx,
+                cancellation_token,
+            )
+            .await;
+        self.end_turn_unless_pending(session_id, &session_id_str)
+            .await;
+        continued
+    }
+
+    /// Revokes the turn's skill-granted tools once no tool call is waiting for
+    /// approval, so the grants never outlive the turn that earned them.
+    async fn end_turn_unless_pending(&self, session_id: uuid::Uuid, session_id_str: &str) {
+        let pending = self
+            .session_manager
+            .has_pending_tool(session_id)
+            .await
+            .unwrap_or(false);
+        if !pending {
+            self.tool_manager.end_turn(session_id_str).await;
+        }
+    }
+
+    async fn continue_pending_turn(
+        &self,
+        session_id: uuid::Uuid,
+        session_id_str: &str,
+        agent_name: &str,
+        event_tx: mpsc::Sender<Event>,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Result<()> {
         // Check for pending tool state and resume if found
         let has_pending = self.session_manager.has_pending_tool(session_id).await?;
 
@@ -1444,8 +1529,8 @@ impl Agent {
             let resumed = self
                 .resume_from_pending_tool(
                     session_id,
-                    &session_id_str,
-                    &agent_name,
+                    session_id_str,
+                    agent_name,
                     event_tx,
                     cancellation_token,
                 )
@@ -1454,7 +1539,7 @@ impl Agent {
                 if self.learning.enabled() {
                     let _ = self
                         .learning
-                        .record_error_message(&agent_name, &err.to_string())
+                        .record_error_message(agent_name, &err.to_string())
                         .await;
                 }
                 return Err(err);
@@ -1462,11 +1547,11 @@ impl Agent {
         } else {
             // No pending state - reload context and continue
             let mut context_window = self
-                .load_context_window_from_session(session_id, &session_id_str, None, &event_tx)
+                .load_context_window_from_session(session_id, session_id_str, None, &event_tx)
                 .await?;
             if let Some(query) = Self::latest_user_task(&context_window) {
                 self.maybe_inject_retrieval_context(
-                    &session_id_str,
+                    session_id_str,
                     &query,
                     &mut context_window,
                     &event_tx,
@@ -1476,8 +1561,8 @@ impl Agent {
             let continued = self
                 .run_assistant_tool_loop(
                     session_id,
-                    &session_id_str,
-                    &agent_name,
+                    session_id_str,
+                    agent_name,
                     context_window,
                     event_tx,
                     cancellation_token,
@@ -1487,7 +1572,7 @@ impl Agent {
                 if self.learning.enabled() {
                     let _ = self
                         .learning
-                        .record_error_message(&agent_name, &err.to_string())
+                        .record_error_message(agent_name, &err.to_string())
                         .await;
                 }
                 return Err(err);
@@ -1508,6 +1593,9 @@ impl Agent {
         let agent_name = self.config.name.clone();
         let session_id_str = session_id.to_string();
 
+        // A new user turn starts without the previous turn's skill grants.
+        self.tool_manager.end_turn(&session_id_str).await;
+
         // 1. Emit AgentThinking event
         let _ = event_tx.try_send(Event::AgentThinking {
             session_id: session_id_str.clone(),
@@ -1560,6 +1648,8 @@ impl Agent {
                 cancellation_token,
             )
             .await;
+        self.end_turn_unless_pending(session_id, &session_id_str)
+            .await;
 
         if self.learning.enabled() {
             if let Err(err) = &turn_result {
@@ -1641,6 +1731,10 @@ impl Agent {
         &self.config
     }
 
+    pub fn provider(&self) -> &Arc<dyn ModelProvider> {
+        &self.provider
+    }
+
     pub async fn record_summary_feedback(
         &self,
         session_id: &str,
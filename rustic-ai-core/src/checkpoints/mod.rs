@@ -0,0 +1,701 @@
+//! Workspace checkpoints backed by a shadow git object store.
+//!
+//! Each workspace gets a bare repository under the global data directory whose
+//! work tree is pointed at the workspace for the duration of an operation. The
+//! user's own `.git` directory, index and history are never read or written;
+//! the workspace's ignore files are honoured so ignored output stays out of
+//! snapshots and is never touched by a restore.
+
+use chrono::{DateTime, TimeZone, Utc};
+use git2::{
+    Delta, DiffFormat, FileMode, IndexAddOption, ObjectType, Oid, Repository, Signature, Tree,
+    TreeWalkMode, TreeWalkResult,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::config::schema::CheckpointConfig;
+use crate::error::{Error, Result};
+
+const REF_PREFIX: &str = "refs/checkpoints";
+const SHORT_ID_LEN: usize = 10;
+const MAX_DIFF_BYTES: usize = 256 * 1024;
+/// Commit message trailer listing a file left out for exceeding `max_file_bytes`.
+const OVERSIZED_TRAILER: &str = "Oversized-file: ";
+/// Unreachable objects younger than this survive a prune, so snapshots being
+/// written by a concurrent operation are not lost.
+const PRUNE_GRACE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub session_id: String,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointFileChange {
+    pub path: String,
+    /// `added`, `deleted` or `modified`, relative to the checkpoint.
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointDiff {
+    pub checkpoint: Checkpoint,
+    pub files: Vec<CheckpointFileChange>,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub patch: String,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub checkpoint: Checkpoint,
+    /// Snapshot taken just before restoring, so the restore itself can be undone.
+    pub previous_state: Option<Checkpoint>,
+    pub written: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    config: CheckpointConfig,
+    workspace: PathBuf,
+    shadow_dir: PathBuf,
+}
+
+impl CheckpointStore {
+    /// `data_dir` is the global data directory; the shadow repository lives in
+    /// `<data_dir>/checkpoints/<workspace-hash>`.
+    pub fn new(config: CheckpointConfig, workspace: &Path, data_dir: &Path) -> Self {
+        let workspace = workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf());
+        let digest = format!(
+            "{:x}",
+            Sha256::digest(workspace.to_string_lossy().as_bytes())
+        );
+        let name = workspace
+            .file_name()
+            .map(|name| {
+                name.to_string_lossy()
+                    .chars()
+                    .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+                    .collect::<String>()
+            })
+            .unwrap_or_else(|| "root".to_owned());
+        let shadow_dir = data_dir
+            .join("checkpoints")
+            .join(format!("{name}-{}", &digest[..16]));
+        Self {
+            config,
+            workspace,
+            shadow_dir,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    fn open(&self) -> Result<Repository> {
+        let repo = if self.shadow_dir.join("HEAD").is_file() {
+            Repository::open_bare(&self.shadow_dir)
+        } else {
+            std::fs::create_dir_all(&self.shadow_dir)?;
+            Repository::init_bare(&self.shadow_dir)
+        }
+        .map_err(|err| storage_error("open shadow repository", err))?;
+        repo.set_workdir(&self.workspace, false)
+            .map_err(|err| storage_error("attach workspace", err))?;
+        let mut rules = String::from(".git/\n");
+        for pattern in &self.config.exclude {
+            rules.push_str(pattern.trim());
+            rules.push('\n');
+        }
+        repo.add_ignore_rule(&rules)
+            .map_err(|err| storage_error("apply exclude rules", err))?;
+        Ok(repo)
+    }
+
+    /// Stages the workspace into the shadow index and writes its tree. Also
+    /// returns the paths left out for exceeding `max_file_bytes`.
+    fn snapshot_tree(&self, repo: &Repository) -> Result<(Oid, Vec<String>)> {
+        let mut index = repo
+            .index()
+            .map_err(|err| storage_error("open shadow index", err))?;
+        let max_files = self.config.max_files;
+        let mut seen = 0usize;
+        let mut too_many = false;
+        let workspace = &self.workspace;
+        let mut on_add = |path: &Path, _: &[u8]| -> i32 {
+            // Embedded repositories (submodules, vendored checkouts) are skipped.
+            if workspace.join(path).join(".git").exists() {
+                return 1;
+            }
+            seen += 1;
+            if seen > max_files {
+                too_many = true;
+                return -1;
+            }
+            0
+        };
+        let added = index.add_all(["*"], IndexAddOption::DEFAULT, Some(&mut on_add));
+        if too_many {
+            return Err(Error::Storage(format!(
+                "workspace has more than {max_files} files; raise checkpoints.max_files to snapshot it"
+            )));
+        }
+        added.map_err(|err| storage_error("stage workspace", err))?;
+        index
+            .update_all(["*"], None)
+            .map_err(|err| storage_error("stage deletions", err))?;
+
+        let oversized = index
+            .iter()
+            .filter(|entry| u64::from(entry.file_size) > self.config.max_file_bytes)
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect::<Vec<_>>();
+        for path in &oversized {
+            index
+                .remove_path(Path::new(path))
+                .map_err(|err| storage_error("drop oversized file", err))?;
+        }
+
+        index
+            .write()
+            .map_err(|err| storage_error("write shadow index", err))?;
+        let tree_id = index
+            .write_tree()
+            .map_err(|err| storage_error("write snapshot tree", err))?;
+        Ok((tree_id, oversized))
+    }
+
+    fn session_refs(&self, repo: &Repository, session_id: Option<&str>) -> Result<Vec<Checkpoint>> {
+        let glob = match session_id {
+            Some(session_id) => format!("{REF_PREFIX}/{session_id}/*"),
+            None => format!("{REF_PREFIX}/*"),
+        };
+        let references = repo
+            .references_glob(&glob)
+            .map_err(|err| storage_error("list checkpoints", err))?;
+        let mut checkpoints = Vec::new();
+        for reference in references.flatten() {
+            let Some(name) = reference.name().map(ToOwned::to_owned) else {
+                continue;
+            };
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            // refs/checkpoints/<session>/<micros>-<short id>
+            let mut segments = name
+                .strip_prefix(&format!("{REF_PREFIX}/"))
+                .unwrap_or_default()
+                .split('/');
+            let session = segments.next().unwrap_or_default().to_owned();
+            let created_at = segments
+                .next()
+                .and_then(|leaf| leaf.split('-').next())
+                .and_then(|micros| micros.parse::<i64>().ok())
+                .and_then(|micros| Utc.timestamp_micros(micros).single())
+                .unwrap_or_else(|| {
+                    Utc.timestamp_opt(commit.time().seconds(), 0)
+                        .single()
+                        .unwrap_or_else(Utc::now)
+                });
+            checkpoints.push(Checkpoint {
+                id: commit.id().to_string()[..SHORT_ID_LEN].to_owned(),
+                session_id: session,
+                label: commit.summary().unwrap_or_default().to_owned(),
+                created_at,
+            });
+        }
+        checkpoints.sort_by_key(|checkpoint| std::cmp::Reverse(checkpoint.created_at));
+        Ok(checkpoints)
+    }
+
+    /// Lists a session's checkpoints, newest first.
+    pub fn list(&self, session_id: &str) -> Result<Vec<Checkpoint>> {
+        validate_session_id(session_id)?;
+        if !self.shadow_dir.join("HEAD").is_file() {
+            return Ok(Vec::new());
+        }
+        let repo = self.open()?;
+        self.session_refs(&repo, Some(session_id))
+    }
+
+    /// Snapshots the workspace. Returns the latest existing checkpoint instead
+    /// when nothing changed since it was taken.
+    pub fn create(&self, session_id: &str, label: &str) -> Result<Checkpoint> {
+        validate_session_id(session_id)?;
+        let repo = self.open()?;
+        let (checkpoint, dropped) = self.create_in(&repo, session_id, label)?;
+        if dropped {
+            self.prune_logged(&repo);
+        }
+        Ok(checkpoint)
+    }
+
+    /// Records a checkpoint, also reporting whether older ones were dropped
+    /// so the caller can prune once it no longer needs their objects.
+    fn create_in(
+        &self,
+        repo: &Repository,
+        session_id: &str,
+        label: &str,
+    ) -> Result<(Checkpoint, bool)> {
+        let (tree_id, oversized) = self.snapshot_tree(repo)?;
+        let existing = self.session_refs(repo, Some(session_id))?;
+        if let Some(latest) = existing.first() {
+            if self.resolve_commit(repo, &latest.id)?.tree_id() == tree_id {
+                return Ok((latest.clone(), false));
+            }
+        }
+
+        let tree = repo
+            .find_tree(tree_id)
+            .map_err(|err| storage_error("load snapshot tree", err))?;
+        let signature = Signature::now("rustic-ai", "checkpoints@rustic-ai.local")
+            .map_err(|err| storage_error("build signature", err))?;
+        let label = label.lines().next().unwrap_or_default().trim();
+        let label = if label.chars().count() > 72 {
+            format!("{}...", label.chars().take(69).collect::<String>())
+        } else if label.is_empty() {
+            "checkpoint".to_owned()
+        } else {
+            label.to_owned()
+        };
+        let mut message = label.clone();
+        if !oversized.is_empty() {
+            message.push('\n');
+            for path in &oversized {
+                message.push_str(&format!(
+                    "\n{OVERSIZED_TRAILER}{}",
+                    serde_json::to_string(path)?
+                ));
+            }
+        }
+        let commit_id = repo
+            .commit(None, &signature, &signature, &message, &tree, &[])
+            .map_err(|err| storage_error("write checkpoint", err))?;
+        let created_at = Utc::now();
+        let ref_name = format!(
+            "{REF_PREFIX}/{session_id}/{:020}-{}",
+            created_at.timestamp_micros(),
+            &commit_id.to_string()[..SHORT_ID_LEN]
+        );
+        repo.reference(&ref_name, commit_id, false, "checkpoint")
+            .map_err(|err| storage_error("record checkpoint", err))?;
+
+        let mut dropped = false;
+        for stale in existing
+            .iter()
+            .skip(self.config.max_per_session.saturating_sub(1))
+        {
+            let glob = format!("{REF_PREFIX}/{session_id}/*-{}", stale.id);
+            if let Ok(references) = repo.references_glob(&glob) {
+                for mut reference in references.flatten() {
+                    dropped |= reference.delete().is_ok();
+                }
+            }
+        }
+
+        let checkpoint = Checkpoint {
+            id: commit_id.to_string()[..SHORT_ID_LEN].to_owned(),
+            session_id: session_id.to_owned(),
+            label,
+            created_at,
+        };
+        Ok((checkpoint, dropped))
+    }
+
+    fn prune_logged(&self, repo: &Repository) {
+        if let Err(err) = self.prune(repo) {
+            tracing::warn!(%err, "failed to prune checkpoint objects");
+        }
+    }
+
+    /// Deletes loose objects that no checkpoint or the shadow index reaches,
+    /// so dropped checkpoints release their file contents.
+    fn prune(&self, repo: &Repository) -> Result<usize> {
+        let mut reachable = std::collections::HashSet::new();
+        let index = repo
+            .index()
+            .map_err(|err| storage_error("open shadow index", err))?;
+        reachable.extend(index.iter().map(|entry| entry.id));
+        let references = repo
+            .references()
+            .map_err(|err| storage_error("list checkpoints", err))?;
+        for reference in references.flatten() {
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            reachable.insert(commit.id());
+            let tree = commit
+                .tree()
+                .map_err(|err| storage_error("load checkpoint tree", err))?;
+            if !reachable.insert(tree.id()) {
+                continue;
+            }
+            tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+                if reachable.insert(entry.id()) {
+                    TreeWalkResult::Ok
+                } else {
+                    // Subtree already seen through another checkpoint.
+                    TreeWalkResult::Skip
+                }
+            })
+            .map_err(|err| storage_error("walk checkpoint tree", err))?;
+        }
+
+        let mut pruned = 0usize;
+        for fanout in std::fs::read_dir(self.shadow_dir.join("objects"))?.flatten() {
+            let prefix = fanout.file_name().to_string_lossy().into_owned();
+            if prefix.len() != 2 || !prefix.chars().all(|ch| ch.is_ascii_hexdigit()) {
+                continue;
+            }
+            for object in std::fs::read_dir(fanout.path())?.flatten() {
+                let name = object.file_name().to_string_lossy().into_owned();
+                let Ok(id) = Oid::from_str(&format!("{prefix}{name}")) else {
+                    continue;
+                };
+                let old_enough = object
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age >= PRUNE_GRACE);
+                if !reachable.contains(&id)
+                    && old_enough
+                    && std::fs::remove_file(object.path()).is_ok()
+                {
+                    pruned += 1;
+                }
+            }
+        }
+        Ok(pruned)
+    }
+
+    fn resolve_commit<'repo>(
+        &self,
+        repo: &'repo Repository,
+        id: &str,
+    ) -> Result<git2::Commit<'repo>> {
+        let id = id.trim();
+        if id.len() < 4 || !id.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(Error::Validation(format!(
+                "invalid checkpoint id '{id}' (expected at least 4 hex characters)"
+            )));
+        }
+        let matches = self
+            .session_refs(repo, None)?
+            .into_iter()
+            .filter(|checkpoint| {
+                checkpoint.id.starts_with(id) || id.starts_with(checkpoint.id.as_str())
+            })
+            .map(|checkpoint| checkpoint.id)
+            .collect::<std::collections::BTreeSet<_>>();
+        let short = match matches.len() {
+            0 => return Err(Error::NotFound(format!("checkpoint '{id}'"))),
+            1 => matches.into_iter().next().unwrap_or_default(),
+            _ => {
+                return Err(Error::Validation(format!(
+                    "checkpoint id '{id}' is ambiguous"
+                )))
+            }
+        };
+        repo.revparse_single(&short)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|err| storage_error("load checkpoint", err))
+    }
+
+    fn describe(&self, repo: &Repository, id: &str) -> Result<Checkpoint> {
+        let commit = self.resolve_commit(repo, id)?;
+        let short = &commit.id().to_string()[..SHORT_ID_LEN];
+        self.session_refs(repo, None)?
+            .into_iter()
+            .find(|checkpoint| checkpoint.id == short)
+            .ok_or_else(|| Error::NotFound(format!("checkpoint '{id}'")))
+    }
+
+    /// Diffs a checkpoint against the current workspace.
+    pub fn diff(&self, id: &str) -> Result<CheckpointDiff> {
+        let repo = self.open()?;
+        let checkpoint = self.describe(&repo, id)?;
+        let base = self
+            .resolve_commit(&repo, id)?
+            .tree()
+            .map_err(|err| storage_error("load checkpoint tree", err))?;
+        let (current_id, _) = self.snapshot_tree(&repo)?;
+        let current = repo
+            .find_tree(current_id)
+            .map_err(|err| storage_error("load workspace tree", err))?;
+        let diff = repo
+            .diff_tree_to_tree(Some(&base), Some(&current), None)
+            .map_err(|err| storage_error("diff checkpoint", err))?;
+
+        let files = diff
+            .deltas()
+            .filter_map(|delta| {
+                let status = match delta.status() {
+                    Delta::Added => "added",
+                    Delta::Deleted => "deleted",
+                    Delta::Modified | Delta::Typechange => "modified",
+                    _ => return None,
+                };
+                let path = delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())?;
+                Some(CheckpointFileChange {
+                    path: path.to_string_lossy().into_owned(),
+                    status: status.to_owned(),
+                })
+            })
+            .collect();
+        let stats = diff
+            .stats()
+            .map_err(|err| storage_error("summarize diff", err))?;
+
+        let mut patch = String::new();
+        let mut truncated = false;
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if patch.len() >= MAX_DIFF_BYTES {
+                truncated = true;
+                return false;
+            }
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })
+        .or_else(|err| {
+            if truncated {
+                Ok(())
+            } else {
+                Err(storage_error("render diff", err))
+            }
+        })?;
+
+        Ok(CheckpointDiff {
+            checkpoint,
+            files,
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+            patch,
+            truncated,
+        })
+    }
+
+    /// Returns the workspace to a checkpoint. Files the checkpoint does not
+    /// cover (ignored, excluded or oversized) are left alone. The current state
+    /// is checkpointed first so the restore can itself be reverted.
+    pub fn restore(&self, session_id: &str, id: &str) -> Result<RestoreReport> {
+        validate_session_id(session_id)?;
+        let repo = self.open()?;
+        let checkpoint = self.describe(&repo, id)?;
+        let target_commit = self.resolve_commit(&repo, id)?;
+        let skipped = oversized_paths(&target_commit);
+        let target = target_commit
+            .tree()
+            .map_err(|err| storage_error("load checkpoint tree", err))?;
+        let (previous_state, dropped) = match self.create_in(
+            &repo,
+            session_id,
+            &format!("before restore to {}", checkpoint.id),
+        ) {
+            Ok((previous_state, dropped)) => (Some(previous_state), dropped),
+            Err(_) => (None, false),
+        };
+        let (current_id, _) = self.snapshot_tree(&repo)?;
+        let current = repo
+            .find_tree(current_id)
+            .map_err(|err| storage_error("load workspace tree", err))?;
+        let diff = repo
+            .diff_tree_to_tree(Some(&current), Some(&target), None)
+            .map_err(|err| storage_error("diff checkpoint", err))?;
+
+        let mut written = 0usize;
+        let mut removed = 0usize;
+        for delta in diff.deltas() {
+            match delta.status() {
+                Delta::Deleted => {
+                    let Some(path) = delta.old_file().path() else {
+                        continue;
+                    };
+                    // Too large to capture then, so the checkpoint says nothing about it.
+                    if skipped.contains(path.to_string_lossy().as_ref()) {
+                        continue;
+                    }
+                    self.remove_file(path)?;
+                    removed += 1;
+                }
+                Delta::Added | Delta::Modified | Delta::Typechange => {
+                    let file = delta.new_file();
+                    let Some(path) = file.path() else {
+                        continue;
+                    };
+                    self.write_blob(&repo, &target, path, file.mode())?;
+                    written += 1;
+                }
+                _ => {}
+            }
+        }
+
+        // The checkpoint being restored may itself have just been dropped.
+        if dropped {
+            self.prune_logged(&repo);
+        }
+
+        Ok(RestoreReport {
+            checkpoint,
+            previous_state,
+            written,
+            removed,
+        })
+    }
+
+    fn remove_file(&self, relative: &Path) -> Result<()> {
+        let path = self.workspace.join(relative);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(Error::Storage(format!(
+                    "failed to remove '{}': {err}",
+                    path.display()
+                )))
+            }
+        }
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if dir == self.workspace || std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+        Ok(())
+    }
+
+    fn write_blob(
+        &self,
+        repo: &Repository,
+        tree: &Tree<'_>,
+        relative: &Path,
+        mode: FileMode,
+    ) -> Result<()> {
+        let entry = tree
+            .get_path(relative)
+            .map_err(|err| storage_error("locate checkpoint file", err))?;
+        if entry.kind() != Some(ObjectType::Blob) {
+            return Ok(());
+        }
+        let blob = repo
+            .find_blob(entry.id())
+            .map_err(|err| storage_error("read checkpoint file", err))?;
+        let path = self.workspace.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if std::fs::symlink_metadata(&path).is_ok_and(|metadata| !metadata.is_dir()) {
+            std::fs::remove_file(&path)?;
+        }
+
+        #[cfg(unix)]
+        if mode == FileMode::Link {
+            let target = PathBuf::from(String::from_utf8_lossy(blob.content()).into_owned());
+            std::os::unix::fs::symlink(target, &path)?;
+            return Ok(());
+        }
+        std::fs::write(&path, blob.content()).map_err(|err| {
+            Error::Storage(format!("failed to restore '{}': {err}", path.display()))
+        })?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let bits = if mode == FileMode::BlobExecutable {
+                0o755
+            } else {
+                0o644
+            };
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(bits))?;
+        }
+        Ok(())
+    }
+}
+
+/// Paths a checkpoint left out for exceeding `max_file_bytes`.
+fn oversized_paths(commit: &git2::Commit<'_>) -> std::collections::HashSet<String> {
+    commit
+        .message()
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.strip_prefix(OVERSIZED_TRAILER))
+        .filter_map(|path| serde_json::from_str::<String>(path).ok())
+        .collect()
+}
+
+fn validate_session_id(session_id: &str) -> Result<()> {
+    if session_id.is_empty()
+        || !session_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(Error::Validation(format!(
+            "invalid checkpoint session id '{session_id}'"
+        )));
+    }
+    Ok(())
+}
+
+fn storage_error(action: &str, err: git2::Error) -> Error {
+    Error::Storage(format!(
+        "checkpoint store failed to {action}: {}",
+        err.message()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_keeps_files_that_were_oversized_at_checkpoint_time() -> Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("rustic-ai-checkpoints-{}", uuid::Uuid::new_v4()));
+        let workspace = root.join("workspace");
+        std::fs::create_dir_all(&workspace)?;
+        std::fs::write(workspace.join("large.bin"), vec![b'x'; 64])?;
+        std::fs::write(workspace.join("notes.txt"), "before")?;
+        let store = CheckpointStore::new(
+            CheckpointConfig {
+                max_file_bytes: 16,
+                ..CheckpointConfig::default()
+            },
+            &workspace,
+            &root.join("data"),
+        );
+
+        let checkpoint = store.create("session", "start")?;
+        std::fs::write(workspace.join("large.bin"), "small now")?;
+        std::fs::write(workspace.join("notes.txt"), "after")?;
+        let report = store.restore("session", &checkpoint.id);
+
+        let large = std::fs::read_to_string(workspace.join("large.bin"));
+        let notes = std::fs::read_to_string(workspace.join("notes.txt"));
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(report?.removed, 0);
+        assert_eq!(large?, "small now");
+        assert_eq!(notes?, "before");
+        Ok(())
+    }
+}
@@ -112,6 +112,19 @@ pub fn merge(base: Config, override_config: Config) -> Config {
         } else {
             override_config.skills
         },
+        packages: if override_config.packages.indexes.is_empty() {
+            base.packages
+        } else {
+            override_config.packages
+        },
+        sandbox: override_config.sandbox,
+        checkpoints: override_config.checkpoints,
+        lsp: if override_config.lsp.servers.is_empty() {
+            base.lsp
+        } else {
+            override_config.lsp
+        },
+        watcher: override_config.watcher,
         workflows: if override_config.workflows.directories.is_empty() {
             base.workflows
         } else {
@@ -27,6 +27,11 @@ pub struct Config {
     pub mcp: McpConfig,
     pub plugins: PluginConfig,
     pub skills: SkillsConfig,
+    pub packages: PackagesConfig,
+    pub sandbox: SandboxConfig,
+    pub checkpoints: CheckpointConfig,
+    pub lsp: LspConfig,
+    pub watcher: WatcherConfig,
     pub workflows: WorkflowsConfig,
     pub project: Option<ProjectConfig>,
     pub rules: RuleConfig,
@@ -49,6 +54,11 @@ impl Default for Config {
             mcp: McpConfig::default(),
             plugins: PluginConfig::default(),
             skills: SkillsConfig::default(),
+            packages: PackagesConfig::default(),
+            sandbox: SandboxConfig::default(),
+            checkpoints: CheckpointConfig::default(),
+            lsp: LspConfig::default(),
+            watcher: WatcherConfig::default(),
             workflows: WorkflowsConfig::default(),
             project: None,
             rules: RuleConfig::default(),
@@ -67,6 +77,8 @@ impl Default for Config {
 #[serde(default)]
 pub struct McpConfig {
     pub servers: Vec<McpServerConfig>,
+    /// What `mcp-serve` exposes when Rustic-AI runs as an MCP server.
+    pub serve: McpServeConfig,
 }
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -344,28 +356,115 @@ impl Default for DynamicRoutingConfig {
     }
 }
 
+/// How the client talks to an MCP server.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum McpTransportKind {
+    /// Newline-delimited JSON over the server process's stdin/stdout.
+    #[default]
+    Stdio,
+    /// `Content-Length` framed JSON over stdio, for older servers.
+    StdioContentLength,
+    /// Streamable HTTP: JSON-RPC POSTs to `url`, answered with JSON or SSE.
+    StreamableHttp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct McpServerConfig {
     pub name: String,
+    pub transport: McpTransportKind,
+    /// Endpoint for `streamable_http`.
+    pub url: Option<String>,
+    /// Extra HTTP headers for `streamable_http`.
+    pub headers: std::collections::BTreeMap<String, String>,
+    /// HTTP headers whose values are read from environment variables
+    /// (header name -> variable name), e.g. for bearer tokens.
+    pub headers_env: std::collections::BTreeMap<String, String>,
     pub command: String,
     pub args: Vec<String>,
     pub env: std::collections::BTreeMap<String, String>,
     pub working_directory: Option<String>,
     pub startup_timeout_seconds: u64,
     pub protocol_version: String,
+    /// Register the server's tools as `mcp.<server>.<tool>`.
+    pub expose_tools: bool,
+    /// Permission mode for exposed tools not listed in `tool_permissions`;
+    /// falls back to the `mcp` tool's mode.
+    pub permission_mode: Option<PermissionMode>,
+    /// Per-tool permission modes keyed by the server's tool name.
+    pub tool_permissions: std::collections::BTreeMap<String, PermissionMode>,
+    /// Let the server request LLM completions (`sampling/createMessage`).
+    /// Each request still needs user approval.
+    pub sampling: bool,
 }
 
 impl Default for McpServerConfig {
     fn default() -> Self {
         Self {
             name: String::new(),
+            transport: McpTransportKind::Stdio,
+            url: None,
+            headers: std::collections::BTreeMap::new(),
+            headers_env: std::collections::BTreeMap::new(),
             command: String::new(),
             args: Vec::new(),
             env: std::collections::BTreeMap::new(),
             working_directory: None,
             startup_timeout_seconds: 20,
-            protocol_version: "2024-11-05".to_owned(),
+            protocol_version: "2025-03-26".to_owned(),
+            expose_tools: true,
+            permission_mode: None,
+            tool_permissions: std::collections::BTreeMap::new(),
+            sampling: true,
+        }
+    }
+}
+
+/// How `mcp-serve` answers calls whose permission mode is `ask`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum McpServeAskMode {
+    /// Refuse the call; there is no user to ask.
+    #[default]
+    Deny,
+    /// Treat the call as approved once, trusting the MCP client to have
+    /// confirmed it with its user.
+    AllowOnce,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpServeConfig {
+    /// Agent whose tool allowlist and permission mode apply to inbound
+    /// calls; the default agent when unset.
+    pub agent: Option<String>,
+    /// `ToolManager` tools exposed under their own names.
+    pub tools: Vec<String>,
+    /// Expose each configured agent as an `agent_<name>` tool.
+    pub expose_agents: bool,
+    /// Expose hybrid code retrieval as `retrieve_code_context`.
+    pub expose_retrieval: bool,
+    /// Expose symbol impact analysis as `analyze_impact`.
+    pub expose_impact_analysis: bool,
+    /// Expose each non-template workflow as a `workflow_<name>` tool.
+    pub expose_workflows: bool,
+    pub ask_mode: McpServeAskMode,
+    /// Address of the Streamable HTTP endpoint; must be a loopback address.
+    pub http_bind: String,
+}
+
+impl Default for McpServeConfig {
+    fn default() -> Self {
+        Self {
+            agent: None,
+            tools: Vec::new(),
+            expose_agents: true,
+            expose_retrieval: true,
+            expose_impact_analysis: true,
+            expose_workflows: true,
+            ask_mode: McpServeAskMode::Deny,
+            http_bind: "127.0.0.1:8767".to_owned(),
         }
     }
 }
@@ -399,6 +498,18 @@ pub struct SkillsConfig {
     pub script_execution_mode: ScriptExecutionMode,
     pub default_timeout_seconds: u64,
     pub sandbox: SkillSandboxConfig,
+    /// Skills directory that skills created by agents are written to; it
+    /// must be one of `directories`.
+    pub authoring_directory: String,
+    /// Where per-skill dependency environments are built.
+    pub environments_dir: String,
+    /// When false, declared dependencies are never installed; a skill whose
+    /// environment is missing fails instead. Useful on offline machines.
+    pub install_dependencies: bool,
+    pub install_timeout_seconds: u64,
+    /// Command per script language (`python`, `javascript`, `typescript`,
+    /// `shell`, `bash`, `ruby`, `lua`), replacing the default interpreter.
+    pub interpreters: std::collections::BTreeMap<String, String>,
 }
 
 impl Default for SkillsConfig {
@@ -413,6 +524,33 @@ impl Default for SkillsConfig {
             script_execution_mode: ScriptExecutionMode::Disabled,
             default_timeout_seconds: 60,
             sandbox: SkillSandboxConfig::default(),
+            authoring_directory: ".rustic-ai/skills".to_owned(),
+            environments_dir: "~/.rustic-ai/skill-envs".to_owned(),
+            install_dependencies: true,
+            install_timeout_seconds: 600,
+            interpreters: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+/// Where `rustic-ai skills|plugins install` finds and puts packages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackagesConfig {
+    /// Package index files, or directories containing an `index.json`.
+    pub indexes: Vec<String>,
+    /// Global scope root; packages go to `skills/` and `plugins/` below it.
+    pub global_root: String,
+    /// Project scope root, relative to the working directory.
+    pub project_root: String,
+}
+
+impl Default for PackagesConfig {
+    fn default() -> Self {
+        Self {
+            indexes: Vec::new(),
+            global_root: "~/.rustic-ai".to_owned(),
+            project_root: ".rustic-ai".to_owned(),
         }
     }
 }
@@ -442,6 +580,213 @@ impl Default for SkillSandboxConfig {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMode {
+    /// Child processes run directly on the host.
+    #[default]
+    Disabled,
+    /// Apply every enabled layer the kernel supports and log the ones it does not.
+    BestEffort,
+    /// Refuse to spawn unless every enabled layer can be applied.
+    Required,
+}
+
+/// OS-level isolation for shell commands, script skills, plugin tools and MCP
+/// servers (Linux only: user/mount/network namespaces, Landlock and seccomp).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub mode: SandboxMode,
+    /// When false, sandboxed processes get an empty network namespace and
+    /// IPv4/IPv6 sockets are refused.
+    pub network: bool,
+    pub namespaces: bool,
+    pub landlock: bool,
+    pub seccomp: bool,
+    /// Paths readable by sandboxed processes; the workspace is always readable.
+    pub readable_paths: Vec<String>,
+    /// Paths writable in addition to the workspace. The workspace itself is only
+    /// writable for `read_write` agents.
+    pub writable_paths: Vec<String>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            mode: SandboxMode::Disabled,
+            network: true,
+            namespaces: true,
+            landlock: true,
+            seccomp: true,
+            readable_paths: vec!["/".to_owned()],
+            writable_paths: vec!["/tmp".to_owned(), "/var/tmp".to_owned(), "/dev".to_owned()],
+        }
+    }
+}
+
+/// Workspace checkpoints taken at the start of each user turn, stored in a
+/// shadow git object store under the global data directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointConfig {
+    pub enabled: bool,
+    /// Oldest checkpoints beyond this count are dropped per session.
+    pub max_per_session: usize,
+    /// Larger files are left out of checkpoints and never touched by restore.
+    pub max_file_bytes: u64,
+    /// Checkpoints are skipped when the workspace holds more files than this.
+    pub max_files: usize,
+    /// Gitignore-style patterns excluded in addition to the workspace's own ignore files.
+    pub exclude: Vec<String>,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_per_session: 50,
+            max_file_bytes: 5 * 1024 * 1024,
+            max_files: 20_000,
+            exclude: vec![
+                ".rustic-ai/".to_owned(),
+                "node_modules/".to_owned(),
+                "target/".to_owned(),
+            ],
+        }
+    }
+}
+
+/// Workspace file watcher (inotify on Linux, polling elsewhere). Debounced
+/// change batches are published as events, re-index changed files and fire
+/// `file_changes` workflow triggers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatcherConfig {
+    pub enabled: bool,
+    /// Quiet period that closes a batch of changes.
+    pub debounce_ms: u64,
+    /// Scan interval for the polling backend.
+    pub poll_interval_ms: u64,
+    /// Watching fails to start when the workspace has more directories than this.
+    pub max_watched_dirs: usize,
+    /// Re-index changed files when `features.indexing_enabled` is set.
+    pub reindex: bool,
+    /// Gitignore-style patterns ignored in addition to the workspace `.gitignore`.
+    pub exclude: Vec<String>,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_ms: 250,
+            poll_interval_ms: 1000,
+            max_watched_dirs: 8192,
+            reindex: true,
+            exclude: vec![
+                ".git/".to_owned(),
+                ".rustic-ai/".to_owned(),
+                "node_modules/".to_owned(),
+                "target/".to_owned(),
+            ],
+        }
+    }
+}
+
+/// Language servers the `lsp` tool selects automatically by language or file
+/// extension. Running servers are pooled per server and workspace root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LspConfig {
+    pub servers: Vec<LspServerConfig>,
+    /// Least recently used servers are shut down beyond this count.
+    pub max_servers: usize,
+    /// How long `diagnostics` waits for the server to publish after a change.
+    pub diagnostics_wait_ms: u64,
+}
+
+impl Default for LspConfig {
+    fn default() -> Self {
+        let server = |name: &str,
+                      command: &str,
+                      args: &[&str],
+                      languages: &[&str],
+                      extensions: &[&str],
+                      root_markers: &[&str]| {
+            let owned = |items: &[&str]| items.iter().map(|item| (*item).to_owned()).collect();
+            LspServerConfig {
+                name: name.to_owned(),
+                command: command.to_owned(),
+                args: owned(args),
+                languages: owned(languages),
+                extensions: owned(extensions),
+                root_markers: owned(root_markers),
+                initialization_options: None,
+            }
+        };
+        Self {
+            servers: vec![
+                server(
+                    "rust-analyzer",
+                    "rust-analyzer",
+                    &[],
+                    &["rust"],
+                    &["rs"],
+                    &["Cargo.toml"],
+                ),
+                server(
+                    "pyright",
+                    "pyright-langserver",
+                    &["--stdio"],
+                    &["python"],
+                    &["py", "pyi"],
+                    &["pyproject.toml", "setup.py", "requirements.txt"],
+                ),
+                server(
+                    "typescript-language-server",
+                    "typescript-language-server",
+                    &["--stdio"],
+                    &[
+                        "typescript",
+                        "typescriptreact",
+                        "javascript",
+                        "javascriptreact",
+                    ],
+                    &["ts", "tsx", "js", "jsx", "mjs", "cjs"],
+                    &["tsconfig.json", "jsconfig.json", "package.json"],
+                ),
+                server("gopls", "gopls", &[], &["go"], &["go"], &["go.mod"]),
+                server(
+                    "clangd",
+                    "clangd",
+                    &[],
+                    &["c", "cpp"],
+                    &["c", "h", "cc", "cpp", "cxx", "hpp", "hh"],
+                    &["compile_commands.json", "CMakeLists.txt", ".clangd"],
+                ),
+            ],
+            max_servers: 6,
+            diagnostics_wait_ms: 3000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LspServerConfig {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// LSP language ids served, e.g. `rust`, `typescriptreact`.
+    pub languages: Vec<String>,
+    /// File extensions (without the dot) routed to this server.
+    pub extensions: Vec<String>,
+    /// Files whose nearest containing directory becomes the workspace root.
+    pub root_markers: Vec<String>,
+    pub initialization_options: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WorkflowsConfig {
@@ -1058,6 +1403,29 @@ pub struct ToolConfig {
     /// Shell-only: command patterns blocked in read-only agent mode.
     /// Empty uses defaults from code; set explicitly to tune behavior.
     pub read_only_blocked_patterns: Vec<String>,
+    /// Docker-only: CPU limit forced on `run` (e.g. "1.5"); agents cannot override it.
+    pub container_cpus: Option<String>,
+    /// Docker-only: memory limit forced on `run` (e.g. "512m").
+    pub container_memory: Option<String>,
+    /// Docker-only: network forced on `run` (e.g. "none" or a named network).
+    pub container_network: Option<String>,
+    /// SSH-only: ssh_config file used instead of `~/.ssh/config`. Agents
+    /// cannot pick one, since `Match exec` and `ProxyCommand` run local commands.
+    pub ssh_config_file: Option<String>,
+    /// WASM plugins only: directories the component may read, relative to the
+    /// working directory. Each is mounted under the path as written.
+    pub wasm_readable_paths: Vec<String>,
+    /// WASM plugins only: directories the component may write; mounted
+    /// read-only for read-only agents.
+    pub wasm_writable_paths: Vec<String>,
+    /// WASM plugins only: host environment variables passed to the component.
+    pub wasm_env: Vec<String>,
+    /// WASM plugins only: allow sockets and DNS lookups.
+    pub wasm_network: bool,
+    /// WASM plugins only: fuel (roughly, instructions) per call.
+    pub wasm_fuel: Option<u64>,
+    /// WASM plugins only: linear memory cap per call, in MiB.
+    pub wasm_max_memory_mb: Option<u64>,
     pub taxonomy_membership: Vec<TaxonomyMembershipConfig>,
 }
 
@@ -1092,6 +1460,16 @@ impl Default for ToolConfig {
                 " git commit".to_owned(),
                 " git push".to_owned(),
             ],
+            container_cpus: None,
+            container_memory: None,
+            container_network: None,
+            ssh_config_file: None,
+            wasm_readable_paths: Vec::new(),
+            wasm_writable_paths: Vec::new(),
+            wasm_env: Vec::new(),
+            wasm_network: false,
+            wasm_fuel: None,
+            wasm_max_memory_mb: None,
             taxonomy_membership: Vec::new(),
         }
     }
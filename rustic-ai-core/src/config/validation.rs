@@ -1,7 +1,8 @@
 use std::collections::HashSet;
 
 use crate::config::schema::{
-    AuthMode, Config, EmbeddingBackend, ProviderType, RuntimeMode, StorageBackendKind,
+    AuthMode, Config, EmbeddingBackend, McpTransportKind, PermissionMode, ProviderType,
+    RuntimeMode, StorageBackendKind,
 };
 use crate::error::{Error, Result};
 use crate::providers::auth_capabilities::{supported_auth_mode_names, supports_auth_mode};
@@ -370,6 +371,85 @@ pub fn validate_config(config: &Config) -> Result<()> {
         if !tool_names.insert(name.to_owned()) {
             return Err(Error::Validation(format!("duplicate tool name '{name}'")));
         }
+
+        if name == "create_skill" && tool.permission_mode == PermissionMode::Allow {
+            return Err(Error::Validation(
+                "tool 'create_skill' cannot use permission_mode 'allow'; authored skills must be approved"
+                    .to_owned(),
+            ));
+        }
+
+        if let Some(cpus) = &tool.container_cpus {
+            if !cpus
+                .trim()
+                .parse::<f64>()
+                .is_ok_and(|value| value.is_finite() && value > 0.0)
+            {
+                return Err(Error::Validation(format!(
+                    "tool '{name}' container_cpus must be a positive number"
+                )));
+            }
+        }
+        if let Some(memory) = &tool.container_memory {
+            let digits = memory.trim().trim_end_matches(|c: char| {
+                matches!(c.to_ascii_lowercase(), 'b' | 'k' | 'm' | 'g')
+            });
+            if digits.is_empty()
+                || memory.trim().len() - digits.len() > 1
+                || !digits.chars().all(|c| c.is_ascii_digit())
+            {
+                return Err(Error::Validation(format!(
+                    "tool '{name}' container_memory must look like 512m or 2g"
+                )));
+            }
+        }
+        if tool
+            .container_network
+            .as_ref()
+            .is_some_and(|network| network.trim().is_empty())
+        {
+            return Err(Error::Validation(format!(
+                "tool '{name}' container_network cannot be empty"
+            )));
+        }
+        if tool
+            .ssh_config_file
+            .as_ref()
+            .is_some_and(|path| path.trim().is_empty())
+        {
+            return Err(Error::Validation(format!(
+                "tool '{name}' ssh_config_file cannot be empty"
+            )));
+        }
+        if tool
+            .wasm_readable_paths
+            .iter()
+            .chain(&tool.wasm_writable_paths)
+            .any(|path| path.trim().is_empty())
+        {
+            return Err(Error::Validation(format!(
+                "tool '{name}' wasm paths cannot be empty"
+            )));
+        }
+        if let Some(var) = tool
+            .wasm_env
+            .iter()
+            .find(|var| var.trim().is_empty() || var.contains('='))
+        {
+            return Err(Error::Validation(format!(
+                "tool '{name}' wasm_env entry '{var}' must be a variable name"
+            )));
+        }
+        if tool.wasm_fuel == Some(0) {
+            return Err(Error::Validation(format!(
+                "tool '{name}' wasm_fuel must be greater than 0"
+            )));
+        }
+        if tool.wasm_max_memory_mb == Some(0) {
+            return Err(Error::Validation(format!(
+                "tool '{name}' wasm_max_memory_mb must be greater than 0"
+            )));
+        }
     }
 
     let mut mcp_server_names = HashSet::new();
@@ -380,16 +460,42 @@ pub fn validate_config(config: &Config) -> Result<()> {
                 "mcp server name cannot be empty".to_owned(),
             ));
         }
+        if name.contains('.') {
+            return Err(Error::Validation(format!(
+                "mcp server name '{name}' cannot contain '.' (tools are named mcp.<server>.<tool>)"
+            )));
+        }
+        if server
+            .tool_permissions
+            .keys()
+            .any(|tool| tool.trim().is_empty())
+        {
+            return Err(Error::Validation(format!(
+                "mcp server '{name}' tool_permissions keys must be non-empty"
+            )));
+        }
         if !mcp_server_names.insert(name.to_owned()) {
             return Err(Error::Validation(format!(
                 "duplicate mcp server name '{name}'"
             )));
         }
 
-        if server.command.trim().is_empty() {
-            return Err(Error::Validation(format!(
-                "mcp server '{name}' must define command"
-            )));
+        match server.transport {
+            McpTransportKind::Stdio | McpTransportKind::StdioContentLength => {
+                if server.command.trim().is_empty() {
+                    return Err(Error::Validation(format!(
+                        "mcp server '{name}' must define command"
+                    )));
+                }
+            }
+            McpTransportKind::StreamableHttp => {
+                let url = server.url.as_deref().map(str::trim).unwrap_or_default();
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(Error::Validation(format!(
+                        "mcp server '{name}' uses streamable_http and must define an http(s) url"
+                    )));
+                }
+            }
         }
         if server.startup_timeout_seconds == 0 {
             return Err(Error::Validation(format!(
@@ -403,6 +509,33 @@ pub fn validate_config(config: &Config) -> Result<()> {
         }
     }
 
+    let serve = &config.mcp.serve;
+    if serve.tools.iter().any(|tool| tool.trim().is_empty()) {
+        return Err(Error::Validation(
+            "mcp.serve.tools entries must be non-empty".to_owned(),
+        ));
+    }
+    if let Some(agent) = &serve.agent {
+        if !config
+            .agents
+            .iter()
+            .any(|candidate| &candidate.name == agent)
+        {
+            return Err(Error::Validation(format!(
+                "mcp.serve.agent '{agent}' is not a configured agent"
+            )));
+        }
+    }
+    match serve.http_bind.parse::<std::net::SocketAddr>() {
+        Ok(addr) if addr.ip().is_loopback() => {}
+        _ => {
+            return Err(Error::Validation(format!(
+                "mcp.serve.http_bind '{}' must be a loopback address such as 127.0.0.1:8767",
+                serve.http_bind
+            )));
+        }
+    }
+
     if config.plugins.manifest_file_name.trim().is_empty() {
         return Err(Error::Validation(
             "plugins.manifest_file_name must be non-empty".to_owned(),
@@ -438,12 +571,150 @@ pub fn validate_config(config: &Config) -> Result<()> {
             )));
         }
     }
+    if !config
+        .skills
+        .directories
+        .iter()
+        .any(|dir| dir.trim() == config.skills.authoring_directory.trim())
+    {
+        return Err(Error::Validation(
+            "skills.authoring_directory must be one of skills.directories".to_owned(),
+        ));
+    }
+    if config.skills.environments_dir.trim().is_empty() {
+        return Err(Error::Validation(
+            "skills.environments_dir must be non-empty".to_owned(),
+        ));
+    }
+    if config.skills.install_timeout_seconds == 0 {
+        return Err(Error::Validation(
+            "skills.install_timeout_seconds must be greater than zero".to_owned(),
+        ));
+    }
+    for (language, command) in &config.skills.interpreters {
+        if crate::skills::ScriptLanguage::from_name(language)
+            .is_none_or(|language| language == crate::skills::ScriptLanguage::Rust)
+        {
+            return Err(Error::Validation(format!(
+                "skills.interpreters has unknown script language '{language}'"
+            )));
+        }
+        if command.trim().is_empty() {
+            return Err(Error::Validation(format!(
+                "skills.interpreters.{language} must be non-empty"
+            )));
+        }
+    }
     if config.skills.sandbox.enabled && config.skills.sandbox.sandbox_type.trim().is_empty() {
         return Err(Error::Validation(
             "skills.sandbox.sandbox_type must be non-empty when sandbox is enabled".to_owned(),
         ));
     }
 
+    for (idx, index) in config.packages.indexes.iter().enumerate() {
+        if index.trim().is_empty() {
+            return Err(Error::Validation(format!(
+                "packages.indexes[{idx}] must be non-empty"
+            )));
+        }
+    }
+    if config.packages.global_root.trim().is_empty()
+        || config.packages.project_root.trim().is_empty()
+    {
+        return Err(Error::Validation(
+            "packages.global_root and packages.project_root must be non-empty".to_owned(),
+        ));
+    }
+
+    let sandbox_requested = config.sandbox.mode != crate::config::schema::SandboxMode::Disabled
+        || config.skills.script_execution_mode
+            == crate::config::schema::ScriptExecutionMode::Sandbox;
+    if sandbox_requested
+        && !(config.sandbox.namespaces || config.sandbox.landlock || config.sandbox.seccomp)
+    {
+        return Err(Error::Validation(
+            "sandbox requires at least one of sandbox.namespaces, sandbox.landlock or sandbox.seccomp"
+                .to_owned(),
+        ));
+    }
+    for (field, paths) in [
+        ("readable_paths", &config.sandbox.readable_paths),
+        ("writable_paths", &config.sandbox.writable_paths),
+    ] {
+        for (idx, path) in paths.iter().enumerate() {
+            if path.trim().is_empty() {
+                return Err(Error::Validation(format!(
+                    "sandbox.{field}[{idx}] must be non-empty"
+                )));
+            }
+        }
+    }
+
+    if config.checkpoints.enabled {
+        if config.checkpoints.max_per_session == 0 {
+            return Err(Error::Validation(
+                "checkpoints.max_per_session must be greater than zero".to_owned(),
+            ));
+        }
+        if config.checkpoints.max_files == 0 {
+            return Err(Error::Validation(
+                "checkpoints.max_files must be greater than zero".to_owned(),
+            ));
+        }
+    }
+    if config.lsp.max_servers == 0 {
+        return Err(Error::Validation(
+            "lsp.max_servers must be greater than zero".to_owned(),
+        ));
+    }
+    let mut lsp_server_names = std::collections::HashSet::new();
+    for (idx, server) in config.lsp.servers.iter().enumerate() {
+        if server.name.trim().is_empty() || server.command.trim().is_empty() {
+            return Err(Error::Validation(format!(
+                "lsp.servers[{idx}] requires non-empty name and command"
+            )));
+        }
+        if !lsp_server_names.insert(server.name.as_str()) {
+            return Err(Error::Validation(format!(
+                "lsp.servers[{idx}] duplicates server name '{}'",
+                server.name
+            )));
+        }
+        if server.languages.is_empty() && server.extensions.is_empty() {
+            return Err(Error::Validation(format!(
+                "lsp.servers[{idx}] must list at least one language or extension"
+            )));
+        }
+    }
+
+    for (idx, pattern) in config.checkpoints.exclude.iter().enumerate() {
+        if pattern.trim().is_empty() {
+            return Err(Error::Validation(format!(
+                "checkpoints.exclude[{idx}] must be non-empty"
+            )));
+        }
+    }
+
+    if config.watcher.enabled {
+        if config.watcher.poll_interval_ms == 0 {
+            return Err(Error::Validation(
+                "watcher.poll_interval_ms must be greater than zero".to_owned(),
+            ));
+        }
+        if config.watcher.max_watched_dirs == 0 {
+            return Err(Error::Validation(
+                "watcher.max_watched_dirs must be greater than zero".to_owned(),
+            ));
+        }
+    }
+    for (idx, pattern) in config.watcher.exclude.iter().enumerate() {
+        if pattern.trim().is_empty() {
+            return Err(Error::Validation(format!(
+                "watcher.exclude[{idx}] must be non-empty"
+            )));
+        }
+    }
+
     if config.workflows.max_discovery_depth == 0 || config.workflows.max_discovery_depth > 32 {
         return Err(Error::Validation(
             "workflows.max_discovery_depth must be between 1 and 32".to_owned(),
@@ -311,6 +311,11 @@ impl SessionManager {
             .await
     }
 
+    /// Pending tool execution state of a session, without clearing it
+    pub async fn get_pending_tool(&self, session_id: Uuid) -> Result<Option<PendingToolState>> {
+        self.storage.get_pending_tool(session_id).await
+    }
+
     /// Check if a session has pending tool execution state (without clearing it)
     pub async fn has_pending_tool(&self, session_id: Uuid) -> Result<bool> {
         self.storage.has_pending_tool(session_id).await
@@ -22,6 +22,9 @@ pub enum Error {
     #[error("timeout error: {0}")]
     Timeout(String),
 
+    #[error("cancelled: {0}")]
+    Cancelled(String),
+
     #[error("storage error: {0}")]
     Storage(String),
 
@@ -68,6 +68,8 @@ pub enum Event {
         session_id: String,
         tool: String,
         args: serde_json::Value,
+        /// Tools an approved `skill` call pre-approves for the rest of the turn.
+        grants: Vec<String>,
     },
     PermissionDecision {
         session_id: String,
@@ -170,6 +172,33 @@ pub enum Event {
         implicit: bool,
         acceptance_count: u32,
     },
+    /// Debounced batch from the workspace file watcher; paths are relative to `root`.
+    FilesChanged {
+        root: String,
+        created: Vec<String>,
+        modified: Vec<String>,
+        deleted: Vec<String>,
+    },
+    /// One stderr line from a running MCP server.
+    McpServerLog {
+        server: String,
+        line: String,
+    },
+    /// MCP server lifecycle change: running, crashed, restarting, failed or stopped.
+    McpServerStateChanged {
+        server: String,
+        state: String,
+        detail: Option<String>,
+    },
+    /// An MCP server asked for an LLM completion; answer with
+    /// `/mcp sampling approve|deny <id>`.
+    McpSamplingRequested {
+        id: String,
+        server: String,
+        agent: Option<String>,
+        summary: String,
+        max_tokens: usize,
+    },
     SessionUpdated(String),
     Error(String),
 }
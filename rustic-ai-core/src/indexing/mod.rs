@@ -219,6 +219,23 @@ impl CodeIndexer {
         Ok(())
     }
 
+    /// Drops a deleted file's entries so lookups stop returning stale symbols.
+    pub async fn remove_file(&self, file_path: &Path) -> Result<()> {
+        if !self.indexing_enabled {
+            return Ok(());
+        }
+
+        let workspace_string = self.workspace.to_string_lossy().to_string();
+        let relative_path = relative_path(&self.workspace, file_path);
+        self.storage
+            .delete_code_file_index(&workspace_string, &relative_path)
+            .await?;
+        self.storage
+            .upsert_code_index_metadata(&workspace_string, Utc::now())
+            .await?;
+        Ok(())
+    }
+
     pub async fn search_symbols(&self, query: &str, limit: usize) -> Result<Vec<SymbolIndex>> {
         if !self.indexing_enabled {
             return Ok(Vec::new());
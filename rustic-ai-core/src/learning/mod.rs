@@ -10,7 +10,7 @@ use std::sync::Arc;
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::storage::StorageBackend;
 
@@ -334,6 +334,7 @@ impl LearningManager {
                 last_used: Utc::now(),
                 success_rate: 1.0,
                 created_at: Utc::now(),
+                skill_name: None,
             });
         }
 
@@ -371,6 +372,7 @@ impl LearningManager {
                     last_used: Utc::now(),
                     success_rate: 1.0,
                     created_at: Utc::now(),
+                    skill_name: None,
                 }
             }
         } else {
@@ -385,6 +387,7 @@ impl LearningManager {
                 last_used: Utc::now(),
                 success_rate: 1.0,
                 created_at: Utc::now(),
+                skill_name: None,
             }
         };
 
@@ -398,6 +401,57 @@ impl LearningManager {
         Ok(pattern)
     }
 
+    /// The agent's success pattern a new skill comes from: the one named by
+    /// `pattern` (id or name), or else the one most similar to `description`.
+    pub async fn match_pattern(
+        &self,
+        agent_name: &str,
+        pattern: Option<&str>,
+        description: &str,
+    ) -> Result<Option<SuccessPattern>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let candidates = self
+            .storage
+            .find_success_patterns(agent_name, None, None, 256)
+            .await?;
+        let Some(wanted) = pattern.map(str::trim).filter(|value| !value.is_empty()) else {
+            return Ok(candidates
+                .into_iter()
+                .map(|candidate| {
+                    let score = success_patterns::similarity(&candidate.description, description);
+                    (candidate, score)
+                })
+                .filter(|(_, score)| *score >= 0.45)
+                .max_by(|left, right| left.1.total_cmp(&right.1))
+                .map(|(candidate, _)| candidate));
+        };
+        candidates
+            .into_iter()
+            .find(|candidate| candidate.id.to_string() == wanted || candidate.name == wanted)
+            .map(Some)
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "success pattern '{wanted}' of agent '{agent_name}' not found"
+                ))
+            })
+    }
+
+    /// Records `skill_name` as the skill created from `pattern`.
+    pub async fn link_skill(
+        &self,
+        mut pattern: SuccessPattern,
+        skill_name: &str,
+    ) -> Result<SuccessPattern> {
+        pattern.skill_name = Some(skill_name.to_owned());
+        if self.enabled {
+            self.storage.upsert_success_pattern(&pattern).await?;
+        }
+        Ok(pattern)
+    }
+
     pub async fn find_patterns(
         &self,
         agent_name: &str,
@@ -140,4 +140,7 @@ pub struct SuccessPattern {
     pub last_used: DateTime<Utc>,
     pub success_rate: f64,
     pub created_at: DateTime<Utc>,
+    /// Skill created from this pattern, if any.
+    #[serde(default)]
+    pub skill_name: Option<String>,
 }
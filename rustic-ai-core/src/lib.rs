@@ -1,6 +1,7 @@
 pub mod agents;
 pub mod auth;
 pub mod catalog;
+pub mod checkpoints;
 pub mod commands;
 pub mod config;
 pub mod conversation;
@@ -9,6 +10,8 @@ pub mod events;
 pub mod indexing;
 pub mod learning;
 pub mod logging;
+pub mod mcp;
+pub mod packages;
 pub mod permissions;
 pub mod project;
 pub mod providers;
@@ -16,10 +19,12 @@ pub mod rag;
 pub mod routing;
 pub mod rules;
 pub mod runtime;
+pub mod sandbox;
 pub mod skills;
 pub mod storage;
 pub mod tools;
 pub mod vector;
+pub mod watcher;
 pub mod workflows;
 
 pub use agents::Agent;
@@ -218,6 +223,132 @@ impl RusticAI {
         Ok(indexing::render_dot(&graph))
     }
 
+    pub fn workflow_executor(&self) -> workflows::WorkflowExecutor {
+        workflows::WorkflowExecutor::new(
+            self.runtime.workflows.clone(),
+            self.runtime.skills.clone(),
+            std::sync::Arc::new(self.runtime.agents.clone()),
+            self.session_manager.clone(),
+            workflows::build_workflow_executor_config(
+                &self.config.workflows,
+                self.work_dir.clone(),
+                self.config.features.dynamic_routing_enabled,
+                self.config.dynamic_routing.clone(),
+                self.config.features.todo_tracking_enabled,
+                self.config
+                    .project
+                    .as_ref()
+                    .map(|project| project.name.clone()),
+                config::schema::AgentPermissionMode::ReadWrite,
+            ),
+        )
+    }
+
+    pub fn checkpoint_store(&self) -> checkpoints::CheckpointStore {
+        let paths = storage::paths::StoragePaths::resolve(&self.work_dir, &self.config);
+        checkpoints::CheckpointStore::new(
+            self.config.checkpoints.clone(),
+            &self.work_dir,
+            &paths.global_data_dir,
+        )
+    }
+
+    /// Starts the workspace file watcher. Each batch is published on the runtime
+    /// event bus and, with `watcher.reindex` and indexing enabled, re-indexed in
+    /// the background. Must be called inside a Tokio runtime.
+    pub fn start_file_watcher(&self) -> Result<watcher::FileWatcher> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| Error::Config("file watcher requires a Tokio runtime".to_owned()))?;
+        let bus = self.runtime.event_bus.clone();
+        let reindex = if self.config.watcher.reindex && self.config.features.indexing_enabled {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<watcher::FileChangeSet>();
+            let indexer = self.code_indexer();
+            let work_dir = self.work_dir.clone();
+            handle.spawn(async move {
+                while let Some(batch) = rx.recv().await {
+                    for path in batch.created.iter().chain(batch.modified.iter()) {
+                        if let Err(err) = indexer.rebuild_file(&work_dir.join(path)).await {
+                            tracing::debug!(%err, path, "incremental re-index failed");
+                        }
+                    }
+                    for path in &batch.deleted {
+                        if let Err(err) = indexer.remove_file(&work_dir.join(path)).await {
+                            tracing::debug!(%err, path, "removing deleted file from index failed");
+                        }
+                    }
+                }
+            });
+            Some(tx)
+        } else {
+            None
+        };
+
+        watcher::FileWatcher::start(&self.work_dir, &self.config.watcher, move |batch| {
+            if let Some(tx) = reindex.as_ref() {
+                let _ = tx.send(batch.clone());
+            }
+            bus.publish(batch.into_event());
+            true
+        })
+    }
+
+    pub fn workflow_run_store(&self) -> workflows::WorkflowRunStore {
+        let paths = storage::paths::StoragePaths::resolve(&self.work_dir, &self.config);
+        workflows::WorkflowRunStore::new(paths.project_data_dir.join("workflow_runs"))
+    }
+
+    /// Resolves a loaded workflow by name, falling back to a definition file path.
+    pub fn resolve_workflow(&self, name_or_path: &str) -> Result<workflows::WorkflowDefinition> {
+        if let Some(workflow) = self.runtime.workflows.get(name_or_path) {
+            return Ok(workflow.clone());
+        }
+        let path = self.work_dir.join(name_or_path);
+        if path.is_file() {
+            return workflows::WorkflowLoader::parse_definition_file(&path);
+        }
+        Err(Error::NotFound(format!(
+            "workflow '{name_or_path}' is not loaded and is not a definition file"
+        )))
+    }
+
+    pub async fn validate_workflow(
+        &self,
+        name_or_path: &str,
+    ) -> Result<workflows::WorkflowValidationReport> {
+        let workflow = self.resolve_workflow(name_or_path)?;
+        let context = workflows::WorkflowValidationContext {
+            tools: Some(self.runtime.tools.list_tools().await.into_iter().collect()),
+            agents: Some(self.runtime.agents.list_agents().into_iter().collect()),
+            skills: Some(self.runtime.skills.list().into_iter().collect()),
+            workflows: Some(self.runtime.workflows.as_ref()),
+            expression_options: workflows::expressions::EvaluationOptions {
+                max_length: self.config.workflows.expression_max_length,
+                max_depth: self.config.workflows.expression_max_depth,
+            },
+        };
+        let mut report = workflows::validate_workflow(&workflow, &context);
+
+        // Definitions read straight from disk have not been through load-time checks.
+        if self.runtime.workflows.get(name_or_path).is_none() {
+            let path = self.work_dir.join(name_or_path);
+            if let Err(err) = workflows::WorkflowLoader::check_definition(
+                &workflow,
+                &path,
+                &self.config.workflows,
+            ) {
+                report.issues.insert(
+                    0,
+                    workflows::WorkflowIssue {
+                        severity: workflows::WorkflowIssueSeverity::Error,
+                        step_id: None,
+                        message: err.to_string(),
+                    },
+                );
+            }
+        }
+        Ok(report)
+    }
+
     pub async fn retrieve_code_context(
         &self,
         query: &str,
@@ -0,0 +1,580 @@
+//! One initialized connection to an MCP server.
+//!
+//! Messages travel over stdio (newline-delimited or `Content-Length` framed)
+//! or Streamable HTTP; see [`crate::mcp::transport`]. Responses are routed to
+//! the waiting request by id, server requests such as `sampling/createMessage`
+//! go to an [`McpRequestHandler`] and notifications are broadcast. For stdio
+//! servers another task publishes every stderr line on the event bus.
+
+use crate::config::schema::{McpServerConfig, McpTransportKind};
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::mcp::transport::{read_stdio, HttpTransport, Inbound, StdioFraming, Transport};
+use crate::sandbox::{Sandbox, SandboxPolicy};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Grace period for a server to exit after stdin is closed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+/// Longest stderr line published as one event.
+const MAX_STDERR_LINE_BYTES: usize = 4 * 1024;
+/// Upper bound on pages followed for one paginated listing.
+const MAX_LIST_PAGES: usize = 32;
+
+type PendingMap = StdMutex<HashMap<i64, oneshot::Sender<Result<Value>>>>;
+
+/// A notification sent by a server, e.g. `notifications/tools/list_changed`.
+#[derive(Debug, Clone)]
+pub struct McpNotification {
+    pub server: String,
+    pub method: String,
+    pub params: Value,
+}
+
+/// How a server process is spawned.
+#[derive(Debug, Clone)]
+pub struct McpSpawnOptions {
+    pub workspace: PathBuf,
+    pub sandbox: Sandbox,
+    pub policy: SandboxPolicy,
+}
+
+/// A request sent by a server to us, e.g. `sampling/createMessage`.
+#[derive(Debug, Clone)]
+pub struct McpServerRequest {
+    pub server: String,
+    /// The agent that last used the connection, if any.
+    pub agent: Option<String>,
+    pub method: String,
+    pub params: Value,
+}
+
+/// JSON-RPC error returned to a server.
+#[derive(Debug, Clone)]
+pub struct McpRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl McpRpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(
+            -32601,
+            format!("method '{method}' is not supported by this client"),
+        )
+    }
+}
+
+/// Answers server-initiated requests. `ping` is handled by the client.
+#[async_trait]
+pub trait McpRequestHandler: Send + Sync {
+    /// Client capabilities advertised to `server` during `initialize`.
+    fn capabilities(&self, server: &McpServerConfig) -> Value;
+
+    async fn handle(&self, request: McpServerRequest) -> std::result::Result<Value, McpRpcError>;
+}
+
+/// State shared between the client and the tasks reading its transport.
+struct ClientInner {
+    server: String,
+    transport: Transport,
+    pending: PendingMap,
+    next_id: AtomicI64,
+    /// Cancelled once the connection is gone.
+    closed: CancellationToken,
+    agent: StdMutex<Option<String>>,
+    handler: Arc<dyn McpRequestHandler>,
+    notifications: broadcast::Sender<McpNotification>,
+}
+
+impl ClientInner {
+    fn fail_pending(&self, reason: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            for (_, tx) in pending.drain() {
+                let _ = tx.send(Err(Error::Tool(reason.to_owned())));
+            }
+        }
+    }
+
+    fn forget(&self, id: i64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+
+    async fn answer(self: Arc<Self>, id: Value, method: String, params: Value) {
+        let outcome = if method == "ping" {
+            Ok(json!({}))
+        } else {
+            let agent = self.agent.lock().ok().and_then(|agent| agent.clone());
+            self.handler
+                .handle(McpServerRequest {
+                    server: self.server.clone(),
+                    agent,
+                    method,
+                    params,
+                })
+                .await
+        };
+        let reply = match outcome {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": err.code, "message": err.message}
+            }),
+        };
+        if let Err(err) = self.transport.send(&reply, &self, &self.closed).await {
+            tracing::debug!(%err, server = self.server, "failed to answer mcp server request");
+        }
+    }
+}
+
+impl Inbound for ClientInner {
+    fn receive(self: Arc<Self>, message: Value) {
+        let method = message.get("method").and_then(Value::as_str);
+        match (message.get("id"), method) {
+            // Response to one of our requests.
+            (Some(id), None) => {
+                let Some(id) = id.as_i64() else {
+                    return;
+                };
+                let waiter = self.pending.lock().ok().and_then(|mut map| map.remove(&id));
+                if let Some(waiter) = waiter {
+                    let outcome = match message.get("error") {
+                        Some(error) => Err(Error::Tool(format!(
+                            "mcp server '{}' returned error: {error}",
+                            self.server
+                        ))),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = waiter.send(outcome);
+                }
+            }
+            // Request from the server; answered without blocking the reader.
+            (Some(id), Some(method)) => {
+                let id = id.clone();
+                let method = method.to_owned();
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                tokio::spawn(self.answer(id, method, params));
+            }
+            (None, Some(method)) => {
+                let _ = self.notifications.send(McpNotification {
+                    server: self.server.clone(),
+                    method: method.to_owned(),
+                    params: message.get("params").cloned().unwrap_or(Value::Null),
+                });
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn fail(self: Arc<Self>, id: i64, error: Error) {
+        let waiter = self.pending.lock().ok().and_then(|mut map| map.remove(&id));
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(Err(error));
+        }
+    }
+}
+
+pub struct McpClient {
+    inner: Arc<ClientInner>,
+    child: Mutex<Option<Child>>,
+    pid: Option<u32>,
+    initialize_result: Value,
+    tasks: StdMutex<Vec<JoinHandle<()>>>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("server", &self.inner.server)
+            .field("pid", &self.pid)
+            .field("alive", &self.is_alive())
+            .finish()
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        // Reader tasks normally end on EOF; this covers servers whose
+        // descendants keep the pipes open and HTTP event streams.
+        if let Ok(mut tasks) = self.tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+    }
+}
+
+impl McpClient {
+    /// Opens the transport and runs the `initialize` handshake. Callers bound
+    /// this with the server's `startup_timeout_seconds`.
+    pub async fn connect(
+        server: &McpServerConfig,
+        options: &McpSpawnOptions,
+        events: EventBus,
+        notifications: broadcast::Sender<McpNotification>,
+        handler: Arc<dyn McpRequestHandler>,
+    ) -> Result<Self> {
+        let capabilities = handler.capabilities(server);
+        let mut tasks = Vec::new();
+        let mut child = None;
+        let mut stdout = None;
+        let transport = match server.transport {
+            McpTransportKind::Stdio | McpTransportKind::StdioContentLength => {
+                let framing = if server.transport == McpTransportKind::Stdio {
+                    StdioFraming::Newline
+                } else {
+                    StdioFraming::ContentLength
+                };
+                let mut spawned = Self::spawn(server, options)?;
+                if let Some(stderr) = spawned.stderr.take() {
+                    tasks.push(tokio::spawn(Self::forward_stderr(
+                        server.name.clone(),
+                        stderr,
+                        events,
+                    )));
+                }
+                stdout = Some((
+                    spawned.stdout.take().ok_or_else(|| {
+                        Error::Tool("failed to capture mcp server stdout".to_owned())
+                    })?,
+                    framing,
+                ));
+                let stdin = spawned.stdin.take();
+                child = Some(spawned);
+                Transport::Stdio {
+                    stdin: Mutex::new(stdin),
+                    framing,
+                }
+            }
+            McpTransportKind::StreamableHttp => Transport::Http(HttpTransport::new(server)?),
+        };
+
+        let inner = Arc::new(ClientInner {
+            server: server.name.clone(),
+            transport,
+            pending: StdMutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+            closed: CancellationToken::new(),
+            agent: StdMutex::new(None),
+            handler,
+            notifications,
+        });
+        if let Some((stdout, framing)) = stdout {
+            tasks.push(tokio::spawn(Self::read_loop(
+                inner.clone(),
+                BufReader::new(stdout),
+                framing,
+            )));
+        }
+
+        let mut client = Self {
+            pid: child.as_ref().and_then(Child::id),
+            inner,
+            child: Mutex::new(child),
+            initialize_result: Value::Null,
+            tasks: StdMutex::new(tasks),
+        };
+        let params = json!({
+            "protocolVersion": server.protocol_version,
+            "capabilities": capabilities,
+            "clientInfo": {
+                "name": "rustic-ai",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        });
+        // The caller bounds the whole handshake, so no per-request timeout here.
+        let initialize = match client.request("initialize", params, Duration::MAX).await {
+            Ok(result) => result,
+            Err(err) => {
+                client.shutdown().await;
+                return Err(Error::Tool(format!(
+                    "mcp initialize failed for server '{}': {err}",
+                    server.name
+                )));
+            }
+        };
+        if let Transport::Http(http) = &client.inner.transport {
+            let negotiated = initialize
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(&server.protocol_version);
+            http.set_protocol_version(negotiated);
+        }
+        client.initialize_result = initialize;
+        client
+            .notify("notifications/initialized", json!({}))
+            .await?;
+
+        if matches!(client.inner.transport, Transport::Http(_)) {
+            let inner = client.inner.clone();
+            let listener = tokio::spawn(async move {
+                if let Transport::Http(http) = &inner.transport {
+                    http.listen(inner.clone()).await;
+                }
+            });
+            if let Ok(mut tasks) = client.tasks.lock() {
+                tasks.push(listener);
+            }
+        }
+        Ok(client)
+    }
+
+    fn spawn(server: &McpServerConfig, options: &McpSpawnOptions) -> Result<Child> {
+        let mut command = Command::new(&server.command);
+        command.args(&server.args);
+        let working_dir = match &server.working_directory {
+            Some(raw) if Path::new(raw).is_absolute() => PathBuf::from(raw),
+            Some(raw) => options.workspace.join(raw),
+            None => options.workspace.clone(),
+        };
+        command
+            .current_dir(&working_dir)
+            .envs(&server.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        options.sandbox.apply_tokio(&mut command, &options.policy)?;
+
+        command.spawn().map_err(|err| {
+            Error::Tool(format!(
+                "failed to spawn mcp server '{}' using '{}': {err}",
+                server.name, server.command
+            ))
+        })
+    }
+
+    pub fn server(&self) -> &str {
+        &self.inner.server
+    }
+
+    /// Process id of a stdio server.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// The server's `initialize` result (protocol version, capabilities, serverInfo).
+    pub fn initialize_result(&self) -> &Value {
+        &self.initialize_result
+    }
+
+    /// True when the server's `initialize` result advertises `capability`.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.initialize_result
+            .get("capabilities")
+            .and_then(|capabilities| capabilities.get(capability))
+            .is_some_and(|value| !value.is_null())
+    }
+
+    /// Records the agent on whose behalf the connection is used, which
+    /// attributes sampling requests.
+    pub fn set_agent(&self, agent: Option<&str>) {
+        if let Ok(mut current) = self.inner.agent.lock() {
+            *current = agent.map(ToOwned::to_owned);
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.inner.closed.is_cancelled()
+    }
+
+    /// Resolves when the connection is gone (process exit, crash or expired
+    /// HTTP session).
+    pub async fn closed(&self) {
+        self.inner.closed.cancelled().await;
+    }
+
+    /// Sends a request and waits up to `timeout` for its response. On timeout
+    /// the server is told to cancel the request.
+    pub async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        self.request_cancellable(method, params, timeout, None)
+            .await
+    }
+
+    /// Like [`Self::request`], but also gives up, and tells the server to
+    /// cancel, when `cancellation` fires.
+    pub async fn request_cancellable(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+        cancellation: Option<&CancellationToken>,
+    ) -> Result<Value> {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.inner.pending.lock() {
+            pending.insert(id, tx);
+        }
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let exchange = async {
+            self.inner
+                .transport
+                .send(&message, &self.inner, &self.inner.closed)
+                .await?;
+            rx.await.map_err(|_| {
+                Error::Tool(format!(
+                    "mcp server '{}' closed the connection before answering {method}",
+                    self.inner.server
+                ))
+            })?
+        };
+        let cancelled = async {
+            match cancellation {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let outcome = tokio::select! {
+            outcome = tokio::time::timeout(timeout, exchange) => outcome,
+            _ = cancelled => {
+                self.inner.forget(id);
+                let _ = self
+                    .notify(
+                        "notifications/cancelled",
+                        json!({"requestId": id, "reason": "cancelled"}),
+                    )
+                    .await;
+                return Err(Error::Cancelled(format!(
+                    "mcp {method} on server '{}'",
+                    self.inner.server
+                )));
+            }
+        };
+        match outcome {
+            Ok(result) => {
+                if result.is_err() {
+                    self.inner.forget(id);
+                }
+                result
+            }
+            Err(_) => {
+                self.inner.forget(id);
+                let _ = self
+                    .notify(
+                        "notifications/cancelled",
+                        json!({"requestId": id, "reason": "timeout"}),
+                    )
+                    .await;
+                Err(Error::Timeout(format!(
+                    "mcp {method} on server '{}' timed out after {}s",
+                    self.inner.server,
+                    timeout.as_secs()
+                )))
+            }
+        }
+    }
+
+    /// Collects `field` from every page of a paginated list method such as
+    /// `tools/list` or `resources/list`, following `nextCursor`.
+    pub async fn list_all(
+        &self,
+        method: &str,
+        field: &str,
+        timeout: Duration,
+    ) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LIST_PAGES {
+            let params = match &cursor {
+                Some(cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let page = self.request(method, params, timeout).await?;
+            if let Some(listed) = page.get(field).and_then(Value::as_array) {
+                items.extend(listed.iter().cloned());
+            }
+            cursor = page
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        self.inner
+            .transport
+            .send(&message, &self.inner, &self.inner.closed)
+            .await
+    }
+
+    /// Closes the transport (stdin, or the HTTP session), gives a stdio server
+    /// a moment to exit, then kills it.
+    pub async fn shutdown(&self) {
+        self.inner.transport.close().await;
+        if let Some(mut child) = self.child.lock().await.take() {
+            if tokio::time::timeout(SHUTDOWN_GRACE, child.wait())
+                .await
+                .is_err()
+            {
+                let _ = child.kill().await;
+            }
+        }
+        self.inner.closed.cancel();
+        self.inner.fail_pending("mcp connection closed");
+    }
+
+    async fn read_loop<R: AsyncRead + Unpin>(
+        inner: Arc<ClientInner>,
+        mut reader: BufReader<R>,
+        framing: StdioFraming,
+    ) {
+        loop {
+            match read_stdio(&mut reader, framing).await {
+                Ok(Some(message)) => inner.clone().receive(message),
+                Ok(None) => break,
+                Err(err) => {
+                    tracing::warn!(%err, server = inner.server, "dropping malformed mcp message");
+                }
+            }
+        }
+
+        inner.closed.cancel();
+        inner.fail_pending(&format!(
+            "mcp server '{}' closed the connection",
+            inner.server
+        ));
+    }
+
+    async fn forward_stderr<R: AsyncRead + Unpin>(server: String, stderr: R, events: EventBus) {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(mut line)) = lines.next_line().await {
+            if line.len() > MAX_STDERR_LINE_BYTES {
+                let mut cut = MAX_STDERR_LINE_BYTES;
+                while !line.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                line.truncate(cut);
+            }
+            events.publish(Event::McpServerLog {
+                server: server.clone(),
+                line,
+            });
+        }
+    }
+}
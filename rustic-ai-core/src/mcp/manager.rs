@@ -0,0 +1,415 @@
+//! Keeps one initialized client per configured MCP server alive for the
+//! lifetime of the runtime.
+//!
+//! Connections are opened lazily on first use, bounded by the server's
+//! `startup_timeout_seconds`. A supervisor task per connection notices crashes
+//! and reconnects with exponential backoff; after `MAX_RESTART_ATTEMPTS`
+//! consecutive failures the server is marked failed until the next call
+//! retries it on demand.
+
+use crate::config::schema::{AgentPermissionMode, McpConfig, McpServerConfig};
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::mcp::client::{McpClient, McpNotification, McpSpawnOptions};
+use crate::mcp::sampling::McpSamplingBroker;
+use crate::sandbox::{Sandbox, SandboxPolicy};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio_util::sync::CancellationToken;
+
+const RESTART_BACKOFF_BASE: Duration = Duration::from_millis(500);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+const NOTIFICATION_BUFFER: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpServerState {
+    /// Configured but not started yet.
+    Idle,
+    Running,
+    Restarting,
+    /// Gave up restarting; the next call tries again.
+    Failed,
+    Stopped,
+}
+
+impl McpServerState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Running => "running",
+            Self::Restarting => "restarting",
+            Self::Failed => "failed",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerStatus {
+    pub server: String,
+    pub workspace: PathBuf,
+    pub workspace_writable: bool,
+    pub state: McpServerState,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+/// Servers run under the sandbox policy of the calling agent, so read-only and
+/// read-write agents get separate processes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ConnectionKey {
+    server: String,
+    workspace: PathBuf,
+    workspace_writable: bool,
+}
+
+#[derive(Debug)]
+struct SlotState {
+    client: Option<Arc<McpClient>>,
+    state: McpServerState,
+    restarts: u32,
+    last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct ServerSlot {
+    server: McpServerConfig,
+    options: McpSpawnOptions,
+    state: Mutex<SlotState>,
+}
+
+/// What supervisors need from the manager without keeping it alive.
+#[derive(Clone)]
+struct Shared {
+    events: EventBus,
+    notifications: broadcast::Sender<McpNotification>,
+    sampling: Arc<McpSamplingBroker>,
+    shutdown: CancellationToken,
+}
+
+pub struct McpConnectionManager {
+    config: Arc<McpConfig>,
+    sandbox: Sandbox,
+    shared: Shared,
+    slots: StdMutex<HashMap<ConnectionKey, Arc<ServerSlot>>>,
+}
+
+impl std::fmt::Debug for McpConnectionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpConnectionManager")
+            .field("servers", &self.config.servers.len())
+            .finish()
+    }
+}
+
+impl McpConnectionManager {
+    pub fn new(config: Arc<McpConfig>, sandbox: Sandbox, events: EventBus) -> Self {
+        let (notifications, _) = broadcast::channel(NOTIFICATION_BUFFER);
+        let sampling = Arc::new(McpSamplingBroker::new(config.clone(), events.clone()));
+        Self {
+            config,
+            sandbox,
+            shared: Shared {
+                events,
+                notifications,
+                sampling,
+                shutdown: CancellationToken::new(),
+            },
+            slots: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &McpConfig {
+        &self.config
+    }
+
+    pub fn server_config(&self, name: &str) -> Result<&McpServerConfig> {
+        self.config
+            .servers
+            .iter()
+            .find(|server| server.name == name)
+            .ok_or_else(|| Error::Tool(format!("mcp server '{name}' is not configured")))
+    }
+
+    /// Answers `sampling/createMessage` requests from every server.
+    pub fn sampling(&self) -> &Arc<McpSamplingBroker> {
+        &self.shared.sampling
+    }
+
+    /// Notifications from every connected server.
+    pub fn subscribe(&self) -> broadcast::Receiver<McpNotification> {
+        self.shared.notifications.subscribe()
+    }
+
+    /// Returns the live client for `server`, starting it if needed.
+    pub async fn client(
+        &self,
+        server: &str,
+        workspace: &Path,
+        mode: AgentPermissionMode,
+    ) -> Result<Arc<McpClient>> {
+        if self.shared.shutdown.is_cancelled() {
+            return Err(Error::Tool("mcp connections are shut down".to_owned()));
+        }
+        let server = self.server_config(server)?.clone();
+        let policy = SandboxPolicy::for_agent(workspace, mode);
+        let key = ConnectionKey {
+            server: server.name.clone(),
+            workspace: policy.workspace.clone(),
+            workspace_writable: policy.workspace_writable,
+        };
+        let slot = {
+            let mut slots = self
+                .slots
+                .lock()
+                .map_err(|_| Error::Tool("mcp connection table is poisoned".to_owned()))?;
+            slots
+                .entry(key)
+                .or_insert_with(|| {
+                    Arc::new(ServerSlot {
+                        server,
+                        options: McpSpawnOptions {
+                            workspace: workspace.to_path_buf(),
+                            sandbox: self.sandbox.clone(),
+                            policy,
+                        },
+                        state: Mutex::new(SlotState {
+                            client: None,
+                            state: McpServerState::Idle,
+                            restarts: 0,
+                            last_error: None,
+                        }),
+                    })
+                })
+                .clone()
+        };
+
+        let mut state = slot.state.lock().await;
+        if let Some(client) = state.client.as_ref().filter(|client| client.is_alive()) {
+            return Ok(client.clone());
+        }
+        match Self::start(&slot, &self.shared).await {
+            Ok(client) => {
+                state.client = Some(client.clone());
+                state.state = McpServerState::Running;
+                state.last_error = None;
+                drop(state);
+                Self::publish_state(
+                    &self.shared,
+                    &slot.server.name,
+                    McpServerState::Running,
+                    None,
+                );
+                tokio::spawn(Self::supervise(slot, self.shared.clone(), client.clone()));
+                Ok(client)
+            }
+            Err(err) => {
+                state.state = McpServerState::Failed;
+                state.last_error = Some(err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    /// Status of every configured server, one entry per live connection key.
+    pub async fn status(&self) -> Vec<McpServerStatus> {
+        let slots = self
+            .slots
+            .lock()
+            .map(|slots| {
+                slots
+                    .iter()
+                    .map(|(key, slot)| (key.clone(), slot.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut statuses = Vec::new();
+        for server in &self.config.servers {
+            let mut seen = false;
+            for (key, slot) in slots.iter().filter(|(key, _)| key.server == server.name) {
+                seen = true;
+                let state = slot.state.lock().await;
+                let running = state.client.as_ref().filter(|client| client.is_alive());
+                statuses.push(McpServerStatus {
+                    server: server.name.clone(),
+                    workspace: key.workspace.clone(),
+                    workspace_writable: key.workspace_writable,
+                    state: state.state,
+                    pid: running.and_then(|client| client.pid()),
+                    restarts: state.restarts,
+                    last_error: state.last_error.clone(),
+                });
+            }
+            if !seen {
+                statuses.push(McpServerStatus {
+                    server: server.name.clone(),
+                    workspace: PathBuf::new(),
+                    workspace_writable: false,
+                    state: McpServerState::Idle,
+                    pid: None,
+                    restarts: 0,
+                    last_error: None,
+                });
+            }
+        }
+        statuses
+    }
+
+    /// Resolves once `shutdown` has been called.
+    pub async fn stopped(&self) {
+        self.shared.shutdown.cancelled().await;
+    }
+
+    /// Stops every server and refuses new connections.
+    pub async fn shutdown(&self) {
+        self.shared.shutdown.cancel();
+        let slots = self
+            .slots
+            .lock()
+            .map(|mut slots| slots.drain().map(|(_, slot)| slot).collect::<Vec<_>>())
+            .unwrap_or_default();
+        for slot in slots {
+            let mut state = slot.state.lock().await;
+            if let Some(client) = state.client.take() {
+                client.shutdown().await;
+                Self::publish_state(
+                    &self.shared,
+                    &slot.server.name,
+                    McpServerState::Stopped,
+                    None,
+                );
+            }
+            state.state = McpServerState::Stopped;
+        }
+    }
+
+    async fn start(slot: &ServerSlot, shared: &Shared) -> Result<Arc<McpClient>> {
+        let startup = Duration::from_secs(slot.server.startup_timeout_seconds.max(1));
+        let connect = McpClient::connect(
+            &slot.server,
+            &slot.options,
+            shared.events.clone(),
+            shared.notifications.clone(),
+            shared.sampling.clone(),
+        );
+        match tokio::time::timeout(startup, connect).await {
+            Ok(result) => result.map(Arc::new),
+            Err(_) => Err(Error::Timeout(format!(
+                "mcp server '{}' did not finish initializing within {}s",
+                slot.server.name,
+                startup.as_secs()
+            ))),
+        }
+    }
+
+    /// Waits for `client` to exit and restarts it unless the exit was requested.
+    async fn supervise(slot: Arc<ServerSlot>, shared: Shared, mut client: Arc<McpClient>) {
+        let name = slot.server.name.clone();
+        loop {
+            tokio::select! {
+                _ = client.closed() => {}
+                _ = shared.shutdown.cancelled() => return,
+            }
+            {
+                let mut state = slot.state.lock().await;
+                let current = state
+                    .client
+                    .as_ref()
+                    .is_some_and(|active| Arc::ptr_eq(active, &client));
+                if !current || shared.shutdown.is_cancelled() {
+                    return;
+                }
+                state.client = None;
+                state.state = McpServerState::Restarting;
+            }
+            client.shutdown().await;
+            Self::publish_state(
+                &shared,
+                &name,
+                McpServerState::Restarting,
+                Some("server exited unexpectedly".to_owned()),
+            );
+
+            let mut attempt = 0u32;
+            client = loop {
+                if attempt >= MAX_RESTART_ATTEMPTS {
+                    let mut state = slot.state.lock().await;
+                    if state.client.is_none() {
+                        state.state = McpServerState::Failed;
+                        drop(state);
+                        Self::publish_state(
+                            &shared,
+                            &name,
+                            McpServerState::Failed,
+                            Some(format!(
+                                "gave up after {MAX_RESTART_ATTEMPTS} restart attempts"
+                            )),
+                        );
+                    }
+                    return;
+                }
+                let backoff = RESTART_BACKOFF_BASE
+                    .saturating_mul(2u32.saturating_pow(attempt))
+                    .min(RESTART_BACKOFF_MAX);
+                attempt += 1;
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shared.shutdown.cancelled() => return,
+                }
+
+                let mut state = slot.state.lock().await;
+                // A call may have reconnected on demand in the meantime; its
+                // own supervisor takes over.
+                if state
+                    .client
+                    .as_ref()
+                    .is_some_and(|active| active.is_alive())
+                    || shared.shutdown.is_cancelled()
+                {
+                    return;
+                }
+                state.restarts = state.restarts.saturating_add(1);
+                match Self::start(&slot, &shared).await {
+                    Ok(restarted) => {
+                        state.client = Some(restarted.clone());
+                        state.state = McpServerState::Running;
+                        state.last_error = None;
+                        drop(state);
+                        Self::publish_state(
+                            &shared,
+                            &name,
+                            McpServerState::Running,
+                            Some(format!("restarted (attempt {attempt})")),
+                        );
+                        break restarted;
+                    }
+                    Err(err) => {
+                        state.last_error = Some(err.to_string());
+                        drop(state);
+                        Self::publish_state(
+                            &shared,
+                            &name,
+                            McpServerState::Restarting,
+                            Some(format!("restart attempt {attempt} failed: {err}")),
+                        );
+                    }
+                }
+            };
+        }
+    }
+
+    fn publish_state(shared: &Shared, server: &str, state: McpServerState, detail: Option<String>) {
+        shared.events.publish(Event::McpServerStateChanged {
+            server: server.to_owned(),
+            state: state.as_str().to_owned(),
+            detail,
+        });
+    }
+}
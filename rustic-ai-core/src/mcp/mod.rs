@@ -0,0 +1,19 @@
+//! Model Context Protocol client and server support.
+//!
+//! [`McpConnectionManager`] owns the long-lived server processes; the `mcp`
+//! tool and anything else that talks to MCP servers goes through it. Servers
+//! may request completions through [`McpSamplingBroker`]. [`McpServer`]
+//! exposes Rustic-AI itself to other MCP clients.
+
+pub mod client;
+pub mod manager;
+pub mod sampling;
+pub mod server;
+mod transport;
+
+pub use client::{
+    McpClient, McpNotification, McpRequestHandler, McpRpcError, McpServerRequest, McpSpawnOptions,
+};
+pub use manager::{McpConnectionManager, McpServerState, McpServerStatus};
+pub use sampling::{McpSamplingBroker, SamplingModel, SamplingModelResolver};
+pub use server::McpServer;
@@ -0,0 +1,292 @@
+//! Answers `sampling/createMessage` requests from MCP servers with the
+//! calling agent's model, after the user approves each request.
+
+use crate::config::schema::{McpConfig, McpServerConfig};
+use crate::events::{Event, EventBus};
+use crate::mcp::client::{McpRequestHandler, McpRpcError, McpServerRequest};
+use crate::providers::types::{ChatMessage, GenerateOptions, ModelProvider};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// How long a sampling request waits for the user.
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+/// Longest request summary shown when asking for approval.
+const MAX_SUMMARY_CHARS: usize = 200;
+/// JSON-RPC error code for requests the user rejected.
+const USER_REJECTED: i64 = -1;
+
+/// The model that answers a sampling request on behalf of an agent.
+pub struct SamplingModel {
+    pub provider: Arc<dyn ModelProvider>,
+    pub temperature: f32,
+    pub max_tokens: usize,
+}
+
+/// Finds the model of `agent`, or of the default agent when `None`.
+pub trait SamplingModelResolver: Send + Sync {
+    fn resolve(&self, agent: Option<&str>) -> Option<SamplingModel>;
+}
+
+pub struct McpSamplingBroker {
+    config: Arc<McpConfig>,
+    events: EventBus,
+    resolver: StdRwLock<Option<Arc<dyn SamplingModelResolver>>>,
+    approvals_enabled: AtomicBool,
+    approvals: StdMutex<HashMap<String, oneshot::Sender<bool>>>,
+    next_id: AtomicU64,
+}
+
+impl std::fmt::Debug for McpSamplingBroker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpSamplingBroker")
+            .field(
+                "approvals_enabled",
+                &self.approvals_enabled.load(Ordering::Relaxed),
+            )
+            .finish()
+    }
+}
+
+impl McpSamplingBroker {
+    pub fn new(config: Arc<McpConfig>, events: EventBus) -> Self {
+        Self {
+            config,
+            events,
+            resolver: StdRwLock::new(None),
+            approvals_enabled: AtomicBool::new(false),
+            approvals: StdMutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn set_resolver(&self, resolver: Arc<dyn SamplingModelResolver>) {
+        if let Ok(mut current) = self.resolver.write() {
+            *current = Some(resolver);
+        }
+    }
+
+    /// Frontends that can ask the user enable approvals. Without them every
+    /// sampling request is rejected.
+    pub fn set_approvals_enabled(&self, enabled: bool) {
+        self.approvals_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Approves or denies a pending request. Returns false for unknown ids.
+    pub fn resolve(&self, id: &str, approved: bool) -> bool {
+        let waiter = self
+            .approvals
+            .lock()
+            .ok()
+            .and_then(|mut approvals| approvals.remove(id));
+        waiter.is_some_and(|waiter| waiter.send(approved).is_ok())
+    }
+
+    /// Ids of requests waiting for approval.
+    pub fn pending(&self) -> Vec<String> {
+        let mut ids = self
+            .approvals
+            .lock()
+            .map(|approvals| approvals.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        ids.sort();
+        ids
+    }
+
+    fn server_allows_sampling(&self, server: &str) -> bool {
+        self.config
+            .servers
+            .iter()
+            .any(|config| config.name == server && config.sampling)
+    }
+
+    async fn await_approval(&self, id: &str) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut approvals) = self.approvals.lock() {
+            approvals.insert(id.to_owned(), tx);
+        }
+        let approved = matches!(
+            tokio::time::timeout(APPROVAL_TIMEOUT, rx).await,
+            Ok(Ok(true))
+        );
+        if let Ok(mut approvals) = self.approvals.lock() {
+            approvals.remove(id);
+        }
+        approved
+    }
+
+    async fn create_message(
+        &self,
+        request: McpServerRequest,
+    ) -> std::result::Result<Value, McpRpcError> {
+        if !self.server_allows_sampling(&request.server) {
+            return Err(McpRpcError::method_not_found(&request.method));
+        }
+        let resolver = self
+            .resolver
+            .read()
+            .ok()
+            .and_then(|resolver| resolver.clone());
+        let model = resolver
+            .and_then(|resolver| resolver.resolve(request.agent.as_deref()))
+            .ok_or_else(|| McpRpcError::new(-32603, "no agent model is available for sampling"))?;
+        if !self.approvals_enabled.load(Ordering::Relaxed) {
+            return Err(McpRpcError::new(
+                USER_REJECTED,
+                "sampling requires interactive approval",
+            ));
+        }
+
+        let params = &request.params;
+        let messages =
+            sampling_messages(params).map_err(|message| McpRpcError::new(-32602, message))?;
+        let max_tokens = params
+            .get("maxTokens")
+            .and_then(Value::as_u64)
+            .map(|tokens| (tokens as usize).min(model.max_tokens))
+            .unwrap_or(model.max_tokens);
+        let id = format!("s{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.events.publish(Event::McpSamplingRequested {
+            id: id.clone(),
+            server: request.server.clone(),
+            agent: request.agent.clone(),
+            summary: summarize(&messages),
+            max_tokens,
+        });
+        if !self.await_approval(&id).await {
+            return Err(McpRpcError::new(
+                USER_REJECTED,
+                "user rejected sampling request",
+            ));
+        }
+
+        let options = GenerateOptions {
+            temperature: params
+                .get("temperature")
+                .and_then(Value::as_f64)
+                .map(|value| value as f32)
+                .unwrap_or(model.temperature),
+            max_tokens,
+            top_p: None,
+            top_k: None,
+            stop_sequences: params
+                .get("stopSequences")
+                .and_then(Value::as_array)
+                .map(|stops| {
+                    stops
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(ToOwned::to_owned)
+                        .collect()
+                }),
+            presence_penalty: None,
+            frequency_penalty: None,
+        };
+        let text = model
+            .provider
+            .generate(&messages, &options)
+            .await
+            .map_err(|err| McpRpcError::new(-32603, format!("sampling failed: {err}")))?;
+        Ok(json!({
+            "role": "assistant",
+            "content": {"type": "text", "text": text},
+            "model": model.provider.name(),
+            "stopReason": "endTurn"
+        }))
+    }
+}
+
+#[async_trait]
+impl McpRequestHandler for McpSamplingBroker {
+    fn capabilities(&self, server: &McpServerConfig) -> Value {
+        if server.sampling {
+            json!({"sampling": {}})
+        } else {
+            json!({})
+        }
+    }
+
+    async fn handle(&self, request: McpServerRequest) -> std::result::Result<Value, McpRpcError> {
+        match request.method.as_str() {
+            "sampling/createMessage" => self.create_message(request).await,
+            other => Err(McpRpcError::method_not_found(other)),
+        }
+    }
+}
+
+/// Converts `systemPrompt` and `messages` into chat messages. Non-text
+/// content is replaced by a placeholder.
+fn sampling_messages(params: &Value) -> std::result::Result<Vec<ChatMessage>, String> {
+    let listed = params
+        .get("messages")
+        .and_then(Value::as_array)
+        .filter(|messages| !messages.is_empty())
+        .ok_or_else(|| "sampling request has no messages".to_owned())?;
+    let mut messages = Vec::with_capacity(listed.len() + 1);
+    if let Some(system) = params
+        .get("systemPrompt")
+        .and_then(Value::as_str)
+        .filter(|system| !system.trim().is_empty())
+    {
+        messages.push(chat_message("system", system.to_owned()));
+    }
+    for message in listed {
+        let role = match message.get("role").and_then(Value::as_str) {
+            Some("assistant") => "assistant",
+            _ => "user",
+        };
+        let content = message.get("content").cloned().unwrap_or(Value::Null);
+        let parts = match content {
+            Value::Array(parts) => parts,
+            part => vec![part],
+        };
+        let text = parts
+            .iter()
+            .map(|part| match part.get("type").and_then(Value::as_str) {
+                Some("text") => part
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_owned(),
+                Some(other) => format!("[{other} content omitted]"),
+                None => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        messages.push(chat_message(role, text));
+    }
+    Ok(messages)
+}
+
+fn chat_message(role: &str, content: String) -> ChatMessage {
+    ChatMessage {
+        role: role.to_owned(),
+        content,
+        name: None,
+        tool_calls: None,
+    }
+}
+
+fn summarize(messages: &[ChatMessage]) -> String {
+    let last = messages
+        .iter()
+        .rev()
+        .find(|message| message.role == "user")
+        .map(|message| {
+            message
+                .content
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    let mut summary = last.chars().take(MAX_SUMMARY_CHARS).collect::<String>();
+    if last.chars().count() > MAX_SUMMARY_CHARS {
+        summary.push('…');
+    }
+    format!("{} message(s); last: {summary}", messages.len())
+}
@@ -0,0 +1,724 @@
+//! Serves Rustic-AI itself over MCP (`mcp-serve`): selected tools, agents,
+//! code retrieval, impact analysis and workflows, over newline-delimited
+//! stdio or a loopback Streamable HTTP endpoint.
+//!
+//! Every inbound call is checked against the permission policy as the
+//! `mcp.serve.agent` identity before it runs.
+
+use crate::config::schema::{McpServeAskMode, McpServeConfig};
+use crate::error::{Error, Result};
+use crate::events::Event;
+use crate::permissions::{AskResolution, PermissionDecision};
+use crate::RusticAI;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex};
+
+/// Protocol versions this server speaks, newest first.
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+const AGENT_TOOL_PREFIX: &str = "agent_";
+const WORKFLOW_TOOL_PREFIX: &str = "workflow_";
+const RETRIEVAL_TOOL: &str = "retrieve_code_context";
+const IMPACT_TOOL: &str = "analyze_impact";
+/// Approvals granted to one agent task before it is stopped.
+const MAX_AGENT_APPROVALS: usize = 32;
+const MAX_HTTP_HEADER_LINES: usize = 100;
+const MAX_HTTP_BODY_BYTES: usize = 4 * 1024 * 1024;
+const SESSION_HEADER: &str = "mcp-session-id";
+
+pub struct McpServer {
+    app: Arc<RusticAI>,
+    config: McpServeConfig,
+    agent: String,
+    session_id: String,
+    events: mpsc::Sender<Event>,
+    http_sessions: StdMutex<HashSet<String>>,
+}
+
+impl McpServer {
+    /// Creates the server and the session its calls are recorded under.
+    /// Must be called inside a Tokio runtime.
+    pub async fn new(app: Arc<RusticAI>) -> Result<Self> {
+        let config = app.config().mcp.serve.clone();
+        let agent = app
+            .runtime()
+            .agents
+            .get_agent(config.agent.as_deref())?
+            .config()
+            .name
+            .clone();
+        let session_id = app
+            .session_manager()
+            .create_session(&agent)
+            .await?
+            .to_string();
+
+        // Nobody watches progress events here; drain them so senders never block.
+        let (events, mut event_rx) = mpsc::channel(256);
+        tokio::spawn(async move { while event_rx.recv().await.is_some() {} });
+
+        Ok(Self {
+            app,
+            config,
+            agent,
+            session_id,
+            events,
+            http_sessions: StdMutex::new(HashSet::new()),
+        })
+    }
+
+    /// Serves newline-delimited JSON-RPC on stdin/stdout until stdin closes.
+    /// Requests run concurrently; stdout carries protocol messages only.
+    pub async fn serve_stdio(self: Arc<Self>) -> Result<()> {
+        let stdout = Arc::new(Mutex::new(tokio::io::stdout()));
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let server = self.clone();
+            let stdout = stdout.clone();
+            tokio::spawn(async move {
+                let Some(reply) = server.handle_payload(&line).await else {
+                    return;
+                };
+                let mut stdout = stdout.lock().await;
+                let written = async {
+                    stdout.write_all(reply.to_string().as_bytes()).await?;
+                    stdout.write_all(b"\n").await?;
+                    stdout.flush().await
+                }
+                .await;
+                if let Err(err) = written {
+                    tracing::warn!("failed to write MCP response: {err}");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Serves Streamable HTTP at `http://<bind>/mcp`. Responses are plain
+    /// JSON; there is no server-initiated stream.
+    pub async fn serve_http(self: Arc<Self>, bind: &str) -> Result<()> {
+        let addr = bind
+            .parse::<std::net::SocketAddr>()
+            .map_err(|err| Error::Config(format!("invalid MCP bind address '{bind}': {err}")))?;
+        if !addr.ip().is_loopback() {
+            return Err(Error::Config(format!(
+                "MCP server only binds to loopback addresses, got '{bind}'"
+            )));
+        }
+        let listener = TcpListener::bind(addr).await.map_err(|err| {
+            Error::Config(format!("failed to bind MCP server on '{bind}': {err}"))
+        })?;
+        eprintln!("MCP server listening on http://{addr}/mcp");
+        loop {
+            let (stream, _peer) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(err) = server.handle_http_connection(stream).await {
+                    tracing::debug!("MCP HTTP connection failed: {err}");
+                }
+            });
+        }
+    }
+
+    async fn handle_http_connection(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).await?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_owned();
+        let path = parts.next().unwrap_or_default().to_owned();
+
+        let mut headers = Vec::new();
+        for _ in 0..MAX_HTTP_HEADER_LINES {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+            }
+        }
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let response = if path.split('?').next() != Some("/mcp") {
+            http_response(404, None, "")
+        } else if header("origin").is_some_and(|origin| !is_local_origin(origin)) {
+            http_response(403, None, "")
+        } else {
+            match method.as_str() {
+                "POST" => {
+                    let length = header("content-length")
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .unwrap_or_default();
+                    if length > MAX_HTTP_BODY_BYTES {
+                        http_response(413, None, "")
+                    } else {
+                        let mut body = vec![0u8; length];
+                        reader.read_exact(&mut body).await?;
+                        self.handle_http_post(header(SESSION_HEADER), &body).await
+                    }
+                }
+                "DELETE" => match header(SESSION_HEADER) {
+                    Some(session) if self.end_http_session(session) => http_response(200, None, ""),
+                    Some(_) => http_response(404, None, ""),
+                    None => http_response(400, None, ""),
+                },
+                _ => http_response(405, None, ""),
+            }
+        };
+
+        let mut stream = reader.into_inner();
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    async fn handle_http_post(&self, session: Option<&str>, body: &[u8]) -> String {
+        let payload = String::from_utf8_lossy(body);
+        let initializing = serde_json::from_str::<Value>(&payload)
+            .ok()
+            .is_some_and(|message| message.get("method") == Some(&json!("initialize")));
+
+        let new_session = if initializing {
+            let session = uuid::Uuid::new_v4().to_string();
+            if let Ok(mut sessions) = self.http_sessions.lock() {
+                sessions.insert(session.clone());
+            }
+            Some(session)
+        } else {
+            match session {
+                Some(session) if self.has_http_session(session) => None,
+                Some(_) => return http_response(404, None, ""),
+                None => return http_response(400, None, ""),
+            }
+        };
+
+        match self.handle_payload(&payload).await {
+            Some(reply) => http_response(200, new_session.as_deref(), &reply.to_string()),
+            None => http_response(202, new_session.as_deref(), ""),
+        }
+    }
+
+    fn has_http_session(&self, session: &str) -> bool {
+        self.http_sessions
+            .lock()
+            .is_ok_and(|sessions| sessions.contains(session))
+    }
+
+    fn end_http_session(&self, session: &str) -> bool {
+        self.http_sessions
+            .lock()
+            .is_ok_and(|mut sessions| sessions.remove(session))
+    }
+
+    /// Handles one JSON-RPC message or batch. Returns `None` when nothing
+    /// needs a reply (notifications and responses).
+    async fn handle_payload(&self, payload: &str) -> Option<Value> {
+        let message = match serde_json::from_str::<Value>(payload) {
+            Ok(message) => message,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    -32700,
+                    &format!("parse error: {err}"),
+                ))
+            }
+        };
+        match message {
+            Value::Array(batch) => {
+                let mut replies = Vec::new();
+                for message in batch {
+                    if let Some(reply) = self.handle_message(message).await {
+                        replies.push(reply);
+                    }
+                }
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            message => self.handle_message(message).await,
+        }
+    }
+
+    async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return id.map(|id| error_response(id, -32600, "invalid request"));
+        };
+        // Notifications (no id) need no reply.
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.list_tools().await})),
+            "tools/call" => self.call_tool(&params).await,
+            other => Err((-32601, format!("method not found: {other}"))),
+        };
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let version = SUPPORTED_PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| **version == requested)
+            .unwrap_or(&SUPPORTED_PROTOCOL_VERSIONS[0]);
+        json!({
+            "protocolVersion": version,
+            "capabilities": {"tools": {"listChanged": false}},
+            "serverInfo": {"name": "rustic-ai", "version": env!("CARGO_PKG_VERSION")},
+            "instructions": "Tools run inside a Rustic-AI workspace under its permission policy."
+        })
+    }
+
+    async fn list_tools(&self) -> Vec<Value> {
+        let tools = &self.app.runtime().tools;
+        let mut listed = Vec::new();
+
+        for name in &self.config.tools {
+            if let Some((description, schema)) = tools.get_tool_schema(name).await {
+                listed.push(tool_entry(name, &description, schema));
+            }
+        }
+
+        if self.config.expose_agents {
+            let agents = &self.app.runtime().agents;
+            let mut names = agents.list_agents();
+            names.sort();
+            for name in names {
+                let provider = agents
+                    .get_agent_config(&name)
+                    .map(|config| config.provider.clone())
+                    .unwrap_or_default();
+                listed.push(tool_entry(
+                    &format!("{AGENT_TOOL_PREFIX}{name}"),
+                    &format!("Run a task with the '{name}' agent ({provider}) and return its final answer."),
+                    json!({
+                        "type": "object",
+                        "properties": {
+                            "task": {"type": "string", "description": "What the agent should do."}
+                        },
+                        "required": ["task"]
+                    }),
+                ));
+            }
+        }
+
+        if self.config.expose_retrieval {
+            listed.push(tool_entry(
+                RETRIEVAL_TOOL,
+                "Retrieve ranked code snippets and symbols for a query from the workspace index.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string"},
+                        "top_k": {"type": "integer", "minimum": 1},
+                        "min_score": {"type": "number"},
+                        "path_prefix": {"type": "string"},
+                        "kind": {"type": "string"}
+                    },
+                    "required": ["query"]
+                }),
+            ));
+        }
+
+        if self.config.expose_impact_analysis {
+            listed.push(tool_entry(
+                IMPACT_TOOL,
+                "List the symbols and files affected by changing a symbol, from the code index.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": {"type": "string"},
+                        "depth": {"type": "integer", "minimum": 1}
+                    },
+                    "required": ["symbol"]
+                }),
+            ));
+        }
+
+        if self.config.expose_workflows && tools.has_tool("workflow").await {
+            let workflows = &self.app.runtime().workflows;
+            for name in workflows.list() {
+                let Some(workflow) = workflows.get(&name).filter(|workflow| !workflow.template)
+                else {
+                    continue;
+                };
+                let entrypoints = workflow.entrypoints.keys().cloned().collect::<Vec<_>>();
+                let input_schema = workflow
+                    .input_schema
+                    .clone()
+                    .unwrap_or_else(|| json!({"type": "object"}));
+                listed.push(tool_entry(
+                    &format!("{WORKFLOW_TOOL_PREFIX}{name}"),
+                    &workflow.description,
+                    json!({
+                        "type": "object",
+                        "properties": {
+                            "input": input_schema,
+                            "entrypoint": {"type": "string", "enum": entrypoints}
+                        }
+                    }),
+                ));
+            }
+        }
+
+        listed
+    }
+
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| (-32602, "tools/call requires 'name'".to_owned()))?;
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        let outcome = if self.config.tools.iter().any(|tool| tool == name) {
+            self.run_managed_tool(name, args).await
+        } else if let Some(agent) = name
+            .strip_prefix(AGENT_TOOL_PREFIX)
+            .filter(|_| self.config.expose_agents)
+        {
+            self.run_agent_task(agent, &args).await
+        } else if let Some(workflow) = name
+            .strip_prefix(WORKFLOW_TOOL_PREFIX)
+            .filter(|workflow| self.exposes_workflow(workflow))
+        {
+            let mut call = json!({
+                "workflow": workflow,
+                "input": args.get("input").cloned().unwrap_or_else(|| json!({}))
+            });
+            if let Some(entrypoint) = args.get("entrypoint") {
+                call["entrypoint"] = entrypoint.clone();
+            }
+            self.run_managed_tool("workflow", call).await
+        } else if name == RETRIEVAL_TOOL && self.config.expose_retrieval {
+            self.retrieve(&args).await
+        } else if name == IMPACT_TOOL && self.config.expose_impact_analysis {
+            self.analyze_impact(&args).await
+        } else {
+            return Err((-32602, format!("unknown tool: {name}")));
+        };
+
+        Ok(match outcome {
+            Ok((text, success)) => tool_result(&text, !success),
+            Err(err) => tool_result(&err.to_string(), true),
+        })
+    }
+
+    fn exposes_workflow(&self, name: &str) -> bool {
+        self.config.expose_workflows
+            && self
+                .app
+                .runtime()
+                .workflows
+                .get(name)
+                .is_some_and(|workflow| !workflow.template)
+    }
+
+    /// Applies the permission policy to a call. `ask` is refused unless
+    /// `ask_mode` trusts the MCP client to have confirmed the call.
+    async fn authorize(&self, agent: &str, tool: &str, args: &Value) -> Result<bool> {
+        let decision = self
+            .app
+            .runtime()
+            .tools
+            .check_permission(self.session_id.clone(), Some(agent.to_owned()), tool, args)
+            .await;
+        match decision {
+            PermissionDecision::Allow
+            | PermissionDecision::AllowRead
+            | PermissionDecision::AllowWrite => Ok(false),
+            PermissionDecision::Deny => Err(Error::Tool(format!(
+                "permission policy denies '{tool}' for agent '{agent}'"
+            ))),
+            PermissionDecision::Ask if self.config.ask_mode == McpServeAskMode::AllowOnce => {
+                Ok(true)
+            }
+            PermissionDecision::Ask => Err(Error::Tool(format!(
+                "'{tool}' needs approval, which mcp-serve cannot ask for; set its permission to allow or mcp.serve.ask_mode to allow_once"
+            ))),
+        }
+    }
+
+    async fn run_managed_tool(&self, tool: &str, args: Value) -> Result<(String, bool)> {
+        let tools = &self.app.runtime().tools;
+        let approved = self.authorize(&self.agent, tool, &args).await?;
+        let result = if approved {
+            tools
+                .resolve_permission(
+                    self.session_id.clone(),
+                    Some(self.agent.clone()),
+                    tool,
+                    args,
+                    AskResolution::AllowOnce,
+                    self.events.clone(),
+                )
+                .await?
+        } else {
+            tools
+                .execute_tool(
+                    self.session_id.clone(),
+                    Some(self.agent.clone()),
+                    tool,
+                    args,
+                    self.events.clone(),
+                )
+                .await?
+        };
+        let result = result.ok_or_else(|| {
+            Error::Tool(format!(
+                "'{tool}' needs interactive input (such as a sudo password), which mcp-serve cannot provide"
+            ))
+        })?;
+        Ok((result.output, result.success))
+    }
+
+    /// Runs `task` in a fresh session of `agent_name`. Tool calls the agent
+    /// makes are checked as that agent; calls needing approval are resumed
+    /// only when `ask_mode` allows it.
+    async fn run_agent_task(&self, agent_name: &str, args: &Value) -> Result<(String, bool)> {
+        let task = args
+            .get("task")
+            .and_then(Value::as_str)
+            .filter(|task| !task.trim().is_empty())
+            .ok_or_else(|| Error::Tool("missing 'task' argument".to_owned()))?;
+        self.authorize(
+            &self.agent,
+            "sub_agent",
+            &json!({"target_agent": agent_name, "task": task}),
+        )
+        .await?;
+
+        let agent = self.app.runtime().agents.get_agent(Some(agent_name))?;
+        let sessions = self.app.session_manager();
+        let session_id = sessions.create_session(agent_name).await?;
+        // Events are only drained. Whether the turn stopped for approval is
+        // read from the session's pending tool state, which is stored before
+        // `start_turn`/`continue_after_tool` return.
+        let (event_tx, mut event_rx) = mpsc::channel(256);
+        let drain = tokio::spawn(async move { while event_rx.recv().await.is_some() {} });
+
+        let mut run = agent
+            .start_turn(session_id, task.to_owned(), event_tx.clone(), None)
+            .await;
+        let mut approvals = 0;
+        while run.is_ok() {
+            let (tool, tool_args) = match sessions.get_pending_tool(session_id).await {
+                Ok(Some(pending)) => (pending.tool_name, pending.args),
+                Ok(None) => break,
+                Err(err) => {
+                    run = Err(err);
+                    break;
+                }
+            };
+            // Denied calls and sudo prompts also leave pending state; only
+            // calls that would ask can be approved here.
+            let decision = self
+                .app
+                .runtime()
+                .tools
+                .check_permission(
+                    session_id.to_string(),
+                    Some(agent_name.to_owned()),
+                    &tool,
+                    &tool_args,
+                )
+                .await;
+            if decision != PermissionDecision::Ask {
+                run = Err(Error::Tool(format!(
+                    "agent '{agent_name}' stopped: '{tool}' was denied or needs interactive input"
+                )));
+                break;
+            }
+            if self.config.ask_mode != McpServeAskMode::AllowOnce
+                || approvals >= MAX_AGENT_APPROVALS
+            {
+                run = Err(Error::Tool(format!(
+                    "agent '{agent_name}' stopped: '{tool}' needs approval, which mcp-serve cannot ask for"
+                )));
+                break;
+            }
+            approvals += 1;
+            self.app
+                .runtime()
+                .tools
+                .record_permission(
+                    session_id.to_string(),
+                    Some(agent_name.to_owned()),
+                    &tool,
+                    &tool_args,
+                    AskResolution::AllowOnce,
+                )
+                .await;
+            run = agent
+                .continue_after_tool(session_id, event_tx.clone(), None)
+                .await;
+        }
+        drop(event_tx);
+        let _ = drain.await;
+        run?;
+
+        let answer = sessions
+            .get_session_messages(session_id)
+            .await?
+            .into_iter()
+            .rev()
+            .find(|message| message.role == "assistant")
+            .map(|message| message.content)
+            .unwrap_or_default();
+        Ok((answer, true))
+    }
+
+    async fn retrieve(&self, args: &Value) -> Result<(String, bool)> {
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::Tool("missing 'query' argument".to_owned()))?;
+        self.authorize(
+            &self.agent,
+            "code_search",
+            &json!({"operation": RETRIEVAL_TOOL, "query": query}),
+        )
+        .await?;
+
+        let top_k = args
+            .get("top_k")
+            .and_then(Value::as_u64)
+            .map(|top_k| top_k.max(1) as usize)
+            .unwrap_or(self.app.config().retrieval.max_snippets);
+        let min_score = args
+            .get("min_score")
+            .and_then(Value::as_f64)
+            .map(|score| score as f32);
+        let mut filters = serde_json::Map::new();
+        for key in ["path_prefix", "kind"] {
+            if let Some(value) = args.get(key).filter(|value| value.is_string()) {
+                filters.insert(key.to_owned(), value.clone());
+            }
+        }
+        let filters = (!filters.is_empty()).then_some(Value::Object(filters));
+
+        let response = self
+            .app
+            .retrieve_code_context(query, top_k, min_score, filters)
+            .await?;
+        let rendered = json!({
+            "snippets": response.snippets.iter().map(|snippet| json!({
+                "kind": snippet.kind,
+                "file_path": snippet.file_path,
+                "line_start": snippet.line_start,
+                "line_end": snippet.line_end,
+                "score": snippet.score,
+                "content": snippet.content,
+            })).collect::<Vec<_>>(),
+            "symbols": response.symbols.iter().map(|symbol| json!({
+                "symbol": symbol.symbol,
+                "file_path": symbol.file_path,
+                "score": symbol.score,
+                "usage_context": symbol.usage_context,
+            })).collect::<Vec<_>>(),
+            "keyword_hits": response.keyword_hits,
+            "vector_hits": response.vector_hits,
+        });
+        Ok((pretty(&rendered), true))
+    }
+
+    async fn analyze_impact(&self, args: &Value) -> Result<(String, bool)> {
+        let symbol = args
+            .get("symbol")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::Tool("missing 'symbol' argument".to_owned()))?;
+        self.authorize(
+            &self.agent,
+            "code_search",
+            &json!({"operation": IMPACT_TOOL, "symbol": symbol}),
+        )
+        .await?;
+
+        let depth = args
+            .get("depth")
+            .and_then(Value::as_u64)
+            .map(|depth| depth.max(1) as usize)
+            .unwrap_or(2);
+        let report = self.app.analyze_symbol_impact(symbol, depth).await?;
+        Ok((pretty(&serde_json::to_value(&report)?), true))
+    }
+}
+
+fn tool_entry(name: &str, description: &str, input_schema: Value) -> Value {
+    json!({"name": name, "description": description, "inputSchema": input_schema})
+}
+
+fn tool_result(text: &str, is_error: bool) -> Value {
+    json!({"content": [{"type": "text", "text": text}], "isError": is_error})
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Browser origins may only reach the endpoint from the local machine.
+fn is_local_origin(origin: &str) -> bool {
+    let authority = origin
+        .split("://")
+        .nth(1)
+        .unwrap_or(origin)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let host = if authority.starts_with("[::1]") {
+        "[::1]"
+    } else {
+        authority.split(':').next().unwrap_or_default()
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn http_response(status_code: u16, session: Option<&str>, body: &str) -> String {
+    let status = match status_code {
+        200 => "200 OK",
+        202 => "202 Accepted",
+        400 => "400 Bad Request",
+        403 => "403 Forbidden",
+        404 => "404 Not Found",
+        405 => "405 Method Not Allowed",
+        413 => "413 Payload Too Large",
+        _ => "500 Internal Server Error",
+    };
+    let session = session
+        .map(|session| format!("Mcp-Session-Id: {session}\r\n"))
+        .unwrap_or_default();
+    let content_type = if body.is_empty() {
+        ""
+    } else {
+        "Content-Type: application/json\r\n"
+    };
+    format!(
+        "HTTP/1.1 {status}\r\n{content_type}{session}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
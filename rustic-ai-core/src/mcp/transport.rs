@@ -0,0 +1,415 @@
+//! Wire formats for MCP: stdio (newline-delimited or `Content-Length`
+//! framed) and Streamable HTTP.
+//!
+//! Transports only move JSON-RPC messages. Everything they receive is passed
+//! to an [`Inbound`] sink, which the client uses to route responses, answer
+//! server requests and forward notifications.
+
+use crate::config::schema::McpServerConfig;
+use crate::error::{Error, Result};
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde_json::Value;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::ChildStdin;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+/// Cap on a single stdio line or SSE event.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Receives every message a transport reads.
+pub(crate) trait Inbound: Send + Sync + 'static {
+    fn receive(self: Arc<Self>, message: Value);
+    /// A request could not be delivered (HTTP error status, network failure).
+    fn fail(self: Arc<Self>, id: i64, error: Error);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StdioFraming {
+    Newline,
+    ContentLength,
+}
+
+pub(crate) enum Transport {
+    Stdio {
+        stdin: Mutex<Option<ChildStdin>>,
+        framing: StdioFraming,
+    },
+    Http(HttpTransport),
+}
+
+impl Transport {
+    /// Sends one message. HTTP responses are read in the background and
+    /// delivered to `inbound`.
+    pub(crate) async fn send<I: Inbound>(
+        &self,
+        message: &Value,
+        inbound: &Arc<I>,
+        closed: &CancellationToken,
+    ) -> Result<()> {
+        match self {
+            Self::Stdio { stdin, framing } => {
+                let mut stdin = stdin.lock().await;
+                write_stdio(stdin.as_mut(), *framing, message).await
+            }
+            Self::Http(http) => http.post(message, inbound, closed).await,
+        }
+    }
+
+    pub(crate) async fn close(&self) {
+        match self {
+            Self::Stdio { stdin, .. } => {
+                stdin.lock().await.take();
+            }
+            Self::Http(http) => http.delete_session().await,
+        }
+    }
+}
+
+async fn write_stdio<W: AsyncWrite + Unpin>(
+    writer: Option<&mut W>,
+    framing: StdioFraming,
+    message: &Value,
+) -> Result<()> {
+    let writer = writer.ok_or_else(|| Error::Tool("mcp connection is closed".to_owned()))?;
+    let payload = serde_json::to_vec(message)
+        .map_err(|err| Error::Tool(format!("failed to serialize mcp message: {err}")))?;
+    if framing == StdioFraming::ContentLength {
+        let header = format!("Content-Length: {}\r\n\r\n", payload.len());
+        writer
+            .write_all(header.as_bytes())
+            .await
+            .map_err(|err| Error::Tool(format!("failed writing mcp header: {err}")))?;
+    }
+    writer
+        .write_all(&payload)
+        .await
+        .map_err(|err| Error::Tool(format!("failed writing mcp payload: {err}")))?;
+    if framing == StdioFraming::Newline {
+        writer
+            .write_all(b"\n")
+            .await
+            .map_err(|err| Error::Tool(format!("failed writing mcp payload: {err}")))?;
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|err| Error::Tool(format!("failed flushing mcp payload: {err}")))
+}
+
+/// Reads one message from a server's stdout; `None` at EOF.
+pub(crate) async fn read_stdio<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    framing: StdioFraming,
+) -> Result<Option<Value>> {
+    match framing {
+        StdioFraming::Newline => loop {
+            let mut line = String::new();
+            let read = reader
+                .read_line(&mut line)
+                .await
+                .map_err(|err| Error::Tool(format!("failed reading mcp message: {err}")))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            return serde_json::from_str::<Value>(trimmed)
+                .map(Some)
+                .map_err(|err| Error::Tool(format!("failed parsing mcp message json: {err}")));
+        },
+        StdioFraming::ContentLength => {
+            let mut content_length: Option<usize> = None;
+            loop {
+                let mut line = String::new();
+                let read = reader
+                    .read_line(&mut line)
+                    .await
+                    .map_err(|err| Error::Tool(format!("failed reading mcp header: {err}")))?;
+                if read == 0 {
+                    return Ok(None);
+                }
+                let trimmed = line.trim_end_matches(['\r', '\n']);
+                if trimmed.is_empty() {
+                    if content_length.is_some() {
+                        break;
+                    }
+                    continue;
+                }
+                let Some((name, value)) = trimmed.split_once(':') else {
+                    continue;
+                };
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = Some(value.trim().parse::<usize>().map_err(|err| {
+                        Error::Tool(format!("invalid mcp Content-Length header: {err}"))
+                    })?);
+                }
+            }
+
+            let len = content_length
+                .filter(|len| *len <= MAX_MESSAGE_BYTES)
+                .ok_or_else(|| Error::Tool("missing or oversized mcp Content-Length".to_owned()))?;
+            let mut body = vec![0u8; len];
+            reader
+                .read_exact(&mut body)
+                .await
+                .map_err(|err| Error::Tool(format!("failed reading mcp message body: {err}")))?;
+            serde_json::from_slice::<Value>(&body)
+                .map(Some)
+                .map_err(|err| Error::Tool(format!("failed parsing mcp message json: {err}")))
+        }
+    }
+}
+
+/// Streamable HTTP client side: every message is POSTed to one endpoint and
+/// the server answers with JSON, an SSE stream or `202 Accepted`.
+pub(crate) struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HeaderMap,
+    session_id: StdMutex<Option<String>>,
+    protocol_version: StdMutex<Option<String>>,
+}
+
+impl HttpTransport {
+    pub(crate) fn new(server: &McpServerConfig) -> Result<Self> {
+        let url = server
+            .url
+            .clone()
+            .ok_or_else(|| Error::Config(format!("mcp server '{}' has no url", server.name)))?;
+        let mut headers = HeaderMap::new();
+        let env_headers = server
+            .headers_env
+            .iter()
+            .map(|(name, var)| {
+                std::env::var(var)
+                    .map(|value| (name.clone(), value))
+                    .map_err(|_| {
+                        Error::Config(format!(
+                            "mcp server '{}' header '{name}' requires env var '{var}' to be set",
+                            server.name
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (name, value) in server.headers.clone().into_iter().chain(env_headers) {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|err| Error::Config(format!("invalid mcp header name '{name}': {err}")))?;
+            let value = HeaderValue::from_str(&value).map_err(|err| {
+                Error::Config(format!("invalid value for mcp header '{name}': {err}"))
+            })?;
+            headers.insert(name, value);
+        }
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|err| Error::Tool(format!("failed to build mcp http client: {err}")))?;
+        Ok(Self {
+            client,
+            url,
+            headers,
+            session_id: StdMutex::new(None),
+            protocol_version: StdMutex::new(None),
+        })
+    }
+
+    pub(crate) fn set_protocol_version(&self, version: &str) {
+        if let Ok(mut current) = self.protocol_version.lock() {
+            *current = Some(version.to_owned());
+        }
+    }
+
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        let session = self.session_id.lock().ok().and_then(|id| id.clone());
+        if let Some(value) = session.and_then(|id| HeaderValue::from_str(&id).ok()) {
+            headers.insert(SESSION_HEADER, value);
+        }
+        let version = self.protocol_version.lock().ok().and_then(|v| v.clone());
+        if let Some(value) = version.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            headers.insert(PROTOCOL_VERSION_HEADER, value);
+        }
+        headers
+    }
+
+    async fn post<I: Inbound>(
+        &self,
+        message: &Value,
+        inbound: &Arc<I>,
+        closed: &CancellationToken,
+    ) -> Result<()> {
+        let request_id = message
+            .get("method")
+            .and(message.get("id"))
+            .and_then(Value::as_i64);
+        let response = self
+            .client
+            .post(&self.url)
+            .headers(self.request_headers())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .json(message)
+            .send()
+            .await
+            .map_err(|err| {
+                Error::Tool(format!("mcp http request to {} failed: {err}", self.url))
+            })?;
+
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            if let Ok(mut current) = self.session_id.lock() {
+                *current = Some(session.to_owned());
+            }
+        }
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && self.has_session() {
+            // The server dropped our session; the supervisor reconnects.
+            closed.cancel();
+            return Err(Error::Tool("mcp http session expired".to_owned()));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let snippet = body.chars().take(300).collect::<String>();
+            return Err(Error::Tool(format!(
+                "mcp http request returned {status}: {snippet}"
+            )));
+        }
+        if status == reqwest::StatusCode::ACCEPTED || request_id.is_none() {
+            return Ok(());
+        }
+
+        // Read the answer in the background so server requests that arrive on
+        // the stream can be handled while the caller waits for its response.
+        let is_sse = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let inbound = inbound.clone();
+        tokio::spawn(async move {
+            let outcome = if is_sse {
+                read_sse(response, &inbound).await
+            } else {
+                match response.json::<Value>().await {
+                    Ok(body) => {
+                        deliver(&inbound, body);
+                        Ok(())
+                    }
+                    Err(err) => Err(Error::Tool(format!(
+                        "failed parsing mcp http response: {err}"
+                    ))),
+                }
+            };
+            if let (Err(err), Some(id)) = (outcome, request_id) {
+                inbound.fail(id, err);
+            }
+        });
+        Ok(())
+    }
+
+    fn has_session(&self) -> bool {
+        self.session_id.lock().is_ok_and(|id| id.is_some())
+    }
+
+    /// Opens the optional GET stream for server-initiated messages. Servers
+    /// that do not offer one answer 405, which is not an error.
+    pub(crate) async fn listen<I: Inbound>(&self, inbound: Arc<I>) {
+        let response = self
+            .client
+            .get(&self.url)
+            .headers(self.request_headers())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => {
+                if let Err(err) = read_sse(response, &inbound).await {
+                    tracing::debug!(%err, "mcp http event stream ended");
+                }
+            }
+            Ok(_) => {}
+            Err(err) => tracing::debug!(%err, "mcp http event stream unavailable"),
+        }
+    }
+
+    async fn delete_session(&self) {
+        if !self.has_session() {
+            return;
+        }
+        let _ = self
+            .client
+            .delete(&self.url)
+            .headers(self.request_headers())
+            .send()
+            .await;
+    }
+}
+
+fn deliver<I: Inbound>(inbound: &Arc<I>, message: Value) {
+    match message {
+        Value::Array(batch) => {
+            for message in batch {
+                inbound.clone().receive(message);
+            }
+        }
+        message => inbound.clone().receive(message),
+    }
+}
+
+/// Delivers the JSON payload of every SSE event in `response`.
+async fn read_sse<I: Inbound>(response: reqwest::Response, inbound: &Arc<I>) -> Result<()> {
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::<u8>::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| Error::Tool(format!("mcp event stream failed: {err}")))?;
+        buffer.extend_from_slice(&chunk);
+        while let Some((end, skip)) = event_boundary(&buffer) {
+            let event = String::from_utf8_lossy(&buffer[..end]).into_owned();
+            buffer.drain(..end + skip);
+            if let Some(message) = sse_data(&event) {
+                deliver(inbound, message);
+            }
+        }
+        if buffer.len() > MAX_MESSAGE_BYTES {
+            return Err(Error::Tool("mcp event exceeds size limit".to_owned()));
+        }
+    }
+    if let Some(message) = sse_data(&String::from_utf8_lossy(&buffer)) {
+        deliver(inbound, message);
+    }
+    Ok(())
+}
+
+/// Position and length of the first blank line separating SSE events.
+fn event_boundary(buffer: &[u8]) -> Option<(usize, usize)> {
+    let lf = buffer.windows(2).position(|window| window == b"\n\n");
+    let crlf = buffer.windows(4).position(|window| window == b"\r\n\r\n");
+    match (lf, crlf) {
+        (Some(lf), Some(crlf)) if crlf < lf => Some((crlf, 4)),
+        (Some(lf), _) => Some((lf, 2)),
+        (None, Some(crlf)) => Some((crlf, 4)),
+        (None, None) => None,
+    }
+}
+
+fn sse_data(event: &str) -> Option<Value> {
+    let data = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if data.trim().is_empty() {
+        return None;
+    }
+    serde_json::from_str(&data).ok()
+}
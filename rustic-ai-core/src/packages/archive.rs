@@ -0,0 +1,242 @@
+//! Reproducible `.tar.gz` package archives.
+
+use super::manifest::{PackageManifest, PACKAGE_MANIFEST_FILE};
+use crate::error::{Error, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Component, Path};
+
+/// Upper bound on the unpacked size of one package.
+const MAX_UNPACKED_BYTES: u64 = 256 * 1024 * 1024;
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn read_manifest_file(dir: &Path) -> Result<PackageManifest> {
+    let path = dir.join(PACKAGE_MANIFEST_FILE);
+    let raw = std::fs::read_to_string(&path).map_err(|err| {
+        Error::Config(format!(
+            "failed reading package manifest '{}': {err}",
+            path.display()
+        ))
+    })?;
+    let manifest: PackageManifest = serde_json::from_str(&raw).map_err(|err| {
+        Error::Config(format!(
+            "failed parsing package manifest '{}': {err}",
+            path.display()
+        ))
+    })?;
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+/// Packs a package directory. Hidden and `.gitignore`d files are left out.
+/// Entries are sorted and timestamps and owners zeroed, so the same files
+/// always give the same sha256.
+pub fn pack_dir(dir: &Path) -> Result<(PackageManifest, Vec<u8>)> {
+    let manifest = read_manifest_file(dir)?;
+    let mut files = Vec::new();
+    let walker = ignore::WalkBuilder::new(dir)
+        .require_git(false)
+        .follow_links(false)
+        .build();
+    for entry in walker {
+        let entry = entry.map_err(|err| Error::Tool(format!("failed to walk package: {err}")))?;
+        let path = entry.path();
+        let metadata = path
+            .symlink_metadata()
+            .map_err(|err| Error::Tool(format!("failed to read '{}': {err}", path.display())))?;
+        if metadata.file_type().is_symlink() {
+            return Err(Error::Tool(format!(
+                "package files cannot be symlinks: '{}'",
+                path.display()
+            )));
+        }
+        if !metadata.is_file() {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((name, path.to_path_buf(), metadata));
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let mut total = 0u64;
+    for (name, path, metadata) in files {
+        total += metadata.len();
+        if total > MAX_UNPACKED_BYTES {
+            return Err(Error::Tool(format!(
+                "package exceeds {} MiB",
+                MAX_UNPACKED_BYTES / (1024 * 1024)
+            )));
+        }
+        let data = std::fs::read(&path)
+            .map_err(|err| Error::Tool(format!("failed to read '{}': {err}", path.display())))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(if is_executable(&metadata) {
+            0o755
+        } else {
+            0o644
+        });
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        builder
+            .append_data(&mut header, &name, data.as_slice())
+            .map_err(|err| Error::Tool(format!("failed to add '{name}' to package: {err}")))?;
+    }
+    let bytes = builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|err| Error::Tool(format!("failed to finish package archive: {err}")))?;
+    Ok((manifest, bytes))
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Relative `/`-separated path of an archive entry, or an error for
+/// absolute paths, `..` and anything other than files and directories.
+fn entry_name<R: Read>(entry: &tar::Entry<'_, R>) -> Result<Option<String>> {
+    let entry_type = entry.header().entry_type();
+    if !(entry_type.is_file() || entry_type.is_dir()) {
+        return Err(Error::Validation(
+            "package archives may only contain files and directories".to_owned(),
+        ));
+    }
+    let path = entry
+        .path()
+        .map_err(|err| Error::Validation(format!("invalid path in package archive: {err}")))?;
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => {
+                return Err(Error::Validation(format!(
+                    "unsafe path '{}' in package archive",
+                    path.display()
+                )));
+            }
+        }
+    }
+    if parts.is_empty() || entry_type.is_dir() {
+        return Ok(None);
+    }
+    Ok(Some(parts.join("/")))
+}
+
+/// A package archive that has been read and checked, but not unpacked.
+pub struct PackageArchive {
+    pub manifest: PackageManifest,
+    /// Files other than the manifest, as relative `/`-separated paths.
+    pub files: BTreeSet<String>,
+    bytes: Vec<u8>,
+}
+
+impl PackageArchive {
+    pub fn read(bytes: Vec<u8>) -> Result<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(bytes.as_slice()));
+        let mut manifest = None;
+        let mut files = BTreeSet::new();
+        let mut total = 0u64;
+        let entries = archive
+            .entries()
+            .map_err(|err| Error::Validation(format!("invalid package archive: {err}")))?;
+        for entry in entries {
+            let mut entry = entry
+                .map_err(|err| Error::Validation(format!("invalid package archive: {err}")))?;
+            let Some(name) = entry_name(&entry)? else {
+                continue;
+            };
+            total += entry.size();
+            if total > MAX_UNPACKED_BYTES {
+                return Err(Error::Validation(format!(
+                    "package exceeds {} MiB unpacked",
+                    MAX_UNPACKED_BYTES / (1024 * 1024)
+                )));
+            }
+            if name == PACKAGE_MANIFEST_FILE {
+                let mut raw = String::new();
+                entry.read_to_string(&mut raw).map_err(|err| {
+                    Error::Validation(format!("failed reading package manifest: {err}"))
+                })?;
+                let parsed: PackageManifest = serde_json::from_str(&raw).map_err(|err| {
+                    Error::Validation(format!("failed parsing package manifest: {err}"))
+                })?;
+                manifest = Some(parsed);
+            } else {
+                files.insert(name);
+            }
+        }
+        let manifest = manifest.ok_or_else(|| {
+            Error::Validation(format!(
+                "package archive has no {PACKAGE_MANIFEST_FILE} at its root"
+            ))
+        })?;
+        manifest.validate()?;
+        Ok(Self {
+            manifest,
+            files,
+            bytes,
+        })
+    }
+
+    pub fn sha256(&self) -> String {
+        sha256_hex(&self.bytes)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Unpacks everything except the manifest into `dest`, which must not
+    /// exist yet.
+    pub fn unpack(&self, dest: &Path) -> Result<()> {
+        std::fs::create_dir_all(dest)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(self.bytes.as_slice()));
+        archive.set_preserve_mtime(false);
+        let entries = archive
+            .entries()
+            .map_err(|err| Error::Validation(format!("invalid package archive: {err}")))?;
+        for entry in entries {
+            let mut entry = entry
+                .map_err(|err| Error::Validation(format!("invalid package archive: {err}")))?;
+            match entry_name(&entry)? {
+                Some(name) if name != PACKAGE_MANIFEST_FILE => {}
+                _ => continue,
+            }
+            entry.unpack_in(dest).map_err(|err| {
+                Error::Tool(format!(
+                    "failed unpacking package into '{}': {err}",
+                    dest.display()
+                ))
+            })?;
+        }
+        Ok(())
+    }
+}
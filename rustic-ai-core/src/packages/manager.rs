@@ -0,0 +1,700 @@
+//! Installs, updates and removes skill and plugin packages in the global or
+//! project scope, recording each scope's packages in a lockfile.
+
+use super::archive::{pack_dir, PackageArchive};
+use super::manifest::{
+    parse_requirement, parse_version, IndexEntry, LockedPackage, Lockfile, PackageIndex,
+    PackageKind, PackageManifest, PackageSource, INDEX_FILE_NAME, LOCKFILE_NAME,
+};
+use crate::config::Config;
+use crate::error::{Error, Result};
+use semver::{Version, VersionReq};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// File that marks a skill package as an Agent Skills bundle.
+const SKILL_FILE_NAME: &str = "SKILL.md";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageScope {
+    Global,
+    Project,
+}
+
+impl PackageScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Project => "project",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstalledPackage {
+    pub scope: PackageScope,
+    pub package: LockedPackage,
+    pub path: PathBuf,
+    /// False when the lockfile lists the package but its directory is gone.
+    pub present: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageUpdate {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackedPackage {
+    pub manifest: PackageManifest,
+    pub archive: PathBuf,
+    pub sha256: String,
+}
+
+pub struct PackageManager {
+    global_root: PathBuf,
+    project_root: PathBuf,
+    indexes: Vec<PathBuf>,
+    plugin_manifest_file: String,
+}
+
+fn resolve_path(raw: &str, base: &Path) -> PathBuf {
+    if let Some(rest) = raw.strip_prefix("~/") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    let path = Path::new(raw);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+impl PackageManager {
+    pub fn new(config: &Config, work_dir: &Path) -> Self {
+        let indexes = config
+            .packages
+            .indexes
+            .iter()
+            .map(|raw| {
+                let path = resolve_path(raw, work_dir);
+                if path.is_dir() {
+                    path.join(INDEX_FILE_NAME)
+                } else {
+                    path
+                }
+            })
+            .collect();
+        Self {
+            global_root: resolve_path(&config.packages.global_root, work_dir),
+            project_root: resolve_path(&config.packages.project_root, work_dir),
+            indexes,
+            plugin_manifest_file: config.plugins.manifest_file_name.clone(),
+        }
+    }
+
+    pub fn root(&self, scope: PackageScope) -> &Path {
+        match scope {
+            PackageScope::Global => &self.global_root,
+            PackageScope::Project => &self.project_root,
+        }
+    }
+
+    pub fn install_dir(&self, scope: PackageScope, kind: PackageKind, name: &str) -> PathBuf {
+        self.root(scope).join(kind.dir_name()).join(name)
+    }
+
+    fn lockfile_path(&self, scope: PackageScope) -> PathBuf {
+        self.root(scope).join(LOCKFILE_NAME)
+    }
+
+    pub fn lockfile(&self, scope: PackageScope) -> Result<Lockfile> {
+        let path = self.lockfile_path(scope);
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let raw = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&raw).map_err(|err| {
+            Error::Config(format!(
+                "failed parsing lockfile '{}': {err}",
+                path.display()
+            ))
+        })
+    }
+
+    fn save_lockfile(&self, scope: PackageScope, lock: &Lockfile) -> Result<()> {
+        let path = self.lockfile_path(scope);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, format!("{}\n", serde_json::to_string_pretty(lock)?))?;
+        Ok(())
+    }
+
+    fn load_index(path: &Path) -> Result<PackageIndex> {
+        let raw = std::fs::read_to_string(path).map_err(|err| {
+            Error::Config(format!(
+                "failed reading package index '{}': {err}",
+                path.display()
+            ))
+        })?;
+        serde_json::from_str(&raw).map_err(|err| {
+            Error::Config(format!(
+                "failed parsing package index '{}': {err}",
+                path.display()
+            ))
+        })
+    }
+
+    /// The highest indexed version of `name` that satisfies `requirement`.
+    fn resolve(&self, name: &str, requirement: &VersionReq) -> Result<(PathBuf, IndexEntry)> {
+        if self.indexes.is_empty() {
+            return Err(Error::NotFound(format!(
+                "package '{name}' is not a local path and no packages.indexes are configured"
+            )));
+        }
+        let mut best: Option<(Version, PathBuf, IndexEntry)> = None;
+        for index_path in &self.indexes {
+            for entry in Self::load_index(index_path)?.packages {
+                if entry.name != name {
+                    continue;
+                }
+                let version = parse_version(&entry.name, &entry.version)?;
+                if !requirement.matches(&version) {
+                    continue;
+                }
+                if best
+                    .as_ref()
+                    .is_none_or(|(current, _, _)| version > *current)
+                {
+                    best = Some((version, index_path.clone(), entry));
+                }
+            }
+        }
+        best.map(|(_, index, entry)| (index, entry)).ok_or_else(|| {
+            Error::NotFound(format!(
+                "no indexed version of '{name}' matches '{requirement}'"
+            ))
+        })
+    }
+
+    /// Reads an indexed archive and checks it against the index entry.
+    fn fetch_indexed(index_path: &Path, entry: &IndexEntry) -> Result<PackageArchive> {
+        let base = index_path.parent().unwrap_or_else(|| Path::new("."));
+        let archive_path = resolve_path(&entry.archive, base);
+        let bytes = std::fs::read(&archive_path).map_err(|err| {
+            Error::NotFound(format!(
+                "failed reading package archive '{}': {err}",
+                archive_path.display()
+            ))
+        })?;
+        let archive = PackageArchive::read(bytes)?;
+        let sha256 = archive.sha256();
+        if !sha256.eq_ignore_ascii_case(entry.sha256.trim()) {
+            return Err(Error::Validation(format!(
+                "checksum mismatch for {} {}: index has {}, archive is {sha256}",
+                entry.name, entry.version, entry.sha256
+            )));
+        }
+        let manifest = &archive.manifest;
+        if manifest.name != entry.name
+            || manifest.version != entry.version
+            || manifest.kind != entry.kind
+        {
+            return Err(Error::Validation(format!(
+                "archive '{}' contains {} {} {}, but the index lists {} {} {}",
+                archive_path.display(),
+                manifest.kind.as_str(),
+                manifest.name,
+                manifest.version,
+                entry.kind.as_str(),
+                entry.name,
+                entry.version
+            )));
+        }
+        Ok(archive)
+    }
+
+    /// Reads a package archive, or packs a package directory.
+    fn fetch_path(path: &Path) -> Result<PackageArchive> {
+        let bytes = if path.is_dir() {
+            pack_dir(path)?.1
+        } else {
+            std::fs::read(path).map_err(|err| {
+                Error::NotFound(format!(
+                    "failed reading package archive '{}': {err}",
+                    path.display()
+                ))
+            })?
+        };
+        PackageArchive::read(bytes)
+    }
+
+    fn fetch_locked(&self, locked: &LockedPackage) -> Result<PackageArchive> {
+        let archive = match &locked.source {
+            PackageSource::Path { path } => Self::fetch_path(Path::new(path))?,
+            PackageSource::Index { index } => {
+                let exact = parse_requirement(&locked.name, &format!("={}", locked.version))?;
+                let recorded = PathBuf::from(index);
+                let indexed = Self::load_index(&recorded).ok().and_then(|parsed| {
+                    parsed
+                        .packages
+                        .into_iter()
+                        .find(|entry| entry.name == locked.name && entry.version == locked.version)
+                });
+                match indexed {
+                    Some(entry) => Self::fetch_indexed(&recorded, &entry)?,
+                    None => {
+                        let (index, entry) = self.resolve(&locked.name, &exact)?;
+                        Self::fetch_indexed(&index, &entry)?
+                    }
+                }
+            }
+        };
+        let sha256 = archive.sha256();
+        if !sha256.eq_ignore_ascii_case(&locked.sha256) {
+            return Err(Error::Validation(format!(
+                "checksum mismatch for {} {}: lockfile has {}, archive is {sha256}",
+                locked.name, locked.version, locked.sha256
+            )));
+        }
+        Ok(archive)
+    }
+
+    /// Skills must be `SKILL.md` bundles and plugins must carry a plugin
+    /// manifest at their root.
+    fn check_layout(&self, archive: &PackageArchive) -> Result<()> {
+        let (required, kind) = match archive.manifest.kind {
+            PackageKind::Skill => (SKILL_FILE_NAME, "skill"),
+            PackageKind::Plugin => (self.plugin_manifest_file.as_str(), "plugin"),
+        };
+        if archive.files.contains(required) {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!(
+                "{kind} package '{}' has no {required} at its root",
+                archive.manifest.name
+            )))
+        }
+    }
+
+    /// Replaces the package directory with the archive contents.
+    fn place(&self, scope: PackageScope, archive: &PackageArchive) -> Result<PathBuf> {
+        let manifest = &archive.manifest;
+        let dest = self.install_dir(scope, manifest.kind, &manifest.name);
+        let staging = dest.with_file_name(format!(".{}.installing", manifest.name));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        if let Err(err) = archive.unpack(&staging) {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(err);
+        }
+        if dest.exists() {
+            std::fs::remove_dir_all(&dest)?;
+        }
+        std::fs::rename(&staging, &dest)?;
+        Ok(dest)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn install_archive(
+        &self,
+        scope: PackageScope,
+        lock: &mut Lockfile,
+        archive: PackageArchive,
+        source: PackageSource,
+        requirement: String,
+        visiting: &mut Vec<String>,
+        installed: &mut Vec<LockedPackage>,
+    ) -> Result<()> {
+        self.check_layout(&archive)?;
+        let manifest = archive.manifest.clone();
+        if let Some(existing) = lock.get(&manifest.name) {
+            if existing.kind != manifest.kind {
+                return Err(Error::Validation(format!(
+                    "'{}' is already installed as a {} package",
+                    manifest.name,
+                    existing.kind.as_str()
+                )));
+            }
+        }
+        if visiting.contains(&manifest.name) {
+            return Err(Error::Validation(format!(
+                "dependency cycle: {} -> {}",
+                visiting.join(" -> "),
+                manifest.name
+            )));
+        }
+        visiting.push(manifest.name.clone());
+        for (dependency, requirement) in &manifest.dependencies {
+            self.ensure_dependency(scope, lock, dependency, requirement, visiting, installed)?;
+        }
+        visiting.pop();
+
+        self.place(scope, &archive)?;
+        let locked = LockedPackage {
+            name: manifest.name,
+            kind: manifest.kind,
+            version: manifest.version,
+            requirement,
+            sha256: archive.sha256(),
+            source,
+            dependencies: manifest.dependencies,
+        };
+        lock.upsert(locked.clone());
+        installed.push(locked);
+        Ok(())
+    }
+
+    fn ensure_dependency(
+        &self,
+        scope: PackageScope,
+        lock: &mut Lockfile,
+        name: &str,
+        requirement: &str,
+        visiting: &mut Vec<String>,
+        installed: &mut Vec<LockedPackage>,
+    ) -> Result<()> {
+        let parsed = parse_requirement(name, requirement)?;
+        if let Some(existing) = lock.get(name) {
+            let version = parse_version(name, &existing.version)?;
+            if parsed.matches(&version) {
+                return Ok(());
+            }
+            return Err(Error::Validation(format!(
+                "{name} {version} is installed, but a dependency requires '{requirement}'"
+            )));
+        }
+        let (index, entry) = self.resolve(name, &parsed)?;
+        let archive = Self::fetch_indexed(&index, &entry)?;
+        self.install_archive(
+            scope,
+            lock,
+            archive,
+            PackageSource::Index {
+                index: index.display().to_string(),
+            },
+            requirement.to_owned(),
+            visiting,
+            installed,
+        )
+    }
+
+    /// Installs each spec, which is an archive path, a package directory or
+    /// `name[@requirement]` from the indexes, together with missing
+    /// dependencies. Returns every package that was installed.
+    pub fn install(
+        &self,
+        scope: PackageScope,
+        kind: PackageKind,
+        specs: &[String],
+        force: bool,
+    ) -> Result<Vec<LockedPackage>> {
+        let mut lock = self.lockfile(scope)?;
+        let mut installed = Vec::new();
+        let result = specs.iter().try_for_each(|spec| {
+            let path = Path::new(spec);
+            let (archive, source, requirement) = if path.exists() {
+                let path = std::fs::canonicalize(path)?;
+                let archive = Self::fetch_path(&path)?;
+                let source = PackageSource::Path {
+                    path: path.display().to_string(),
+                };
+                (archive, source, None)
+            } else {
+                let (name, requirement) = match spec.split_once('@') {
+                    Some((name, requirement)) => (name, Some(requirement.to_owned())),
+                    None => (spec.as_str(), None),
+                };
+                let parsed = parse_requirement(name, requirement.as_deref().unwrap_or("*"))?;
+                let (index, entry) = self.resolve(name, &parsed)?;
+                let archive = Self::fetch_indexed(&index, &entry)?;
+                let source = PackageSource::Index {
+                    index: index.display().to_string(),
+                };
+                (archive, source, requirement)
+            };
+
+            let manifest = &archive.manifest;
+            if manifest.kind != kind {
+                return Err(Error::Validation(format!(
+                    "'{}' is a {} package; use `rustic-ai {} install`",
+                    manifest.name,
+                    manifest.kind.as_str(),
+                    manifest.kind.dir_name()
+                )));
+            }
+            let unchanged = lock
+                .get(&manifest.name)
+                .is_some_and(|locked| locked.sha256 == archive.sha256());
+            if unchanged && !force && self.install_dir(scope, kind, &manifest.name).exists() {
+                return Ok(());
+            }
+            // Without an explicit requirement, updates stay on the same major version.
+            let requirement = requirement.unwrap_or_else(|| format!("^{}", manifest.version));
+            self.install_archive(
+                scope,
+                &mut lock,
+                archive,
+                source,
+                requirement,
+                &mut Vec::new(),
+                &mut installed,
+            )
+        });
+        // Record whatever was installed before a failure.
+        self.save_lockfile(scope, &lock)?;
+        result.map(|()| installed)
+    }
+
+    /// Installs the lockfile's packages of `kind`, and the packages they
+    /// depend on, at their locked versions. Existing directories are kept
+    /// unless `force` is set.
+    pub fn sync(
+        &self,
+        scope: PackageScope,
+        kind: PackageKind,
+        force: bool,
+    ) -> Result<Vec<LockedPackage>> {
+        let lock = self.lockfile(scope)?;
+        let mut wanted = BTreeSet::new();
+        let mut pending = lock
+            .packages
+            .iter()
+            .filter(|package| package.kind == kind)
+            .map(|package| package.name.clone())
+            .collect::<Vec<_>>();
+        while let Some(name) = pending.pop() {
+            if !wanted.insert(name.clone()) {
+                continue;
+            }
+            let Some(locked) = lock.get(&name) else {
+                return Err(Error::Config(format!(
+                    "lockfile is missing dependency '{name}'"
+                )));
+            };
+            pending.extend(locked.dependencies.keys().cloned());
+        }
+
+        let mut installed = Vec::new();
+        for name in wanted {
+            let Some(locked) = lock.get(&name) else {
+                continue;
+            };
+            if !force && self.install_dir(scope, locked.kind, &name).exists() {
+                continue;
+            }
+            let archive = self.fetch_locked(locked)?;
+            self.check_layout(&archive)?;
+            self.place(scope, &archive)?;
+            installed.push(locked.clone());
+        }
+        Ok(installed)
+    }
+
+    /// Moves packages of `kind` (or just `name`) to the newest version their
+    /// lockfile requirement allows. Path packages are reinstalled when their
+    /// contents changed.
+    pub fn update(
+        &self,
+        scope: PackageScope,
+        kind: PackageKind,
+        name: Option<&str>,
+    ) -> Result<Vec<PackageUpdate>> {
+        let mut lock = self.lockfile(scope)?;
+        let candidates = lock
+            .packages
+            .iter()
+            .filter(|package| package.kind == kind && name.is_none_or(|name| package.name == name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if let (Some(name), true) = (name, candidates.is_empty()) {
+            return Err(Error::NotFound(format!(
+                "no {} package '{name}' is installed in the {} scope",
+                kind.as_str(),
+                scope.as_str()
+            )));
+        }
+
+        let mut updates = Vec::new();
+        let result = candidates.into_iter().try_for_each(|locked| {
+            let requirement = parse_requirement(&locked.name, &locked.requirement)?;
+            let current = parse_version(&locked.name, &locked.version)?;
+            let (archive, source) = match &locked.source {
+                PackageSource::Index { .. } => {
+                    let (index, entry) = self.resolve(&locked.name, &requirement)?;
+                    if parse_version(&entry.name, &entry.version)? <= current {
+                        return Ok(());
+                    }
+                    let source = PackageSource::Index {
+                        index: index.display().to_string(),
+                    };
+                    (Self::fetch_indexed(&index, &entry)?, source)
+                }
+                PackageSource::Path { path } => {
+                    let archive = Self::fetch_path(Path::new(path))?;
+                    if archive.sha256() == locked.sha256 {
+                        return Ok(());
+                    }
+                    let version = parse_version(&archive.manifest.name, &archive.manifest.version)?;
+                    if !requirement.matches(&version) {
+                        return Err(Error::Validation(format!(
+                            "'{path}' now contains {} {version}, outside '{}'",
+                            locked.name, locked.requirement
+                        )));
+                    }
+                    (archive, locked.source.clone())
+                }
+            };
+            let to = archive.manifest.version.clone();
+            self.install_archive(
+                scope,
+                &mut lock,
+                archive,
+                source,
+                locked.requirement.clone(),
+                &mut Vec::new(),
+                &mut Vec::new(),
+            )?;
+            updates.push(PackageUpdate {
+                name: locked.name,
+                from: locked.version,
+                to,
+            });
+            Ok(())
+        });
+        self.save_lockfile(scope, &lock)?;
+        result.map(|()| updates)
+    }
+
+    /// Removes a package unless another installed package depends on it.
+    pub fn remove(
+        &self,
+        scope: PackageScope,
+        kind: PackageKind,
+        name: &str,
+    ) -> Result<LockedPackage> {
+        let mut lock = self.lockfile(scope)?;
+        if lock.get(name).is_none_or(|package| package.kind != kind) {
+            return Err(Error::NotFound(format!(
+                "no {} package '{name}' is installed in the {} scope",
+                kind.as_str(),
+                scope.as_str()
+            )));
+        }
+        let dependents = lock
+            .packages
+            .iter()
+            .filter(|package| package.dependencies.contains_key(name))
+            .map(|package| package.name.as_str())
+            .collect::<Vec<_>>();
+        if !dependents.is_empty() {
+            return Err(Error::Validation(format!(
+                "'{name}' is required by {}",
+                dependents.join(", ")
+            )));
+        }
+        let dir = self.install_dir(scope, kind, name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        let removed = lock
+            .remove(name)
+            .ok_or_else(|| Error::NotFound(format!("package '{name}' is not installed")))?;
+        self.save_lockfile(scope, &lock)?;
+        Ok(removed)
+    }
+
+    /// Installed packages of `kind` in the project and global scopes.
+    pub fn list(&self, kind: PackageKind) -> Result<Vec<InstalledPackage>> {
+        let mut listed = Vec::new();
+        for scope in [PackageScope::Project, PackageScope::Global] {
+            for package in self.lockfile(scope)?.packages {
+                if package.kind != kind {
+                    continue;
+                }
+                let path = self.install_dir(scope, kind, &package.name);
+                listed.push(InstalledPackage {
+                    scope,
+                    present: path.is_dir(),
+                    path,
+                    package,
+                });
+            }
+        }
+        Ok(listed)
+    }
+
+    /// Packs a package directory into `<name>-<version>.tar.gz` under
+    /// `output_dir`, optionally adding it to an index file.
+    pub fn pack(
+        &self,
+        dir: &Path,
+        output_dir: &Path,
+        index: Option<&Path>,
+    ) -> Result<PackedPackage> {
+        let (_, bytes) = pack_dir(dir)?;
+        let archive = PackageArchive::read(bytes)?;
+        self.check_layout(&archive)?;
+        let manifest = archive.manifest.clone();
+        std::fs::create_dir_all(output_dir)?;
+        let archive_path =
+            output_dir.join(format!("{}-{}.tar.gz", manifest.name, manifest.version));
+        std::fs::write(&archive_path, archive.bytes())?;
+        let sha256 = archive.sha256();
+
+        if let Some(index_path) = index {
+            let mut parsed = if index_path.exists() {
+                Self::load_index(index_path)?
+            } else {
+                PackageIndex::default()
+            };
+            let archive_path = std::fs::canonicalize(&archive_path)?;
+            let index_dir = index_path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map(std::fs::canonicalize)
+                .transpose()?
+                .unwrap_or(std::env::current_dir()?);
+            let relative = archive_path
+                .strip_prefix(&index_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| archive_path.clone());
+            parsed.packages.retain(|entry| {
+                !(entry.name == manifest.name && entry.version == manifest.version)
+            });
+            parsed.packages.push(IndexEntry {
+                name: manifest.name.clone(),
+                version: manifest.version.clone(),
+                kind: manifest.kind,
+                description: manifest.description.clone(),
+                archive: relative.to_string_lossy().replace('\\', "/"),
+                sha256: sha256.clone(),
+                dependencies: manifest.dependencies.clone(),
+            });
+            parsed.packages.sort_by(|a, b| {
+                a.name.cmp(&b.name).then_with(|| {
+                    match (Version::parse(&a.version), Version::parse(&b.version)) {
+                        (Ok(left), Ok(right)) => left.cmp(&right),
+                        _ => a.version.cmp(&b.version),
+                    }
+                })
+            });
+            std::fs::write(
+                index_path,
+                format!("{}\n", serde_json::to_string_pretty(&parsed)?),
+            )?;
+        }
+
+        Ok(PackedPackage {
+            manifest,
+            archive: archive_path,
+            sha256,
+        })
+    }
+}
@@ -0,0 +1,179 @@
+//! Package, index and lockfile formats for installable skills and plugins.
+
+use crate::error::{Error, Result};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Manifest at the root of every package archive.
+pub const PACKAGE_MANIFEST_FILE: &str = "rustic-package.json";
+/// Lockfile kept in each scope root.
+pub const LOCKFILE_NAME: &str = "packages.lock.json";
+/// Index file looked up when an index entry names a directory.
+pub const INDEX_FILE_NAME: &str = "index.json";
+pub const LOCKFILE_VERSION: u32 = 1;
+const MAX_PACKAGE_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageKind {
+    Skill,
+    Plugin,
+}
+
+impl PackageKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skill => "skill",
+            Self::Plugin => "plugin",
+        }
+    }
+
+    /// Directory below a scope root that packages of this kind go into.
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Self::Skill => "skills",
+            Self::Plugin => "plugins",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub name: String,
+    pub version: String,
+    pub kind: PackageKind,
+    #[serde(default)]
+    pub description: String,
+    /// Package name to semver requirement, such as `^1.2`.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+}
+
+impl PackageManifest {
+    /// Checks the name, version and dependency requirements.
+    pub fn validate(&self) -> Result<Version> {
+        validate_package_name(&self.name)?;
+        let version = parse_version(&self.name, &self.version)?;
+        for (name, requirement) in &self.dependencies {
+            validate_package_name(name)?;
+            parse_requirement(name, requirement)?;
+        }
+        Ok(version)
+    }
+}
+
+/// One archive listed in a package index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub version: String,
+    pub kind: PackageKind,
+    #[serde(default)]
+    pub description: String,
+    /// Archive path, relative to the index file.
+    pub archive: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageIndex {
+    #[serde(default)]
+    pub packages: Vec<IndexEntry>,
+}
+
+/// Where an installed package came from, so the lockfile can fetch it again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PackageSource {
+    Index { index: String },
+    Path { path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub kind: PackageKind,
+    pub version: String,
+    /// Requirement used by `update`; `=x.y.z` pins a version.
+    pub requirement: String,
+    pub sha256: String,
+    pub source: PackageSource,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub lockfile_version: u32,
+    #[serde(default)]
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            lockfile_version: LOCKFILE_VERSION,
+            packages: Vec::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    pub fn upsert(&mut self, package: LockedPackage) {
+        self.packages
+            .retain(|existing| existing.name != package.name);
+        self.packages.push(package);
+        self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<LockedPackage> {
+        let position = self
+            .packages
+            .iter()
+            .position(|package| package.name == name)?;
+        Some(self.packages.remove(position))
+    }
+}
+
+/// Lowercase letters, digits, `-`, `_` and `.`, starting with a letter or digit.
+pub fn validate_package_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PACKAGE_NAME_CHARS
+        && name
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit())
+        && name.chars().all(|ch| {
+            ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '-' | '_' | '.')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "invalid package name '{name}': use at most {MAX_PACKAGE_NAME_CHARS} lowercase letters, digits, '-', '_' or '.'"
+        )))
+    }
+}
+
+pub fn parse_version(name: &str, version: &str) -> Result<Version> {
+    Version::parse(version.trim()).map_err(|err| {
+        Error::Validation(format!(
+            "package '{name}' has invalid version '{version}': {err}"
+        ))
+    })
+}
+
+pub fn parse_requirement(name: &str, requirement: &str) -> Result<VersionReq> {
+    VersionReq::parse(requirement.trim()).map_err(|err| {
+        Error::Validation(format!(
+            "invalid version requirement '{requirement}' for package '{name}': {err}"
+        ))
+    })
+}
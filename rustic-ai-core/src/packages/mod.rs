@@ -0,0 +1,10 @@
+pub mod archive;
+pub mod manager;
+pub mod manifest;
+
+pub use archive::{sha256_hex, PackageArchive};
+pub use manager::{InstalledPackage, PackageManager, PackageScope, PackageUpdate, PackedPackage};
+pub use manifest::{
+    IndexEntry, LockedPackage, Lockfile, PackageIndex, PackageKind, PackageManifest, PackageSource,
+    PACKAGE_MANIFEST_FILE,
+};
@@ -5,6 +5,7 @@ use crate::permissions::policy::{
     AskResolution, CommandPatternBucket, PermissionContext, PermissionDecision, PermissionPolicy,
 };
 use crate::rules::discovery::simple_glob_match;
+use crate::tools::mcp::tool_list_allows;
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Component, Path, PathBuf};
@@ -43,6 +44,7 @@ pub struct ConfigurablePermissionPolicy {
     runtime_global_allowed_paths: HashSet<String>,
     runtime_project_allowed_paths: HashSet<String>,
     session_allowed_paths: HashMap<String, HashSet<String>>,
+    turn_allowed_tools: HashMap<String, HashSet<String>>,
     runtime_global_command_patterns: CommandPatternConfig,
     runtime_project_command_patterns: CommandPatternConfig,
     session_command_patterns: HashMap<String, CommandPatternConfig>,
@@ -70,6 +72,7 @@ impl ConfigurablePermissionPolicy {
             runtime_global_allowed_paths: HashSet::new(),
             runtime_project_allowed_paths: HashSet::new(),
             session_allowed_paths: HashMap::new(),
+            turn_allowed_tools: HashMap::new(),
             runtime_global_command_patterns: CommandPatternConfig::default(),
             runtime_project_command_patterns: CommandPatternConfig::default(),
             session_command_patterns: HashMap::new(),
@@ -209,6 +212,20 @@ impl ConfigurablePermissionPolicy {
                             paths.push(destination.to_owned());
                         }
                     }
+                    "apply_patch" => {
+                        let mut patches = args
+                            .get("patch")
+                            .and_then(|value| value.as_str())
+                            .and_then(|raw| crate::tools::patch::parse(raw).ok())
+                            .unwrap_or_default();
+                        if let Some(edits) = args
+                            .get("edits")
+                            .and_then(|value| crate::tools::patch::parse_edits(value).ok())
+                        {
+                            patches.extend(edits);
+                        }
+                        paths.extend(patches.into_iter().map(|patch| patch.path));
+                    }
                     "list" | "glob" => {
                         paths.push(
                             args.get("path")
@@ -260,11 +277,17 @@ impl ConfigurablePermissionPolicy {
         true
     }
 
-    fn extract_shell_command(args: &serde_json::Value) -> Option<String> {
+    /// Shell input subject to command checks: `command`, or the text typed into a
+    /// PTY session via `send_keys`.
+    fn shell_input(args: &serde_json::Value) -> &str {
         args.get("command")
+            .or_else(|| args.get("keys"))
             .and_then(|value| value.as_str())
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty())
+            .unwrap_or_default()
+    }
+
+    fn extract_shell_command(args: &serde_json::Value) -> Option<String> {
+        Some(Self::shell_input(args).trim().to_owned()).filter(|value| !value.is_empty())
     }
 
     fn matches_any_pattern(value: &str, patterns: &[String]) -> bool {
@@ -380,7 +403,7 @@ impl ConfigurablePermissionPolicy {
                     .unwrap_or_default();
                 match operation {
                     "read" | "list" | "info" | "glob" | "hash" => ToolAccessKind::Read,
-                    "write" | "edit" | "mkdir" | "delete" | "copy" | "move" => {
+                    "write" | "edit" | "apply_patch" | "mkdir" | "delete" | "copy" | "move" => {
                         ToolAccessKind::Write
                     }
                     _ => ToolAccessKind::Unknown,
@@ -411,11 +434,7 @@ impl ConfigurablePermissionPolicy {
                 }
             }
             "shell" => {
-                let command = args
-                    .get("command")
-                    .and_then(|value| value.as_str())
-                    .unwrap_or_default()
-                    .to_ascii_lowercase();
+                let command = Self::shell_input(args).to_ascii_lowercase();
                 if command.trim().is_empty() {
                     return ToolAccessKind::Unknown;
                 }
@@ -457,11 +476,7 @@ impl ConfigurablePermissionPolicy {
     }
 
     fn shell_access_from_patterns(&self, args: &serde_json::Value) -> ToolAccessKind {
-        let command = args
-            .get("command")
-            .and_then(|value| value.as_str())
-            .unwrap_or_default()
-            .to_ascii_lowercase();
+        let command = Self::shell_input(args).to_ascii_lowercase();
         if command.trim().is_empty() {
             return ToolAccessKind::Unknown;
         }
@@ -534,7 +549,7 @@ impl PermissionPolicy for ConfigurablePermissionPolicy {
 
         if let Some(agent_name) = &context.agent_name {
             if let Some(allowed_tools) = self.agent_tool_allowlist.get(agent_name) {
-                if !allowed_tools.contains(tool) {
+                if !tool_list_allows(allowed_tools.iter().map(String::as_str), tool) {
                     return PermissionDecision::Deny;
                 }
             }
@@ -578,6 +593,14 @@ impl PermissionPolicy for ConfigurablePermissionPolicy {
                     };
                 }
 
+                if self
+                    .turn_allowed_tools
+                    .get(&context.session_id)
+                    .is_some_and(|tools| tool_list_allows(tools.iter().map(String::as_str), tool))
+                {
+                    return Self::allow_decision_for_access(access_kind);
+                }
+
                 // Need to ask user
                 PermissionDecision::Ask
             }
@@ -625,6 +648,20 @@ impl PermissionPolicy for ConfigurablePermissionPolicy {
             .insert(path.trim().to_owned());
     }
 
+    fn add_turn_allowed_tool(&mut self, session_id: &str, tool: &str) {
+        if tool.trim().is_empty() {
+            return;
+        }
+        self.turn_allowed_tools
+            .entry(session_id.to_owned())
+            .or_default()
+            .insert(tool.trim().to_owned());
+    }
+
+    fn clear_turn_allowed_tools(&mut self, session_id: &str) {
+        self.turn_allowed_tools.remove(session_id);
+    }
+
     fn add_session_command_pattern(
         &mut self,
         session_id: &str,
@@ -686,4 +723,8 @@ impl PermissionPolicy for ConfigurablePermissionPolicy {
             CommandPatternBucket::Deny => self.runtime_project_command_patterns.deny.push(value),
         }
     }
+
+    fn set_tool_permission_mode(&mut self, tool: &str, mode: PermissionMode) {
+        self.tool_specific_modes.insert(tool.to_owned(), mode);
+    }
 }
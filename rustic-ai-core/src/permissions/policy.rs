@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-use crate::config::schema::AgentPermissionMode;
+use crate::config::schema::{AgentPermissionMode, PermissionMode};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PermissionDecision {
@@ -53,6 +53,14 @@ pub trait PermissionPolicy: Send + Sync {
 
     fn add_session_allowed_path(&mut self, _session_id: &str, _path: &str) {}
 
+    /// Pre-approves `tool` until the current turn of the session ends, as
+    /// requested by an invoked skill. Only tools that would otherwise ask are
+    /// affected.
+    fn add_turn_allowed_tool(&mut self, _session_id: &str, _tool: &str) {}
+
+    /// Drops the tools pre-approved for the session's current turn.
+    fn clear_turn_allowed_tools(&mut self, _session_id: &str) {}
+
     fn add_session_command_pattern(
         &mut self,
         _session_id: &str,
@@ -68,4 +76,8 @@ pub trait PermissionPolicy: Send + Sync {
     fn add_global_command_pattern(&mut self, _bucket: CommandPatternBucket, _pattern: &str) {}
 
     fn add_project_command_pattern(&mut self, _bucket: CommandPatternBucket, _pattern: &str) {}
+
+    /// Sets the mode for a tool registered after startup, such as a
+    /// discovered MCP tool.
+    fn set_tool_permission_mode(&mut self, _tool: &str, _mode: PermissionMode) {}
 }
@@ -6,6 +6,7 @@ use crate::error::{Error, Result};
 use crate::rules::frontmatter::parse_json_frontmatter;
 use crate::rules::gitignore::GitignoreMatcher;
 use crate::rules::precedence::sort_rule_files_by_precedence;
+use crate::skills::loader::is_skill_bundle;
 
 pub fn discover_rule_and_context_files(
     work_dir: &Path,
@@ -210,7 +211,7 @@ fn scan_rules_dir(
             if rule_config.use_gitignore && gitignore.is_ignored(&path, true) {
                 continue;
             }
-            if !rule_config.recursive_discovery {
+            if !rule_config.recursive_discovery || is_skill_bundle(&path) {
                 continue;
             }
             scan_rules_dir(
@@ -297,7 +298,7 @@ fn scan_context_dir(
             if rule_config.use_gitignore && gitignore.is_ignored(&path, true) {
                 continue;
             }
-            if !rule_config.recursive_discovery {
+            if !rule_config.recursive_discovery || is_skill_bundle(&path) {
                 continue;
             }
             scan_context_dir(&path, work_dir, rule_config, depth + 1, gitignore, out)?;
@@ -54,7 +54,9 @@ pub fn parse_json_frontmatter(content: &str) -> Result<ParsedRuleMetadata> {
     })
 }
 
-fn split_frontmatter_and_body(content: &str) -> Result<(String, String)> {
+/// Splits a `---` delimited header from the body. Without a header the
+/// frontmatter is `{}`.
+pub(crate) fn split_frontmatter_and_body(content: &str) -> Result<(String, String)> {
     let mut lines = content.lines();
     let first = lines.next().unwrap_or_default().trim();
     if first != "---" {
@@ -36,43 +36,55 @@ impl GitignoreMatcher {
         };
 
         for raw_line in content.lines() {
-            let line = raw_line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
+            matcher.push_line(raw_line);
+        }
 
-            let is_negation = line.starts_with('!');
-            let pattern_without_negation = if is_negation { &line[1..] } else { line };
-            if pattern_without_negation.is_empty() {
-                continue;
-            }
+        Ok(matcher)
+    }
 
-            let anchored = pattern_without_negation.starts_with('/');
-            let trimmed_pattern = if anchored {
-                &pattern_without_negation[1..]
-            } else {
-                pattern_without_negation
-            };
-            let dir_only = trimmed_pattern.ends_with('/');
-            let pattern = if dir_only {
-                trimmed_pattern.trim_end_matches('/').to_owned()
-            } else {
-                trimmed_pattern.to_owned()
-            };
+    /// Appends extra rules in `.gitignore` syntax after the file's own rules.
+    pub fn with_patterns(mut self, patterns: &[String]) -> Self {
+        for pattern in patterns {
+            self.push_line(pattern);
+        }
+        self
+    }
 
-            if pattern.is_empty() {
-                continue;
-            }
+    fn push_line(&mut self, raw_line: &str) {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
 
-            matcher.patterns.push(IgnorePattern {
-                pattern,
-                is_negation,
-                dir_only,
-                anchored,
-            });
+        let is_negation = line.starts_with('!');
+        let pattern_without_negation = if is_negation { &line[1..] } else { line };
+        if pattern_without_negation.is_empty() {
+            return;
         }
 
-        Ok(matcher)
+        let anchored = pattern_without_negation.starts_with('/');
+        let trimmed_pattern = if anchored {
+            &pattern_without_negation[1..]
+        } else {
+            pattern_without_negation
+        };
+        let dir_only = trimmed_pattern.ends_with('/');
+        let pattern = if dir_only {
+            trimmed_pattern.trim_end_matches('/').to_owned()
+        } else {
+            trimmed_pattern.to_owned()
+        };
+
+        if pattern.is_empty() {
+            return;
+        }
+
+        self.patterns.push(IgnorePattern {
+            pattern,
+            is_negation,
+            dir_only,
+            anchored,
+        });
     }
 
     pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
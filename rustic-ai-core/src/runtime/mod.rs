@@ -9,6 +9,7 @@ use crate::permissions::{ConfigurablePermissionPolicy, PermissionPolicy};
 use crate::providers::create_provider_registry;
 use crate::providers::registry::ProviderRegistry;
 use crate::rag::HybridRetriever;
+use crate::sandbox::Sandbox;
 use crate::skills::{SkillLoader, SkillRegistry};
 use crate::tools::{ToolExecutionContext, ToolManager, ToolManagerInit};
 use crate::workflows::{WorkflowLoader, WorkflowRegistry};
@@ -36,9 +37,10 @@ impl Runtime {
         let work_dir = std::env::current_dir()
             .map_err(|err| crate::Error::Config(format!("failed to resolve current dir: {err}")))?;
         let providers = create_provider_registry(&config, &work_dir)?;
+        let sandbox = Sandbox::new(config.sandbox.clone());
 
         let skills = if config.features.skills_enabled {
-            Arc::new(SkillLoader::load(&config.skills, &work_dir)?)
+            Arc::new(SkillLoader::load(&config.skills, &work_dir, &sandbox)?)
         } else {
             Arc::new(SkillRegistry::new())
         };
@@ -56,7 +58,11 @@ impl Runtime {
             taxonomy.register_tool(tool);
         }
         for skill_name in skills.list() {
-            taxonomy.register_skill(&skill_name, Vec::new());
+            let membership = skills
+                .get(&skill_name)
+                .map(|skill| skill.spec().taxonomy_membership.clone())
+                .unwrap_or_default();
+            taxonomy.register_skill(&skill_name, membership);
         }
         let taxonomy = Arc::new(taxonomy);
 
@@ -80,16 +86,21 @@ impl Runtime {
                 agent_tool_allowlist,
             ));
 
+        let event_bus = EventBus::default();
+
         // Create tool manager
         let tools = Arc::new(ToolManager::new(ToolManagerInit {
             permission_policy,
             permission_config: Arc::new(config.permissions.clone()),
             mcp_enabled: config.features.mcp_enabled,
             mcp_config: Arc::new(config.mcp.clone()),
+            lsp_config: Arc::new(config.lsp.clone()),
+            watcher_config: Arc::new(config.watcher.clone()),
             skills_enabled: config.features.skills_enabled,
             sub_agent_parallel_enabled: config.features.sub_agent_parallel_enabled,
             sub_agent_output_caching_enabled: config.features.sub_agent_output_caching_enabled,
             skills: skills.clone(),
+            learning: learning.clone(),
             workflows_enabled: config.features.workflows_enabled,
             workflows: workflows.clone(),
             workflows_config: Arc::new(config.workflows.clone()),
@@ -101,6 +112,8 @@ impl Runtime {
             plugins_enabled: config.features.plugins_enabled,
             plugin_config: Arc::new(config.plugins.clone()),
             tool_configs: config.tools.clone(),
+            sandbox: Arc::new(sandbox),
+            event_bus: event_bus.clone(),
             execution_context: ToolExecutionContext {
                 working_directory: work_dir,
                 session_id: None,
@@ -125,7 +138,7 @@ impl Runtime {
         tools.attach_agents(Arc::new(agents.clone()));
 
         Ok(Self {
-            event_bus: EventBus::default(),
+            event_bus,
             providers,
             agents,
             tools,
@@ -135,4 +148,9 @@ impl Runtime {
             config,
         })
     }
+
+    /// Releases long-lived resources (MCP server processes). Call before exit.
+    pub async fn shutdown(&self) {
+        self.tools.shutdown().await;
+    }
 }
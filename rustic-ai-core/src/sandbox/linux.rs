@@ -0,0 +1,375 @@
+use super::{resolve_path, SandboxLayers, SandboxPolicy, SandboxSupport};
+use crate::config::schema::SandboxConfig;
+use crate::error::{Error, Result};
+use landlock::{
+    path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreated, RulesetCreatedAttr,
+    ABI,
+};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule, TargetArch,
+};
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LANDLOCK_ABI: ABI = ABI::V5;
+
+/// Syscalls a sandboxed process never needs: kernel/module management, tracing,
+/// namespace and mount manipulation, and other escape primitives.
+const DENIED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_reboot,
+    libc::SYS_kexec_load,
+    libc::SYS_kexec_file_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_userfaultfd,
+    libc::SYS_setns,
+    libc::SYS_unshare,
+    libc::SYS_acct,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_name_to_handle_at,
+    libc::SYS_quotactl,
+    libc::SYS_syslog,
+];
+
+pub(super) fn detect_support() -> SandboxSupport {
+    SandboxSupport {
+        namespaces: probe_namespaces(),
+        landlock: probe_landlock(),
+        seccomp: probe_seccomp(),
+    }
+}
+
+/// Spawns a trivial child that enters fresh user/mount/network namespaces;
+/// unprivileged user namespaces are frequently disabled by distributions.
+fn probe_namespaces() -> bool {
+    let mut probe = std::process::Command::new("/bin/sh");
+    probe
+        .args(["-c", ":"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // SAFETY: unshare is async-signal-safe and touches no parent state.
+    unsafe {
+        probe.pre_exec(|| {
+            if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWNET) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    probe.status().is_ok_and(|status| status.success())
+}
+
+fn probe_landlock() -> bool {
+    const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+    // SAFETY: querying the ABI version takes no pointers and creates no fd.
+    let version = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    version >= 1
+}
+
+fn probe_seccomp() -> bool {
+    // SAFETY: PR_GET_SECCOMP only reads the calling thread's mode.
+    unsafe { libc::prctl(libc::PR_GET_SECCOMP) >= 0 }
+}
+
+/// Namespace setup performed by the child before exec.
+struct NamespaceSetup {
+    flags: libc::c_int,
+    uid_map: Vec<u8>,
+    gid_map: Vec<u8>,
+    /// Workspace path and the mount flags that must be preserved on remount.
+    read_only_workspace: Option<(CString, libc::c_ulong)>,
+}
+
+impl NamespaceSetup {
+    fn prepare(config: &SandboxConfig, policy: &SandboxPolicy) -> Result<Option<Self>> {
+        let read_only = !policy.workspace_writable;
+        if !read_only && config.network {
+            return Ok(None);
+        }
+
+        let mut flags = libc::CLONE_NEWUSER;
+        if read_only {
+            flags |= libc::CLONE_NEWNS;
+        }
+        if !config.network {
+            flags |= libc::CLONE_NEWNET;
+        }
+        // SAFETY: getuid/getgid cannot fail.
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let read_only_workspace = if read_only {
+            let path = CString::new(policy.workspace.as_os_str().as_bytes()).map_err(|_| {
+                Error::Tool(format!(
+                    "sandbox workspace path contains a NUL byte: {}",
+                    policy.workspace.display()
+                ))
+            })?;
+            let locked = locked_mount_flags(&path)?;
+            Some((path, locked))
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            flags,
+            uid_map: format!("{uid} {uid} 1").into_bytes(),
+            gid_map: format!("{gid} {gid} 1").into_bytes(),
+            read_only_workspace,
+        }))
+    }
+
+    fn enter(&self) -> io::Result<()> {
+        // SAFETY: every call below is a raw syscall on buffers owned by `self`
+        // or on the stack; nothing allocates between fork and exec.
+        unsafe {
+            check(libc::unshare(self.flags))?;
+            // Older kernels lack setgroups; writing the maps still works there.
+            let _ = write_proc_file(c"/proc/self/setgroups", b"deny");
+            write_proc_file(c"/proc/self/uid_map", &self.uid_map)?;
+            write_proc_file(c"/proc/self/gid_map", &self.gid_map)?;
+
+            if let Some((path, locked)) = &self.read_only_workspace {
+                check(libc::mount(
+                    std::ptr::null(),
+                    c"/".as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    std::ptr::null(),
+                ))?;
+                check(libc::mount(
+                    path.as_ptr(),
+                    path.as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_BIND | libc::MS_REC,
+                    std::ptr::null(),
+                ))?;
+                check(libc::mount(
+                    std::ptr::null(),
+                    path.as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | locked,
+                    std::ptr::null(),
+                ))?;
+
+                // The working directory was entered before the bind mount; re-enter
+                // it so relative paths resolve through the read-only mount.
+                let mut cwd = [0 as libc::c_char; libc::PATH_MAX as usize];
+                if !libc::getcwd(cwd.as_mut_ptr(), cwd.len()).is_null() {
+                    check(libc::chdir(cwd.as_ptr()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mount flags an unprivileged remount inside a user namespace must keep.
+fn locked_mount_flags(path: &CString) -> Result<libc::c_ulong> {
+    // SAFETY: `stat` is a plain-old-data out parameter.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL terminated and `stat` is valid for writes.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(Error::Tool(format!(
+            "failed to inspect sandbox workspace mount: {}",
+            io::Error::last_os_error()
+        )));
+    }
+    let mapping = [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ];
+    Ok(mapping
+        .into_iter()
+        .filter(|(st, _)| stat.f_flag & st != 0)
+        .fold(0, |flags, (_, ms)| flags | ms))
+}
+
+unsafe fn write_proc_file(path: &std::ffi::CStr, contents: &[u8]) -> io::Result<()> {
+    let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+    libc::close(fd);
+    if written < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Everything the child needs, prepared in the parent.
+pub(super) struct ChildSetup {
+    namespaces: Option<NamespaceSetup>,
+    landlock: Mutex<Option<RulesetCreated>>,
+    seccomp: Option<BpfProgram>,
+}
+
+impl ChildSetup {
+    pub(super) fn prepare(
+        config: &SandboxConfig,
+        layers: SandboxLayers,
+        policy: &SandboxPolicy,
+    ) -> Result<Self> {
+        let workspace = policy
+            .workspace
+            .canonicalize()
+            .unwrap_or_else(|_| policy.workspace.clone());
+        let policy = SandboxPolicy {
+            workspace,
+            workspace_writable: policy.workspace_writable,
+        };
+
+        let namespaces = if layers.namespaces {
+            NamespaceSetup::prepare(config, &policy)?
+        } else {
+            None
+        };
+        let landlock = if layers.landlock {
+            Some(landlock_ruleset(config, &policy)?)
+        } else {
+            None
+        };
+        let seccomp = if layers.seccomp {
+            Some(seccomp_program(config)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            namespaces,
+            landlock: Mutex::new(landlock),
+            seccomp,
+        })
+    }
+
+    /// The `pre_exec` hook: namespaces first (they need mount/unshare), then
+    /// Landlock, then seccomp, which forbids the earlier steps' syscalls.
+    pub(super) fn into_hook(self) -> impl FnMut() -> io::Result<()> + Send + Sync + 'static {
+        move || {
+            if let Some(namespaces) = &self.namespaces {
+                namespaces.enter()?;
+            }
+            let ruleset = match self.landlock.lock() {
+                Ok(mut guard) => guard.take(),
+                Err(_) => None,
+            };
+            if let Some(ruleset) = ruleset {
+                ruleset
+                    .restrict_self()
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EPERM))?;
+            }
+            if let Some(program) = &self.seccomp {
+                seccompiler::apply_filter(program)
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EPERM))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn existing_paths(raw: &[String], base: &Path) -> Vec<PathBuf> {
+    raw.iter()
+        .map(|path| resolve_path(path, base))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Read access beneath `readable_paths` and the workspace; full access beneath
+/// `writable_paths` (plus the workspace for read-write agents).
+fn landlock_ruleset(config: &SandboxConfig, policy: &SandboxPolicy) -> Result<RulesetCreated> {
+    let mut readable = existing_paths(&config.readable_paths, &policy.workspace);
+    readable.push(policy.workspace.clone());
+    let mut writable = existing_paths(&config.writable_paths, &policy.workspace);
+    if policy.workspace_writable {
+        writable.push(policy.workspace.clone());
+    }
+
+    let to_error = |err: landlock::RulesetError| {
+        Error::Tool(format!("failed to prepare Landlock ruleset: {err}"))
+    };
+    Ruleset::default()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))
+        .map_err(to_error)?
+        .create()
+        .map_err(to_error)?
+        .add_rules(path_beneath_rules(
+            &readable,
+            AccessFs::from_read(LANDLOCK_ABI),
+        ))
+        .map_err(to_error)?
+        .add_rules(path_beneath_rules(
+            &writable,
+            AccessFs::from_all(LANDLOCK_ABI),
+        ))
+        .map_err(to_error)
+}
+
+fn seccomp_program(config: &SandboxConfig) -> Result<BpfProgram> {
+    let to_error = |err: seccompiler::BackendError| {
+        Error::Tool(format!("failed to build seccomp filter: {err}"))
+    };
+
+    let mut rules = DENIED_SYSCALLS
+        .iter()
+        .map(|syscall| (*syscall, Vec::new()))
+        .collect::<BTreeMap<i64, Vec<SeccompRule>>>();
+    if !config.network {
+        let socket_rules = [libc::AF_INET, libc::AF_INET6]
+            .into_iter()
+            .map(|family| {
+                SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)
+                    .and_then(|condition| SeccompRule::new(vec![condition]))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(to_error)?;
+        rules.insert(libc::SYS_socket, socket_rules);
+    }
+
+    let arch = TargetArch::try_from(std::env::consts::ARCH).map_err(to_error)?;
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        arch,
+    )
+    .map_err(to_error)?;
+    filter.try_into().map_err(to_error)
+}
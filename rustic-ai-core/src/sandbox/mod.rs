@@ -0,0 +1,227 @@
+//! OS-level isolation for child processes spawned by shell commands, script
+//! skills, plugin tools and MCP servers.
+//!
+//! On Linux the sandbox layers user/mount/network namespaces (read-only
+//! workspace bind mount, empty network namespace), Landlock filesystem rules and
+//! a seccomp deny-list. Every layer is prepared in the parent and applied in the
+//! child between `fork` and `exec`, so no allocation happens after the fork.
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+use crate::config::schema::{AgentPermissionMode, SandboxConfig, SandboxMode};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Per-spawn inputs: the workspace the child runs against and whether the
+/// calling agent may write to it.
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    pub workspace: PathBuf,
+    pub workspace_writable: bool,
+}
+
+impl SandboxPolicy {
+    pub fn for_agent(workspace: &Path, mode: AgentPermissionMode) -> Self {
+        Self {
+            workspace: workspace.to_path_buf(),
+            workspace_writable: mode == AgentPermissionMode::ReadWrite,
+        }
+    }
+}
+
+/// Isolation layers the running kernel supports.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct SandboxSupport {
+    pub namespaces: bool,
+    pub landlock: bool,
+    pub seccomp: bool,
+}
+
+impl SandboxSupport {
+    /// Probes the kernel once per process.
+    pub fn detect() -> Self {
+        static SUPPORT: OnceLock<SandboxSupport> = OnceLock::new();
+        *SUPPORT.get_or_init(|| {
+            #[cfg(target_os = "linux")]
+            {
+                linux::detect_support()
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                SandboxSupport::default()
+            }
+        })
+    }
+}
+
+/// Layers that will actually be applied to a spawn.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct SandboxLayers {
+    namespaces: bool,
+    landlock: bool,
+    seccomp: bool,
+}
+
+impl SandboxLayers {
+    fn any(self) -> bool {
+        self.namespaces || self.landlock || self.seccomp
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Sandbox {
+    config: SandboxConfig,
+}
+
+impl Sandbox {
+    pub fn new(config: SandboxConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.mode != SandboxMode::Disabled
+    }
+
+    /// The same layers with `mode` overridden; script skills configured with
+    /// `script_execution_mode = sandbox` always run with `Required`.
+    pub fn with_mode(&self, mode: SandboxMode) -> Self {
+        let mut config = self.config.clone();
+        config.mode = mode;
+        Self { config }
+    }
+
+    /// True when the kernel keeps read-only agents from writing anywhere, so
+    /// command heuristics are redundant. Only Landlock without
+    /// `writable_paths` does that; the mount namespace covers the workspace
+    /// alone and leaves HOME, /tmp and the rest of the filesystem writable.
+    pub fn enforces_read_only(&self) -> bool {
+        self.config.writable_paths.is_empty()
+            && self.resolve_layers().is_some_and(|layers| layers.landlock)
+    }
+
+    /// Installs the sandbox on a Tokio command. Returns whether any layer was applied.
+    pub fn apply_tokio(
+        &self,
+        command: &mut tokio::process::Command,
+        policy: &SandboxPolicy,
+    ) -> Result<bool> {
+        let Some(layers) = self.layers()? else {
+            return Ok(false);
+        };
+        #[cfg(target_os = "linux")]
+        {
+            let hook = linux::ChildSetup::prepare(&self.config, layers, policy)?.into_hook();
+            // SAFETY: the hook only issues raw syscalls on state prepared before fork.
+            unsafe {
+                command.pre_exec(hook);
+            }
+            Ok(true)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (command, policy, layers);
+            Ok(false)
+        }
+    }
+
+    /// Installs the sandbox on a std command. Returns whether any layer was applied.
+    pub fn apply_std(
+        &self,
+        command: &mut std::process::Command,
+        policy: &SandboxPolicy,
+    ) -> Result<bool> {
+        let Some(layers) = self.layers()? else {
+            return Ok(false);
+        };
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let hook = linux::ChildSetup::prepare(&self.config, layers, policy)?.into_hook();
+            // SAFETY: the hook only issues raw syscalls on state prepared before fork.
+            unsafe {
+                command.pre_exec(hook);
+            }
+            Ok(true)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (command, policy, layers);
+            Ok(false)
+        }
+    }
+
+    fn resolve_layers(&self) -> Option<SandboxLayers> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let support = SandboxSupport::detect();
+        let layers = SandboxLayers {
+            namespaces: self.config.namespaces && support.namespaces,
+            landlock: self.config.landlock && support.landlock,
+            seccomp: self.config.seccomp && support.seccomp,
+        };
+        layers.any().then_some(layers)
+    }
+
+    /// Resolves the layers to apply, failing in `Required` mode when an enabled
+    /// layer is unsupported and warning (once) in `BestEffort` mode.
+    fn layers(&self) -> Result<Option<SandboxLayers>> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let support = SandboxSupport::detect();
+        let missing = [
+            ("namespaces", self.config.namespaces, support.namespaces),
+            ("landlock", self.config.landlock, support.landlock),
+            ("seccomp", self.config.seccomp, support.seccomp),
+        ]
+        .into_iter()
+        .filter(|(_, wanted, supported)| *wanted && !supported)
+        .map(|(name, _, _)| name)
+        .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            if self.config.mode == SandboxMode::Required {
+                return Err(Error::Tool(format!(
+                    "sandbox is required but unavailable on this system: {}",
+                    missing.join(", ")
+                )));
+            }
+            static WARNED: OnceLock<()> = OnceLock::new();
+            WARNED.get_or_init(|| {
+                tracing::warn!(
+                    missing = %missing.join(", "),
+                    "sandbox layers unsupported on this system; continuing without them"
+                );
+            });
+        }
+        Ok(self.resolve_layers())
+    }
+}
+
+/// Expands `~/` and resolves relative entries against `base`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn resolve_path(raw: &str, base: &Path) -> PathBuf {
+    let raw = raw.trim();
+    let expanded = match raw.strip_prefix("~/") {
+        Some(suffix) => match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(suffix),
+            Err(_) => PathBuf::from(raw),
+        },
+        None => PathBuf::from(raw),
+    };
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        base.join(expanded)
+    }
+}
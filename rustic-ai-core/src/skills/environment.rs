@@ -0,0 +1,532 @@
+//! Per-skill dependency environments.
+//!
+//! A script whose skill declares dependencies for its language runs in an
+//! environment under `skills.environments_dir`: a venv for Python, an npm
+//! prefix for JavaScript and TypeScript, a gem directory for Ruby and a
+//! luarocks tree for Lua. Rust scripts are always compiled there with cargo.
+//!
+//! An environment is keyed by the skill, language, interpreter and packages
+//! (and, for Rust, the source), built on first use and reused for as long as
+//! its ready marker exists. A failed build is removed so the next call
+//! retries it.
+
+use super::types::{ScriptLanguage, SkillDependencies};
+use crate::config::schema::SkillsConfig;
+use crate::error::{Error, Result};
+use crate::sandbox::{Sandbox, SandboxPolicy};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Written last, so its presence means the environment is complete.
+const READY_MARKER: &str = ".rustic-ai-ready";
+/// Package name of the generated cargo project for Rust scripts.
+const RUST_PACKAGE: &str = "skill-script";
+/// Lines of installer output kept in error messages.
+const ERROR_TAIL_LINES: usize = 20;
+
+/// How to start one script: the program, its arguments and extra variables.
+#[derive(Debug, Clone)]
+pub struct ScriptLaunch {
+    pub program: PathBuf,
+    pub args: Vec<PathBuf>,
+    pub env: Vec<(String, String)>,
+}
+
+impl ScriptLaunch {
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+}
+
+/// Checks declared packages when a skill is loaded, so a bad declaration is
+/// reported once instead of on every call.
+pub fn validate_dependencies(dependencies: &SkillDependencies) -> std::result::Result<(), String> {
+    for (ecosystem, packages) in [
+        ("python", &dependencies.python),
+        ("node", &dependencies.node),
+        ("ruby", &dependencies.ruby),
+        ("lua", &dependencies.lua),
+        ("rust", &dependencies.rust),
+    ] {
+        for package in packages {
+            let package = package.trim();
+            // A leading '-' would be read as an installer option.
+            if package.is_empty() || package.starts_with('-') {
+                return Err(format!("invalid {ecosystem} dependency '{package}'"));
+            }
+            if ecosystem != "lua" && package.chars().any(char::is_whitespace) {
+                return Err(format!(
+                    "{ecosystem} dependency '{package}' must not contain whitespace"
+                ));
+            }
+            if ecosystem == "rust" {
+                rust_dependency_line(package)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `name` or `name@requirement` as a `[dependencies]` line.
+fn rust_dependency_line(package: &str) -> std::result::Result<String, String> {
+    let (name, requirement) = package.split_once('@').unwrap_or((package, "*"));
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    let valid_requirement = !requirement.is_empty()
+        && requirement
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "<>=^~*., +-".contains(ch));
+    if !valid_name || !valid_requirement {
+        return Err(format!(
+            "rust dependency '{package}' must be 'name' or 'name@version-requirement'"
+        ));
+    }
+    Ok(format!("{name} = \"{requirement}\""))
+}
+
+/// Builds and locates skill environments.
+#[derive(Debug)]
+pub struct SkillEnvironments {
+    root: PathBuf,
+    install: bool,
+    install_timeout: Duration,
+    interpreters: BTreeMap<String, String>,
+    sandbox: Sandbox,
+    /// One lock per environment so concurrent calls build it once.
+    builds: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SkillEnvironments {
+    pub fn new(config: &SkillsConfig, root: PathBuf, sandbox: Sandbox) -> Self {
+        Self {
+            root,
+            install: config.install_dependencies,
+            install_timeout: Duration::from_secs(config.install_timeout_seconds),
+            interpreters: config.interpreters.clone(),
+            sandbox,
+            builds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn interpreter(&self, language: ScriptLanguage) -> String {
+        self.interpreters
+            .get(language.name())
+            .cloned()
+            .unwrap_or_else(|| language.default_command().to_owned())
+    }
+
+    /// Returns how to run `script`, building the skill's environment first
+    /// when it needs one.
+    pub async fn prepare(
+        &self,
+        skill: &str,
+        language: ScriptLanguage,
+        script: &Path,
+        dependencies: &SkillDependencies,
+    ) -> Result<ScriptLaunch> {
+        let interpreter = self.interpreter(language);
+        let packages = dependencies.for_language(language);
+        if packages.is_empty() && language != ScriptLanguage::Rust {
+            return Ok(ScriptLaunch {
+                program: PathBuf::from(interpreter),
+                args: vec![script.to_path_buf()],
+                env: Vec::new(),
+            });
+        }
+
+        let source = if language == ScriptLanguage::Rust {
+            tokio::fs::read(script).await.map_err(|err| {
+                Error::Tool(format!(
+                    "failed reading rust script '{}' of skill '{skill}': {err}",
+                    script.display()
+                ))
+            })?
+        } else {
+            Vec::new()
+        };
+        let dir = self.environment_dir(skill, language, &interpreter, packages, &source);
+
+        let lock = {
+            let mut builds = self
+                .builds
+                .lock()
+                .map_err(|_| Error::Tool("skill environment lock poisoned".to_owned()))?;
+            builds.entry(dir.clone()).or_default().clone()
+        };
+        let _guard = lock.lock().await;
+        if !dir.join(READY_MARKER).is_file() {
+            let build = Build {
+                environments: self,
+                skill,
+                language,
+                dir: &dir,
+            };
+            if let Err(err) = build.run(&interpreter, packages, &source).await {
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Err(err);
+            }
+            let marker = serde_json::json!({
+                "skill": skill,
+                "language": language.name(),
+                "packages": packages,
+            });
+            tokio::fs::write(dir.join(READY_MARKER), marker.to_string())
+                .await
+                .map_err(|err| {
+                    Error::Tool(format!(
+                        "failed finishing environment '{}': {err}",
+                        dir.display()
+                    ))
+                })?;
+        }
+
+        Ok(Self::launch(&dir, language, &interpreter, script))
+    }
+
+    fn environment_dir(
+        &self,
+        skill: &str,
+        language: ScriptLanguage,
+        interpreter: &str,
+        packages: &[String],
+        source: &[u8],
+    ) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(language.name());
+        hasher.update([0]);
+        hasher.update(interpreter);
+        for package in packages {
+            hasher.update([0]);
+            hasher.update(package.trim());
+        }
+        hasher.update([0]);
+        hasher.update(source);
+        let digest = format!("{:x}", hasher.finalize());
+        let slug = skill
+            .strip_prefix("skill.")
+            .unwrap_or(skill)
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        self.root
+            .join(slug)
+            .join(format!("{}-{}", language.name(), &digest[..16]))
+    }
+
+    fn launch(
+        dir: &Path,
+        language: ScriptLanguage,
+        interpreter: &str,
+        script: &Path,
+    ) -> ScriptLaunch {
+        let path = |path: PathBuf| path.display().to_string();
+        let (program, args, env) = match language {
+            ScriptLanguage::Python => (
+                venv_python(dir),
+                vec![script.to_path_buf()],
+                vec![("VIRTUAL_ENV".to_owned(), path(dir.to_path_buf()))],
+            ),
+            ScriptLanguage::JavaScript | ScriptLanguage::TypeScript => (
+                PathBuf::from(interpreter),
+                vec![script.to_path_buf()],
+                vec![("NODE_PATH".to_owned(), path(dir.join("node_modules")))],
+            ),
+            ScriptLanguage::Ruby => (
+                PathBuf::from(interpreter),
+                vec![script.to_path_buf()],
+                vec![
+                    ("GEM_HOME".to_owned(), path(dir.to_path_buf())),
+                    ("GEM_PATH".to_owned(), path(dir.to_path_buf())),
+                ],
+            ),
+            ScriptLanguage::Lua => (
+                PathBuf::from(interpreter),
+                vec![script.to_path_buf()],
+                lua_search_paths(dir),
+            ),
+            ScriptLanguage::Rust => (rust_binary(dir), Vec::new(), Vec::new()),
+            ScriptLanguage::Shell | ScriptLanguage::Bash => (
+                PathBuf::from(interpreter),
+                vec![script.to_path_buf()],
+                Vec::new(),
+            ),
+        };
+        ScriptLaunch { program, args, env }
+    }
+}
+
+fn venv_python(dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        dir.join("Scripts").join("python.exe")
+    } else {
+        dir.join("bin").join("python")
+    }
+}
+
+fn rust_binary(dir: &Path) -> PathBuf {
+    let name = if cfg!(windows) {
+        format!("{RUST_PACKAGE}.exe")
+    } else {
+        RUST_PACKAGE.to_owned()
+    };
+    dir.join("target").join("release").join(name)
+}
+
+/// `LUA_PATH` and `LUA_CPATH` covering every Lua version in the tree, with
+/// `;;` keeping the interpreter's defaults.
+fn lua_search_paths(dir: &Path) -> Vec<(String, String)> {
+    let versions = |base: PathBuf| {
+        let mut dirs = std::fs::read_dir(base)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        dirs.sort();
+        dirs
+    };
+    let mut lua_path = String::new();
+    for version in versions(dir.join("share").join("lua")) {
+        lua_path.push_str(&format!("{0}/?.lua;{0}/?/init.lua;", version.display()));
+    }
+    let mut lua_cpath = String::new();
+    for version in versions(dir.join("lib").join("lua")) {
+        lua_cpath.push_str(&format!("{}/?.so;", version.display()));
+    }
+    lua_path.push(';');
+    lua_cpath.push(';');
+    vec![
+        ("LUA_PATH".to_owned(), lua_path),
+        ("LUA_CPATH".to_owned(), lua_cpath),
+    ]
+}
+
+/// One environment being built.
+struct Build<'a> {
+    environments: &'a SkillEnvironments,
+    skill: &'a str,
+    language: ScriptLanguage,
+    dir: &'a Path,
+}
+
+impl Build<'_> {
+    async fn run(&self, interpreter: &str, packages: &[String], source: &[u8]) -> Result<()> {
+        let installs = !packages.is_empty();
+        if installs && !self.environments.install && self.language != ScriptLanguage::Rust {
+            return Err(Error::Tool(format!(
+                "{} dependencies of skill '{}' ({}) are not installed and skills.install_dependencies is false; build the environment where the packages are reachable, or copy it to '{}'",
+                self.language.name(),
+                self.skill,
+                packages.join(", "),
+                self.dir.display()
+            )));
+        }
+
+        let _ = tokio::fs::remove_dir_all(self.dir).await;
+        tokio::fs::create_dir_all(self.dir).await.map_err(|err| {
+            Error::Tool(format!(
+                "failed creating skill environment '{}': {err}",
+                self.dir.display()
+            ))
+        })?;
+        let dir = self.dir.display().to_string();
+
+        match self.language {
+            ScriptLanguage::Python => {
+                self.exec(interpreter, &["-m", "venv", &dir], &[]).await?;
+                let python = venv_python(self.dir).display().to_string();
+                let mut args = vec![
+                    "-m",
+                    "pip",
+                    "install",
+                    "--disable-pip-version-check",
+                    "--no-input",
+                    "--quiet",
+                ];
+                args.extend(packages.iter().map(|package| package.trim()));
+                self.exec(&python, &args, &[]).await
+            }
+            ScriptLanguage::JavaScript | ScriptLanguage::TypeScript => {
+                let mut args = vec![
+                    "install",
+                    "--prefix",
+                    &dir,
+                    "--no-audit",
+                    "--no-fund",
+                    "--no-package-lock",
+                    "--loglevel=error",
+                ];
+                args.extend(packages.iter().map(|package| package.trim()));
+                let cache = self.dir.join(".npm-cache").display().to_string();
+                self.exec("npm", &args, &[("npm_config_cache", &cache)])
+                    .await
+            }
+            ScriptLanguage::Ruby => {
+                let mut args = vec![
+                    "-S",
+                    "gem",
+                    "install",
+                    "--install-dir",
+                    &dir,
+                    "--no-document",
+                ];
+                args.extend(packages.iter().map(|package| package.trim()));
+                self.exec(interpreter, &args, &[]).await
+            }
+            ScriptLanguage::Lua => {
+                for package in packages {
+                    let mut args = vec!["install", "--tree", &dir];
+                    args.extend(package.split_whitespace());
+                    self.exec("luarocks", &args, &[]).await?;
+                }
+                Ok(())
+            }
+            ScriptLanguage::Rust => self.build_rust(packages, source).await,
+            ScriptLanguage::Shell | ScriptLanguage::Bash => Ok(()),
+        }
+    }
+
+    async fn build_rust(&self, packages: &[String], source: &[u8]) -> Result<()> {
+        let mut manifest = format!(
+            "[package]\nname = \"{RUST_PACKAGE}\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\n"
+        );
+        for package in packages {
+            manifest.push_str(&rust_dependency_line(package.trim()).map_err(Error::Tool)?);
+            manifest.push('\n');
+        }
+        // An empty workspace keeps the package out of any enclosing one.
+        manifest.push_str("\n[workspace]\n");
+        let src = self.dir.join("src");
+        let written = async {
+            tokio::fs::create_dir_all(&src).await?;
+            tokio::fs::write(self.dir.join("Cargo.toml"), manifest).await?;
+            tokio::fs::write(src.join("main.rs"), source).await
+        };
+        written.await.map_err(|err| {
+            Error::Tool(format!(
+                "failed writing cargo project '{}': {err}",
+                self.dir.display()
+            ))
+        })?;
+
+        let manifest_path = self.dir.join("Cargo.toml").display().to_string();
+        let target = self.dir.join("target").display().to_string();
+        let mut args = vec![
+            "build",
+            "--release",
+            "--quiet",
+            "--manifest-path",
+            &manifest_path,
+        ];
+        if !self.environments.install {
+            args.push("--offline");
+        }
+        self.exec("cargo", &args, &[("CARGO_TARGET_DIR", &target)])
+            .await
+    }
+
+    /// Runs one installer step inside the environment, under the skill sandbox
+    /// with the environment as its only writable workspace.
+    async fn exec(&self, program: &str, args: &[&str], env: &[(&str, &str)]) -> Result<()> {
+        let action = if self.language == ScriptLanguage::Rust {
+            "compiling the rust script".to_owned()
+        } else {
+            format!("installing {} dependencies", self.language.name())
+        };
+
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .envs(env.iter().copied())
+            .current_dir(self.dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        self.environments.sandbox.apply_tokio(
+            &mut command,
+            &SandboxPolicy {
+                workspace: self.dir.to_path_buf(),
+                workspace_writable: true,
+            },
+        )?;
+
+        let child = command.spawn().map_err(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                Error::Tool(format!(
+                    "{action} of skill '{}' needs '{program}', which was not found on PATH",
+                    self.skill
+                ))
+            } else {
+                Error::Tool(format!(
+                    "failed starting '{program}' for {action} of skill '{}': {err}",
+                    self.skill
+                ))
+            }
+        })?;
+        let output =
+            tokio::time::timeout(self.environments.install_timeout, child.wait_with_output())
+                .await
+                .map_err(|_| {
+                    Error::Timeout(format!(
+                        "{action} of skill '{}' timed out after {} seconds",
+                        self.skill,
+                        self.environments.install_timeout.as_secs()
+                    ))
+                })?
+                .map_err(|err| {
+                    Error::Tool(format!(
+                        "failed waiting for '{program}' while {action} of skill '{}': {err}",
+                        self.skill
+                    ))
+                })?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let text = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        let mut lines = text.trim().lines().collect::<Vec<_>>();
+        // Retry warnings (pip prints one per attempt) bury the actual error.
+        if lines.iter().any(|line| !line.starts_with("WARNING:")) {
+            lines.retain(|line| !line.starts_with("WARNING:"));
+        }
+        let tail = lines[lines.len().saturating_sub(ERROR_TAIL_LINES)..].join("\n");
+        let hint = if self.language == ScriptLanguage::Rust && !self.environments.install {
+            " (built with --offline because skills.install_dependencies is false; crates missing from the local cargo registry cannot be fetched)"
+        } else if self.language == ScriptLanguage::Rust {
+            ""
+        } else {
+            " (installing needs network access or a reachable package index)"
+        };
+        Err(Error::Tool(format!(
+            "{action} of skill '{}' failed{hint}: {tail}",
+            self.skill
+        )))
+    }
+}
@@ -1,20 +1,32 @@
+use super::environment::{validate_dependencies, SkillEnvironments};
 use super::registry::SkillRegistry;
 use super::types::{
-    ScriptLanguage, Skill, SkillExecutionContext, SkillKind, SkillResult, SkillSpec,
+    ScriptLanguage, Skill, SkillDependencies, SkillExecutionContext, SkillKind, SkillResult,
+    SkillSpec,
+};
+use crate::config::schema::{
+    SandboxMode, ScriptExecutionMode, SkillsConfig, TaxonomyMembershipConfig,
 };
-use crate::config::schema::{ScriptExecutionMode, SkillsConfig};
 use crate::error::{Error, Result};
+use crate::rules::frontmatter::split_frontmatter_and_body;
+use crate::sandbox::{Sandbox, SandboxPolicy};
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::{BTreeMap, VecDeque};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::Command;
 use tokio::time::timeout;
 
 const OUTPUT_CAPTURE_LIMIT_BYTES: usize = 10 * 1024;
+/// Largest bundled resource returned in full.
+const RESOURCE_READ_LIMIT_BYTES: usize = 256 * 1024;
+/// File that marks a directory as a standard skill bundle.
+const SKILL_FILE_NAME: &str = "SKILL.md";
+const MAX_SKILL_NAME_CHARS: usize = 64;
+const MAX_SKILL_DESCRIPTION_CHARS: usize = 1024;
 
 #[derive(Debug, Clone)]
 struct InstructionSkill {
@@ -50,13 +62,15 @@ impl Skill for InstructionSkill {
     }
 }
 
+/// Runs skill scripts with the configured execution mode and sandbox.
 #[derive(Debug, Clone)]
-struct ScriptSkill {
-    spec: SkillSpec,
+struct ScriptRunner {
     execution_mode: ScriptExecutionMode,
+    sandbox: Sandbox,
+    environments: Arc<SkillEnvironments>,
 }
 
-impl ScriptSkill {
+impl ScriptRunner {
     fn append_bounded(buffer: &mut String, chunk: &str) {
         let remaining = OUTPUT_CAPTURE_LIMIT_BYTES.saturating_sub(buffer.len());
         if remaining == 0 {
@@ -77,46 +91,29 @@ impl ScriptSkill {
         }
     }
 
-    fn command_for_language(language: &ScriptLanguage) -> &'static str {
-        match language {
-            ScriptLanguage::Python => "python3",
-            ScriptLanguage::JavaScript => "node",
-            ScriptLanguage::TypeScript => "tsx",
-        }
-    }
-}
-
-#[async_trait]
-impl Skill for ScriptSkill {
-    fn spec(&self) -> &SkillSpec {
-        &self.spec
-    }
-
-    async fn execute(&self, input: Value, context: &SkillExecutionContext) -> Result<SkillResult> {
+    /// Runs `path` for `spec`; `skill_name` names the script in messages.
+    async fn run(
+        &self,
+        spec: &SkillSpec,
+        skill_name: &str,
+        path: &Path,
+        language: ScriptLanguage,
+        input: Value,
+        context: &SkillExecutionContext,
+    ) -> Result<SkillResult> {
         if self.execution_mode == ScriptExecutionMode::Disabled {
             return Err(Error::Tool(format!(
-                "script skills are disabled by configuration; cannot execute '{}'",
-                self.spec.name
+                "script skills are disabled by configuration; cannot execute '{skill_name}'"
             )));
         }
-        if self.execution_mode == ScriptExecutionMode::Sandbox {
-            return Err(Error::Tool(
-                "script sandbox mode requested but not implemented yet".to_owned(),
-            ));
-        }
-
-        let (path, language) = match &self.spec.kind {
-            SkillKind::Script { path, language } => (path, language),
-            _ => {
-                return Err(Error::Tool(
-                    "internal skill kind mismatch for script skill".to_owned(),
-                ));
-            }
-        };
-
-        let mut cmd = Command::new(Self::command_for_language(language));
-        cmd.arg(path)
-            .current_dir(&context.working_directory)
+        let timeout_seconds = spec.timeout_seconds;
+
+        let launch = self
+            .environments
+            .prepare(&spec.name, language, path, &spec.dependencies)
+            .await?;
+        let mut cmd = launch.command();
+        cmd.current_dir(&context.working_directory)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -124,30 +121,34 @@ impl Skill for ScriptSkill {
         if !context.environment.is_empty() {
             cmd.envs(&context.environment);
         }
+        self.sandbox.apply_tokio(
+            &mut cmd,
+            &SandboxPolicy::for_agent(&context.working_directory, context.agent_permission_mode),
+        )?;
 
         let mut child = cmd.spawn().map_err(|err| {
             Error::Tool(format!(
-                "failed to spawn script skill '{}' using '{}': {err}",
-                self.spec.name,
+                "failed to spawn script skill '{}' using '{}' for '{}': {err}",
+                skill_name,
+                launch.program.display(),
                 path.display()
             ))
         })?;
 
         if let Some(mut stdin) = child.stdin.take() {
-            let payload = serde_json::to_vec(&input)
+            let mut payload = serde_json::to_vec(&input)
                 .map_err(|err| Error::Tool(format!("failed to serialize skill input: {err}")))?;
-            stdin
-                .write_all(&payload)
-                .await
-                .map_err(|err| Error::Tool(format!("failed writing skill input: {err}")))?;
-            stdin
-                .write_all(b"\n")
-                .await
-                .map_err(|err| Error::Tool(format!("failed finalizing skill input: {err}")))?;
-            stdin
-                .flush()
-                .await
-                .map_err(|err| Error::Tool(format!("failed flushing skill input: {err}")))?;
+            payload.push(b'\n');
+            let written = async {
+                stdin.write_all(&payload).await?;
+                stdin.flush().await
+            };
+            // A script that ignores its input may exit before reading it.
+            if let Err(err) = written.await {
+                if err.kind() != std::io::ErrorKind::BrokenPipe {
+                    return Err(Error::Tool(format!("failed writing skill input: {err}")));
+                }
+            }
         }
 
         let stdout = child
@@ -180,15 +181,14 @@ impl Skill for ScriptSkill {
         });
 
         let wait = timeout(
-            std::time::Duration::from_secs(self.spec.timeout_seconds),
+            std::time::Duration::from_secs(timeout_seconds),
             child.wait(),
         )
         .await
         .map_err(|_| {
             let _ = child.start_kill();
             Error::Tool(format!(
-                "script skill '{}' timed out after {} seconds",
-                self.spec.name, self.spec.timeout_seconds
+                "script skill '{skill_name}' timed out after {timeout_seconds} seconds"
             ))
         })?
         .map_err(|err| Error::Tool(format!("failed waiting for script skill: {err}")))?;
@@ -213,6 +213,244 @@ impl Skill for ScriptSkill {
     }
 }
 
+#[derive(Debug, Clone)]
+struct ScriptSkill {
+    spec: SkillSpec,
+    runner: ScriptRunner,
+}
+
+#[async_trait]
+impl Skill for ScriptSkill {
+    fn spec(&self) -> &SkillSpec {
+        &self.spec
+    }
+
+    async fn execute(&self, input: Value, context: &SkillExecutionContext) -> Result<SkillResult> {
+        let (path, language) = match &self.spec.kind {
+            SkillKind::Script { path, language } => (path, language),
+            _ => {
+                return Err(Error::Tool(
+                    "internal skill kind mismatch for script skill".to_owned(),
+                ));
+            }
+        };
+        self.runner
+            .run(&self.spec, &self.spec.name, path, *language, input, context)
+            .await
+    }
+}
+
+/// A `SKILL.md` skill. Invoking it returns the full instructions; bundled
+/// files are read and scripts run only when asked for.
+#[derive(Debug, Clone)]
+struct BundleSkill {
+    spec: SkillSpec,
+    runner: ScriptRunner,
+}
+
+impl BundleSkill {
+    fn bundle(&self) -> Result<(&Path, &[String], &[String])> {
+        match &self.spec.kind {
+            SkillKind::Bundle {
+                root,
+                resources,
+                scripts,
+                ..
+            } => Ok((root, resources, scripts)),
+            _ => Err(Error::Tool(
+                "internal skill kind mismatch for bundle skill".to_owned(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Skill for BundleSkill {
+    fn spec(&self) -> &SkillSpec {
+        &self.spec
+    }
+
+    async fn execute(&self, input: Value, context: &SkillExecutionContext) -> Result<SkillResult> {
+        if let SkillKind::Bundle {
+            entrypoint: Some(entrypoint),
+            ..
+        } = &self.spec.kind
+        {
+            return self.run_script(entrypoint, input, context).await;
+        }
+        let (root, resources, scripts) = self.bundle()?;
+        let path = root.join(SKILL_FILE_NAME);
+        let content = tokio::fs::read_to_string(&path).await.map_err(|err| {
+            Error::Tool(format!("failed reading skill '{}': {err}", path.display()))
+        })?;
+        let (_, instructions) = split_frontmatter_and_body(&content)?;
+        let payload = json!({
+            "skill": self.spec.name,
+            "description": self.spec.description,
+            "input": input,
+            "instruction": instructions.trim(),
+            "resources": resources,
+            "scripts": scripts,
+            "allowed_tools": self.spec.allowed_tools,
+        });
+        Ok(SkillResult {
+            success: true,
+            output: payload.to_string(),
+            exit_code: Some(0),
+        })
+    }
+
+    async fn read_resource(&self, path: &str) -> Result<SkillResult> {
+        let (root, resources, _) = self.bundle()?;
+        if !resources.iter().any(|resource| resource == path) {
+            return Err(Error::NotFound(format!(
+                "skill '{}' has no bundled resource '{path}'",
+                self.spec.name
+            )));
+        }
+        let bytes = tokio::fs::read(root.join(path)).await.map_err(|err| {
+            Error::Tool(format!(
+                "failed reading resource '{path}' of skill '{}': {err}",
+                self.spec.name
+            ))
+        })?;
+        let mut output =
+            String::from_utf8_lossy(&bytes[..bytes.len().min(RESOURCE_READ_LIMIT_BYTES)])
+                .into_owned();
+        if bytes.len() > RESOURCE_READ_LIMIT_BYTES {
+            output.push_str(&format!(
+                "\n[truncated at {RESOURCE_READ_LIMIT_BYTES} of {} bytes]",
+                bytes.len()
+            ));
+        }
+        Ok(SkillResult {
+            success: true,
+            output,
+            exit_code: Some(0),
+        })
+    }
+
+    async fn run_script(
+        &self,
+        path: &str,
+        input: Value,
+        context: &SkillExecutionContext,
+    ) -> Result<SkillResult> {
+        let (root, _, scripts) = self.bundle()?;
+        let language = scripts
+            .iter()
+            .any(|script| script == path)
+            .then(|| Path::new(path).extension().and_then(|ext| ext.to_str()))
+            .flatten()
+            .and_then(ScriptLanguage::from_extension)
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "skill '{}' has no bundled script '{path}'",
+                    self.spec.name
+                ))
+            })?;
+        self.runner
+            .run(
+                &self.spec,
+                &format!("{}/{path}", self.spec.name),
+                &root.join(path),
+                language,
+                input,
+                context,
+            )
+            .await
+    }
+}
+
+/// Fields of a `SKILL.md` header. Unknown keys such as `license` or
+/// `metadata` are ignored.
+#[derive(Debug, Deserialize)]
+struct SkillFrontmatter {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default, alias = "allowed-tools")]
+    allowed_tools: Option<AllowedTools>,
+    #[serde(default, alias = "input-schema")]
+    input_schema: Option<Value>,
+    #[serde(default, alias = "taxonomy_membership")]
+    taxonomy: Vec<TaxonomyMembershipConfig>,
+    #[serde(default, alias = "timeout-seconds")]
+    timeout_seconds: Option<u64>,
+    #[serde(default)]
+    dependencies: SkillDependencies,
+    /// Bundled script run when the skill is invoked.
+    #[serde(default)]
+    entrypoint: Option<String>,
+}
+
+/// `allowed-tools` is space-delimited in the standard; a list also works.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AllowedTools {
+    Listed(Vec<String>),
+    Spaced(String),
+}
+
+impl AllowedTools {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::Listed(tools) => tools,
+            Self::Spaced(tools) => tools.split_whitespace().map(ToOwned::to_owned).collect(),
+        }
+    }
+}
+
+/// Something the loader found: a loose file or a `SKILL.md` directory.
+enum SkillSource {
+    File(PathBuf),
+    Bundle(PathBuf),
+}
+
+impl SkillSource {
+    fn path(&self) -> &Path {
+        match self {
+            Self::File(path) | Self::Bundle(path) => path,
+        }
+    }
+}
+
+/// Whether `dir` is a skill bundle. Rule and context discovery skip these so
+/// skill instructions are only loaded when the skill is invoked.
+pub(crate) fn is_skill_bundle(dir: &Path) -> bool {
+    dir.join(SKILL_FILE_NAME).is_file()
+}
+
+/// Loads `SKILL.md` bundles written after startup, such as skills authored
+/// by agents, with the same runner as the initial load.
+#[derive(Clone)]
+pub struct BundleLoader {
+    config: SkillsConfig,
+    runner: ScriptRunner,
+    authoring_dir: PathBuf,
+}
+
+impl BundleLoader {
+    pub(crate) fn load(&self, root: &Path) -> Result<Arc<dyn Skill>> {
+        let spec = SkillLoader::bundle_spec(root, &self.config, self.runner.execution_mode)?;
+        Ok(Arc::new(BundleSkill {
+            spec,
+            runner: self.runner.clone(),
+        }))
+    }
+
+    /// Where new skills are written: `skills.authoring_directory`, resolved
+    /// against the working directory.
+    pub(crate) fn authoring_dir(&self) -> &Path {
+        &self.authoring_dir
+    }
+
+    pub(crate) fn scripts_enabled(&self) -> bool {
+        self.runner.execution_mode != ScriptExecutionMode::Disabled
+    }
+}
+
 pub struct SkillLoader;
 
 impl SkillLoader {
@@ -230,8 +468,8 @@ impl SkillLoader {
         }
     }
 
-    fn discover_files(config: &SkillsConfig, base: &Path) -> Vec<PathBuf> {
-        let mut files = Vec::new();
+    fn discover(config: &SkillsConfig, base: &Path) -> Vec<SkillSource> {
+        let mut sources = Vec::new();
         for dir in &config.directories {
             let root = Self::resolve_dir(dir, base);
             if !root.exists() || !root.is_dir() {
@@ -241,6 +479,11 @@ impl SkillLoader {
             let mut queue = VecDeque::new();
             queue.push_back((root, 0usize));
             while let Some((current, depth)) = queue.pop_front() {
+                // A directory with a SKILL.md is one skill; its files belong to it.
+                if is_skill_bundle(&current) {
+                    sources.push(SkillSource::Bundle(current));
+                    continue;
+                }
                 let entries = match std::fs::read_dir(&current) {
                     Ok(v) => v,
                     Err(_) => continue,
@@ -257,16 +500,166 @@ impl SkillLoader {
                         continue;
                     }
                     if file_type.is_file() {
-                        files.push(path);
+                        sources.push(SkillSource::File(path));
                     }
                 }
             }
         }
 
+        sources.sort_by(|a, b| a.path().cmp(b.path()));
+        sources
+    }
+
+    /// Relative paths of the files bundled with a skill, excluding `SKILL.md`
+    /// and hidden entries.
+    fn bundle_files(root: &Path, max_depth: usize) -> Vec<String> {
+        let mut files = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((root.to_path_buf(), 0usize));
+        while let Some((current, depth)) = queue.pop_front() {
+            let Ok(entries) = std::fs::read_dir(&current) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    if depth < max_depth {
+                        queue.push_back((path, depth + 1));
+                    }
+                    continue;
+                }
+                if !file_type.is_file() || path == root.join(SKILL_FILE_NAME) {
+                    continue;
+                }
+                if let Ok(relative) = path.strip_prefix(root) {
+                    let relative = relative
+                        .components()
+                        .map(|part| part.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    files.push(relative);
+                }
+            }
+        }
         files.sort();
         files
     }
 
+    fn bundle_spec(
+        root: &Path,
+        config: &SkillsConfig,
+        mode: ScriptExecutionMode,
+    ) -> Result<SkillSpec> {
+        let path = root.join(SKILL_FILE_NAME);
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            Error::Config(format!("failed reading skill '{}': {err}", path.display()))
+        })?;
+        let (frontmatter, _) = split_frontmatter_and_body(&content)
+            .map_err(|err| Error::Config(format!("invalid skill '{}': {err}", path.display())))?;
+        let header: SkillFrontmatter = serde_yaml::from_str(&frontmatter).map_err(|err| {
+            Error::Config(format!(
+                "invalid SKILL.md frontmatter in '{}': {err}",
+                path.display()
+            ))
+        })?;
+
+        let name = header.name.trim();
+        if name.is_empty()
+            || name.len() > MAX_SKILL_NAME_CHARS
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+        {
+            return Err(Error::Config(format!(
+                "skill '{}' needs a name of at most {MAX_SKILL_NAME_CHARS} lowercase letters, digits or hyphens",
+                path.display()
+            )));
+        }
+        let description = header.description.trim();
+        if description.is_empty() || description.chars().count() > MAX_SKILL_DESCRIPTION_CHARS {
+            return Err(Error::Config(format!(
+                "skill '{}' needs a description of 1 to {MAX_SKILL_DESCRIPTION_CHARS} characters",
+                path.display()
+            )));
+        }
+        validate_dependencies(&header.dependencies)
+            .map_err(|err| Error::Config(format!("skill '{}': {err}", path.display())))?;
+        if let Some(schema) = &header.input_schema {
+            if !schema.is_object() {
+                return Err(Error::Config(format!(
+                    "input_schema of skill '{}' must be an object",
+                    path.display()
+                )));
+            }
+        }
+
+        let resources = Self::bundle_files(root, config.max_discovery_depth);
+        // Scripts stay readable as resources when execution is disabled.
+        let scripts = if mode == ScriptExecutionMode::Disabled {
+            Vec::new()
+        } else {
+            resources
+                .iter()
+                .filter(|file| {
+                    Path::new(file)
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .and_then(ScriptLanguage::from_extension)
+                        .is_some()
+                })
+                .cloned()
+                .collect()
+        };
+        if let Some(entrypoint) = &header.entrypoint {
+            let is_script = Path::new(entrypoint)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(ScriptLanguage::from_extension)
+                .is_some();
+            if !is_script || !resources.contains(entrypoint) {
+                return Err(Error::Config(format!(
+                    "entrypoint '{entrypoint}' of skill '{}' must be a bundled script",
+                    path.display()
+                )));
+            }
+        }
+        let root = std::fs::canonicalize(root).map_err(|err| {
+            Error::Config(format!(
+                "failed canonicalizing skill '{}': {err}",
+                root.display()
+            ))
+        })?;
+
+        Ok(SkillSpec {
+            name: format!("skill.{name}"),
+            description: description.to_owned(),
+            schema: header
+                .input_schema
+                .unwrap_or_else(|| json!({"type": "object", "additionalProperties": true})),
+            timeout_seconds: header
+                .timeout_seconds
+                .unwrap_or(config.default_timeout_seconds),
+            kind: SkillKind::Bundle {
+                root,
+                resources,
+                scripts,
+                entrypoint: header.entrypoint,
+            },
+            taxonomy_membership: header.taxonomy,
+            allowed_tools: header
+                .allowed_tools
+                .map(AllowedTools::into_vec)
+                .unwrap_or_default(),
+            dependencies: header.dependencies,
+        })
+    }
+
     fn stem_name(path: &Path) -> Option<String> {
         path.file_stem()
             .and_then(|value| value.to_str())
@@ -298,6 +691,8 @@ impl SkillLoader {
             timeout_seconds,
             kind: SkillKind::Instruction { content },
             taxonomy_membership: Vec::new(),
+            allowed_tools: Vec::new(),
+            dependencies: SkillDependencies::default(),
         }))
     }
 
@@ -310,11 +705,8 @@ impl SkillLoader {
             return Ok(None);
         };
 
-        let language = match ext {
-            "py" => ScriptLanguage::Python,
-            "js" => ScriptLanguage::JavaScript,
-            "ts" => ScriptLanguage::TypeScript,
-            _ => return Ok(None),
+        let Some(language) = ScriptLanguage::from_extension(ext) else {
+            return Ok(None);
         };
 
         if mode == ScriptExecutionMode::Disabled {
@@ -342,23 +734,58 @@ impl SkillLoader {
                 language,
             },
             taxonomy_membership: Vec::new(),
+            allowed_tools: Vec::new(),
+            dependencies: SkillDependencies::default(),
         }))
     }
 
-    pub fn load(config: &SkillsConfig, work_dir: &Path) -> Result<SkillRegistry> {
-        let files = Self::discover_files(config, work_dir);
-        let mut registry = SkillRegistry::new();
+    pub fn load(
+        config: &SkillsConfig,
+        work_dir: &Path,
+        sandbox: &Sandbox,
+    ) -> Result<SkillRegistry> {
+        let sources = Self::discover(config, work_dir);
+        // `script_execution_mode = sandbox` refuses to run scripts unconfined.
+        let script_sandbox = match config.script_execution_mode {
+            ScriptExecutionMode::Sandbox => sandbox.with_mode(SandboxMode::Required),
+            _ => sandbox.clone(),
+        };
+        let environments = SkillEnvironments::new(
+            config,
+            Self::resolve_dir(&config.environments_dir, work_dir),
+            script_sandbox.clone(),
+        );
+        let runner = ScriptRunner {
+            execution_mode: config.script_execution_mode,
+            sandbox: script_sandbox,
+            environments: Arc::new(environments),
+        };
+        let registry = SkillRegistry::with_bundle_loader(BundleLoader {
+            config: config.clone(),
+            runner: runner.clone(),
+            authoring_dir: Self::resolve_dir(&config.authoring_directory, work_dir),
+        });
 
         let mut names = BTreeMap::<String, PathBuf>::new();
-        for file in files {
-            let spec = match Self::instruction_spec(&file, config.default_timeout_seconds)? {
-                Some(v) => Some(v),
-                None => Self::script_spec(
-                    &file,
-                    config.default_timeout_seconds,
+        for source in sources {
+            let spec = match &source {
+                SkillSource::Bundle(root) => Some(Self::bundle_spec(
+                    root,
+                    config,
                     config.script_execution_mode,
-                )?,
+                )?),
+                SkillSource::File(file) => {
+                    match Self::instruction_spec(file, config.default_timeout_seconds)? {
+                        Some(v) => Some(v),
+                        None => Self::script_spec(
+                            file,
+                            config.default_timeout_seconds,
+                            config.script_execution_mode,
+                        )?,
+                    }
+                }
             };
+            let file = source.path();
 
             let Some(spec) = spec else {
                 continue;
@@ -372,13 +799,17 @@ impl SkillLoader {
                     file.display()
                 )));
             }
-            names.insert(spec.name.clone(), file.clone());
+            names.insert(spec.name.clone(), file.to_path_buf());
 
             let skill: Arc<dyn Skill> = match spec.kind {
                 SkillKind::Instruction { .. } => Arc::new(InstructionSkill { spec }),
                 SkillKind::Script { .. } => Arc::new(ScriptSkill {
                     spec,
-                    execution_mode: config.script_execution_mode,
+                    runner: runner.clone(),
+                }),
+                SkillKind::Bundle { .. } => Arc::new(BundleSkill {
+                    spec,
+                    runner: runner.clone(),
                 }),
             };
             registry.register(skill.spec().name.clone(), skill);
@@ -1,7 +1,10 @@
+pub mod environment;
 pub mod loader;
 pub mod registry;
 pub mod types;
 
 pub use loader::SkillLoader;
 pub use registry::SkillRegistry;
-pub use types::{ScriptLanguage, SkillExecutionContext, SkillKind, SkillResult, SkillSpec};
+pub use types::{
+    ScriptLanguage, SkillDependencies, SkillExecutionContext, SkillKind, SkillResult, SkillSpec,
+};
@@ -1,10 +1,17 @@
+use super::loader::BundleLoader;
 use super::types::Skill;
+use crate::error::{Error, Result};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 
+/// Loaded skills. Skills can be added after startup, so lookups go through a
+/// lock.
 #[derive(Default)]
 pub struct SkillRegistry {
-    skills: HashMap<String, Arc<dyn Skill>>,
+    skills: RwLock<HashMap<String, Arc<dyn Skill>>>,
+    /// Set when skills are enabled; loads bundles written at runtime.
+    bundles: Option<BundleLoader>,
 }
 
 impl SkillRegistry {
@@ -12,17 +19,57 @@ impl SkillRegistry {
         Self::default()
     }
 
-    pub fn register(&mut self, name: String, skill: Arc<dyn Skill>) {
-        self.skills.insert(name, skill);
+    pub(super) fn with_bundle_loader(bundles: BundleLoader) -> Self {
+        Self {
+            skills: RwLock::default(),
+            bundles: Some(bundles),
+        }
+    }
+
+    pub fn register(&self, name: String, skill: Arc<dyn Skill>) {
+        if let Ok(mut skills) = self.skills.write() {
+            skills.insert(name, skill);
+        }
     }
 
     pub fn get(&self, name: &str) -> Option<Arc<dyn Skill>> {
-        self.skills.get(name).cloned()
+        self.skills
+            .read()
+            .ok()
+            .and_then(|skills| skills.get(name).cloned())
     }
 
     pub fn list(&self) -> Vec<String> {
-        let mut names: Vec<String> = self.skills.keys().cloned().collect();
+        let mut names: Vec<String> = self
+            .skills
+            .read()
+            .map(|skills| skills.keys().cloned().collect())
+            .unwrap_or_default();
         names.sort();
         names
     }
+
+    pub(crate) fn bundle_loader(&self) -> Option<&BundleLoader> {
+        self.bundles.as_ref()
+    }
+
+    /// Loads the `SKILL.md` bundle at `root` and registers it. Fails when
+    /// skills are disabled or the name is taken.
+    pub fn load_bundle(&self, root: &Path) -> Result<Arc<dyn Skill>> {
+        let bundles = self
+            .bundles
+            .as_ref()
+            .ok_or_else(|| Error::Config("skills are disabled".to_owned()))?;
+        let skill = bundles.load(root)?;
+        let name = skill.spec().name.clone();
+        let mut skills = self
+            .skills
+            .write()
+            .map_err(|_| Error::Tool("skill registry lock poisoned".to_owned()))?;
+        if skills.contains_key(&name) {
+            return Err(Error::Validation(format!("skill '{name}' already exists")));
+        }
+        skills.insert(name, skill.clone());
+        Ok(skill)
+    }
 }
@@ -1,15 +1,133 @@
-use crate::config::schema::TaxonomyMembershipConfig;
-use crate::error::Result;
+use crate::config::schema::{AgentPermissionMode, TaxonomyMembershipConfig};
+use crate::error::{Error, Result};
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScriptLanguage {
     Python,
     JavaScript,
     TypeScript,
+    Shell,
+    Bash,
+    Ruby,
+    Lua,
+    /// Compiled with cargo into the skill's environment before the first run.
+    Rust,
+}
+
+impl ScriptLanguage {
+    const ALL: [Self; 8] = [
+        Self::Python,
+        Self::JavaScript,
+        Self::TypeScript,
+        Self::Shell,
+        Self::Bash,
+        Self::Ruby,
+        Self::Lua,
+        Self::Rust,
+    ];
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "py" => Some(Self::Python),
+            "js" => Some(Self::JavaScript),
+            "ts" => Some(Self::TypeScript),
+            "sh" => Some(Self::Shell),
+            "bash" => Some(Self::Bash),
+            "rb" => Some(Self::Ruby),
+            "lua" => Some(Self::Lua),
+            "rs" => Some(Self::Rust),
+            _ => None,
+        }
+    }
+
+    /// Name used in `skills.interpreters` and error messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+            Self::Shell => "shell",
+            Self::Bash => "bash",
+            Self::Ruby => "ruby",
+            Self::Lua => "lua",
+            Self::Rust => "rust",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.name() == name)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Python => "py",
+            Self::JavaScript => "js",
+            Self::TypeScript => "ts",
+            Self::Shell => "sh",
+            Self::Bash => "bash",
+            Self::Ruby => "rb",
+            Self::Lua => "lua",
+            Self::Rust => "rs",
+        }
+    }
+
+    /// Interpreter used when `skills.interpreters` has no entry.
+    pub fn default_command(self) -> &'static str {
+        match self {
+            Self::Python => "python3",
+            Self::JavaScript => "node",
+            Self::TypeScript => "tsx",
+            Self::Shell => "sh",
+            Self::Bash => "bash",
+            Self::Ruby => "ruby",
+            Self::Lua => "lua",
+            Self::Rust => "cargo",
+        }
+    }
+}
+
+/// Packages a skill needs, per ecosystem, as declared in the `dependencies`
+/// key of its `SKILL.md`. They are installed into an environment of the skill
+/// on first use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SkillDependencies {
+    /// pip requirement specifiers, e.g. `requests>=2.31`.
+    #[serde(default)]
+    pub python: Vec<String>,
+    /// npm package specs for JavaScript and TypeScript, e.g. `lodash@4`.
+    #[serde(default)]
+    pub node: Vec<String>,
+    /// Gems, optionally pinned as `name:version`.
+    #[serde(default)]
+    pub ruby: Vec<String>,
+    /// Rocks, optionally followed by a version, e.g. `lpeg 1.1.0`.
+    #[serde(default)]
+    pub lua: Vec<String>,
+    /// Crates as `name` or `name@version-requirement`.
+    #[serde(default)]
+    pub rust: Vec<String>,
+}
+
+impl SkillDependencies {
+    /// The packages scripts in `language` need. Shell scripts have none.
+    pub fn for_language(&self, language: ScriptLanguage) -> &[String] {
+        match language {
+            ScriptLanguage::Python => &self.python,
+            ScriptLanguage::JavaScript | ScriptLanguage::TypeScript => &self.node,
+            ScriptLanguage::Ruby => &self.ruby,
+            ScriptLanguage::Lua => &self.lua,
+            ScriptLanguage::Rust => &self.rust,
+            ScriptLanguage::Shell | ScriptLanguage::Bash => &[],
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +139,15 @@ pub enum SkillKind {
         path: PathBuf,
         language: ScriptLanguage,
     },
+    /// A directory with a `SKILL.md`. Instructions are read when the skill
+    /// is invoked; bundled files are listed relative to `root`. With an
+    /// `entrypoint`, invoking the skill runs that script instead.
+    Bundle {
+        root: PathBuf,
+        resources: Vec<String>,
+        scripts: Vec<String>,
+        entrypoint: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +158,9 @@ pub struct SkillSpec {
     pub timeout_seconds: u64,
     pub kind: SkillKind,
     pub taxonomy_membership: Vec<TaxonomyMembershipConfig>,
+    /// Tools pre-approved for the session once the skill has been invoked.
+    pub allowed_tools: Vec<String>,
+    pub dependencies: SkillDependencies,
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +174,7 @@ pub struct SkillResult {
 pub struct SkillExecutionContext {
     pub working_directory: PathBuf,
     pub environment: BTreeMap<String, String>,
+    pub agent_permission_mode: AgentPermissionMode,
 }
 
 #[async_trait]
@@ -51,4 +182,25 @@ pub trait Skill: Send + Sync {
     fn spec(&self) -> &SkillSpec;
 
     async fn execute(&self, input: Value, context: &SkillExecutionContext) -> Result<SkillResult>;
+
+    /// Reads a bundled file, for skills that have them.
+    async fn read_resource(&self, path: &str) -> Result<SkillResult> {
+        Err(Error::Tool(format!(
+            "skill '{}' has no bundled resource '{path}'",
+            self.spec().name
+        )))
+    }
+
+    /// Runs a bundled script with `input`, for skills that have them.
+    async fn run_script(
+        &self,
+        path: &str,
+        _input: Value,
+        _context: &SkillExecutionContext,
+    ) -> Result<SkillResult> {
+        Err(Error::Tool(format!(
+            "skill '{}' has no bundled script '{path}'",
+            self.spec().name
+        )))
+    }
 }
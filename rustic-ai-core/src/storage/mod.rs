@@ -55,6 +55,7 @@ pub trait StorageBackend: Send + Sync {
         &self,
         session_id: Uuid,
     ) -> Result<Option<PendingToolState>>;
+    async fn get_pending_tool(&self, session_id: Uuid) -> Result<Option<PendingToolState>>;
     async fn delete_stale_pending_tools(&self, older_than_secs: u64) -> Result<usize>;
     async fn has_pending_tool(&self, session_id: Uuid) -> Result<bool>;
 
@@ -133,6 +134,8 @@ pub trait StorageBackend: Send + Sync {
         edges: &[CallEdge],
     ) -> Result<()>;
     async fn list_code_call_edges(&self, workspace: &str) -> Result<Vec<IndexedCallEdgeRecord>>;
+    /// Drops the file, symbol, call-edge and `file:` embedding rows for one path.
+    async fn delete_code_file_index(&self, workspace: &str, path: &str) -> Result<()>;
 
     // Vector storage
     async fn upsert_vector_embedding(
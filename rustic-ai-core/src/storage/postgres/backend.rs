@@ -94,6 +94,11 @@ const SCHEMA_V8_MIGRATION: [&str; 3] = [
     "UPDATE schema_version SET version = 8",
 ];
 
+const SCHEMA_V9_MIGRATION: [&str; 2] = [
+    "ALTER TABLE success_patterns ADD COLUMN IF NOT EXISTS skill_name TEXT",
+    "UPDATE schema_version SET version = 9",
+];
+
 #[derive(Debug, Clone)]
 pub struct PostgresStorage {
     pool: sqlx::PgPool,
@@ -189,6 +194,12 @@ impl PostgresStorage {
                     }
                 }
 
+                if current_version < 9 {
+                    for statement in SCHEMA_V9_MIGRATION {
+                        sqlx::query(statement).execute(&self.pool).await?;
+                    }
+                }
+
                 Ok::<(), sqlx::Error>(())
             })
             .await
@@ -196,6 +207,28 @@ impl PostgresStorage {
             .map(|_| ())
     }
 
+    fn parse_pending_tool(row: &PgRow) -> Result<PendingToolState> {
+        let tool_messages_json = row.get::<String, _>("tool_messages_json");
+        let context_snapshot_json = row.get::<String, _>("context_snapshot_json");
+        let args_json = row.get::<String, _>("args_json");
+        let session_id_str = row.get::<String, _>("session_id");
+        let created_at_str = row.get::<String, _>("created_at");
+
+        Ok(PendingToolState {
+            session_id: Uuid::parse_str(&session_id_str).map_err(|err| {
+                Error::Storage(format!(
+                    "invalid session uuid in pending tool state '{session_id_str}': {err}"
+                ))
+            })?,
+            tool_name: row.get::<String, _>("tool_name"),
+            args: serde_json::from_str(&args_json)?,
+            round_index: row.get::<i64, _>("round_index") as usize,
+            tool_messages: serde_json::from_str(&tool_messages_json)?,
+            context_snapshot: serde_json::from_str(&context_snapshot_json)?,
+            created_at: Self::parse_timestamp(&created_at_str)?,
+        })
+    }
+
     fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
         DateTime::parse_from_rfc3339(value)
             .map(|timestamp| timestamp.with_timezone(&Utc))
@@ -603,16 +636,7 @@ impl StorageBackend for PostgresStorage {
         &self,
         session_id: Uuid,
     ) -> Result<Option<PendingToolState>> {
-        self.ensure_initialized().await?;
-
-        let row = sqlx::query(
-            "SELECT session_id, tool_name, args_json, round_index, tool_messages_json, context_snapshot_json, created_at FROM pending_tools WHERE session_id = $1",
-        )
-        .bind(session_id.to_string())
-        .fetch_optional(&self.pool)
-        .await?;
-
-        let Some(row) = row else {
+        let Some(state) = self.get_pending_tool(session_id).await? else {
             return Ok(None);
         };
 
@@ -621,25 +645,19 @@ impl StorageBackend for PostgresStorage {
             .execute(&self.pool)
             .await?;
 
-        let tool_messages_json = row.get::<String, _>("tool_messages_json");
-        let context_snapshot_json = row.get::<String, _>("context_snapshot_json");
-        let args_json = row.get::<String, _>("args_json");
-        let session_id_str = row.get::<String, _>("session_id");
-        let created_at_str = row.get::<String, _>("created_at");
+        Ok(Some(state))
+    }
 
-        Ok(Some(PendingToolState {
-            session_id: Uuid::parse_str(&session_id_str).map_err(|err| {
-                Error::Storage(format!(
-                    "invalid session uuid in pending tool state '{session_id_str}': {err}"
-                ))
-            })?,
-            tool_name: row.get::<String, _>("tool_name"),
-            args: serde_json::from_str(&args_json)?,
-            round_index: row.get::<i64, _>("round_index") as usize,
-            tool_messages: serde_json::from_str(&tool_messages_json)?,
-            context_snapshot: serde_json::from_str(&context_snapshot_json)?,
-            created_at: Self::parse_timestamp(&created_at_str)?,
-        }))
+    async fn get_pending_tool(&self, session_id: Uuid) -> Result<Option<PendingToolState>> {
+        self.ensure_initialized().await?;
+
+        let row = sqlx::query(
+            "SELECT session_id, tool_name, args_json, round_index, tool_messages_json, context_snapshot_json, created_at FROM pending_tools WHERE session_id = $1",
+        )
+        .bind(session_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(Self::parse_pending_tool).transpose()
     }
 
     async fn delete_stale_pending_tools(&self, older_than_secs: u64) -> Result<usize> {
@@ -841,7 +859,7 @@ impl StorageBackend for PostgresStorage {
     async fn upsert_success_pattern(&self, pattern: &SuccessPattern) -> Result<()> {
         self.ensure_initialized().await?;
 
-        sqlx::query("INSERT INTO success_patterns(id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT(id) DO UPDATE SET name = EXCLUDED.name, category = EXCLUDED.category, description = EXCLUDED.description, template = EXCLUDED.template, frequency = EXCLUDED.frequency, last_used = EXCLUDED.last_used, success_rate = EXCLUDED.success_rate")
+        sqlx::query("INSERT INTO success_patterns(id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) ON CONFLICT(id) DO UPDATE SET name = EXCLUDED.name, category = EXCLUDED.category, description = EXCLUDED.description, template = EXCLUDED.template, frequency = EXCLUDED.frequency, last_used = EXCLUDED.last_used, success_rate = EXCLUDED.success_rate, skill_name = EXCLUDED.skill_name")
             .bind(pattern.id.to_string())
             .bind(&pattern.agent_name)
             .bind(&pattern.name)
@@ -852,6 +870,7 @@ impl StorageBackend for PostgresStorage {
             .bind(pattern.last_used.to_rfc3339())
             .bind(pattern.success_rate)
             .bind(pattern.created_at.to_rfc3339())
+            .bind(&pattern.skill_name)
             .execute(&self.pool)
             .await?;
 
@@ -870,7 +889,7 @@ impl StorageBackend for PostgresStorage {
         let search = query.map(|value| format!("%{}%", value.to_ascii_lowercase()));
         let rows = match (category, search.as_ref()) {
             (Some(category), Some(search)) => {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = $1 AND category = $2 AND (LOWER(name) LIKE $3 OR LOWER(description) LIKE $3 OR LOWER(template) LIKE $3) ORDER BY frequency DESC, last_used DESC LIMIT $4")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = $1 AND category = $2 AND (LOWER(name) LIKE $3 OR LOWER(description) LIKE $3 OR LOWER(template) LIKE $3) ORDER BY frequency DESC, last_used DESC LIMIT $4")
                     .bind(agent_name)
                     .bind(category.as_str())
                     .bind(search)
@@ -879,7 +898,7 @@ impl StorageBackend for PostgresStorage {
                     .await?
             }
             (Some(category), None) => {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = $1 AND category = $2 ORDER BY frequency DESC, last_used DESC LIMIT $3")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = $1 AND category = $2 ORDER BY frequency DESC, last_used DESC LIMIT $3")
                     .bind(agent_name)
                     .bind(category.as_str())
                     .bind(limit as i64)
@@ -887,7 +906,7 @@ impl StorageBackend for PostgresStorage {
                     .await?
             }
             (None, Some(search)) => {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = $1 AND (LOWER(name) LIKE $2 OR LOWER(description) LIKE $2 OR LOWER(template) LIKE $2) ORDER BY frequency DESC, last_used DESC LIMIT $3")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = $1 AND (LOWER(name) LIKE $2 OR LOWER(description) LIKE $2 OR LOWER(template) LIKE $2) ORDER BY frequency DESC, last_used DESC LIMIT $3")
                     .bind(agent_name)
                     .bind(search)
                     .bind(limit as i64)
@@ -895,7 +914,7 @@ impl StorageBackend for PostgresStorage {
                     .await?
             }
             (None, None) => {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = $1 ORDER BY frequency DESC, last_used DESC LIMIT $2")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = $1 ORDER BY frequency DESC, last_used DESC LIMIT $2")
                     .bind(agent_name)
                     .bind(limit as i64)
                     .fetch_all(&self.pool)
@@ -922,6 +941,7 @@ impl StorageBackend for PostgresStorage {
                 last_used: Self::parse_timestamp(&last_used)?,
                 success_rate: row.get::<f64, _>("success_rate"),
                 created_at: Self::parse_timestamp(&created_at)?,
+                skill_name: row.get::<Option<String>, _>("skill_name"),
             });
         }
 
@@ -1124,6 +1144,38 @@ impl StorageBackend for PostgresStorage {
         Ok(())
     }
 
+    async fn delete_code_file_index(&self, workspace: &str, path: &str) -> Result<()> {
+        self.ensure_initialized().await?;
+        let embedding_id = format!("file:{path}");
+        let mut tx = self.pool.begin().await?;
+        for (statement, key) in [
+            (
+                "DELETE FROM code_file_indexes WHERE workspace = $1 AND path = $2",
+                path,
+            ),
+            (
+                "DELETE FROM code_symbol_indexes WHERE workspace = $1 AND file_path = $2",
+                path,
+            ),
+            (
+                "DELETE FROM code_call_edges WHERE workspace = $1 AND file_path = $2",
+                path,
+            ),
+            (
+                "DELETE FROM vector_embeddings WHERE workspace = $1 AND id = $2",
+                embedding_id.as_str(),
+            ),
+        ] {
+            sqlx::query(statement)
+                .bind(workspace)
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn list_code_call_edges(&self, workspace: &str) -> Result<Vec<IndexedCallEdgeRecord>> {
         self.ensure_initialized().await?;
         let rows = sqlx::query("SELECT caller_symbol, callee_symbol, file_path, line, column_number, updated_at FROM code_call_edges WHERE workspace = $1 ORDER BY file_path ASC, line ASC")
@@ -95,6 +95,11 @@ const SCHEMA_V8_MIGRATION: [&str; 3] = [
     "UPDATE schema_version SET version = 8",
 ];
 
+const SCHEMA_V9_MIGRATION: [&str; 2] = [
+    "ALTER TABLE success_patterns ADD COLUMN skill_name TEXT",
+    "UPDATE schema_version SET version = 9",
+];
+
 #[derive(Debug, Clone)]
 pub struct SqliteStorage {
     pool: sqlx::SqlitePool,
@@ -181,6 +186,12 @@ impl SqliteStorage {
                     }
                 }
 
+                if current_version < 9 {
+                    for statement in SCHEMA_V9_MIGRATION {
+                        sqlx::query(statement).execute(&self.pool).await?;
+                    }
+                }
+
                 Ok::<(), sqlx::Error>(())
             })
             .await
@@ -266,6 +277,28 @@ impl SqliteStorage {
         }
     }
 
+    fn parse_pending_tool(row: &SqliteRow) -> Result<PendingToolState> {
+        let tool_messages_json = row.get::<String, _>("tool_messages_json");
+        let context_snapshot_json = row.get::<String, _>("context_snapshot_json");
+        let args_json = row.get::<String, _>("args_json");
+        let session_id_str = row.get::<String, _>("session_id");
+        let created_at_str = row.get::<String, _>("created_at");
+
+        Ok(PendingToolState {
+            session_id: Uuid::parse_str(&session_id_str).map_err(|err| {
+                Error::Storage(format!(
+                    "invalid session uuid in pending tool state '{session_id_str}': {err}"
+                ))
+            })?,
+            tool_name: row.get::<String, _>("tool_name"),
+            args: serde_json::from_str(&args_json)?,
+            round_index: row.get::<i64, _>("round_index") as usize,
+            tool_messages: serde_json::from_str(&tool_messages_json)?,
+            context_snapshot: serde_json::from_str(&context_snapshot_json)?,
+            created_at: Self::parse_timestamp(&created_at_str)?,
+        })
+    }
+
     fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
         DateTime::parse_from_rfc3339(value)
             .map(|timestamp| timestamp.with_timezone(&Utc))
@@ -700,44 +733,28 @@ impl StorageBackend for SqliteStorage {
         &self,
         session_id: Uuid,
     ) -> Result<Option<PendingToolState>> {
-        self.ensure_initialized().await?;
-
-        let row = sqlx::query(
-            "SELECT session_id, tool_name, args_json, round_index, tool_messages_json, context_snapshot_json, created_at FROM pending_tools WHERE session_id = ?",
-        )
-        .bind(session_id.to_string())
-        .fetch_optional(&self.pool)
-        .await?;
-
-        let Some(row) = row else {
+        let Some(state) = self.get_pending_tool(session_id).await? else {
             return Ok(None);
         };
 
-        // Delete the pending tool state
         sqlx::query("DELETE FROM pending_tools WHERE session_id = ?")
             .bind(session_id.to_string())
             .execute(&self.pool)
             .await?;
 
-        let tool_messages_json = row.get::<String, _>("tool_messages_json");
-        let context_snapshot_json = row.get::<String, _>("context_snapshot_json");
-        let args_json = row.get::<String, _>("args_json");
-        let session_id_str = row.get::<String, _>("session_id");
-        let created_at_str = row.get::<String, _>("created_at");
+        Ok(Some(state))
+    }
 
-        Ok(Some(PendingToolState {
-            session_id: Uuid::parse_str(&session_id_str).map_err(|err| {
-                Error::Storage(format!(
-                    "invalid session uuid in pending tool state '{session_id_str}': {err}"
-                ))
-            })?,
-            tool_name: row.get::<String, _>("tool_name"),
-            args: serde_json::from_str(&args_json)?,
-            round_index: row.get::<i64, _>("round_index") as usize,
-            tool_messages: serde_json::from_str(&tool_messages_json)?,
-            context_snapshot: serde_json::from_str(&context_snapshot_json)?,
-            created_at: Self::parse_timestamp(&created_at_str)?,
-        }))
+    async fn get_pending_tool(&self, session_id: Uuid) -> Result<Option<PendingToolState>> {
+        self.ensure_initialized().await?;
+
+        let row = sqlx::query(
+            "SELECT session_id, tool_name, args_json, round_index, tool_messages_json, context_snapshot_json, created_at FROM pending_tools WHERE session_id = ?",
+        )
+        .bind(session_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(Self::parse_pending_tool).transpose()
     }
 
     async fn delete_stale_pending_tools(&self, older_than_secs: u64) -> Result<usize> {
@@ -945,7 +962,7 @@ impl StorageBackend for SqliteStorage {
     async fn upsert_success_pattern(&self, pattern: &SuccessPattern) -> Result<()> {
         self.ensure_initialized().await?;
 
-        sqlx::query("INSERT INTO success_patterns(id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT(id) DO UPDATE SET name = excluded.name, category = excluded.category, description = excluded.description, template = excluded.template, frequency = excluded.frequency, last_used = excluded.last_used, success_rate = excluded.success_rate")
+        sqlx::query("INSERT INTO success_patterns(id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT(id) DO UPDATE SET name = excluded.name, category = excluded.category, description = excluded.description, template = excluded.template, frequency = excluded.frequency, last_used = excluded.last_used, success_rate = excluded.success_rate, skill_name = excluded.skill_name")
             .bind(pattern.id.to_string())
             .bind(&pattern.agent_name)
             .bind(&pattern.name)
@@ -956,6 +973,7 @@ impl StorageBackend for SqliteStorage {
             .bind(pattern.last_used.to_rfc3339())
             .bind(pattern.success_rate)
             .bind(pattern.created_at.to_rfc3339())
+            .bind(&pattern.skill_name)
             .execute(&self.pool)
             .await?;
 
@@ -974,7 +992,7 @@ impl StorageBackend for SqliteStorage {
         let rows = if let Some(category) = category {
             let search = query.map(|value| format!("%{}%", value.to_ascii_lowercase()));
             if let Some(search) = search {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = ? AND category = ? AND (LOWER(name) LIKE ? OR LOWER(description) LIKE ? OR LOWER(template) LIKE ?) ORDER BY frequency DESC, last_used DESC LIMIT ?")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = ? AND category = ? AND (LOWER(name) LIKE ? OR LOWER(description) LIKE ? OR LOWER(template) LIKE ?) ORDER BY frequency DESC, last_used DESC LIMIT ?")
                     .bind(agent_name)
                     .bind(category.as_str())
                     .bind(&search)
@@ -984,7 +1002,7 @@ impl StorageBackend for SqliteStorage {
                     .fetch_all(&self.pool)
                     .await?
             } else {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = ? AND category = ? ORDER BY frequency DESC, last_used DESC LIMIT ?")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = ? AND category = ? ORDER BY frequency DESC, last_used DESC LIMIT ?")
                     .bind(agent_name)
                     .bind(category.as_str())
                     .bind(limit as i64)
@@ -994,7 +1012,7 @@ impl StorageBackend for SqliteStorage {
         } else {
             let search = query.map(|value| format!("%{}%", value.to_ascii_lowercase()));
             if let Some(search) = search {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = ? AND (LOWER(name) LIKE ? OR LOWER(description) LIKE ? OR LOWER(template) LIKE ?) ORDER BY frequency DESC, last_used DESC LIMIT ?")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = ? AND (LOWER(name) LIKE ? OR LOWER(description) LIKE ? OR LOWER(template) LIKE ?) ORDER BY frequency DESC, last_used DESC LIMIT ?")
                     .bind(agent_name)
                     .bind(&search)
                     .bind(&search)
@@ -1003,7 +1021,7 @@ impl StorageBackend for SqliteStorage {
                     .fetch_all(&self.pool)
                     .await?
             } else {
-                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at FROM success_patterns WHERE agent_name = ? ORDER BY frequency DESC, last_used DESC LIMIT ?")
+                sqlx::query("SELECT id, agent_name, name, category, description, template, frequency, last_used, success_rate, created_at, skill_name FROM success_patterns WHERE agent_name = ? ORDER BY frequency DESC, last_used DESC LIMIT ?")
                     .bind(agent_name)
                     .bind(limit as i64)
                     .fetch_all(&self.pool)
@@ -1030,6 +1048,7 @@ impl StorageBackend for SqliteStorage {
                 last_used: Self::parse_timestamp(&last_used)?,
                 success_rate: row.get::<f64, _>("success_rate"),
                 created_at: Self::parse_timestamp(&created_at)?,
+                skill_name: row.get::<Option<String>, _>("skill_name"),
             });
         }
 
@@ -1231,6 +1250,38 @@ impl StorageBackend for SqliteStorage {
         Ok(())
     }
 
+    async fn delete_code_file_index(&self, workspace: &str, path: &str) -> Result<()> {
+        self.ensure_initialized().await?;
+        let embedding_id = format!("file:{path}");
+        let mut tx = self.pool.begin().await?;
+        for (statement, key) in [
+            (
+                "DELETE FROM code_file_indexes WHERE workspace = ? AND path = ?",
+                path,
+            ),
+            (
+                "DELETE FROM code_symbol_indexes WHERE workspace = ? AND file_path = ?",
+                path,
+            ),
+            (
+                "DELETE FROM code_call_edges WHERE workspace = ? AND file_path = ?",
+                path,
+            ),
+            (
+                "DELETE FROM vector_embeddings WHERE workspace = ? AND id = ?",
+                embedding_id.as_str(),
+            ),
+        ] {
+            sqlx::query(statement)
+                .bind(workspace)
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn list_code_call_edges(&self, workspace: &str) -> Result<Vec<IndexedCallEdgeRecord>> {
         self.ensure_initialized().await?;
         let rows = sqlx::query("SELECT caller_symbol, callee_symbol, file_path, line, column_number, updated_at FROM code_call_edges WHERE workspace = ? ORDER BY file_path ASC, line ASC")
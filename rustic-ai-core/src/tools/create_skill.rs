@@ -0,0 +1,320 @@
+use crate::config::schema::{AgentPermissionMode, ToolConfig};
+use crate::error::{Error, Result};
+use crate::events::Event;
+use crate::learning::LearningManager;
+use crate::skills::environment::validate_dependencies;
+use crate::skills::{ScriptLanguage, SkillDependencies, SkillRegistry};
+use crate::tools::{Tool, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Lets an agent write a new `SKILL.md` skill into the project and register
+/// it without a restart. Every call goes through the permission `ask` flow,
+/// so the user reviews the proposed skill before anything is written.
+#[derive(Clone)]
+pub struct CreateSkillTool {
+    config: ToolConfig,
+    schema: Value,
+    skills: Arc<SkillRegistry>,
+    learning: Arc<LearningManager>,
+}
+
+/// A validated proposal from the tool arguments.
+struct SkillDraft {
+    name: String,
+    description: String,
+    instructions: String,
+    script: Option<(ScriptLanguage, String)>,
+    input_schema: Option<Value>,
+    dependencies: Option<Value>,
+}
+
+impl CreateSkillTool {
+    pub fn new(
+        config: ToolConfig,
+        skills: Arc<SkillRegistry>,
+        learning: Arc<LearningManager>,
+    ) -> Self {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Skill name: lowercase letters, digits and hyphens"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "What the skill does and when to use it"
+                },
+                "kind": {"type": "string", "enum": ["instruction", "script"]},
+                "content": {
+                    "type": "string",
+                    "description": "Instructions for an instruction skill, or the script source"
+                },
+                "language": {
+                    "type": "string",
+                    "enum": ["python", "javascript", "typescript", "shell", "bash", "ruby", "lua", "rust"],
+                    "description": "Script language, required for script skills"
+                },
+                "instructions": {
+                    "type": "string",
+                    "description": "Optional usage notes shown alongside a script skill"
+                },
+                "input_schema": {"type": "object", "description": "JSON schema of the skill input"},
+                "dependencies": {
+                    "type": "object",
+                    "description": "Packages per ecosystem: python, node, ruby, lua, rust"
+                },
+                "success_pattern": {
+                    "type": "string",
+                    "description": "Id or name of the success pattern the skill captures; defaults to the closest match"
+                }
+            },
+            "required": ["name", "description", "kind", "content"]
+        });
+        Self {
+            config,
+            schema,
+            skills,
+            learning,
+        }
+    }
+
+    fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+        args.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| Error::Tool(format!("missing '{key}' argument")))
+    }
+
+    fn parse_draft(args: &Value) -> Result<SkillDraft> {
+        let name = Self::required_str(args, "name")?;
+        if name.len() > 64
+            || name.starts_with('-')
+            || name.ends_with('-')
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+        {
+            return Err(Error::Tool(format!(
+                "skill name '{name}' must be at most 64 lowercase letters, digits and hyphens"
+            )));
+        }
+        let description = Self::required_str(args, "description")?;
+        let content = args
+            .get("content")
+            .and_then(Value::as_str)
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| Error::Tool("missing 'content' argument".to_owned()))?;
+
+        let input_schema = match args.get("input_schema") {
+            None | Some(Value::Null) => None,
+            Some(value @ Value::Object(_)) => Some(value.clone()),
+            Some(_) => return Err(Error::Tool("'input_schema' must be an object".to_owned())),
+        };
+        let dependencies = match args.get("dependencies") {
+            None | Some(Value::Null) => None,
+            Some(value) => {
+                let parsed: SkillDependencies = serde_json::from_value(value.clone())
+                    .map_err(|err| Error::Tool(format!("invalid 'dependencies': {err}")))?;
+                validate_dependencies(&parsed)
+                    .map_err(|err| Error::Tool(format!("invalid 'dependencies': {err}")))?;
+                Some(value.clone())
+            }
+        };
+
+        let (instructions, script) = match Self::required_str(args, "kind")? {
+            "instruction" => {
+                if dependencies.is_some() {
+                    return Err(Error::Tool(
+                        "'dependencies' only apply to script skills".to_owned(),
+                    ));
+                }
+                (content.to_owned(), None)
+            }
+            "script" => {
+                let language = Self::required_str(args, "language")?;
+                let language = ScriptLanguage::from_name(language).ok_or_else(|| {
+                    Error::Tool(format!("unsupported script language '{language}'"))
+                })?;
+                let instructions = args
+                    .get("instructions")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or(description)
+                    .to_owned();
+                (instructions, Some((language, content.to_owned())))
+            }
+            other => {
+                return Err(Error::Tool(format!(
+                    "unsupported skill kind '{other}', expected 'instruction' or 'script'"
+                )));
+            }
+        };
+
+        Ok(SkillDraft {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            instructions,
+            script,
+            input_schema,
+            dependencies,
+        })
+    }
+
+    /// Writes `SKILL.md` and the entrypoint script of `draft` into `dir`.
+    fn write_bundle(dir: &Path, draft: &SkillDraft, pattern_id: Option<String>) -> Result<()> {
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("authored-by".to_owned(), json!("agent"));
+        if let Some(pattern_id) = pattern_id {
+            metadata.insert("success-pattern".to_owned(), json!(pattern_id));
+        }
+        let mut fields = vec![
+            ("name", json!(draft.name)),
+            ("description", json!(draft.description)),
+        ];
+        if let Some((language, _)) = &draft.script {
+            fields.push((
+                "entrypoint",
+                json!(format!("scripts/{}.{}", draft.name, language.extension())),
+            ));
+        }
+        if let Some(input_schema) = &draft.input_schema {
+            fields.push(("input-schema", input_schema.clone()));
+        }
+        if let Some(dependencies) = &draft.dependencies {
+            fields.push(("dependencies", dependencies.clone()));
+        }
+        fields.push(("metadata", Value::Object(metadata)));
+
+        let render_error =
+            |err: serde_yaml::Error| Error::Tool(format!("failed to render skill header: {err}"));
+        let mut frontmatter = serde_yaml::Mapping::new();
+        for (key, value) in fields {
+            frontmatter.insert(
+                key.into(),
+                serde_yaml::to_value(value).map_err(render_error)?,
+            );
+        }
+        let header = serde_yaml::to_string(&frontmatter).map_err(render_error)?;
+
+        std::fs::create_dir_all(dir)?;
+        if let Some((language, source)) = &draft.script {
+            let scripts = dir.join("scripts");
+            std::fs::create_dir_all(&scripts)?;
+            std::fs::write(
+                scripts.join(format!("{}.{}", draft.name, language.extension())),
+                source,
+            )?;
+        }
+        std::fs::write(
+            dir.join("SKILL.md"),
+            format!("---\n{header}---\n\n{}\n", draft.instructions.trim_end()),
+        )?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Tool for CreateSkillTool {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn description(&self) -> &str {
+        "Propose a new instruction or script skill; once approved it is saved to the project and usable through the skill tool"
+    }
+
+    fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    async fn execute(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let (dummy_tx, _) = mpsc::channel(1);
+        self.stream_execute(args, dummy_tx, context).await
+    }
+
+    async fn stream_execute(
+        &self,
+        args: Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        if context.agent_permission_mode == AgentPermissionMode::ReadOnly {
+            return Err(Error::Tool(
+                "read-only agents cannot create skills".to_owned(),
+            ));
+        }
+        let bundles = self
+            .skills
+            .bundle_loader()
+            .ok_or_else(|| Error::Tool("skills are disabled".to_owned()))?;
+        let draft = Self::parse_draft(&args)?;
+        if draft.script.is_some() && !bundles.scripts_enabled() {
+            return Err(Error::Tool(
+                "script skills are disabled by skills.script_execution_mode".to_owned(),
+            ));
+        }
+
+        let skill_name = format!("skill.{}", draft.name);
+        let dir = bundles.authoring_dir().join(&draft.name);
+        if self.skills.get(&skill_name).is_some() || dir.exists() {
+            return Err(Error::Tool(format!("skill '{skill_name}' already exists")));
+        }
+
+        let agent_name = context.agent_name.as_deref().unwrap_or_default();
+        let pattern = self
+            .learning
+            .match_pattern(
+                agent_name,
+                args.get("success_pattern").and_then(Value::as_str),
+                &draft.description,
+            )
+            .await?;
+
+        let _ = tx.try_send(Event::ToolStarted {
+            tool: self.config.name.clone(),
+            args: args.clone(),
+        });
+
+        let registered = Self::write_bundle(
+            &dir,
+            &draft,
+            pattern.as_ref().map(|pattern| pattern.id.to_string()),
+        )
+        .and_then(|()| self.skills.load_bundle(&dir));
+        if let Err(err) = registered {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(err);
+        }
+
+        let linked = match pattern {
+            Some(pattern) => {
+                let pattern = self.learning.link_skill(pattern, &skill_name).await?;
+                json!({"id": pattern.id, "name": pattern.name})
+            }
+            None => Value::Null,
+        };
+
+        let _ = tx.try_send(Event::ToolCompleted {
+            tool: self.config.name.clone(),
+            exit_code: 0,
+        });
+
+        Ok(ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({
+                "skill": skill_name,
+                "path": dir.display().to_string(),
+                "success_pattern": linked,
+            })
+            .to_string(),
+        })
+    }
+}
@@ -1,19 +1,51 @@
 use async_trait::async_trait;
 use base64::Engine;
-use futures::TryStreamExt;
+use futures::{Stream, TryStreamExt};
 use serde_json::{json, Map, Value};
-use sqlx::postgres::PgPoolOptions;
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Column, Row};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
 
-use crate::config::schema::ToolConfig;
+use crate::config::schema::{AgentPermissionMode, ToolConfig};
 use crate::error::{Error, Result};
 use crate::events::Event;
+use crate::tools::sql_guard::{self, SqlDialect};
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 
 const MAX_QUERY_ROWS: usize = 10_000;
+const DEFAULT_MAX_BYTES: usize = 256 * 1024;
+const MAX_RESULT_BYTES: usize = 8 * 1024 * 1024;
+/// Row cap for `export_path`; exports stream to disk instead of the payload.
+const MAX_EXPORT_ROWS: usize = 1_000_000;
+/// Rows echoed inline when a query is exported.
+const EXPORT_PREVIEW_ROWS: usize = 20;
+
+const POSTGRES_FOREIGN_KEYS_SQL: &str = "SELECT con.conname AS \"constraint\", \
+    src_ns.nspname || '.' || src.relname AS from_table, src_att.attname AS from_column, \
+    dst_ns.nspname || '.' || dst.relname AS to_table, dst_att.attname AS to_column \
+    FROM pg_constraint con \
+    JOIN pg_class src ON src.oid = con.conrelid \
+    JOIN pg_namespace src_ns ON src_ns.oid = src.relnamespace \
+    JOIN pg_class dst ON dst.oid = con.confrelid \
+    JOIN pg_namespace dst_ns ON dst_ns.oid = dst.relnamespace \
+    CROSS JOIN LATERAL unnest(con.conkey, con.confkey) AS cols(src_attnum, dst_attnum) \
+    JOIN pg_attribute src_att ON src_att.attrelid = con.conrelid AND src_att.attnum = cols.src_attnum \
+    JOIN pg_attribute dst_att ON dst_att.attrelid = con.confrelid AND dst_att.attnum = cols.dst_attnum \
+    WHERE con.contype = 'f' AND src_ns.nspname NOT IN ('pg_catalog', 'information_schema') \
+    ORDER BY from_table, con.conname";
+
+const MYSQL_FOREIGN_KEYS_SQL: &str = "SELECT CONSTRAINT_NAME AS `constraint`, \
+    TABLE_NAME AS from_table, COLUMN_NAME AS from_column, \
+    REFERENCED_TABLE_NAME AS to_table, REFERENCED_COLUMN_NAME AS to_column \
+    FROM information_schema.KEY_COLUMN_USAGE \
+    WHERE TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME IS NOT NULL \
+    ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION";
 
 #[derive(Debug, Clone)]
 pub struct DatabaseTool {
@@ -27,6 +59,8 @@ enum DatabaseCommand {
     Query,
     ListTables,
     DescribeTable,
+    Explain,
+    SchemaGraph,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,6 +70,28 @@ enum DatabaseType {
     Mysql,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Per-call settings shared by every backend.
+#[derive(Debug, Clone)]
+struct RunOptions {
+    /// Opens the connection read-only and guards the SQL (read-only agents).
+    read_only: bool,
+    max_rows: usize,
+    max_bytes: usize,
+    export: Option<(PathBuf, ExportFormat)>,
+}
+
+/// Streams result rows to an export file as they arrive.
+enum ExportWriter {
+    Csv(Box<csv::Writer<std::fs::File>>),
+    Json(std::io::BufWriter<std::fs::File>),
+}
+
 impl DatabaseCommand {
     fn parse(value: &str) -> Result<Self> {
         match value.trim().to_ascii_lowercase().as_str() {
@@ -43,8 +99,10 @@ impl DatabaseCommand {
             "query" => Ok(Self::Query),
             "list_tables" => Ok(Self::ListTables),
             "describe_table" => Ok(Self::DescribeTable),
+            "explain" => Ok(Self::Explain),
+            "schema_graph" => Ok(Self::SchemaGraph),
             other => Err(Error::Tool(format!(
-                "unsupported database command '{other}' (expected connect|query|list_tables|describe_table)"
+                "unsupported database command '{other}' (expected connect|query|list_tables|describe_table|explain|schema_graph)"
             ))),
         }
     }
@@ -55,12 +113,78 @@ impl DatabaseType {
         match value.trim().to_ascii_lowercase().as_str() {
             "sqlite" => Ok(Self::Sqlite),
             "postgres" | "postgresql" => Ok(Self::Postgres),
-            "mysql" => Ok(Self::Mysql),
+            "mysql" | "mariadb" => Ok(Self::Mysql),
             other => Err(Error::Tool(format!(
                 "unsupported database_type '{other}' (expected sqlite|postgres|mysql)"
             ))),
         }
     }
+
+    fn dialect(self) -> SqlDialect {
+        match self {
+            Self::Sqlite => SqlDialect::Sqlite,
+            Self::Postgres => SqlDialect::Postgres,
+            Self::Mysql => SqlDialect::Mysql,
+        }
+    }
+}
+
+impl ExportWriter {
+    fn create(path: &PathBuf, format: ExportFormat) -> Result<Self> {
+        let file = std::fs::File::create(path).map_err(|err| {
+            Error::Tool(format!(
+                "failed to create export file '{}': {err}",
+                path.display()
+            ))
+        })?;
+        Ok(match format {
+            ExportFormat::Csv => Self::Csv(Box::new(csv::Writer::from_writer(file))),
+            ExportFormat::Json => {
+                let mut writer = std::io::BufWriter::new(file);
+                writer.write_all(b"[").map_err(Error::Io)?;
+                Self::Json(writer)
+            }
+        })
+    }
+
+    fn write_row(&mut self, index: usize, columns: &[String], cells: &[Value]) -> Result<()> {
+        match self {
+            Self::Csv(writer) => {
+                if index == 0 {
+                    writer
+                        .write_record(columns)
+                        .map_err(|err| Error::Tool(format!("csv export failed: {err}")))?;
+                }
+                writer
+                    .write_record(cells.iter().map(|cell| match cell {
+                        Value::Null => String::new(),
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    }))
+                    .map_err(|err| Error::Tool(format!("csv export failed: {err}")))
+            }
+            Self::Json(writer) => {
+                let row = columns
+                    .iter()
+                    .cloned()
+                    .zip(cells.iter().cloned())
+                    .collect::<Map<_, _>>();
+                let separator: &[u8] = if index == 0 { b"\n" } else { b",\n" };
+                writer.write_all(separator).map_err(Error::Io)?;
+                serde_json::to_writer(&mut *writer, &row).map_err(Error::Json)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Csv(mut writer) => writer.flush().map_err(Error::Io),
+            Self::Json(mut writer) => {
+                writer.write_all(b"\n]\n").map_err(Error::Io)?;
+                writer.flush().map_err(Error::Io)
+            }
+        }
+    }
 }
 
 impl DatabaseTool {
@@ -68,13 +192,16 @@ impl DatabaseTool {
         let schema = json!({
             "type": "object",
             "properties": {
-                "command": {"type": "string", "enum": ["connect", "query", "list_tables", "describe_table"]},
+                "command": {"type": "string", "enum": ["connect", "query", "list_tables", "describe_table", "explain", "schema_graph"]},
                 "database_type": {"type": "string", "enum": ["sqlite", "postgres", "mysql"]},
                 "connection_url": {"type": "string"},
-                "sql": {"type": "string"},
+                "sql": {"type": "string", "description": "Statement for query, or the single statement to plan for explain"},
                 "table": {"type": "string"},
                 "timeout_seconds": {"type": "integer", "minimum": 1, "maximum": 600},
-                "max_rows": {"type": "integer", "minimum": 1, "maximum": 10000}
+                "max_rows": {"type": "integer", "minimum": 1, "maximum": MAX_EXPORT_ROWS, "description": "Row limit (up to 10000 inline, 1000000 with export_path)"},
+                "max_bytes": {"type": "integer", "minimum": 1024, "maximum": MAX_RESULT_BYTES, "description": "Limit on the JSON size of inline rows"},
+                "export_path": {"type": "string", "description": "Workspace file to write the full query result to"},
+                "export_format": {"type": "string", "enum": ["csv", "json"], "description": "Defaults from the export_path extension"}
             },
             "required": ["command", "database_type", "connection_url"]
         });
@@ -96,11 +223,23 @@ impl DatabaseTool {
             .clamp(1, 600)
     }
 
-    fn max_rows(args: &Value) -> usize {
+    fn max_rows(args: &Value, exporting: bool) -> usize {
+        let (default, cap) = if exporting {
+            (MAX_EXPORT_ROWS, MAX_EXPORT_ROWS)
+        } else {
+            (200, MAX_QUERY_ROWS)
+        };
         args.get("max_rows")
             .and_then(Value::as_u64)
-            .unwrap_or(200)
-            .clamp(1, MAX_QUERY_ROWS as u64) as usize
+            .unwrap_or(default as u64)
+            .clamp(1, cap as u64) as usize
+    }
+
+    fn max_bytes(args: &Value) -> usize {
+        args.get("max_bytes")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_MAX_BYTES as u64)
+            .clamp(1024, MAX_RESULT_BYTES as u64) as usize
     }
 
     fn split_table_name(table: &str) -> (String, String) {
@@ -129,6 +268,83 @@ impl DatabaseTool {
         )))
     }
 
+    /// Resolves `export_path` inside the workspace (creating parents) and
+    /// picks the format from `export_format` or the file extension.
+    fn resolve_export(
+        args: &Value,
+        context: &ToolExecutionContext,
+    ) -> Result<Option<(PathBuf, ExportFormat)>> {
+        let Some(raw) = args
+            .get("export_path")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        else {
+            return Ok(None);
+        };
+        if context.agent_permission_mode == AgentPermissionMode::ReadOnly {
+            return Err(Error::Tool(
+                "export_path writes to the workspace and is not allowed in read_only agent mode"
+                    .to_owned(),
+            ));
+        }
+
+        let format = match args.get("export_format").and_then(Value::as_str) {
+            Some("csv") => ExportFormat::Csv,
+            Some("json") => ExportFormat::Json,
+            Some(other) => {
+                return Err(Error::Tool(format!(
+                    "unsupported export_format '{other}' (expected csv|json)"
+                )))
+            }
+            None if raw.to_ascii_lowercase().ends_with(".csv") => ExportFormat::Csv,
+            None => ExportFormat::Json,
+        };
+
+        let workspace = std::fs::canonicalize(&context.working_directory).map_err(|err| {
+            Error::Tool(format!(
+                "failed to resolve workspace '{}': {err}",
+                context.working_directory.display()
+            ))
+        })?;
+        let candidate = workspace.join(raw);
+        let parent = candidate
+            .parent()
+            .ok_or_else(|| Error::Tool(format!("export_path '{raw}' has no parent")))?;
+        let escapes = candidate
+            .components()
+            .any(|part| part == std::path::Component::ParentDir);
+        if escapes || !parent.starts_with(&workspace) {
+            return Err(Error::Tool(format!(
+                "export_path '{raw}' is outside the workspace"
+            )));
+        }
+        std::fs::create_dir_all(parent).map_err(|err| {
+            Error::Tool(format!(
+                "failed to create export directory '{}': {err}",
+                parent.display()
+            ))
+        })?;
+        let parent_resolved = std::fs::canonicalize(parent).map_err(Error::Io)?;
+        if !parent_resolved.starts_with(&workspace) {
+            return Err(Error::Tool(format!(
+                "export_path '{raw}' is outside the workspace"
+            )));
+        }
+        Ok(Some((candidate, format)))
+    }
+
+    /// Returns the `sql` argument, or its only statement when `single` is set
+    /// (`explain` must not smuggle a second statement past the prefix).
+    fn checked_sql(args: &Value, dialect: SqlDialect, single: bool) -> Result<String> {
+        let sql = Self::required_string(args, "sql")?;
+        if single {
+            sql_guard::single_statement(sql, dialect)
+        } else {
+            Ok(sql.to_owned())
+        }
+    }
+
     fn decode_sqlite_cell(row: &sqlx::sqlite::SqliteRow, index: usize) -> Value {
         if let Ok(value) = row.try_get::<Option<String>, _>(index) {
             return value.map(Value::String).unwrap_or(Value::Null);
@@ -157,12 +373,18 @@ impl DatabaseTool {
         if let Ok(value) = row.try_get::<Option<i64>, _>(index) {
             return value.map_or(Value::Null, |v| json!(v));
         }
+        if let Ok(value) = row.try_get::<Option<i32>, _>(index) {
+            return value.map_or(Value::Null, |v| json!(v));
+        }
         if let Ok(value) = row.try_get::<Option<f64>, _>(index) {
             return value.map_or(Value::Null, |v| json!(v));
         }
         if let Ok(value) = row.try_get::<Option<bool>, _>(index) {
             return value.map_or(Value::Null, |v| json!(v));
         }
+        if let Ok(value) = row.try_get::<Option<sqlx::types::Json<Value>>, _>(index) {
+            return value.map_or(Value::Null, |v| v.0);
+        }
         if let Ok(value) = row.try_get::<Option<Vec<u8>>, _>(index) {
             return value.map_or(Value::Null, |v| {
                 Value::String(base64::engine::general_purpose::STANDARD.encode(v))
@@ -171,26 +393,214 @@ impl DatabaseTool {
         Value::String("<unrenderable>".to_owned())
     }
 
-    fn sqlite_row_to_json(row: &sqlx::sqlite::SqliteRow) -> Value {
+    fn decode_mysql_cell(row: &sqlx::mysql::MySqlRow, index: usize) -> Value {
+        if let Ok(value) = row.try_get::<Option<String>, _>(index) {
+            return value.map(Value::String).unwrap_or(Value::Null);
+        }
+        if let Ok(value) = row.try_get::<Option<i64>, _>(index) {
+            return value.map_or(Value::Null, |v| json!(v));
+        }
+        if let Ok(value) = row.try_get::<Option<u64>, _>(index) {
+            return value.map_or(Value::Null, |v| json!(v));
+        }
+        if let Ok(value) = row.try_get::<Option<f64>, _>(index) {
+            return value.map_or(Value::Null, |v| json!(v));
+        }
+        if let Ok(value) = row.try_get::<Option<bool>, _>(index) {
+            return value.map_or(Value::Null, |v| json!(v));
+        }
+        if let Ok(value) = row.try_get::<Option<sqlx::types::Json<Value>>, _>(index) {
+            return value.map_or(Value::Null, |v| v.0);
+        }
+        if let Ok(value) = row.try_get::<Option<Vec<u8>>, _>(index) {
+            return value.map_or(Value::Null, |v| match String::from_utf8(v) {
+                Ok(text) => Value::String(text),
+                Err(err) => Value::String(
+                    base64::engine::general_purpose::STANDARD.encode(err.into_bytes()),
+                ),
+            });
+        }
+        Value::String("<unrenderable>".to_owned())
+    }
+
+    fn row_to_json<R: Row>(row: &R, decode: fn(&R, usize) -> Value) -> Value {
         let mut map = Map::new();
         for (index, column) in row.columns().iter().enumerate() {
-            map.insert(
-                column.name().to_owned(),
-                Self::decode_sqlite_cell(row, index),
-            );
+            map.insert(column.name().to_owned(), decode(row, index));
         }
         Value::Object(map)
     }
 
+    fn sqlite_row_to_json(row: &sqlx::sqlite::SqliteRow) -> Value {
+        Self::row_to_json(row, Self::decode_sqlite_cell)
+    }
+
     fn postgres_row_to_json(row: &sqlx::postgres::PgRow) -> Value {
-        let mut map = Map::new();
-        for (index, column) in row.columns().iter().enumerate() {
-            map.insert(
-                column.name().to_owned(),
-                Self::decode_postgres_cell(row, index),
-            );
+        Self::row_to_json(row, Self::decode_postgres_cell)
+    }
+
+    fn mysql_row_to_json(row: &sqlx::mysql::MySqlRow) -> Value {
+        Self::row_to_json(row, Self::decode_mysql_cell)
+    }
+
+    /// Drains a result stream under the row/byte limits, writing every row
+    /// to the export file when one is requested.
+    async fn collect_rows<R, S>(
+        &self,
+        mut stream: S,
+        decode: fn(&R, usize) -> Value,
+        options: &RunOptions,
+        tx: Option<&mpsc::Sender<Event>>,
+    ) -> Result<Value>
+    where
+        R: Row,
+        S: Stream<Item = std::result::Result<R, sqlx::Error>> + Unpin,
+    {
+        let mut export = options
+            .export
+            .as_ref()
+            .map(|(path, format)| ExportWriter::create(path, *format))
+            .transpose()?;
+        let inline_limit = if export.is_some() {
+            EXPORT_PREVIEW_ROWS.min(options.max_rows)
+        } else {
+            options.max_rows
+        };
+
+        let mut columns = Vec::new();
+        let mut rows = Vec::new();
+        let mut read = 0usize;
+        let mut inline_bytes = 0usize;
+        let mut inline_full = false;
+        let mut truncated = None;
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|err| Error::Tool(format!("query execution failed: {err}")))?
+        {
+            if read == options.max_rows {
+                truncated = Some("max_rows");
+                break;
+            }
+            if columns.is_empty() {
+                columns = row
+                    .columns()
+                    .iter()
+                    .map(|column| column.name().to_owned())
+                    .collect::<Vec<_>>();
+            }
+            let cells = (0..columns.len())
+                .map(|index| decode(&row, index))
+                .collect::<Vec<_>>();
+            if let Some(writer) = export.as_mut() {
+                writer.write_row(read, &columns, &cells)?;
+            }
+            read += 1;
+
+            if !inline_full && rows.len() < inline_limit {
+                let object = Value::Object(columns.iter().cloned().zip(cells).collect());
+                let size = object.to_string().len();
+                if inline_bytes + size > options.max_bytes {
+                    inline_full = true;
+                    if export.is_none() {
+                        truncated = Some("max_bytes");
+                        break;
+                    }
+                } else {
+                    inline_bytes += size;
+                    rows.push(object);
+                }
+            }
+            if let Some(tx) = tx {
+                let _ = tx.try_send(Event::ToolOutput {
+                    tool: self.config.name.clone(),
+                    stdout_chunk: format!("streamed {read} rows\n"),
+                    stderr_chunk: String::new(),
+                });
+            }
         }
-        Value::Object(map)
+
+        let mut payload = json!({
+            "columns": columns,
+            "row_count": rows.len(),
+            "rows": rows,
+            "bytes": inline_bytes,
+            "truncated": truncated
+        });
+        if let (Some(writer), Some((path, format))) = (export, options.export.as_ref()) {
+            writer.finish()?;
+            payload["export"] = json!({
+                "path": path,
+                "format": if *format == ExportFormat::Csv { "csv" } else { "json" },
+                "rows": read
+            });
+        }
+        Ok(payload)
+    }
+
+    /// Wraps a backend result into the tool payload with the common keys.
+    fn tagged(command: &str, database_type: &str, mut payload: Value) -> Value {
+        payload["command"] = json!(command);
+        payload["database_type"] = json!(database_type);
+        payload
+    }
+
+    fn schema_graph_payload(database_type: &str, tables: Vec<String>, edges: Vec<Value>) -> Value {
+        json!({
+            "command": "schema_graph",
+            "database_type": database_type,
+            "table_count": tables.len(),
+            "tables": tables,
+            "edge_count": edges.len(),
+            "edges": edges
+        })
+    }
+
+    async fn sqlite_tables(pool: &sqlx::SqlitePool) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|err| Error::Tool(format!("sqlite list_tables failed: {err}")))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.try_get::<String, _>("name").ok())
+            .collect())
+    }
+
+    async fn postgres_tables(pool: &sqlx::PgPool) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            "SELECT table_schema, table_name FROM information_schema.tables WHERE table_type = 'BASE TABLE' AND table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY table_schema, table_name",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|err| Error::Tool(format!("postgres list_tables failed: {err}")))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let schema = row.try_get::<String, _>("table_schema").ok()?;
+                let table = row.try_get::<String, _>("table_name").ok()?;
+                Some(format!("{schema}.{table}"))
+            })
+            .collect())
+    }
+
+    async fn mysql_tables(pool: &sqlx::MySqlPool) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            "SELECT TABLE_NAME AS table_name FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|err| Error::Tool(format!("mysql list_tables failed: {err}")))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Self::decode_mysql_cell(row, 0)
+                    .as_str()
+                    .map(ToOwned::to_owned)
+            })
+            .collect())
     }
 
     async fn run_sqlite(
@@ -198,11 +608,15 @@ impl DatabaseTool {
         command: DatabaseCommand,
         connection_url: &str,
         args: &Value,
+        options: &RunOptions,
         tx: Option<mpsc::Sender<Event>>,
     ) -> Result<Value> {
+        let connect_options = SqliteConnectOptions::from_str(connection_url)
+            .map_err(|err| Error::Tool(format!("invalid sqlite connection_url: {err}")))?
+            .read_only(options.read_only);
         let pool = SqlitePoolOptions::new()
             .max_connections(2)
-            .connect(connection_url)
+            .connect_with(connect_options)
             .await
             .map_err(|err| Error::Tool(format!("failed to connect sqlite database: {err}")))?;
 
@@ -219,16 +633,7 @@ impl DatabaseTool {
                 })
             }
             DatabaseCommand::ListTables => {
-                let rows = sqlx::query(
-                    "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
-                )
-                .fetch_all(&pool)
-                .await
-                .map_err(|err| Error::Tool(format!("sqlite list_tables failed: {err}")))?;
-                let tables = rows
-                    .iter()
-                    .filter_map(|row| row.try_get::<String, _>("name").ok())
-                    .collect::<Vec<_>>();
+                let tables = Self::sqlite_tables(&pool).await?;
                 json!({
                     "command": "list_tables",
                     "database_type": "sqlite",
@@ -256,34 +661,64 @@ impl DatabaseTool {
                 })
             }
             DatabaseCommand::Query => {
-                let sql = Self::required_string(args, "sql")?;
-                let max_rows = Self::max_rows(args);
-                let mut stream = sqlx::query(sql).fetch(&pool);
-                let mut rows = Vec::new();
-                while let Some(row) = stream
-                    .try_next()
+                let sql = Self::checked_sql(args, SqlDialect::Sqlite, false)?;
+                let stream = sqlx::query(&sql).fetch(&pool);
+                let payload = self
+                    .collect_rows(stream, Self::decode_sqlite_cell, options, tx.as_ref())
+                    .await?;
+                Self::tagged("query", "sqlite", payload)
+            }
+            DatabaseCommand::Explain => {
+                let sql = Self::checked_sql(args, SqlDialect::Sqlite, true)?;
+                let rows = sqlx::query(&format!("EXPLAIN QUERY PLAN {sql}"))
+                    .fetch_all(&pool)
                     .await
-                    .map_err(|err| Error::Tool(format!("sqlite query execution failed: {err}")))?
-                {
-                    rows.push(Self::sqlite_row_to_json(&row));
-                    if rows.len() >= max_rows {
-                        break;
-                    }
-                    if let Some(tx) = tx.as_ref() {
-                        let _ = tx.try_send(Event::ToolOutput {
-                            tool: self.config.name.clone(),
-                            stdout_chunk: format!("streamed {} rows\n", rows.len()),
-                            stderr_chunk: String::new(),
-                        });
-                    }
-                }
+                    .map_err(|err| Error::Tool(format!("sqlite explain failed: {err}")))?;
+                // Render the (id, parent, detail) rows as an indented tree.
+                let mut depths = std::collections::HashMap::<i64, usize>::new();
+                let plan = rows
+                    .iter()
+                    .map(|row| {
+                        let id = row.try_get::<i64, _>("id").unwrap_or_default();
+                        let parent = row.try_get::<i64, _>("parent").unwrap_or_default();
+                        let detail = row.try_get::<String, _>("detail").unwrap_or_default();
+                        let depth = depths.get(&parent).map_or(0, |depth| depth + 1);
+                        depths.insert(id, depth);
+                        format!("{}{detail}", "  ".repeat(depth))
+                    })
+                    .collect::<Vec<_>>();
                 json!({
-                    "command": "query",
+                    "command": "explain",
                     "database_type": "sqlite",
-                    "row_count": rows.len(),
-                    "rows": rows
+                    "sql": sql,
+                    "plan": plan
                 })
             }
+            DatabaseCommand::SchemaGraph => {
+                let tables = Self::sqlite_tables(&pool).await?;
+                let mut edges = Vec::new();
+                for table in &tables {
+                    let rows = sqlx::query(
+                        "SELECT id, \"table\", \"from\", \"to\" FROM pragma_foreign_key_list(?) ORDER BY id, seq",
+                    )
+                    .bind(table)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|err| Error::Tool(format!("sqlite schema_graph failed: {err}")))?;
+                    for row in &rows {
+                        let id = row.try_get::<i64, _>("id").unwrap_or_default();
+                        edges.push(json!({
+                            "constraint": format!("{table}_fk_{id}"),
+                            "from_table": table,
+                            "from_column": row.try_get::<String, _>("from").ok(),
+                            "to_table": row.try_get::<String, _>("table").ok(),
+                            // NULL means the referenced table's primary key.
+                            "to_column": row.try_get::<Option<String>, _>("to").ok().flatten()
+                        }));
+                    }
+                }
+                Self::schema_graph_payload("sqlite", tables, edges)
+            }
         };
 
         pool.close().await;
@@ -295,17 +730,23 @@ impl DatabaseTool {
         command: DatabaseCommand,
         connection_url: &str,
         args: &Value,
+        options: &RunOptions,
         tx: Option<mpsc::Sender<Event>>,
     ) -> Result<Value> {
+        let mut connect_options = PgConnectOptions::from_str(connection_url)
+            .map_err(|err| Error::Tool(format!("invalid postgres connection_url: {err}")))?;
+        if options.read_only {
+            connect_options = connect_options.options([("default_transaction_read_only", "on")]);
+        }
         let pool = PgPoolOptions::new()
             .max_connections(2)
-            .connect(connection_url)
+            .connect_with(connect_options)
             .await
             .map_err(|err| Error::Tool(format!("failed to connect postgres database: {err}")))?;
 
         let result = match command {
             DatabaseCommand::Connect => {
-                let row: (i64,) = sqlx::query_as("SELECT 1")
+                let row: (i32,) = sqlx::query_as("SELECT 1")
                     .fetch_one(&pool)
                     .await
                     .map_err(|err| Error::Tool(format!("postgres ping failed: {err}")))?;
@@ -316,20 +757,7 @@ impl DatabaseTool {
                 })
             }
             DatabaseCommand::ListTables => {
-                let rows = sqlx::query(
-                    "SELECT table_schema, table_name FROM information_schema.tables WHERE table_type = 'BASE TABLE' AND table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY table_schema, table_name",
-                )
-                .fetch_all(&pool)
-                .await
-                .map_err(|err| Error::Tool(format!("postgres list_tables failed: {err}")))?;
-                let tables = rows
-                    .iter()
-                    .filter_map(|row| {
-                        let schema = row.try_get::<String, _>("table_schema").ok()?;
-                        let table = row.try_get::<String, _>("table_name").ok()?;
-                        Some(format!("{schema}.{table}"))
-                    })
-                    .collect::<Vec<_>>();
+                let tables = Self::postgres_tables(&pool).await?;
                 json!({
                     "command": "list_tables",
                     "database_type": "postgres",
@@ -362,34 +790,146 @@ impl DatabaseTool {
                 })
             }
             DatabaseCommand::Query => {
-                let sql = Self::required_string(args, "sql")?;
-                let max_rows = Self::max_rows(args);
-                let mut stream = sqlx::query(sql).fetch(&pool);
-                let mut rows = Vec::new();
-                while let Some(row) = stream
-                    .try_next()
+                let sql = Self::checked_sql(args, SqlDialect::Postgres, false)?;
+                let stream = sqlx::query(&sql).fetch(&pool);
+                let payload = self
+                    .collect_rows(stream, Self::decode_postgres_cell, options, tx.as_ref())
+                    .await?;
+                Self::tagged("query", "postgres", payload)
+            }
+            DatabaseCommand::Explain => {
+                let sql = Self::checked_sql(args, SqlDialect::Postgres, true)?;
+                let row = sqlx::query(&format!("EXPLAIN (FORMAT JSON) {sql}"))
+                    .fetch_one(&pool)
                     .await
-                    .map_err(|err| Error::Tool(format!("postgres query execution failed: {err}")))?
-                {
-                    rows.push(Self::postgres_row_to_json(&row));
-                    if rows.len() >= max_rows {
-                        break;
-                    }
-                    if let Some(tx) = tx.as_ref() {
-                        let _ = tx.try_send(Event::ToolOutput {
-                            tool: self.config.name.clone(),
-                            stdout_chunk: format!("streamed {} rows\n", rows.len()),
-                            stderr_chunk: String::new(),
-                        });
-                    }
-                }
+                    .map_err(|err| Error::Tool(format!("postgres explain failed: {err}")))?;
                 json!({
-                    "command": "query",
+                    "command": "explain",
                     "database_type": "postgres",
-                    "row_count": rows.len(),
-                    "rows": rows
+                    "sql": sql,
+                    "plan": Self::decode_postgres_cell(&row, 0)
                 })
             }
+            DatabaseCommand::SchemaGraph => {
+                let tables = Self::postgres_tables(&pool).await?;
+                let edges = sqlx::query(POSTGRES_FOREIGN_KEYS_SQL)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|err| Error::Tool(format!("postgres schema_graph failed: {err}")))?
+                    .iter()
+                    .map(Self::postgres_row_to_json)
+                    .collect();
+                Self::schema_graph_payload("postgres", tables, edges)
+            }
+        };
+
+        pool.close().await;
+        Ok(result)
+    }
+
+    async fn run_mysql(
+        &self,
+        command: DatabaseCommand,
+        connection_url: &str,
+        args: &Value,
+        options: &RunOptions,
+        tx: Option<mpsc::Sender<Event>>,
+    ) -> Result<Value> {
+        let connect_options = MySqlConnectOptions::from_str(connection_url)
+            .map_err(|err| Error::Tool(format!("invalid mysql connection_url: {err}")))?;
+        let mut pool_options = MySqlPoolOptions::new().max_connections(2);
+        if options.read_only {
+            pool_options = pool_options.after_connect(|conn, _| {
+                Box::pin(async move {
+                    sqlx::query("SET SESSION TRANSACTION READ ONLY")
+                        .execute(conn)
+                        .await
+                        .map(|_| ())
+                })
+            });
+        }
+        let pool = pool_options
+            .connect_with(connect_options)
+            .await
+            .map_err(|err| Error::Tool(format!("failed to connect mysql database: {err}")))?;
+
+        let result = match command {
+            DatabaseCommand::Connect => {
+                let row: (i64,) = sqlx::query_as("SELECT 1")
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|err| Error::Tool(format!("mysql ping failed: {err}")))?;
+                json!({
+                    "command": "connect",
+                    "database_type": "mysql",
+                    "ok": row.0 == 1
+                })
+            }
+            DatabaseCommand::ListTables => {
+                let tables = Self::mysql_tables(&pool).await?;
+                json!({
+                    "command": "list_tables",
+                    "database_type": "mysql",
+                    "count": tables.len(),
+                    "tables": tables
+                })
+            }
+            DatabaseCommand::DescribeTable => {
+                let table = Self::required_string(args, "table")?;
+                Self::validate_identifier(table)?;
+                let rows = sqlx::query(
+                    "SELECT COLUMN_NAME AS column_name, COLUMN_TYPE AS data_type, IS_NULLABLE AS is_nullable, COLUMN_DEFAULT AS column_default, COLUMN_KEY AS column_key FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                )
+                .bind(table)
+                .fetch_all(&pool)
+                .await
+                .map_err(|err| Error::Tool(format!("mysql describe_table failed: {err}")))?;
+                let columns = rows.iter().map(Self::mysql_row_to_json).collect::<Vec<_>>();
+                json!({
+                    "command": "describe_table",
+                    "database_type": "mysql",
+                    "table": table,
+                    "columns": columns
+                })
+            }
+            DatabaseCommand::Query => {
+                let sql = Self::checked_sql(args, SqlDialect::Mysql, false)?;
+                let stream = sqlx::query(&sql).fetch(&pool);
+                let payload = self
+                    .collect_rows(stream, Self::decode_mysql_cell, options, tx.as_ref())
+                    .await?;
+                Self::tagged("query", "mysql", payload)
+            }
+            DatabaseCommand::Explain => {
+                let sql = Self::checked_sql(args, SqlDialect::Mysql, true)?;
+                let row = sqlx::query(&format!("EXPLAIN FORMAT=JSON {sql}"))
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|err| Error::Tool(format!("mysql explain failed: {err}")))?;
+                let plan = match Self::decode_mysql_cell(&row, 0) {
+                    Value::String(text) => {
+                        serde_json::from_str(&text).unwrap_or(Value::String(text))
+                    }
+                    other => other,
+                };
+                json!({
+                    "command": "explain",
+                    "database_type": "mysql",
+                    "sql": sql,
+                    "plan": plan
+                })
+            }
+            DatabaseCommand::SchemaGraph => {
+                let tables = Self::mysql_tables(&pool).await?;
+                let edges = sqlx::query(MYSQL_FOREIGN_KEYS_SQL)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|err| Error::Tool(format!("mysql schema_graph failed: {err}")))?
+                    .iter()
+                    .map(Self::mysql_row_to_json)
+                    .collect();
+                Self::schema_graph_payload("mysql", tables, edges)
+            }
         };
 
         pool.close().await;
@@ -435,13 +975,33 @@ impl DatabaseTool {
         let database_type = DatabaseType::parse(Self::required_string(&args, "database_type")?)?;
         let connection_url = Self::required_string(&args, "connection_url")?.to_owned();
         let timeout_seconds = self.timeout_seconds(&args);
+        let export = if command == DatabaseCommand::Query {
+            Self::resolve_export(&args, context)?
+        } else {
+            None
+        };
+        let options = RunOptions {
+            read_only: context.agent_permission_mode == AgentPermissionMode::ReadOnly,
+            max_rows: Self::max_rows(&args, export.is_some()),
+            max_bytes: Self::max_bytes(&args),
+            export,
+        };
+        if options.read_only && matches!(command, DatabaseCommand::Query | DatabaseCommand::Explain)
+        {
+            // The connection is also opened read-only; this rejects writes up front
+            // with a clear reason instead of a driver error.
+            sql_guard::ensure_read_only(
+                Self::required_string(&args, "sql")?,
+                database_type.dialect(),
+            )?;
+        }
 
         let payload = match database_type {
             DatabaseType::Sqlite => {
                 self.run_with_controls(
                     timeout_seconds,
                     context.cancellation_token.clone(),
-                    self.run_sqlite(command, &connection_url, &args, tx),
+                    self.run_sqlite(command, &connection_url, &args, &options, tx),
                 )
                 .await?
             }
@@ -449,15 +1009,17 @@ impl DatabaseTool {
                 self.run_with_controls(
                     timeout_seconds,
                     context.cancellation_token.clone(),
-                    self.run_postgres(command, &connection_url, &args, tx),
+                    self.run_postgres(command, &connection_url, &args, &options, tx),
                 )
                 .await?
             }
             DatabaseType::Mysql => {
-                return Err(Error::Tool(
-                    "database_type 'mysql' is not enabled in this build; enable MySQL sqlx feature to support it"
-                        .to_owned(),
-                ));
+                self.run_with_controls(
+                    timeout_seconds,
+                    context.cancellation_token.clone(),
+                    self.run_mysql(command, &connection_url, &args, &options, tx),
+                )
+                .await?
             }
         };
 
@@ -476,7 +1038,7 @@ impl Tool for DatabaseTool {
     }
 
     fn description(&self) -> &str {
-        "Database operations for sqlite/postgres with timeouts and streaming"
+        "Database operations for sqlite/postgres/mysql: queries with row/byte limits and CSV/JSON export, query plans and foreign-key graphs"
     }
 
     fn schema(&self) -> &Value {
@@ -1,4 +1,6 @@
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use tokio::process::Command;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
@@ -8,6 +10,197 @@ use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 
+const ACTIONS: &[&str] = &[
+    "ps",
+    "images",
+    "logs",
+    "inspect",
+    "run",
+    "stop",
+    "rm",
+    "pull",
+    "exec",
+    "build",
+    "cp",
+    "compose_up",
+    "compose_down",
+    "compose_ps",
+    "compose_logs",
+];
+const MAX_TIMEOUT_SECS: u64 = 300;
+/// `build` and `compose_up` may pull and compile for a while.
+const MAX_LONG_TIMEOUT_SECS: u64 = 1_800;
+const MAX_BUILD_CONTEXT_BYTES: u64 = 1024 * 1024 * 1024;
+const DEFAULT_COMPOSE_LOG_TAIL: u64 = 200;
+/// Short `run` flags with their long names. Only these are accepted while
+/// limits are configured, so every option's arity is known.
+const RUN_SHORT_FLAGS: &[(char, &str)] = &[
+    ('a', "--attach"),
+    ('c', "--cpu-shares"),
+    ('d', "--detach"),
+    ('e', "--env"),
+    ('h', "--hostname"),
+    ('i', "--interactive"),
+    ('l', "--label"),
+    ('m', "--memory"),
+    ('p', "--publish"),
+    ('P', "--publish-all"),
+    ('t', "--tty"),
+    ('u', "--user"),
+    ('v', "--volume"),
+    ('w', "--workdir"),
+];
+/// `run` flags that take no value; `--flag=false` is still allowed.
+const RUN_BOOLEAN_FLAGS: &[&str] = &[
+    "--detach",
+    "--disable-content-trust",
+    "--init",
+    "--interactive",
+    "--no-healthcheck",
+    "--oom-kill-disable",
+    "--privileged",
+    "--publish-all",
+    "--quiet",
+    "--read-only",
+    "--rm",
+    "--sig-proxy",
+    "--tty",
+];
+/// `run` flags that take a value, inline after `=` or as the next token.
+const RUN_VALUE_FLAGS: &[&str] = &[
+    "--add-host",
+    "--annotation",
+    "--attach",
+    "--blkio-weight",
+    "--blkio-weight-device",
+    "--cap-add",
+    "--cap-drop",
+    "--cgroup-parent",
+    "--cgroupns",
+    "--cidfile",
+    "--cpu-period",
+    "--cpu-quota",
+    "--cpu-rt-period",
+    "--cpu-rt-runtime",
+    "--cpu-shares",
+    "--cpus",
+    "--cpuset-cpus",
+    "--cpuset-mems",
+    "--detach-keys",
+    "--device",
+    "--device-cgroup-rule",
+    "--device-read-bps",
+    "--device-read-iops",
+    "--device-write-bps",
+    "--device-write-iops",
+    "--dns",
+    "--dns-option",
+    "--dns-search",
+    "--domainname",
+    "--entrypoint",
+    "--env",
+    "--env-file",
+    "--expose",
+    "--gpus",
+    "--group-add",
+    "--health-cmd",
+    "--health-interval",
+    "--health-retries",
+    "--health-start-interval",
+    "--health-start-period",
+    "--health-timeout",
+    "--hostname",
+    "--ip",
+    "--ip6",
+    "--ipc",
+    "--isolation",
+    "--kernel-memory",
+    "--label",
+    "--label-file",
+    "--link",
+    "--link-local-ip",
+    "--log-driver",
+    "--log-opt",
+    "--mac-address",
+    "--memory",
+    "--memory-reservation",
+    "--memory-swap",
+    "--memory-swappiness",
+    "--mount",
+    "--name",
+    "--net",
+    "--network",
+    "--network-alias",
+    "--oom-score-adj",
+    "--pid",
+    "--pids-limit",
+    "--platform",
+    "--publish",
+    "--pull",
+    "--restart",
+    "--runtime",
+    "--security-opt",
+    "--shm-size",
+    "--stop-signal",
+    "--stop-timeout",
+    "--storage-opt",
+    "--sysctl",
+    "--tmpfs",
+    "--ulimit",
+    "--user",
+    "--userns",
+    "--uts",
+    "--volume",
+    "--volume-driver",
+    "--volumes-from",
+    "--workdir",
+];
+/// Options that would let a limited container reach past its limits or the
+/// host, refused whenever limits are configured.
+const RUN_ESCAPE_FLAGS: &[&str] = &[
+    "--privileged",
+    "--cap-add",
+    "--security-opt",
+    "--device",
+    "--device-cgroup-rule",
+    "--cgroup-parent",
+    "--volume-driver",
+    "--volumes-from",
+    "--runtime",
+];
+/// Namespace options refused with the value `host` while limits are configured.
+const RUN_NAMESPACE_FLAGS: &[&str] = &[
+    "--pid",
+    "--ipc",
+    "--uts",
+    "--userns",
+    "--cgroupns",
+    "--network",
+    "--net",
+];
+
+/// A fully built engine invocation; `stdin` is streamed to the engine when set.
+struct Invocation {
+    args: Vec<String>,
+    stdin: Option<PathBuf>,
+    details: Map<String, Value>,
+}
+
+impl Invocation {
+    fn new(args: Vec<String>) -> Self {
+        Self {
+            args,
+            stdin: None,
+            details: Map::new(),
+        }
+    }
+}
+
+struct BuildContext {
+    files: usize,
+    bytes: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct DockerTool {
     config: ToolConfig,
@@ -21,15 +214,51 @@ impl DockerTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["ps", "images", "logs", "inspect", "run", "stop", "rm", "pull"]
+                    "enum": ACTIONS
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Raw arguments for ps/images/logs/inspect/run/stop/rm/pull; extra options for the other actions"
                 },
-                "args": { "type": "array", "items": { "type": "string" } },
                 "engine": {
                     "type": "string",
                     "enum": ["auto", "docker", "podman"],
                     "description": "Container CLI engine"
                 },
-                "timeout_seconds": { "type": "integer", "minimum": 1, "maximum": 300 }
+                "container": { "type": "string", "description": "exec/cp: container name or id" },
+                "command": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "exec: command and arguments to run in the container"
+                },
+                "workdir": { "type": "string", "description": "exec: working directory inside the container" },
+                "user": { "type": "string", "description": "exec: user[:group] inside the container" },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "exec: environment variables"
+                },
+                "context": { "type": "string", "description": "build: workspace directory sent as the build context (default '.')" },
+                "dockerfile": { "type": "string", "description": "build: Dockerfile path relative to the context" },
+                "tag": { "type": "string", "description": "build: image tag" },
+                "build_args": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" }
+                },
+                "target": { "type": "string", "description": "build: target stage" },
+                "direction": {
+                    "type": "string",
+                    "enum": ["in", "out"],
+                    "description": "cp: 'in' copies host_path into the container, 'out' copies container_path to the workspace"
+                },
+                "host_path": { "type": "string", "description": "cp: workspace path" },
+                "container_path": { "type": "string", "description": "cp: absolute path inside the container" },
+                "compose_file": { "type": "string", "description": "compose_*: compose file relative to the workspace" },
+                "project_name": { "type": "string" },
+                "services": { "type": "array", "items": { "type": "string" } },
+                "tail": { "type": "integer", "minimum": 1, "maximum": 10000 },
+                "timeout_seconds": { "type": "integer", "minimum": 1, "maximum": MAX_LONG_TIMEOUT_SECS }
             },
             "required": ["action"]
         });
@@ -46,17 +275,19 @@ impl DockerTool {
             .ok_or_else(|| Error::Tool("missing 'action' argument".to_owned()))?
             .to_ascii_lowercase();
 
-        match action.as_str() {
-            "ps" | "images" | "logs" | "inspect" | "run" | "stop" | "rm" | "pull" => Ok(action),
-            _ => Err(Error::Tool(format!(
-                "unsupported docker action '{}' (expected ps|images|logs|inspect|run|stop|rm|pull)",
-                action
-            ))),
+        if ACTIONS.contains(&action.as_str()) {
+            Ok(action)
+        } else {
+            Err(Error::Tool(format!(
+                "unsupported docker action '{}' (expected {})",
+                action,
+                ACTIONS.join("|")
+            )))
         }
     }
 
-    fn parse_args(args: &Value) -> Vec<String> {
-        args.get("args")
+    fn string_list(args: &Value, key: &str) -> Vec<String> {
+        args.get(key)
             .and_then(Value::as_array)
             .map(|items| {
                 items
@@ -68,11 +299,49 @@ impl DockerTool {
             .unwrap_or_default()
     }
 
-    fn parse_timeout(&self, args: &Value) -> u64 {
+    fn parse_args(args: &Value) -> Vec<String> {
+        Self::string_list(args, "args")
+    }
+
+    fn optional_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+        args.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn required_str<'a>(args: &'a Value, key: &str, action: &str) -> Result<&'a str> {
+        Self::optional_str(args, key)
+            .ok_or_else(|| Error::Tool(format!("docker action '{action}' requires '{key}'")))
+    }
+
+    fn string_map(args: &Value, key: &str) -> Result<Vec<(String, String)>> {
+        let Some(value) = args.get(key) else {
+            return Ok(Vec::new());
+        };
+        let map = value
+            .as_object()
+            .ok_or_else(|| Error::Tool(format!("'{key}' must be an object of strings")))?;
+        map.iter()
+            .map(|(name, value)| {
+                value
+                    .as_str()
+                    .map(|value| (name.clone(), value.to_owned()))
+                    .ok_or_else(|| Error::Tool(format!("'{key}.{name}' must be a string")))
+            })
+            .collect()
+    }
+
+    fn parse_timeout(&self, action: &str, args: &Value) -> u64 {
+        let max = if matches!(action, "build" | "compose_up") {
+            MAX_LONG_TIMEOUT_SECS
+        } else {
+            MAX_TIMEOUT_SECS
+        };
         args.get("timeout_seconds")
             .and_then(Value::as_u64)
             .unwrap_or(self.config.timeout_seconds)
-            .clamp(1, 300)
+            .clamp(1, max)
     }
 
     fn parse_engine(args: &Value) -> Result<String> {
@@ -91,9 +360,25 @@ impl DockerTool {
         }
     }
 
+    fn is_write_action(action: &str) -> bool {
+        matches!(
+            action,
+            "run"
+                | "stop"
+                | "rm"
+                | "pull"
+                | "exec"
+                | "build"
+                | "cp"
+                | "compose_up"
+                | "compose_down"
+        )
+    }
+
     fn enforce_permission(action: &str, context: &ToolExecutionContext) -> Result<()> {
-        let write_action = matches!(action, "run" | "stop" | "rm" | "pull");
-        if write_action && context.agent_permission_mode == AgentPermissionMode::ReadOnly {
+        if Self::is_write_action(action)
+            && context.agent_permission_mode == AgentPermissionMode::ReadOnly
+        {
             return Err(Error::Tool(format!(
                 "docker action '{}' is blocked in read-only agent mode",
                 action
@@ -101,6 +386,536 @@ impl DockerTool {
         }
         Ok(())
     }
+
+    /// Resolves `path` inside the workspace, rejecting anything that escapes
+    /// it. The path itself may not exist yet; its nearest existing ancestor
+    /// is what gets checked.
+    fn resolve_workspace_path(context: &ToolExecutionContext, path: &str) -> Result<PathBuf> {
+        let workspace = std::fs::canonicalize(&context.working_directory).map_err(|err| {
+            Error::Tool(format!(
+                "failed to resolve workspace '{}': {err}",
+                context.working_directory.display()
+            ))
+        })?;
+        let candidate = workspace.join(path);
+        let mut existing = candidate.as_path();
+        let mut suffix = Vec::new();
+        let resolved = loop {
+            match std::fs::canonicalize(existing) {
+                Ok(resolved) => break resolved,
+                Err(_) => {
+                    let (Some(parent), Some(name)) = (existing.parent(), existing.file_name())
+                    else {
+                        return Err(Error::Tool(format!("invalid path '{path}'")));
+                    };
+                    suffix.push(name.to_owned());
+                    existing = parent;
+                }
+            }
+        };
+        if suffix.iter().any(|part| part == "..") || !resolved.starts_with(&workspace) {
+            return Err(Error::Tool(format!(
+                "path '{path}' is outside the workspace"
+            )));
+        }
+        Ok(suffix
+            .into_iter()
+            .rev()
+            .fold(resolved, |path, part| path.join(part)))
+    }
+
+    /// Flags that set a configured limit, so agents cannot loosen it.
+    fn run_limit_flags(&self) -> Vec<(&'static [&'static str], &str)> {
+        let mut flags: Vec<(&'static [&'static str], &str)> = Vec::new();
+        if let Some(cpus) = &self.config.container_cpus {
+            flags.push((&["--cpus", "--cpu-quota", "--cpu-period"], cpus.trim()));
+        }
+        if let Some(memory) = &self.config.container_memory {
+            flags.push((&["--memory", "--memory-swap"], memory.trim()));
+        }
+        if let Some(network) = &self.config.container_network {
+            flags.push((&["--network", "--net"], network.trim()));
+        }
+        flags
+    }
+
+    /// Splits the option tokens before the image into `(long name, value)`
+    /// pairs. Short flags and bundles such as `-dm 8g` are expanded to their
+    /// long names; unknown options are rejected because their arity, and so
+    /// where the image starts, cannot be known.
+    fn parse_run_options(run_args: &[String]) -> Result<Vec<(&'static str, Option<String>)>> {
+        let mut options = Vec::new();
+        let mut tokens = run_args.iter();
+        let needs_value = |name: &str, tokens: &mut std::slice::Iter<'_, String>| {
+            tokens
+                .next()
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| Error::Tool(format!("docker run option '{name}' needs a value")))
+        };
+        while let Some(token) = tokens.next() {
+            if token == "--" || token == "-" || !token.starts_with('-') {
+                break;
+            }
+            if token.starts_with("--") {
+                let (name, inline) = match token.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_owned())),
+                    None => (token.as_str(), None),
+                };
+                if let Some(known) = RUN_BOOLEAN_FLAGS.iter().find(|flag| **flag == name) {
+                    options.push((*known, inline));
+                } else if let Some(known) = RUN_VALUE_FLAGS.iter().find(|flag| **flag == name) {
+                    let value = match inline {
+                        Some(value) => Some(value),
+                        None => needs_value(name, &mut tokens)?,
+                    };
+                    options.push((*known, value));
+                } else {
+                    return Err(Error::Tool(format!(
+                        "docker run option '{name}' is not supported while container limits are configured"
+                    )));
+                }
+                continue;
+            }
+
+            let flags = &token[1..];
+            for (offset, flag) in flags.char_indices() {
+                let Some((_, name)) = RUN_SHORT_FLAGS.iter().find(|(short, _)| *short == flag)
+                else {
+                    return Err(Error::Tool(format!(
+                        "docker run option '-{flag}' is not supported while container limits are configured"
+                    )));
+                };
+                if RUN_BOOLEAN_FLAGS.contains(name) {
+                    options.push((*name, None));
+                    continue;
+                }
+                let rest = &flags[offset + flag.len_utf8()..];
+                let value = if rest.is_empty() {
+                    needs_value(name, &mut tokens)?
+                } else {
+                    Some(rest.strip_prefix('=').unwrap_or(rest).to_owned())
+                };
+                options.push((*name, value));
+                break;
+            }
+        }
+        Ok(options)
+    }
+
+    /// Host directory bound by a `--volume` or `--mount` value, if any.
+    fn bind_source(name: &str, value: &str) -> Option<String> {
+        if name == "--volume" {
+            let (source, _) = value.split_once(':')?;
+            return (source.starts_with('/') || source.starts_with('.')).then(|| source.to_owned());
+        }
+        let fields = value
+            .split(',')
+            .filter_map(|field| field.split_once('='))
+            .collect::<Vec<_>>();
+        let is_bind = fields
+            .iter()
+            .any(|(key, kind)| *key == "type" && *kind == "bind");
+        fields
+            .iter()
+            .find(|(key, _)| is_bind && matches!(*key, "source" | "src"))
+            .map(|(_, source)| (*source).to_owned())
+    }
+
+    /// Prepends the configured `run` limits. Option tokens before the image
+    /// may not override them, escalate privileges, join host namespaces or
+    /// bind host paths outside the workspace.
+    fn apply_run_limits(
+        &self,
+        run_args: Vec<String>,
+        context: &ToolExecutionContext,
+    ) -> Result<Invocation> {
+        let limits = self.run_limit_flags();
+        if !limits.is_empty() {
+            let guarded = limits
+                .iter()
+                .flat_map(|(names, _)| names.iter().copied())
+                .collect::<Vec<_>>();
+            for (name, value) in Self::parse_run_options(&run_args)? {
+                let value = value.unwrap_or_default();
+                let escapes = RUN_ESCAPE_FLAGS.contains(&name)
+                    && !(RUN_BOOLEAN_FLAGS.contains(&name) && value == "false");
+                if guarded.contains(&name) {
+                    return Err(Error::Tool(format!(
+                        "docker run option '{name}' is fixed by the tool configuration"
+                    )));
+                }
+                if escapes || (RUN_NAMESPACE_FLAGS.contains(&name) && value == "host") {
+                    return Err(Error::Tool(format!(
+                        "docker run option '{name}' is not allowed while container limits are configured"
+                    )));
+                }
+                if name == "--mount" && value.contains("volume-opt") {
+                    return Err(Error::Tool(
+                        "docker run '--mount' volume options are not allowed while container limits are configured"
+                            .to_owned(),
+                    ));
+                }
+                if matches!(name, "--volume" | "--mount") {
+                    if let Some(source) = Self::bind_source(name, &value) {
+                        Self::resolve_workspace_path(context, &source)?;
+                    }
+                }
+            }
+        }
+
+        let mut args = vec!["run".to_owned()];
+        let mut applied = Map::new();
+        for (names, value) in limits {
+            args.push(names[0].to_owned());
+            args.push(value.to_owned());
+            applied.insert(
+                names[0].trim_start_matches('-').to_owned(),
+                Value::String(value.to_owned()),
+            );
+        }
+        args.extend(run_args);
+        let mut invocation = Invocation::new(args);
+        if !applied.is_empty() {
+            invocation
+                .details
+                .insert("limits".to_owned(), Value::Object(applied));
+        }
+        Ok(invocation)
+    }
+
+    /// Checks `exec` extra options while limits are configured. Only
+    /// `-w`/`--workdir` and `-e`/`--env` are accepted, so options such as
+    /// `--privileged` cannot undo what `run` enforced.
+    fn check_exec_extras(&self, extra: &[String]) -> Result<()> {
+        if self.run_limit_flags().is_empty() {
+            return Ok(());
+        }
+        let mut tokens = extra.iter();
+        while let Some(token) = tokens.next() {
+            let (name, inline) = match token.split_once('=') {
+                Some((name, _)) if name.starts_with("--") => (name, true),
+                _ => (token.as_str(), false),
+            };
+            let (name, inline) = match name {
+                "-w" | "--workdir" | "-e" | "--env" => (name, inline),
+                _ if name.len() > 2 && (name.starts_with("-w") || name.starts_with("-e")) => {
+                    (&name[..2], true)
+                }
+                _ => {
+                    return Err(Error::Tool(format!(
+                        "docker exec option '{name}' is not allowed while container limits are configured (use -w or -e)"
+                    )))
+                }
+            };
+            if !inline && tokens.next().is_none() {
+                return Err(Error::Tool(format!(
+                    "docker exec option '{name}' needs a value"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn exec_invocation(&self, args: &Value, extra: Vec<String>) -> Result<Invocation> {
+        self.check_exec_extras(&extra)?;
+        let container = Self::required_str(args, "container", "exec")?;
+        let command = Self::string_list(args, "command");
+        if command.is_empty() {
+            return Err(Error::Tool(
+                "docker action 'exec' requires a non-empty 'command' array".to_owned(),
+            ));
+        }
+        let mut argv = vec!["exec".to_owned()];
+        argv.extend(extra);
+        if let Some(workdir) = Self::optional_str(args, "workdir") {
+            argv.extend(["--workdir".to_owned(), workdir.to_owned()]);
+        }
+        if let Some(user) = Self::optional_str(args, "user") {
+            argv.extend(["--user".to_owned(), user.to_owned()]);
+        }
+        for (key, value) in Self::string_map(args, "env")? {
+            argv.extend(["--env".to_owned(), format!("{key}={value}")]);
+        }
+        argv.push(container.to_owned());
+        argv.extend(command);
+        Ok(Invocation::new(argv))
+    }
+
+    fn cp_invocation(
+        args: &Value,
+        extra: Vec<String>,
+        context: &ToolExecutionContext,
+    ) -> Result<Invocation> {
+        let container = Self::required_str(args, "container", "cp")?;
+        let container_path = Self::required_str(args, "container_path", "cp")?;
+        let host_path = Self::required_str(args, "host_path", "cp")?;
+        let host_path = Self::resolve_workspace_path(context, host_path)?;
+        let remote = format!("{container}:{container_path}");
+        let local = host_path.display().to_string();
+        let (source, destination) = match Self::required_str(args, "direction", "cp")? {
+            "in" => {
+                if !host_path.exists() {
+                    return Err(Error::Tool(format!("host path '{local}' does not exist")));
+                }
+                (local, remote)
+            }
+            "out" => (remote, local),
+            other => {
+                return Err(Error::Tool(format!(
+                    "unsupported cp direction '{other}' (expected in|out)"
+                )))
+            }
+        };
+        let mut argv = vec!["cp".to_owned()];
+        argv.extend(extra);
+        argv.extend([source, destination]);
+        Ok(Invocation::new(argv))
+    }
+
+    fn compose_invocation(
+        action: &str,
+        args: &Value,
+        extra: Vec<String>,
+        context: &ToolExecutionContext,
+    ) -> Result<Invocation> {
+        let mut argv = vec!["compose".to_owned()];
+        if let Some(file) = Self::optional_str(args, "compose_file") {
+            let file = Self::resolve_workspace_path(context, file)?;
+            argv.extend(["--file".to_owned(), file.display().to_string()]);
+        }
+        if let Some(project) = Self::optional_str(args, "project_name") {
+            argv.extend(["--project-name".to_owned(), project.to_owned()]);
+        }
+        match action {
+            // Always detached: the tool cannot attach to a foreground stack.
+            "compose_up" => argv.extend(["up".to_owned(), "--detach".to_owned()]),
+            "compose_down" => argv.push("down".to_owned()),
+            "compose_ps" => argv.extend(["ps".to_owned(), "--all".to_owned()]),
+            "compose_logs" => {
+                let tail = args
+                    .get("tail")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(DEFAULT_COMPOSE_LOG_TAIL);
+                argv.extend([
+                    "logs".to_owned(),
+                    "--no-color".to_owned(),
+                    "--tail".to_owned(),
+                    tail.to_string(),
+                ]);
+            }
+            _ => {
+                return Err(Error::Tool(format!(
+                    "unsupported compose action '{action}'"
+                )))
+            }
+        }
+        argv.extend(extra);
+        argv.extend(Self::string_list(args, "services"));
+        Ok(Invocation::new(argv))
+    }
+
+    /// Packs `context_dir` into an uncompressed tarball, honouring
+    /// `.dockerignore` and never following symlinks out of the workspace.
+    fn write_build_context(
+        context_dir: &Path,
+        dockerfile: &str,
+        archive_path: &Path,
+    ) -> Result<BuildContext> {
+        let archive = std::fs::File::create(archive_path)
+            .map_err(|err| Error::Tool(format!("failed to create build context archive: {err}")))?;
+        let mut builder = tar::Builder::new(archive);
+        builder.follow_symlinks(false);
+
+        let mut summary = BuildContext { files: 0, bytes: 0 };
+        let mut saw_dockerfile = false;
+        let walker = ignore::WalkBuilder::new(context_dir)
+            .standard_filters(false)
+            .add_custom_ignore_filename(".dockerignore")
+            .follow_links(false)
+            .build();
+        for entry in walker {
+            let entry =
+                entry.map_err(|err| Error::Tool(format!("failed to walk build context: {err}")))?;
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(context_dir) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            let metadata = entry.path().symlink_metadata().map_err(|err| {
+                Error::Tool(format!("failed to read '{}': {err}", path.display()))
+            })?;
+            if metadata.is_file() {
+                summary.files += 1;
+                summary.bytes += metadata.len();
+                if summary.bytes > MAX_BUILD_CONTEXT_BYTES {
+                    return Err(Error::Tool(format!(
+                        "build context exceeds {} MiB; add a .dockerignore",
+                        MAX_BUILD_CONTEXT_BYTES / (1024 * 1024)
+                    )));
+                }
+            }
+            saw_dockerfile |= relative == Path::new(dockerfile);
+            builder
+                .append_path_with_name(path, relative)
+                .map_err(|err| {
+                    Error::Tool(format!(
+                        "failed to add '{}' to build context: {err}",
+                        relative.display()
+                    ))
+                })?;
+        }
+
+        // The engine always needs the Dockerfile, even when .dockerignore lists it.
+        if !saw_dockerfile {
+            let path = context_dir.join(dockerfile);
+            if !path.is_file() {
+                return Err(Error::Tool(format!(
+                    "Dockerfile '{dockerfile}' not found in build context"
+                )));
+            }
+            builder
+                .append_path_with_name(&path, dockerfile)
+                .map_err(|err| Error::Tool(format!("failed to add Dockerfile: {err}")))?;
+            summary.files += 1;
+        }
+        builder
+            .finish()
+            .map_err(|err| Error::Tool(format!("failed to finish build context: {err}")))?;
+        Ok(summary)
+    }
+
+    async fn build_invocation(
+        args: &Value,
+        extra: Vec<String>,
+        context: &ToolExecutionContext,
+    ) -> Result<Invocation> {
+        let context_dir = Self::resolve_workspace_path(
+            context,
+            Self::optional_str(args, "context").unwrap_or("."),
+        )?;
+        if !context_dir.is_dir() {
+            return Err(Error::Tool(format!(
+                "build context '{}' is not a directory",
+                context_dir.display()
+            )));
+        }
+        let dockerfile = Self::optional_str(args, "dockerfile")
+            .unwrap_or("Dockerfile")
+            .trim_start_matches("./")
+            .to_owned();
+        if Path::new(&dockerfile).is_absolute()
+            || Path::new(&dockerfile)
+                .components()
+                .any(|part| matches!(part, std::path::Component::ParentDir))
+        {
+            return Err(Error::Tool(
+                "'dockerfile' must be a path inside the build context".to_owned(),
+            ));
+        }
+
+        let archive_path =
+            std::env::temp_dir().join(format!("rustic-ai-build-{}.tar", uuid::Uuid::new_v4()));
+        let packed = {
+            let context_dir = context_dir.clone();
+            let dockerfile = dockerfile.clone();
+            let archive_path = archive_path.clone();
+            tokio::task::spawn_blocking(move || {
+                Self::write_build_context(&context_dir, &dockerfile, &archive_path)
+            })
+            .await
+            .map_err(|err| Error::Tool(format!("build context task failed: {err}")))?
+        };
+        let summary = match packed {
+            Ok(summary) => summary,
+            Err(err) => {
+                let _ = std::fs::remove_file(&archive_path);
+                return Err(err);
+            }
+        };
+
+        let mut argv = vec!["build".to_owned(), "--file".to_owned(), dockerfile];
+        if let Some(tag) = Self::optional_str(args, "tag") {
+            argv.extend(["--tag".to_owned(), tag.to_owned()]);
+        }
+        if let Some(target) = Self::optional_str(args, "target") {
+            argv.extend(["--target".to_owned(), target.to_owned()]);
+        }
+        for (key, value) in Self::string_map(args, "build_args")? {
+            argv.extend(["--build-arg".to_owned(), format!("{key}={value}")]);
+        }
+        argv.extend(extra);
+        argv.push("-".to_owned());
+
+        let mut invocation = Invocation::new(argv);
+        invocation.stdin = Some(archive_path);
+        invocation.details.insert(
+            "context".to_owned(),
+            json!({
+                "path": context_dir,
+                "files": summary.files,
+                "bytes": summary.bytes,
+            }),
+        );
+        Ok(invocation)
+    }
+
+    async fn prepare(
+        &self,
+        action: &str,
+        args: &Value,
+        context: &ToolExecutionContext,
+    ) -> Result<Invocation> {
+        let extra = Self::parse_args(args);
+        match action {
+            "run" => self.apply_run_limits(extra, context),
+            "exec" => self.exec_invocation(args, extra),
+            "cp" => Self::cp_invocation(args, extra, context),
+            "build" => Self::build_invocation(args, extra, context).await,
+            "compose_up" | "compose_down" | "compose_ps" | "compose_logs" => {
+                Self::compose_invocation(action, args, extra, context)
+            }
+            _ => {
+                let mut argv = vec![action.to_owned()];
+                argv.extend(extra);
+                Ok(Invocation::new(argv))
+            }
+        }
+    }
+
+    async fn run_engine(
+        engine_binary: &str,
+        invocation: &Invocation,
+        context: &ToolExecutionContext,
+        timeout_seconds: u64,
+    ) -> Result<std::process::Output> {
+        let mut command = Command::new(engine_binary);
+        command.args(&invocation.args);
+        command.current_dir(&context.working_directory);
+        command.kill_on_drop(true);
+        match &invocation.stdin {
+            Some(path) => {
+                let file = std::fs::File::open(path).map_err(|err| {
+                    Error::Tool(format!("failed to open build context archive: {err}"))
+                })?;
+                command.stdin(Stdio::from(file));
+            }
+            None => {
+                command.stdin(Stdio::null());
+            }
+        }
+
+        timeout(Duration::from_secs(timeout_seconds), command.output())
+            .await
+            .map_err(|_| {
+                Error::Timeout(format!(
+                    "{engine_binary} command timed out after {timeout_seconds} seconds"
+                ))
+            })?
+            .map_err(|err| Error::Tool(format!("failed to execute {engine_binary} command: {err}")))
+    }
 }
 
 #[async_trait::async_trait]
@@ -110,7 +925,7 @@ impl Tool for DockerTool {
     }
 
     fn description(&self) -> &str {
-        "Run bounded docker operations"
+        "Run bounded docker operations: containers, exec, build, cp and compose stacks"
     }
 
     fn schema(&self) -> &Value {
@@ -129,9 +944,8 @@ impl Tool for DockerTool {
         context: &ToolExecutionContext,
     ) -> Result<ToolResult> {
         let action = Self::parse_action(&args)?;
-        let action_args = Self::parse_args(&args);
         let engine = Self::parse_engine(&args)?;
-        let timeout_seconds = self.parse_timeout(&args);
+        let timeout_seconds = self.parse_timeout(&action, &args);
         Self::enforce_permission(&action, context)?;
 
         let selected_engine = match engine.as_str() {
@@ -140,41 +954,31 @@ impl Tool for DockerTool {
             _ => "docker",
         };
 
-        let run_output = async |engine_binary: &str| -> Result<std::process::Output> {
-            let mut command = Command::new(engine_binary);
-            command.arg(&action);
-            command.args(&action_args);
-            command.current_dir(&context.working_directory);
-
-            timeout(Duration::from_secs(timeout_seconds), command.output())
-                .await
-                .map_err(|_| {
-                    Error::Timeout(format!(
-                        "{engine_binary} command timed out after {timeout_seconds} seconds"
-                    ))
-                })?
-                .map_err(|err| {
-                    Error::Tool(format!("failed to execute {engine_binary} command: {err}"))
-                })
-        };
-
-        let (output, used_engine) = match run_output(selected_engine).await {
-            Ok(result) => (result, selected_engine),
-            Err(err) if engine == "auto" => {
-                let not_found = matches!(
-                    &err,
-                    Error::Tool(message)
-                        if message.contains("failed to execute docker command")
-                            && message.to_ascii_lowercase().contains("no such file")
-                );
-                if not_found {
-                    (run_output("podman").await?, "podman")
-                } else {
-                    return Err(err);
+        let invocation = self.prepare(&action, &args, context).await?;
+        let result =
+            match Self::run_engine(selected_engine, &invocation, context, timeout_seconds).await {
+                Ok(result) => Ok((result, selected_engine)),
+                Err(err) if engine == "auto" => {
+                    let not_found = matches!(
+                        &err,
+                        Error::Tool(message)
+                            if message.contains("failed to execute docker command")
+                                && message.to_ascii_lowercase().contains("no such file")
+                    );
+                    if not_found {
+                        Self::run_engine("podman", &invocation, context, timeout_seconds)
+                            .await
+                            .map(|result| (result, "podman"))
+                    } else {
+                        Err(err)
+                    }
                 }
-            }
-            Err(err) => return Err(err),
-        };
+                Err(err) => Err(err),
+            };
+        if let Some(archive) = &invocation.stdin {
+            let _ = std::fs::remove_file(archive);
+        }
+        let (output, used_engine) = result?;
 
         let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
         let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
@@ -195,18 +999,21 @@ impl Tool for DockerTool {
             }
         }
 
+        let mut payload = json!({
+            "action": action,
+            "args": invocation.args,
+            "engine": used_engine,
+            "success": output.status.success(),
+            "stdout": stdout,
+            "stderr": stderr,
+        });
+        if let Some(object) = payload.as_object_mut() {
+            object.extend(invocation.details);
+        }
         Ok(ToolResult {
             success: output.status.success(),
             exit_code: output.status.code(),
-            output: json!({
-                "action": action,
-                "args": action_args,
-                "engine": used_engine,
-                "success": output.status.success(),
-                "stdout": stdout,
-                "stderr": stderr,
-            })
-            .to_string(),
+            output: payload.to_string(),
         })
     }
 }
@@ -5,18 +5,22 @@ use std::collections::{HashSet, VecDeque};
 use std::fs;
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use crate::config::schema::{ToolConfig, WorkingDirMode};
 use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::rules::discovery::simple_glob_match;
+use crate::tools::journal::EditJournal;
+use crate::tools::patch;
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 
 #[derive(Debug, Clone)]
 pub struct FilesystemTool {
     config: ToolConfig,
     schema: Value,
+    journal: Arc<EditJournal>,
 }
 
 const MAX_READ_BYTES: u64 = 10 * 1024 * 1024;
@@ -32,26 +36,50 @@ const MAX_DELETE_ENTRIES: usize = 50_000;
 const MAX_MKDIR_DEPTH: usize = 32;
 
 impl FilesystemTool {
-    pub fn new(config: ToolConfig) -> Self {
+    pub fn new(config: ToolConfig, journal: Arc<EditJournal>) -> Self {
         let schema = json!({
             "type": "object",
             "properties": {
                 "operation": {
                     "type": "string",
                     "enum": [
-                        "read", "write", "edit", "list", "mkdir", "delete", "copy", "move", "info", "glob", "hash"
+                        "read", "write", "edit", "apply_patch", "list", "mkdir", "delete", "copy",
+                        "move", "info", "glob", "hash"
                     ]
                 },
                 "path": { "type": "string" },
                 "content": { "type": "string" },
                 "source": { "type": "string" },
                 "destination": { "type": "string" },
-                "pattern": { "type": "string" }
+                "pattern": { "type": "string" },
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff or SEARCH/REPLACE blocks for apply_patch"
+                },
+                "edits": {
+                    "type": "array",
+                    "description": "apply_patch replacements across files",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": { "type": "string" },
+                            "old_text": { "type": "string" },
+                            "new_text": { "type": "string" },
+                            "replace_all": { "type": "boolean" }
+                        },
+                        "required": ["path", "old_text"]
+                    }
+                },
+                "dry_run": { "type": "boolean" }
             },
             "required": ["operation"]
         });
 
-        Self { config, schema }
+        Self {
+            config,
+            schema,
+            journal,
+        }
     }
 
     fn resolve_working_dir(&self, context: &ToolExecutionContext, args: &Value) -> Result<PathBuf> {
@@ -422,6 +450,99 @@ impl FilesystemTool {
         Ok(())
     }
 
+    /// Paths a write-mode operation may change, snapshotted into the session's
+    /// undo journal before the operation runs.
+    fn journal_targets(
+        &self,
+        operation: &str,
+        args: &Value,
+        working_dir: &Path,
+    ) -> Result<Option<Vec<PathBuf>>> {
+        let keys: &[&str] = match operation {
+            "write" | "edit" | "mkdir" | "delete" => &["path"],
+            "copy" => &["destination"],
+            "move" => &["source", "destination"],
+            _ => return Ok(None),
+        };
+        keys.iter()
+            .map(|key| self.checked_path(self.required_string(args, key)?, working_dir))
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    fn with_journaled(mut result: ToolResult, journaled: bool) -> ToolResult {
+        if let Ok(Value::Object(mut payload)) = serde_json::from_str::<Value>(&result.output) {
+            payload.insert("journaled".to_owned(), Value::Bool(journaled));
+            result.output = Value::Object(payload).to_string();
+        }
+        result
+    }
+
+    fn apply_patch(
+        &self,
+        args: &Value,
+        working_dir: &Path,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let mut patches = match args.get("patch").and_then(Value::as_str) {
+            Some(raw) if !raw.trim().is_empty() => patch::parse(raw)?,
+            _ => Vec::new(),
+        };
+        if let Some(edits) = args.get("edits") {
+            patches.extend(patch::parse_edits(edits)?);
+        }
+        if patches.is_empty() {
+            return Err(Error::Tool(
+                "apply_patch requires a 'patch' string or an 'edits' array".to_owned(),
+            ));
+        }
+        let dry_run = Self::optional_bool(args, "dry_run", false);
+
+        let (plan, stats) = patch::plan(
+            &patches,
+            |raw| self.checked_path(raw, working_dir),
+            MAX_EDIT_BYTES,
+        )?;
+        let files = plan
+            .iter()
+            .map(|file| {
+                let action = match (&file.original, &file.updated) {
+                    (None, _) => "created",
+                    (_, None) => "deleted",
+                    _ => "modified",
+                };
+                let applied = stats.get(&file.path);
+                json!({
+                    "path": file.path.to_string_lossy(),
+                    "action": action,
+                    "hunks": applied.map_or(0, |applied| applied.hunks),
+                    "fuzzy_hunks": applied.map_or(0, |applied| applied.fuzzy)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if dry_run {
+            return Ok(Self::operation_result(json!({
+                "dry_run": true,
+                "files": files
+            })));
+        }
+
+        let targets = plan
+            .iter()
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        let pending = self.journal.begin("apply_patch", &targets);
+        patch::commit(&plan)?;
+        let journaled = self.journal.commit(context.session_id, pending);
+
+        Ok(Self::operation_result(json!({
+            "dry_run": false,
+            "files": files,
+            "journaled": journaled
+        })))
+    }
+
     fn execute_operation(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
         let operation = self
             .required_string(&args, "operation")?
@@ -429,7 +550,25 @@ impl FilesystemTool {
         self.enforce_agent_permission(&operation, context)?;
         let working_dir = self.resolve_working_dir(context, &args)?;
 
-        match operation.as_str() {
+        if operation == "apply_patch" {
+            return self.apply_patch(&args, &working_dir, context);
+        }
+        let Some(targets) = self.journal_targets(&operation, &args, &working_dir)? else {
+            return self.run_operation(&operation, args, working_dir);
+        };
+        let pending = self.journal.begin(&operation, &targets);
+        let result = self.run_operation(&operation, args, working_dir)?;
+        let journaled = self.journal.commit(context.session_id, pending);
+        Ok(Self::with_journaled(result, journaled))
+    }
+
+    fn run_operation(
+        &self,
+        operation: &str,
+        args: Value,
+        working_dir: PathBuf,
+    ) -> Result<ToolResult> {
+        match operation {
             "read" => {
                 let path = self.checked_path(self.required_string(&args, "path")?, &working_dir)?;
                 let metadata = fs::metadata(&path).map_err(|err| {
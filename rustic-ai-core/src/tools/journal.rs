@@ -0,0 +1,356 @@
+//! Per-session undo/redo journal for filesystem tool writes. Each entry stores
+//! the before/after state of every path an operation touched, so undo and redo
+//! restore exact bytes and refuse to clobber files changed since.
+
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+const MAX_ENTRIES_PER_SESSION: usize = 50;
+/// Snapshots larger than this are not journaled; the write still happens.
+const MAX_ENTRY_BYTES: usize = 32 * 1024 * 1024;
+const MAX_SNAPSHOT_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathState {
+    Missing,
+    Directory,
+    File(Vec<u8>),
+}
+
+impl PathState {
+    fn size(&self) -> usize {
+        match self {
+            Self::File(bytes) => bytes.len(),
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PathChange {
+    path: PathBuf,
+    before: PathState,
+    after: PathState,
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    id: u64,
+    operation: String,
+    recorded_at: chrono::DateTime<chrono::Utc>,
+    changes: Vec<PathChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntrySummary {
+    pub id: u64,
+    pub operation: String,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub paths: Vec<String>,
+}
+
+impl From<&JournalEntry> for JournalEntrySummary {
+    fn from(entry: &JournalEntry) -> Self {
+        Self {
+            id: entry.id,
+            operation: entry.operation.clone(),
+            recorded_at: entry.recorded_at,
+            paths: entry
+                .changes
+                .iter()
+                .map(|change| change.path.display().to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionJournal {
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+/// Captured pre-operation state, turned into an entry once the operation succeeds.
+#[derive(Debug)]
+pub struct PendingChange {
+    operation: String,
+    roots: Vec<PathBuf>,
+    before: Option<BTreeMap<PathBuf, PathState>>,
+}
+
+#[derive(Debug, Default)]
+pub struct EditJournal {
+    sessions: Mutex<HashMap<Option<Uuid>, SessionJournal>>,
+    next_id: Mutex<u64>,
+}
+
+impl EditJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshots `paths` before a write. Paths that do not exist are tracked from
+    /// their highest missing ancestor so created parent directories are undone too.
+    pub fn begin(&self, operation: &str, paths: &[PathBuf]) -> PendingChange {
+        let roots = paths
+            .iter()
+            .map(|path| snapshot_root(path))
+            .collect::<Vec<_>>();
+        let before = snapshot_all(&roots);
+        PendingChange {
+            operation: operation.to_owned(),
+            roots,
+            before,
+        }
+    }
+
+    /// Records a completed operation. Returns whether it was journaled; oversized
+    /// or unreadable snapshots are skipped.
+    pub fn commit(&self, session_id: Option<Uuid>, pending: PendingChange) -> bool {
+        let Some(before) = pending.before else {
+            return false;
+        };
+        let Some(after) = snapshot_all(&pending.roots) else {
+            return false;
+        };
+
+        let mut paths = before
+            .keys()
+            .chain(after.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        let changes = paths
+            .into_iter()
+            .filter_map(|path| {
+                let before = before.get(&path).cloned().unwrap_or(PathState::Missing);
+                let after = after.get(&path).cloned().unwrap_or(PathState::Missing);
+                (before != after).then_some(PathChange {
+                    path,
+                    before,
+                    after,
+                })
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return true;
+        }
+
+        let id = {
+            let Ok(mut next_id) = self.next_id.lock() else {
+                return false;
+            };
+            *next_id += 1;
+            *next_id
+        };
+        let Ok(mut sessions) = self.sessions.lock() else {
+            return false;
+        };
+        let journal = sessions.entry(session_id).or_default();
+        journal.undo.push(JournalEntry {
+            id,
+            operation: pending.operation,
+            recorded_at: chrono::Utc::now(),
+            changes,
+        });
+        if journal.undo.len() > MAX_ENTRIES_PER_SESSION {
+            journal.undo.remove(0);
+        }
+        journal.redo.clear();
+        true
+    }
+
+    pub fn history(&self, session_id: Option<Uuid>) -> Vec<JournalEntrySummary> {
+        self.sessions
+            .lock()
+            .map(|sessions| {
+                sessions
+                    .get(&session_id)
+                    .map(|journal| journal.undo.iter().rev().map(Into::into).collect())
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Restores the before-state of the latest entry. Unless `force`, fails when
+    /// any touched path no longer matches what the operation left behind.
+    pub fn undo(&self, session_id: Option<Uuid>, force: bool) -> Result<JournalEntrySummary> {
+        self.step(session_id, force, true)
+    }
+
+    /// Re-applies the most recently undone entry.
+    pub fn redo(&self, session_id: Option<Uuid>, force: bool) -> Result<JournalEntrySummary> {
+        self.step(session_id, force, false)
+    }
+
+    fn step(
+        &self,
+        session_id: Option<Uuid>,
+        force: bool,
+        undo: bool,
+    ) -> Result<JournalEntrySummary> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| Error::Tool("edit journal lock poisoned".to_owned()))?;
+        let journal = sessions.entry(session_id).or_default();
+        let (from, verb) = if undo {
+            (&mut journal.undo, "undo")
+        } else {
+            (&mut journal.redo, "redo")
+        };
+        let entry = from
+            .last()
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("nothing to {verb}")))?;
+
+        let expected = |change: &PathChange| {
+            if undo {
+                change.after.clone()
+            } else {
+                change.before.clone()
+            }
+        };
+        if !force {
+            for change in &entry.changes {
+                if read_state(&change.path)? != expected(change) {
+                    return Err(Error::Tool(format!(
+                        "cannot {verb} '{}': '{}' changed since; retry with force",
+                        entry.operation,
+                        change.path.display()
+                    )));
+                }
+            }
+        }
+
+        let targets = entry
+            .changes
+            .iter()
+            .map(|change| {
+                let target = if undo { &change.before } else { &change.after };
+                (change.path.clone(), target.clone())
+            })
+            .collect::<Vec<_>>();
+        apply_states(&targets)?;
+
+        from.pop();
+        if undo {
+            journal.redo.push(entry.clone());
+        } else {
+            journal.undo.push(entry.clone());
+        }
+        Ok((&entry).into())
+    }
+}
+
+fn snapshot_root(path: &Path) -> PathBuf {
+    let mut root = path.to_path_buf();
+    while let Some(parent) = root.parent() {
+        if parent.as_os_str().is_empty() || fs::symlink_metadata(parent).is_ok() {
+            break;
+        }
+        root = parent.to_path_buf();
+    }
+    root
+}
+
+fn read_state(path: &Path) -> Result<PathState> {
+    match fs::symlink_metadata(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(PathState::Missing),
+        Err(err) => Err(Error::Tool(format!(
+            "failed to inspect '{}': {err}",
+            path.display()
+        ))),
+        Ok(metadata) if metadata.is_dir() => Ok(PathState::Directory),
+        Ok(metadata) if metadata.is_file() => fs::read(path)
+            .map(PathState::File)
+            .map_err(|err| Error::Tool(format!("failed to read '{}': {err}", path.display()))),
+        Ok(_) => Err(Error::Tool(format!(
+            "'{}' is not a regular file or directory",
+            path.display()
+        ))),
+    }
+}
+
+/// Snapshots every root (recursively for directories). `None` when the snapshot
+/// exceeds the journal budget or contains unsupported entries such as symlinks.
+fn snapshot_all(roots: &[PathBuf]) -> Option<BTreeMap<PathBuf, PathState>> {
+    let mut states = BTreeMap::new();
+    let mut bytes = 0usize;
+    let mut stack = roots.to_vec();
+    while let Some(path) = stack.pop() {
+        if states.contains_key(&path) {
+            continue;
+        }
+        let state = read_state(&path).ok()?;
+        bytes += state.size();
+        if bytes > MAX_ENTRY_BYTES || states.len() >= MAX_SNAPSHOT_ENTRIES {
+            return None;
+        }
+        if state == PathState::Directory {
+            for entry in fs::read_dir(&path).ok()? {
+                stack.push(entry.ok()?.path());
+            }
+        }
+        states.insert(path, state);
+    }
+    Some(states)
+}
+
+/// Moves every path to its target state: removals deepest first, then
+/// directories shallowest first, then file contents.
+fn apply_states(targets: &[(PathBuf, PathState)]) -> Result<()> {
+    let depth = |path: &Path| path.components().count();
+
+    let mut removals = targets
+        .iter()
+        .filter(|(_, state)| *state == PathState::Missing)
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    removals.sort_by_key(|path| std::cmp::Reverse(depth(path)));
+    for path in removals {
+        let result = match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir(path),
+            Ok(_) => fs::remove_file(path),
+            Err(_) => Ok(()),
+        };
+        result
+            .map_err(|err| Error::Tool(format!("failed to remove '{}': {err}", path.display())))?;
+    }
+
+    let mut directories = targets
+        .iter()
+        .filter(|(_, state)| *state == PathState::Directory)
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    directories.sort_by_key(|path| depth(path));
+    for path in directories {
+        if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_file()) {
+            fs::remove_file(path).map_err(|err| {
+                Error::Tool(format!("failed to remove '{}': {err}", path.display()))
+            })?;
+        }
+        fs::create_dir_all(path)
+            .map_err(|err| Error::Tool(format!("failed to create '{}': {err}", path.display())))?;
+    }
+
+    for (path, state) in targets {
+        let PathState::File(bytes) = state else {
+            continue;
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                Error::Tool(format!("failed to create '{}': {err}", parent.display()))
+            })?;
+        }
+        fs::write(path, bytes)
+            .map_err(|err| Error::Tool(format!("failed to write '{}': {err}", path.display())))?;
+    }
+    Ok(())
+}
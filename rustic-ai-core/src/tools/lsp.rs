@@ -5,49 +5,96 @@ use lsp_types::{
 };
 use reqwest::Url;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
-use tokio::sync::mpsc;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, oneshot, Mutex, Notify};
 use tokio::time::{timeout, Duration};
 
-use crate::config::schema::ToolConfig;
+use crate::config::schema::{LspConfig, LspServerConfig, ToolConfig};
 use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 
+/// Quiet period after a diagnostics publish before the result is considered settled.
+const DIAGNOSTICS_SETTLE_MS: u64 = 300;
+const MAX_RESOLVED_CODE_ACTIONS: usize = 20;
+
 #[derive(Debug, Clone)]
 pub struct LspTool {
     config: ToolConfig,
+    lsp_config: Arc<LspConfig>,
     schema: Value,
-    servers: Arc<Mutex<HashMap<String, Arc<Mutex<LspServerHandle>>>>>,
+    servers: Arc<Mutex<HashMap<String, Arc<LspServer>>>>,
+    /// One lock per server id, held while that server starts so the pool lock
+    /// is not held across spawn and initialize.
+    starting: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+}
+
+type PendingReply = oneshot::Sender<std::result::Result<Value, Value>>;
+
+/// State shared with the server's reader task.
+#[derive(Debug, Default)]
+struct ServerInbox {
+    pending: HashMap<u64, PendingReply>,
+    /// Latest `publishDiagnostics` per document uri, tagged with a publish counter.
+    diagnostics: HashMap<String, (u64, Vec<Value>)>,
+    publishes: u64,
+    closed: bool,
 }
 
 #[derive(Debug)]
-struct LspServerHandle {
+struct OpenDocument {
+    version: i32,
+    text: String,
+}
+
+#[derive(Debug)]
+struct LspServer {
     server_id: String,
+    name: String,
     command: String,
     workspace_root: PathBuf,
-    child: Child,
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
-    next_id: u64,
-    symbol_cache: HashMap<String, Vec<Value>>,
+    child: Mutex<Child>,
+    stdin: Arc<Mutex<ChildStdin>>,
+    inbox: Arc<StdMutex<ServerInbox>>,
+    published: Arc<Notify>,
+    next_id: AtomicU64,
+    capabilities: StdMutex<Value>,
+    documents: Mutex<HashMap<String, OpenDocument>>,
+    symbol_cache: Mutex<HashMap<String, Vec<Value>>>,
+    last_used: StdMutex<Instant>,
+}
+
+/// Result of syncing a file into the server: its uri, current text and the
+/// publish counter observed before any change was sent.
+struct SyncedDocument {
+    uri: String,
+    text: String,
+    changed: bool,
+    publishes_before: u64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum LspOperation {
     StartServer,
     StopServer,
+    ListServers,
     SymbolSearch,
     Definition,
     References,
     Hover,
+    Diagnostics,
+    Rename,
+    CodeActions,
+    Format,
+    DocumentSymbols,
 }
 
 impl LspOperation {
@@ -55,12 +102,18 @@ impl LspOperation {
         match value.trim().to_ascii_lowercase().as_str() {
             "start_server" => Ok(Self::StartServer),
             "stop_server" => Ok(Self::StopServer),
+            "list_servers" => Ok(Self::ListServers),
             "symbol_search" => Ok(Self::SymbolSearch),
             "definition" => Ok(Self::Definition),
             "references" => Ok(Self::References),
             "hover" => Ok(Self::Hover),
+            "diagnostics" => Ok(Self::Diagnostics),
+            "rename" => Ok(Self::Rename),
+            "code_actions" => Ok(Self::CodeActions),
+            "format" => Ok(Self::Format),
+            "document_symbols" => Ok(Self::DocumentSymbols),
             other => Err(Error::Tool(format!(
-                "unsupported lsp operation '{other}' (expected start_server|stop_server|symbol_search|definition|references|hover)"
+                "unsupported lsp operation '{other}' (expected start_server|stop_server|list_servers|symbol_search|definition|references|hover|diagnostics|rename|code_actions|format|document_symbols)"
             ))),
         }
     }
@@ -69,28 +122,162 @@ impl LspOperation {
         match self {
             Self::StartServer => "start_server",
             Self::StopServer => "stop_server",
+            Self::ListServers => "list_servers",
             Self::SymbolSearch => "symbol_search",
             Self::Definition => "definition",
             Self::References => "references",
             Self::Hover => "hover",
+            Self::Diagnostics => "diagnostics",
+            Self::Rename => "rename",
+            Self::CodeActions => "code_actions",
+            Self::Format => "format",
+            Self::DocumentSymbols => "document_symbols",
+        }
+    }
+}
+
+impl LspServer {
+    fn touch(&self) {
+        if let Ok(mut last_used) = self.last_used.lock() {
+            *last_used = Instant::now();
+        }
+    }
+
+    fn idle_secs(&self) -> u64 {
+        self.last_used
+            .lock()
+            .map(|last_used| last_used.elapsed().as_secs())
+            .unwrap_or_default()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.inbox
+            .lock()
+            .map(|inbox| !inbox.closed)
+            .unwrap_or(false)
+    }
+
+    fn publishes(&self) -> u64 {
+        self.inbox
+            .lock()
+            .map(|inbox| inbox.publishes)
+            .unwrap_or_default()
+    }
+
+    fn published_diagnostics(&self, uri: &str) -> Option<(u64, Vec<Value>)> {
+        self.inbox
+            .lock()
+            .ok()
+            .and_then(|inbox| inbox.diagnostics.get(uri).cloned())
+    }
+
+    fn capability(&self, name: &str) -> Option<Value> {
+        self.capabilities
+            .lock()
+            .ok()
+            .and_then(|capabilities| capabilities.get(name).cloned())
+            .filter(|value| !value.is_null() && *value != Value::Bool(false))
+    }
+
+    async fn send(&self, payload: &Value) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        LspTool::write_message(&mut stdin, payload).await
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+        .await
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout_seconds: u64) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut inbox = self
+                .inbox
+                .lock()
+                .map_err(|_| Error::Tool("lsp inbox lock poisoned".to_owned()))?;
+            if inbox.closed {
+                return Err(Error::Tool(format!(
+                    "lsp server '{}' has exited",
+                    self.server_id
+                )));
+            }
+            inbox.pending.insert(id, tx);
+        }
+        let sent = self
+            .send(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .await;
+        if let Err(err) = sent {
+            if let Ok(mut inbox) = self.inbox.lock() {
+                inbox.pending.remove(&id);
+            }
+            return Err(err);
+        }
+
+        match timeout(Duration::from_secs(timeout_seconds), rx).await {
+            Err(_) => {
+                if let Ok(mut inbox) = self.inbox.lock() {
+                    inbox.pending.remove(&id);
+                }
+                let _ = self.notify("$/cancelRequest", json!({ "id": id })).await;
+                Err(Error::Timeout(format!(
+                    "LSP request '{}' timed out after {} seconds",
+                    method, timeout_seconds
+                )))
+            }
+            Ok(Err(_)) => Err(Error::Tool(format!(
+                "lsp server '{}' exited before answering '{method}'",
+                self.server_id
+            ))),
+            Ok(Ok(Err(err))) => Err(Error::Tool(format!("LSP '{}' error: {}", method, err))),
+            Ok(Ok(Ok(result))) => Ok(result),
         }
     }
+
+    async fn shutdown(&self, timeout_seconds: u64) {
+        if self.is_alive() {
+            let _ = self.request("shutdown", Value::Null, timeout_seconds).await;
+            let _ = self.notify("exit", Value::Null).await;
+        }
+        let _ = self.child.lock().await.start_kill();
+    }
 }
 
 impl LspTool {
-    pub fn new(config: ToolConfig) -> Self {
+    pub fn new(config: ToolConfig, lsp_config: Arc<LspConfig>) -> Self {
         let schema = json!({
             "type": "object",
             "properties": {
-                "operation": {"type": "string", "enum": ["start_server", "stop_server", "symbol_search", "definition", "references", "hover"]},
+                "operation": {"type": "string", "enum": [
+                    "start_server", "stop_server", "list_servers", "symbol_search", "definition",
+                    "references", "hover", "diagnostics", "rename", "code_actions", "format",
+                    "document_symbols"
+                ]},
                 "server_id": {"type": "string"},
-                "server_command": {"type": "string"},
+                "server_command": {"type": "string", "description": "Explicit server command; omit to select a configured server by language"},
                 "server_args": {"type": "array", "items": {"type": "string"}},
                 "workspace_root": {"type": "string"},
                 "query": {"type": "string"},
                 "file_path": {"type": "string"},
                 "line": {"type": "integer", "minimum": 0},
                 "character": {"type": "integer", "minimum": 0},
+                "end_line": {"type": "integer", "minimum": 0},
+                "end_character": {"type": "integer", "minimum": 0},
+                "new_name": {"type": "string"},
+                "only": {"type": "array", "items": {"type": "string"}},
+                "tab_size": {"type": "integer", "minimum": 1, "maximum": 16},
+                "insert_spaces": {"type": "boolean"},
+                "wait_ms": {"type": "integer", "minimum": 0, "maximum": 60000},
                 "include_declaration": {"type": "boolean"},
                 "language_id": {"type": "string"},
                 "max_results": {"type": "integer", "minimum": 1, "maximum": 500},
@@ -101,8 +288,10 @@ impl LspTool {
 
         Self {
             config,
+            lsp_config,
             schema,
             servers: Arc::new(Mutex::new(HashMap::new())),
+            starting: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -121,6 +310,12 @@ impl LspTool {
             .filter(|v| !v.is_empty())
     }
 
+    fn optional_u32(args: &Value, key: &str) -> Option<u32> {
+        args.get(key)
+            .and_then(Value::as_u64)
+            .map(|value| value.min(u64::from(u32::MAX)) as u32)
+    }
+
     fn timeout_seconds(&self, args: &Value) -> u64 {
         args.get("timeout_seconds")
             .and_then(Value::as_u64)
@@ -228,6 +423,31 @@ impl LspTool {
         }
     }
 
+    fn language_id_for(path: &Path) -> String {
+        let extension = path
+            .extension()
+            .and_then(|v| v.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "rs" => "rust",
+            "py" | "pyi" => "python",
+            "ts" => "typescript",
+            "tsx" => "typescriptreact",
+            "js" | "mjs" | "cjs" => "javascript",
+            "jsx" => "javascriptreact",
+            "go" => "go",
+            "c" | "h" => "c",
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+            "rb" => "ruby",
+            "sh" | "bash" => "shellscript",
+            "md" => "markdown",
+            "" => "plaintext",
+            other => return other.to_owned(),
+        }
+        .to_owned()
+    }
+
     async fn write_message(stdin: &mut ChildStdin, payload: &Value) -> Result<()> {
         let body = payload.to_string();
         let header = format!("Content-Length: {}\r\n\r\n", body.len());
@@ -282,74 +502,141 @@ impl LspTool {
             .map_err(|err| Error::Tool(format!("invalid JSON from LSP server: {err}")))
     }
 
-    async fn send_request(
-        handle: &mut LspServerHandle,
-        method: &str,
-        params: Value,
-        timeout_seconds: u64,
-    ) -> Result<Value> {
-        let id = handle.next_id;
-        handle.next_id = handle.next_id.saturating_add(1);
-        let req = json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "method": method,
-            "params": params,
-        });
-        Self::write_message(&mut handle.stdin, &req).await?;
-
-        loop {
-            let response = timeout(
-                Duration::from_secs(timeout_seconds),
-                Self::read_message(&mut handle.stdout),
-            )
-            .await
-            .map_err(|_| {
-                Error::Timeout(format!(
-                    "LSP request '{}' timed out after {} seconds",
-                    method, timeout_seconds
-                ))
-            })??;
-
-            if response.get("id").and_then(Value::as_u64) == Some(id) {
-                if let Some(err) = response.get("error") {
-                    return Err(Error::Tool(format!("LSP '{}' error: {}", method, err)));
+    /// Routes responses to waiting requests, records published diagnostics and
+    /// answers server-initiated requests until the server closes stdout.
+    async fn reader_loop(
+        mut stdout: BufReader<ChildStdout>,
+        stdin: Arc<Mutex<ChildStdin>>,
+        inbox: Arc<StdMutex<ServerInbox>>,
+        published: Arc<Notify>,
+    ) {
+        while let Ok(message) = Self::read_message(&mut stdout).await {
+            let method = message.get("method").and_then(Value::as_str);
+            match (method, message.get("id")) {
+                (Some(method), Some(id)) => {
+                    // Server requests (configuration, capability registration,
+                    // progress tokens) get neutral answers so servers don't stall.
+                    let result = match method {
+                        "workspace/configuration" => {
+                            let items = message
+                                .pointer("/params/items")
+                                .and_then(Value::as_array)
+                                .map_or(0, Vec::len);
+                            Value::Array(vec![Value::Null; items])
+                        }
+                        _ => Value::Null,
+                    };
+                    let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                    let mut stdin = stdin.lock().await;
+                    let _ = Self::write_message(&mut stdin, &reply).await;
+                }
+                (Some("textDocument/publishDiagnostics"), None) => {
+                    let Some(uri) = message.pointer("/params/uri").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let items = message
+                        .pointer("/params/diagnostics")
+                        .and_then(Value::as_array)
+                        .cloned()
+                        .unwrap_or_default();
+                    if let Ok(mut inbox) = inbox.lock() {
+                        inbox.publishes += 1;
+                        let publishes = inbox.publishes;
+                        inbox.diagnostics.insert(uri.to_owned(), (publishes, items));
+                    }
+                    published.notify_waiters();
                 }
-                return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+                (None, Some(id)) => {
+                    let Some(id) = id.as_u64() else {
+                        continue;
+                    };
+                    let reply = match message.get("error") {
+                        Some(error) => Err(error.clone()),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let waiter = inbox
+                        .lock()
+                        .ok()
+                        .and_then(|mut inbox| inbox.pending.remove(&id));
+                    if let Some(waiter) = waiter {
+                        let _ = waiter.send(reply);
+                    }
+                }
+                _ => {}
             }
         }
+
+        if let Ok(mut inbox) = inbox.lock() {
+            inbox.closed = true;
+            inbox.pending.clear();
+        }
+        published.notify_waiters();
     }
 
-    async fn send_notification(
-        handle: &mut LspServerHandle,
-        method: &str,
-        params: Value,
-    ) -> Result<()> {
-        let req = json!({
-            "jsonrpc": "2.0",
-            "method": method,
-            "params": params,
-        });
-        Self::write_message(&mut handle.stdin, &req).await
+    fn server_id_for(name: &str, workspace_root: &Path) -> String {
+        format!("{}::{}", name, workspace_root.display())
     }
 
-    fn server_id_for(command: &str, workspace_root: &Path) -> String {
-        format!("{}::{}", command, workspace_root.display())
+    fn client_capabilities() -> Value {
+        json!({
+            "workspace": {
+                "configuration": true,
+                "workspaceEdit": {
+                    "documentChanges": true,
+                    "resourceOperations": ["create", "rename", "delete"]
+                },
+                "symbol": {}
+            },
+            "textDocument": {
+                "synchronization": { "didSave": true },
+                "publishDiagnostics": { "relatedInformation": true },
+                "diagnostic": { "dynamicRegistration": false },
+                "hover": { "contentFormat": ["markdown", "plaintext"] },
+                "rename": { "prepareSupport": false },
+                "formatting": {},
+                "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+                "codeAction": {
+                    "codeActionLiteralSupport": {
+                        "codeActionKind": {
+                            "valueSet": [
+                                "", "quickfix", "refactor", "refactor.extract", "refactor.inline",
+                                "refactor.rewrite", "source", "source.organizeImports", "source.fixAll"
+                            ]
+                        }
+                    },
+                    "isPreferredSupport": true,
+                    "resolveSupport": { "properties": ["edit"] }
+                }
+            }
+        })
     }
 
-    async fn start_server_internal(
+    /// Returns the pooled server for `name` at `workspace_root`, starting it if
+    /// needed and shutting down the least recently used servers past the limit.
+    async fn get_or_start_server(
         &self,
+        name: &str,
         command: &str,
         args: &[String],
+        initialization_options: Option<&Value>,
         workspace_root: &Path,
         timeout_seconds: u64,
-    ) -> Result<String> {
-        let server_id = Self::server_id_for(command, workspace_root);
-        {
-            let servers = self.servers.lock().await;
-            if servers.contains_key(&server_id) {
-                return Ok(server_id);
-            }
+    ) -> Result<Arc<LspServer>> {
+        let server_id = Self::server_id_for(name, workspace_root);
+        if let Some(server) = self.pooled_server(&server_id).await {
+            return Ok(server);
+        }
+        let start_lock = self
+            .starting
+            .lock()
+            .await
+            .entry(server_id.clone())
+            .or_default()
+            .clone();
+        let _starting = start_lock.lock().await;
+        // Another call may have started the server while we waited.
+        if let Some(server) = self.pooled_server(&server_id).await {
+            return Ok(server);
         }
 
         let mut cmd = Command::new(command);
@@ -357,10 +644,14 @@ impl LspTool {
             .current_dir(workspace_root)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null());
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
 
         let mut child = cmd.spawn().map_err(|err| {
-            Error::Tool(format!("failed to spawn LSP server '{}': {err}", command))
+            Error::Tool(format!(
+                "failed to spawn LSP server '{}': {err} (install it or adjust lsp.servers)",
+                command
+            ))
         })?;
         let stdin = child
             .stdin
@@ -371,40 +662,91 @@ impl LspTool {
             .take()
             .ok_or_else(|| Error::Tool("LSP child missing stdout".to_owned()))?;
 
-        let mut handle = LspServerHandle {
+        let stdin = Arc::new(Mutex::new(stdin));
+        let inbox = Arc::new(StdMutex::new(ServerInbox::default()));
+        let published = Arc::new(Notify::new());
+        tokio::spawn(Self::reader_loop(
+            BufReader::new(stdout),
+            stdin.clone(),
+            inbox.clone(),
+            published.clone(),
+        ));
+
+        let server = Arc::new(LspServer {
             server_id: server_id.clone(),
+            name: name.to_owned(),
             command: command.to_owned(),
             workspace_root: workspace_root.to_path_buf(),
-            child,
+            child: Mutex::new(child),
             stdin,
-            stdout: BufReader::new(stdout),
-            next_id: 1,
-            symbol_cache: HashMap::new(),
-        };
+            inbox,
+            published,
+            next_id: AtomicU64::new(1),
+            capabilities: StdMutex::new(Value::Null),
+            documents: Mutex::new(HashMap::new()),
+            symbol_cache: Mutex::new(HashMap::new()),
+            last_used: StdMutex::new(Instant::now()),
+        });
 
         let root_uri = Self::path_to_lsp_uri(workspace_root, true)?;
+        let initialized = server
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "rootUri": root_uri,
+                    "capabilities": Self::client_capabilities(),
+                    "initializationOptions": initialization_options,
+                    "trace": "off",
+                    "workspaceFolders": [{
+                        "uri": root_uri,
+                        "name": workspace_root.file_name().and_then(|v| v.to_str()).unwrap_or("workspace")
+                    }]
+                }),
+                timeout_seconds,
+            )
+            .await;
+        let init = match initialized {
+            Ok(init) => init,
+            Err(err) => {
+                let _ = server.child.lock().await.start_kill();
+                return Err(err);
+            }
+        };
+        if let Ok(mut capabilities) = server.capabilities.lock() {
+            *capabilities = init.get("capabilities").cloned().unwrap_or(Value::Null);
+        }
+        server.notify("initialized", json!({})).await?;
 
-        let _init = Self::send_request(
-            &mut handle,
-            "initialize",
-            json!({
-                "processId": null,
-                "rootUri": root_uri,
-                "capabilities": {},
-                "trace": "off",
-                "workspaceFolders": [{
-                    "uri": root_uri,
-                    "name": workspace_root.file_name().and_then(|v| v.to_str()).unwrap_or("workspace")
-                }]
-            }),
-            timeout_seconds,
-        )
-        .await?;
-        Self::send_notification(&mut handle, "initialized", json!({})).await?;
+        let mut servers = self.servers.lock().await;
+        servers.insert(server_id, server.clone());
+        let max_servers = self.lsp_config.max_servers.max(1);
+        while servers.len() > max_servers {
+            let Some(oldest) = servers
+                .values()
+                .filter(|candidate| candidate.server_id != server.server_id)
+                .max_by_key(|candidate| candidate.idle_secs())
+                .map(|candidate| candidate.server_id.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = servers.remove(&oldest) {
+                tokio::spawn(async move { evicted.shutdown(5).await });
+            }
+        }
+        Ok(server)
+    }
 
+    /// Returns the live pooled server for `server_id`, dropping it if it exited.
+    async fn pooled_server(&self, server_id: &str) -> Option<Arc<LspServer>> {
         let mut servers = self.servers.lock().await;
-        servers.insert(server_id.clone(), Arc::new(Mutex::new(handle)));
-        Ok(server_id)
+        let server = servers.get(server_id).cloned()?;
+        if server.is_alive() {
+            server.touch();
+            return Some(server);
+        }
+        servers.remove(server_id);
+        None
     }
 
     async fn stop_server_internal(&self, server_id: &str, timeout_seconds: u64) -> Result<Value> {
@@ -414,10 +756,7 @@ impl LspTool {
         }
         .ok_or_else(|| Error::Tool(format!("lsp server '{}' not found", server_id)))?;
 
-        let mut handle = server.lock().await;
-        let _ = Self::send_request(&mut handle, "shutdown", json!({}), timeout_seconds).await;
-        let _ = Self::send_notification(&mut handle, "exit", json!({})).await;
-        let _ = handle.child.start_kill();
+        server.shutdown(timeout_seconds).await;
 
         Ok(json!({
             "operation": "stop_server",
@@ -426,68 +765,269 @@ impl LspTool {
         }))
     }
 
-    async fn get_handle_for_operation(
+    /// Configured server for a file (by extension) or language id.
+    fn configured_server(
+        &self,
+        file_path: Option<&Path>,
+        language_id: Option<&str>,
+    ) -> Option<&LspServerConfig> {
+        let extension = file_path
+            .and_then(|path| path.extension())
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let language = language_id
+            .map(str::to_ascii_lowercase)
+            .or_else(|| file_path.map(Self::language_id_for));
+        self.lsp_config
+            .servers
+            .iter()
+            .find(|server| {
+                extension.as_ref().is_some_and(|ext| {
+                    server
+                        .extensions
+                        .iter()
+                        .any(|candidate| candidate.eq_ignore_ascii_case(ext))
+                })
+            })
+            .or_else(|| {
+                self.lsp_config.servers.iter().find(|server| {
+                    language.as_ref().is_some_and(|language| {
+                        server
+                            .languages
+                            .iter()
+                            .any(|candidate| candidate.eq_ignore_ascii_case(language))
+                    })
+                })
+            })
+    }
+
+    /// Nearest ancestor of `file_path` (within `base`) holding one of `markers`.
+    fn detect_root(file_path: &Path, base: &Path, markers: &[String]) -> PathBuf {
+        let mut dir = file_path.parent();
+        while let Some(current) = dir {
+            if !current.starts_with(base) {
+                break;
+            }
+            if markers.iter().any(|marker| current.join(marker).exists()) {
+                return current.to_path_buf();
+            }
+            dir = current.parent();
+        }
+        base.to_path_buf()
+    }
+
+    /// Resolves the server for an operation: an explicit `server_id`, an
+    /// explicit `server_command`, or the configured server for the file/language.
+    async fn server_for_operation(
         &self,
         args: &Value,
         context: &ToolExecutionContext,
+        file_path: Option<&Path>,
         timeout_seconds: u64,
-    ) -> Result<Arc<Mutex<LspServerHandle>>> {
+    ) -> Result<Arc<LspServer>> {
         if let Some(server_id) = Self::optional_string(args, "server_id") {
             let servers = self.servers.lock().await;
-            return servers
+            let server = servers
                 .get(server_id)
                 .cloned()
-                .ok_or_else(|| Error::Tool(format!("lsp server '{}' not found", server_id)));
-        }
-
-        let command = Self::required_string(args, "server_command")?;
-        let server_args = Self::optional_string_array(args, "server_args");
-        let workspace_root = self
-            .resolve_workspace_root(context, Self::optional_string(args, "workspace_root"))
-            .await?;
-        let server_id = self
-            .start_server_internal(command, &server_args, &workspace_root, timeout_seconds)
-            .await?;
-        let servers = self.servers.lock().await;
-        servers
-            .get(&server_id)
+                .ok_or_else(|| Error::Tool(format!("lsp server '{}' not found", server_id)))?;
+            server.touch();
+            return Ok(server);
+        }
+
+        let explicit_root = Self::optional_string(args, "workspace_root");
+        if let Some(command) = Self::optional_string(args, "server_command") {
+            let server_args = Self::optional_string_array(args, "server_args");
+            let workspace_root = self.resolve_workspace_root(context, explicit_root).await?;
+            return self
+                .get_or_start_server(
+                    command,
+                    command,
+                    &server_args,
+                    None,
+                    &workspace_root,
+                    timeout_seconds,
+                )
+                .await;
+        }
+
+        let language_id = Self::optional_string(args, "language_id");
+        let configured = self
+            .configured_server(file_path, language_id)
             .cloned()
-            .ok_or_else(|| Error::Tool(format!("lsp server '{}' not found after start", server_id)))
+            .ok_or_else(|| {
+                let target = language_id
+                    .map(ToOwned::to_owned)
+                    .or_else(|| file_path.map(|path| path.display().to_string()))
+                    .unwrap_or_else(|| "this request".to_owned());
+                Error::Tool(format!(
+                    "no language server configured for {target}; pass file_path or language_id matching lsp.servers, or server_command"
+                ))
+            })?;
+        let workspace_root = match (explicit_root, file_path) {
+            (Some(raw), _) => self.resolve_workspace_root(context, Some(raw)).await?,
+            (None, Some(path)) => {
+                let base = Self::canonicalize(&context.working_directory).await?;
+                Self::detect_root(path, &base, &configured.root_markers)
+            }
+            (None, None) => self.resolve_workspace_root(context, None).await?,
+        };
+        self.get_or_start_server(
+            &configured.name,
+            &configured.command,
+            &configured.args,
+            configured.initialization_options.as_ref(),
+            &workspace_root,
+            timeout_seconds,
+        )
+        .await
     }
 
-    async fn did_open_document(
+    /// Opens the file in the server or, when its content changed on disk since
+    /// the last sync, sends the full new text followed by `didSave`.
+    async fn sync_document(
         &self,
-        handle: &mut LspServerHandle,
+        server: &LspServer,
         file_path: &Path,
         language_id: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<SyncedDocument> {
         let text = tokio::fs::read_to_string(file_path).await.map_err(|err| {
             Error::Tool(format!("failed to read '{}': {err}", file_path.display()))
         })?;
-        let uri = Self::path_to_lsp_uri(file_path, false)?;
-        let lang = language_id
-            .map(ToOwned::to_owned)
-            .or_else(|| {
-                file_path
-                    .extension()
-                    .and_then(|v| v.to_str())
-                    .map(|ext| ext.to_ascii_lowercase())
-            })
-            .unwrap_or_else(|| "text".to_owned());
+        let uri = Self::path_to_lsp_uri(file_path, false)?.as_str().to_owned();
+        let publishes_before = server.publishes();
 
-        Self::send_notification(
-            handle,
-            "textDocument/didOpen",
-            json!({
-                "textDocument": {
-                    "uri": uri,
-                    "languageId": lang,
-                    "version": 1,
-                    "text": text,
+        let mut documents = server.documents.lock().await;
+        let changed = match documents.get_mut(&uri) {
+            None => {
+                let lang = language_id
+                    .map(ToOwned::to_owned)
+                    .unwrap_or_else(|| Self::language_id_for(file_path));
+                server
+                    .notify(
+                        "textDocument/didOpen",
+                        json!({
+                            "textDocument": {
+                                "uri": uri,
+                                "languageId": lang,
+                                "version": 1,
+                                "text": text,
+                            }
+                        }),
+                    )
+                    .await?;
+                documents.insert(
+                    uri.clone(),
+                    OpenDocument {
+                        version: 1,
+                        text: text.clone(),
+                    },
+                );
+                true
+            }
+            Some(document) if document.text != text => {
+                document.version += 1;
+                document.text = text.clone();
+                server
+                    .notify(
+                        "textDocument/didChange",
+                        json!({
+                            "textDocument": { "uri": uri, "version": document.version },
+                            "contentChanges": [{ "text": text }]
+                        }),
+                    )
+                    .await?;
+                server
+                    .notify(
+                        "textDocument/didSave",
+                        json!({ "textDocument": { "uri": uri } }),
+                    )
+                    .await?;
+                true
+            }
+            Some(_) => false,
+        };
+        if changed {
+            server.symbol_cache.lock().await.clear();
+        }
+
+        Ok(SyncedDocument {
+            uri,
+            text,
+            changed,
+            publishes_before,
+        })
+    }
+
+    /// Diagnostics for a synced document: pulled when the server supports
+    /// `textDocument/diagnostic`, otherwise the next `publishDiagnostics` after
+    /// the sync (allowing a short settle window for follow-up publishes).
+    async fn document_diagnostics(
+        &self,
+        server: &LspServer,
+        document: &SyncedDocument,
+        wait: Duration,
+        timeout_seconds: u64,
+    ) -> Result<(Vec<Value>, bool)> {
+        if server.capability("diagnosticProvider").is_some() {
+            let pulled = server
+                .request(
+                    "textDocument/diagnostic",
+                    json!({ "textDocument": { "uri": document.uri } }),
+                    timeout_seconds,
+                )
+                .await;
+            if let Ok(report) = pulled {
+                if let Some(items) = report.get("items").and_then(Value::as_array) {
+                    return Ok((items.clone(), true));
                 }
-            }),
-        )
-        .await
+            }
+        }
+
+        if !document.changed {
+            if let Some((_, items)) = server.published_diagnostics(&document.uri) {
+                return Ok((items, true));
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + wait;
+        let mut latest: Option<(u64, Vec<Value>)> = None;
+        loop {
+            let notified = server.published.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some((publish, items)) = server.published_diagnostics(&document.uri) {
+                if publish > document.publishes_before
+                    && latest.as_ref().is_none_or(|(seen, _)| publish > *seen)
+                {
+                    latest = Some((publish, items));
+                }
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline || !server.is_alive() {
+                break;
+            }
+            let window = if latest.is_some() {
+                Duration::from_millis(DIAGNOSTICS_SETTLE_MS).min(deadline - now)
+            } else {
+                deadline - now
+            };
+            if timeout(window, notified).await.is_err() {
+                break;
+            }
+        }
+
+        match latest {
+            Some((_, items)) => Ok((items, true)),
+            None => Ok((
+                server
+                    .published_diagnostics(&document.uri)
+                    .map(|(_, items)| items)
+                    .unwrap_or_default(),
+                false,
+            )),
+        }
     }
 
     fn location_to_json(location: &lsp_types::Location) -> Value {
@@ -540,6 +1080,220 @@ impl LspTool {
         Ok(items)
     }
 
+    fn uri_to_path(uri: &str) -> Option<String> {
+        Url::parse(uri)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .map(|path| path.display().to_string())
+    }
+
+    fn severity_name(severity: Option<u64>) -> &'static str {
+        match severity {
+            Some(1) => "error",
+            Some(2) => "warning",
+            Some(3) => "information",
+            Some(4) => "hint",
+            _ => "unknown",
+        }
+    }
+
+    fn diagnostic_to_json(uri: &str, diagnostic: &Value) -> Value {
+        json!({
+            "path": Self::uri_to_path(uri),
+            "range": diagnostic.get("range"),
+            "severity": Self::severity_name(diagnostic.get("severity").and_then(Value::as_u64)),
+            "code": diagnostic.get("code"),
+            "source": diagnostic.get("source"),
+            "message": diagnostic.get("message"),
+        })
+    }
+
+    fn severity_counts(diagnostics: &[Value]) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for diagnostic in diagnostics {
+            let severity = diagnostic
+                .get("severity")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            *counts.entry(severity.to_owned()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn text_edits_to_json(edits: &Value) -> Vec<Value> {
+        edits
+            .as_array()
+            .map(|edits| {
+                edits
+                    .iter()
+                    .map(|edit| {
+                        json!({
+                            "range": edit.get("range"),
+                            "new_text": edit.get("newText"),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Flattens a `WorkspaceEdit` (`changes` or `documentChanges`) into
+    /// per-file text edits plus resource operations.
+    fn workspace_edit_to_json(edit: &Value) -> Value {
+        let mut files = Vec::new();
+        let mut operations = Vec::new();
+        if let Some(changes) = edit.get("changes").and_then(Value::as_object) {
+            for (uri, edits) in changes {
+                files.push(json!({
+                    "path": Self::uri_to_path(uri),
+                    "uri": uri,
+                    "edits": Self::text_edits_to_json(edits),
+                }));
+            }
+        }
+        if let Some(document_changes) = edit.get("documentChanges").and_then(Value::as_array) {
+            for change in document_changes {
+                if let Some(kind) = change.get("kind").and_then(Value::as_str) {
+                    operations.push(json!({
+                        "kind": kind,
+                        "path": change.get("uri").and_then(Value::as_str).and_then(Self::uri_to_path),
+                        "old_path": change.get("oldUri").and_then(Value::as_str).and_then(Self::uri_to_path),
+                        "new_path": change.get("newUri").and_then(Value::as_str).and_then(Self::uri_to_path),
+                    }));
+                    continue;
+                }
+                let uri = change
+                    .pointer("/textDocument/uri")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                files.push(json!({
+                    "path": Self::uri_to_path(uri),
+                    "uri": uri,
+                    "edits": Self::text_edits_to_json(change.get("edits").unwrap_or(&Value::Null)),
+                }));
+            }
+        }
+        let edit_count = files
+            .iter()
+            .map(|file| file["edits"].as_array().map_or(0, Vec::len))
+            .sum::<usize>();
+        json!({
+            "files": files,
+            "operations": operations,
+            "edit_count": edit_count,
+        })
+    }
+
+    /// Byte offset of an LSP position (UTF-16 code units) in `text`.
+    fn position_to_offset(text: &str, position: &Value) -> usize {
+        let line = position.get("line").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let character = position
+            .get("character")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize;
+        let mut offset = 0usize;
+        for (index, segment) in text.split_inclusive('\n').enumerate() {
+            if index == line {
+                let content = segment.trim_end_matches(['\n', '\r']);
+                let mut units = 0usize;
+                for (byte, ch) in content.char_indices() {
+                    if units >= character {
+                        return offset + byte;
+                    }
+                    units += ch.len_utf16();
+                }
+                return offset + content.len();
+            }
+            offset += segment.len();
+        }
+        text.len()
+    }
+
+    /// Applies LSP text edits, last range first. Edits at the same position
+    /// keep their order; overlapping ranges are rejected as the spec requires.
+    fn apply_text_edits(text: &str, edits: &[Value]) -> Result<String> {
+        let mut resolved = edits
+            .iter()
+            .enumerate()
+            .filter_map(|(index, edit)| {
+                let range = edit.get("range")?;
+                let start = Self::position_to_offset(text, range.get("start")?);
+                let end = Self::position_to_offset(text, range.get("end")?);
+                let new_text = edit.get("newText").and_then(Value::as_str).unwrap_or("");
+                Some((start, end.max(start), index, new_text))
+            })
+            .collect::<Vec<_>>();
+        resolved.sort_by_key(|(start, end, index, _)| std::cmp::Reverse((*start, *end, *index)));
+        if resolved.windows(2).any(|pair| pair[1].1 > pair[0].0) {
+            return Err(Error::Tool(
+                "language server returned overlapping text edits".to_owned(),
+            ));
+        }
+        let mut result = text.to_owned();
+        for (start, end, _, new_text) in resolved {
+            result.replace_range(start..end, new_text);
+        }
+        Ok(result)
+    }
+
+    fn document_symbol_to_json(symbol: &Value) -> Value {
+        let kind = symbol
+            .get("kind")
+            .and_then(Value::as_u64)
+            .and_then(|kind| serde_json::from_value::<SymbolKind>(json!(kind)).ok())
+            .map(Self::symbol_kind_name)
+            .unwrap_or("unknown");
+        let children = symbol
+            .get("children")
+            .and_then(Value::as_array)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(Self::document_symbol_to_json)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        json!({
+            "name": symbol.get("name"),
+            "kind": kind,
+            "detail": symbol.get("detail"),
+            "range": symbol.get("range").or_else(|| symbol.pointer("/location/range")),
+            "selection_range": symbol.get("selectionRange"),
+            "container_name": symbol.get("containerName"),
+            "children": children,
+        })
+    }
+
+    fn ranges_overlap(range: &Value, line: u32, end_line: u32) -> bool {
+        let start = range
+            .pointer("/start/line")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        let end = range
+            .pointer("/end/line")
+            .and_then(Value::as_u64)
+            .unwrap_or(start);
+        start <= u64::from(end_line) && end >= u64::from(line)
+    }
+
+    fn server_summary(server: &LspServer) -> Value {
+        json!({
+            "server_id": server.server_id,
+            "name": server.name,
+            "server_command": server.command,
+            "workspace_root": server.workspace_root,
+        })
+    }
+
+    fn with_server(server: &LspServer, operation: LspOperation, payload: Value) -> Value {
+        let mut merged = Self::server_summary(server);
+        if let (Some(target), Value::Object(fields)) = (merged.as_object_mut(), payload) {
+            target.insert("operation".to_owned(), json!(operation.as_str()));
+            target.extend(fields);
+        }
+        merged
+    }
+
     async fn execute_operation(
         &self,
         args: Value,
@@ -551,27 +1305,36 @@ impl LspTool {
 
         let payload = match operation {
             LspOperation::StartServer => {
-                let command = Self::required_string(&args, "server_command")?;
-                let server_args = Self::optional_string_array(&args, "server_args");
-                let workspace_root = self
-                    .resolve_workspace_root(context, Self::optional_string(&args, "workspace_root"))
-                    .await?;
-                let server_id = self
-                    .start_server_internal(command, &server_args, &workspace_root, timeout_seconds)
+                let file_path = match Self::optional_string(&args, "file_path") {
+                    Some(raw) => Some(self.resolve_file_path(context, raw).await?),
+                    None => None,
+                };
+                let server = self
+                    .server_for_operation(&args, context, file_path.as_deref(), timeout_seconds)
                     .await?;
-                json!({
-                    "operation": operation.as_str(),
-                    "server_id": server_id,
-                    "command": command,
-                    "workspace_root": workspace_root,
-                    "started": true,
-                })
+                Self::with_server(&server, operation, json!({ "started": true }))
             }
             LspOperation::StopServer => {
                 let server_id = Self::required_string(&args, "server_id")?;
                 self.stop_server_internal(server_id, timeout_seconds)
                     .await?
             }
+            LspOperation::ListServers => {
+                let servers = self.servers.lock().await;
+                let mut listed = Vec::new();
+                for server in servers.values() {
+                    let mut summary = Self::server_summary(server);
+                    summary["alive"] = json!(server.is_alive());
+                    summary["idle_secs"] = json!(server.idle_secs());
+                    summary["open_documents"] = json!(server.documents.lock().await.len());
+                    listed.push(summary);
+                }
+                json!({
+                    "operation": operation.as_str(),
+                    "count": listed.len(),
+                    "servers": listed,
+                })
+            }
             LspOperation::SymbolSearch => {
                 let query = Self::required_string(&args, "query")?;
                 let max_results = args
@@ -579,89 +1342,137 @@ impl LspTool {
                     .and_then(Value::as_u64)
                     .unwrap_or(100)
                     .clamp(1, 500) as usize;
+                let file_path = match Self::optional_string(&args, "file_path") {
+                    Some(raw) => Some(self.resolve_file_path(context, raw).await?),
+                    None => None,
+                };
 
                 let server = self
-                    .get_handle_for_operation(&args, context, timeout_seconds)
+                    .server_for_operation(&args, context, file_path.as_deref(), timeout_seconds)
                     .await?;
-                let mut handle = server.lock().await;
 
-                if let Some(cached) = handle.symbol_cache.get(query) {
-                    let mut items = cached.clone();
+                let cached = server.symbol_cache.lock().await.get(query).cloned();
+                if let Some(cached) = cached {
+                    let mut items = cached;
                     items.truncate(max_results);
-                    json!({
-                        "operation": operation.as_str(),
-                        "server_id": handle.server_id,
-                        "server_command": handle.command,
-                        "workspace_root": handle.workspace_root,
-                        "cached": true,
-                        "count": items.len(),
-                        "symbols": items,
-                    })
-                } else {
-                    let result = Self::send_request(
-                        &mut handle,
-                        "workspace/symbol",
-                        json!({ "query": query }),
-                        timeout_seconds,
+                    Self::with_server(
+                        &server,
+                        operation,
+                        json!({
+                            "cached": true,
+                            "count": items.len(),
+                            "symbols": items,
+                        }),
                     )
-                    .await?;
+                } else {
+                    let result = server
+                        .request(
+                            "workspace/symbol",
+                            json!({ "query": query }),
+                            timeout_seconds,
+                        )
+                        .await?;
                     let mut symbols = Self::parse_workspace_symbols(result)?;
                     symbols.truncate(max_results);
-                    handle
+                    server
                         .symbol_cache
+                        .lock()
+                        .await
                         .insert(query.to_owned(), symbols.clone());
-                    json!({
-                        "operation": operation.as_str(),
-                        "server_id": handle.server_id,
-                        "server_command": handle.command,
-                        "workspace_root": handle.workspace_root,
-                        "cached": false,
-                        "count": symbols.len(),
-                        "symbols": symbols,
-                    })
+                    Self::with_server(
+                        &server,
+                        operation,
+                        json!({
+                            "cached": false,
+                            "count": symbols.len(),
+                            "symbols": symbols,
+                        }),
+                    )
+                }
+            }
+            LspOperation::Diagnostics if Self::optional_string(&args, "file_path").is_none() => {
+                // Without a file, report everything already published; with no
+                // server hint that covers every pooled server.
+                let targeted = ["server_id", "server_command", "language_id"]
+                    .iter()
+                    .any(|key| Self::optional_string(&args, key).is_some());
+                let servers = if targeted {
+                    vec![
+                        self.server_for_operation(&args, context, None, timeout_seconds)
+                            .await?,
+                    ]
+                } else {
+                    self.servers.lock().await.values().cloned().collect()
+                };
+                let mut diagnostics = Vec::new();
+                for server in &servers {
+                    let published = server
+                        .inbox
+                        .lock()
+                        .map(|inbox| inbox.diagnostics.clone())
+                        .unwrap_or_default();
+                    for (uri, (_, items)) in &published {
+                        diagnostics
+                            .extend(items.iter().map(|item| Self::diagnostic_to_json(uri, item)));
+                    }
                 }
+                json!({
+                    "operation": operation.as_str(),
+                    "server_ids": servers.iter().map(|server| server.server_id.clone()).collect::<Vec<_>>(),
+                    "fresh": false,
+                    "count": diagnostics.len(),
+                    "counts": Self::severity_counts(&diagnostics),
+                    "diagnostics": diagnostics,
+                })
             }
-            LspOperation::Definition | LspOperation::References | LspOperation::Hover => {
+            LspOperation::Definition
+            | LspOperation::References
+            | LspOperation::Hover
+            | LspOperation::Diagnostics
+            | LspOperation::Rename
+            | LspOperation::CodeActions
+            | LspOperation::Format
+            | LspOperation::DocumentSymbols => {
                 let file_path = self
                     .resolve_file_path(context, Self::required_string(&args, "file_path")?)
                     .await?;
-                let line = args.get("line").and_then(Value::as_u64).unwrap_or(0) as u32;
-                let character = args.get("character").and_then(Value::as_u64).unwrap_or(0) as u32;
+                let line = Self::optional_u32(&args, "line").unwrap_or(0);
+                let character = Self::optional_u32(&args, "character").unwrap_or(0);
                 let language_id = Self::optional_string(&args, "language_id");
 
                 let server = self
-                    .get_handle_for_operation(&args, context, timeout_seconds)
-                    .await?;
-                let mut handle = server.lock().await;
-                self.did_open_document(&mut handle, &file_path, language_id)
+                    .server_for_operation(&args, context, Some(&file_path), timeout_seconds)
                     .await?;
+                let document = self.sync_document(&server, &file_path, language_id).await?;
 
                 let uri = Self::path_to_lsp_uri(&file_path, false)?;
+                let position = json!({ "line": line, "character": character });
 
                 match operation {
                     LspOperation::Definition => {
-                        let result = Self::send_request(
-                            &mut handle,
-                            "textDocument/definition",
-                            json!({
-                                "textDocument": { "uri": uri },
-                                "position": { "line": line, "character": character }
-                            }),
-                            timeout_seconds,
-                        )
-                        .await?;
-                        let parsed: GotoDefinitionResponse = serde_json::from_value(result)
+                        let result = server
+                            .request(
+                                "textDocument/definition",
+                                json!({
+                                    "textDocument": { "uri": uri },
+                                    "position": position
+                                }),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        let parsed: Option<GotoDefinitionResponse> = serde_json::from_value(result)
                             .map_err(|err| {
                                 Error::Tool(format!("invalid definition response: {err}"))
                             })?;
                         let locations = match parsed {
-                            GotoDefinitionResponse::Scalar(loc) => {
+                            None => Vec::new(),
+                            Some(GotoDefinitionResponse::Scalar(loc)) => {
                                 vec![Self::location_to_json(&loc)]
                             }
-                            GotoDefinitionResponse::Array(locs) => {
+                            Some(GotoDefinitionResponse::Array(locs)) => {
                                 locs.iter().map(Self::location_to_json).collect::<Vec<_>>()
                             }
-                            GotoDefinitionResponse::Link(links) => links
+                            Some(GotoDefinitionResponse::Link(links)) => links
                                 .iter()
                                 .map(|link| {
                                     json!({
@@ -673,14 +1484,14 @@ impl LspTool {
                                 })
                                 .collect::<Vec<_>>(),
                         };
-                        json!({
-                            "operation": operation.as_str(),
-                            "server_id": handle.server_id,
-                            "server_command": handle.command,
-                            "workspace_root": handle.workspace_root,
-                            "count": locations.len(),
-                            "definitions": locations,
-                        })
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "count": locations.len(),
+                                "definitions": locations,
+                            }),
+                        )
                     }
                     LspOperation::References => {
                         let include_declaration = args
@@ -700,15 +1511,17 @@ impl LspTool {
                             work_done_progress_params: Default::default(),
                             partial_result_params: Default::default(),
                         };
-                        let result = Self::send_request(
-                            &mut handle,
-                            "textDocument/references",
-                            serde_json::to_value(&params).map_err(|err| {
-                                Error::Tool(format!("failed to encode references params: {err}"))
-                            })?,
-                            timeout_seconds,
-                        )
-                        .await?;
+                        let result = server
+                            .request(
+                                "textDocument/references",
+                                serde_json::to_value(&params).map_err(|err| {
+                                    Error::Tool(format!(
+                                        "failed to encode references params: {err}"
+                                    ))
+                                })?,
+                                timeout_seconds,
+                            )
+                            .await?;
                         let parsed: Option<Vec<lsp_types::Location>> =
                             serde_json::from_value(result).map_err(|err| {
                                 Error::Tool(format!("invalid references response: {err}"))
@@ -718,35 +1531,239 @@ impl LspTool {
                             .iter()
                             .map(Self::location_to_json)
                             .collect::<Vec<_>>();
-                        json!({
-                            "operation": operation.as_str(),
-                            "server_id": handle.server_id,
-                            "server_command": handle.command,
-                            "workspace_root": handle.workspace_root,
-                            "count": refs.len(),
-                            "references": refs,
-                        })
-                    }
-                    LspOperation::Hover => {
-                        let result = Self::send_request(
-                            &mut handle,
-                            "textDocument/hover",
+                        Self::with_server(
+                            &server,
+                            operation,
                             json!({
-                                "textDocument": { "uri": uri },
-                                "position": { "line": line, "character": character }
+                                "count": refs.len(),
+                                "references": refs,
                             }),
-                            timeout_seconds,
                         )
-                        .await?;
+                    }
+                    LspOperation::Hover => {
+                        let result = server
+                            .request(
+                                "textDocument/hover",
+                                json!({
+                                    "textDocument": { "uri": uri },
+                                    "position": position
+                                }),
+                                timeout_seconds,
+                            )
+                            .await?;
                         let parsed: Option<Hover> = serde_json::from_value(result)
                             .map_err(|err| Error::Tool(format!("invalid hover response: {err}")))?;
-                        json!({
-                            "operation": operation.as_str(),
-                            "server_id": handle.server_id,
-                            "server_command": handle.command,
-                            "workspace_root": handle.workspace_root,
-                            "hover": parsed,
-                        })
+                        Self::with_server(&server, operation, json!({ "hover": parsed }))
+                    }
+                    LspOperation::Diagnostics => {
+                        let wait_ms = args
+                            .get("wait_ms")
+                            .and_then(Value::as_u64)
+                            .unwrap_or(self.lsp_config.diagnostics_wait_ms)
+                            .min(timeout_seconds.saturating_mul(1000));
+                        let (items, fresh) = self
+                            .document_diagnostics(
+                                &server,
+                                &document,
+                                Duration::from_millis(wait_ms),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        let diagnostics = items
+                            .iter()
+                            .map(|item| Self::diagnostic_to_json(&document.uri, item))
+                            .collect::<Vec<_>>();
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "file_path": file_path,
+                                "fresh": fresh,
+                                "count": diagnostics.len(),
+                                "counts": Self::severity_counts(&diagnostics),
+                                "diagnostics": diagnostics,
+                            }),
+                        )
+                    }
+                    LspOperation::Rename => {
+                        let new_name = Self::required_string(&args, "new_name")?;
+                        let result = server
+                            .request(
+                                "textDocument/rename",
+                                json!({
+                                    "textDocument": { "uri": uri },
+                                    "position": position,
+                                    "newName": new_name
+                                }),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "new_name": new_name,
+                                "workspace_edit": Self::workspace_edit_to_json(&result),
+                            }),
+                        )
+                    }
+                    LspOperation::CodeActions => {
+                        let end_line = Self::optional_u32(&args, "end_line").unwrap_or(line);
+                        let end_character =
+                            Self::optional_u32(&args, "end_character").unwrap_or(character);
+                        let context_diagnostics = server
+                            .published_diagnostics(&document.uri)
+                            .map(|(_, items)| items)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|item| {
+                                item.get("range").is_some_and(|range| {
+                                    Self::ranges_overlap(range, line, end_line)
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        let only = Self::optional_string_array(&args, "only");
+                        let mut action_context = json!({ "diagnostics": context_diagnostics });
+                        if !only.is_empty() {
+                            action_context["only"] = json!(only);
+                        }
+                        let result = server
+                            .request(
+                                "textDocument/codeAction",
+                                json!({
+                                    "textDocument": { "uri": uri },
+                                    "range": {
+                                        "start": position,
+                                        "end": { "line": end_line, "character": end_character }
+                                    },
+                                    "context": action_context
+                                }),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        let can_resolve = server
+                            .capability("codeActionProvider")
+                            .and_then(|provider| provider.get("resolveProvider").cloned())
+                            .and_then(|resolve| resolve.as_bool())
+                            .unwrap_or(false);
+
+                        let mut actions = Vec::new();
+                        for (index, action) in result
+                            .as_array()
+                            .cloned()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .enumerate()
+                        {
+                            // Bare `Command` results carry the command name in `command`.
+                            if action.get("command").and_then(Value::as_str).is_some() {
+                                actions.push(json!({
+                                    "title": action.get("title"),
+                                    "kind": Value::Null,
+                                    "command": action,
+                                    "edit": Value::Null,
+                                }));
+                                continue;
+                            }
+                            let mut action = action;
+                            if can_resolve
+                                && action.get("edit").is_none()
+                                && index < MAX_RESOLVED_CODE_ACTIONS
+                            {
+                                if let Ok(resolved) = server
+                                    .request("codeAction/resolve", action.clone(), timeout_seconds)
+                                    .await
+                                {
+                                    action = resolved;
+                                }
+                            }
+                            actions.push(json!({
+                                "title": action.get("title"),
+                                "kind": action.get("kind"),
+                                "is_preferred": action.get("isPreferred"),
+                                "diagnostics": action
+                                    .get("diagnostics")
+                                    .and_then(Value::as_array)
+                                    .map(|items| items
+                                        .iter()
+                                        .filter_map(|item| item.get("message").cloned())
+                                        .collect::<Vec<_>>()),
+                                "edit": action.get("edit").map(Self::workspace_edit_to_json),
+                                "command": action.get("command"),
+                            }));
+                        }
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "count": actions.len(),
+                                "actions": actions,
+                            }),
+                        )
+                    }
+                    LspOperation::Format => {
+                        let tab_size = args
+                            .get("tab_size")
+                            .and_then(Value::as_u64)
+                            .unwrap_or(4)
+                            .clamp(1, 16);
+                        let insert_spaces = args
+                            .get("insert_spaces")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(true);
+                        let result = server
+                            .request(
+                                "textDocument/formatting",
+                                json!({
+                                    "textDocument": { "uri": uri },
+                                    "options": {
+                                        "tabSize": tab_size,
+                                        "insertSpaces": insert_spaces
+                                    }
+                                }),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        let edits = result.as_array().cloned().unwrap_or_default();
+                        let formatted = Self::apply_text_edits(&document.text, &edits)?;
+                        let changed = formatted != document.text;
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "file_path": file_path,
+                                "changed": changed,
+                                "edits": Self::text_edits_to_json(&result),
+                                "formatted_text": if changed { Some(formatted) } else { None },
+                            }),
+                        )
+                    }
+                    LspOperation::DocumentSymbols => {
+                        let result = server
+                            .request(
+                                "textDocument/documentSymbol",
+                                json!({ "textDocument": { "uri": uri } }),
+                                timeout_seconds,
+                            )
+                            .await?;
+                        let symbols = result
+                            .as_array()
+                            .map(|items| {
+                                items
+                                    .iter()
+                                    .map(Self::document_symbol_to_json)
+                                    .collect::<Vec<_>>()
+                            })
+                            .unwrap_or_default();
+                        Self::with_server(
+                            &server,
+                            operation,
+                            json!({
+                                "file_path": file_path,
+                                "count": symbols.len(),
+                                "symbols": symbols,
+                            }),
+                        )
                     }
                     _ => unreachable!(),
                 }
@@ -792,7 +1809,7 @@ impl Tool for LspTool {
     }
 
     fn description(&self) -> &str {
-        "LSP navigation and symbol search over running language servers"
+        "LSP navigation, diagnostics, rename, code actions and formatting over pooled language servers"
     }
 
     fn schema(&self) -> &Value {
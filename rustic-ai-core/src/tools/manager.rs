@@ -1,21 +1,49 @@
 use crate::config::schema::{
-    AgentPermissionMode, DynamicRoutingConfig, McpConfig, PermissionConfig, PluginConfig,
-    SubAgentCacheMode, ToolConfig, WorkflowsConfig,
+    AgentPermissionMode, DynamicRoutingConfig, LspConfig, McpConfig, PermissionConfig,
+    PluginConfig, SubAgentCacheMode, ToolConfig, WatcherConfig, WorkflowsConfig,
 };
 use crate::error::{Error, Result};
-use crate::events::Event;
+use crate::events::{Event, EventBus};
+use crate::learning::LearningManager;
+use crate::mcp::{McpClient, McpConnectionManager, SamplingModel, SamplingModelResolver};
 use crate::permissions::{
     AskResolution, CommandPatternBucket, PermissionContext, PermissionDecision, PermissionPolicy,
 };
+use crate::sandbox::Sandbox;
 use crate::skills::SkillRegistry;
-use crate::tools::plugin::PluginLoader;
+use crate::tools::journal::{EditJournal, JournalEntrySummary};
+use crate::tools::mcp::{
+    connect_for, list_remote_tools, namespaced_tool_name, McpRemoteTool, MCP_TOOL_PREFIX,
+};
+use crate::tools::plugin::{PluginLoader, RpcPlugin, RpcPluginTool};
+use crate::tools::supervisor::ProcessSupervisor;
 use crate::tools::{
-    bracket_validator::BracketValidatorTool, code_search::CodeSearchTool, convert::ConvertTool,
-    crawler::CrawlerTool, database::DatabaseTool, docker::DockerTool, download::DownloadTool,
-    encoding::EncodingTool, filesystem::FilesystemTool, format::FormatTool, git::GitTool,
-    grep::GrepTool, http::HttpTool, image::ImageTool, lsp::LspTool, mcp::McpToolAdapter,
-    process::ProcessTool, regex::RegexTool, shell::ShellTool, skill::SkillTool, ssh::SshTool,
-    sub_agent::SubAgentTool, watch::WatchTool, web_fetch::WebFetchTool, web_search::WebSearchTool,
+    bracket_validator::BracketValidatorTool,
+    code_search::CodeSearchTool,
+    convert::ConvertTool,
+    crawler::CrawlerTool,
+    create_skill::CreateSkillTool,
+    database::DatabaseTool,
+    docker::DockerTool,
+    download::DownloadTool,
+    encoding::EncodingTool,
+    filesystem::FilesystemTool,
+    format::FormatTool,
+    git::GitTool,
+    grep::GrepTool,
+    http::HttpTool,
+    image::ImageTool,
+    lsp::LspTool,
+    mcp::McpToolAdapter,
+    process::ProcessTool,
+    regex::RegexTool,
+    shell::{ShellSessions, ShellTool},
+    skill::SkillTool,
+    ssh::SshTool,
+    sub_agent::SubAgentTool,
+    watch::WatchTool,
+    web_fetch::WebFetchTool,
+    web_search::WebSearchTool,
     Tool, ToolExecutionContext,
 };
 use crate::workflows::{
@@ -24,6 +52,7 @@ use crate::workflows::{
 use crate::{agents::coordinator::AgentCoordinator, conversation::session_manager::SessionManager};
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock};
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
@@ -34,6 +63,23 @@ struct LazyToolSpec {
     priority: usize,
 }
 
+/// Answers MCP sampling requests with the model of the requesting agent.
+struct AgentModelResolver {
+    agents: Arc<StdRwLock<Option<Arc<AgentCoordinator>>>>,
+}
+
+impl SamplingModelResolver for AgentModelResolver {
+    fn resolve(&self, agent: Option<&str>) -> Option<SamplingModel> {
+        let guard = self.agents.read().ok()?;
+        let agent = guard.as_ref()?.get_agent(agent).ok()?;
+        Some(SamplingModel {
+            provider: agent.provider().clone(),
+            temperature: agent.config().temperature,
+            max_tokens: agent.config().max_tokens,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct ToolManager {
     tools: Arc<RwLock<HashMap<String, Arc<dyn Tool>>>>,
@@ -48,16 +94,30 @@ pub struct ToolManager {
     todo_tracking_enabled: bool,
     project_id: Option<String>,
     skills: Arc<SkillRegistry>,
+    learning: Arc<LearningManager>,
     agents: Arc<StdRwLock<Option<Arc<AgentCoordinator>>>>,
     session_manager: Arc<SessionManager>,
     permission_config: Arc<PermissionConfig>,
     mcp_enabled: bool,
-    mcp_config: Arc<McpConfig>,
+    mcp_connections: Arc<McpConnectionManager>,
+    lsp_config: Arc<LspConfig>,
+    watcher_config: Arc<WatcherConfig>,
     skills_enabled: bool,
     sub_agent_parallel_enabled: bool,
     sub_agent_output_caching_enabled: bool,
     lazy_loaders: Arc<RwLock<HashMap<String, LazyToolSpec>>>,
     active_tools: Arc<RwLock<HashSet<String>>>,
+    sandbox: Arc<Sandbox>,
+    edit_journal: Arc<EditJournal>,
+    process_supervisor: Arc<ProcessSupervisor>,
+    shell_sessions: Arc<ShellSessions>,
+    mcp_watch_started: Arc<AtomicBool>,
+    /// Processes of `mode: rpc` plugins, managed like MCP servers.
+    plugin_connections: Arc<McpConnectionManager>,
+    rpc_plugins: Arc<Vec<RpcPlugin>>,
+    /// Tools registered for each rpc plugin.
+    plugin_tools: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    plugin_watch_started: Arc<AtomicBool>,
 }
 
 pub struct ToolManagerInit {
@@ -65,10 +125,13 @@ pub struct ToolManagerInit {
     pub permission_config: Arc<PermissionConfig>,
     pub mcp_enabled: bool,
     pub mcp_config: Arc<McpConfig>,
+    pub lsp_config: Arc<LspConfig>,
+    pub watcher_config: Arc<WatcherConfig>,
     pub skills_enabled: bool,
     pub sub_agent_parallel_enabled: bool,
     pub sub_agent_output_caching_enabled: bool,
     pub skills: Arc<SkillRegistry>,
+    pub learning: Arc<LearningManager>,
     pub workflows_enabled: bool,
     pub workflows: Arc<WorkflowRegistry>,
     pub workflows_config: Arc<WorkflowsConfig>,
@@ -81,11 +144,16 @@ pub struct ToolManagerInit {
     pub plugin_config: Arc<PluginConfig>,
     pub tool_configs: Vec<ToolConfig>,
     pub execution_context: ToolExecutionContext,
+    pub sandbox: Arc<Sandbox>,
+    pub event_bus: EventBus,
 }
 
 impl ToolManager {
     fn is_lazy_tool(tool_name: &str) -> bool {
-        matches!(tool_name, "mcp" | "ssh" | "skill" | "sub_agent")
+        matches!(
+            tool_name,
+            "mcp" | "ssh" | "skill" | "create_skill" | "sub_agent"
+        )
     }
 
     fn tool_priority(tool_name: &str) -> usize {
@@ -114,8 +182,10 @@ impl ToolManager {
             "workflow" => 85,
             "sub_agent" => 80,
             "skill" => 70,
+            "create_skill" => 68,
             "ssh" => 65,
             "mcp" => 60,
+            name if name.starts_with(MCP_TOOL_PREFIX) => 60,
             _ => 40,
         }
     }
@@ -132,8 +202,10 @@ impl ToolManager {
             "download" => "Download files with resume and integrity checks",
             "web_fetch" => "Fetch a single URL and return normalized content",
             "crawler" => "Extract bounded links from a page with host filtering",
-            "watch" => "Capture filesystem snapshots and report changes",
-            "process" => "Start, list, inspect, and stop background processes",
+            "watch" => "Capture filesystem snapshots, report changes or wait for the next change",
+            "process" => {
+                "Start, list, inspect, tail logs of, wait on, and stop managed background processes"
+            }
             "docker" => "Run bounded Docker operations with permission checks",
             "regex" => "Regex matching and replacement with captures",
             "format" => "Format and minify JSON/XML payloads",
@@ -142,11 +214,12 @@ impl ToolManager {
             "bracket_validator" => {
                 "Validate bracket nesting with language-aware comment/string handling"
             }
-            "lsp" => "LSP symbol and navigation queries",
+            "lsp" => "LSP navigation, diagnostics, rename, code actions and formatting",
             "image" => "Image resize/crop/rotate/convert/metadata",
             "git" => "Inspect and modify git repositories",
-            "ssh" => "Execute and transfer files over SSH",
-            "skill" => "Invoke loaded instruction/script skills",
+            "ssh" => "Execute, forward ports and sync files over SSH",
+            "skill" => "Invoke loaded instruction, script and SKILL.md skills",
+            "create_skill" => "Save a new instruction or script skill after user approval",
             "workflow" => "Run workflow entrypoints",
             "sub_agent" => "Delegate task to another configured agent",
             "mcp" => "Invoke MCP server tools",
@@ -160,19 +233,31 @@ impl ToolManager {
         permission_config: &PermissionConfig,
         skills_enabled: bool,
         skills: &Arc<SkillRegistry>,
+        learning: &Arc<LearningManager>,
         mcp_enabled: bool,
-        mcp_config: &Arc<McpConfig>,
+        mcp_connections: &Arc<McpConnectionManager>,
+        lsp_config: &Arc<LspConfig>,
+        watcher_config: &Arc<WatcherConfig>,
         agents: &Arc<StdRwLock<Option<Arc<AgentCoordinator>>>>,
         session_manager: &Arc<SessionManager>,
         sub_agent_output_caching_enabled: bool,
         sub_agent_parallel_enabled: bool,
+        sandbox: &Sandbox,
+        edit_journal: &Arc<EditJournal>,
+        process_supervisor: &Arc<ProcessSupervisor>,
+        shell_sessions: &Arc<ShellSessions>,
     ) -> Option<Arc<dyn Tool>> {
         match config.name.as_str() {
             "shell" => Some(Arc::new(ShellTool::new(
                 config.clone(),
                 permission_config.sudo_cache_ttl_secs,
+                sandbox.clone(),
+                shell_sessions.clone(),
+            ))),
+            "filesystem" => Some(Arc::new(FilesystemTool::new(
+                config.clone(),
+                edit_journal.clone(),
             ))),
-            "filesystem" => Some(Arc::new(FilesystemTool::new(config.clone()))),
             "grep" => Some(Arc::new(GrepTool::new(config.clone()))),
             "git" => Some(Arc::new(GitTool::new(config.clone()))),
             "database" => Some(Arc::new(DatabaseTool::new(config.clone()))),
@@ -180,16 +265,22 @@ impl ToolManager {
             "code_search" => Some(Arc::new(CodeSearchTool::new(config.clone()))),
             "web_fetch" => Some(Arc::new(WebFetchTool::new(config.clone()))),
             "crawler" => Some(Arc::new(CrawlerTool::new(config.clone()))),
-            "watch" => Some(Arc::new(WatchTool::new(config.clone()))),
+            "watch" => Some(Arc::new(WatchTool::new(
+                config.clone(),
+                watcher_config.clone(),
+            ))),
             "download" => Some(Arc::new(DownloadTool::new(config.clone()))),
-            "process" => Some(Arc::new(ProcessTool::new(config.clone()))),
+            "process" => Some(Arc::new(ProcessTool::new(
+                config.clone(),
+                process_supervisor.clone(),
+            ))),
             "docker" => Some(Arc::new(DockerTool::new(config.clone()))),
             "regex" => Some(Arc::new(RegexTool::new(config.clone()))),
             "format" => Some(Arc::new(FormatTool::new(config.clone()))),
             "encoding" => Some(Arc::new(EncodingTool::new(config.clone()))),
             "convert" => Some(Arc::new(ConvertTool::new(config.clone()))),
             "bracket_validator" => Some(Arc::new(BracketValidatorTool::new(config.clone()))),
-            "lsp" => Some(Arc::new(LspTool::new(config.clone()))),
+            "lsp" => Some(Arc::new(LspTool::new(config.clone(), lsp_config.clone()))),
             "image" => Some(Arc::new(ImageTool::new(config.clone()))),
             "http" => Some(Arc::new(HttpTool::new(config.clone()))),
             "ssh" => Some(Arc::new(SshTool::new(config.clone()))),
@@ -199,13 +290,23 @@ impl ToolManager {
                 }
                 Some(Arc::new(SkillTool::new(config.clone(), skills.clone())))
             }
+            "create_skill" => {
+                if !skills_enabled {
+                    return None;
+                }
+                Some(Arc::new(CreateSkillTool::new(
+                    config.clone(),
+                    skills.clone(),
+                    learning.clone(),
+                )))
+            }
             "mcp" => {
                 if !mcp_enabled {
                     return None;
                 }
                 Some(Arc::new(McpToolAdapter::new(
                     config.clone(),
-                    mcp_config.clone(),
+                    mcp_connections.clone(),
                 )))
             }
             "sub_agent" => Some(Arc::new(SubAgentTool::new(
@@ -228,12 +329,19 @@ impl ToolManager {
             self.permission_config.as_ref(),
             self.skills_enabled,
             &self.skills,
+            &self.learning,
             self.mcp_enabled,
-            &self.mcp_config,
+            &self.mcp_connections,
+            &self.lsp_config,
+            &self.watcher_config,
             &self.agents,
             &self.session_manager,
             self.sub_agent_output_caching_enabled,
             self.sub_agent_parallel_enabled,
+            &self.sandbox,
+            &self.edit_journal,
+            &self.process_supervisor,
+            &self.shell_sessions,
         )
     }
 
@@ -274,13 +382,58 @@ impl ToolManager {
         execution_context: &ToolExecutionContext,
     ) -> Result<crate::tools::ToolResult> {
         self.active_tools.write().await.insert(tool_name.to_owned());
+        let skill_grant = self.skill_allowed_tools(tool_name, &tool_args);
         let result = tool
             .stream_execute(tool_args, event_tx, execution_context)
             .await;
         self.active_tools.write().await.remove(tool_name);
+        if let (Ok(output), Some(session_id)) = (&result, execution_context.session_id) {
+            if output.success && !skill_grant.is_empty() {
+                let session_id = session_id.to_string();
+                let mut policy = self.permission_policy.write().await;
+                for allowed in &skill_grant {
+                    policy.add_turn_allowed_tool(&session_id, allowed);
+                }
+            }
+        }
         result
     }
 
+    /// Ends the session's current turn, revoking the tools that invoked
+    /// skills pre-approved for it.
+    pub async fn end_turn(&self, session_id: &str) {
+        self.permission_policy
+            .write()
+            .await
+            .clear_turn_allowed_tools(session_id);
+    }
+
+    /// Tools pre-approved for the rest of the turn by the skill a `skill` call
+    /// invokes. Reading a resource or running a script grants nothing, and no
+    /// skill can pre-approve `create_skill`.
+    fn skill_allowed_tools(&self, tool_name: &str, args: &Value) -> Vec<String> {
+        if tool_name != "skill" || args.get("resource").is_some() || args.get("script").is_some() {
+            return Vec::new();
+        }
+        args.get("skill")
+            .and_then(Value::as_str)
+            .and_then(|name| self.skills.get(name))
+            .map(|skill| {
+                skill
+                    .spec()
+                    .allowed_tools
+                    .iter()
+                    .filter(|allowed| allowed.as_str() != "create_skill")
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn skills(&self) -> &Arc<SkillRegistry> {
+        &self.skills
+    }
+
     fn shell_command_program(command: &str) -> Option<String> {
         command
             .split_whitespace()
@@ -309,6 +462,16 @@ impl ToolManager {
         if !config.enabled {
             return false;
         }
+        // PTY sessions reject sudo themselves; there is no stdin to feed a
+        // password. `send_keys` only submits lines while another program owns
+        // the terminal, so commands only reach the shell through `session_run`.
+        if args
+            .get("action")
+            .and_then(Value::as_str)
+            .is_some_and(|action| action != "exec")
+        {
+            return false;
+        }
 
         let command = match args.get("command").and_then(|value| value.as_str()) {
             Some(command) => command,
@@ -404,10 +567,13 @@ impl ToolManager {
             permission_config,
             mcp_enabled,
             mcp_config,
+            lsp_config,
+            watcher_config,
             skills_enabled,
             sub_agent_parallel_enabled,
             sub_agent_output_caching_enabled,
             skills,
+            learning,
             workflows_enabled,
             workflows,
             workflows_config,
@@ -420,12 +586,27 @@ impl ToolManager {
             plugin_config,
             tool_configs,
             execution_context,
+            sandbox,
+            event_bus,
         } = init;
 
         let mut tools = HashMap::new();
         let mut configs = HashMap::new();
         let mut lazy_loaders = HashMap::new();
         let agents = Arc::new(StdRwLock::new(None));
+        let edit_journal = Arc::new(EditJournal::new());
+        let process_supervisor = Arc::new(ProcessSupervisor::new());
+        let shell_sessions = Arc::new(ShellSessions::default());
+        let mcp_connections = Arc::new(McpConnectionManager::new(
+            mcp_config,
+            sandbox.as_ref().clone(),
+            event_bus.clone(),
+        ));
+        mcp_connections
+            .sampling()
+            .set_resolver(Arc::new(AgentModelResolver {
+                agents: agents.clone(),
+            }));
 
         for config in tool_configs {
             if !config.enabled {
@@ -449,12 +630,19 @@ impl ToolManager {
                 permission_config.as_ref(),
                 skills_enabled,
                 &skills,
+                &learning,
                 mcp_enabled,
-                &mcp_config,
+                &mcp_connections,
+                &lsp_config,
+                &watcher_config,
                 &agents,
                 &session_manager,
                 sub_agent_output_caching_enabled,
                 sub_agent_parallel_enabled,
+                &sandbox,
+                &edit_journal,
+                &process_supervisor,
+                &shell_sessions,
             ) else {
                 continue;
             };
@@ -462,17 +650,21 @@ impl ToolManager {
             tools.insert(config.name.clone(), tool);
         }
 
+        let mut rpc_plugins = Vec::new();
         if plugins_enabled {
             match PluginLoader::load_plugins(
                 &plugin_config,
                 &execution_context,
+                &configs,
                 permission_config.default_tool_permission,
+                &sandbox,
             ) {
                 Ok(loaded_plugins) => {
-                    for plugin in loaded_plugins {
+                    for plugin in loaded_plugins.tools {
                         tools.insert(plugin.name.clone(), plugin.tool);
                         configs.insert(plugin.name, plugin.config);
                     }
+                    rpc_plugins = loaded_plugins.rpc;
                 }
                 Err(err) => {
                     tracing::warn!(%err, "failed to load plugins; continuing without plugin tools");
@@ -480,6 +672,18 @@ impl ToolManager {
             }
         }
 
+        let plugin_connections = Arc::new(McpConnectionManager::new(
+            Arc::new(McpConfig {
+                servers: rpc_plugins
+                    .iter()
+                    .map(|plugin| plugin.server.clone())
+                    .collect(),
+                ..McpConfig::default()
+            }),
+            sandbox.as_ref().clone(),
+            event_bus,
+        ));
+
         Self {
             tools: Arc::new(RwLock::new(tools)),
             tool_configs: Arc::new(RwLock::new(configs)),
@@ -493,17 +697,350 @@ impl ToolManager {
             todo_tracking_enabled,
             project_id,
             skills,
+            learning,
             agents,
             session_manager,
             permission_config,
             mcp_enabled,
-            mcp_config,
+            mcp_connections,
+            lsp_config,
+            watcher_config,
             skills_enabled,
             sub_agent_parallel_enabled,
             sub_agent_output_caching_enabled,
             lazy_loaders: Arc::new(RwLock::new(lazy_loaders)),
             active_tools: Arc::new(RwLock::new(HashSet::new())),
+            sandbox,
+            edit_journal,
+            process_supervisor,
+            shell_sessions,
+            mcp_watch_started: Arc::new(AtomicBool::new(false)),
+            plugin_connections,
+            rpc_plugins: Arc::new(rpc_plugins),
+            plugin_tools: Arc::new(RwLock::new(HashMap::new())),
+            plugin_watch_started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Reverts the latest journaled filesystem change made in `session_id`.
+    pub fn undo_file_change(&self, session_id: &str, force: bool) -> Result<JournalEntrySummary> {
+        self.edit_journal
+            .undo(uuid::Uuid::parse_str(session_id).ok(), force)
+    }
+
+    /// Re-applies the latest undone filesystem change made in `session_id`.
+    pub fn redo_file_change(&self, session_id: &str, force: bool) -> Result<JournalEntrySummary> {
+        self.edit_journal
+            .redo(uuid::Uuid::parse_str(session_id).ok(), force)
+    }
+
+    pub fn file_change_history(&self, session_id: &str) -> Vec<JournalEntrySummary> {
+        self.edit_journal
+            .history(uuid::Uuid::parse_str(session_id).ok())
+    }
+
+    /// Shared MCP server connections, e.g. for status and notifications.
+    pub fn mcp_connections(&self) -> &Arc<McpConnectionManager> {
+        &self.mcp_connections
+    }
+
+    /// Client for `server` under `agent_name`'s sandbox policy, for frontends
+    /// that read resources or render prompts directly.
+    pub async fn mcp_client(
+        &self,
+        server: &str,
+        agent_name: Option<&str>,
+    ) -> Result<Arc<McpClient>> {
+        if !self.mcp_enabled {
+            return Err(Error::Tool("mcp is disabled".to_owned()));
+        }
+        let context = self.build_execution_context("", agent_name, None);
+        connect_for(&self.mcp_connections, server, &context).await
+    }
+
+    /// Discovers the tools of every MCP server with `expose_tools` and
+    /// registers them as `mcp.<server>.<tool>`, returning the count per
+    /// server. Afterwards the tools of a server are re-registered whenever it
+    /// sends `notifications/tools/list_changed`.
+    pub async fn refresh_mcp_tools(&self) -> Vec<(String, Result<usize>)> {
+        if !self.mcp_enabled {
+            return Vec::new();
+        }
+        self.start_mcp_tool_watch();
+        let servers = self
+            .mcp_connections
+            .config()
+            .servers
+            .iter()
+            .filter(|server| server.expose_tools)
+            .map(|server| server.name.clone())
+            .collect::<Vec<_>>();
+        let mut counts = Vec::new();
+        for server in servers {
+            let count = self.refresh_mcp_server_tools(&server).await;
+            if let Err(err) = &count {
+                tracing::warn!(%err, server, "failed to discover mcp tools");
+            }
+            counts.push((server, count));
+        }
+        counts
+    }
+
+    /// Replaces the registered tools of one MCP server with its current listing.
+    pub async fn refresh_mcp_server_tools(&self, server: &str) -> Result<usize> {
+        let server_config = self.mcp_connections.server_config(server)?.clone();
+        let base_config = self
+            .tool_configs
+            .read()
+            .await
+            .get("mcp")
+            .cloned()
+            .unwrap_or_default();
+        let listing = list_remote_tools(
+            &self.mcp_connections,
+            server,
+            &self.execution_context,
+            std::time::Duration::from_secs(base_config.timeout_seconds.max(1)),
+        )
+        .await?;
+
+        let mut discovered = Vec::new();
+        for entry in &listing {
+            let Some(remote_name) = entry.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let mode = server_config
+                .tool_permissions
+                .get(remote_name)
+                .copied()
+                .or(server_config.permission_mode)
+                .unwrap_or(base_config.permission_mode);
+            let config = ToolConfig {
+                name: namespaced_tool_name(server, remote_name),
+                enabled: true,
+                permission_mode: mode,
+                ..base_config.clone()
+            };
+            if let Some(tool) = McpRemoteTool::from_listing(
+                config.clone(),
+                server,
+                entry,
+                self.mcp_connections.clone(),
+            ) {
+                discovered.push((config, tool));
+            }
+        }
+
+        let prefix = namespaced_tool_name(server, "");
+        {
+            let mut policy = self.permission_policy.write().await;
+            for (config, _) in &discovered {
+                policy.set_tool_permission_mode(&config.name, config.permission_mode);
+            }
+        }
+        let mut tools = self.tools.write().await;
+        let mut configs = self.tool_configs.write().await;
+        tools.retain(|name, _| !name.starts_with(&prefix));
+        configs.retain(|name, _| !name.starts_with(&prefix));
+        let count = discovered.len();
+        for (config, tool) in discovered {
+            tools.insert(config.name.clone(), Arc::new(tool));
+            configs.insert(config.name.clone(), config);
+        }
+        Ok(count)
+    }
+
+    fn start_mcp_tool_watch(&self) {
+        if self.mcp_watch_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let manager = self.clone();
+        let mut notifications = self.mcp_connections.subscribe();
+        tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                let notification = tokio::select! {
+                    received = notifications.recv() => received,
+                    _ = manager.mcp_connections.stopped() => return,
+                };
+                match notification {
+                    Ok(notification)
+                        if notification.method == "notifications/tools/list_changed" =>
+                    {
+                        let exposed = manager
+                            .mcp_connections
+                            .server_config(&notification.server)
+                            .is_ok_and(|server| server.expose_tools);
+                        if !exposed {
+                            continue;
+                        }
+                        if let Err(err) =
+                            manager.refresh_mcp_server_tools(&notification.server).await
+                        {
+                            tracing::warn!(%err, server = notification.server, "failed to refresh mcp tools");
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Processes of `mode: rpc` plugins, e.g. for status.
+    pub fn plugin_connections(&self) -> &Arc<McpConnectionManager> {
+        &self.plugin_connections
+    }
+
+    /// Starts every rpc plugin and registers the tools it advertises under
+    /// their own names, returning the count per plugin. Names already taken
+    /// by other tools are skipped. Afterwards a plugin's tools are
+    /// re-registered whenever it sends `notifications/tools/list_changed`.
+    pub async fn refresh_plugin_tools(&self) -> Vec<(String, Result<usize>)> {
+        if self.rpc_plugins.is_empty() {
+            return Vec::new();
+        }
+        self.start_plugin_tool_watch();
+        let mut counts = Vec::new();
+        for plugin in self.rpc_plugins.iter() {
+            let count = self.refresh_rpc_plugin_tools(plugin).await;
+            if let Err(err) = &count {
+                tracing::warn!(%err, plugin = plugin.name, "failed to discover plugin tools");
+            }
+            counts.push((plugin.name.clone(), count));
+        }
+        counts
+    }
+
+    async fn refresh_rpc_plugin_tools(&self, plugin: &RpcPlugin) -> Result<usize> {
+        let listing = list_remote_tools(
+            &self.plugin_connections,
+            &plugin.server.name,
+            &self.execution_context,
+            std::time::Duration::from_secs(plugin.defaults.timeout_seconds.max(1)),
+        )
+        .await?;
+
+        let mut owned = self.plugin_tools.write().await;
+        let previous = owned.remove(&plugin.name).unwrap_or_default();
+        let mut discovered = Vec::new();
+        {
+            let tools = self.tools.read().await;
+            let configs = self.tool_configs.read().await;
+            let lazy_loaders = self.lazy_loaders.read().await;
+            for entry in &listing {
+                let Some(name) = entry.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                let taken = (tools.contains_key(name) && !previous.iter().any(|tool| tool == name))
+                    || lazy_loaders.contains_key(name)
+                    || discovered
+                        .iter()
+                        .any(|(config, _): &(ToolConfig, RpcPluginTool)| config.name == name);
+                if taken {
+                    tracing::warn!(
+                        plugin = plugin.name,
+                        tool = name,
+                        "plugin tool name is already taken; skipping"
+                    );
+                    continue;
+                }
+                let config = configs.get(name).cloned().unwrap_or_else(|| ToolConfig {
+                    name: name.to_owned(),
+                    ..plugin.defaults.clone()
+                });
+                if !config.enabled {
+                    continue;
+                }
+                let tool = RpcPluginTool::from_listing(
+                    config.clone(),
+                    plugin,
+                    entry,
+                    self.plugin_connections.clone(),
+                );
+                discovered.push((config, tool));
+            }
+        }
+
+        {
+            let mut policy = self.permission_policy.write().await;
+            for (config, _) in &discovered {
+                policy.set_tool_permission_mode(&config.name, config.permission_mode);
+            }
+        }
+        let mut tools = self.tools.write().await;
+        let mut configs = self.tool_configs.write().await;
+        for name in &previous {
+            tools.remove(name);
+        }
+        let count = discovered.len();
+        let mut names = Vec::with_capacity(count);
+        for (config, tool) in discovered {
+            names.push(config.name.clone());
+            tools.insert(config.name.clone(), Arc::new(tool));
+            configs.insert(config.name.clone(), config);
         }
+        owned.insert(plugin.name.clone(), names);
+        Ok(count)
+    }
+
+    fn start_plugin_tool_watch(&self) {
+        if self.plugin_watch_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let manager = self.clone();
+        let mut notifications = self.plugin_connections.subscribe();
+        tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                let notification = tokio::select! {
+                    received = notifications.recv() => received,
+                    _ = manager.plugin_connections.stopped() => return,
+                };
+                match notification {
+                    Ok(notification)
+                        if notification.method == "notifications/tools/list_changed" =>
+                    {
+                        let Some(plugin) = manager
+                            .rpc_plugins
+                            .iter()
+                            .find(|plugin| plugin.server.name == notification.server)
+                        else {
+                            continue;
+                        };
+                        if let Err(err) = manager.refresh_rpc_plugin_tools(plugin).await {
+                            tracing::warn!(%err, plugin = plugin.name, "failed to refresh plugin tools");
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Stops runtime-wide resources such as MCP server and rpc plugin
+    /// processes. Call once when the runtime shuts down.
+    pub async fn shutdown(&self) {
+        self.mcp_connections.shutdown().await;
+        self.plugin_connections.shutdown().await;
+    }
+
+    /// Stops the managed background processes and closes the PTY shell
+    /// sessions started from `session_id`, returning their names. Call when
+    /// the session ends.
+    pub async fn end_session(&self, session_id: &str) -> Vec<String> {
+        let Ok(session_id) = uuid::Uuid::parse_str(session_id) else {
+            return Vec::new();
+        };
+        let mut stopped = self.process_supervisor.stop_session(session_id).await;
+        stopped.extend(
+            ShellTool::close_sessions_for(&self.shell_sessions, &session_id.to_string())
+                .await
+                .into_iter()
+                .map(|name| format!("shell session '{name}'")),
+        );
+        stopped
     }
 
     fn build_execution_context(
@@ -610,6 +1147,7 @@ impl ToolManager {
             .get("input")
             .cloned()
             .unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let params = args.get("params").cloned().unwrap_or(Value::Null);
 
         let agents = {
             let guard = self
@@ -634,6 +1172,8 @@ impl ToolManager {
                 self.dynamic_routing.as_ref().clone(),
                 self.todo_tracking_enabled,
                 self.project_id.clone(),
+                self.build_execution_context(&session_id, agent_name.as_deref(), None)
+                    .agent_permission_mode,
             ),
         );
 
@@ -645,8 +1185,10 @@ impl ToolManager {
                     session_id,
                     agent_name,
                     input,
+                    params,
                     recursion_depth: 0,
                     workflow_stack: Vec::new(),
+                    resume_from: None,
                 },
                 self,
                 event_tx,
@@ -660,6 +1202,7 @@ impl ToolManager {
                 "success": result.success,
                 "steps_executed": result.steps_executed,
                 "outputs": result.outputs,
+                "result": result.result,
             }))
             .unwrap_or_else(|_| "{}".to_owned()),
         }))
@@ -676,10 +1219,31 @@ impl ToolManager {
         self.tool_configs.read().await.contains_key(name)
     }
 
+    pub async fn list_tools(&self) -> Vec<String> {
+        let mut names = self
+            .tool_configs
+            .read()
+            .await
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
     pub async fn get_tool_config(&self, name: &str) -> Option<ToolConfig> {
         self.tool_configs.read().await.get(name).cloned()
     }
 
+    /// Input schema and description of a loaded tool.
+    pub async fn get_tool_schema(&self, name: &str) -> Option<(String, Value)> {
+        self.tools
+            .read()
+            .await
+            .get(name)
+            .map(|tool| (tool.description().to_owned(), tool.schema().clone()))
+    }
+
     pub async fn unload_unused(&self, keep_tools: &[String]) -> usize {
         let keep = keep_tools.iter().cloned().collect::<HashSet<_>>();
         let lazy = self.lazy_loaders.read().await;
@@ -871,6 +1435,19 @@ impl ToolManager {
     ///
     /// Returns None if permission was denied
     /// Returns Some(ToolResult) if execution completed
+    /// Checks the permission policy for a call without running the tool.
+    pub async fn check_permission(
+        &self,
+        session_id: String,
+        agent_name: Option<String>,
+        tool_name: &str,
+        args: &serde_json::Value,
+    ) -> PermissionDecision {
+        let permission_context = self.build_permission_context(session_id, agent_name);
+        let policy = self.permission_policy.read().await;
+        policy.check_tool_permission(tool_name, args, &permission_context)
+    }
+
     pub async fn execute_tool(
         &self,
         session_id: String,
@@ -959,16 +1536,33 @@ impl ToolManager {
             }
             PermissionDecision::Ask => {
                 // Emit request - caller must handle this and call resolve_permission
+                let grants = self.skill_allowed_tools(tool_name, &args);
                 let _ = event_tx.try_send(Event::PermissionRequest {
                     session_id,
                     tool: tool_name.to_string(),
                     args,
+                    grants,
                 });
                 Ok(None)
             }
         }
     }
 
+    /// Records a decision for a pending call without running the tool, so a
+    /// resumed agent turn can execute it.
+    pub async fn record_permission(
+        &self,
+        session_id: String,
+        agent_name: Option<String>,
+        tool_name: &str,
+        args: &serde_json::Value,
+        decision: AskResolution,
+    ) {
+        let permission_context = self.build_permission_context(session_id, agent_name);
+        let mut policy = self.permission_policy.write().await;
+        policy.record_permission(tool_name, args, &permission_context, decision);
+    }
+
     /// Resolve a permission decision and optionally execute the tool
     pub async fn resolve_permission(
         &self,
@@ -1,32 +1,120 @@
-use crate::config::schema::{McpConfig, McpServerConfig, ToolConfig};
+use crate::config::schema::ToolConfig;
 use crate::error::{Error, Result};
 use crate::events::Event;
+use crate::mcp::{McpClient, McpConnectionManager};
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use std::io::{BufRead, BufReader, Read, Write};
-use std::path::{Path, PathBuf};
-use std::process::{Command as StdCommand, Stdio};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
-use tokio::time::timeout;
+
+/// Prefix of tools discovered on MCP servers, e.g. `mcp.github.create_issue`.
+pub const MCP_TOOL_PREFIX: &str = "mcp.";
+
+pub fn namespaced_tool_name(server: &str, tool: &str) -> String {
+    format!("{MCP_TOOL_PREFIX}{server}.{tool}")
+}
+
+/// Splits `mcp.<server>.<tool>` into server and remote tool name. Server
+/// names cannot contain dots; tool names may.
+pub fn split_tool_name(name: &str) -> Option<(&str, &str)> {
+    name.strip_prefix(MCP_TOOL_PREFIX)?
+        .split_once('.')
+        .filter(|(server, tool)| !server.is_empty() && !tool.is_empty())
+}
+
+/// True when an agent's tool list admits `tool`. Besides exact names, `mcp`
+/// admits every discovered MCP tool and `mcp.<server>` admits that server's.
+pub fn tool_list_allows<'a>(allowed: impl IntoIterator<Item = &'a str>, tool: &str) -> bool {
+    let server = split_tool_name(tool).map(|(server, _)| server);
+    allowed.into_iter().any(|entry| {
+        entry == tool
+            || server.is_some_and(|server| {
+                entry == "mcp" || entry.strip_prefix(MCP_TOOL_PREFIX) == Some(server)
+            })
+    })
+}
+
+/// Client for `server` under the calling agent's sandbox policy. The agent is
+/// recorded so sampling requests can be attributed to it.
+pub async fn connect_for(
+    connections: &McpConnectionManager,
+    server: &str,
+    context: &ToolExecutionContext,
+) -> Result<Arc<McpClient>> {
+    let client = connections
+        .client(
+            server,
+            &context.working_directory,
+            context.agent_permission_mode,
+        )
+        .await?;
+    client.set_agent(context.agent_name.as_deref());
+    Ok(client)
+}
+
+/// Lists every tool on `server`, following `nextCursor` pages.
+pub async fn list_remote_tools(
+    connections: &McpConnectionManager,
+    server: &str,
+    context: &ToolExecutionContext,
+    timeout: Duration,
+) -> Result<Vec<Value>> {
+    connect_for(connections, server, context)
+        .await?
+        .list_all("tools/list", "tools", timeout)
+        .await
+}
+
+/// Joins the text of a `resources/read` result; binary contents are listed
+/// by URI and MIME type only.
+pub fn render_resource_contents(result: &Value) -> String {
+    result
+        .get("contents")
+        .and_then(Value::as_array)
+        .map(|contents| {
+            contents
+                .iter()
+                .map(|item| match item.get("text").and_then(Value::as_str) {
+                    Some(text) => text.to_owned(),
+                    None => format!(
+                        "[binary resource {} ({})]",
+                        item.get("uri").and_then(Value::as_str).unwrap_or("?"),
+                        item.get("mimeType")
+                            .and_then(Value::as_str)
+                            .unwrap_or("application/octet-stream")
+                    ),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
 
 #[derive(Debug, Clone)]
 pub struct McpToolAdapter {
     config: ToolConfig,
     schema: Value,
-    mcp_config: Arc<McpConfig>,
+    connections: Arc<McpConnectionManager>,
 }
 
 impl McpToolAdapter {
-    pub fn new(config: ToolConfig, mcp_config: Arc<McpConfig>) -> Self {
+    pub fn new(config: ToolConfig, connections: Arc<McpConnectionManager>) -> Self {
         let schema = json!({
             "type": "object",
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["list_servers", "list_tools", "call_tool"]
+                    "enum": [
+                        "list_servers",
+                        "list_tools",
+                        "call_tool",
+                        "list_resources",
+                        "read_resource",
+                        "list_prompts",
+                        "get_prompt"
+                    ]
                 },
                 "server": {
                     "type": "string",
@@ -38,7 +126,15 @@ impl McpToolAdapter {
                 },
                 "arguments": {
                     "type": "object",
-                    "description": "Arguments object for remote MCP tool call"
+                    "description": "Arguments for call_tool, or string arguments for get_prompt"
+                },
+                "uri": {
+                    "type": "string",
+                    "description": "Resource URI for read_resource"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "Prompt name for get_prompt"
                 }
             },
             "required": ["operation"]
@@ -47,7 +143,7 @@ impl McpToolAdapter {
         Self {
             config,
             schema,
-            mcp_config,
+            connections,
         }
     }
 
@@ -59,283 +155,66 @@ impl McpToolAdapter {
             .ok_or_else(|| Error::Tool(format!("missing '{key}' argument")))
     }
 
-    fn server_by_name(&self, name: &str) -> Result<McpServerConfig> {
-        self.mcp_config
-            .servers
-            .iter()
-            .find(|server| server.name == name)
-            .cloned()
-            .ok_or_else(|| Error::Tool(format!("mcp server '{name}' is not configured")))
-    }
-
-    fn resolve_path(raw: &str, work_dir: &Path) -> PathBuf {
-        let path = Path::new(raw);
-        if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            work_dir.join(path)
-        }
-    }
-
-    fn write_framed_message(writer: &mut dyn Write, message: &Value) -> Result<()> {
-        let payload = serde_json::to_vec(message)
-            .map_err(|err| Error::Tool(format!("failed to serialize mcp message: {err}")))?;
-        let header = format!("Content-Length: {}\r\n\r\n", payload.len());
-        writer
-            .write_all(header.as_bytes())
-            .map_err(|err| Error::Tool(format!("failed writing mcp header: {err}")))?;
-        writer
-            .write_all(&payload)
-            .map_err(|err| Error::Tool(format!("failed writing mcp payload: {err}")))?;
-        writer
-            .flush()
-            .map_err(|err| Error::Tool(format!("failed flushing mcp payload: {err}")))?;
-        Ok(())
-    }
-
-    fn read_framed_message(reader: &mut BufReader<impl Read>) -> Result<Value> {
-        let mut content_length: Option<usize> = None;
-
-        loop {
-            let mut line = String::new();
-            let read = reader
-                .read_line(&mut line)
-                .map_err(|err| Error::Tool(format!("failed reading mcp header: {err}")))?;
-            if read == 0 {
-                return Err(Error::Tool(
-                    "mcp server closed stream unexpectedly".to_owned(),
-                ));
-            }
-
-            let trimmed = line.trim_end_matches(['\r', '\n']);
-            if trimmed.is_empty() {
-                break;
-            }
-
-            let Some((name, value)) = trimmed.split_once(':') else {
-                continue;
-            };
-            if name.eq_ignore_ascii_case("content-length") {
-                let parsed = value.trim().parse::<usize>().map_err(|err| {
-                    Error::Tool(format!("invalid mcp Content-Length header: {err}"))
-                })?;
-                content_length = Some(parsed);
-            }
-        }
-
-        let len = content_length
-            .ok_or_else(|| Error::Tool("missing mcp Content-Length header".to_owned()))?;
-        let mut body = vec![0u8; len];
-        reader
-            .read_exact(&mut body)
-            .map_err(|err| Error::Tool(format!("failed reading mcp message body: {err}")))?;
-        serde_json::from_slice::<Value>(&body)
-            .map_err(|err| Error::Tool(format!("failed parsing mcp message json: {err}")))
-    }
-
-    fn read_response_for_id(
-        reader: &mut BufReader<impl Read>,
-        id: i64,
-    ) -> Result<(Option<Value>, Option<Value>)> {
-        loop {
-            let message = Self::read_framed_message(reader)?;
-            let message_id = message.get("id").and_then(Value::as_i64);
-            if message_id != Some(id) {
-                continue;
-            }
-
-            let result = message.get("result").cloned();
-            let error = message.get("error").cloned();
-            return Ok((result, error));
-        }
-    }
-
-    fn spawn_server_process(
-        server: &McpServerConfig,
-        context: &ToolExecutionContext,
-    ) -> Result<std::process::Child> {
-        let mut command = StdCommand::new(&server.command);
-        command.args(&server.args);
-
-        let working_dir = if let Some(raw) = &server.working_directory {
-            Self::resolve_path(raw, &context.working_directory)
-        } else {
-            context.working_directory.clone()
-        };
-        command.current_dir(&working_dir);
-
-        if !server.env.is_empty() {
-            command.envs(&server.env);
-        }
-
-        command
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|err| {
-                Error::Tool(format!(
-                    "failed to spawn mcp server '{}' using '{}': {err}",
-                    server.name, server.command
-                ))
-            })
-    }
-
-    fn initialize_and_open(
-        server: &McpServerConfig,
-        context: &ToolExecutionContext,
-    ) -> Result<(
-        std::process::Child,
-        std::process::ChildStdin,
-        BufReader<std::process::ChildStdout>,
-    )> {
-        let mut child = Self::spawn_server_process(server, context)?;
-        let mut child_stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| Error::Tool("failed to capture mcp server stdin".to_owned()))?;
-        let child_stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| Error::Tool("failed to capture mcp server stdout".to_owned()))?;
-        let mut reader = BufReader::new(child_stdout);
-
-        let initialize = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "initialize",
-            "params": {
-                "protocolVersion": server.protocol_version,
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "rustic-ai",
-                    "version": env!("CARGO_PKG_VERSION")
-                }
-            }
-        });
-        Self::write_framed_message(&mut child_stdin, &initialize)?;
-
-        let (_, init_error) = Self::read_response_for_id(&mut reader, 1)?;
-        if let Some(err) = init_error {
-            let _ = child.kill();
-            return Err(Error::Tool(format!(
-                "mcp initialize failed for server '{}': {err}",
-                server.name
-            )));
-        }
-
-        let initialized = json!({
-            "jsonrpc": "2.0",
-            "method": "notifications/initialized",
-            "params": {}
-        });
-        Self::write_framed_message(&mut child_stdin, &initialized)?;
-
-        Ok((child, child_stdin, reader))
-    }
-
-    fn list_tools_blocking(
-        server: McpServerConfig,
-        context: ToolExecutionContext,
-    ) -> Result<Value> {
-        let (mut child, mut child_stdin, mut reader) =
-            Self::initialize_and_open(&server, &context)?;
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 2,
-            "method": "tools/list",
-            "params": {}
-        });
-        Self::write_framed_message(&mut child_stdin, &request)?;
-
-        let (result, error) = Self::read_response_for_id(&mut reader, 2)?;
-        let _ = child.kill();
-
-        if let Some(err) = error {
-            return Err(Error::Tool(format!(
-                "mcp tools/list failed for server '{}': {err}",
-                server.name
-            )));
-        }
-
-        Ok(result.unwrap_or_else(|| json!({})))
-    }
-
-    fn call_tool_blocking(
-        server: McpServerConfig,
-        context: ToolExecutionContext,
-        tool_name: String,
-        arguments: Value,
-    ) -> Result<Value> {
-        let (mut child, mut child_stdin, mut reader) =
-            Self::initialize_and_open(&server, &context)?;
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 3,
-            "method": "tools/call",
-            "params": {
-                "name": tool_name,
-                "arguments": arguments
-            }
-        });
-        Self::write_framed_message(&mut child_stdin, &request)?;
-
-        let (result, error) = Self::read_response_for_id(&mut reader, 3)?;
-        let _ = child.kill();
-
-        if let Some(err) = error {
-            return Err(Error::Tool(format!(
-                "mcp tools/call failed for server '{}': {err}",
-                server.name
-            )));
-        }
-
-        Ok(result.unwrap_or_else(|| json!({})))
+    fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_seconds.max(1))
     }
 
     async fn list_tools_for_server(
         &self,
-        server: McpServerConfig,
+        server: &str,
         context: &ToolExecutionContext,
     ) -> Result<Value> {
-        let ctx = context.clone();
-        let timeout_secs = server.startup_timeout_seconds.max(1);
-        let handle = tokio::task::spawn_blocking(move || Self::list_tools_blocking(server, ctx));
-        let joined = timeout(Duration::from_secs(timeout_secs), handle)
-            .await
-            .map_err(|_| Error::Tool("mcp tools/list timed out".to_owned()))?;
-        joined.map_err(|err| Error::Tool(format!("mcp task join failed: {err}")))?
+        let tools =
+            list_remote_tools(&self.connections, server, context, self.request_timeout()).await?;
+        Ok(json!({"tools": tools}))
     }
 
     async fn call_tool_on_server(
         &self,
-        server: McpServerConfig,
+        server: &str,
         context: &ToolExecutionContext,
-        tool_name: String,
+        tool_name: &str,
         arguments: Value,
     ) -> Result<Value> {
-        let ctx = context.clone();
-        let timeout_secs = server.startup_timeout_seconds.max(1);
-        let handle = tokio::task::spawn_blocking(move || {
-            Self::call_tool_blocking(server, ctx, tool_name, arguments)
-        });
-        let joined = timeout(Duration::from_secs(timeout_secs), handle)
+        let client = connect_for(&self.connections, server, context).await?;
+        client
+            .request(
+                "tools/call",
+                json!({"name": tool_name, "arguments": arguments}),
+                self.request_timeout(),
+            )
             .await
-            .map_err(|_| Error::Tool("mcp tools/call timed out".to_owned()))?;
-        joined.map_err(|err| Error::Tool(format!("mcp task join failed: {err}")))?
     }
 
-    fn server_summary(&self) -> Vec<Value> {
-        self.mcp_config
+    async fn server_summary(&self) -> Vec<Value> {
+        let statuses = self.connections.status().await;
+        self.connections
+            .config()
             .servers
             .iter()
             .map(|server| {
+                let connections = statuses
+                    .iter()
+                    .filter(|status| status.server == server.name)
+                    .map(|status| {
+                        json!({
+                            "state": status.state,
+                            "pid": status.pid,
+                            "workspace_writable": status.workspace_writable,
+                            "restarts": status.restarts,
+                            "last_error": status.last_error
+                        })
+                    })
+                    .collect::<Vec<_>>();
                 json!({
                     "name": server.name,
+                    "transport": server.transport,
+                    "url": server.url,
                     "command": server.command,
                     "args": server.args,
                     "startup_timeout_seconds": server.startup_timeout_seconds,
-                    "protocol_version": server.protocol_version
+                    "protocol_version": server.protocol_version,
+                    "connections": connections
                 })
             })
             .collect()
@@ -349,7 +228,7 @@ impl Tool for McpToolAdapter {
     }
 
     fn description(&self) -> &str {
-        "Call external MCP tools via configured stdio servers"
+        "Call tools, read resources and render prompts on configured MCP servers"
     }
 
     fn schema(&self) -> &Value {
@@ -377,8 +256,8 @@ impl Tool for McpToolAdapter {
         let result = match operation.as_str() {
             "list_servers" => {
                 let payload = json!({
-                    "servers": self.server_summary(),
-                    "count": self.mcp_config.servers.len()
+                    "servers": self.server_summary().await,
+                    "count": self.connections.config().servers.len()
                 });
                 Ok(ToolResult {
                     success: true,
@@ -388,8 +267,7 @@ impl Tool for McpToolAdapter {
             }
             "list_tools" => {
                 let server_name = self.required_string(&args, "server")?.to_owned();
-                let server = self.server_by_name(&server_name)?;
-                let listed = self.list_tools_for_server(server, context).await?;
+                let listed = self.list_tools_for_server(&server_name, context).await?;
                 Ok(ToolResult {
                     success: true,
                     exit_code: Some(0),
@@ -404,9 +282,8 @@ impl Tool for McpToolAdapter {
                     .cloned()
                     .unwrap_or_else(|| Value::Object(serde_json::Map::new()));
 
-                let server = self.server_by_name(&server_name)?;
                 let called = self
-                    .call_tool_on_server(server, context, tool_name.clone(), arguments)
+                    .call_tool_on_server(&server_name, context, &tool_name, arguments)
                     .await?;
                 Ok(ToolResult {
                     success: true,
@@ -419,6 +296,62 @@ impl Tool for McpToolAdapter {
                     .to_string(),
                 })
             }
+            "list_resources" | "list_prompts" => {
+                let server_name = self.required_string(&args, "server")?.to_owned();
+                let (method, field) = if operation == "list_resources" {
+                    ("resources/list", "resources")
+                } else {
+                    ("prompts/list", "prompts")
+                };
+                let listed = connect_for(&self.connections, &server_name, context)
+                    .await?
+                    .list_all(method, field, self.request_timeout())
+                    .await?;
+                Ok(ToolResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: json!({"server": server_name, field: listed}).to_string(),
+                })
+            }
+            "read_resource" => {
+                let server_name = self.required_string(&args, "server")?.to_owned();
+                let uri = self.required_string(&args, "uri")?.to_owned();
+                let read = connect_for(&self.connections, &server_name, context)
+                    .await?
+                    .request(
+                        "resources/read",
+                        json!({"uri": uri}),
+                        self.request_timeout(),
+                    )
+                    .await?;
+                Ok(ToolResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: render_resource_contents(&read),
+                })
+            }
+            "get_prompt" => {
+                let server_name = self.required_string(&args, "server")?.to_owned();
+                let prompt = self.required_string(&args, "prompt")?.to_owned();
+                let arguments = args
+                    .get("arguments")
+                    .cloned()
+                    .unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+                let rendered = connect_for(&self.connections, &server_name, context)
+                    .await?
+                    .request(
+                        "prompts/get",
+                        json!({"name": prompt, "arguments": arguments}),
+                        self.request_timeout(),
+                    )
+                    .await?;
+                Ok(ToolResult {
+                    success: true,
+                    exit_code: Some(0),
+                    output: json!({"server": server_name, "prompt": prompt, "result": rendered})
+                        .to_string(),
+                })
+            }
             other => Err(Error::Tool(format!("unsupported mcp operation '{other}'"))),
         };
 
@@ -440,3 +373,131 @@ impl Tool for McpToolAdapter {
         }
     }
 }
+
+/// One tool discovered on an MCP server, registered as `mcp.<server>.<tool>`
+/// with the server's own input schema.
+#[derive(Debug, Clone)]
+pub struct McpRemoteTool {
+    config: ToolConfig,
+    server: String,
+    remote_name: String,
+    description: String,
+    schema: Value,
+    connections: Arc<McpConnectionManager>,
+}
+
+impl McpRemoteTool {
+    /// Builds the tool from one `tools/list` entry. `config.name` must already
+    /// be the namespaced name.
+    pub fn from_listing(
+        config: ToolConfig,
+        server: &str,
+        listing: &Value,
+        connections: Arc<McpConnectionManager>,
+    ) -> Option<Self> {
+        let remote_name = listing.get("name").and_then(Value::as_str)?.to_owned();
+        let description = listing
+            .get("description")
+            .and_then(Value::as_str)
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| format!("MCP tool '{remote_name}' on server '{server}'"));
+        let schema = listing
+            .get("inputSchema")
+            .filter(|schema| schema.is_object())
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object"}));
+        Some(Self {
+            config,
+            server: server.to_owned(),
+            remote_name,
+            description,
+            schema,
+            connections,
+        })
+    }
+
+    /// Text content joined by newlines; anything else is returned as JSON.
+    fn render_output(result: &Value) -> String {
+        let content = result.get("content").and_then(Value::as_array);
+        let texts = content.map(|items| {
+            items
+                .iter()
+                .map(|item| {
+                    (item.get("type").and_then(Value::as_str) == Some("text"))
+                        .then(|| item.get("text").and_then(Value::as_str))
+                        .flatten()
+                })
+                .collect::<Option<Vec<_>>>()
+        });
+        match texts {
+            Some(Some(texts)) if result.get("structuredContent").is_none() => texts.join("\n"),
+            _ => result.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for McpRemoteTool {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    async fn execute(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let (dummy_tx, _) = mpsc::channel(1);
+        self.stream_execute(args, dummy_tx, context).await
+    }
+
+    async fn stream_execute(
+        &self,
+        args: Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let _ = tx.try_send(Event::ToolStarted {
+            tool: self.config.name.clone(),
+            args: args.clone(),
+        });
+
+        let arguments = if args.is_null() { json!({}) } else { args };
+        let called = async {
+            let client = connect_for(&self.connections, &self.server, context).await?;
+            client
+                .request(
+                    "tools/call",
+                    json!({"name": self.remote_name, "arguments": arguments}),
+                    Duration::from_secs(self.config.timeout_seconds.max(1)),
+                )
+                .await
+        }
+        .await;
+
+        let result = called.map(|result| {
+            let failed = result
+                .get("isError")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            ToolResult {
+                success: !failed,
+                exit_code: Some(if failed { 1 } else { 0 }),
+                output: Self::render_output(&result),
+            }
+        });
+        let _ = tx.try_send(Event::ToolCompleted {
+            tool: self.config.name.clone(),
+            exit_code: result
+                .as_ref()
+                .map(|result| result.exit_code.unwrap_or_default())
+                .unwrap_or(1),
+        });
+        result
+    }
+}
@@ -2,6 +2,7 @@ pub mod bracket_validator;
 pub mod code_search;
 pub mod convert;
 pub mod crawler;
+pub mod create_skill;
 pub mod database;
 pub mod docker;
 pub mod download;
@@ -12,17 +13,22 @@ pub mod git;
 pub mod grep;
 pub mod http;
 pub mod image;
+pub mod journal;
 pub mod lsp;
 pub mod manager;
 pub mod mcp;
+pub mod patch;
 pub mod plugin;
 pub mod process;
+pub mod pty;
 pub mod regex;
 pub mod registry;
 pub mod shell;
 pub mod skill;
+pub mod sql_guard;
 pub mod ssh;
 pub mod sub_agent;
+pub mod supervisor;
 pub mod types;
 pub mod watch;
 pub mod web_fetch;
@@ -0,0 +1,762 @@
+//! Patch parsing and all-or-nothing application for the filesystem tool's
+//! `apply_patch` operation. Accepts unified diffs, SEARCH/REPLACE blocks and
+//! structured `edits` arrays; every hunk is resolved in memory before anything
+//! touches disk, and failed hunks come back with fuzzy-match diagnostics.
+
+use crate::error::{Error, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SEARCH_MARKER: &str = "<<<<<<< SEARCH";
+const DIVIDER_MARKER: &str = "=======";
+const REPLACE_MARKER: &str = ">>>>>>> REPLACE";
+
+#[derive(Debug, Clone)]
+pub enum Hunk {
+    /// Whole-line hunk; `hint` is the zero-based old start line from a unified
+    /// diff, `None` for SEARCH/REPLACE blocks. An empty `old` inserts at `hint`
+    /// (or appends), which also creates missing files.
+    Lines {
+        hint: Option<usize>,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    /// Literal substring replacement from an `edits` entry.
+    Text {
+        old: String,
+        new: String,
+        replace_all: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum FileChange {
+    Create(String),
+    Delete,
+    Modify(Vec<Hunk>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    pub path: String,
+    pub change: FileChange,
+}
+
+/// Parses a unified diff or a series of SEARCH/REPLACE blocks.
+pub fn parse(input: &str) -> Result<Vec<FilePatch>> {
+    let patches = if input.lines().any(|line| line.trim_end() == SEARCH_MARKER) {
+        parse_search_replace(input)?
+    } else {
+        parse_unified(input)?
+    };
+    if patches.is_empty() {
+        return Err(Error::Tool(
+            "patch contains no file changes (expected a unified diff or SEARCH/REPLACE blocks)"
+                .to_owned(),
+        ));
+    }
+    Ok(patches)
+}
+
+/// Converts an `edits` array of `{path, old_text, new_text, replace_all}` objects.
+pub fn parse_edits(value: &Value) -> Result<Vec<FilePatch>> {
+    let edits = value
+        .as_array()
+        .ok_or_else(|| Error::Tool("argument 'edits' must be an array".to_owned()))?;
+    edits
+        .iter()
+        .enumerate()
+        .map(|(index, edit)| {
+            let field = |key: &str| edit.get(key).and_then(Value::as_str);
+            let path = field("path")
+                .filter(|path| !path.trim().is_empty())
+                .ok_or_else(|| Error::Tool(format!("edits[{index}] is missing 'path'")))?;
+            let old = field("old_text").unwrap_or_default();
+            let new = field("new_text").unwrap_or_default();
+            let replace_all = edit
+                .get("replace_all")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            Ok(FilePatch {
+                path: path.to_owned(),
+                change: FileChange::Modify(vec![Hunk::Text {
+                    old: old.to_owned(),
+                    new: new.to_owned(),
+                    replace_all,
+                }]),
+            })
+        })
+        .collect()
+}
+
+fn strip_diff_path(raw: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or(raw).trim();
+    if raw == "/dev/null" {
+        return None;
+    }
+    let raw = raw
+        .strip_prefix("a/")
+        .or_else(|| raw.strip_prefix("b/"))
+        .unwrap_or(raw);
+    Some(raw.to_owned())
+}
+
+/// Old and new line ranges of a hunk header.
+struct HunkHeader {
+    old_start: usize,
+    old_count: usize,
+    new_count: usize,
+}
+
+fn parse_hunk_header(line: &str) -> Result<HunkHeader> {
+    // @@ -start[,count] +start[,count] @@
+    let malformed = || Error::Tool(format!("malformed hunk header '{line}'"));
+    let range = |position: usize, sign: char| -> Result<(usize, usize)> {
+        let range = line
+            .split_whitespace()
+            .nth(position)
+            .and_then(|range| range.strip_prefix(sign))
+            .ok_or_else(malformed)?;
+        let mut parts = range.splitn(2, ',');
+        let start = parts
+            .next()
+            .and_then(|value| value.parse::<usize>().ok())
+            .ok_or_else(malformed)?;
+        let count = match parts.next() {
+            Some(value) => value.parse::<usize>().map_err(|_| malformed())?,
+            None => 1,
+        };
+        Ok((start, count))
+    };
+    let (old_start, old_count) = range(1, '-')?;
+    let (_, new_count) = range(2, '+')?;
+    Ok(HunkHeader {
+        old_start,
+        old_count,
+        new_count,
+    })
+}
+
+fn parse_unified(input: &str) -> Result<Vec<FilePatch>> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut patches = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let Some(old_raw) = lines[index].strip_prefix("--- ") else {
+            index += 1;
+            continue;
+        };
+        let Some(new_raw) = lines
+            .get(index + 1)
+            .and_then(|line| line.strip_prefix("+++ "))
+        else {
+            index += 1;
+            continue;
+        };
+        let old_path = strip_diff_path(old_raw);
+        let new_path = strip_diff_path(new_raw);
+        index += 2;
+
+        let mut hunks = Vec::new();
+        while index < lines.len() && lines[index].starts_with("@@") {
+            let header_line = index + 1;
+            let header = parse_hunk_header(lines[index])?;
+            index += 1;
+            let mut old = Vec::new();
+            let mut new = Vec::new();
+            // The header counts decide where the hunk ends, so removed lines
+            // that look like `--- ` or `diff ` headers stay part of it.
+            while old.len() < header.old_count || new.len() < header.new_count {
+                let Some(line) = lines.get(index) else {
+                    break;
+                };
+                let (old_line, new_line) = match line.chars().next() {
+                    Some(' ') => (Some(&line[1..]), Some(&line[1..])),
+                    Some('-') => (Some(&line[1..]), None),
+                    Some('+') => (None, Some(&line[1..])),
+                    Some('\\') => {
+                        index += 1;
+                        continue;
+                    }
+                    // Some generators drop the leading space on blank context lines.
+                    None => (Some(""), Some("")),
+                    Some(_) => break,
+                };
+                if (old_line.is_some() && old.len() == header.old_count)
+                    || (new_line.is_some() && new.len() == header.new_count)
+                {
+                    break;
+                }
+                old.extend(old_line.map(str::to_owned));
+                new.extend(new_line.map(str::to_owned));
+                index += 1;
+            }
+            if old.len() != header.old_count || new.len() != header.new_count {
+                return Err(Error::Tool(format!(
+                    "hunk at line {header_line} has {} old and {} new lines but its header declares {} and {}",
+                    old.len(),
+                    new.len(),
+                    header.old_count,
+                    header.new_count
+                )));
+            }
+            // "\ No newline at end of file" markers trail the last line.
+            while lines.get(index).is_some_and(|line| line.starts_with('\\')) {
+                index += 1;
+            }
+            let hint = if header.old_count == 0 {
+                header.old_start
+            } else {
+                header.old_start.saturating_sub(1)
+            };
+            hunks.push(Hunk::Lines {
+                hint: Some(hint),
+                old,
+                new,
+            });
+        }
+
+        let change = match (&old_path, &new_path) {
+            (None, Some(_)) => FileChange::Create(
+                hunks
+                    .iter()
+                    .flat_map(|hunk| match hunk {
+                        Hunk::Lines { new, .. } => new.clone(),
+                        Hunk::Text { .. } => Vec::new(),
+                    })
+                    .map(|line| line + "\n")
+                    .collect(),
+            ),
+            (Some(_), None) => FileChange::Delete,
+            (Some(_), Some(_)) => FileChange::Modify(hunks),
+            (None, None) => {
+                return Err(Error::Tool(
+                    "diff header has /dev/null on both sides".to_owned(),
+                ))
+            }
+        };
+        let Some(path) = new_path.or(old_path) else {
+            continue;
+        };
+        patches.push(FilePatch { path, change });
+    }
+    Ok(patches)
+}
+
+fn parse_search_replace(input: &str) -> Result<Vec<FilePatch>> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut current_path: Option<String> = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        if line.trim_end() != SEARCH_MARKER {
+            let trimmed = line.trim();
+            let candidate = trimmed.trim_matches('`').trim();
+            // A fence such as "```rust" between the path and the block is not a path.
+            if !trimmed.starts_with("```") && !candidate.is_empty() && !candidate.contains(' ') {
+                current_path = Some(candidate.to_owned());
+            }
+            index += 1;
+            continue;
+        }
+
+        let path = current_path.clone().ok_or_else(|| {
+            Error::Tool(format!(
+                "SEARCH block at line {} has no file path on the preceding line",
+                index + 1
+            ))
+        })?;
+        let mut search = Vec::new();
+        let mut replace = Vec::new();
+        index += 1;
+        while index < lines.len() && lines[index].trim_end() != DIVIDER_MARKER {
+            search.push(lines[index]);
+            index += 1;
+        }
+        index += 1;
+        while index < lines.len() && lines[index].trim_end() != REPLACE_MARKER {
+            replace.push(lines[index]);
+            index += 1;
+        }
+        if index >= lines.len() {
+            return Err(Error::Tool(format!(
+                "unterminated SEARCH/REPLACE block for '{path}'"
+            )));
+        }
+        index += 1;
+
+        let hunk = Hunk::Lines {
+            hint: None,
+            old: search.into_iter().map(str::to_owned).collect(),
+            new: replace.into_iter().map(str::to_owned).collect(),
+        };
+        match patches.iter_mut().find(|patch| patch.path == path) {
+            Some(FilePatch {
+                change: FileChange::Modify(hunks),
+                ..
+            }) => hunks.push(hunk),
+            _ => patches.push(FilePatch {
+                path,
+                change: FileChange::Modify(vec![hunk]),
+            }),
+        }
+    }
+    Ok(patches)
+}
+
+/// How a hunk was located in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Exact,
+    Offset,
+    Whitespace,
+}
+
+#[derive(Debug, Clone)]
+pub struct HunkFailure {
+    pub hunk: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ApplyStats {
+    pub hunks: usize,
+    pub fuzzy: usize,
+}
+
+/// Applies hunks to a file's content in memory. `None` content means the file
+/// does not exist; an empty-search text hunk then creates it.
+pub fn apply_hunks(
+    content: Option<&str>,
+    hunks: &[Hunk],
+) -> std::result::Result<(String, ApplyStats), Vec<HunkFailure>> {
+    let mut text = content.unwrap_or_default().to_owned();
+    let mut stats = ApplyStats::default();
+    let mut failures = Vec::new();
+    let mut delta: isize = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let outcome = match hunk {
+            Hunk::Lines { hint, old, new } => {
+                let expected = hint.map(|hint| hint.saturating_add_signed(delta));
+                apply_lines(&mut text, expected, old, new).inspect(|_| {
+                    delta += new.len() as isize - old.len() as isize;
+                })
+            }
+            Hunk::Text {
+                old,
+                new,
+                replace_all,
+            } => {
+                if old.is_empty() {
+                    if content.is_none() || text.is_empty() {
+                        text = new.clone();
+                        Ok(MatchKind::Exact)
+                    } else {
+                        Err("empty search text only applies to new or empty files".to_owned())
+                    }
+                } else if text.contains(old.as_str()) {
+                    text = if *replace_all {
+                        text.replace(old.as_str(), new)
+                    } else {
+                        text.replacen(old.as_str(), new, 1)
+                    };
+                    Ok(MatchKind::Exact)
+                } else {
+                    let old_lines = split_lines(old);
+                    let new_lines = split_lines(new);
+                    apply_lines(&mut text, None, &old_lines, &new_lines)
+                }
+            }
+        };
+        match outcome {
+            Ok(kind) => {
+                stats.hunks += 1;
+                if kind != MatchKind::Exact {
+                    stats.fuzzy += 1;
+                }
+            }
+            Err(message) => failures.push(HunkFailure {
+                hunk: index + 1,
+                message,
+            }),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok((text, stats))
+    } else {
+        Err(failures)
+    }
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::to_owned).collect()
+}
+
+fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Replaces `old` with `new` at the best location: exact at `expected`, exact
+/// elsewhere (nearest to `expected`), then whitespace-insensitive.
+fn apply_lines(
+    text: &mut String,
+    expected: Option<usize>,
+    old: &[String],
+    new: &[String],
+) -> std::result::Result<MatchKind, String> {
+    let eol = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines = text.lines().collect::<Vec<_>>();
+    let trailing_newline = text.is_empty() || text.ends_with('\n');
+
+    let position = if old.is_empty() {
+        let at = expected.unwrap_or(lines.len()).min(lines.len());
+        Some((at, MatchKind::Exact))
+    } else {
+        find_block(&lines, old, expected)
+    };
+    let Some((start, kind)) = position else {
+        return Err(diagnose(&lines, old, expected));
+    };
+
+    let mut result = lines[..start]
+        .iter()
+        .map(|line| (*line).to_owned())
+        .collect::<Vec<_>>();
+    result.extend(new.iter().cloned());
+    result.extend(
+        lines[start + old.len()..]
+            .iter()
+            .map(|line| (*line).to_owned()),
+    );
+
+    let mut joined = result.join(eol);
+    if trailing_newline && !result.is_empty() {
+        joined.push_str(eol);
+    }
+    *text = joined;
+    Ok(kind)
+}
+
+fn find_block(
+    lines: &[&str],
+    old: &[String],
+    expected: Option<usize>,
+) -> Option<(usize, MatchKind)> {
+    if old.len() > lines.len() {
+        return None;
+    }
+    let starts = 0..=lines.len() - old.len();
+    let exact = |start: usize| {
+        lines[start..start + old.len()]
+            .iter()
+            .zip(old)
+            .all(|(line, want)| *line == want.as_str())
+    };
+    if let Some(at) = expected.filter(|at| *at + old.len() <= lines.len()) {
+        if exact(at) {
+            return Some((at, MatchKind::Exact));
+        }
+    }
+    let distance = |start: &usize| expected.map_or(*start, |at| start.abs_diff(at));
+    if let Some(start) = starts
+        .clone()
+        .filter(|start| exact(*start))
+        .min_by_key(distance)
+    {
+        let kind = if expected.is_some() {
+            MatchKind::Offset
+        } else {
+            MatchKind::Exact
+        };
+        return Some((start, kind));
+    }
+
+    let wanted = old.iter().map(|line| normalize(line)).collect::<Vec<_>>();
+    starts
+        .filter(|start| {
+            lines[*start..*start + old.len()]
+                .iter()
+                .zip(&wanted)
+                .all(|(line, want)| normalize(line) == *want)
+        })
+        .min_by_key(distance)
+        .map(|start| (start, MatchKind::Whitespace))
+}
+
+/// Describes the closest candidate location for a hunk that did not match.
+fn diagnose(lines: &[&str], old: &[String], expected: Option<usize>) -> String {
+    if lines.is_empty() {
+        return "file is empty".to_owned();
+    }
+    let wanted = old.iter().map(|line| normalize(line)).collect::<Vec<_>>();
+    let window = old.len().min(lines.len());
+    let best = (0..=lines.len() - window)
+        .map(|start| {
+            let score = lines[start..start + window]
+                .iter()
+                .zip(&wanted)
+                .filter(|(line, want)| normalize(line) == **want)
+                .count();
+            (start, score)
+        })
+        .max_by_key(|(start, score)| {
+            (
+                *score,
+                std::cmp::Reverse(expected.map_or(*start, |at| start.abs_diff(at))),
+            )
+        });
+    let Some((start, score)) = best else {
+        return "no candidate location found".to_owned();
+    };
+    if score == 0 {
+        return format!(
+            "no line of the {}-line search block was found in the file",
+            old.len()
+        );
+    }
+
+    let similarity = score * 100 / old.len().max(1);
+    let mut message = format!(
+        "closest match at line {} ({similarity}% of lines match)",
+        start + 1
+    );
+    let mismatch = old.iter().enumerate().find(|(offset, want)| {
+        lines
+            .get(start + offset)
+            .is_none_or(|line| normalize(line) != normalize(want))
+    });
+    if let Some((offset, want)) = mismatch {
+        let found = lines
+            .get(start + offset)
+            .copied()
+            .unwrap_or("<end of file>");
+        message.push_str(&format!(
+            "; first difference at line {}: expected {:?}, found {:?}",
+            start + offset + 1,
+            want,
+            found
+        ));
+    }
+    message
+}
+
+/// Target state of one file after the patch.
+#[derive(Debug, Clone)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub original: Option<Vec<u8>>,
+    pub updated: Option<Vec<u8>>,
+}
+
+/// Writes every planned file or none: new contents are staged in temp files next
+/// to their targets and renamed into place; on any failure already-committed
+/// files are restored from `original` and created directories removed.
+pub fn commit(plan: &[PlannedFile]) -> Result<()> {
+    let mut created_dirs: Vec<PathBuf> = Vec::new();
+    let mut staged: Vec<(usize, PathBuf)> = Vec::new();
+
+    let cleanup = |staged: &[(usize, PathBuf)], created_dirs: &[PathBuf]| {
+        for (_, temp) in staged {
+            let _ = fs::remove_file(temp);
+        }
+        for dir in created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    };
+
+    for (index, file) in plan.iter().enumerate() {
+        let Some(bytes) = &file.updated else {
+            continue;
+        };
+        let staged_result = stage(&file.path, bytes, &mut created_dirs);
+        match staged_result {
+            Ok(temp) => staged.push((index, temp)),
+            Err(err) => {
+                cleanup(&staged, &created_dirs);
+                return Err(err);
+            }
+        }
+    }
+
+    let mut committed: Vec<usize> = Vec::new();
+    let mut failure = None;
+    for (index, file) in plan.iter().enumerate() {
+        let result = match &file.updated {
+            Some(_) => staged
+                .iter()
+                .find(|(staged_index, _)| *staged_index == index)
+                .map(|(_, temp)| fs::rename(temp, &file.path))
+                .unwrap_or(Ok(())),
+            None => fs::remove_file(&file.path),
+        };
+        match result {
+            Ok(()) => committed.push(index),
+            Err(err) => {
+                failure = Some(Error::Tool(format!(
+                    "failed to write '{}': {err}",
+                    file.path.display()
+                )));
+                break;
+            }
+        }
+    }
+
+    let Some(err) = failure else {
+        return Ok(());
+    };
+    for index in committed.into_iter().rev() {
+        let file = &plan[index];
+        let _ = match &file.original {
+            Some(bytes) => fs::write(&file.path, bytes),
+            None => fs::remove_file(&file.path),
+        };
+    }
+    cleanup(&staged, &created_dirs);
+    Err(err)
+}
+
+fn stage(path: &Path, bytes: &[u8], created_dirs: &mut Vec<PathBuf>) -> Result<PathBuf> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| Error::Tool(format!("'{}' has no parent directory", path.display())))?;
+    let mut missing = parent
+        .ancestors()
+        .take_while(|dir| !dir.exists())
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+    missing.reverse();
+    for dir in missing {
+        fs::create_dir(&dir)
+            .map_err(|err| Error::Tool(format!("failed to create '{}': {err}", dir.display())))?;
+        created_dirs.push(dir);
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = parent.join(format!(
+        ".{file_name}.patch-{}-{}",
+        std::process::id(),
+        uuid::Uuid::new_v4().simple()
+    ));
+    fs::write(&temp, bytes)
+        .map_err(|err| Error::Tool(format!("failed to stage '{}': {err}", path.display())))?;
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = fs::set_permissions(&temp, metadata.permissions());
+    }
+    Ok(temp)
+}
+
+/// Resolves parsed patches against the current disk state into a plan, merging
+/// repeated changes to the same file. All failures are reported together.
+pub fn plan(
+    patches: &[FilePatch],
+    resolve: impl Fn(&str) -> Result<PathBuf>,
+    max_file_bytes: u64,
+) -> Result<(Vec<PlannedFile>, BTreeMap<PathBuf, ApplyStats>)> {
+    let mut states: BTreeMap<PathBuf, (Option<Vec<u8>>, Option<String>)> = BTreeMap::new();
+    let mut stats: BTreeMap<PathBuf, ApplyStats> = BTreeMap::new();
+    let mut errors = Vec::new();
+
+    for patch in patches {
+        let path = resolve(&patch.path)?;
+        if !states.contains_key(&path) {
+            let original = match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => {
+                    return Err(Error::Tool(format!("'{}' is a directory", path.display())))
+                }
+                Ok(metadata) if metadata.len() > max_file_bytes => {
+                    return Err(Error::Tool(format!(
+                        "file '{}' is too large to patch safely ({} bytes, limit {max_file_bytes} bytes)",
+                        path.display(),
+                        metadata.len()
+                    )))
+                }
+                Ok(_) => Some(fs::read(&path).map_err(|err| {
+                    Error::Tool(format!("failed to read '{}': {err}", path.display()))
+                })?),
+                Err(_) => None,
+            };
+            let text = match &original {
+                Some(bytes) => Some(String::from_utf8(bytes.clone()).map_err(|_| {
+                    Error::Tool(format!("'{}' is not valid UTF-8", path.display()))
+                })?),
+                None => None,
+            };
+            states.insert(path.clone(), (original, text));
+        }
+        let Some((_, current)) = states.get_mut(&path) else {
+            continue;
+        };
+        let entry = stats.entry(path.clone()).or_default();
+
+        match &patch.change {
+            FileChange::Create(content) => {
+                if current.is_some() {
+                    errors.push(format!("{}: file already exists", patch.path));
+                } else {
+                    *current = Some(content.clone());
+                    entry.hunks += 1;
+                }
+            }
+            FileChange::Delete => {
+                if current.take().is_none() {
+                    errors.push(format!("{}: file to delete does not exist", patch.path));
+                } else {
+                    entry.hunks += 1;
+                }
+            }
+            FileChange::Modify(hunks) => {
+                let creates = hunks.iter().all(|hunk| match hunk {
+                    Hunk::Text { old, .. } => old.is_empty(),
+                    Hunk::Lines { hint, old, .. } => hint.is_none() && old.is_empty(),
+                });
+                if current.is_none() && !creates {
+                    errors.push(format!("{}: file does not exist", patch.path));
+                    continue;
+                }
+                match apply_hunks(current.as_deref(), hunks) {
+                    Ok((text, applied)) => {
+                        *current = Some(text);
+                        entry.hunks += applied.hunks;
+                        entry.fuzzy += applied.fuzzy;
+                    }
+                    Err(failures) => errors.extend(failures.into_iter().map(|failure| {
+                        format!(
+                            "{}: hunk {} failed: {}",
+                            patch.path, failure.hunk, failure.message
+                        )
+                    })),
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Tool(format!(
+            "patch not applied; no files were changed:\n{}",
+            errors.join("\n")
+        )));
+    }
+
+    let plan = states
+        .into_iter()
+        .filter_map(|(path, (original, updated))| {
+            let updated = updated.map(String::into_bytes);
+            (original != updated).then_some(PlannedFile {
+                path,
+                original,
+                updated,
+            })
+        })
+        .collect();
+    Ok((plan, stats))
+}
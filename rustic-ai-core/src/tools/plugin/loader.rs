@@ -1,11 +1,13 @@
-use super::manifest::PluginManifest;
+use super::manifest::{PluginManifest, PluginMode, PLUGIN_API_V1, PLUGIN_API_V2};
+use super::rpc::RpcPlugin;
 use crate::config::schema::{PermissionMode, PluginConfig, ToolConfig};
 use crate::error::{Error, Result};
 use crate::events::Event;
+use crate::sandbox::{Sandbox, SandboxPolicy};
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
@@ -22,10 +24,18 @@ pub struct LoadedPlugin {
     pub config: ToolConfig,
 }
 
+#[derive(Default)]
+pub struct LoadedPlugins {
+    pub tools: Vec<LoadedPlugin>,
+    /// `mode: rpc` plugins, whose tools are listed once their process runs.
+    pub rpc: Vec<RpcPlugin>,
+}
+
 #[derive(Debug, Clone)]
 struct ExternalPluginTool {
     manifest: PluginManifest,
     manifest_path: PathBuf,
+    sandbox: Sandbox,
 }
 
 impl ExternalPluginTool {
@@ -105,6 +115,10 @@ impl ExternalPluginTool {
         if !self.manifest.env.is_empty() {
             cmd.envs(&self.manifest.env);
         }
+        self.sandbox.apply_tokio(
+            &mut cmd,
+            &SandboxPolicy::for_agent(&context.working_directory, context.agent_permission_mode),
+        )?;
 
         let mut child = cmd.spawn().map_err(|err| {
             Error::Tool(format!(
@@ -347,9 +361,10 @@ impl PluginLoader {
             ))
         })?;
 
-        if manifest.api_version.trim() != "rustic-ai-plugin/v1" {
+        let api_version = manifest.api_version.trim();
+        if api_version != PLUGIN_API_V1 && api_version != PLUGIN_API_V2 {
             return Err(Error::Tool(format!(
-                "plugin manifest '{}' has unsupported api_version '{}'; expected 'rustic-ai-plugin/v1'",
+                "plugin manifest '{}' has unsupported api_version '{}'; expected '{PLUGIN_API_V1}' or '{PLUGIN_API_V2}'",
                 path.display(),
                 manifest.api_version
             )));
@@ -360,7 +375,20 @@ impl PluginLoader {
                 path.display()
             )));
         }
-        if manifest.tool_name.trim().is_empty() {
+        if api_version == PLUGIN_API_V2 {
+            if manifest
+                .component
+                .as_deref()
+                .is_none_or(|component| component.trim().is_empty())
+            {
+                return Err(Error::Tool(format!(
+                    "plugin manifest '{}' must define non-empty component",
+                    path.display()
+                )));
+            }
+            return Ok(manifest);
+        }
+        if manifest.mode == PluginMode::Process && manifest.tool_name.trim().is_empty() {
             return Err(Error::Tool(format!(
                 "plugin manifest '{}' must define non-empty tool_name",
                 path.display()
@@ -376,12 +404,19 @@ impl PluginLoader {
         Ok(manifest)
     }
 
+    /// The `tools` entry named after the plugin when there is one, so grants
+    /// and permissions can be set there; otherwise defaults from the manifest.
     fn tool_config_for_manifest(
+        name: &str,
         manifest: &PluginManifest,
+        configured: &HashMap<String, ToolConfig>,
         default_permission: PermissionMode,
     ) -> ToolConfig {
+        if let Some(config) = configured.get(name) {
+            return config.clone();
+        }
         ToolConfig {
-            name: manifest.tool_name.clone(),
+            name: name.to_owned(),
             enabled: manifest.enabled,
             permission_mode: default_permission,
             timeout_seconds: manifest.timeout_seconds.unwrap_or(60).max(1),
@@ -394,18 +429,63 @@ impl PluginLoader {
             require_sudo: false,
             privileged_command_patterns: Vec::new(),
             read_only_blocked_patterns: Vec::new(),
+            container_cpus: None,
+            container_memory: None,
+            container_network: None,
+            ssh_config_file: None,
+            wasm_readable_paths: Vec::new(),
+            wasm_writable_paths: Vec::new(),
+            wasm_env: Vec::new(),
+            wasm_network: false,
+            wasm_fuel: None,
+            wasm_max_memory_mb: None,
             taxonomy_membership: Vec::new(),
         }
     }
 
+    #[cfg(feature = "plugin-wasm")]
+    fn load_wasm_plugin(
+        manifest: &PluginManifest,
+        manifest_path: &Path,
+        configured: &HashMap<String, ToolConfig>,
+        default_permission: PermissionMode,
+    ) -> Result<LoadedPlugin> {
+        let mut tool = super::wasm::WasmPluginTool::load(manifest, manifest_path)?;
+        let name = tool.name().to_owned();
+        let config =
+            Self::tool_config_for_manifest(&name, manifest, configured, default_permission);
+        tool.config = config.clone();
+        Ok(LoadedPlugin {
+            name,
+            tool: Arc::new(tool),
+            config,
+        })
+    }
+
+    #[cfg(not(feature = "plugin-wasm"))]
+    fn load_wasm_plugin(
+        _manifest: &PluginManifest,
+        _manifest_path: &Path,
+        _configured: &HashMap<String, ToolConfig>,
+        _default_permission: PermissionMode,
+    ) -> Result<LoadedPlugin> {
+        Err(Error::Tool(format!(
+            "{PLUGIN_API_V2} plugins need rustic-ai built with the 'plugin-wasm' feature"
+        )))
+    }
+
+    /// `configured` holds the enabled `tools` entries; one named after a
+    /// plugin overrides the defaults derived from its manifest.
     pub fn load_plugins(
         config: &PluginConfig,
         execution_context: &ToolExecutionContext,
+        configured: &HashMap<String, ToolConfig>,
         default_permission: PermissionMode,
-    ) -> Result<Vec<LoadedPlugin>> {
+        sandbox: &Sandbox,
+    ) -> Result<LoadedPlugins> {
         let manifest_files =
             Self::discover_manifest_files(config, &execution_context.working_directory);
-        let mut loaded = Vec::new();
+        let mut loaded = LoadedPlugins::default();
 
         for manifest_path in manifest_files {
             let manifest = match Self::read_manifest(&manifest_path) {
@@ -420,14 +500,54 @@ impl PluginLoader {
                 continue;
             }
 
+            if manifest.api_version.trim() == PLUGIN_API_V2 {
+                match Self::load_wasm_plugin(
+                    &manifest,
+                    &manifest_path,
+                    configured,
+                    default_permission,
+                ) {
+                    Ok(plugin) => loaded.tools.push(plugin),
+                    Err(err) => {
+                        tracing::warn!(%err, path = %manifest_path.display(), "skipping WebAssembly plugin");
+                    }
+                }
+                continue;
+            }
+
+            if manifest.mode == PluginMode::Rpc {
+                if loaded
+                    .rpc
+                    .iter()
+                    .any(|plugin| plugin.name == manifest.plugin_name)
+                {
+                    tracing::warn!(plugin = manifest.plugin_name, path = %manifest_path.display(), "skipping duplicate rpc plugin");
+                    continue;
+                }
+                let defaults = Self::tool_config_for_manifest(
+                    "",
+                    &manifest,
+                    &HashMap::new(),
+                    default_permission,
+                );
+                loaded.rpc.push(RpcPlugin::from_manifest(
+                    &manifest,
+                    &manifest_path,
+                    defaults,
+                ));
+                continue;
+            }
+
             let name = manifest.tool_name.clone();
             let tool = Arc::new(ExternalPluginTool {
                 manifest: manifest.clone(),
                 manifest_path: manifest_path.clone(),
+                sandbox: sandbox.clone(),
             }) as Arc<dyn Tool>;
-            let tool_config = Self::tool_config_for_manifest(&manifest, default_permission);
+            let tool_config =
+                Self::tool_config_for_manifest(&name, &manifest, configured, default_permission);
 
-            loaded.push(LoadedPlugin {
+            loaded.tools.push(LoadedPlugin {
                 name,
                 tool,
                 config: tool_config,
@@ -2,6 +2,23 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+/// External command plugins.
+pub const PLUGIN_API_V1: &str = "rustic-ai-plugin/v1";
+/// WebAssembly component plugins.
+pub const PLUGIN_API_V2: &str = "rustic-ai-plugin/v2";
+
+/// How a `rustic-ai-plugin/v1` command is run.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginMode {
+    /// One process per call: JSON on stdin, output on stdout.
+    #[default]
+    Process,
+    /// One long-lived process speaking JSON-RPC over stdio that may
+    /// advertise several tools.
+    Rpc,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct PluginManifest {
@@ -11,30 +28,39 @@ pub struct PluginManifest {
     pub version: String,
     pub tool_name: String,
     pub description: String,
+    /// v1 only: executable run once per call.
     pub command: String,
     pub args: Vec<String>,
+    /// v2 only: path to the `.wasm` component, relative to the manifest.
+    pub component: Option<String>,
     pub env: BTreeMap<String, String>,
     pub schema: Value,
     pub timeout_seconds: Option<u64>,
     pub working_directory: Option<String>,
     pub enabled: bool,
+    pub mode: PluginMode,
+    /// `rpc` only: time allowed for the `initialize` handshake.
+    pub startup_timeout_seconds: Option<u64>,
 }
 
 impl Default for PluginManifest {
     fn default() -> Self {
         Self {
-            api_version: "rustic-ai-plugin/v1".to_owned(),
+            api_version: PLUGIN_API_V1.to_owned(),
             plugin_name: String::new(),
             version: "0.0.0".to_owned(),
             tool_name: String::new(),
             description: String::new(),
             command: String::new(),
             args: Vec::new(),
+            component: None,
             env: BTreeMap::new(),
             schema: Value::Object(serde_json::Map::new()),
             timeout_seconds: None,
             working_directory: None,
             enabled: true,
+            mode: PluginMode::Process,
+            startup_timeout_seconds: None,
         }
     }
 }
@@ -1,4 +1,8 @@
 pub mod loader;
 pub mod manifest;
+pub mod rpc;
+#[cfg(feature = "plugin-wasm")]
+mod wasm;
 
-pub use loader::{LoadedPlugin, PluginLoader};
+pub use loader::{LoadedPlugin, LoadedPlugins, PluginLoader};
+pub use rpc::{RpcPlugin, RpcPluginTool};
@@ -0,0 +1,269 @@
+//! Long-lived `mode: rpc` plugins.
+//!
+//! Each plugin is one process speaking newline-delimited JSON-RPC over stdio.
+//! Processes are managed by a [`McpConnectionManager`] of their own, so they
+//! follow the lifecycle of managed MCP servers: lazy start bounded by
+//! `startup_timeout_seconds`, restarts with backoff, stderr and state events,
+//! one process per sandbox policy and shutdown with the runtime.
+//!
+//! After `initialize`, the host calls `tools/list` (`{tools: [{name,
+//! description, inputSchema}]}`) and `tools/call` (`{name, arguments,
+//! streamId}` answered with `{output, success}`). While a call runs the
+//! plugin may send `tools/output` notifications (`{streamId, stdout,
+//! stderr}`); the host sends `notifications/cancelled` (`{requestId}`) when a
+//! call times out or is cancelled. `notifications/tools/list_changed`
+//! re-registers the plugin's tools.
+
+use super::manifest::{PluginManifest, PLUGIN_API_V1};
+use crate::config::schema::{McpServerConfig, McpTransportKind, ToolConfig};
+use crate::error::Result;
+use crate::events::Event;
+use crate::mcp::McpConnectionManager;
+use crate::tools::mcp::connect_for;
+use crate::tools::{Tool, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Prefix of the connection names of rpc plugins, as seen in MCP server
+/// events, e.g. `plugin.formatter`.
+pub const RPC_PLUGIN_SERVER_PREFIX: &str = "plugin.";
+/// Notification carrying streamed output of a running call.
+pub const RPC_OUTPUT_NOTIFICATION: &str = "tools/output";
+
+pub fn rpc_server_name(plugin: &str) -> String {
+    format!("{RPC_PLUGIN_SERVER_PREFIX}{plugin}")
+}
+
+/// An rpc plugin found during discovery. Its tools are only known once the
+/// process runs; see `ToolManager::refresh_plugin_tools`.
+#[derive(Debug, Clone)]
+pub struct RpcPlugin {
+    pub name: String,
+    pub server: McpServerConfig,
+    /// Config for advertised tools without a `tools` entry of their own.
+    pub defaults: ToolConfig,
+}
+
+impl RpcPlugin {
+    pub(super) fn from_manifest(
+        manifest: &PluginManifest,
+        manifest_path: &Path,
+        defaults: ToolConfig,
+    ) -> Self {
+        let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+        let resolve = |raw: &str| {
+            let path = Path::new(raw);
+            if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                manifest_dir.join(path)
+            }
+        };
+        let command = if manifest.command.contains(['/', '\\']) {
+            resolve(&manifest.command).display().to_string()
+        } else {
+            manifest.command.clone()
+        };
+        let working_directory = manifest
+            .working_directory
+            .as_deref()
+            .map(resolve)
+            .unwrap_or_else(|| manifest_dir.to_path_buf());
+        let server_defaults = McpServerConfig::default();
+        Self {
+            name: manifest.plugin_name.clone(),
+            server: McpServerConfig {
+                name: rpc_server_name(&manifest.plugin_name),
+                transport: McpTransportKind::Stdio,
+                command,
+                args: manifest.args.clone(),
+                env: manifest.env.clone(),
+                working_directory: Some(working_directory.display().to_string()),
+                startup_timeout_seconds: manifest
+                    .startup_timeout_seconds
+                    .unwrap_or(server_defaults.startup_timeout_seconds),
+                protocol_version: PLUGIN_API_V1.to_owned(),
+                sampling: false,
+                ..server_defaults
+            },
+            defaults,
+        }
+    }
+}
+
+/// One tool advertised by an rpc plugin.
+#[derive(Debug, Clone)]
+pub struct RpcPluginTool {
+    config: ToolConfig,
+    server: String,
+    description: String,
+    schema: Value,
+    connections: Arc<McpConnectionManager>,
+}
+
+impl RpcPluginTool {
+    /// Builds the tool from one `tools/list` entry; `config.name` must be the
+    /// advertised name.
+    pub fn from_listing(
+        config: ToolConfig,
+        plugin: &RpcPlugin,
+        listing: &Value,
+        connections: Arc<McpConnectionManager>,
+    ) -> Self {
+        let description = listing
+            .get("description")
+            .and_then(Value::as_str)
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| format!("Tool '{}' of plugin '{}'", config.name, plugin.name));
+        let schema = listing
+            .get("inputSchema")
+            .filter(|schema| schema.is_object())
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object"}));
+        Self {
+            config,
+            server: plugin.server.name.clone(),
+            description,
+            schema,
+            connections,
+        }
+    }
+
+    async fn call(
+        &self,
+        args: Value,
+        tx: &mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let client = connect_for(&self.connections, &self.server, context).await?;
+        let stream_id = uuid::Uuid::new_v4().to_string();
+        let mut notifications = self.connections.subscribe();
+        // Output notifications precede the response on the same stream, so
+        // draining the receiver before stopping loses nothing.
+        let done = CancellationToken::new();
+        let forward = {
+            let done = done.clone();
+            let server = self.server.clone();
+            let tool = self.config.name.clone();
+            let stream_id = stream_id.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    let received = tokio::select! {
+                        biased;
+                        received = notifications.recv() => received,
+                        _ = done.cancelled() => return,
+                    };
+                    let notification = match received {
+                        Ok(notification) => notification,
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return,
+                    };
+                    if notification.server != server
+                        || notification.method != RPC_OUTPUT_NOTIFICATION
+                        || notification.params.get("streamId").and_then(Value::as_str)
+                            != Some(stream_id.as_str())
+                    {
+                        continue;
+                    }
+                    let chunk = |key: &str| {
+                        notification
+                            .params
+                            .get(key)
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_owned()
+                    };
+                    let _ = tx.try_send(Event::ToolOutput {
+                        tool: tool.clone(),
+                        stdout_chunk: chunk("stdout"),
+                        stderr_chunk: chunk("stderr"),
+                    });
+                }
+            })
+        };
+
+        let arguments = if args.is_null() { json!({}) } else { args };
+        let called = client
+            .request_cancellable(
+                "tools/call",
+                json!({
+                    "name": self.config.name,
+                    "arguments": arguments,
+                    "streamId": stream_id
+                }),
+                Duration::from_secs(self.config.timeout_seconds.max(1)),
+                context.cancellation_token.as_ref(),
+            )
+            .await;
+        done.cancel();
+        let _ = forward.await;
+
+        let result = called?;
+        let success = result
+            .get("success")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let output = match result.get("output") {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        Ok(ToolResult {
+            success,
+            exit_code: Some(if success { 0 } else { 1 }),
+            output,
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for RpcPluginTool {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    async fn execute(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let (dummy_tx, _) = mpsc::channel(1);
+        self.stream_execute(args, dummy_tx, context).await
+    }
+
+    async fn stream_execute(
+        &self,
+        args: Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let _ = tx.try_send(Event::ToolStarted {
+            tool: self.config.name.clone(),
+            args: args.clone(),
+        });
+
+        let result = self.call(args, &tx, context).await;
+        let _ = tx.try_send(Event::ToolCompleted {
+            tool: self.config.name.clone(),
+            exit_code: result
+                .as_ref()
+                .ok()
+                .and_then(|value| value.exit_code)
+                .unwrap_or(1),
+        });
+
+        result
+    }
+}
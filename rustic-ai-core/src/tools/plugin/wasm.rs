@@ -0,0 +1,462 @@
+//! `rustic-ai-plugin/v2` tools backed by WebAssembly components.
+//!
+//! Components implement the `rustic-ai:plugin` world in `wit/plugin.wit`.
+//! Every call gets a fresh store, so no state leaks between calls. The
+//! component sees only the directories, environment variables and network
+//! access granted by its `ToolConfig`, and runs under fuel, memory and
+//! wall-clock limits.
+
+use super::manifest::PluginManifest;
+use crate::config::schema::{AgentPermissionMode, ToolConfig};
+use crate::error::{Error, Result};
+use crate::events::Event;
+use crate::tools::{Tool, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap, UpdateDeadline};
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::{DirPerms, FilePerms, IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+wasmtime::component::bindgen!({ path: "wit", world: "plugin" });
+
+const EPOCH_TICK: Duration = Duration::from_millis(50);
+const DEFAULT_FUEL: u64 = 10_000_000_000;
+const DEFAULT_MAX_MEMORY_MB: u64 = 256;
+/// Limits for reading the name, description and schema at load time.
+const DESCRIBE_FUEL: u64 = 100_000_000;
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+const STDIO_CAPTURE_BYTES: usize = 64 * 1024;
+
+/// One engine per process; a background thread advances its epoch so that
+/// stores can enforce wall-clock deadlines.
+fn engine() -> Result<&'static Engine> {
+    static ENGINE: OnceLock<std::result::Result<Engine, String>> = OnceLock::new();
+    ENGINE
+        .get_or_init(|| {
+            let mut config = Config::new();
+            config
+                .wasm_component_model(true)
+                .consume_fuel(true)
+                .epoch_interruption(true);
+            let engine = Engine::new(&config).map_err(|err| err.to_string())?;
+            let ticker = engine.clone();
+            std::thread::Builder::new()
+                .name("wasm-plugin-epoch".to_owned())
+                .spawn(move || loop {
+                    std::thread::sleep(EPOCH_TICK);
+                    ticker.increment_epoch();
+                })
+                .map_err(|err| err.to_string())?;
+            Ok(engine)
+        })
+        .as_ref()
+        .map_err(|err| Error::Tool(format!("failed to start WebAssembly engine: {err}")))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Interrupt {
+    Timeout,
+    Cancelled,
+}
+
+struct PluginState {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    limits: StoreLimits,
+    tool: String,
+    output: Option<mpsc::Sender<Event>>,
+    interrupt: Option<Interrupt>,
+}
+
+impl IoView for PluginState {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for PluginState {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl rustic_ai::plugin::host::Host for PluginState {
+    fn emit_output(&mut self, chunk: String) {
+        if let Some(tx) = &self.output {
+            let _ = tx.try_send(Event::ToolOutput {
+                tool: self.tool.clone(),
+                stdout_chunk: chunk,
+                stderr_chunk: String::new(),
+            });
+        }
+    }
+}
+
+/// What a single call may touch, resolved from the tool config and agent.
+#[derive(Debug, Clone)]
+struct Grants {
+    /// Host directory, guest path and whether it is writable.
+    dirs: Vec<(PathBuf, String, bool)>,
+    env: Vec<(String, String)>,
+    network: bool,
+    fuel: u64,
+    max_memory_bytes: usize,
+    timeout: Duration,
+}
+
+impl Grants {
+    fn describe() -> Self {
+        Self {
+            dirs: Vec::new(),
+            env: Vec::new(),
+            network: false,
+            fuel: DESCRIBE_FUEL,
+            max_memory_bytes: mib_to_bytes(DEFAULT_MAX_MEMORY_MB),
+            timeout: DESCRIBE_TIMEOUT,
+        }
+    }
+
+    fn for_call(
+        config: &ToolConfig,
+        manifest_env: &BTreeMap<String, String>,
+        context: &ToolExecutionContext,
+    ) -> Self {
+        let resolve = |raw: &String| {
+            let path = Path::new(raw.trim());
+            if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                context.working_directory.join(path)
+            }
+        };
+        let writable = context.agent_permission_mode == AgentPermissionMode::ReadWrite;
+        let mut dirs = config
+            .wasm_readable_paths
+            .iter()
+            .map(|raw| (resolve(raw), raw.trim().to_owned(), false))
+            .collect::<Vec<_>>();
+        dirs.extend(
+            config
+                .wasm_writable_paths
+                .iter()
+                .map(|raw| (resolve(raw), raw.trim().to_owned(), writable)),
+        );
+
+        let mut env = manifest_env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        env.extend(config.wasm_env.iter().filter_map(|name| {
+            std::env::var(name.trim())
+                .ok()
+                .map(|value| (name.trim().to_owned(), value))
+        }));
+
+        Self {
+            dirs,
+            env,
+            network: config.wasm_network,
+            fuel: config.wasm_fuel.unwrap_or(DEFAULT_FUEL),
+            max_memory_bytes: mib_to_bytes(
+                config.wasm_max_memory_mb.unwrap_or(DEFAULT_MAX_MEMORY_MB),
+            ),
+            timeout: Duration::from_secs(config.timeout_seconds.max(1)),
+        }
+    }
+}
+
+fn mib_to_bytes(mib: u64) -> usize {
+    usize::try_from(mib.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX)
+}
+
+/// A compiled component and the linker it is instantiated with.
+#[derive(Clone)]
+struct WasmComponent {
+    tool: String,
+    component: Component,
+    linker: Arc<Linker<PluginState>>,
+}
+
+impl WasmComponent {
+    fn load(path: &Path) -> Result<Self> {
+        let engine = engine()?;
+        let component = Component::from_file(engine, path).map_err(|err| {
+            Error::Tool(format!(
+                "failed to compile WebAssembly component '{}': {err:#}",
+                path.display()
+            ))
+        })?;
+        let mut linker = Linker::new(engine);
+        wasmtime_wasi::add_to_linker_sync(&mut linker)
+            .and_then(|_| Plugin::add_to_linker(&mut linker, |state: &mut PluginState| state))
+            .map_err(|err| Error::Tool(format!("failed to link plugin host functions: {err:#}")))?;
+        Ok(Self {
+            tool: path.display().to_string(),
+            component,
+            linker: Arc::new(linker),
+        })
+    }
+
+    /// Instantiates the component in a fresh store and runs `call` on it.
+    fn with_instance<R>(
+        &self,
+        grants: &Grants,
+        output: Option<mpsc::Sender<Event>>,
+        cancellation: Option<CancellationToken>,
+        call: impl FnOnce(&Plugin, &mut Store<PluginState>) -> wasmtime::Result<R>,
+    ) -> Result<R> {
+        let engine = engine()?;
+        let stdout = MemoryOutputPipe::new(STDIO_CAPTURE_BYTES);
+        let stderr = MemoryOutputPipe::new(STDIO_CAPTURE_BYTES);
+        let mut builder = WasiCtxBuilder::new();
+        builder
+            .stdout(stdout)
+            .stderr(stderr.clone())
+            .envs(&grants.env);
+        for (host, guest, writable) in &grants.dirs {
+            let (dir_perms, file_perms) = if *writable {
+                (DirPerms::all(), FilePerms::all())
+            } else {
+                (DirPerms::READ, FilePerms::READ)
+            };
+            builder
+                .preopened_dir(host, guest, dir_perms, file_perms)
+                .map_err(|err| {
+                    Error::Tool(format!(
+                        "plugin '{}' cannot open granted directory '{}': {err}",
+                        self.tool,
+                        host.display()
+                    ))
+                })?;
+        }
+        if grants.network {
+            builder.inherit_network().allow_ip_name_lookup(true);
+        }
+
+        let mut store = Store::new(
+            engine,
+            PluginState {
+                wasi: builder.build(),
+                table: ResourceTable::new(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(grants.max_memory_bytes)
+                    .trap_on_grow_failure(true)
+                    .build(),
+                tool: self.tool.clone(),
+                output,
+                interrupt: None,
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(grants.fuel)
+            .map_err(|err| Error::Tool(format!("failed to set plugin fuel: {err}")))?;
+        let deadline = Instant::now() + grants.timeout;
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(move |mut ctx| {
+            let interrupt = if cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                Interrupt::Cancelled
+            } else if Instant::now() >= deadline {
+                Interrupt::Timeout
+            } else {
+                return Ok(UpdateDeadline::Continue(1));
+            };
+            ctx.data_mut().interrupt = Some(interrupt);
+            Err(wasmtime::Error::msg("plugin interrupted"))
+        });
+
+        let result = Plugin::instantiate(&mut store, &self.component, &self.linker)
+            .and_then(|plugin| call(&plugin, &mut store));
+        result.map_err(|err| {
+            let stderr = String::from_utf8_lossy(&stderr.contents())
+                .trim()
+                .to_owned();
+            let detail = if stderr.is_empty() {
+                String::new()
+            } else {
+                format!("\nstderr:\n{stderr}")
+            };
+            match store.data().interrupt {
+                Some(Interrupt::Timeout) => Error::Tool(format!(
+                    "plugin '{}' timed out after {} seconds",
+                    self.tool,
+                    grants.timeout.as_secs()
+                )),
+                Some(Interrupt::Cancelled) => Error::Timeout(format!(
+                    "plugin '{}' cancelled by workflow timeout",
+                    self.tool
+                )),
+                None if err.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+                    Error::Tool(format!(
+                        "plugin '{}' ran out of fuel after {} units{detail}",
+                        self.tool, grants.fuel
+                    ))
+                }
+                None => Error::Tool(format!("plugin '{}' failed: {err:#}{detail}", self.tool)),
+            }
+        })
+    }
+}
+
+pub(super) struct WasmPluginTool {
+    name: String,
+    description: String,
+    schema: Value,
+    component: WasmComponent,
+    manifest_env: BTreeMap<String, String>,
+    pub(super) config: ToolConfig,
+}
+
+impl WasmPluginTool {
+    /// Compiles the manifest's component and reads its name, description and
+    /// schema; non-empty manifest fields take precedence.
+    pub(super) fn load(manifest: &PluginManifest, manifest_path: &Path) -> Result<Self> {
+        let raw = manifest.component.as_deref().unwrap_or_default();
+        let path = Path::new(raw);
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            manifest_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(path)
+        };
+        let mut component = WasmComponent::load(&path)?;
+        let (name, description, schema) =
+            component.with_instance(&Grants::describe(), None, None, |plugin, store| {
+                let tool = plugin.rustic_ai_plugin_tool();
+                Ok((
+                    tool.call_name(&mut *store)?,
+                    tool.call_description(&mut *store)?,
+                    tool.call_schema(&mut *store)?,
+                ))
+            })?;
+
+        let name = if manifest.tool_name.trim().is_empty() {
+            name
+        } else {
+            manifest.tool_name.clone()
+        };
+        if name.trim().is_empty() {
+            return Err(Error::Tool(format!(
+                "WebAssembly component '{}' reported an empty tool name",
+                path.display()
+            )));
+        }
+        let description = if manifest.description.trim().is_empty() {
+            description
+        } else {
+            manifest.description.clone()
+        };
+        let schema = if manifest
+            .schema
+            .as_object()
+            .is_some_and(|map| !map.is_empty())
+        {
+            manifest.schema.clone()
+        } else {
+            serde_json::from_str(&schema).map_err(|err| {
+                Error::Tool(format!(
+                    "WebAssembly component '{}' returned an invalid schema: {err}",
+                    path.display()
+                ))
+            })?
+        };
+        component.tool = name.clone();
+
+        Ok(Self {
+            name,
+            description,
+            schema,
+            component,
+            manifest_env: manifest.env.clone(),
+            config: ToolConfig::default(),
+        })
+    }
+
+    async fn run_plugin(
+        &self,
+        args: Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let grants = Grants::for_call(&self.config, &self.manifest_env, context);
+        let component = self.component.clone();
+        let cancellation = context.cancellation_token.clone();
+        let args = args.to_string();
+        let outcome = tokio::task::spawn_blocking(move || {
+            component.with_instance(&grants, Some(tx), cancellation, |plugin, store| {
+                plugin.rustic_ai_plugin_tool().call_execute(store, &args)
+            })
+        })
+        .await
+        .map_err(|err| Error::Tool(format!("plugin task join error: {err}")))??;
+
+        Ok(match outcome {
+            Ok(output) => ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output,
+            },
+            Err(message) => ToolResult {
+                success: false,
+                exit_code: Some(1),
+                output: message,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for WasmPluginTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    async fn execute(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let (dummy_tx, _) = mpsc::channel(1);
+        self.stream_execute(args, dummy_tx, context).await
+    }
+
+    async fn stream_execute(
+        &self,
+        args: Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let _ = tx.try_send(Event::ToolStarted {
+            tool: self.name.clone(),
+            args: args.clone(),
+        });
+
+        let result = self.run_plugin(args, tx.clone(), context).await;
+        let _ = tx.try_send(Event::ToolCompleted {
+            tool: self.name.clone(),
+            exit_code: result
+                .as_ref()
+                .ok()
+                .and_then(|value| value.exit_code)
+                .unwrap_or(1),
+        });
+
+        result
+    }
+}
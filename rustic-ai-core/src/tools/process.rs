@@ -1,41 +1,93 @@
+use regex::Regex;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::process::Stdio;
-use std::sync::LazyLock;
-use tokio::process::{Child, Command};
-use tokio::sync::{mpsc, Mutex};
-use tokio::time::{timeout, Duration};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::Duration;
 
 use crate::config::schema::{AgentPermissionMode, ToolConfig};
 use crate::error::{Error, Result};
 use crate::events::Event;
+use crate::tools::supervisor::{
+    LogQuery, ProcessSpec, ProcessSupervisor, ReadinessProbe, RestartPolicy, DEFAULT_LOG_LINES,
+    MAX_LOG_LINES,
+};
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
 
-static PROCESS_TABLE: LazyLock<Mutex<HashMap<u32, Child>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+const DEFAULT_LOG_TAIL: usize = 100;
+const MAX_LOG_TAIL: usize = 2_000;
+const DEFAULT_MAX_RESTARTS: u32 = 5;
+const MAX_READY_TIMEOUT_SECS: u64 = 600;
 
 #[derive(Debug, Clone)]
 pub struct ProcessTool {
     config: ToolConfig,
+    supervisor: Arc<ProcessSupervisor>,
     schema: Value,
 }
 
 impl ProcessTool {
-    pub fn new(config: ToolConfig) -> Self {
+    pub fn new(config: ToolConfig, supervisor: Arc<ProcessSupervisor>) -> Self {
         let schema = json!({
             "type": "object",
             "properties": {
-                "action": { "type": "string", "enum": ["start", "status", "stop", "list"] },
+                "action": {
+                    "type": "string",
+                    "enum": ["start", "status", "stop", "list", "logs", "wait_ready"]
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Managed process name (defaults to the program name on start)"
+                },
                 "command": { "type": "string" },
                 "args": { "type": "array", "items": { "type": "string" } },
                 "working_dir": { "type": "string" },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" }
+                },
                 "pid": { "type": "integer", "minimum": 1 },
-                "timeout_seconds": { "type": "integer", "minimum": 1, "maximum": 120 }
+                "restart": {
+                    "type": "string",
+                    "enum": ["never", "on_failure", "always"],
+                    "description": "start: restart policy when the process exits"
+                },
+                "max_restarts": { "type": "integer", "minimum": 0, "maximum": 100 },
+                "log_lines": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_LOG_LINES,
+                    "description": "start: stdout/stderr lines kept in the ring buffer"
+                },
+                "ready": {
+                    "type": "object",
+                    "description": "Readiness probe; every given check must pass",
+                    "properties": {
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                        "host": { "type": "string" },
+                        "log_pattern": { "type": "string", "description": "Regex matched against output lines" },
+                        "http_url": { "type": "string" },
+                        "http_status": { "type": "integer", "minimum": 100, "maximum": 599 }
+                    }
+                },
+                "stream": { "type": "string", "enum": ["stdout", "stderr", "all"] },
+                "tail": { "type": "integer", "minimum": 1, "maximum": MAX_LOG_TAIL },
+                "since": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "logs: only lines after this cursor (from a previous logs call)"
+                },
+                "grep": { "type": "string", "description": "logs: regex filter" },
+                "timeout_seconds": { "type": "integer", "minimum": 1, "maximum": MAX_READY_TIMEOUT_SECS }
             },
             "required": ["action"]
         });
 
-        Self { config, schema }
+        Self {
+            config,
+            supervisor,
+            schema,
+        }
     }
 
     fn parse_action(args: &Value) -> Result<String> {
@@ -46,26 +98,37 @@ impl ProcessTool {
             .trim()
             .to_ascii_lowercase();
         match action.as_str() {
-            "start" | "status" | "stop" | "list" => Ok(action),
+            "start" | "status" | "stop" | "list" | "logs" | "wait_ready" => Ok(action),
             other => Err(Error::Tool(format!(
-                "unsupported process action '{other}' (expected start|status|stop|list)"
+                "unsupported process action '{other}' (expected start|status|stop|list|logs|wait_ready)"
             ))),
         }
     }
 
-    fn parse_timeout(&self, args: &Value) -> u64 {
-        args.get("timeout_seconds")
-            .and_then(Value::as_u64)
-            .unwrap_or(self.config.timeout_seconds)
-            .clamp(1, 120)
+    fn parse_timeout(&self, args: &Value, max: u64) -> Duration {
+        Duration::from_secs(
+            args.get("timeout_seconds")
+                .and_then(Value::as_u64)
+                .unwrap_or(self.config.timeout_seconds)
+                .clamp(1, max),
+        )
     }
 
-    fn parse_pid(args: &Value) -> Result<u32> {
-        let pid = args
-            .get("pid")
+    fn parse_pid(args: &Value) -> Result<Option<u32>> {
+        args.get("pid")
             .and_then(Value::as_u64)
-            .ok_or_else(|| Error::Tool("missing 'pid' argument".to_owned()))?;
-        u32::try_from(pid).map_err(|_| Error::Tool("pid is out of range".to_owned()))
+            .map(|pid| {
+                u32::try_from(pid).map_err(|_| Error::Tool("pid is out of range".to_owned()))
+            })
+            .transpose()
+    }
+
+    fn parse_name(args: &Value) -> Option<String> {
+        args.get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned)
     }
 
     fn parse_command(args: &Value) -> Result<String> {
@@ -90,6 +153,109 @@ impl ProcessTool {
             .unwrap_or_default()
     }
 
+    fn parse_env(args: &Value) -> Result<Vec<(String, String)>> {
+        let Some(env) = args.get("env") else {
+            return Ok(Vec::new());
+        };
+        let env = env
+            .as_object()
+            .ok_or_else(|| Error::Tool("'env' must be an object of strings".to_owned()))?;
+        env.iter()
+            .map(|(key, value)| {
+                value
+                    .as_str()
+                    .map(|value| (key.clone(), value.to_owned()))
+                    .ok_or_else(|| Error::Tool(format!("env value for '{key}' must be a string")))
+            })
+            .collect()
+    }
+
+    fn parse_regex(value: &str, field: &str) -> Result<Regex> {
+        Regex::new(value).map_err(|err| Error::Tool(format!("invalid '{field}' regex: {err}")))
+    }
+
+    fn parse_readiness(args: &Value) -> Result<Option<ReadinessProbe>> {
+        let Some(ready) = args.get("ready") else {
+            return Ok(None);
+        };
+        let mut probe = ReadinessProbe::default();
+        if let Some(port) = ready.get("port").and_then(Value::as_u64) {
+            probe.port = Some(
+                u16::try_from(port)
+                    .ok()
+                    .filter(|port| *port > 0)
+                    .ok_or_else(|| Error::Tool("ready.port is out of range".to_owned()))?,
+            );
+        }
+        if let Some(host) = ready.get("host").and_then(Value::as_str) {
+            if !host.trim().is_empty() {
+                probe.host = host.trim().to_owned();
+            }
+        }
+        if let Some(pattern) = ready.get("log_pattern").and_then(Value::as_str) {
+            probe.log_pattern = Some(Self::parse_regex(pattern, "ready.log_pattern")?);
+        }
+        if let Some(url) = ready.get("http_url").and_then(Value::as_str) {
+            let url = url.trim();
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(Error::Tool(
+                    "ready.http_url must start with http:// or https://".to_owned(),
+                ));
+            }
+            probe.http_url = Some(url.to_owned());
+        }
+        if let Some(status) = ready.get("http_status").and_then(Value::as_u64) {
+            probe.http_status = u16::try_from(status)
+                .ok()
+                .filter(|status| (100..=599).contains(status))
+                .ok_or_else(|| Error::Tool("ready.http_status is out of range".to_owned()))?;
+        }
+        if probe.is_empty() {
+            return Err(Error::Tool(
+                "'ready' needs at least one of port, log_pattern or http_url".to_owned(),
+            ));
+        }
+        Ok(Some(probe))
+    }
+
+    fn resolve_working_dir(args: &Value, context: &ToolExecutionContext) -> PathBuf {
+        match args
+            .get("working_dir")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            Some(dir) if Path::new(dir).is_absolute() => PathBuf::from(dir),
+            Some(dir) => context.working_directory.join(dir),
+            None => context.working_directory.clone(),
+        }
+    }
+
+    /// Resolves the target from `name`, falling back to a managed `pid`.
+    async fn resolve_target(&self, args: &Value) -> Result<Option<String>> {
+        if let Some(name) = Self::parse_name(args) {
+            return Ok(Some(name));
+        }
+        match Self::parse_pid(args)? {
+            Some(pid) => Ok(self.supervisor.name_for_pid(pid).await),
+            None => Err(Error::Tool("missing 'name' or 'pid' argument".to_owned())),
+        }
+    }
+
+    fn unknown(action: &str, args: &Value) -> ToolResult {
+        ToolResult {
+            success: false,
+            exit_code: Some(1),
+            output: json!({
+                "action": action,
+                "name": Self::parse_name(args),
+                "pid": args.get("pid"),
+                "known": false,
+            })
+            .to_string(),
+        }
+    }
+
     fn enforce_write_permissions(action: &str, context: &ToolExecutionContext) -> Result<()> {
         if matches!(action, "start" | "stop")
             && context.agent_permission_mode == AgentPermissionMode::ReadOnly
@@ -100,6 +266,152 @@ impl ProcessTool {
         }
         Ok(())
     }
+
+    async fn start(
+        &self,
+        args: &Value,
+        tx: &mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<ToolResult> {
+        let command = Self::parse_command(args)?;
+        let command_args = Self::parse_args(args);
+        let restart = args
+            .get("restart")
+            .and_then(Value::as_str)
+            .map(RestartPolicy::parse)
+            .transpose()?
+            .unwrap_or(RestartPolicy::Never);
+        let readiness = Self::parse_readiness(args)?;
+        let name = match Self::parse_name(args) {
+            Some(name) => name,
+            None => {
+                let program = Path::new(&command)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| command.clone());
+                self.supervisor.unique_name(&program).await?
+            }
+        };
+        let spec = ProcessSpec {
+            name: name.clone(),
+            command: command.clone(),
+            args: command_args,
+            working_dir: Self::resolve_working_dir(args, context),
+            env: Self::parse_env(args)?,
+            restart,
+            max_restarts: args
+                .get("max_restarts")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_MAX_RESTARTS, |value| value.min(100) as u32),
+            log_lines: args
+                .get("log_lines")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_LOG_LINES, |value| value as usize)
+                .clamp(1, MAX_LOG_LINES),
+            readiness: readiness.clone(),
+        };
+
+        let grace = self.parse_timeout(args, 120).min(Duration::from_secs(10));
+        let status = self
+            .supervisor
+            .start(spec, context.session_id, grace)
+            .await?;
+        let _ = tx.try_send(Event::ToolOutput {
+            tool: self.config.name.clone(),
+            stdout_chunk: format!(
+                "started process '{name}' pid={}\n",
+                status.pid.map(|pid| pid.to_string()).unwrap_or_default()
+            ),
+            stderr_chunk: String::new(),
+        });
+
+        let Some(probe) = readiness else {
+            return Ok(ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output: json!({ "action": "start", "process": status }).to_string(),
+            });
+        };
+
+        let outcome = self
+            .supervisor
+            .wait_ready(
+                &name,
+                Some(probe.clone()),
+                self.parse_timeout(args, MAX_READY_TIMEOUT_SECS),
+            )
+            .await?;
+        let mut output = json!({
+            "action": "start",
+            "process": self.supervisor.status(&name).await,
+            "readiness": probe.describe(),
+            "ready": outcome,
+        });
+        if !outcome.ready {
+            output["recent_logs"] = self.recent_logs(&name).await;
+        }
+        Ok(ToolResult {
+            success: outcome.ready,
+            exit_code: Some(if outcome.ready { 0 } else { 1 }),
+            output: output.to_string(),
+        })
+    }
+
+    async fn recent_logs(&self, name: &str) -> Value {
+        let query = LogQuery {
+            tail: 20,
+            ..LogQuery::default()
+        };
+        self.supervisor
+            .logs(name, &query)
+            .await
+            .map(|page| json!(page.lines))
+            .unwrap_or(Value::Null)
+    }
+
+    async fn logs(&self, args: &Value) -> Result<ToolResult> {
+        let Some(name) = self.resolve_target(args).await? else {
+            return Ok(Self::unknown("logs", args));
+        };
+        let stream = match args.get("stream").and_then(Value::as_str).map(str::trim) {
+            None | Some("all") | Some("") => None,
+            Some(stream @ ("stdout" | "stderr")) => Some(stream.to_owned()),
+            Some(other) => {
+                return Err(Error::Tool(format!(
+                    "unsupported log stream '{other}' (expected stdout|stderr|all)"
+                )))
+            }
+        };
+        let query = LogQuery {
+            stream,
+            since: args.get("since").and_then(Value::as_u64),
+            grep: args
+                .get("grep")
+                .and_then(Value::as_str)
+                .map(|pattern| Self::parse_regex(pattern, "grep"))
+                .transpose()?,
+            tail: args
+                .get("tail")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_LOG_TAIL, |value| value as usize)
+                .clamp(1, MAX_LOG_TAIL),
+        };
+        let Some(page) = self.supervisor.logs(&name, &query).await else {
+            return Ok(Self::unknown("logs", args));
+        };
+        Ok(ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({
+                "action": "logs",
+                "name": name,
+                "lines": page.lines,
+                "cursor": page.cursor,
+                "truncated": page.truncated,
+            })
+            .to_string(),
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -109,7 +421,7 @@ impl Tool for ProcessTool {
     }
 
     fn description(&self) -> &str {
-        "Start, inspect, and stop background processes"
+        "Start, inspect, tail logs of, wait on, and stop managed background processes"
     }
 
     fn schema(&self) -> &Value {
@@ -131,147 +443,72 @@ impl Tool for ProcessTool {
         Self::enforce_write_permissions(action.as_str(), context)?;
 
         match action.as_str() {
-            "start" => {
-                let command = Self::parse_command(&args)?;
-                let command_args = Self::parse_args(&args);
-                let mut cmd = Command::new(&command);
-                cmd.args(&command_args);
-                cmd.stdin(Stdio::null());
-                cmd.stdout(Stdio::null());
-                cmd.stderr(Stdio::null());
-
-                if let Some(custom_dir) = args.get("working_dir").and_then(Value::as_str) {
-                    if !custom_dir.trim().is_empty() {
-                        cmd.current_dir(custom_dir);
-                    }
-                } else {
-                    cmd.current_dir(&context.working_directory);
-                }
-
-                let child = cmd.spawn().map_err(|err| {
-                    Error::Tool(format!("failed to start process '{command}': {err}"))
-                })?;
-                let pid = child
-                    .id()
-                    .ok_or_else(|| Error::Tool("started process has no pid".to_owned()))?;
-                PROCESS_TABLE.lock().await.insert(pid, child);
-
-                let _ = tx.try_send(Event::ToolOutput {
-                    tool: self.config.name.clone(),
-                    stdout_chunk: format!("started process pid={pid}\n"),
-                    stderr_chunk: String::new(),
-                });
-
+            "start" => self.start(&args, &tx, context).await,
+            "status" => {
+                let Some(name) = self.resolve_target(&args).await? else {
+                    return Ok(Self::unknown("status", &args));
+                };
+                let Some(status) = self.supervisor.status(&name).await else {
+                    return Ok(Self::unknown("status", &args));
+                };
                 Ok(ToolResult {
                     success: true,
                     exit_code: Some(0),
-                    output: json!({
-                        "action": "start",
-                        "pid": pid,
-                        "command": command,
-                        "args": command_args,
-                    })
-                    .to_string(),
+                    output: json!({ "action": "status", "known": true, "process": status })
+                        .to_string(),
                 })
             }
-            "status" => {
-                let pid = Self::parse_pid(&args)?;
-                let mut table = PROCESS_TABLE.lock().await;
-                let Some(child) = table.get_mut(&pid) else {
-                    return Ok(ToolResult {
-                        success: false,
-                        exit_code: Some(1),
-                        output: json!({"action":"status","pid":pid,"running":false,"known":false})
-                            .to_string(),
-                    });
+            "stop" => {
+                let Some(name) = self.resolve_target(&args).await? else {
+                    return Ok(Self::unknown("stop", &args));
+                };
+                let Some(status) = self.supervisor.stop(&name).await else {
+                    return Ok(Self::unknown("stop", &args));
                 };
-
-                let status = child
-                    .try_wait()
-                    .map_err(|err| Error::Tool(format!("failed to query process status: {err}")))?;
-                let running = status.is_none();
-                let exit_code = status.and_then(|value| value.code());
-                if !running {
-                    table.remove(&pid);
-                }
-
                 Ok(ToolResult {
                     success: true,
                     exit_code: Some(0),
                     output: json!({
-                        "action": "status",
-                        "pid": pid,
-                        "running": running,
+                        "action": "stop",
                         "known": true,
-                        "exit_code": exit_code,
+                        "stopped": true,
+                        "process": status,
                     })
                     .to_string(),
                 })
             }
-            "stop" => {
-                let pid = Self::parse_pid(&args)?;
-                let timeout_seconds = self.parse_timeout(&args);
-
-                let mut table = PROCESS_TABLE.lock().await;
-                let Some(mut child) = table.remove(&pid) else {
-                    return Ok(ToolResult {
-                        success: false,
-                        exit_code: Some(1),
-                        output: json!({"action":"stop","pid":pid,"stopped":false,"known":false})
-                            .to_string(),
-                    });
+            "list" => Ok(ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output: json!({ "action": "list", "processes": self.supervisor.list().await })
+                    .to_string(),
+            }),
+            "logs" => self.logs(&args).await,
+            "wait_ready" => {
+                let Some(name) = self.resolve_target(&args).await? else {
+                    return Ok(Self::unknown("wait_ready", &args));
                 };
-
-                child
-                    .kill()
-                    .await
-                    .map_err(|err| Error::Tool(format!("failed to stop process {pid}: {err}")))?;
-
-                let _ = timeout(Duration::from_secs(timeout_seconds), child.wait())
-                    .await
-                    .map_err(|_| {
-                        Error::Timeout(format!("waiting for process {pid} exceeded timeout"))
-                    })
-                    .and_then(|result| {
-                        result.map_err(|err| {
-                            Error::Tool(format!("failed while waiting for process {pid}: {err}"))
-                        })
-                    })?;
-
-                Ok(ToolResult {
-                    success: true,
-                    exit_code: Some(0),
-                    output: json!({"action":"stop","pid":pid,"stopped":true,"known":true})
-                        .to_string(),
-                })
-            }
-            "list" => {
-                let mut table = PROCESS_TABLE.lock().await;
-                let mut entries = Vec::new();
-                let mut finished = Vec::new();
-                for (pid, child) in table.iter_mut() {
-                    let status = child.try_wait().map_err(|err| {
-                        Error::Tool(format!("failed to query process status: {err}"))
-                    })?;
-                    let running = status.is_none();
-                    let exit_code = status.and_then(|value| value.code());
-                    if !running {
-                        finished.push(*pid);
-                    }
-                    entries.push(json!({
-                        "pid": pid,
-                        "running": running,
-                        "exit_code": exit_code,
-                    }));
-                }
-                for pid in finished {
-                    table.remove(&pid);
+                let probe = Self::parse_readiness(&args)?;
+                let outcome = self
+                    .supervisor
+                    .wait_ready(
+                        &name,
+                        probe,
+                        self.parse_timeout(&args, MAX_READY_TIMEOUT_SECS),
+                    )
+                    .await?;
+                let mut output = json!({
+                    "action": "wait_ready",
+                    "name": name,
+                    "ready": outcome,
+                });
+                if !outcome.ready {
+                    output["recent_logs"] = self.recent_logs(&name).await;
                 }
-
                 Ok(ToolResult {
-                    success: true,
-                    exit_code: Some(0),
-                    output: json!({"action":"list","processes":entries}).to_string(),
+                    success: outcome.ready,
+                    exit_code: Some(if outcome.ready { 0 } else { 1 }),
+                    output: output.to_string(),
                 })
             }
             _ => Err(Error::Tool("unsupported process action".to_owned())),
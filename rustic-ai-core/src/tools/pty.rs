@@ -0,0 +1,405 @@
+//! Pseudo-terminal backed shell sessions. A session keeps one interactive shell
+//! alive so `cd`, exported variables and REPL state carry over between calls.
+
+use crate::error::{Error, Result};
+use regex::Regex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, Notify};
+use tokio_util::sync::CancellationToken;
+
+const PTY_ROWS: u16 = 40;
+const PTY_COLS: u16 = 160;
+/// Unread terminal output kept per session; older bytes are dropped first.
+const PENDING_OUTPUT_LIMIT_BYTES: usize = 256 * 1024;
+
+static ANSI_ESCAPE: LazyLock<Option<Regex>> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[()][0-9A-Za-z]|\x1b[=>78cDEHM]")
+        .ok()
+});
+
+#[derive(Default)]
+struct PtyOutput {
+    pending: std::sync::Mutex<Vec<u8>>,
+    notify: Notify,
+    closed: AtomicBool,
+}
+
+impl PtyOutput {
+    fn push(&self, chunk: &[u8]) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.extend_from_slice(chunk);
+            let overflow = pending.len().saturating_sub(PENDING_OUTPUT_LIMIT_BYTES);
+            if overflow > 0 {
+                pending.drain(..overflow);
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    fn take(&self) -> Vec<u8> {
+        self.pending
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default()
+    }
+}
+
+/// What a read collected and why it stopped.
+#[derive(Debug, Clone)]
+pub struct PtyRead {
+    pub output: String,
+    /// No output arrived for the quiet period.
+    pub quiescent: bool,
+    /// The `until` pattern matched.
+    pub matched: bool,
+    /// The shell exited and the terminal closed.
+    pub exited: bool,
+}
+
+pub struct PtySession {
+    name: String,
+    child: Mutex<Child>,
+    writer: std::sync::Mutex<std::fs::File>,
+    output: Arc<PtyOutput>,
+    started_at: Instant,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+impl std::fmt::Debug for PtySession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PtySession")
+            .field("name", &self.name)
+            .field("started_at", &self.started_at)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PtySession {
+    /// Spawns `command` attached to a new pseudo-terminal as its controlling tty.
+    /// Working directory, environment and sandboxing must already be configured.
+    #[cfg(unix)]
+    pub fn spawn(name: &str, mut command: Command) -> Result<Self> {
+        use std::os::fd::{FromRawFd, OwnedFd};
+
+        let mut master = -1;
+        let mut slave = -1;
+        let mut size = libc::winsize {
+            ws_row: PTY_ROWS,
+            ws_col: PTY_COLS,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: out pointers are valid for writes; name and termios are optional.
+        let ret = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::addr_of_mut!(size),
+            )
+        };
+        if ret != 0 {
+            return Err(Error::Tool(format!(
+                "failed to allocate pseudo-terminal: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        // SAFETY: openpty returned two fresh descriptors we now own.
+        let (master, slave) =
+            unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+        let set_cloexec = |fd: &OwnedFd| {
+            use std::os::fd::AsRawFd;
+            // SAFETY: fcntl on a descriptor we own.
+            unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+        };
+        set_cloexec(&master);
+        set_cloexec(&slave);
+
+        let stdio = |fd: &OwnedFd| -> Result<std::process::Stdio> {
+            fd.try_clone()
+                .map(std::process::Stdio::from)
+                .map_err(|err| Error::Tool(format!("failed to duplicate pty descriptor: {err}")))
+        };
+        command
+            .stdin(stdio(&slave)?)
+            .stdout(stdio(&slave)?)
+            .stderr(stdio(&slave)?)
+            .kill_on_drop(true);
+        // SAFETY: setsid and ioctl are async-signal-safe; the hook touches no parent state.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command
+            .spawn()
+            .map_err(|err| Error::Tool(format!("failed to spawn shell session '{name}': {err}")))?;
+        drop(slave);
+
+        let writer = std::fs::File::from(master);
+        let mut reader = writer
+            .try_clone()
+            .map_err(|err| Error::Tool(format!("failed to duplicate pty descriptor: {err}")))?;
+        let output = Arc::new(PtyOutput::default());
+        let reader_output = output.clone();
+        std::thread::Builder::new()
+            .name(format!("pty-{name}"))
+            .spawn(move || {
+                use std::io::Read;
+                let mut buffer = [0u8; 4096];
+                loop {
+                    match reader.read(&mut buffer) {
+                        Ok(0) => break,
+                        Ok(read) => reader_output.push(&buffer[..read]),
+                        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                        // The master reports EIO once the last slave descriptor closes.
+                        Err(_) => break,
+                    }
+                }
+                reader_output.closed.store(true, Ordering::SeqCst);
+                reader_output.notify.notify_one();
+            })
+            .map_err(|err| Error::Tool(format!("failed to start pty reader: {err}")))?;
+
+        let now = Instant::now();
+        Ok(Self {
+            name: name.to_owned(),
+            child: Mutex::new(child),
+            writer: std::sync::Mutex::new(writer),
+            output,
+            started_at: now,
+            last_used: std::sync::Mutex::new(now),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn spawn(name: &str, _command: Command) -> Result<Self> {
+        Err(Error::Tool(format!(
+            "shell session '{name}' requires a pseudo-terminal, which is only supported on Unix"
+        )))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn idle(&self) -> Duration {
+        self.last_used
+            .lock()
+            .map(|last_used| last_used.elapsed())
+            .unwrap_or_default()
+    }
+
+    pub async fn pid(&self) -> Option<u32> {
+        self.child.lock().await.id()
+    }
+
+    pub async fn is_alive(&self) -> bool {
+        !self.output.closed.load(Ordering::SeqCst)
+            && matches!(self.child.lock().await.try_wait(), Ok(None))
+    }
+
+    /// Whether a job other than the session shell owns the terminal, as when a
+    /// command waits at a prompt or runs a REPL. The shell leads its own session,
+    /// so its pid is the process group it holds the terminal with.
+    #[cfg(unix)]
+    pub async fn job_in_foreground(&self) -> bool {
+        use std::os::fd::AsRawFd;
+        let Some(pid) = self.pid().await else {
+            return false;
+        };
+        let Ok(writer) = self.writer.lock() else {
+            return false;
+        };
+        // SAFETY: tcgetpgrp only queries the master descriptor we own.
+        let foreground = unsafe { libc::tcgetpgrp(writer.as_raw_fd()) };
+        u32::try_from(foreground).is_ok_and(|group| group > 0 && group != pid)
+    }
+
+    #[cfg(not(unix))]
+    pub async fn job_in_foreground(&self) -> bool {
+        false
+    }
+
+    /// Current working directory of the shell process, where the platform exposes it.
+    pub async fn cwd(&self) -> Option<std::path::PathBuf> {
+        let pid = self.pid().await?;
+        std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+    }
+
+    pub fn write(&self, bytes: &[u8]) -> Result<()> {
+        use std::io::Write;
+        if self.output.closed.load(Ordering::SeqCst) {
+            return Err(Error::Tool(format!(
+                "shell session '{}' has exited",
+                self.name
+            )));
+        }
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| Error::Tool("shell session writer lock poisoned".to_owned()))?;
+        writer
+            .write_all(bytes)
+            .and_then(|_| writer.flush())
+            .map_err(|err| {
+                Error::Tool(format!(
+                    "failed writing to shell session '{}': {err}",
+                    self.name
+                ))
+            })?;
+        if let Ok(mut last_used) = self.last_used.lock() {
+            *last_used = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Collects output until nothing arrives for `quiet`, `until` matches the
+    /// collected text, the shell exits, or `max_wait` elapses. Cancellation sends
+    /// Ctrl-C to the foreground program and fails the read.
+    pub async fn read_until_quiescent(
+        &self,
+        quiet: Duration,
+        max_wait: Duration,
+        until: Option<&Regex>,
+        cancellation_token: Option<&CancellationToken>,
+    ) -> Result<PtyRead> {
+        let deadline = tokio::time::Instant::now() + max_wait;
+        let mut collected = Vec::new();
+        let never = CancellationToken::new();
+        let cancellation_token = cancellation_token.unwrap_or(&never);
+
+        let (quiescent, matched) = loop {
+            collected.extend(self.output.take());
+            if let Some(pattern) = until {
+                if pattern.is_match(&clean_terminal_output(&collected)) {
+                    break (false, true);
+                }
+            }
+            if self.output.closed.load(Ordering::SeqCst) {
+                collected.extend(self.output.take());
+                break (false, false);
+            }
+
+            tokio::select! {
+                _ = self.output.notify.notified() => {}
+                _ = tokio::time::sleep(quiet) => break (true, false),
+                _ = tokio::time::sleep_until(deadline) => break (false, false),
+                _ = cancellation_token.cancelled() => {
+                    let _ = self.write(b"\x03");
+                    return Err(Error::Timeout("command cancelled by workflow timeout".to_owned()));
+                }
+            }
+        };
+
+        if let Ok(mut last_used) = self.last_used.lock() {
+            *last_used = Instant::now();
+        }
+        Ok(PtyRead {
+            output: clean_terminal_output(&collected),
+            quiescent,
+            matched,
+            exited: self.output.closed.load(Ordering::SeqCst),
+        })
+    }
+
+    pub async fn terminate(&self) {
+        let mut child = self.child.lock().await;
+        let _ = child.start_kill();
+        let _ = tokio::time::timeout(Duration::from_secs(2), child.wait()).await;
+    }
+}
+
+/// Strips ANSI escape sequences and normalises terminal line endings.
+pub fn clean_terminal_output(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    let text = match ANSI_ESCAPE.as_ref() {
+        Some(ansi_escape) => ansi_escape.replace_all(&text, ""),
+        None => text,
+    };
+    text.replace("\r\n", "\n").replace('\r', "")
+}
+
+/// Translates `send_keys` input: literal text with `<Enter>`, `<Tab>`, `<Esc>`,
+/// `<Backspace>`, `<Space>`, arrow keys (`<Up>`...) and `<C-x>` control chords.
+/// Unrecognised `<...>` tokens are sent literally.
+pub fn encode_keys(keys: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(keys.len());
+    let mut rest = keys;
+    while let Some(start) = rest.find('<') {
+        encoded.extend_from_slice(&rest.as_bytes()[..start]);
+        let candidate = &rest[start + 1..];
+        let token = candidate
+            .find(['<', '>'])
+            .filter(|end| candidate.as_bytes()[*end] == b'>')
+            .and_then(|end| named_key(&candidate[..end]).map(|bytes| (end, bytes)));
+        match token {
+            Some((end, bytes)) => {
+                encoded.extend_from_slice(&bytes);
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                encoded.push(b'<');
+                rest = candidate;
+            }
+        }
+    }
+    encoded.extend_from_slice(rest.as_bytes());
+    encoded
+}
+
+/// Whether `encoded` input would submit a line: carriage return, line feed
+/// or readline's accept-and-next (`C-o`).
+pub fn submits_line(encoded: &[u8]) -> bool {
+    encoded
+        .iter()
+        .any(|byte| matches!(byte, b'\r' | b'\n' | 0x0f))
+}
+
+fn named_key(token: &str) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match token.to_ascii_lowercase().as_str() {
+        "enter" | "cr" | "return" => b"\r",
+        "tab" => b"\t",
+        "esc" | "escape" => b"\x1b",
+        "backspace" | "bs" => b"\x7f",
+        "space" => b" ",
+        "up" => b"\x1b[A",
+        "down" => b"\x1b[B",
+        "right" => b"\x1b[C",
+        "left" => b"\x1b[D",
+        "home" => b"\x1b[H",
+        "end" => b"\x1b[F",
+        "pageup" => b"\x1b[5~",
+        "pagedown" => b"\x1b[6~",
+        "delete" | "del" => b"\x1b[3~",
+        "lt" => b"<",
+        lowered => {
+            let chord = lowered.strip_prefix("c-")?;
+            let mut chars = chord.chars();
+            let (Some(key), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            return match key {
+                'a'..='z' => Some(vec![key as u8 - b'a' + 1]),
+                '[' => Some(vec![0x1b]),
+                '\\' => Some(vec![0x1c]),
+                _ => None,
+            };
+        }
+    };
+    Some(bytes.to_vec())
+}
@@ -1,9 +1,11 @@
 use crate::config::schema::{ToolConfig, WorkingDirMode};
 use crate::error::{Error, Result};
 use crate::events::Event;
+use crate::sandbox::{Sandbox, SandboxPolicy};
+use crate::tools::pty::{encode_keys, submits_line, PtyRead, PtySession};
 use crate::tools::ToolExecutionContext;
 use async_trait::async_trait;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -19,6 +21,19 @@ const SAFE_PASSTHROUGH_ENV_VARS: &[&str] = &[
     "PATH", "HOME", "USER", "SHELL", "TMP", "TMPDIR", "TEMP", "LANG", "LC_ALL",
 ];
 const OUTPUT_CAPTURE_LIMIT_BYTES: usize = 10 * 1024;
+const MAX_SESSIONS_PER_OWNER: usize = 8;
+const DEFAULT_SESSION_QUIET_MS: u64 = 500;
+/// PTY sessions keyed by `<agent session>/<name>`, shared with the tool
+/// manager so they can be closed when the agent session ends.
+pub type ShellSessions = Mutex<HashMap<String, Arc<PtySession>>>;
+
+const SESSION_ACTIONS: &[&str] = &[
+    "session_run",
+    "send_keys",
+    "read",
+    "list_sessions",
+    "close_session",
+];
 const READ_ONLY_BLOCKED_TOKENS: &[&str] = &[
     " rm ",
     " rm-",
@@ -58,6 +73,7 @@ struct CommandExecutionInput<'a> {
     per_call_override: Option<&'a Path>,
     tool_name: &'a str,
     cancellation_token: Option<CancellationToken>,
+    sandbox_policy: SandboxPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -66,19 +82,47 @@ pub struct ShellTool {
     schema: serde_json::Value,
     sudo_cache_ttl_secs: u64,
     sudo_password_cache: Arc<Mutex<HashMap<String, SudoPasswordEntry>>>,
+    sandbox: Sandbox,
+    sessions: Arc<ShellSessions>,
 }
 
 impl ShellTool {
-    pub fn new(config: ToolConfig, sudo_cache_ttl_secs: u64) -> Self {
+    pub fn new(
+        config: ToolConfig,
+        sudo_cache_ttl_secs: u64,
+        sandbox: Sandbox,
+        sessions: Arc<ShellSessions>,
+    ) -> Self {
         let mut schema = json!({
             "type": "object",
             "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["exec", "session_run", "send_keys", "read", "list_sessions", "close_session"],
+                    "description": "exec (default) runs a one-off command; session_* actions drive a persistent PTY shell"
+                },
                 "command": {
                     "type": "string",
                     "description": "Shell command to execute"
+                },
+                "session": {
+                    "type": "string",
+                    "description": "Named shell session; cwd, environment and running programs persist between calls"
+                },
+                "keys": {
+                    "type": "string",
+                    "description": "send_keys input: literal text plus <Tab>, <Esc>, <Up>, <C-c> style tokens; <Enter> only answers a running program's prompt; shell commands are submitted with session_run"
+                },
+                "quiet_ms": {
+                    "type": "integer",
+                    "minimum": 50,
+                    "description": "Session reads return after this long without new output"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Session reads return once the output matches this regex"
                 }
-            },
-            "required": ["command"]
+            }
         });
 
         if config.enabled {
@@ -93,7 +137,33 @@ impl ShellTool {
             schema,
             sudo_cache_ttl_secs,
             sudo_password_cache: Arc::new(Mutex::new(HashMap::new())),
+            sandbox,
+            sessions,
+        }
+    }
+
+    /// Terminates every PTY session owned by `owner` (an agent session id),
+    /// returning their names.
+    pub async fn close_sessions_for(sessions: &ShellSessions, owner: &str) -> Vec<String> {
+        let prefix = format!("{owner}/");
+        let closed = {
+            let mut sessions = sessions.lock().await;
+            let keys = sessions
+                .keys()
+                .filter(|key| key.starts_with(&prefix))
+                .cloned()
+                .collect::<Vec<_>>();
+            keys.into_iter()
+                .filter_map(|key| sessions.remove(&key))
+                .collect::<Vec<_>>()
+        };
+        let mut names = Vec::with_capacity(closed.len());
+        for session in closed {
+            session.terminate().await;
+            names.push(session.name().to_owned());
         }
+        names.sort();
+        names
     }
 
     async fn resolve_working_dir(
@@ -217,6 +287,10 @@ impl ShellTool {
         if context.agent_permission_mode == crate::config::schema::AgentPermissionMode::ReadWrite {
             return Ok(());
         }
+        // Landlock makes the whole filesystem read-only, so token heuristics are redundant.
+        if self.sandbox.enforces_read_only() {
+            return Ok(());
+        }
 
         let normalized = format!(" {} ", command.to_ascii_lowercase());
         let configured_patterns = if self.config.read_only_blocked_patterns.is_empty() {
@@ -343,6 +417,290 @@ impl ShellTool {
         }
     }
 
+    fn session_owner(args: &Value, context: &ToolExecutionContext) -> String {
+        args.get("_session_id")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .or_else(|| context.session_id.map(|id| id.to_string()))
+            .unwrap_or_else(|| "default".to_owned())
+    }
+
+    fn session_name(args: &Value) -> Result<String> {
+        args.get("session")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::Tool("missing 'session' argument".to_owned()))
+    }
+
+    /// Keeps the tail of terminal output, which holds the most recent prompt.
+    fn tail_bounded(text: &str) -> String {
+        if text.len() <= OUTPUT_CAPTURE_LIMIT_BYTES {
+            return text.to_owned();
+        }
+        let mut start = text.len() - OUTPUT_CAPTURE_LIMIT_BYTES;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        text[start..].to_owned()
+    }
+
+    async fn start_session(
+        &self,
+        name: &str,
+        args: &Value,
+        context: &ToolExecutionContext,
+    ) -> Result<Arc<PtySession>> {
+        let per_call_override = args
+            .get("working_directory")
+            .and_then(Value::as_str)
+            .map(PathBuf::from);
+        let working_dir = self
+            .resolve_working_dir(&context.working_directory, per_call_override.as_deref())
+            .await?;
+
+        let (shell, shell_args): (&str, &[&str]) = if Path::new("/bin/bash").exists() {
+            ("/bin/bash", &["--noprofile", "--norc", "-i"])
+        } else {
+            ("/bin/sh", &["-i"])
+        };
+        let mut cmd = Command::new(shell);
+        cmd.args(shell_args).current_dir(&working_dir);
+        self.apply_environment(&mut cmd);
+        cmd.env("TERM", "xterm-256color").env("PS1", "$ ");
+        self.sandbox.apply_tokio(
+            &mut cmd,
+            &SandboxPolicy::for_agent(&context.working_directory, context.agent_permission_mode),
+        )?;
+
+        let session = Arc::new(PtySession::spawn(name, cmd)?);
+        // Swallow the startup banner and first prompt.
+        let _ = session
+            .read_until_quiescent(
+                Duration::from_millis(200),
+                Duration::from_secs(2),
+                None,
+                None,
+            )
+            .await;
+        Ok(session)
+    }
+
+    async fn session_for(
+        &self,
+        owner: &str,
+        name: &str,
+        create: bool,
+        args: &Value,
+        context: &ToolExecutionContext,
+    ) -> Result<Arc<PtySession>> {
+        let key = format!("{owner}/{name}");
+        let mut sessions = self.sessions.lock().await;
+        if let Some(session) = sessions.get(&key) {
+            if session.is_alive().await || !create {
+                return Ok(session.clone());
+            }
+            sessions.remove(&key);
+        }
+        if !create {
+            return Err(Error::NotFound(format!("shell session '{name}' not found")));
+        }
+
+        let prefix = format!("{owner}/");
+        let mut owned = 0usize;
+        let mut exited = Vec::new();
+        for (existing_key, session) in sessions.iter() {
+            if !existing_key.starts_with(&prefix) {
+                continue;
+            }
+            if session.is_alive().await {
+                owned += 1;
+            } else {
+                exited.push(existing_key.clone());
+            }
+        }
+        for existing_key in exited {
+            sessions.remove(&existing_key);
+        }
+        if owned >= MAX_SESSIONS_PER_OWNER {
+            return Err(Error::Tool(format!(
+                "shell session limit reached ({MAX_SESSIONS_PER_OWNER}); close a session first"
+            )));
+        }
+
+        let session = self.start_session(name, args, context).await?;
+        sessions.insert(key, session.clone());
+        Ok(session)
+    }
+
+    async fn read_session(
+        &self,
+        session: &PtySession,
+        args: &Value,
+        context: &ToolExecutionContext,
+    ) -> Result<PtyRead> {
+        let quiet = args
+            .get("quiet_ms")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_SESSION_QUIET_MS)
+            .max(50);
+        let until = args
+            .get("until")
+            .and_then(Value::as_str)
+            .map(|pattern| {
+                regex::Regex::new(pattern)
+                    .map_err(|err| Error::Tool(format!("invalid 'until' pattern: {err}")))
+            })
+            .transpose()?;
+        session
+            .read_until_quiescent(
+                Duration::from_millis(quiet),
+                Duration::from_secs(self.config.timeout_seconds.max(1)),
+                until.as_ref(),
+                context.cancellation_token.as_ref(),
+            )
+            .await
+    }
+
+    async fn run_session_action(
+        &self,
+        action: &str,
+        args: &Value,
+        tx: mpsc::Sender<Event>,
+        context: &ToolExecutionContext,
+    ) -> Result<super::ToolResult> {
+        if !SESSION_ACTIONS.contains(&action) {
+            return Err(Error::Tool(format!(
+                "unsupported shell action '{action}' (expected exec|{})",
+                SESSION_ACTIONS.join("|")
+            )));
+        }
+        let tool_name = self.config.name.clone();
+        let owner = Self::session_owner(args, context);
+
+        if action == "list_sessions" {
+            let prefix = format!("{owner}/");
+            let sessions = self.sessions.lock().await;
+            let mut listed = Vec::new();
+            for (key, session) in sessions.iter() {
+                if !key.starts_with(&prefix) {
+                    continue;
+                }
+                listed.push(json!({
+                    "session": session.name(),
+                    "alive": session.is_alive().await,
+                    "pid": session.pid().await,
+                    "cwd": session.cwd().await.map(|path| path.display().to_string()),
+                    "uptime_seconds": session.uptime().as_secs(),
+                    "idle_seconds": session.idle().as_secs(),
+                }));
+            }
+            listed.sort_by(|left, right| left["session"].as_str().cmp(&right["session"].as_str()));
+            return Ok(super::ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output: json!({ "action": action, "sessions": listed }).to_string(),
+            });
+        }
+
+        let name = Self::session_name(args)?;
+        if action == "close_session" {
+            let removed = self
+                .sessions
+                .lock()
+                .await
+                .remove(&format!("{owner}/{name}"));
+            if let Some(session) = &removed {
+                session.terminate().await;
+            }
+            return Ok(super::ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output: json!({ "action": action, "session": name, "closed": removed.is_some() })
+                    .to_string(),
+            });
+        }
+
+        let input = match action {
+            "session_run" => {
+                let command = args
+                    .get("command")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| Error::Tool("missing 'command' argument".to_owned()))?;
+                self.validate_command(command)?;
+                self.enforce_agent_permission(command, context)?;
+                if self.command_requires_sudo(command) {
+                    return Err(Error::Tool(
+                        "sudo is not supported in shell sessions; use action 'exec'".to_owned(),
+                    ));
+                }
+                // `C-e C-u` first discards text typed earlier with `send_keys`,
+                // so the submitted line is exactly the checked command.
+                Some(format!("\x05\x15{command}\r").into_bytes())
+            }
+            "send_keys" => {
+                let keys = args
+                    .get("keys")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| Error::Tool("missing 'keys' argument".to_owned()))?;
+                self.enforce_agent_permission(keys, context)?;
+                Some(encode_keys(keys))
+            }
+            _ => None,
+        };
+
+        let session = self
+            .session_for(&owner, &name, action == "session_run", args, context)
+            .await?;
+        // Lines typed at the session shell must go through `session_run`, which
+        // applies the command allow/deny lists and refuses sudo. Answers to a
+        // prompt or REPL started by a checked command may be submitted directly.
+        if action == "send_keys"
+            && input.as_deref().is_some_and(submits_line)
+            && !session.job_in_foreground().await
+        {
+            return Err(Error::Tool(
+                "send_keys cannot submit a line to the session shell; use action 'session_run' to run commands"
+                    .to_owned(),
+            ));
+        }
+        let _ = tx.try_send(Event::ToolStarted {
+            tool: tool_name.clone(),
+            args: Self::args_with_redacted_secrets(args),
+        });
+        if let Some(input) = input {
+            session.write(&input)?;
+        }
+        let read = self.read_session(&session, args, context).await?;
+        let output = Self::tail_bounded(&read.output);
+        if !output.is_empty() {
+            let _ = tx.try_send(Event::ToolOutput {
+                tool: tool_name.clone(),
+                stdout_chunk: output.clone(),
+                stderr_chunk: String::new(),
+            });
+        }
+        let _ = tx.try_send(Event::ToolCompleted {
+            tool: tool_name,
+            exit_code: 0,
+        });
+
+        Ok(super::ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({
+                "action": action,
+                "session": name,
+                "output": output,
+                "quiescent": read.quiescent,
+                "matched": read.matched,
+                "exited": read.exited,
+            })
+            .to_string(),
+        })
+    }
+
     async fn stream_command(
         &self,
         input: CommandExecutionInput<'_>,
@@ -355,12 +713,18 @@ impl ShellTool {
         let per_call_override = input.per_call_override;
         let tool_name = input.tool_name;
         let cancellation_token = input.cancellation_token;
+        let sandbox_policy = input.sandbox_policy;
 
         self.cleanup_expired_sudo_passwords().await;
         let working_dir = self
             .resolve_working_dir(work_dir, per_call_override)
             .await?;
         let requires_sudo = self.command_requires_sudo(command);
+        if requires_sudo && self.sandbox.is_enabled() {
+            return Err(Error::Tool(
+                "sudo is unavailable inside the sandbox".to_owned(),
+            ));
+        }
 
         let effective_password = if requires_sudo {
             if let Some(password) = sudo_password {
@@ -398,6 +762,7 @@ impl ShellTool {
         cmd.stdin(Stdio::piped());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
+        self.sandbox.apply_tokio(&mut cmd, &sandbox_policy)?;
 
         let mut child = cmd
             .spawn()
@@ -542,6 +907,10 @@ impl super::Tool for ShellTool {
         context: &ToolExecutionContext,
     ) -> Result<super::ToolResult> {
         let tool_name = self.name().to_owned();
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("exec");
+        if action != "exec" {
+            return self.run_session_action(action, &args, tx, context).await;
+        }
         let command = args
             .get("command")
             .and_then(|v| v.as_str())
@@ -574,6 +943,10 @@ impl super::Tool for ShellTool {
                     per_call_override: per_call_working_dir.as_deref(),
                     tool_name: &tool_name,
                     cancellation_token: context.cancellation_token.clone(),
+                    sandbox_policy: SandboxPolicy::for_agent(
+                        &context.working_directory,
+                        context.agent_permission_mode,
+                    ),
                 },
                 tx.clone(),
             )
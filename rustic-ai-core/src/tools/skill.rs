@@ -21,7 +21,15 @@ impl SkillTool {
             "type": "object",
             "properties": {
                 "skill": {"type": "string", "description": "Registered skill name"},
-                "input": {"type": "object", "description": "Skill input payload"}
+                "input": {"type": "object", "description": "Skill input payload"},
+                "resource": {
+                    "type": "string",
+                    "description": "Bundled file to read instead of invoking the skill"
+                },
+                "script": {
+                    "type": "string",
+                    "description": "Bundled script to run with 'input' instead of invoking the skill"
+                }
             },
             "required": ["skill"]
         });
@@ -40,7 +48,7 @@ impl Tool for SkillTool {
     }
 
     fn description(&self) -> &str {
-        "Execute a registered skill by name"
+        "Invoke a registered skill by name, or read one of its bundled resources or run one of its bundled scripts"
     }
 
     fn schema(&self) -> &Value {
@@ -77,15 +85,23 @@ impl Tool for SkillTool {
             args: args.clone(),
         });
 
-        let result = skill
-            .execute(
-                input,
-                &SkillExecutionContext {
-                    working_directory: context.working_directory.clone(),
-                    environment: Default::default(),
-                },
-            )
-            .await?;
+        let skill_context = SkillExecutionContext {
+            working_directory: context.working_directory.clone(),
+            environment: Default::default(),
+            agent_permission_mode: context.agent_permission_mode,
+        };
+        let resource = args.get("resource").and_then(Value::as_str);
+        let script = args.get("script").and_then(Value::as_str);
+        let result = match (resource, script) {
+            (Some(_), Some(_)) => {
+                return Err(Error::Tool(
+                    "'resource' and 'script' cannot be combined".to_owned(),
+                ));
+            }
+            (Some(resource), None) => skill.read_resource(resource).await?,
+            (None, Some(script)) => skill.run_script(script, input, &skill_context).await?,
+            (None, None) => skill.execute(input, &skill_context).await?,
+        };
 
         let _ = tx.try_send(Event::ToolCompleted {
             tool: self.config.name.clone(),
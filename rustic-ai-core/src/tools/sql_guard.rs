@@ -0,0 +1,334 @@
+//! Lexical SQL checks that keep read-only agents from changing data.
+//!
+//! The guard does not try to understand full SQL grammars. It tokenizes the
+//! input with the quoting and comment rules of the target dialect, then only
+//! admits statements that start with a read keyword and contain no write
+//! keyword or side-effecting function call anywhere. Anything it cannot lex
+//! confidently is rejected.
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+/// Keywords a read-only statement may start with.
+const READ_STATEMENTS: &[&str] = &[
+    "SELECT", "WITH", "VALUES", "TABLE", "SHOW", "EXPLAIN", "DESCRIBE", "DESC", "PRAGMA",
+];
+
+/// Keywords that make a statement write even after a read keyword: data
+/// modifying CTEs, `SELECT ... INTO`, `FOR UPDATE` and `EXPLAIN ANALYZE`
+/// (or PostgreSQL's `ANALYSE` spelling).
+const WRITE_KEYWORDS: &[&str] = &[
+    "INSERT", "UPDATE", "DELETE", "MERGE", "UPSERT", "INTO", "ANALYZE", "ANALYSE",
+];
+
+/// `REPLACE` is a write statement in MySQL/SQLite but also a string function.
+const REPLACE_KEYWORD: &str = "REPLACE";
+
+/// Functions (lower case) that write, sleep, lock or touch the server filesystem.
+const SIDE_EFFECT_FUNCTIONS: &[&str] = &[
+    "nextval",
+    "setval",
+    "set_config",
+    "pg_sleep",
+    "pg_terminate_backend",
+    "pg_cancel_backend",
+    "pg_reload_conf",
+    "pg_rotate_logfile",
+    "pg_advisory_lock",
+    "pg_advisory_xact_lock",
+    "pg_read_file",
+    "pg_read_binary_file",
+    "pg_ls_dir",
+    "lo_import",
+    "lo_export",
+    "lo_create",
+    "lo_unlink",
+    "lo_put",
+    "dblink",
+    "dblink_exec",
+    "sleep",
+    "benchmark",
+    "get_lock",
+    "load_file",
+    "load_extension",
+    "readfile",
+    "writefile",
+    "edit",
+];
+
+/// SQLite pragmas that only report state (when used without `=`).
+const READ_PRAGMAS: &[&str] = &[
+    "TABLE_INFO",
+    "TABLE_XINFO",
+    "TABLE_LIST",
+    "INDEX_LIST",
+    "INDEX_INFO",
+    "INDEX_XINFO",
+    "FOREIGN_KEY_LIST",
+    "FOREIGN_KEY_CHECK",
+    "DATABASE_LIST",
+    "COLLATION_LIST",
+    "FUNCTION_LIST",
+    "MODULE_LIST",
+    "PRAGMA_LIST",
+    "COMPILE_OPTIONS",
+    "INTEGRITY_CHECK",
+    "QUICK_CHECK",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Upper-cased bare word, or the original text of a quoted identifier.
+    Word {
+        text: String,
+        quoted: bool,
+    },
+    Symbol(char),
+    Literal,
+}
+
+/// Splits `sql` into statements, returning each one's trimmed source text.
+pub fn split_statements(sql: &str, dialect: SqlDialect) -> Result<Vec<String>> {
+    Ok(lex(sql, dialect, false)?
+        .into_iter()
+        .map(|(range, _)| sql[range].trim().to_owned())
+        .collect())
+}
+
+/// Returns the only statement in `sql`, rejecting empty input and batches.
+pub fn single_statement(sql: &str, dialect: SqlDialect) -> Result<String> {
+    let mut statements = split_statements(sql, dialect)?;
+    match statements.len() {
+        0 => Err(Error::Tool("'sql' contains no statement".to_owned())),
+        1 => Ok(statements.remove(0)),
+        count => Err(Error::Tool(format!(
+            "'sql' must contain a single statement (found {count})"
+        ))),
+    }
+}
+
+/// Rejects `sql` unless every statement in it is read-only.
+///
+/// Backslashes escape quotes in MySQL and PostgreSQL `E''` strings but not
+/// elsewhere, so the input is lexed both ways and must pass both.
+pub fn ensure_read_only(sql: &str, dialect: SqlDialect) -> Result<()> {
+    for backslash_escapes in [false, true] {
+        let statements = lex(sql, dialect, backslash_escapes)?;
+        if statements.is_empty() {
+            return Err(Error::Tool("'sql' contains no statement".to_owned()));
+        }
+        for (_, tokens) in &statements {
+            check_statement(tokens, dialect)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_statement(tokens: &[Token], dialect: SqlDialect) -> Result<()> {
+    let first = tokens.iter().find_map(|token| match token {
+        Token::Symbol('(') => None,
+        Token::Word {
+            text,
+            quoted: false,
+        } => Some(Some(text.as_str())),
+        _ => Some(None),
+    });
+    let Some(Some(first)) = first else {
+        return Err(Error::Tool(
+            "read-only agents may only run statements that start with SELECT, WITH, VALUES, SHOW, EXPLAIN or DESCRIBE".to_owned(),
+        ));
+    };
+    if !READ_STATEMENTS.contains(&first) || (first == "PRAGMA" && dialect != SqlDialect::Sqlite) {
+        return Err(Error::Tool(format!(
+            "read-only agents may not run {first} statements"
+        )));
+    }
+    if first == "PRAGMA" {
+        check_pragma(tokens)?;
+    }
+
+    for (index, token) in tokens.iter().enumerate() {
+        let Token::Word { text, quoted } = token else {
+            continue;
+        };
+        let is_call = matches!(tokens.get(index + 1), Some(Token::Symbol('(')));
+        if !quoted
+            && (WRITE_KEYWORDS.contains(&text.as_str()) || (text == REPLACE_KEYWORD && !is_call))
+        {
+            return Err(Error::Tool(format!(
+                "read-only agents may not run statements containing {text}"
+            )));
+        }
+        if is_call && SIDE_EFFECT_FUNCTIONS.contains(&text.to_ascii_lowercase().as_str()) {
+            return Err(Error::Tool(format!(
+                "read-only agents may not call {}()",
+                text.to_ascii_lowercase()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_pragma(tokens: &[Token]) -> Result<()> {
+    let words = tokens
+        .iter()
+        .skip(1)
+        .take(3)
+        .map(|token| match token {
+            Token::Word { text, .. } => Some(text.to_ascii_uppercase()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // `PRAGMA name` or `PRAGMA schema.name`.
+    let name = if matches!(tokens.get(2), Some(Token::Symbol('.'))) {
+        words.get(2).cloned().flatten()
+    } else {
+        words.first().cloned().flatten()
+    };
+    let readable = name.is_some_and(|name| READ_PRAGMAS.contains(&name.as_str()));
+    if !readable || tokens.contains(&Token::Symbol('=')) {
+        return Err(Error::Tool(
+            "read-only agents may only run informational PRAGMAs (table_info, index_list, foreign_key_list, ...)".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Tokenizes `sql` into statements as `(source range, tokens)` pairs.
+fn lex(
+    sql: &str,
+    dialect: SqlDialect,
+    backslash_escapes: bool,
+) -> Result<Vec<(std::ops::Range<usize>, Vec<Token>)>> {
+    let chars = sql.char_indices().collect::<Vec<_>>();
+    let byte_at = |index: usize| chars.get(index).map_or(sql.len(), |(byte, _)| *byte);
+    let char_at = |index: usize| chars.get(index).map(|(_, ch)| *ch);
+
+    let mut statements = Vec::new();
+    let mut tokens = Vec::new();
+    let mut statement_start = 0usize;
+    let mut i = 0usize;
+    while let Some(ch) = char_at(i) {
+        match ch {
+            ch if ch.is_whitespace() => i += 1,
+            '-' if char_at(i + 1) == Some('-') => {
+                while char_at(i).is_some_and(|ch| ch != '\n') {
+                    i += 1;
+                }
+            }
+            '#' if dialect == SqlDialect::Mysql => {
+                while char_at(i).is_some_and(|ch| ch != '\n') {
+                    i += 1;
+                }
+            }
+            '/' if char_at(i + 1) == Some('*') => {
+                if dialect == SqlDialect::Mysql && matches!(char_at(i + 2), Some('!' | '+')) {
+                    return Err(Error::Tool(
+                        "MySQL executable comments (/*! ... */) are not allowed".to_owned(),
+                    ));
+                }
+                let mut end = i + 2;
+                loop {
+                    match (char_at(end), char_at(end + 1)) {
+                        (Some('*'), Some('/')) => break,
+                        (Some(_), _) => end += 1,
+                        (None, _) => {
+                            return Err(Error::Tool("unterminated comment in 'sql'".to_owned()))
+                        }
+                    }
+                }
+                i = end + 2;
+            }
+            '\'' | '"' | '`' => {
+                let escapes = backslash_escapes && ch == '\'';
+                let mut end = i + 1;
+                let mut text = String::new();
+                loop {
+                    match char_at(end) {
+                        None => {
+                            return Err(Error::Tool(
+                                "unterminated quoted string in 'sql'".to_owned(),
+                            ))
+                        }
+                        Some('\\') if escapes => end += 2,
+                        Some(c) if c == ch => {
+                            if char_at(end + 1) == Some(ch) {
+                                text.push(ch);
+                                end += 2;
+                            } else {
+                                break;
+                            }
+                        }
+                        Some(c) => {
+                            text.push(c);
+                            end += 1;
+                        }
+                    }
+                }
+                tokens.push(if ch == '\'' {
+                    Token::Literal
+                } else {
+                    Token::Word { text, quoted: true }
+                });
+                i = end + 1;
+            }
+            '$' if dialect == SqlDialect::Postgres => {
+                // `$tag$ ... $tag$` dollar quoting; `$1` is a parameter.
+                let mut tag_end = i + 1;
+                while char_at(tag_end).is_some_and(|ch| ch.is_alphanumeric() || ch == '_') {
+                    tag_end += 1;
+                }
+                let tag_is_valid = char_at(tag_end) == Some('$')
+                    && !char_at(i + 1).is_some_and(|ch| ch.is_ascii_digit());
+                if !tag_is_valid {
+                    tokens.push(Token::Symbol('$'));
+                    i += 1;
+                    continue;
+                }
+                let tag = &sql[byte_at(i)..byte_at(tag_end + 1)];
+                let body_start = byte_at(tag_end + 1);
+                let Some(offset) = sql[body_start..].find(tag) else {
+                    return Err(Error::Tool(
+                        "unterminated dollar-quoted string in 'sql'".to_owned(),
+                    ));
+                };
+                let close = body_start + offset + tag.len();
+                tokens.push(Token::Literal);
+                while byte_at(i) < close {
+                    i += 1;
+                }
+            }
+            ';' => {
+                if !tokens.is_empty() {
+                    statements.push((statement_start..byte_at(i), std::mem::take(&mut tokens)));
+                }
+                i += 1;
+                statement_start = byte_at(i);
+            }
+            ch if ch.is_alphanumeric() || ch == '_' => {
+                let start = i;
+                while char_at(i).is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$') {
+                    i += 1;
+                }
+                tokens.push(Token::Word {
+                    text: sql[byte_at(start)..byte_at(i)].to_ascii_uppercase(),
+                    quoted: false,
+                });
+            }
+            ch => {
+                tokens.push(Token::Symbol(ch));
+                i += 1;
+            }
+        }
+    }
+    if !tokens.is_empty() {
+        statements.push((statement_start..sql.len(), tokens));
+    }
+    Ok(statements)
+}
@@ -2,9 +2,12 @@ use crate::config::schema::ToolConfig;
 use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
+use crate::watcher::compile_globs;
 use async_trait::async_trait;
+use globset::GlobSet;
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
@@ -17,18 +20,78 @@ use tokio::time::timeout;
 const OUTPUT_CAPTURE_LIMIT_BYTES: usize = 10 * 1024;
 const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 15;
 const DEFAULT_CONTROL_PERSIST_SECS: u64 = 600;
+/// Nested `Include` directives followed when listing ssh_config hosts.
+const MAX_CONFIG_INCLUDE_DEPTH: usize = 8;
+/// Paths listed per category in sync results.
+const SYNC_REPORT_LIMIT: usize = 200;
+
+/// Effective settings for a host as reported by `ssh -G`, which applies
+/// `~/.ssh/config` (or the tool's `ssh_config_file`) exactly like a real connection.
+#[derive(Debug, Clone, Default, Serialize)]
+struct ResolvedHost {
+    hostname: String,
+    user: Option<String>,
+    port: u16,
+    proxy_jump: Option<String>,
+    identity_files: Vec<String>,
+    forward_agent: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PortForward {
+    id: String,
+    /// `local` (-L) or `remote` (-R).
+    kind: &'static str,
+    bind_address: Option<String>,
+    listen_port: u16,
+    destination_host: String,
+    destination_port: u16,
+}
+
+impl PortForward {
+    fn flag(&self) -> &'static str {
+        if self.kind == "local" {
+            "-L"
+        } else {
+            "-R"
+        }
+    }
+
+    fn spec(&self) -> String {
+        let listen = match &self.bind_address {
+            Some(bind) => format!("{bind}:{}", self.listen_port),
+            None => self.listen_port.to_string(),
+        };
+        format!(
+            "{listen}:{}:{}",
+            self.destination_host, self.destination_port
+        )
+    }
+}
 
 #[derive(Debug, Clone)]
 struct SshSession {
     name: String,
+    /// Host as given, which may be an ssh_config alias.
     host: String,
     user: Option<String>,
-    port: u16,
+    /// Explicit port; `None` defers to ssh_config.
+    port: Option<u16>,
+    jump: Option<String>,
+    config_file: Option<PathBuf>,
     control_path: PathBuf,
     identity_file: Option<PathBuf>,
     strict_host_key_checking: String,
     known_hosts_file: Option<PathBuf>,
     created_at_epoch_secs: u64,
+    resolved: ResolvedHost,
+    forwards: Vec<PortForward>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RemoteFile {
+    size: u64,
+    mtime: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -52,14 +115,28 @@ impl SshTool {
                         "list_sessions",
                         "close_all",
                         "scp_upload",
-                        "scp_download"
+                        "scp_download",
+                        "list_hosts",
+                        "forward_local",
+                        "forward_remote",
+                        "cancel_forward",
+                        "list_forwards",
+                        "sftp_sync"
                     ]
                 },
                 "session": { "type": "string", "description": "Logical SSH session name" },
-                "host": { "type": "string", "description": "Remote host name or IP" },
+                "host": { "type": "string", "description": "Remote host name, IP or ssh_config alias" },
                 "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
                 "user": { "type": "string", "description": "SSH username" },
                 "identity_file": { "type": "string", "description": "Private key path" },
+                "jump": {
+                    "type": "string",
+                    "description": "ProxyJump bastion(s), e.g. 'bastion' or 'ops@bastion:2222,inner'"
+                },
+                "forward_agent": {
+                    "type": "boolean",
+                    "description": "Forward the local ssh-agent to the remote host"
+                },
                 "strict_host_key_checking": {
                     "type": "string",
                     "enum": ["accept-new", "yes", "no"],
@@ -84,16 +161,58 @@ impl SshTool {
                 },
                 "local_path": {
                     "type": "string",
-                    "description": "Local filesystem path for SCP transfer"
+                    "description": "Local filesystem path for SCP transfer or SFTP sync"
                 },
                 "remote_path": {
                     "type": "string",
-                    "description": "Remote path for SCP transfer"
+                    "description": "Remote path for SCP transfer or SFTP sync"
                 },
                 "recursive": {
                     "type": "boolean",
                     "description": "Enable recursive SCP transfer"
-                }
+                },
+                "local_port": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 65535,
+                    "description": "forward_local: listening port (0 or omitted picks a free one); forward_remote: destination port"
+                },
+                "remote_port": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 65535,
+                    "description": "forward_local: destination port; forward_remote: listening port on the remote side (0 lets the server pick)"
+                },
+                "remote_host": {
+                    "type": "string",
+                    "description": "forward_local: destination host as seen from the remote side (default localhost)"
+                },
+                "local_host": {
+                    "type": "string",
+                    "description": "forward_remote: destination host as seen from this machine (default localhost)"
+                },
+                "bind_address": { "type": "string", "description": "Listening address for the forward" },
+                "forward_id": { "type": "string", "description": "Forward to cancel (from list_forwards)" },
+                "direction": {
+                    "type": "string",
+                    "enum": ["upload", "download"],
+                    "description": "sftp_sync direction"
+                },
+                "include": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "sftp_sync: globs (relative to the synced roots) to include; default all"
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "sftp_sync: globs to skip"
+                },
+                "delete": {
+                    "type": "boolean",
+                    "description": "sftp_sync: remove destination files missing from the source (within the filters)"
+                },
+                "dry_run": { "type": "boolean", "description": "sftp_sync: report the plan only" }
             },
             "required": ["operation"]
         });
@@ -121,15 +240,69 @@ impl SshTool {
             .map(ToOwned::to_owned)
     }
 
+    fn optional_port(args: &Value, key: &str) -> Result<Option<u16>> {
+        args.get(key)
+            .and_then(Value::as_u64)
+            .map(|raw| {
+                u16::try_from(raw)
+                    .map_err(|_| Error::Tool(format!("'{key}' must be between 0 and 65535")))
+            })
+            .transpose()
+    }
+
+    fn string_list(args: &Value, key: &str) -> Vec<String> {
+        args.get(key)
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn resolve_local_path(raw: &str, context: &ToolExecutionContext) -> PathBuf {
         let path = Path::new(raw);
         if path.is_absolute() {
             path.to_path_buf()
+        } else if let Some(rest) = raw.strip_prefix("~/") {
+            Self::home_dir().join(rest)
         } else {
             context.working_directory.join(path)
         }
     }
 
+    /// The operator's `ssh_config_file`, if configured. It is never taken from
+    /// call arguments: `Match exec`, `ProxyCommand` and `LocalCommand` in an
+    /// ssh_config run local commands.
+    fn config_file(&self, context: &ToolExecutionContext) -> Option<PathBuf> {
+        self.config
+            .ssh_config_file
+            .as_deref()
+            .map(|raw| Self::resolve_local_path(raw, context))
+    }
+
+    /// Refuses destinations that ssh would parse as options (`-oProxyCommand=…`).
+    fn reject_option_like(key: &str, value: &str) -> Result<()> {
+        if value
+            .split([',', '@'])
+            .any(|part| part.trim_start().starts_with('-'))
+        {
+            return Err(Error::Tool(format!("'{key}' must not start with '-'")));
+        }
+        Ok(())
+    }
+
+    fn home_dir() -> PathBuf {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/"))
+    }
+
     fn append_bounded(buffer: &mut String, chunk: &str) {
         let remaining = OUTPUT_CAPTURE_LIMIT_BYTES.saturating_sub(buffer.len());
         if remaining == 0 {
@@ -170,6 +343,26 @@ impl SshTool {
         format!("{user_part}@{host}:{port}")
     }
 
+    /// Single-quotes `value` for the remote POSIX shell.
+    fn shell_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    /// Quotes a path for an `sftp -b` batch line, escaping glob characters so
+    /// `put`/`get` treat it literally.
+    fn sftp_quote(value: &str) -> String {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        quoted.push('"');
+        for ch in value.chars() {
+            if matches!(ch, '"' | '\\' | '*' | '?' | '[' | ']') {
+                quoted.push('\\');
+            }
+            quoted.push(ch);
+        }
+        quoted.push('"');
+        quoted
+    }
+
     fn ensure_ssh_available() -> Result<()> {
         let output = std::process::Command::new("ssh")
             .arg("-V")
@@ -216,13 +409,22 @@ impl SshTool {
     fn enforce_agent_permission(
         &self,
         operation: &str,
+        args: &Value,
         context: &ToolExecutionContext,
     ) -> Result<()> {
         if context.agent_permission_mode == crate::config::schema::AgentPermissionMode::ReadWrite {
             return Ok(());
         }
 
-        let allowed = matches!(operation, "exec" | "list_sessions" | "scp_download");
+        let allowed = match operation {
+            "exec" | "list_sessions" | "scp_download" | "list_hosts" | "list_forwards" => true,
+            // Pulling files mirrors scp_download; pushing or deleting does not.
+            "sftp_sync" => {
+                args.get("direction").and_then(Value::as_str) == Some("download")
+                    && !args.get("delete").and_then(Value::as_bool).unwrap_or(false)
+            }
+            _ => false,
+        };
         if allowed {
             return Ok(());
         }
@@ -233,6 +435,168 @@ impl SshTool {
         )))
     }
 
+    /// Adds the options every command for `session` shares. `port_flag` is
+    /// `-p` for ssh and `-P` for scp/sftp.
+    fn apply_session_options(cmd: &mut Command, session: &SshSession, port_flag: &str) {
+        if let Some(config_file) = &session.config_file {
+            cmd.arg("-F").arg(config_file);
+        }
+        cmd.arg("-o")
+            .arg("BatchMode=yes")
+            .arg("-o")
+            .arg(format!(
+                "StrictHostKeyChecking={}",
+                session.strict_host_key_checking
+            ))
+            .arg("-o")
+            .arg(format!("ControlPath={}", session.control_path.display()));
+        if let Some(port) = session.port {
+            cmd.arg(port_flag).arg(port.to_string());
+        }
+        if let Some(jump) = &session.jump {
+            cmd.arg("-J").arg(jump);
+        }
+        if let Some(identity) = &session.identity_file {
+            cmd.arg("-i").arg(identity);
+        }
+        if let Some(known_hosts) = &session.known_hosts_file {
+            cmd.arg("-o")
+                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
+        }
+    }
+
+    async fn get_session(&self, args: &Value) -> Result<SshSession> {
+        let session_name = self.required_string(args, "session")?;
+        self.sessions
+            .lock()
+            .await
+            .get(session_name)
+            .cloned()
+            .ok_or_else(|| Error::Tool(format!("ssh session '{session_name}' does not exist")))
+    }
+
+    /// Resolves the effective settings for `host` with `ssh -G`.
+    async fn resolve_host(
+        config_file: Option<&Path>,
+        host: &str,
+        user: Option<&str>,
+        port: Option<u16>,
+        jump: Option<&str>,
+    ) -> Result<ResolvedHost> {
+        let mut cmd = Command::new("ssh");
+        cmd.arg("-G");
+        if let Some(config_file) = config_file {
+            cmd.arg("-F").arg(config_file);
+        }
+        if let Some(port) = port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        if let Some(jump) = jump {
+            cmd.arg("-J").arg(jump);
+        }
+        cmd.arg(Self::ssh_target(user, host));
+        let output = timeout(Duration::from_secs(10), cmd.output())
+            .await
+            .map_err(|_| Error::Tool("ssh -G timed out".to_owned()))?
+            .map_err(|err| Error::Tool(format!("failed to run ssh -G: {err}")))?;
+        if !output.status.success() {
+            return Err(Error::Tool(format!(
+                "failed to resolve ssh config for '{host}': {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut resolved = ResolvedHost {
+            hostname: host.to_owned(),
+            port: 22,
+            ..ResolvedHost::default()
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let value = value.trim();
+            match key {
+                "hostname" => resolved.hostname = value.to_owned(),
+                "user" => resolved.user = Some(value.to_owned()),
+                "port" => resolved.port = value.parse().unwrap_or(22),
+                "proxyjump" if value != "none" => resolved.proxy_jump = Some(value.to_owned()),
+                "identityfile" => resolved.identity_files.push(value.to_owned()),
+                "forwardagent" => resolved.forward_agent = value == "yes",
+                _ => {}
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Collects `Host` aliases (without wildcards) from `path` and its `Include`s.
+    fn collect_config_hosts(path: &Path, depth: usize, hosts: &mut BTreeSet<String>) {
+        if depth > MAX_CONFIG_INCLUDE_DEPTH {
+            return;
+        }
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let ssh_dir = Self::home_dir().join(".ssh");
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once(|ch: char| ch == '=' || ch.is_whitespace()) {
+                Some((key, value)) => (key.trim(), value.trim().trim_start_matches('=').trim()),
+                None => continue,
+            };
+            if key.eq_ignore_ascii_case("host") {
+                hosts.extend(
+                    value
+                        .split_whitespace()
+                        .filter(|alias| !alias.contains(['*', '?', '!']))
+                        .map(ToOwned::to_owned),
+                );
+            } else if key.eq_ignore_ascii_case("include") {
+                for pattern in value.split_whitespace() {
+                    let pattern = match pattern.strip_prefix("~/") {
+                        Some(rest) => Self::home_dir().join(rest),
+                        None if Path::new(pattern).is_absolute() => PathBuf::from(pattern),
+                        None => ssh_dir.join(pattern),
+                    };
+                    for included in Self::expand_include(&pattern) {
+                        Self::collect_config_hosts(&included, depth + 1, hosts);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Expands a wildcard in the file name of an `Include` path.
+    fn expand_include(pattern: &Path) -> Vec<PathBuf> {
+        let Some(name) = pattern.file_name().and_then(|name| name.to_str()) else {
+            return Vec::new();
+        };
+        if !name.contains(['*', '?', '[']) {
+            return vec![pattern.to_path_buf()];
+        }
+        let (Some(parent), Ok(glob)) = (pattern.parent(), globset::Glob::new(name)) else {
+            return Vec::new();
+        };
+        let matcher = glob.compile_matcher();
+        let mut matches = std::fs::read_dir(parent)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name()
+                            .is_some_and(|name| matcher.is_match(Path::new(name)))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        matches.sort();
+        matches
+    }
+
     async fn run_ssh_streaming(
         &self,
         mut cmd: Command,
@@ -317,6 +681,36 @@ impl SshTool {
         ))
     }
 
+    /// Runs a remote command over the session without streaming, returning the
+    /// full stdout (for listings that may exceed the capture limit).
+    async fn run_remote_capture(&self, session: &SshSession, command: &str) -> Result<String> {
+        let mut cmd = Command::new("ssh");
+        Self::apply_session_options(&mut cmd, session, "-p");
+        cmd.arg(Self::ssh_target(session.user.as_deref(), &session.host))
+            .arg(command)
+            .stdin(Stdio::null());
+        let output = timeout(
+            Duration::from_secs(self.config.timeout_seconds),
+            cmd.output(),
+        )
+        .await
+        .map_err(|_| {
+            Error::Tool(format!(
+                "ssh command timed out after {} seconds",
+                self.config.timeout_seconds
+            ))
+        })?
+        .map_err(|err| Error::Tool(format!("failed to run ssh command: {err}")))?;
+        if !output.status.success() {
+            return Err(Error::Tool(format!(
+                "remote command failed (exit {}): {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
     async fn connect_session(
         &self,
         args: &Value,
@@ -326,18 +720,34 @@ impl SshTool {
         Self::ensure_ssh_available()?;
 
         let host = self.required_string(args, "host")?.to_owned();
-        let port = args
-            .get("port")
-            .and_then(Value::as_u64)
-            .map(|raw| {
-                u16::try_from(raw)
-                    .map_err(|_| Error::Tool("'port' must be between 1 and 65535".to_owned()))
-            })
-            .transpose()?
-            .unwrap_or(22);
+        let port = Self::optional_port(args, "port")?;
+        if port == Some(0) {
+            return Err(Error::Tool("'port' must be between 1 and 65535".to_owned()));
+        }
         let user = Self::optional_string(args, "user");
-        let session_name = Self::optional_string(args, "session")
-            .unwrap_or_else(|| Self::default_session_name(&host, user.as_deref(), port));
+        let jump = Self::optional_string(args, "jump");
+        Self::reject_option_like("host", &host)?;
+        for (key, value) in [("user", &user), ("jump", &jump)] {
+            if let Some(value) = value {
+                Self::reject_option_like(key, value)?;
+            }
+        }
+        let config_file = self.config_file(context);
+        let forward_agent = args
+            .get("forward_agent")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let resolved = Self::resolve_host(
+            config_file.as_deref(),
+            &host,
+            user.as_deref(),
+            port,
+            jump.as_deref(),
+        )
+        .await?;
+        let session_name = Self::optional_string(args, "session").unwrap_or_else(|| {
+            Self::default_session_name(&host, resolved.user.as_deref(), resolved.port)
+        });
 
         let strict_host_key_checking = Self::optional_string(args, "strict_host_key_checking")
             .unwrap_or_else(|| "accept-new".to_owned());
@@ -376,33 +786,33 @@ impl SshTool {
         })?;
         let control_path = Self::build_control_path(&control_dir, &session_name);
 
-        let target = Self::ssh_target(user.as_deref(), &host);
+        let session = SshSession {
+            name: session_name.clone(),
+            host,
+            user,
+            port,
+            jump,
+            config_file,
+            control_path,
+            identity_file,
+            strict_host_key_checking,
+            known_hosts_file,
+            created_at_epoch_secs: Self::now_epoch_secs(),
+            resolved,
+            forwards: Vec::new(),
+        };
+
         let mut cmd = Command::new("ssh");
-        cmd.arg("-M")
-            .arg("-N")
-            .arg("-f")
-            .arg("-o")
-            .arg("BatchMode=yes")
-            .arg("-o")
+        cmd.arg("-M").arg("-N").arg("-f");
+        Self::apply_session_options(&mut cmd, &session, "-p");
+        cmd.arg("-o")
             .arg(format!("ConnectTimeout={connect_timeout}"))
             .arg("-o")
-            .arg(format!("ControlPersist={DEFAULT_CONTROL_PERSIST_SECS}"))
-            .arg("-o")
-            .arg(format!("ControlPath={}", control_path.display()))
-            .arg("-o")
-            .arg(format!("StrictHostKeyChecking={strict_host_key_checking}"))
-            .arg("-p")
-            .arg(port.to_string());
-
-        if let Some(identity) = &identity_file {
-            cmd.arg("-i").arg(identity);
-        }
-        if let Some(known_hosts) = &known_hosts_file {
-            cmd.arg("-o")
-                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
+            .arg(format!("ControlPersist={DEFAULT_CONTROL_PERSIST_SECS}"));
+        if forward_agent {
+            cmd.arg("-A");
         }
-
-        cmd.arg(&target);
+        cmd.arg(Self::ssh_target(session.user.as_deref(), &session.host));
 
         let (stdout, stderr, exit_code) =
             self.run_ssh_streaming(cmd, tx, &self.config.name).await?;
@@ -413,17 +823,6 @@ impl SshTool {
             )));
         }
 
-        let session = SshSession {
-            name: session_name.clone(),
-            host,
-            user,
-            port,
-            control_path,
-            identity_file,
-            strict_host_key_checking,
-            known_hosts_file,
-            created_at_epoch_secs: Self::now_epoch_secs(),
-        };
         sessions.insert(session_name.clone(), session.clone());
 
         Ok(ToolResult {
@@ -432,7 +831,9 @@ impl SshTool {
             output: json!({
                 "session": session_name,
                 "target": Self::ssh_target(session.user.as_deref(), &session.host),
-                "port": session.port,
+                "port": session.resolved.port,
+                "resolved": session.resolved,
+                "jump": session.jump,
                 "control_path": session.control_path,
                 "created_at_epoch_secs": session.created_at_epoch_secs
             })
@@ -441,45 +842,21 @@ impl SshTool {
     }
 
     async fn exec_command(&self, args: &Value, tx: mpsc::Sender<Event>) -> Result<ToolResult> {
-        let session_name = self.required_string(args, "session")?.to_owned();
         let command = self.required_string(args, "command")?.to_owned();
         let pty = args.get("pty").and_then(Value::as_bool).unwrap_or(false);
+        let session = self.get_session(args).await?;
 
-        let session = {
-            let sessions = self.sessions.lock().await;
-            sessions.get(&session_name).cloned()
-        }
-        .ok_or_else(|| Error::Tool(format!("ssh session '{session_name}' does not exist")))?;
-
-        let target = Self::ssh_target(session.user.as_deref(), &session.host);
         let mut cmd = Command::new("ssh");
-        cmd.arg("-S")
-            .arg(&session.control_path)
-            .arg("-o")
-            .arg("ControlMaster=auto")
-            .arg("-o")
-            .arg("BatchMode=yes")
-            .arg("-o")
-            .arg(format!(
-                "StrictHostKeyChecking={}",
-                session.strict_host_key_checking
-            ))
-            .arg("-p")
-            .arg(session.port.to_string());
-
+        Self::apply_session_options(&mut cmd, &session, "-p");
+        cmd.arg("-o").arg("ControlMaster=auto");
         if pty {
             cmd.arg("-tt");
         }
 
-        if let Some(identity) = &session.identity_file {
-            cmd.arg("-i").arg(identity);
-        }
-        if let Some(known_hosts) = &session.known_hosts_file {
-            cmd.arg("-o")
-                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
-        }
-
-        cmd.arg(&target).arg("sh").arg("-lc").arg(&command);
+        cmd.arg(Self::ssh_target(session.user.as_deref(), &session.host))
+            .arg("sh")
+            .arg("-lc")
+            .arg(&command);
 
         let (stdout, stderr, exit_code) =
             self.run_ssh_streaming(cmd, tx, &self.config.name).await?;
@@ -498,7 +875,7 @@ impl SshTool {
     ) -> Result<ToolResult> {
         Self::ensure_scp_available()?;
 
-        let session_name = self.required_string(args, "session")?.to_owned();
+        let session = self.get_session(args).await?;
         let local_path_raw = self.required_string(args, "local_path")?;
         let remote_path = self.required_string(args, "remote_path")?.to_owned();
         let recursive = args
@@ -506,12 +883,6 @@ impl SshTool {
             .and_then(Value::as_bool)
             .unwrap_or(false);
 
-        let session = {
-            let sessions = self.sessions.lock().await;
-            sessions.get(&session_name).cloned()
-        }
-        .ok_or_else(|| Error::Tool(format!("ssh session '{session_name}' does not exist")))?;
-
         let local_path = Self::resolve_local_path(local_path_raw, context);
         if !local_path.exists() {
             return Err(Error::Tool(format!(
@@ -524,30 +895,11 @@ impl SshTool {
         let remote_target = format!("{target}:{remote_path}");
 
         let mut cmd = Command::new("scp");
-        cmd.arg("-B")
-            .arg("-P")
-            .arg(session.port.to_string())
-            .arg("-o")
-            .arg("BatchMode=yes")
-            .arg("-o")
-            .arg(format!(
-                "StrictHostKeyChecking={}",
-                session.strict_host_key_checking
-            ))
-            .arg("-o")
-            .arg(format!("ControlPath={}", session.control_path.display()));
-
+        cmd.arg("-B");
+        Self::apply_session_options(&mut cmd, &session, "-P");
         if recursive {
             cmd.arg("-r");
         }
-        if let Some(identity) = &session.identity_file {
-            cmd.arg("-i").arg(identity);
-        }
-        if let Some(known_hosts) = &session.known_hosts_file {
-            cmd.arg("-o")
-                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
-        }
-
         cmd.arg(&local_path).arg(&remote_target);
 
         let (stdout, stderr, exit_code) =
@@ -557,7 +909,7 @@ impl SshTool {
             exit_code: Some(exit_code),
             output: if exit_code == 0 {
                 json!({
-                    "session": session_name,
+                    "session": session.name,
                     "local_path": local_path,
                     "remote_path": remote_path,
                     "recursive": recursive,
@@ -578,7 +930,7 @@ impl SshTool {
     ) -> Result<ToolResult> {
         Self::ensure_scp_available()?;
 
-        let session_name = self.required_string(args, "session")?.to_owned();
+        let session = self.get_session(args).await?;
         let remote_path = self.required_string(args, "remote_path")?.to_owned();
         let local_path_raw = self.required_string(args, "local_path")?;
         let recursive = args
@@ -586,12 +938,6 @@ impl SshTool {
             .and_then(Value::as_bool)
             .unwrap_or(false);
 
-        let session = {
-            let sessions = self.sessions.lock().await;
-            sessions.get(&session_name).cloned()
-        }
-        .ok_or_else(|| Error::Tool(format!("ssh session '{session_name}' does not exist")))?;
-
         let local_path = Self::resolve_local_path(local_path_raw, context);
         if let Some(parent) = local_path.parent() {
             std::fs::create_dir_all(parent).map_err(|err| {
@@ -606,30 +952,11 @@ impl SshTool {
         let remote_source = format!("{target}:{remote_path}");
 
         let mut cmd = Command::new("scp");
-        cmd.arg("-B")
-            .arg("-P")
-            .arg(session.port.to_string())
-            .arg("-o")
-            .arg("BatchMode=yes")
-            .arg("-o")
-            .arg(format!(
-                "StrictHostKeyChecking={}",
-                session.strict_host_key_checking
-            ))
-            .arg("-o")
-            .arg(format!("ControlPath={}", session.control_path.display()));
-
+        cmd.arg("-B");
+        Self::apply_session_options(&mut cmd, &session, "-P");
         if recursive {
             cmd.arg("-r");
         }
-        if let Some(identity) = &session.identity_file {
-            cmd.arg("-i").arg(identity);
-        }
-        if let Some(known_hosts) = &session.known_hosts_file {
-            cmd.arg("-o")
-                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
-        }
-
         cmd.arg(&remote_source).arg(&local_path);
 
         let (stdout, stderr, exit_code) =
@@ -639,7 +966,7 @@ impl SshTool {
             exit_code: Some(exit_code),
             output: if exit_code == 0 {
                 json!({
-                    "session": session_name,
+                    "session": session.name,
                     "remote_path": remote_path,
                     "local_path": local_path,
                     "recursive": recursive,
@@ -652,6 +979,460 @@ impl SshTool {
         })
     }
 
+    async fn list_hosts(&self, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let config_file = self.config_file(context);
+        let path = config_file
+            .clone()
+            .unwrap_or_else(|| Self::home_dir().join(".ssh").join("config"));
+        let mut aliases = BTreeSet::new();
+        Self::collect_config_hosts(&path, 0, &mut aliases);
+
+        let mut hosts = Vec::new();
+        for alias in aliases {
+            let resolved = match Self::reject_option_like("alias", &alias) {
+                Ok(()) => {
+                    Self::resolve_host(config_file.as_deref(), &alias, None, None, None).await
+                }
+                Err(err) => Err(err),
+            };
+            hosts.push(match resolved {
+                Ok(resolved) => json!({ "alias": alias, "resolved": resolved }),
+                Err(err) => json!({ "alias": alias, "error": err.to_string() }),
+            });
+        }
+
+        Ok(ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({ "config_file": path, "hosts": hosts, "count": hosts.len() })
+                .to_string(),
+        })
+    }
+
+    /// Sends a multiplexing control command (`-O forward`/`-O cancel`) to the
+    /// session's master connection.
+    async fn control_forward(
+        &self,
+        session: &SshSession,
+        control: &str,
+        forward: &PortForward,
+        tx: mpsc::Sender<Event>,
+    ) -> Result<String> {
+        let mut cmd = Command::new("ssh");
+        Self::apply_session_options(&mut cmd, session, "-p");
+        cmd.arg("-O")
+            .arg(control)
+            .arg(forward.flag())
+            .arg(forward.spec())
+            .arg(Self::ssh_target(session.user.as_deref(), &session.host));
+        let (stdout, stderr, exit_code) =
+            self.run_ssh_streaming(cmd, tx, &self.config.name).await?;
+        if exit_code != 0 {
+            return Err(Error::Tool(format!(
+                "ssh -O {control} {} {} failed (exit {exit_code}): {}",
+                forward.flag(),
+                forward.spec(),
+                if stderr.is_empty() { stdout } else { stderr }.trim()
+            )));
+        }
+        Ok(stdout)
+    }
+
+    async fn add_forward(
+        &self,
+        kind: &'static str,
+        args: &Value,
+        tx: mpsc::Sender<Event>,
+    ) -> Result<ToolResult> {
+        let session = self.get_session(args).await?;
+        let bind_address = Self::optional_string(args, "bind_address");
+        let local_port = Self::optional_port(args, "local_port")?;
+        let remote_port = Self::optional_port(args, "remote_port")?;
+
+        let mut forward = if kind == "local" {
+            let destination_port = remote_port
+                .filter(|port| *port > 0)
+                .ok_or_else(|| Error::Tool("missing 'remote_port' argument".to_owned()))?;
+            let listen_port = match local_port.filter(|port| *port > 0) {
+                Some(port) => port,
+                None => {
+                    let bind = bind_address.as_deref().unwrap_or("127.0.0.1");
+                    std::net::TcpListener::bind((bind, 0))
+                        .and_then(|listener| listener.local_addr())
+                        .map(|address| address.port())
+                        .map_err(|err| {
+                            Error::Tool(format!("failed to pick a free local port: {err}"))
+                        })?
+                }
+            };
+            PortForward {
+                id: String::new(),
+                kind,
+                bind_address,
+                listen_port,
+                destination_host: Self::optional_string(args, "remote_host")
+                    .unwrap_or_else(|| "localhost".to_owned()),
+                destination_port,
+            }
+        } else {
+            PortForward {
+                id: String::new(),
+                kind,
+                bind_address,
+                listen_port: remote_port
+                    .ok_or_else(|| Error::Tool("missing 'remote_port' argument".to_owned()))?,
+                destination_host: Self::optional_string(args, "local_host")
+                    .unwrap_or_else(|| "localhost".to_owned()),
+                destination_port: local_port
+                    .filter(|port| *port > 0)
+                    .ok_or_else(|| Error::Tool("missing 'local_port' argument".to_owned()))?,
+            }
+        };
+
+        let stdout = self
+            .control_forward(&session, "forward", &forward, tx)
+            .await?;
+        if forward.listen_port == 0 {
+            // The server picked the remote port; ssh prints it on stdout.
+            forward.listen_port = stdout
+                .split_whitespace()
+                .filter_map(|word| word.parse::<u16>().ok())
+                .next_back()
+                .ok_or_else(|| {
+                    Error::Tool("ssh did not report the allocated remote port".to_owned())
+                })?;
+        }
+        forward.id = format!(
+            "{}:{}:{}",
+            forward.kind,
+            forward.bind_address.as_deref().unwrap_or("*"),
+            forward.listen_port
+        );
+
+        let mut sessions = self.sessions.lock().await;
+        if let Some(stored) = sessions.get_mut(&session.name) {
+            stored.forwards.retain(|existing| existing.id != forward.id);
+            stored.forwards.push(forward.clone());
+        }
+
+        Ok(ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({ "session": session.name, "forward": forward }).to_string(),
+        })
+    }
+
+    async fn cancel_forward(&self, args: &Value, tx: mpsc::Sender<Event>) -> Result<ToolResult> {
+        let session = self.get_session(args).await?;
+        let forward_id = self.required_string(args, "forward_id")?;
+        let forward = session
+            .forwards
+            .iter()
+            .find(|forward| forward.id == forward_id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::Tool(format!(
+                    "ssh session '{}' has no forward '{forward_id}'",
+                    session.name
+                ))
+            })?;
+        self.control_forward(&session, "cancel", &forward, tx)
+            .await?;
+
+        let mut sessions = self.sessions.lock().await;
+        if let Some(stored) = sessions.get_mut(&session.name) {
+            stored.forwards.retain(|existing| existing.id != forward.id);
+        }
+        Ok(ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({ "session": session.name, "cancelled": forward }).to_string(),
+        })
+    }
+
+    async fn list_forwards(&self, args: &Value) -> ToolResult {
+        let filter = Self::optional_string(args, "session");
+        let sessions = self.sessions.lock().await;
+        let items = sessions
+            .values()
+            .filter(|session| filter.as_deref().is_none_or(|name| name == session.name))
+            .flat_map(|session| {
+                session
+                    .forwards
+                    .iter()
+                    .map(|forward| json!({ "session": session.name, "forward": forward }))
+            })
+            .collect::<Vec<_>>();
+        ToolResult {
+            success: true,
+            exit_code: Some(0),
+            output: json!({ "forwards": items, "count": items.len() }).to_string(),
+        }
+    }
+
+    fn sync_filter(patterns: &[String]) -> Result<Option<GlobSet>> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        compile_globs(patterns)
+            .map(Some)
+            .map_err(|err| Error::Tool(err.to_string()))
+    }
+
+    fn list_local_tree(root: &Path) -> Result<BTreeMap<String, RemoteFile>> {
+        let mut files = BTreeMap::new();
+        if !root.exists() {
+            return Ok(files);
+        }
+        if !root.is_dir() {
+            return Err(Error::Tool(format!(
+                "local_path '{}' is not a directory",
+                root.display()
+            )));
+        }
+        for entry in ignore::WalkBuilder::new(root)
+            .standard_filters(false)
+            .follow_links(false)
+            .build()
+            .flatten()
+        {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_secs());
+            let relative = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(
+                relative,
+                RemoteFile {
+                    size: metadata.len(),
+                    mtime,
+                },
+            );
+        }
+        Ok(files)
+    }
+
+    /// Lists regular files under `root` on the remote host as `size mtime path`
+    /// (GNU or BusyBox `stat`); a missing directory lists as empty.
+    async fn list_remote_tree(
+        &self,
+        session: &SshSession,
+        root: &str,
+    ) -> Result<BTreeMap<String, RemoteFile>> {
+        let script = format!(
+            "if [ -d {root} ]; then cd {root} && find . -type f -exec stat -c '%s %Y %n' {{}} +; fi",
+            root = Self::shell_quote(root)
+        );
+        let listing = self
+            .run_remote_capture(session, &format!("sh -c {}", Self::shell_quote(&script)))
+            .await?;
+        let mut files = BTreeMap::new();
+        for line in listing.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (Some(size), Some(mtime), Some(path)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let (Ok(size), Ok(mtime)) = (size.parse(), mtime.parse()) else {
+                continue;
+            };
+            let path = path.strip_prefix("./").unwrap_or(path);
+            files.insert(path.to_owned(), RemoteFile { size, mtime });
+        }
+        Ok(files)
+    }
+
+    async fn sftp_sync(
+        &self,
+        args: &Value,
+        context: &ToolExecutionContext,
+        tx: mpsc::Sender<Event>,
+    ) -> Result<ToolResult> {
+        let session = self.get_session(args).await?;
+        let direction = self.required_string(args, "direction")?.to_owned();
+        if !matches!(direction.as_str(), "upload" | "download") {
+            return Err(Error::Tool(
+                "direction must be one of: upload, download".to_owned(),
+            ));
+        }
+        let local_root =
+            Self::resolve_local_path(self.required_string(args, "local_path")?, context);
+        let remote_root = self
+            .required_string(args, "remote_path")?
+            .trim_end_matches('/')
+            .to_owned();
+        let remote_root = if remote_root.is_empty() {
+            "/".to_owned()
+        } else {
+            remote_root
+        };
+        let include = Self::sync_filter(&Self::string_list(args, "include"))?;
+        let exclude = Self::sync_filter(&Self::string_list(args, "exclude"))?;
+        let delete = args.get("delete").and_then(Value::as_bool).unwrap_or(false);
+        let dry_run = args
+            .get("dry_run")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let selected = |path: &String| {
+            include.as_ref().is_none_or(|globs| globs.is_match(path))
+                && !exclude.as_ref().is_some_and(|globs| globs.is_match(path))
+        };
+        let local_files = {
+            let local_root = local_root.clone();
+            tokio::task::spawn_blocking(move || Self::list_local_tree(&local_root))
+                .await
+                .map_err(|err| Error::Tool(format!("local listing task failed: {err}")))??
+        };
+        let remote_files = self.list_remote_tree(&session, &remote_root).await?;
+        let (source, destination) = if direction == "upload" {
+            (&local_files, &remote_files)
+        } else {
+            (&remote_files, &local_files)
+        };
+
+        let mut transfer = Vec::new();
+        let mut unchanged = 0usize;
+        for (path, file) in source.iter().filter(|(path, _)| selected(path)) {
+            match destination.get(path) {
+                Some(existing) if existing.size == file.size && existing.mtime >= file.mtime => {
+                    unchanged += 1;
+                }
+                _ => transfer.push(path.clone()),
+            }
+        }
+        let removals = if delete {
+            destination
+                .keys()
+                .filter(|path| selected(path) && !source.contains_key(*path))
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        let remote_path = |relative: &str| {
+            if remote_root == "/" {
+                format!("/{relative}")
+            } else {
+                format!("{remote_root}/{relative}")
+            }
+        };
+        let mut batch = Vec::new();
+        if direction == "upload" {
+            let mut directories = BTreeSet::new();
+            for path in &transfer {
+                let mut parent = Path::new(path).parent();
+                while let Some(dir) = parent.filter(|dir| !dir.as_os_str().is_empty()) {
+                    directories.insert(dir.to_string_lossy().into_owned());
+                    parent = dir.parent();
+                }
+            }
+            // A leading '-' lets sftp ignore "already exists" failures.
+            batch.push(format!("-mkdir {}", Self::sftp_quote(&remote_root)));
+            for dir in &directories {
+                batch.push(format!("-mkdir {}", Self::sftp_quote(&remote_path(dir))));
+            }
+            for path in &transfer {
+                batch.push(format!(
+                    "put -p {} {}",
+                    Self::sftp_quote(&local_root.join(path).to_string_lossy()),
+                    Self::sftp_quote(&remote_path(path))
+                ));
+            }
+            for path in &removals {
+                batch.push(format!("rm {}", Self::sftp_quote(&remote_path(path))));
+            }
+        } else {
+            for path in &transfer {
+                batch.push(format!(
+                    "get -p {} {}",
+                    Self::sftp_quote(&remote_path(path)),
+                    Self::sftp_quote(&local_root.join(path).to_string_lossy())
+                ));
+            }
+        }
+
+        let mut exit_code = 0;
+        let mut stderr = String::new();
+        if !dry_run {
+            if direction == "download" {
+                for path in &transfer {
+                    if let Some(parent) = local_root.join(path).parent() {
+                        std::fs::create_dir_all(parent).map_err(|err| {
+                            Error::Tool(format!(
+                                "failed to create local directory '{}': {err}",
+                                parent.display()
+                            ))
+                        })?;
+                    }
+                }
+            }
+            if !transfer.is_empty() || (direction == "upload" && !removals.is_empty()) {
+                let batch_path = std::env::temp_dir()
+                    .join(format!("rustic-ai-sftp-{}.batch", uuid::Uuid::new_v4()));
+                std::fs::write(&batch_path, batch.join("\n") + "\n").map_err(|err| {
+                    Error::Tool(format!("failed to write sftp batch file: {err}"))
+                })?;
+                let mut cmd = Command::new("sftp");
+                Self::apply_session_options(&mut cmd, &session, "-P");
+                cmd.arg("-b")
+                    .arg(&batch_path)
+                    .arg(Self::ssh_target(session.user.as_deref(), &session.host));
+                let result = self.run_ssh_streaming(cmd, tx, &self.config.name).await;
+                let _ = std::fs::remove_file(&batch_path);
+                let (_, err_output, code) = result?;
+                exit_code = code;
+                stderr = err_output;
+            }
+            if direction == "download" && exit_code == 0 {
+                for path in &removals {
+                    let _ = std::fs::remove_file(local_root.join(path));
+                }
+            }
+        }
+
+        let report = |paths: &[String]| {
+            paths
+                .iter()
+                .take(SYNC_REPORT_LIMIT)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        Ok(ToolResult {
+            success: exit_code == 0,
+            exit_code: Some(exit_code),
+            output: json!({
+                "session": session.name,
+                "direction": direction,
+                "local_path": local_root,
+                "remote_path": remote_root,
+                "dry_run": dry_run,
+                "transferred": report(&transfer),
+                "transferred_count": transfer.len(),
+                "deleted": report(&removals),
+                "deleted_count": removals.len(),
+                "unchanged_count": unchanged,
+                "stderr": stderr,
+            })
+            .to_string(),
+        })
+    }
+
     async fn disconnect_session(
         &self,
         args: &Value,
@@ -664,15 +1445,11 @@ impl SshTool {
         }
         .ok_or_else(|| Error::Tool(format!("ssh session '{session_name}' does not exist")))?;
 
-        let target = Self::ssh_target(session.user.as_deref(), &session.host);
         let mut cmd = Command::new("ssh");
-        cmd.arg("-S")
-            .arg(&session.control_path)
-            .arg("-O")
+        Self::apply_session_options(&mut cmd, &session, "-p");
+        cmd.arg("-O")
             .arg("exit")
-            .arg("-p")
-            .arg(session.port.to_string())
-            .arg(&target);
+            .arg(Self::ssh_target(session.user.as_deref(), &session.host));
 
         let (stdout, stderr, exit_code) =
             self.run_ssh_streaming(cmd, tx, &self.config.name).await?;
@@ -687,7 +1464,12 @@ impl SshTool {
         Ok(ToolResult {
             success: true,
             exit_code: Some(0),
-            output: json!({ "session": session_name, "closed": true }).to_string(),
+            output: json!({
+                "session": session_name,
+                "closed": true,
+                "closed_forwards": session.forwards.len()
+            })
+            .to_string(),
         })
     }
 
@@ -699,7 +1481,10 @@ impl SshTool {
                 json!({
                     "session": session.name,
                     "target": Self::ssh_target(session.user.as_deref(), &session.host),
-                    "port": session.port,
+                    "port": session.resolved.port,
+                    "hostname": session.resolved.hostname,
+                    "jump": session.jump.as_ref().or(session.resolved.proxy_jump.as_ref()),
+                    "forwards": session.forwards.len(),
                     "control_path": session.control_path,
                     "created_at_epoch_secs": session.created_at_epoch_secs
                 })
@@ -747,7 +1532,7 @@ impl Tool for SshTool {
     }
 
     fn description(&self) -> &str {
-        "Manage persistent SSH sessions and execute remote commands"
+        "Manage persistent SSH sessions: remote commands, port forwards, jump hosts and SFTP sync"
     }
 
     fn schema(&self) -> &Value {
@@ -766,7 +1551,7 @@ impl Tool for SshTool {
         context: &ToolExecutionContext,
     ) -> Result<ToolResult> {
         let operation = self.required_string(&args, "operation")?.to_owned();
-        self.enforce_agent_permission(&operation, context)?;
+        self.enforce_agent_permission(&operation, &args, context)?;
 
         let _ = tx.try_send(Event::ToolStarted {
             tool: self.config.name.clone(),
@@ -781,6 +1566,12 @@ impl Tool for SshTool {
             "close_all" => self.close_all_sessions(tx.clone()).await,
             "scp_upload" => self.scp_upload(&args, context, tx.clone()).await,
             "scp_download" => self.scp_download(&args, context, tx.clone()).await,
+            "list_hosts" => self.list_hosts(context).await,
+            "forward_local" => self.add_forward("local", &args, tx.clone()).await,
+            "forward_remote" => self.add_forward("remote", &args, tx.clone()).await,
+            "cancel_forward" => self.cancel_forward(&args, tx.clone()).await,
+            "list_forwards" => Ok(self.list_forwards(&args).await),
+            "sftp_sync" => self.sftp_sync(&args, context, tx.clone()).await,
             other => Err(Error::Tool(format!("unsupported ssh operation '{other}'"))),
         };
 
@@ -0,0 +1,706 @@
+//! Supervised background processes for the `process` tool. Each process has a
+//! name, a bounded log of its recent stdout/stderr lines, an optional readiness
+//! probe and a restart policy, and is stopped when its owning session ends.
+
+use crate::error::{Error, Result};
+use crate::tools::pty::clean_terminal_output;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+pub const DEFAULT_LOG_LINES: usize = 2_000;
+pub const MAX_LOG_LINES: usize = 20_000;
+/// Longer lines are cut so one runaway line cannot evict the whole log.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+const PROBE_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(2);
+const RESTART_BACKOFF_BASE: Duration = Duration::from_millis(500);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Highest numeric suffix `unique_name` tries before giving up.
+const MAX_NAME_SUFFIX: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "never" | "no" => Ok(Self::Never),
+            "on_failure" | "on-failure" => Ok(Self::OnFailure),
+            "always" => Ok(Self::Always),
+            other => Err(Error::Tool(format!(
+                "unsupported restart policy '{other}' (expected never|on_failure|always)"
+            ))),
+        }
+    }
+
+    fn should_restart(self, success: bool) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure => !success,
+            Self::Always => true,
+        }
+    }
+}
+
+/// Checks that must all pass before a process counts as ready.
+#[derive(Debug, Clone)]
+pub struct ReadinessProbe {
+    /// TCP port accepting connections on `host`.
+    pub port: Option<u16>,
+    pub host: String,
+    /// Pattern matched against log lines from the current run.
+    pub log_pattern: Option<Regex>,
+    /// URL answering with `http_status`.
+    pub http_url: Option<String>,
+    pub http_status: u16,
+}
+
+impl Default for ReadinessProbe {
+    fn default() -> Self {
+        Self {
+            port: None,
+            host: "127.0.0.1".to_owned(),
+            log_pattern: None,
+            http_url: None,
+            http_status: 200,
+        }
+    }
+}
+
+impl ReadinessProbe {
+    pub fn is_empty(&self) -> bool {
+        self.port.is_none() && self.log_pattern.is_none() && self.http_url.is_none()
+    }
+
+    pub fn describe(&self) -> Value {
+        json!({
+            "port": self.port,
+            "host": self.host,
+            "log_pattern": self.log_pattern.as_ref().map(Regex::as_str),
+            "http_url": self.http_url,
+            "http_status": self.http_status,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub working_dir: PathBuf,
+    pub env: Vec<(String, String)>,
+    pub restart: RestartPolicy,
+    pub max_restarts: u32,
+    pub log_lines: usize,
+    pub readiness: Option<ReadinessProbe>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    pub seq: u64,
+    /// `stdout`, `stderr` or `supervisor`.
+    pub stream: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Only lines from this stream; `None` returns every stream.
+    pub stream: Option<String>,
+    /// Only lines with a sequence number above this cursor.
+    pub since: Option<u64>,
+    pub grep: Option<Regex>,
+    /// Keep the last `tail` matching lines.
+    pub tail: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub lines: Vec<LogLine>,
+    /// Pass back as `since` to read only newer lines.
+    pub cursor: u64,
+    /// Lines after `since` were already evicted from the buffer.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessStatus {
+    pub name: String,
+    pub pid: Option<u32>,
+    pub running: bool,
+    /// The supervisor gave up: the process exited and will not be restarted.
+    pub finished: bool,
+    pub exit_code: Option<i32>,
+    pub restarts: u32,
+    pub ready: bool,
+    pub command: String,
+    pub args: Vec<String>,
+    pub restart: RestartPolicy,
+    pub started_at: DateTime<Utc>,
+    pub owner_session: Option<Uuid>,
+    pub log_cursor: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadyOutcome {
+    pub ready: bool,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug)]
+struct ProcessLog {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+    capacity: usize,
+}
+
+impl ProcessLog {
+    fn push(&mut self, stream: &'static str, mut text: String) {
+        if text.len() > MAX_LINE_BYTES {
+            let mut cut = MAX_LINE_BYTES;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+            text.push_str(" …");
+        }
+        self.next_seq += 1;
+        self.lines.push_back(LogLine {
+            seq: self.next_seq,
+            stream,
+            text,
+        });
+        while self.lines.len() > self.capacity {
+            self.lines.pop_front();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProcessState {
+    pid: Option<u32>,
+    running: bool,
+    finished: bool,
+    exit_code: Option<i32>,
+    restarts: u32,
+    ready: bool,
+    /// First log sequence number of the current run.
+    run_start_seq: u64,
+}
+
+#[derive(Debug)]
+struct ManagedProcess {
+    spec: ProcessSpec,
+    owner: Option<Uuid>,
+    started_at: DateTime<Utc>,
+    state: std::sync::Mutex<ProcessState>,
+    log: Arc<std::sync::Mutex<ProcessLog>>,
+    stop: CancellationToken,
+    monitor: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ManagedProcess {
+    fn log(&self, stream: &'static str, text: String) {
+        if let Ok(mut log) = self.log.lock() {
+            log.push(stream, text);
+        }
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.log
+            .lock()
+            .map(|log| log.next_seq + 1)
+            .unwrap_or_default()
+    }
+
+    fn status(&self) -> ProcessStatus {
+        let log_cursor = self.log.lock().map(|log| log.next_seq).unwrap_or_default();
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        ProcessStatus {
+            name: self.spec.name.clone(),
+            pid: state.pid,
+            running: state.running,
+            finished: state.finished,
+            exit_code: state.exit_code,
+            restarts: state.restarts,
+            ready: state.ready,
+            command: self.spec.command.clone(),
+            args: self.spec.args.clone(),
+            restart: self.spec.restart,
+            started_at: self.started_at,
+            owner_session: self.owner,
+            log_cursor,
+        }
+    }
+
+    fn mark_exited(&self, exit_code: Option<i32>, finished: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            state.running = false;
+            state.ready = false;
+            state.finished = finished;
+            state.exit_code = exit_code;
+        }
+    }
+
+    fn attach(&self, child: &mut Child) {
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(pump_lines(stdout, "stdout", self.log.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(pump_lines(stderr, "stderr", self.log.clone()));
+        }
+    }
+
+    async fn probe(&self, probe: &ReadinessProbe) -> std::result::Result<(), String> {
+        if let Some(port) = probe.port {
+            let address = format!("{}:{port}", probe.host);
+            match tokio::time::timeout(
+                PROBE_ATTEMPT_TIMEOUT,
+                tokio::net::TcpStream::connect(&address),
+            )
+            .await
+            {
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => return Err(format!("{address} not accepting connections: {err}")),
+                Err(_) => return Err(format!("{address} connect timed out")),
+            }
+        }
+        if let Some(pattern) = &probe.log_pattern {
+            let run_start_seq = self
+                .state
+                .lock()
+                .map(|state| state.run_start_seq)
+                .unwrap_or_default();
+            let matched = self.log.lock().is_ok_and(|log| {
+                log.lines
+                    .iter()
+                    .filter(|line| line.seq >= run_start_seq && line.stream != "supervisor")
+                    .any(|line| pattern.is_match(&line.text))
+            });
+            if !matched {
+                return Err(format!("no log line matches /{}/ yet", pattern.as_str()));
+            }
+        }
+        if let Some(url) = &probe.http_url {
+            let client = reqwest::Client::builder()
+                .timeout(PROBE_ATTEMPT_TIMEOUT)
+                .build()
+                .map_err(|err| format!("failed to build HTTP client: {err}"))?;
+            let status = client
+                .get(url)
+                .send()
+                .await
+                .map_err(|err| format!("GET {url} failed: {err}"))?
+                .status();
+            if status.as_u16() != probe.http_status {
+                return Err(format!(
+                    "GET {url} returned {status}, expected {}",
+                    probe.http_status
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn pump_lines(
+    reader: impl AsyncRead + Unpin,
+    stream: &'static str,
+    log: Arc<std::sync::Mutex<ProcessLog>>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let text = clean_terminal_output(&buffer);
+                let text = text.trim_end_matches(['\n', '\r']);
+                // Progress bars redraw with carriage returns; keep the final frame.
+                let text = text.rsplit('\r').next().unwrap_or(text).to_owned();
+                if let Ok(mut log) = log.lock() {
+                    log.push(stream, text);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_child(spec: &ProcessSpec) -> Result<Child> {
+    let mut command = Command::new(&spec.command);
+    command
+        .args(&spec.args)
+        .current_dir(&spec.working_dir)
+        .envs(spec.env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // A process group lets stop reach the servers a wrapper script forks.
+    #[cfg(unix)]
+    command.process_group(0);
+    command.spawn().map_err(|err| {
+        Error::Tool(format!(
+            "failed to start process '{}' ({}): {err}",
+            spec.name, spec.command
+        ))
+    })
+}
+
+/// Asks the process group to exit, then kills it after `grace`.
+async fn terminate(child: &mut Child, grace: Duration) -> Option<i32> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: signalling a process group we created; no memory is shared.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGTERM);
+        }
+        if let Ok(Ok(status)) = tokio::time::timeout(grace, child.wait()).await {
+            return status.code();
+        }
+        // SAFETY: as above.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+    let _ = child.start_kill();
+    child.wait().await.ok().and_then(|status| status.code())
+}
+
+async fn supervise(process: Arc<ManagedProcess>, mut child: Child, grace: Duration) {
+    loop {
+        let status = tokio::select! {
+            status = child.wait() => status.ok(),
+            _ = process.stop.cancelled() => {
+                let code = terminate(&mut child, grace).await;
+                process.mark_exited(code, true);
+                process.log("supervisor", "stopped".to_owned());
+                return;
+            }
+        };
+        let exit_code = status.and_then(|status| status.code());
+        let success = status.is_some_and(|status| status.success());
+        let restarts = process
+            .state
+            .lock()
+            .map(|state| state.restarts)
+            .unwrap_or_default();
+        let restart =
+            process.spec.restart.should_restart(success) && restarts < process.spec.max_restarts;
+        let code_label = exit_code.map_or_else(|| "signal".to_owned(), |code| code.to_string());
+        if !restart {
+            process.mark_exited(exit_code, true);
+            process.log("supervisor", format!("exited ({code_label})"));
+            return;
+        }
+
+        let backoff = RESTART_BACKOFF_BASE
+            .saturating_mul(2u32.saturating_pow(restarts))
+            .min(RESTART_BACKOFF_MAX);
+        process.mark_exited(exit_code, false);
+        process.log(
+            "supervisor",
+            format!(
+                "exited ({code_label}); restarting in {}ms (attempt {} of {})",
+                backoff.as_millis(),
+                restarts + 1,
+                process.spec.max_restarts
+            ),
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = process.stop.cancelled() => {
+                process.mark_exited(exit_code, true);
+                process.log("supervisor", "stopped".to_owned());
+                return;
+            }
+        }
+
+        match spawn_child(&process.spec) {
+            Ok(mut next) => {
+                let run_start_seq = process.next_seq();
+                process.attach(&mut next);
+                if let Ok(mut state) = process.state.lock() {
+                    state.pid = next.id();
+                    state.running = true;
+                    state.exit_code = None;
+                    state.restarts += 1;
+                    state.run_start_seq = run_start_seq;
+                }
+                child = next;
+            }
+            Err(err) => {
+                process.mark_exited(exit_code, true);
+                process.log("supervisor", format!("restart failed: {err}"));
+                return;
+            }
+        }
+    }
+}
+
+/// Owns every managed process; shared by all `process` tool instances so
+/// processes outlive tool reloads.
+#[derive(Debug, Default)]
+pub struct ProcessSupervisor {
+    processes: Mutex<HashMap<String, Arc<ManagedProcess>>>,
+}
+
+impl ProcessSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `base`, or `base-2`, `base-3`, … when a live process already uses it.
+    pub async fn unique_name(&self, base: &str) -> Result<String> {
+        let processes = self.processes.lock().await;
+        let taken = |name: &str| {
+            processes
+                .get(name)
+                .is_some_and(|process| !process.status().finished)
+        };
+        if !taken(base) {
+            return Ok(base.to_owned());
+        }
+        (2..=MAX_NAME_SUFFIX)
+            .map(|index| format!("{base}-{index}"))
+            .find(|name| !taken(name))
+            .ok_or_else(|| {
+                Error::Tool(format!(
+                    "too many running processes named '{base}'; pass an explicit 'name'"
+                ))
+            })
+    }
+
+    pub async fn start(
+        &self,
+        spec: ProcessSpec,
+        owner: Option<Uuid>,
+        stop_grace: Duration,
+    ) -> Result<ProcessStatus> {
+        let mut processes = self.processes.lock().await;
+        if let Some(existing) = processes.get(&spec.name) {
+            if !existing.status().finished {
+                return Err(Error::Tool(format!(
+                    "process '{}' is already running; stop it first or pick another name",
+                    spec.name
+                )));
+            }
+        }
+
+        let mut child = spawn_child(&spec)?;
+        let process = Arc::new(ManagedProcess {
+            owner,
+            started_at: Utc::now(),
+            state: std::sync::Mutex::new(ProcessState {
+                pid: child.id(),
+                running: true,
+                finished: false,
+                exit_code: None,
+                restarts: 0,
+                ready: false,
+                run_start_seq: 1,
+            }),
+            log: Arc::new(std::sync::Mutex::new(ProcessLog {
+                lines: VecDeque::new(),
+                next_seq: 0,
+                capacity: spec.log_lines.clamp(1, MAX_LOG_LINES),
+            })),
+            stop: CancellationToken::new(),
+            monitor: Mutex::new(None),
+            spec,
+        });
+        process.attach(&mut child);
+        let handle = tokio::spawn(supervise(process.clone(), child, stop_grace));
+        *process.monitor.lock().await = Some(handle);
+        processes.insert(process.spec.name.clone(), process.clone());
+        Ok(process.status())
+    }
+
+    pub async fn status(&self, name: &str) -> Option<ProcessStatus> {
+        self.processes
+            .lock()
+            .await
+            .get(name)
+            .map(|process| process.status())
+    }
+
+    /// Name of the managed process currently or last running as `pid`.
+    pub async fn name_for_pid(&self, pid: u32) -> Option<String> {
+        self.processes
+            .lock()
+            .await
+            .values()
+            .find(|process| process.status().pid == Some(pid))
+            .map(|process| process.spec.name.clone())
+    }
+
+    pub async fn list(&self) -> Vec<ProcessStatus> {
+        let mut listed = self
+            .processes
+            .lock()
+            .await
+            .values()
+            .map(|process| process.status())
+            .collect::<Vec<_>>();
+        listed.sort_by(|left, right| left.name.cmp(&right.name));
+        listed
+    }
+
+    /// Stops `name` (SIGTERM to its process group, SIGKILL after the grace
+    /// period) and forgets it.
+    pub async fn stop(&self, name: &str) -> Option<ProcessStatus> {
+        let process = self.processes.lock().await.remove(name)?;
+        process.stop.cancel();
+        if let Some(handle) = process.monitor.lock().await.take() {
+            let _ = handle.await;
+        }
+        Some(process.status())
+    }
+
+    /// Stops and forgets every process started from `session_id`, returning
+    /// the names of those that were still alive.
+    pub async fn stop_session(&self, session_id: Uuid) -> Vec<String> {
+        let owned = self
+            .processes
+            .lock()
+            .await
+            .values()
+            .filter(|process| process.owner == Some(session_id))
+            .map(|process| (process.spec.name.clone(), !process.status().finished))
+            .collect::<Vec<_>>();
+        let mut stopped = Vec::new();
+        for (name, alive) in owned {
+            if self.stop(&name).await.is_some() && alive {
+                stopped.push(name);
+            }
+        }
+        stopped
+    }
+
+    pub async fn logs(&self, name: &str, query: &LogQuery) -> Option<LogPage> {
+        let process = self.processes.lock().await.get(name).cloned()?;
+        let log = process.log.lock().ok()?;
+        let since = query.since.unwrap_or(0);
+        let oldest = log.lines.front().map_or(log.next_seq + 1, |line| line.seq);
+        let mut lines = log
+            .lines
+            .iter()
+            .filter(|line| line.seq > since)
+            .filter(|line| {
+                query
+                    .stream
+                    .as_deref()
+                    .is_none_or(|stream| line.stream == stream || line.stream == "supervisor")
+            })
+            .filter(|line| {
+                query
+                    .grep
+                    .as_ref()
+                    .is_none_or(|pattern| pattern.is_match(&line.text))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if lines.len() > query.tail {
+            lines.drain(..lines.len() - query.tail);
+        }
+        Some(LogPage {
+            lines,
+            cursor: log.next_seq,
+            truncated: query.since.is_some() && since + 1 < oldest,
+        })
+    }
+
+    /// Polls `probe` (or the probe given at start) until it passes, the
+    /// process exits for good, or `timeout` elapses.
+    pub async fn wait_ready(
+        &self,
+        name: &str,
+        probe: Option<ReadinessProbe>,
+        timeout: Duration,
+    ) -> Result<ReadyOutcome> {
+        let process = self
+            .processes
+            .lock()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("no managed process named '{name}'")))?;
+        let probe = probe
+            .or_else(|| process.spec.readiness.clone())
+            .filter(|probe| !probe.is_empty())
+            .ok_or_else(|| {
+                Error::Tool(format!(
+                    "process '{name}' has no readiness probe; pass port, log_pattern or http_url"
+                ))
+            })?;
+
+        let started = Instant::now();
+        let deadline = started + timeout;
+        loop {
+            let status = process.status();
+            if status.finished {
+                return Ok(ReadyOutcome {
+                    ready: false,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    reason: Some(format!(
+                        "process exited{}",
+                        status
+                            .exit_code
+                            .map(|code| format!(" with code {code}"))
+                            .unwrap_or_default()
+                    )),
+                });
+            }
+            let last_error = if status.running {
+                match process.probe(&probe).await {
+                    Ok(()) => {
+                        if let Ok(mut state) = process.state.lock() {
+                            state.ready = state.running;
+                        }
+                        return Ok(ReadyOutcome {
+                            ready: true,
+                            elapsed_ms: started.elapsed().as_millis() as u64,
+                            reason: None,
+                        });
+                    }
+                    Err(err) => err,
+                }
+            } else {
+                "process is restarting".to_owned()
+            };
+            if Instant::now() + PROBE_INTERVAL > deadline {
+                return Ok(ReadyOutcome {
+                    ready: false,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    reason: Some(format!("timed out: {last_error}")),
+                });
+            }
+            tokio::time::sleep(PROBE_INTERVAL).await;
+        }
+    }
+}
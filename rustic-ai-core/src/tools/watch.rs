@@ -4,13 +4,15 @@ use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::UNIX_EPOCH;
 use tokio::sync::{mpsc, Mutex};
 
-use crate::config::schema::ToolConfig;
+use crate::config::schema::{ToolConfig, WatcherConfig};
 use crate::error::{Error, Result};
 use crate::events::Event;
 use crate::tools::{Tool, ToolExecutionContext, ToolResult};
+use crate::watcher::{compile_globs, FileWatcher};
 
 #[derive(Debug, Clone)]
 struct FileFingerprint {
@@ -27,36 +29,45 @@ struct WatchArgs {
     max_files: usize,
     max_changes: usize,
     update_snapshot: bool,
+    timeout_ms: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct WatchTool {
     config: ToolConfig,
+    watcher_config: Arc<WatcherConfig>,
     schema: Value,
     snapshots: std::sync::Arc<Mutex<HashMap<String, HashMap<String, FileFingerprint>>>>,
 }
 
 impl WatchTool {
-    pub fn new(config: ToolConfig) -> Self {
+    pub fn new(config: ToolConfig, watcher_config: Arc<WatcherConfig>) -> Self {
         let schema = json!({
             "type": "object",
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["snapshot", "changes", "clear"]
+                    "enum": ["snapshot", "changes", "clear", "wait"]
                 },
-                "key": { "type": "string", "description": "Logical snapshot key" },
+                "key": { "type": "string", "description": "Logical snapshot key (not used by wait)" },
                 "path": { "type": "string" },
                 "glob": { "type": "string" },
                 "max_files": { "type": "integer", "minimum": 1, "maximum": 200000 },
                 "max_changes": { "type": "integer", "minimum": 1, "maximum": 10000 },
-                "update_snapshot": { "type": "boolean" }
+                "update_snapshot": { "type": "boolean" },
+                "timeout_ms": {
+                    "type": "integer",
+                    "minimum": 100,
+                    "maximum": 600000,
+                    "description": "wait: give up after this long without a matching change"
+                }
             },
-            "required": ["operation", "key"]
+            "required": ["operation"]
         });
 
         Self {
             config,
+            watcher_config,
             schema,
             snapshots: std::sync::Arc::new(Mutex::new(HashMap::new())),
         }
@@ -70,7 +81,10 @@ impl WatchTool {
             .filter(|value| !value.is_empty())
             .ok_or_else(|| Error::Tool("missing 'operation' argument".to_owned()))?
             .to_ascii_lowercase();
-        if !matches!(operation.as_str(), "snapshot" | "changes" | "clear") {
+        if !matches!(
+            operation.as_str(),
+            "snapshot" | "changes" | "clear" | "wait"
+        ) {
             return Err(Error::Tool(format!(
                 "unsupported watch operation '{}'",
                 operation
@@ -82,8 +96,12 @@ impl WatchTool {
             .and_then(Value::as_str)
             .map(str::trim)
             .filter(|value| !value.is_empty())
-            .ok_or_else(|| Error::Tool("missing 'key' argument".to_owned()))?
-            .to_owned();
+            .map(ToOwned::to_owned);
+        let key = match (key, operation.as_str()) {
+            (Some(key), _) => key,
+            (None, "wait") => String::new(),
+            (None, _) => return Err(Error::Tool("missing 'key' argument".to_owned())),
+        };
 
         let path = args
             .get("path")
@@ -111,6 +129,11 @@ impl WatchTool {
             .get("update_snapshot")
             .and_then(Value::as_bool)
             .unwrap_or(false);
+        let timeout_ms = args
+            .get("timeout_ms")
+            .and_then(Value::as_u64)
+            .unwrap_or(self.config.timeout_seconds.saturating_mul(1000))
+            .clamp(100, 600_000);
 
         Ok(WatchArgs {
             operation,
@@ -120,6 +143,7 @@ impl WatchTool {
             max_files,
             max_changes,
             update_snapshot,
+            timeout_ms,
         })
     }
 
@@ -227,6 +251,65 @@ impl WatchTool {
         Ok(snapshot)
     }
 
+    /// Blocks until the watcher reports a batch with paths matching `glob`
+    /// (relative to the watched path), or the timeout passes.
+    async fn wait_for_changes(
+        &self,
+        context: &ToolExecutionContext,
+        parsed: &WatchArgs,
+    ) -> Result<Value> {
+        let root = self.resolve_root(context, parsed.path.as_deref())?;
+        let globs = parsed
+            .glob
+            .as_ref()
+            .map(|glob| compile_globs(std::slice::from_ref(glob)))
+            .transpose()
+            .map_err(|err| Error::Tool(err.to_string()))?;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = FileWatcher::start(&root, &self.watcher_config, move |batch| {
+            tx.send(batch).is_ok()
+        })
+        .map_err(|err| Error::Tool(format!("failed to start watcher: {err}")))?;
+
+        let deadline =
+            tokio::time::Instant::now() + std::time::Duration::from_millis(parsed.timeout_ms);
+        loop {
+            let batch = match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(batch)) => batch,
+                Ok(None) | Err(_) => {
+                    return Ok(json!({
+                        "operation": "wait",
+                        "path": root,
+                        "backend": watcher.backend(),
+                        "changed": false,
+                        "timed_out": true,
+                    }));
+                }
+            };
+            let batch = match globs.as_ref() {
+                Some(globs) => batch.filtered(globs),
+                None => batch,
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            let truncate = |mut paths: Vec<String>| {
+                paths.truncate(parsed.max_changes);
+                paths
+            };
+            return Ok(json!({
+                "operation": "wait",
+                "path": root,
+                "backend": watcher.backend(),
+                "changed": true,
+                "timed_out": false,
+                "created": truncate(batch.created),
+                "modified": truncate(batch.modified),
+                "deleted": truncate(batch.deleted),
+            }));
+        }
+    }
+
     fn diff_snapshots(
         before: &HashMap<String, FileFingerprint>,
         after: &HashMap<String, FileFingerprint>,
@@ -272,7 +355,7 @@ impl Tool for WatchTool {
     }
 
     fn description(&self) -> &str {
-        "Capture filesystem snapshots and report changes"
+        "Capture filesystem snapshots, report changes or wait for the next change"
     }
 
     fn schema(&self) -> &Value {
@@ -281,6 +364,14 @@ impl Tool for WatchTool {
 
     async fn execute(&self, args: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
         let parsed = self.parse_args(&args)?;
+        if parsed.operation == "wait" {
+            let payload = self.wait_for_changes(context, &parsed).await?;
+            return Ok(ToolResult {
+                success: true,
+                exit_code: Some(0),
+                output: payload.to_string(),
+            });
+        }
         let context = context.clone();
         let tool = self.clone();
         let current_snapshot = tokio::task::spawn_blocking(move || {
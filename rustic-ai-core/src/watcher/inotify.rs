@@ -0,0 +1,206 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{EventKind, INotifyWatcher, RecursiveMode, Watcher};
+
+use super::{ChangeKind, Debouncer, FileChangeSet, WatchFilter, STOP_CHECK_INTERVAL};
+use crate::error::{Error, Result};
+
+/// Follows the workspace through `notify`'s inotify watcher. Directories are
+/// watched one by one so ignored trees such as `target/` never use up watches.
+pub(super) struct InotifyBackend {
+    watcher: INotifyWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    max_dirs: usize,
+    dirs: HashSet<PathBuf>,
+    /// Files seen so far, so a directory moved away can report what it held.
+    files: BTreeSet<PathBuf>,
+}
+
+impl InotifyBackend {
+    pub(super) fn new(filter: &WatchFilter, max_dirs: usize) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let watcher = INotifyWatcher::new(tx, notify::Config::default())
+            .map_err(|err| Error::Io(std::io::Error::other(err.to_string())))?;
+        let mut backend = Self {
+            watcher,
+            events,
+            max_dirs,
+            dirs: HashSet::new(),
+            files: BTreeSet::new(),
+        };
+        backend.add_tree(filter, &filter.root.clone(), &mut |_| {})?;
+        Ok(backend)
+    }
+
+    /// Watches `dir` and every non-ignored directory below it, reporting the
+    /// files found so ones created before their directory was watched are not lost.
+    fn add_tree(
+        &mut self,
+        filter: &WatchFilter,
+        dir: &Path,
+        on_file: &mut dyn FnMut(PathBuf),
+    ) -> Result<()> {
+        let mut stack = vec![dir.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            if self.dirs.contains(&dir) {
+                continue;
+            }
+            if self.dirs.len() >= self.max_dirs {
+                return Err(Error::Config(format!(
+                    "workspace has more than {} directories; raise watcher.max_watched_dirs or add watcher.exclude rules",
+                    self.max_dirs
+                )));
+            }
+            if let Err(err) = self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                if matches!(err.kind, notify::ErrorKind::MaxFilesWatch) {
+                    return Err(Error::Io(std::io::Error::other(err.to_string())));
+                }
+                // The directory vanished or is unreadable; skip it.
+                continue;
+            }
+            self.dirs.insert(dir.clone());
+
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                if filter.is_ignored(&path, is_dir) {
+                    continue;
+                }
+                if is_dir {
+                    stack.push(path);
+                } else {
+                    self.files.insert(path.clone());
+                    on_file(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops watching `dir` and everything below it after it was moved away or
+    /// deleted, returning the files it held.
+    fn remove_tree(&mut self, dir: &Path) -> Vec<PathBuf> {
+        let stale = self
+            .dirs
+            .iter()
+            .filter(|path| path.starts_with(dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in stale {
+            self.dirs.remove(&path);
+            // notify drops watches of moved or deleted directories on its own.
+            let _ = self.watcher.unwatch(&path);
+        }
+        let gone = self
+            .files
+            .range(dir.to_path_buf()..)
+            .take_while(|path| path.starts_with(dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in &gone {
+            self.files.remove(path);
+        }
+        gone
+    }
+
+    /// Waits up to `timeout` for events, then drains everything queued.
+    fn next_events(&self, timeout: Duration) -> Vec<notify::Event> {
+        let first = match self.events.recv_timeout(timeout) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return Vec::new(),
+        };
+        std::iter::once(first)
+            .chain(self.events.try_iter())
+            .filter_map(|event| match event {
+                Ok(event) => Some(event),
+                Err(err) => {
+                    tracing::warn!(%err, "file watcher error");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn handle(&mut self, filter: &WatchFilter, debouncer: &mut Debouncer, event: notify::Event) {
+        if event.need_rescan() {
+            tracing::warn!("inotify queue overflowed; some file changes were missed");
+            return;
+        }
+        for path in event.paths {
+            match event.kind {
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    let is_dir = std::fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_dir());
+                    if filter.is_ignored(&path, is_dir) {
+                        continue;
+                    }
+                    if is_dir {
+                        let mut found = Vec::new();
+                        if let Err(err) = self.add_tree(filter, &path, &mut |file| found.push(file))
+                        {
+                            tracing::warn!(%err, "file watcher could not follow new directory");
+                        }
+                        for file in found {
+                            debouncer.record(file, ChangeKind::Created);
+                        }
+                    } else {
+                        self.files.insert(path.clone());
+                        debouncer.record(path, ChangeKind::Created);
+                    }
+                }
+                EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    if self.dirs.contains(&path) {
+                        for file in self.remove_tree(&path) {
+                            debouncer.record(file, ChangeKind::Deleted);
+                        }
+                    } else if self.files.remove(&path) {
+                        debouncer.record(path, ChangeKind::Deleted);
+                    }
+                }
+                EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any)
+                | EventKind::Access(AccessKind::Close(AccessMode::Write))
+                    if !self.dirs.contains(&path) && !filter.is_ignored(&path, false) =>
+                {
+                    debouncer.record(path, ChangeKind::Modified);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(super) fn run<F>(
+        mut self,
+        filter: &WatchFilter,
+        debounce: Duration,
+        stop: &AtomicBool,
+        mut sink: F,
+    ) where
+        F: FnMut(FileChangeSet) -> bool,
+    {
+        let mut debouncer = Debouncer::new(debounce);
+        while !stop.load(Ordering::Relaxed) {
+            let wait = debouncer
+                .remaining(Instant::now())
+                .map_or(STOP_CHECK_INTERVAL, |remaining| {
+                    remaining.min(STOP_CHECK_INTERVAL)
+                });
+            for event in self.next_events(wait) {
+                self.handle(filter, &mut debouncer, event);
+            }
+
+            if debouncer.remaining(Instant::now()) == Some(Duration::ZERO) {
+                let batch = debouncer.take(filter);
+                if !batch.is_empty() && !sink(batch) {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Debounced workspace file watching.
+//!
+//! On Linux a background thread follows the workspace through inotify, adding
+//! watches for new directories as they appear; elsewhere (or when inotify is
+//! unavailable) it falls back to periodic metadata scans. Both backends skip
+//! paths ignored by the workspace `.gitignore` plus `watcher.exclude`, coalesce
+//! bursts of events per path and hand each quiet batch to a sink.
+
+#[cfg(target_os = "linux")]
+mod inotify;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+
+use crate::config::schema::WatcherConfig;
+use crate::error::{Error, Result};
+use crate::events::Event;
+use crate::rules::gitignore::GitignoreMatcher;
+
+/// How often the backends check the stop flag while idle.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+/// A batch is flushed after this many debounce periods even if events keep coming.
+const MAX_BATCH_DEBOUNCES: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// One debounced batch of changes; paths are relative to `root` and sorted.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileChangeSet {
+    pub root: PathBuf,
+    pub created: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl FileChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.created.len() + self.modified.len() + self.deleted.len()
+    }
+
+    /// Created, modified and deleted paths in that order.
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.created
+            .iter()
+            .chain(self.modified.iter())
+            .chain(self.deleted.iter())
+    }
+
+    pub fn into_event(self) -> Event {
+        Event::FilesChanged {
+            root: self.root.display().to_string(),
+            created: self.created,
+            modified: self.modified,
+            deleted: self.deleted,
+        }
+    }
+
+    /// Keeps only the paths matched by `globs`.
+    pub fn filtered(&self, globs: &GlobSet) -> Self {
+        let keep = |paths: &[String]| {
+            paths
+                .iter()
+                .filter(|path| globs.is_match(path.as_str()))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        Self {
+            root: self.root.clone(),
+            created: keep(&self.created),
+            modified: keep(&self.modified),
+            deleted: keep(&self.deleted),
+        }
+    }
+}
+
+/// Compiles watcher globs such as `src/**/*.rs` into one matcher.
+pub fn compile_globs(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|err| Error::Validation(format!("invalid glob '{pattern}': {err}")))?,
+        );
+    }
+    builder
+        .build()
+        .map_err(|err| Error::Validation(format!("invalid glob set: {err}")))
+}
+
+/// Ignore rules applied to every watched path.
+#[derive(Debug, Clone)]
+struct WatchFilter {
+    root: PathBuf,
+    matcher: GitignoreMatcher,
+}
+
+impl WatchFilter {
+    fn new(root: &Path, exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            matcher: GitignoreMatcher::from_root(root)?.with_patterns(exclude),
+        })
+    }
+
+    /// Checks the path and each ancestor directory below the root, since
+    /// directory-only rules never match the files inside them directly.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if path == self.root {
+            return false;
+        }
+        if self.matcher.is_ignored(path, is_dir) {
+            return true;
+        }
+        path.ancestors()
+            .skip(1)
+            .take_while(|ancestor| *ancestor != self.root && ancestor.starts_with(&self.root))
+            .any(|ancestor| self.matcher.is_ignored(ancestor, true))
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Coalesces raw events per path until the workspace has been quiet for the
+/// debounce period.
+#[derive(Debug)]
+struct Debouncer {
+    debounce: Duration,
+    pending: BTreeMap<PathBuf, ChangeKind>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Debouncer {
+    fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            pending: BTreeMap::new(),
+            first: None,
+            last: None,
+        }
+    }
+
+    fn record(&mut self, path: PathBuf, kind: ChangeKind) {
+        use ChangeKind::{Created, Deleted, Modified};
+
+        let now = Instant::now();
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+        let merged = match (self.pending.get(&path).copied(), kind) {
+            (None, kind) => Some(kind),
+            (Some(Created), Deleted) => None,
+            (Some(Created), _) => Some(Created),
+            (Some(Deleted), Created | Modified) => Some(Modified),
+            (Some(_), Deleted) => Some(Deleted),
+            (Some(Modified), _) => Some(Modified),
+        };
+        match merged {
+            Some(kind) => {
+                self.pending.insert(path, kind);
+            }
+            None => {
+                self.pending.remove(&path);
+            }
+        }
+    }
+
+    /// Time until the pending batch is due, or `None` when nothing is pending.
+    fn remaining(&self, now: Instant) -> Option<Duration> {
+        let (first, last) = (self.first?, self.last?);
+        let quiet_at = last + self.debounce;
+        let cap_at = first + self.debounce * MAX_BATCH_DEBOUNCES;
+        Some(quiet_at.min(cap_at).saturating_duration_since(now))
+    }
+
+    fn take(&mut self, filter: &WatchFilter) -> FileChangeSet {
+        let mut batch = FileChangeSet {
+            root: filter.root.clone(),
+            ..FileChangeSet::default()
+        };
+        for (path, kind) in std::mem::take(&mut self.pending) {
+            let relative = filter.relative(&path);
+            match kind {
+                ChangeKind::Created => batch.created.push(relative),
+                ChangeKind::Modified => batch.modified.push(relative),
+                ChangeKind::Deleted => batch.deleted.push(relative),
+            }
+        }
+        self.first = None;
+        self.last = None;
+        batch
+    }
+}
+
+/// Handle for a running watcher; the background thread stops when it is dropped.
+#[derive(Debug)]
+pub struct FileWatcher {
+    root: PathBuf,
+    backend: &'static str,
+    stop: Arc<AtomicBool>,
+}
+
+impl FileWatcher {
+    /// Starts watching `root`. `sink` receives each non-empty batch and returns
+    /// `false` to stop the watcher.
+    pub fn start<F>(root: &Path, config: &WatcherConfig, sink: F) -> Result<Self>
+    where
+        F: FnMut(FileChangeSet) -> bool + Send + 'static,
+    {
+        let root = std::fs::canonicalize(root).map_err(|err| {
+            Error::Config(format!(
+                "failed to resolve watch root '{}': {err}",
+                root.display()
+            ))
+        })?;
+        let filter = WatchFilter::new(&root, &config.exclude)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let debounce = Duration::from_millis(config.debounce_ms);
+
+        #[cfg(target_os = "linux")]
+        match inotify::InotifyBackend::new(&filter, config.max_watched_dirs) {
+            Ok(backend) => {
+                let thread_stop = stop.clone();
+                spawn_thread(move || backend.run(&filter, debounce, &thread_stop, sink))?;
+                return Ok(Self {
+                    root,
+                    backend: "inotify",
+                    stop,
+                });
+            }
+            Err(Error::Config(message)) => return Err(Error::Config(message)),
+            Err(err) => {
+                tracing::warn!(%err, "inotify unavailable; falling back to polling");
+            }
+        }
+
+        let poll_interval = Duration::from_millis(config.poll_interval_ms.max(1));
+        let max_dirs = config.max_watched_dirs;
+        let initial = scan(&filter, max_dirs)?;
+        let thread_stop = stop.clone();
+        spawn_thread(move || {
+            poll_loop(
+                &filter,
+                initial,
+                poll_interval,
+                debounce,
+                max_dirs,
+                &thread_stop,
+                sink,
+            )
+        })?;
+        Ok(Self {
+            root,
+            backend: "poll",
+            stop,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `inotify` or `poll`.
+    pub fn backend(&self) -> &'static str {
+        self.backend
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn spawn_thread(body: impl FnOnce() + Send + 'static) -> Result<()> {
+    std::thread::Builder::new()
+        .name("rustic-ai-watcher".to_owned())
+        .spawn(body)
+        .map(|_| ())
+        .map_err(Error::Io)
+}
+
+type Fingerprint = (u64, Option<SystemTime>);
+
+/// Walks the workspace, returning a fingerprint per non-ignored file.
+fn scan(filter: &WatchFilter, max_dirs: usize) -> Result<HashMap<PathBuf, Fingerprint>> {
+    let mut files = HashMap::new();
+    let mut dirs = vec![filter.root.clone()];
+    let mut visited = 0usize;
+    while let Some(dir) = dirs.pop() {
+        visited += 1;
+        if visited > max_dirs {
+            return Err(Error::Config(format!(
+                "workspace has more than {max_dirs} directories; raise watcher.max_watched_dirs or add watcher.exclude rules"
+            )));
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if !filter.is_ignored(&path, true) {
+                    dirs.push(path);
+                }
+            } else if !filter.is_ignored(&path, false) {
+                if let Ok(metadata) = entry.metadata() {
+                    files.insert(path, (metadata.len(), metadata.modified().ok()));
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn poll_loop<F>(
+    filter: &WatchFilter,
+    mut previous: HashMap<PathBuf, Fingerprint>,
+    poll_interval: Duration,
+    debounce: Duration,
+    max_dirs: usize,
+    stop: &AtomicBool,
+    mut sink: F,
+) where
+    F: FnMut(FileChangeSet) -> bool,
+{
+    let mut debouncer = Debouncer::new(debounce);
+    let mut next_scan = Instant::now() + poll_interval;
+    while !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= next_scan {
+            next_scan = now + poll_interval;
+            match scan(filter, max_dirs) {
+                Ok(current) => {
+                    for (path, fingerprint) in &current {
+                        match previous.get(path) {
+                            None => debouncer.record(path.clone(), ChangeKind::Created),
+                            Some(old) if old != fingerprint => {
+                                debouncer.record(path.clone(), ChangeKind::Modified)
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    for path in previous.keys() {
+                        if !current.contains_key(path) {
+                            debouncer.record(path.clone(), ChangeKind::Deleted);
+                        }
+                    }
+                    previous = current;
+                }
+                Err(err) => tracing::warn!(%err, "file watcher scan failed"),
+            }
+        }
+
+        if debouncer.remaining(Instant::now()) == Some(Duration::ZERO) {
+            let batch = debouncer.take(filter);
+            if !batch.is_empty() && !sink(batch) {
+                return;
+            }
+        }
+
+        let now = Instant::now();
+        let mut wait = next_scan
+            .saturating_duration_since(now)
+            .min(STOP_CHECK_INTERVAL);
+        if let Some(remaining) = debouncer.remaining(now) {
+            wait = wait.min(remaining);
+        }
+        std::thread::sleep(wait.max(Duration::from_millis(10)));
+    }
+}
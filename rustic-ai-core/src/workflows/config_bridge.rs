@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::config::schema::{DynamicRoutingConfig, WorkflowsConfig};
+use crate::config::schema::{AgentPermissionMode, DynamicRoutingConfig, WorkflowsConfig};
 
 use super::executor::WorkflowExecutorConfig;
 
@@ -11,6 +11,7 @@ pub fn build_workflow_executor_config(
     dynamic_routing: DynamicRoutingConfig,
     todo_tracking_enabled: bool,
     project_id: Option<String>,
+    agent_permission_mode: AgentPermissionMode,
 ) -> WorkflowExecutorConfig {
     WorkflowExecutorConfig {
         max_recursion_depth: workflows_config.max_recursion_depth,
@@ -44,5 +45,6 @@ pub fn build_workflow_executor_config(
         dynamic_routing,
         todo_tracking_enabled,
         project_id,
+        agent_permission_mode,
     }
 }
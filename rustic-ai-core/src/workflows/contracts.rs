@@ -0,0 +1,132 @@
+use jsonschema::JSONSchema;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::types::WorkflowDefinition;
+use crate::error::{Error, Result};
+
+/// Fails when `schema` is not a compilable JSON Schema.
+pub fn check_schema(schema: &Value, subject: &str) -> Result<()> {
+    JSONSchema::compile(schema)
+        .map(|_| ())
+        .map_err(|err| Error::Validation(format!("{subject} has an invalid JSON schema: {err}")))
+}
+
+/// Validates `value` against `schema`, naming `subject` in the error.
+pub fn validate_against(schema: &Value, value: &Value, subject: &str) -> Result<()> {
+    let compiled = JSONSchema::compile(schema)
+        .map_err(|err| Error::Validation(format!("{subject} has an invalid JSON schema: {err}")))?;
+    if let Err(errors) = compiled.validate(value) {
+        let details = errors
+            .map(|err| format!("{}: {}", err.instance_path, err))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(Error::Validation(format!(
+            "{subject} violates its schema: {details}"
+        )));
+    }
+    Ok(())
+}
+
+pub fn validate_input(workflow: &WorkflowDefinition, input: &Value) -> Result<()> {
+    match &workflow.input_schema {
+        Some(schema) => validate_against(
+            schema,
+            input,
+            &format!("workflow '{}' input", workflow.name),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Binds caller-supplied `params` to the declared parameters: applies defaults,
+/// rejects unknown or missing required parameters and checks each value's schema.
+pub fn bind_parameters(workflow: &WorkflowDefinition, params: &Value) -> Result<Value> {
+    let provided = match params {
+        Value::Null => Map::new(),
+        Value::Object(map) => map.clone(),
+        other => {
+            return Err(Error::Validation(format!(
+                "workflow '{}' params must be an object, got {other}",
+                workflow.name
+            )))
+        }
+    };
+
+    if let Some(unknown) = provided
+        .keys()
+        .find(|name| !workflow.parameters.contains_key(*name))
+    {
+        return Err(Error::Validation(format!(
+            "workflow '{}' has no parameter '{unknown}'",
+            workflow.name
+        )));
+    }
+
+    let mut bound = Map::new();
+    for (name, parameter) in &workflow.parameters {
+        let value = match provided.get(name).or(parameter.default.as_ref()) {
+            Some(value) => value.clone(),
+            None if parameter.required => {
+                return Err(Error::Validation(format!(
+                    "workflow '{}' requires parameter '{name}'",
+                    workflow.name
+                )))
+            }
+            None => continue,
+        };
+        if let Some(schema) = &parameter.schema {
+            validate_against(
+                schema,
+                &value,
+                &format!("workflow '{}' parameter '{name}'", workflow.name),
+            )?;
+        }
+        bound.insert(name.clone(), value);
+    }
+    Ok(Value::Object(bound))
+}
+
+/// Resolves the declared outputs from the final run `outputs`, matching `from`
+/// against output keys (`step.<id>.result`) before treating it as a dotted path.
+/// Returns `None` when the workflow declares no output contract.
+pub fn resolve_declared_outputs(
+    workflow: &WorkflowDefinition,
+    outputs: &BTreeMap<String, Value>,
+    extract_path: impl Fn(&Value, &str) -> Option<Value>,
+) -> Result<Option<Value>> {
+    if workflow.outputs.is_empty() {
+        return Ok(None);
+    }
+
+    let outputs_root = serde_json::to_value(outputs)?;
+    let mut resolved = Map::new();
+    for (name, contract) in &workflow.outputs {
+        let from = contract.from.trim();
+        let value = match outputs
+            .get(from)
+            .cloned()
+            .or_else(|| extract_path(&outputs_root, from))
+        {
+            Some(value) => value,
+            None if contract.required => {
+                return Err(Error::Validation(format!(
+                    "workflow '{}' did not produce required output '{name}' (from '{}')",
+                    workflow.name, contract.from
+                )))
+            }
+            None => Value::Null,
+        };
+        if let Some(schema) = &contract.schema {
+            if contract.required || !value.is_null() {
+                validate_against(
+                    schema,
+                    &value,
+                    &format!("workflow '{}' output '{name}'", workflow.name),
+                )?;
+            }
+        }
+        resolved.insert(name.clone(), value);
+    }
+    Ok(Some(Value::Object(resolved)))
+}
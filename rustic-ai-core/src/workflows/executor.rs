@@ -1,14 +1,18 @@
+use super::contracts;
 use super::expressions::{
-    evaluate_expression_with_locals_and_options, evaluate_expression_with_options, is_truthy,
-    EvaluationOptions,
+    compare_for_sort, evaluate_expression_with_locals_and_options,
+    evaluate_expression_with_options, is_truthy, EvaluationOptions,
 };
+use super::json_path;
 use super::registry::WorkflowRegistry;
 use super::types::{
     ConditionClause, ConditionGroup, ConditionOperator, LogicalOperator, NullHandlingMode,
     WorkflowDefinition, WorkflowExecutionConfig, WorkflowStep, WorkflowStepKind,
 };
 use crate::agents::AgentCoordinator;
-use crate::config::schema::{DynamicRoutingConfig, WorkflowCompatibilityPreset};
+use crate::config::schema::{
+    AgentPermissionMode, DynamicRoutingConfig, WorkflowCompatibilityPreset,
+};
 use crate::conversation::session_manager::SessionManager;
 use crate::error::{Error, Result};
 use crate::events::Event;
@@ -17,8 +21,9 @@ use crate::tools::ToolManager;
 use futures::future::BoxFuture;
 use futures::stream::{self, StreamExt};
 use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -57,6 +62,8 @@ pub struct WorkflowExecutorConfig {
     pub dynamic_routing: DynamicRoutingConfig,
     pub todo_tracking_enabled: bool,
     pub project_id: Option<String>,
+    /// Mode of the agent that started the run; skill steps run with it.
+    pub agent_permission_mode: AgentPermissionMode,
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +152,8 @@ pub struct WorkflowExecutionResult {
     pub success: bool,
     pub outputs: BTreeMap<String, Value>,
     pub steps_executed: usize,
+    /// Declared workflow outputs, present when the definition has an output contract.
+    pub result: Option<Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -154,8 +163,39 @@ pub struct WorkflowRunRequest {
     pub session_id: String,
     pub agent_name: Option<String>,
     pub input: Value,
+    /// Template parameters bound against the workflow's declared `parameters`.
+    pub params: Value,
     pub recursion_depth: usize,
     pub workflow_stack: Vec<String>,
+    pub resume_from: Option<WorkflowResumePoint>,
+}
+
+/// Restarts a run at `step_id` with the outputs recorded before that step ran.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowResumePoint {
+    pub step_id: String,
+    pub outputs: BTreeMap<String, Value>,
+}
+
+/// Last step started by a top-level run together with the outputs visible to it.
+/// Written before every step so a failed run can be resumed from that step.
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowCheckpoint {
+    pub step_id: Option<String>,
+    pub outputs: BTreeMap<String, Value>,
+}
+
+/// Canned outputs used in place of live tool, skill, agent and wait steps during a
+/// dry run. Step ids take precedence over tool/skill/agent names; steps listed in
+/// `failures` report `success=false` so failure routing can be exercised.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowDryRunFixtures {
+    pub steps: BTreeMap<String, Value>,
+    pub tools: BTreeMap<String, Value>,
+    pub skills: BTreeMap<String, Value>,
+    pub agents: BTreeMap<String, Value>,
+    pub failures: BTreeSet<String>,
 }
 
 #[derive(Clone)]
@@ -165,6 +205,8 @@ pub struct WorkflowExecutor {
     agents: Arc<AgentCoordinator>,
     session_manager: Arc<SessionManager>,
     config: WorkflowExecutorConfig,
+    dry_run: Option<Arc<WorkflowDryRunFixtures>>,
+    checkpoint: Option<Arc<std::sync::Mutex<WorkflowCheckpoint>>>,
 }
 
 impl WorkflowExecutor {
@@ -181,9 +223,33 @@ impl WorkflowExecutor {
             agents,
             session_manager,
             config,
+            dry_run: None,
+            checkpoint: None,
         }
     }
 
+    /// Records the current step and outputs of top-level runs into `checkpoint`.
+    pub fn with_checkpoint(
+        mut self,
+        checkpoint: Arc<std::sync::Mutex<WorkflowCheckpoint>>,
+    ) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Mocks tool, skill, agent and wait steps from `fixtures` instead of running
+    /// them. Nested workflow steps inherit the dry run.
+    pub fn with_dry_run(mut self, fixtures: WorkflowDryRunFixtures) -> Self {
+        self.dry_run = Some(Arc::new(fixtures));
+        self
+    }
+
+    /// Runs skill steps with the permission mode of the agent starting the run.
+    pub fn with_agent_permission_mode(mut self, mode: AgentPermissionMode) -> Self {
+        self.config.agent_permission_mode = mode;
+        self
+    }
+
     fn outputs_root(outputs: &BTreeMap<String, Value>) -> Value {
         json!(outputs)
     }
@@ -214,13 +280,27 @@ impl WorkflowExecutor {
         outputs: &BTreeMap<String, Value>,
         step: &WorkflowStep,
         expression_options: EvaluationOptions,
+    ) -> Result<Value> {
+        Self::render_value_with_locals(value, outputs, &BTreeMap::new(), step, expression_options)
+    }
+
+    fn render_value_with_locals(
+        value: &Value,
+        outputs: &BTreeMap<String, Value>,
+        locals: &BTreeMap<String, Value>,
+        step: &WorkflowStep,
+        expression_options: EvaluationOptions,
     ) -> Result<Value> {
         match value {
             Value::String(text) => {
                 if Self::is_expression_candidate(text) {
                     let expression = Self::expression_from_template(text);
-                    match evaluate_expression_with_options(expression, outputs, expression_options)
-                    {
+                    match evaluate_expression_with_locals_and_options(
+                        expression,
+                        outputs,
+                        locals,
+                        expression_options,
+                    ) {
                         Ok(v) => Ok(v),
                         Err(err) => {
                             let mode = Self::expression_error_mode(step);
@@ -245,9 +325,10 @@ impl WorkflowExecutor {
             Value::Array(items) => {
                 let mut rendered_items = Vec::with_capacity(items.len());
                 for item in items {
-                    rendered_items.push(Self::render_value_with_outputs(
+                    rendered_items.push(Self::render_value_with_locals(
                         item,
                         outputs,
+                        locals,
                         step,
                         expression_options,
                     )?);
@@ -259,7 +340,13 @@ impl WorkflowExecutor {
                 for (key, item) in map {
                     rendered.insert(
                         key.clone(),
-                        Self::render_value_with_outputs(item, outputs, step, expression_options)?,
+                        Self::render_value_with_locals(
+                            item,
+                            outputs,
+                            locals,
+                            step,
+                            expression_options,
+                        )?,
                     );
                 }
                 Ok(Value::Object(rendered))
@@ -272,7 +359,7 @@ impl WorkflowExecutor {
         serde_json::from_str::<Value>(raw).unwrap_or_else(|_| Value::String(raw.to_owned()))
     }
 
-    fn step_kind_name(kind: WorkflowStepKind) -> &'static str {
+    pub(crate) fn step_kind_name(kind: WorkflowStepKind) -> &'static str {
         match kind {
             WorkflowStepKind::Tool => "tool",
             WorkflowStepKind::Skill => "skill",
@@ -283,10 +370,11 @@ impl WorkflowExecutor {
             WorkflowStepKind::Loop => "loop",
             WorkflowStepKind::Merge => "merge",
             WorkflowStepKind::Switch => "switch",
+            WorkflowStepKind::Transform => "transform",
         }
     }
 
-    fn is_expression_candidate(text: &str) -> bool {
+    pub(crate) fn is_expression_candidate(text: &str) -> bool {
         let trimmed = text.trim();
         if trimmed.is_empty() {
             return false;
@@ -324,7 +412,7 @@ impl WorkflowExecutor {
         false
     }
 
-    fn expression_from_template(text: &str) -> &str {
+    pub(crate) fn expression_from_template(text: &str) -> &str {
         let trimmed = text.trim();
         if trimmed.starts_with("${") && trimmed.ends_with('}') && trimmed.len() > 3 {
             &trimmed[2..trimmed.len() - 1]
@@ -1488,6 +1576,303 @@ impl WorkflowExecutor {
         }
     }
 
+    fn execute_transform_step(
+        &self,
+        step: &WorkflowStep,
+        outputs: &BTreeMap<String, Value>,
+        workflow_config: &EffectiveWorkflowConfig,
+    ) -> Result<Value> {
+        let expression_options = self.expression_options_for_step(step, workflow_config);
+        let mut current = match step.config.get("input") {
+            Some(input) => {
+                Self::render_value_with_outputs(input, outputs, step, expression_options)?
+            }
+            None => Value::Null,
+        };
+
+        if let Some(operations) = step.config.get("operations") {
+            let operations = operations.as_array().ok_or_else(|| {
+                Error::Tool(format!(
+                    "workflow transform step '{}' config.operations must be an array",
+                    step.id
+                ))
+            })?;
+            for (index, operation) in operations.iter().enumerate() {
+                current = Self::apply_transform_operation(
+                    step,
+                    index,
+                    operation,
+                    current,
+                    outputs,
+                    expression_options,
+                )?;
+            }
+        }
+
+        match step.config.get("template") {
+            Some(template) => {
+                let mut locals = BTreeMap::new();
+                locals.insert("value".to_owned(), current);
+                Self::render_value_with_locals(template, outputs, &locals, step, expression_options)
+            }
+            None => Ok(current),
+        }
+    }
+
+    fn apply_transform_operation(
+        step: &WorkflowStep,
+        index: usize,
+        operation: &Value,
+        current: Value,
+        outputs: &BTreeMap<String, Value>,
+        expression_options: EvaluationOptions,
+    ) -> Result<Value> {
+        let op = operation.get("op").and_then(Value::as_str).ok_or_else(|| {
+            Error::Tool(format!(
+                "workflow transform step '{}' operations[{}] missing 'op'",
+                step.id, index
+            ))
+        })?;
+        let required_str = |field: &str| -> Result<&str> {
+            operation.get(field).and_then(Value::as_str).ok_or_else(|| {
+                Error::Tool(format!(
+                    "workflow transform step '{}' operations[{}] ({}) missing '{}'",
+                    step.id, index, op, field
+                ))
+            })
+        };
+        let mut locals = BTreeMap::new();
+        locals.insert("value".to_owned(), current.clone());
+
+        match op {
+            "set" => {
+                let path = required_str("path")?;
+                let template = operation.get("value").cloned().unwrap_or(Value::Null);
+                let rendered = Self::render_value_with_locals(
+                    &template,
+                    outputs,
+                    &locals,
+                    step,
+                    expression_options,
+                )?;
+                let mut target = if current.is_null() {
+                    Value::Object(serde_json::Map::new())
+                } else {
+                    current
+                };
+                Self::set_transform_path(&mut target, path, rendered).map_err(|message| {
+                    Error::Tool(format!(
+                        "workflow transform step '{}' operations[{}] (set) {}",
+                        step.id, index, message
+                    ))
+                })?;
+                Ok(target)
+            }
+            "remove" => {
+                let path = required_str("path")?;
+                let mut target = current;
+                Self::remove_transform_path(&mut target, path);
+                Ok(target)
+            }
+            "project" => {
+                let path = required_str("path")?;
+                json_path::project(&current, path).map_err(|err| {
+                    Error::Tool(format!(
+                        "workflow transform step '{}' operations[{}] (project) failed: {err}",
+                        step.id, index
+                    ))
+                })
+            }
+            "map" | "filter" | "group" | "sort" => {
+                let items = match current {
+                    Value::Array(items) => items,
+                    Value::Null => Vec::new(),
+                    other => {
+                        return Err(Error::Tool(format!(
+                            "workflow transform step '{}' operations[{}] ({}) expects an array, got {}",
+                            step.id, index, op, other
+                        )));
+                    }
+                };
+                let item_variable = operation
+                    .get("item_variable")
+                    .and_then(Value::as_str)
+                    .unwrap_or("item");
+                let item_locals = |item: &Value, item_index: usize| {
+                    let mut item_locals = locals.clone();
+                    item_locals.insert(item_variable.to_owned(), item.clone());
+                    item_locals.insert("index".to_owned(), json!(item_index));
+                    item_locals
+                };
+                let evaluate = |expression: &str, item_locals: &BTreeMap<String, Value>| {
+                    evaluate_expression_with_locals_and_options(
+                        expression,
+                        outputs,
+                        item_locals,
+                        expression_options,
+                    )
+                    .map_err(|err| {
+                        Error::Tool(format!(
+                            "workflow transform step '{}' operations[{}] ({}) failed evaluating '{}': {err}",
+                            step.id, index, op, expression
+                        ))
+                    })
+                };
+
+                match op {
+                    "map" => {
+                        let template = operation.get("template");
+                        let expression = operation.get("expression").and_then(Value::as_str);
+                        if template.is_none() && expression.is_none() {
+                            return Err(Error::Tool(format!(
+                                "workflow transform step '{}' operations[{}] (map) requires 'template' or 'expression'",
+                                step.id, index
+                            )));
+                        }
+                        let mut mapped = Vec::with_capacity(items.len());
+                        for (item_index, item) in items.iter().enumerate() {
+                            let item_locals = item_locals(item, item_index);
+                            mapped.push(match (template, expression) {
+                                (Some(template), _) => Self::render_value_with_locals(
+                                    template,
+                                    outputs,
+                                    &item_locals,
+                                    step,
+                                    expression_options,
+                                )?,
+                                (None, Some(expression)) => evaluate(expression, &item_locals)?,
+                                (None, None) => Value::Null,
+                            });
+                        }
+                        Ok(Value::Array(mapped))
+                    }
+                    "filter" => {
+                        let expression = required_str("expression")?;
+                        let mut kept = Vec::new();
+                        for (item_index, item) in items.into_iter().enumerate() {
+                            if is_truthy(&evaluate(expression, &item_locals(&item, item_index))?) {
+                                kept.push(item);
+                            }
+                        }
+                        Ok(Value::Array(kept))
+                    }
+                    "group" => {
+                        let by = required_str("by")?;
+                        let mut groups = serde_json::Map::new();
+                        for (item_index, item) in items.into_iter().enumerate() {
+                            let key = evaluate(by, &item_locals(&item, item_index))?;
+                            let entry = groups
+                                .entry(Self::switch_key_from_value(&key))
+                                .or_insert_with(|| Value::Array(Vec::new()));
+                            if let Value::Array(bucket) = entry {
+                                bucket.push(item);
+                            }
+                        }
+                        Ok(Value::Object(groups))
+                    }
+                    _ => {
+                        let by = operation.get("by").and_then(Value::as_str);
+                        let descending = match operation
+                            .get("order")
+                            .and_then(Value::as_str)
+                            .unwrap_or("asc")
+                        {
+                            "asc" => false,
+                            "desc" => true,
+                            other => {
+                                return Err(Error::Tool(format!(
+                                    "workflow transform step '{}' operations[{}] (sort) has unsupported order '{}'; expected asc|desc",
+                                    step.id, index, other
+                                )));
+                            }
+                        };
+                        let mut keyed = Vec::with_capacity(items.len());
+                        for (item_index, item) in items.into_iter().enumerate() {
+                            let key = match by {
+                                Some(by) => evaluate(by, &item_locals(&item, item_index))?,
+                                None => item.clone(),
+                            };
+                            keyed.push((key, item));
+                        }
+                        keyed.sort_by(|(left, _), (right, _)| {
+                            let ordering = compare_for_sort(left, right);
+                            if descending {
+                                ordering.reverse()
+                            } else {
+                                ordering
+                            }
+                        });
+                        Ok(Value::Array(
+                            keyed.into_iter().map(|(_, item)| item).collect(),
+                        ))
+                    }
+                }
+            }
+            other => Err(Error::Tool(format!(
+                "workflow transform step '{}' operations[{}] has unsupported op '{}'",
+                step.id, index, other
+            ))),
+        }
+    }
+
+    fn set_transform_path(
+        target: &mut Value,
+        path: &str,
+        value: Value,
+    ) -> std::result::Result<(), String> {
+        let segments = path
+            .split('.')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        let Some((last, parents)) = segments.split_last() else {
+            *target = value;
+            return Ok(());
+        };
+
+        let mut current = target;
+        for segment in parents {
+            let Value::Object(map) = current else {
+                return Err(format!(
+                    "cannot set '{}' because '{}' is not inside an object",
+                    path, segment
+                ));
+            };
+            current = map
+                .entry((*segment).to_owned())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+        }
+
+        let Value::Object(map) = current else {
+            return Err(format!(
+                "cannot set '{}' because its parent is not an object",
+                path
+            ));
+        };
+        map.insert((*last).to_owned(), value);
+        Ok(())
+    }
+
+    fn remove_transform_path(target: &mut Value, path: &str) {
+        let segments = path
+            .split('.')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        let Some((last, parents)) = segments.split_last() else {
+            return;
+        };
+
+        let mut current = target;
+        for segment in parents {
+            match current.get_mut(*segment) {
+                Some(next) => current = next,
+                None => return,
+            }
+        }
+        if let Value::Object(map) = current {
+            map.remove(*last);
+        }
+    }
+
     fn map_named_outputs(
         step: &WorkflowStep,
         result: &Value,
@@ -1537,6 +1922,7 @@ impl WorkflowExecutor {
             success,
             outputs,
             steps_executed: step_count,
+            result: None,
         }
     }
 
@@ -1698,22 +2084,44 @@ impl WorkflowExecutor {
 
     fn finalize_current_run(
         event_tx: &mpsc::Sender<Event>,
-        request: &WorkflowRunRequest,
+        workflow: &WorkflowDefinition,
         success: bool,
         step_count: usize,
         retry_events: usize,
         timeout_events: usize,
         outputs: BTreeMap<String, Value>,
-    ) -> WorkflowExecutionResult {
-        Self::finalize_workflow_result(
+    ) -> Result<WorkflowExecutionResult> {
+        let declared = if success {
+            match contracts::resolve_declared_outputs(workflow, &outputs, Self::extract_path) {
+                Ok(declared) => declared,
+                Err(err) => {
+                    Self::finalize_workflow_result(
+                        event_tx,
+                        &workflow.name,
+                        false,
+                        step_count,
+                        retry_events,
+                        timeout_events,
+                        outputs,
+                    );
+                    return Err(err);
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut result = Self::finalize_workflow_result(
             event_tx,
-            &request.workflow_name,
+            &workflow.name,
             success,
             step_count,
             retry_events,
             timeout_events,
             outputs,
-        )
+        );
+        result.result = declared;
+        Ok(result)
     }
 
     fn resolve_step_or_error(
@@ -1800,6 +2208,48 @@ impl WorkflowExecutor {
         )))
     }
 
+    fn execute_dry_run_step(
+        &self,
+        fixtures: &WorkflowDryRunFixtures,
+        step_ctx: &StepExecutionContext<'_>,
+        outputs: &BTreeMap<String, Value>,
+    ) -> Result<(bool, Value)> {
+        let step = step_ctx.step;
+        let (target_key, template_key, by_name) = match step.kind {
+            WorkflowStepKind::Tool => ("tool", "args", Some(&fixtures.tools)),
+            WorkflowStepKind::Skill => ("skill", "input", Some(&fixtures.skills)),
+            WorkflowStepKind::Agent => ("agent", "input", Some(&fixtures.agents)),
+            _ => ("", "", None),
+        };
+        let target = step.config.get(target_key).and_then(Value::as_str);
+
+        // Render templates anyway so broken expressions surface during the dry run.
+        let rendered = match step.config.get(template_key) {
+            Some(template) => Self::render_value_with_outputs(
+                template,
+                outputs,
+                step,
+                self.expression_options_for_step(step, step_ctx.workflow_config),
+            )?,
+            None => Value::Null,
+        };
+
+        let payload = fixtures
+            .steps
+            .get(&step.id)
+            .or_else(|| by_name.zip(target).and_then(|(map, name)| map.get(name)))
+            .cloned()
+            .unwrap_or_else(|| {
+                json!({
+                    "dry_run": true,
+                    "kind": Self::step_kind_name(step.kind),
+                    "target": target,
+                    "rendered": rendered,
+                })
+            });
+        Ok((!fixtures.failures.contains(&step.id), payload))
+    }
+
     async fn execute_tool_step(
         &self,
         request: &WorkflowRunRequest,
@@ -1989,6 +2439,7 @@ impl WorkflowExecutor {
                     &SkillExecutionContext {
                         working_directory: self.config.working_directory.clone(),
                         environment: Default::default(),
+                        agent_permission_mode: self.config.agent_permission_mode,
                     },
                 )
                 .await;
@@ -2462,6 +2913,15 @@ impl WorkflowExecutor {
             step_ctx.step,
             self.expression_options_for_step(step_ctx.step, step_ctx.workflow_config),
         )?;
+        let nested_params = match step_ctx.step.config.get("params") {
+            Some(template) => Self::render_value_with_outputs(
+                template,
+                outputs,
+                step_ctx.step,
+                self.expression_options_for_step(step_ctx.step, step_ctx.workflow_config),
+            )?,
+            None => Value::Null,
+        };
 
         let (retry_count, retry_backoff_ms, retry_multiplier, retry_backoff_max_ms) =
             Self::retry_settings(step_ctx.step, step_ctx.workflow_config);
@@ -2480,8 +2940,10 @@ impl WorkflowExecutor {
                         session_id: request.session_id.clone(),
                         agent_name: request.agent_name.clone(),
                         input: nested_input.clone(),
+                        params: nested_params.clone(),
                         recursion_depth: request.recursion_depth + 1,
                         workflow_stack: workflow_stack.to_vec(),
+                        resume_from: None,
                     },
                     tools,
                     step_ctx.event_tx.clone(),
@@ -2490,8 +2952,11 @@ impl WorkflowExecutor {
 
             match nested {
                 Ok(nested) => {
-                    let payload =
+                    let mut payload =
                         json!({"outputs": nested.outputs, "steps_executed": nested.steps_executed});
+                    if let Some(result) = nested.result {
+                        payload["result"] = result;
+                    }
                     if nested.success || attempt >= retry_count {
                         break (nested.success, payload);
                     }
@@ -2679,9 +3144,15 @@ impl WorkflowExecutor {
             }
         }
 
-        if request.workflow_stack.contains(&request.workflow_name) {
+        // Versions of the same workflow share a name, so `a@1 -> a@2` is still a cycle.
+        let (workflow_name, _) = WorkflowRegistry::parse_reference(&request.workflow_name);
+        if request
+            .workflow_stack
+            .iter()
+            .any(|entry| entry == workflow_name)
+        {
             let mut cycle_chain = request.workflow_stack.clone();
-            cycle_chain.push(request.workflow_name.clone());
+            cycle_chain.push(workflow_name.to_owned());
             return Err(Error::Tool(format!(
                 "workflow recursion cycle detected: {}",
                 cycle_chain.join(" -> ")
@@ -2689,7 +3160,7 @@ impl WorkflowExecutor {
         }
 
         let mut workflow_stack = request.workflow_stack.clone();
-        workflow_stack.push(request.workflow_name.clone());
+        workflow_stack.push(workflow_name.to_owned());
         Ok(workflow_stack)
     }
 
@@ -2740,11 +3211,23 @@ impl WorkflowExecutor {
                 .unwrap_or(self.config.default_timeout_seconds);
             let started_at = Instant::now();
             let workflow_stack = Self::validate_recursion_and_cycle(&request, &workflow_config)?;
-            let mut current = Self::resolve_entry_step(&workflow, &request)?;
+            let (mut current, mut outputs) = match &request.resume_from {
+                Some(resume) => (resume.step_id.clone(), resume.outputs.clone()),
+                None => (
+                    Self::resolve_entry_step(&workflow, &request)?,
+                    BTreeMap::<String, Value>::new(),
+                ),
+            };
             let by_id = Self::build_step_index(&workflow);
 
-            let mut outputs = BTreeMap::<String, Value>::new();
-            outputs.insert("input".to_owned(), request.input.clone());
+            contracts::validate_input(&workflow, &request.input)?;
+            outputs
+                .entry("input".to_owned())
+                .or_insert_with(|| request.input.clone());
+            if !workflow.parameters.is_empty() || !request.params.is_null() {
+                let params = contracts::bind_parameters(&workflow, &request.params)?;
+                outputs.insert("params".to_owned(), params);
+            }
             let mut step_count = 0usize;
             let mut retry_events = 0usize;
             let mut timeout_events = 0usize;
@@ -2789,6 +3272,14 @@ impl WorkflowExecutor {
                 step_count += 1;
 
                 let step = Self::resolve_step_or_error(&by_id, &request.workflow_name, &current)?;
+                if request.recursion_depth == 0 {
+                    if let Some(checkpoint) = &self.checkpoint {
+                        if let Ok(mut guard) = checkpoint.lock() {
+                            guard.step_id = Some(step.id.clone());
+                            guard.outputs = outputs.clone();
+                        }
+                    }
+                }
                 let timeout_ctx = Self::build_timeout_context(
                     started_at,
                     workflow_timeout_seconds,
@@ -2807,6 +3298,15 @@ impl WorkflowExecutor {
                 );
 
                 let step_result = match step.kind {
+                    WorkflowStepKind::Tool
+                    | WorkflowStepKind::Skill
+                    | WorkflowStepKind::Agent
+                    | WorkflowStepKind::Wait
+                        if self.dry_run.is_some() =>
+                    {
+                        let fixtures = self.dry_run.clone().unwrap_or_default();
+                        self.execute_dry_run_step(&fixtures, &step_ctx, &outputs)?
+                    }
                     WorkflowStepKind::Tool => {
                         self.execute_tool_step(&request, &step_ctx, &outputs, tools, &mut counters)
                             .await?
@@ -2842,15 +3342,15 @@ impl WorkflowExecutor {
                         if Self::advance_to_target(&mut current, target) {
                             continue;
                         }
-                        return Ok(Self::finalize_current_run(
+                        return Self::finalize_current_run(
                             &event_tx,
-                            &request,
+                            &workflow,
                             matched,
                             step_count,
                             retry_events,
                             timeout_events,
                             outputs,
-                        ));
+                        );
                     }
                     WorkflowStepKind::Wait => {
                         self.execute_wait_step(&step_ctx, &outputs, &mut counters)
@@ -2865,6 +3365,10 @@ impl WorkflowExecutor {
                         true,
                         self.execute_merge_step(&step, &outputs, &workflow_config)?,
                     ),
+                    WorkflowStepKind::Transform => (
+                        true,
+                        self.execute_transform_step(&step, &outputs, &workflow_config)?,
+                    ),
                     WorkflowStepKind::Switch => {
                         let switch_result = self
                             .execute_switch_step(&step_ctx, &outputs, &mut counters)
@@ -2880,15 +3384,15 @@ impl WorkflowExecutor {
                         if Self::advance_to_target(&mut current, target) {
                             continue;
                         }
-                        return Ok(Self::finalize_current_run(
+                        return Self::finalize_current_run(
                             &event_tx,
-                            &request,
+                            &workflow,
                             true,
                             step_count,
                             retry_events,
                             timeout_events,
                             outputs,
-                        ));
+                        );
                     }
                     WorkflowStepKind::Agent => {
                         self.execute_agent_step(&request, &step_ctx, &outputs, &mut counters)
@@ -2911,15 +3415,15 @@ impl WorkflowExecutor {
                     continue;
                 }
 
-                return Ok(Self::finalize_current_run(
+                return Self::finalize_current_run(
                     &event_tx,
-                    &request,
+                    &workflow,
                     success,
                     step_count,
                     retry_events,
                     timeout_events,
                     outputs,
-                ));
+                );
             }
         })
     }
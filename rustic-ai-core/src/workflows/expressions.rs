@@ -1,6 +1,12 @@
+use super::json_path;
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
 use regex::Regex;
 use serde_json::{Map, Number, Value};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::fmt::Write;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -61,6 +67,10 @@ enum Expr {
         name: String,
         args: Vec<Expr>,
     },
+    Lambda {
+        params: Vec<String>,
+        body: Box<Expr>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +97,7 @@ enum Token {
     OrOr,
     Contains,
     Matches,
+    Arrow,
     Identifier(String),
     Number(f64),
     String(String),
@@ -149,6 +160,21 @@ pub fn evaluate_expression_with_locals_and_options(
     locals: &BTreeMap<String, Value>,
     options: EvaluationOptions,
 ) -> Result<Value, ExpressionError> {
+    let expr = parse_checked(expression, options)?;
+    let evaluator = Evaluator::new(outputs, locals);
+    evaluator.eval(&expr)
+}
+
+/// Parses `expression` without evaluating it, applying the same length and depth
+/// limits as evaluation. Used for static workflow validation.
+pub fn validate_expression(
+    expression: &str,
+    options: EvaluationOptions,
+) -> Result<(), ExpressionError> {
+    parse_checked(expression, options).map(|_| ())
+}
+
+fn parse_checked(expression: &str, options: EvaluationOptions) -> Result<Expr, ExpressionError> {
     if expression.len() > options.max_length {
         return Err(ExpressionError::Parse(format!(
             "expression exceeds max length {}",
@@ -167,9 +193,7 @@ pub fn evaluate_expression_with_locals_and_options(
             depth, options.max_depth
         )));
     }
-
-    let evaluator = Evaluator::new(outputs, locals);
-    evaluator.eval(&expr)
+    Ok(expr)
 }
 
 fn expression_depth(expr: &Expr) -> usize {
@@ -183,6 +207,7 @@ fn expression_depth(expr: &Expr) -> usize {
             let args_depth = args.iter().map(expression_depth).max().unwrap_or(0);
             1 + args_depth
         }
+        Expr::Lambda { body, .. } => 1 + expression_depth(body),
     }
 }
 
@@ -322,6 +347,11 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ExpressionError> {
                     tokens.push(Token::EqEq);
                     continue;
                 }
+                if matches!(chars.peek(), Some((_, '>'))) {
+                    chars.next();
+                    tokens.push(Token::Arrow);
+                    continue;
+                }
                 return Err(ExpressionError::Parse(
                     "unexpected '='; use '==' for comparison".to_owned(),
                 ));
@@ -569,6 +599,13 @@ impl Parser {
             }
             Token::Identifier(name) => {
                 self.index += 1;
+                if self.match_token(|t| matches!(t, Token::Arrow)) {
+                    let body = self.parse_expression()?;
+                    return Ok(Expr::Lambda {
+                        params: vec![name],
+                        body: Box::new(body),
+                    });
+                }
                 if self.match_token(|t| matches!(t, Token::LParen)) {
                     let mut args = Vec::new();
                     if !self.match_token(|t| matches!(t, Token::RParen)) {
@@ -589,6 +626,14 @@ impl Parser {
                 }
             }
             Token::LParen => {
+                if let Some((params, consumed)) = self.lambda_params_ahead() {
+                    self.index += consumed;
+                    let body = self.parse_expression()?;
+                    return Ok(Expr::Lambda {
+                        params,
+                        body: Box::new(body),
+                    });
+                }
                 self.index += 1;
                 let expr = self.parse_expression()?;
                 self.consume(
@@ -603,6 +648,32 @@ impl Parser {
         }
     }
 
+    /// Detects `(a, b) =>` lambda parameter lists without consuming tokens.
+    fn lambda_params_ahead(&self) -> Option<(Vec<String>, usize)> {
+        let mut offset = self.index + 1;
+        let mut params = Vec::new();
+        if !matches!(self.tokens.get(offset), Some(Token::RParen)) {
+            loop {
+                let Some(Token::Identifier(name)) = self.tokens.get(offset) else {
+                    return None;
+                };
+                params.push(name.clone());
+                offset += 1;
+                match self.tokens.get(offset) {
+                    Some(Token::Comma) => offset += 1,
+                    Some(Token::RParen) => break,
+                    _ => return None,
+                }
+            }
+        }
+        offset += 1;
+        if matches!(self.tokens.get(offset), Some(Token::Arrow)) {
+            Some((params, offset + 1 - self.index))
+        } else {
+            None
+        }
+    }
+
     fn match_binary_comparison_op(&mut self) -> Option<BinaryOp> {
         let token = self.peek()?.clone();
         let op = match token {
@@ -662,6 +733,22 @@ impl Parser {
     }
 }
 
+const HIGHER_ORDER_FUNCTIONS: [&str; 7] =
+    ["map", "filter", "find", "any", "all", "sort_by", "group_by"];
+
+enum Callback<'e> {
+    Lambda {
+        params: &'e [String],
+        body: &'e Expr,
+    },
+    Expression {
+        expression: String,
+        variable: String,
+    },
+    Field(String),
+    Identity,
+}
+
 struct Evaluator<'a> {
     outputs: &'a BTreeMap<String, Value>,
     locals: &'a BTreeMap<String, Value>,
@@ -715,12 +802,19 @@ impl<'a> Evaluator<'a> {
                 })
             }
             Expr::Call { name, args } => {
+                let lowered = name.to_ascii_lowercase();
+                if HIGHER_ORDER_FUNCTIONS.contains(&lowered.as_str()) {
+                    return self.call_higher_order(&lowered, args);
+                }
                 let evaluated = args
                     .iter()
                     .map(|arg| self.eval(arg))
                     .collect::<Result<Vec<_>, _>>()?;
                 self.call_function(name, &evaluated)
             }
+            Expr::Lambda { .. } => Err(ExpressionError::Evaluation(
+                "lambda expressions are only valid as function arguments".to_owned(),
+            )),
         }
     }
 
@@ -879,8 +973,6 @@ impl<'a> Evaluator<'a> {
                 let max = items.iter().filter_map(to_number).reduce(f64::max);
                 Ok(max.map(number_to_value).unwrap_or(Value::Null))
             }
-            "map" => self.map_function(args),
-            "filter" => self.filter_function(args),
             "keys" => {
                 let map = required_arg_as_object(&name, args, 0)?;
                 Ok(Value::Array(
@@ -927,6 +1019,174 @@ impl<'a> Evaluator<'a> {
                 };
                 Ok(Value::String(kind.to_owned()))
             }
+            "coalesce" => Ok(args
+                .iter()
+                .find(|value| !value.is_null())
+                .cloned()
+                .unwrap_or(Value::Null)),
+            "default" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                if value.is_null() {
+                    Ok(args.get(1).cloned().unwrap_or(Value::Null))
+                } else {
+                    Ok(value)
+                }
+            }
+            "format" => {
+                let template = required_arg_as_string(&name, args, 0)?;
+                Ok(Value::String(format_template(&template, &args[1..])?))
+            }
+            "pad_left" | "pad_right" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                let width = required_arg_as_number(&name, args, 1)?.max(0.0) as usize;
+                let fill = optional_arg_as_string(args, 2)
+                    .and_then(|fill| fill.chars().next())
+                    .unwrap_or(' ');
+                let missing = width.saturating_sub(text.chars().count());
+                let padding = std::iter::repeat_n(fill, missing).collect::<String>();
+                Ok(Value::String(if name == "pad_left" {
+                    format!("{padding}{text}")
+                } else {
+                    format!("{text}{padding}")
+                }))
+            }
+            "regex_capture" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                let regex = compile_regex(&required_arg_as_string(&name, args, 1)?)?;
+                Ok(regex
+                    .captures(&text)
+                    .map(|captures| captures_to_array(&captures))
+                    .unwrap_or(Value::Null))
+            }
+            "regex_capture_all" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                let regex = compile_regex(&required_arg_as_string(&name, args, 1)?)?;
+                Ok(Value::Array(
+                    regex
+                        .captures_iter(&text)
+                        .map(|captures| captures_to_array(&captures))
+                        .collect(),
+                ))
+            }
+            "regex_named" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                let regex = compile_regex(&required_arg_as_string(&name, args, 1)?)?;
+                let Some(captures) = regex.captures(&text) else {
+                    return Ok(Value::Null);
+                };
+                let named = regex
+                    .capture_names()
+                    .flatten()
+                    .map(|group| {
+                        let value = captures
+                            .name(group)
+                            .map(|found| Value::String(found.as_str().to_owned()))
+                            .unwrap_or(Value::Null);
+                        (group.to_owned(), value)
+                    })
+                    .collect::<Map<String, Value>>();
+                Ok(Value::Object(named))
+            }
+            "json_parse" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                serde_json::from_str::<Value>(&text).map_err(|err| {
+                    ExpressionError::Evaluation(format!("function 'json_parse' failed: {err}"))
+                })
+            }
+            "json_stringify" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let pretty = args.get(1).map(is_truthy).unwrap_or(false);
+                let rendered = if pretty {
+                    serde_json::to_string_pretty(&value)
+                } else {
+                    serde_json::to_string(&value)
+                };
+                rendered.map(Value::String).map_err(|err| {
+                    ExpressionError::Evaluation(format!("function 'json_stringify' failed: {err}"))
+                })
+            }
+            "json_path" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let path = required_arg_as_string(&name, args, 1)?;
+                json_path::project(&value, &path)
+            }
+            "base64_encode" => one_string_arg(&name, args, |v| {
+                Value::String(base64::engine::general_purpose::STANDARD.encode(v))
+            }),
+            "base64_decode" => {
+                let text = required_arg_as_string(&name, args, 0)?;
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(text.trim())
+                    .map_err(|err| {
+                        ExpressionError::Evaluation(format!(
+                            "function 'base64_decode' failed: {err}"
+                        ))
+                    })?;
+                String::from_utf8(bytes).map(Value::String).map_err(|err| {
+                    ExpressionError::Evaluation(format!(
+                        "function 'base64_decode' produced non-utf8 output: {err}"
+                    ))
+                })
+            }
+            "sha256" => one_string_arg(&name, args, |v| {
+                let mut hasher = Sha256::new();
+                hasher.update(v.as_bytes());
+                Value::String(format!("{:x}", hasher.finalize()))
+            }),
+            "uuid" => Ok(Value::String(uuid::Uuid::new_v4().to_string())),
+            "now" => Ok(datetime_to_value(Utc::now())),
+            "parse_date" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let format = optional_arg_as_string(args, 1);
+                Ok(datetime_to_value(parse_datetime(
+                    &name,
+                    &value,
+                    format.as_deref(),
+                )?))
+            }
+            "format_date" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let date = parse_datetime(&name, &value, None)?;
+                let format = required_arg_as_string(&name, args, 1)?;
+                let mut rendered = String::new();
+                write!(rendered, "{}", date.format(&format)).map_err(|_| {
+                    ExpressionError::Evaluation(format!(
+                        "function 'format_date' has invalid format '{format}'"
+                    ))
+                })?;
+                Ok(Value::String(rendered))
+            }
+            "date_add" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let date = parse_datetime(&name, &value, None)?;
+                let amount = required_arg_as_number(&name, args, 1)?;
+                let unit = optional_arg_as_string(args, 2).unwrap_or_else(|| "seconds".to_owned());
+                let millis = (amount * unit_in_millis(&unit)?).round();
+                let shifted = Duration::try_milliseconds(millis as i64)
+                    .and_then(|delta| date.checked_add_signed(delta))
+                    .ok_or_else(|| {
+                        ExpressionError::Evaluation(
+                            "function 'date_add' result is out of range".to_owned(),
+                        )
+                    })?;
+                Ok(datetime_to_value(shifted))
+            }
+            "date_diff" => {
+                let left = parse_datetime(&name, args.first().unwrap_or(&Value::Null), None)?;
+                let right = parse_datetime(&name, args.get(1).unwrap_or(&Value::Null), None)?;
+                let unit = optional_arg_as_string(args, 2).unwrap_or_else(|| "seconds".to_owned());
+                let millis = (left - right).num_milliseconds() as f64;
+                Ok(number_to_value(millis / unit_in_millis(&unit)?))
+            }
+            "to_unix" => {
+                let value = args.first().cloned().unwrap_or(Value::Null);
+                let date = parse_datetime(&name, &value, None)?;
+                Ok(number_to_value(date.timestamp_millis() as f64 / 1000.0))
+            }
+            "from_unix" => {
+                let seconds = required_arg_as_number(&name, args, 0)?;
+                Ok(datetime_to_value(datetime_from_unix(&name, seconds)?))
+            }
             _ => Err(ExpressionError::Evaluation(format!(
                 "unknown function '{}'",
                 name
@@ -934,45 +1194,187 @@ impl<'a> Evaluator<'a> {
         }
     }
 
-    fn map_function(&self, args: &[Value]) -> Result<Value, ExpressionError> {
-        let name = "map";
-        let items = required_arg_as_array(name, args, 0)?;
-        let expr = required_arg_as_string(name, args, 1)?;
-        let var = optional_arg_as_string(args, 2).unwrap_or_else(|| "item".to_owned());
-        let mut mapped = Vec::with_capacity(items.len());
-
-        for (index, item) in items.iter().enumerate() {
-            let mut locals = self.locals.clone();
-            locals.insert(var.clone(), item.clone());
-            locals.insert("index".to_owned(), number_to_value(index as f64));
-            mapped.push(evaluate_expression_with_locals(
-                &expr,
-                self.outputs,
-                &locals,
-            )?);
+    fn call_higher_order(&self, name: &str, args: &[Expr]) -> Result<Value, ExpressionError> {
+        let items_value = match args.first() {
+            Some(arg) => self.eval(arg)?,
+            None => {
+                return Err(ExpressionError::Evaluation(format!(
+                    "function '{}' expects argument 1",
+                    name
+                )));
+            }
+        };
+        let items = items_value.as_array().ok_or_else(|| {
+            ExpressionError::Evaluation(format!("function '{}' argument 1 must be array", name))
+        })?;
+        let callback = self.callback_arg(name, args)?;
+
+        match name {
+            "map" => {
+                let mut mapped = Vec::with_capacity(items.len());
+                for (index, item) in items.iter().enumerate() {
+                    mapped.push(self.invoke_callback(&callback, item, index)?);
+                }
+                Ok(Value::Array(mapped))
+            }
+            "filter" => {
+                let mut filtered = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    if is_truthy(&self.invoke_callback(&callback, item, index)?) {
+                        filtered.push(item.clone());
+                    }
+                }
+                Ok(Value::Array(filtered))
+            }
+            "find" => {
+                for (index, item) in items.iter().enumerate() {
+                    if is_truthy(&self.invoke_callback(&callback, item, index)?) {
+                        return Ok(item.clone());
+                    }
+                }
+                Ok(Value::Null)
+            }
+            "any" => {
+                for (index, item) in items.iter().enumerate() {
+                    if is_truthy(&self.invoke_callback(&callback, item, index)?) {
+                        return Ok(Value::Bool(true));
+                    }
+                }
+                Ok(Value::Bool(false))
+            }
+            "all" => {
+                for (index, item) in items.iter().enumerate() {
+                    if !is_truthy(&self.invoke_callback(&callback, item, index)?) {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            }
+            "group_by" => {
+                let mut groups = Map::new();
+                for (index, item) in items.iter().enumerate() {
+                    let key = value_to_string(&self.invoke_callback(&callback, item, index)?);
+                    let entry = groups
+                        .entry(key)
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(bucket) = entry {
+                        bucket.push(item.clone());
+                    }
+                }
+                Ok(Value::Object(groups))
+            }
+            _ => {
+                let descending = match args.get(2) {
+                    Some(arg) => match self.eval(arg)? {
+                        Value::String(order) if order.eq_ignore_ascii_case("desc") => true,
+                        Value::String(order) if order.eq_ignore_ascii_case("asc") => false,
+                        other => {
+                            return Err(ExpressionError::Evaluation(format!(
+                                "function 'sort_by' argument 3 must be 'asc' or 'desc', got {}",
+                                value_to_string(&other)
+                            )));
+                        }
+                    },
+                    None => false,
+                };
+                let mut keyed = Vec::with_capacity(items.len());
+                for (index, item) in items.iter().enumerate() {
+                    keyed.push((self.invoke_callback(&callback, item, index)?, item.clone()));
+                }
+                keyed.sort_by(|(left, _), (right, _)| {
+                    let ordering = compare_for_sort(left, right);
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                });
+                Ok(Value::Array(
+                    keyed.into_iter().map(|(_, item)| item).collect(),
+                ))
+            }
         }
-
-        Ok(Value::Array(mapped))
     }
 
-    fn filter_function(&self, args: &[Value]) -> Result<Value, ExpressionError> {
-        let name = "filter";
-        let items = required_arg_as_array(name, args, 0)?;
-        let expr = required_arg_as_string(name, args, 1)?;
-        let var = optional_arg_as_string(args, 2).unwrap_or_else(|| "item".to_owned());
-        let mut filtered = Vec::new();
-
-        for (index, item) in items.iter().enumerate() {
-            let mut locals = self.locals.clone();
-            locals.insert(var.clone(), item.clone());
-            locals.insert("index".to_owned(), number_to_value(index as f64));
-            let passed = evaluate_expression_with_locals(&expr, self.outputs, &locals)?;
-            if is_truthy(&passed) {
-                filtered.push(item.clone());
+    /// Resolves the callback argument of a higher-order function.
+    ///
+    /// Lambdas (`x => x.score`) are preferred. For backwards compatibility `map`,
+    /// `filter`, `find`, `any` and `all` still accept an expression string with an
+    /// optional variable name, while `sort_by` and `group_by` accept a field path.
+    fn callback_arg<'e>(
+        &self,
+        name: &str,
+        args: &'e [Expr],
+    ) -> Result<Callback<'e>, ExpressionError> {
+        match args.get(1) {
+            Some(Expr::Lambda { params, body }) => {
+                if params.len() > 2 {
+                    return Err(ExpressionError::Evaluation(format!(
+                        "function '{}' lambda accepts at most 2 parameters (item, index)",
+                        name
+                    )));
+                }
+                Ok(Callback::Lambda { params, body })
             }
+            Some(arg) => {
+                let text = as_string(&self.eval(arg)?).ok_or_else(|| {
+                    ExpressionError::Evaluation(format!(
+                        "function '{}' argument 2 must be a lambda or string",
+                        name
+                    ))
+                })?;
+                if matches!(name, "sort_by" | "group_by") {
+                    return Ok(Callback::Field(text));
+                }
+                let variable = match args.get(2) {
+                    Some(arg) => as_string(&self.eval(arg)?).unwrap_or_else(|| "item".to_owned()),
+                    None => "item".to_owned(),
+                };
+                Ok(Callback::Expression {
+                    expression: text,
+                    variable,
+                })
+            }
+            None if name == "sort_by" => Ok(Callback::Identity),
+            None => Err(ExpressionError::Evaluation(format!(
+                "function '{}' expects argument 2",
+                name
+            ))),
         }
+    }
 
-        Ok(Value::Array(filtered))
+    fn invoke_callback(
+        &self,
+        callback: &Callback<'_>,
+        item: &Value,
+        index: usize,
+    ) -> Result<Value, ExpressionError> {
+        match callback {
+            Callback::Lambda { params, body } => {
+                let mut locals = self.locals.clone();
+                if let Some(name) = params.first() {
+                    locals.insert(name.clone(), item.clone());
+                }
+                if let Some(name) = params.get(1) {
+                    locals.insert(name.clone(), number_to_value(index as f64));
+                }
+                Evaluator::new(self.outputs, &locals).eval(body)
+            }
+            Callback::Expression {
+                expression,
+                variable,
+            } => {
+                let mut locals = self.locals.clone();
+                locals.insert(variable.clone(), item.clone());
+                locals.insert("index".to_owned(), number_to_value(index as f64));
+                evaluate_expression_with_locals(expression, self.outputs, &locals)
+            }
+            Callback::Field(path) => Ok(path
+                .split('.')
+                .filter(|segment| !segment.is_empty())
+                .fold(item.clone(), |value, segment| get_segment(&value, segment))),
+            Callback::Identity => Ok(item.clone()),
+        }
     }
 
     fn root_value(&self) -> Value {
@@ -1042,6 +1444,192 @@ fn compare_values(left: &Value, right: &Value, op: BinaryOp) -> Result<Value, Ex
     )))
 }
 
+/// Orders values for sorting: nulls last, numbers and strings by value, mixed
+/// types by their rendered string form.
+pub fn compare_for_sort(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => value_to_string(left).cmp(&value_to_string(right)),
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex, ExpressionError> {
+    Regex::new(pattern)
+        .map_err(|err| ExpressionError::Evaluation(format!("invalid regex '{pattern}': {err}")))
+}
+
+fn captures_to_array(captures: &regex::Captures<'_>) -> Value {
+    Value::Array(
+        captures
+            .iter()
+            .map(|group| {
+                group
+                    .map(|found| Value::String(found.as_str().to_owned()))
+                    .unwrap_or(Value::Null)
+            })
+            .collect(),
+    )
+}
+
+fn format_template(template: &str, args: &[Value]) -> Result<String, ExpressionError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut next_positional = 0usize;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let mut key = String::new();
+                let mut closed = false;
+                for inner in chars.by_ref() {
+                    if inner == '}' {
+                        closed = true;
+                        break;
+                    }
+                    key.push(inner);
+                }
+                if !closed {
+                    return Err(ExpressionError::Evaluation(
+                        "function 'format' has unterminated '{' placeholder".to_owned(),
+                    ));
+                }
+                let key = key.trim();
+                let value = if key.is_empty() {
+                    next_positional += 1;
+                    args.get(next_positional - 1)
+                } else if let Ok(position) = key.parse::<usize>() {
+                    args.get(position)
+                } else {
+                    args.first().and_then(|named| named.get(key))
+                };
+                let value = value.ok_or_else(|| {
+                    ExpressionError::Evaluation(format!(
+                        "function 'format' placeholder '{{{key}}}' has no matching argument"
+                    ))
+                })?;
+                rendered.push_str(&value_to_string(value));
+            }
+            other => rendered.push(other),
+        }
+    }
+
+    Ok(rendered)
+}
+
+fn datetime_to_value(date: DateTime<Utc>) -> Value {
+    Value::String(date.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+fn datetime_from_unix(fn_name: &str, seconds: f64) -> Result<DateTime<Utc>, ExpressionError> {
+    Utc.timestamp_millis_opt((seconds * 1000.0).round() as i64)
+        .single()
+        .ok_or_else(|| {
+            ExpressionError::Evaluation(format!(
+                "function '{}' timestamp {} is out of range",
+                fn_name, seconds
+            ))
+        })
+}
+
+/// Parses RFC 3339, RFC 2822, `YYYY-MM-DD[ HH:MM:SS]` or unix seconds, or the
+/// explicit chrono `format` when given. Naive values are treated as UTC.
+fn parse_datetime(
+    fn_name: &str,
+    value: &Value,
+    format: Option<&str>,
+) -> Result<DateTime<Utc>, ExpressionError> {
+    if let Value::Number(number) = value {
+        let seconds = number.as_f64().unwrap_or_default();
+        return datetime_from_unix(fn_name, seconds);
+    }
+
+    let text = as_string(value).ok_or_else(|| {
+        ExpressionError::Evaluation(format!(
+            "function '{}' expects a date string or unix timestamp",
+            fn_name
+        ))
+    })?;
+    let text = text.trim();
+
+    let parsed = match format {
+        Some(format) => DateTime::parse_from_str(text, format)
+            .map(|date| date.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(text, format)
+                    .ok()
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(text, format)
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+            }),
+        None => DateTime::parse_from_rfc3339(text)
+            .or_else(|_| DateTime::parse_from_rfc2822(text))
+            .map(|date| date.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                    .iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+            })
+            .or_else(|| {
+                text.parse::<f64>()
+                    .ok()
+                    .and_then(|seconds| datetime_from_unix(fn_name, seconds).ok())
+            }),
+    };
+
+    parsed.ok_or_else(|| {
+        ExpressionError::Evaluation(format!(
+            "function '{}' could not parse date '{}'",
+            fn_name, text
+        ))
+    })
+}
+
+fn unit_in_millis(unit: &str) -> Result<f64, ExpressionError> {
+    Ok(match unit.to_ascii_lowercase().as_str() {
+        "ms" | "millisecond" | "milliseconds" => 1.0,
+        "s" | "second" | "seconds" => 1_000.0,
+        "m" | "minute" | "minutes" => 60_000.0,
+        "h" | "hour" | "hours" => 3_600_000.0,
+        "d" | "day" | "days" => 86_400_000.0,
+        "w" | "week" | "weeks" => 604_800_000.0,
+        other => {
+            return Err(ExpressionError::Evaluation(format!(
+                "unsupported time unit '{}'; expected ms|seconds|minutes|hours|days|weeks",
+                other
+            )));
+        }
+    })
+}
+
 fn contains_value(actual: &Value, expected: &Value) -> bool {
     match (actual, expected) {
         (Value::String(a), Value::String(b)) => a.contains(b),
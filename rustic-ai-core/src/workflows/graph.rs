@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::executor::WorkflowExecutor;
+use super::types::{WorkflowDefinition, WorkflowStep, WorkflowStepKind};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowGraphNode {
+    pub id: String,
+    pub label: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowGraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorkflowGraph {
+    pub name: String,
+    pub entrypoints: Vec<WorkflowGraphEdge>,
+    pub nodes: Vec<WorkflowGraphNode>,
+    pub edges: Vec<WorkflowGraphEdge>,
+}
+
+/// Lists every routing edge leaving `step`: `next`, `on_success`, `on_failure`
+/// and, for switch steps, exact/pattern case targets plus `default`.
+pub fn step_targets(step: &WorkflowStep) -> Vec<(String, String)> {
+    let mut targets = Vec::new();
+    for (label, target) in [
+        ("next", &step.next),
+        ("on_success", &step.on_success),
+        ("on_failure", &step.on_failure),
+    ] {
+        if let Some(target) = target {
+            targets.push((label.to_owned(), target.clone()));
+        }
+    }
+
+    if step.kind == WorkflowStepKind::Switch {
+        if let Some(cases) = step.config.get("cases").and_then(Value::as_object) {
+            for (key, target) in cases {
+                if let Some(target) = target.as_str() {
+                    targets.push((format!("case {key}"), target.to_owned()));
+                }
+            }
+        }
+        if let Some(pattern_cases) = step.config.get("pattern_cases").and_then(Value::as_array) {
+            for case in pattern_cases {
+                let pattern = case.get("pattern").and_then(Value::as_str).unwrap_or("?");
+                if let Some(target) = case.get("target").and_then(Value::as_str) {
+                    targets.push((format!("/{pattern}/"), target.to_owned()));
+                }
+            }
+        }
+        if let Some(target) = step.config.get("default").and_then(Value::as_str) {
+            targets.push(("default".to_owned(), target.to_owned()));
+        }
+    }
+
+    targets
+}
+
+pub fn build_workflow_graph(workflow: &WorkflowDefinition) -> WorkflowGraph {
+    let entrypoints = workflow
+        .entrypoints
+        .iter()
+        .map(|(name, entrypoint)| WorkflowGraphEdge {
+            from: name.clone(),
+            to: entrypoint.step.clone(),
+            label: "entrypoint".to_owned(),
+        })
+        .collect();
+
+    let nodes = workflow
+        .steps
+        .iter()
+        .map(|step| WorkflowGraphNode {
+            id: step.id.clone(),
+            label: node_label(step),
+            kind: WorkflowExecutor::step_kind_name(step.kind).to_owned(),
+        })
+        .collect();
+
+    let edges = workflow
+        .steps
+        .iter()
+        .flat_map(|step| {
+            step_targets(step)
+                .into_iter()
+                .map(|(label, to)| WorkflowGraphEdge {
+                    from: step.id.clone(),
+                    to,
+                    label,
+                })
+        })
+        .collect();
+
+    WorkflowGraph {
+        name: workflow.name.clone(),
+        entrypoints,
+        nodes,
+        edges,
+    }
+}
+
+pub fn render_dot(graph: &WorkflowGraph) -> String {
+    let mut lines = Vec::new();
+    lines.push(format!("digraph \"{}\" {{", escape_dot(&graph.name)));
+    lines.push("  rankdir=TB;".to_owned());
+
+    for entry in &graph.entrypoints {
+        lines.push(format!(
+            "  \"entry:{}\" [label=\"{}\", shape=circle];",
+            escape_dot(&entry.from),
+            escape_dot(&entry.from)
+        ));
+    }
+    for node in &graph.nodes {
+        lines.push(format!(
+            "  \"{}\" [label=\"{}\", shape={}];",
+            escape_dot(&node.id),
+            escape_dot(&node.label),
+            dot_shape(&node.kind)
+        ));
+    }
+    for entry in &graph.entrypoints {
+        lines.push(format!(
+            "  \"entry:{}\" -> \"{}\";",
+            escape_dot(&entry.from),
+            escape_dot(&entry.to)
+        ));
+    }
+    for edge in &graph.edges {
+        let style = if edge.label == "on_failure" {
+            ", style=dashed, color=red"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"{}];",
+            escape_dot(&edge.from),
+            escape_dot(&edge.to),
+            escape_dot(&edge.label),
+            style
+        ));
+    }
+
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
+
+pub fn render_mermaid(graph: &WorkflowGraph) -> String {
+    let node_id = |step_id: &str| -> String {
+        graph
+            .nodes
+            .iter()
+            .position(|node| node.id == step_id)
+            .map(|index| format!("s{index}"))
+            .unwrap_or_else(|| format!("missing_{}", sanitize_mermaid_id(step_id)))
+    };
+
+    let mut lines = Vec::new();
+    lines.push("flowchart TD".to_owned());
+
+    for (index, entry) in graph.entrypoints.iter().enumerate() {
+        lines.push(format!("  e{index}((\"{}\"))", escape_mermaid(&entry.from)));
+    }
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let label = escape_mermaid(&node.label);
+        let shape = match node.kind.as_str() {
+            "condition" | "switch" => format!("s{index}{{\"{label}\"}}"),
+            "workflow" => format!("s{index}[[\"{label}\"]]"),
+            "wait" => format!("s{index}([\"{label}\"])"),
+            _ => format!("s{index}[\"{label}\"]"),
+        };
+        lines.push(format!("  {shape}"));
+    }
+    for (index, entry) in graph.entrypoints.iter().enumerate() {
+        lines.push(format!("  e{index} --> {}", node_id(&entry.to)));
+    }
+    for edge in &graph.edges {
+        let arrow = if edge.label == "on_failure" {
+            "-.->"
+        } else {
+            "-->"
+        };
+        lines.push(format!(
+            "  {} {}|\"{}\"| {}",
+            node_id(&edge.from),
+            arrow,
+            escape_mermaid(&edge.label),
+            node_id(&edge.to)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn node_label(step: &WorkflowStep) -> String {
+    let kind = WorkflowExecutor::step_kind_name(step.kind);
+    let target_key = match step.kind {
+        WorkflowStepKind::Tool => Some("tool"),
+        WorkflowStepKind::Skill => Some("skill"),
+        WorkflowStepKind::Agent => Some("agent"),
+        WorkflowStepKind::Workflow => Some("workflow"),
+        _ => None,
+    };
+    let detail = target_key
+        .and_then(|key| step.config.get(key))
+        .and_then(Value::as_str)
+        .map(|name| format!("{kind}: {name}"))
+        .unwrap_or_else(|| kind.to_owned());
+    let title = if step.name.trim().is_empty() {
+        step.id.as_str()
+    } else {
+        step.name.as_str()
+    };
+    format!("{title}\n{detail}")
+}
+
+fn dot_shape(kind: &str) -> &'static str {
+    match kind {
+        "condition" | "switch" => "diamond",
+        "workflow" => "box3d",
+        "wait" => "ellipse",
+        _ => "box",
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(value: &str) -> String {
+    value.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+fn sanitize_mermaid_id(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect()
+}
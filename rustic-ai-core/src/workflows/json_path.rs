@@ -0,0 +1,243 @@
+use super::expressions::ExpressionError;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+enum PathSegment {
+    Field(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Wildcard,
+    Descendant(String),
+}
+
+impl PathSegment {
+    fn is_multi(&self) -> bool {
+        matches!(
+            self,
+            PathSegment::Wildcard | PathSegment::Slice(..) | PathSegment::Descendant(_)
+        )
+    }
+}
+
+/// Selects every value matched by a JSONPath-like `path`.
+///
+/// Supported syntax: `$`, `.field`, `['field']`, `[index]` (negative counts from
+/// the end), `[start:end]`, `*` / `[*]` and `..field` recursive descent.
+pub fn select(value: &Value, path: &str) -> Result<Vec<Value>, ExpressionError> {
+    let segments = parse_path(path)?;
+    let mut current = vec![value.clone()];
+    for segment in &segments {
+        let mut next = Vec::new();
+        for item in &current {
+            apply_segment(item, segment, &mut next);
+        }
+        current = next;
+    }
+    Ok(current)
+}
+
+/// Projects `path` out of `value`.
+///
+/// Paths without wildcards, slices or recursive descent resolve to a single value
+/// (`null` when missing); projecting paths always resolve to an array.
+pub fn project(value: &Value, path: &str) -> Result<Value, ExpressionError> {
+    let segments = parse_path(path)?;
+    let is_projection = segments.iter().any(PathSegment::is_multi);
+    let mut selected = select(value, path)?;
+    if is_projection {
+        Ok(Value::Array(selected))
+    } else {
+        Ok(selected.pop().unwrap_or(Value::Null))
+    }
+}
+
+fn apply_segment(value: &Value, segment: &PathSegment, out: &mut Vec<Value>) {
+    match segment {
+        PathSegment::Field(name) => match value {
+            Value::Object(map) => {
+                if let Some(found) = map.get(name) {
+                    out.push(found.clone());
+                }
+            }
+            Value::Array(items) => {
+                if let Ok(index) = name.parse::<usize>() {
+                    if let Some(found) = items.get(index) {
+                        out.push(found.clone());
+                    }
+                }
+            }
+            _ => {}
+        },
+        PathSegment::Index(index) => {
+            if let Value::Array(items) = value {
+                if let Some(resolved) = resolve_index(*index, items.len()) {
+                    out.push(items[resolved].clone());
+                }
+            }
+        }
+        PathSegment::Slice(start, end) => {
+            if let Value::Array(items) = value {
+                let len = items.len() as i64;
+                let clamp = |raw: i64| -> usize {
+                    let adjusted = if raw < 0 { len + raw } else { raw };
+                    adjusted.clamp(0, len) as usize
+                };
+                let from = start.map(clamp).unwrap_or(0);
+                let to = end.map(clamp).unwrap_or(items.len());
+                if from < to {
+                    out.extend(items[from..to].iter().cloned());
+                }
+            }
+        }
+        PathSegment::Wildcard => match value {
+            Value::Object(map) => out.extend(map.values().cloned()),
+            Value::Array(items) => out.extend(items.iter().cloned()),
+            _ => {}
+        },
+        PathSegment::Descendant(name) => collect_descendants(value, name, out),
+    }
+}
+
+fn collect_descendants(value: &Value, name: &str, out: &mut Vec<Value>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(found) = map.get(name) {
+                out.push(found.clone());
+            }
+            for child in map.values() {
+                collect_descendants(child, name, out);
+            }
+        }
+        Value::Array(items) => {
+            for child in items {
+                collect_descendants(child, name, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { len as i64 + index } else { index };
+    if resolved < 0 || resolved >= len as i64 {
+        None
+    } else {
+        Some(resolved as usize)
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, ExpressionError> {
+    let trimmed = path.trim();
+    let chars = trimmed.chars().collect::<Vec<_>>();
+    let mut segments = Vec::new();
+    let mut index = 0usize;
+
+    if chars.first() == Some(&'$') {
+        index = 1;
+    }
+
+    while index < chars.len() {
+        match chars[index] {
+            '.' => {
+                if chars.get(index + 1) == Some(&'.') {
+                    index += 2;
+                    let name = read_identifier(&chars, &mut index);
+                    if name.is_empty() {
+                        return Err(ExpressionError::Parse(format!(
+                            "json path '{path}' expects field name after '..'"
+                        )));
+                    }
+                    segments.push(PathSegment::Descendant(name));
+                } else {
+                    index += 1;
+                    if chars.get(index) == Some(&'*') {
+                        index += 1;
+                        segments.push(PathSegment::Wildcard);
+                        continue;
+                    }
+                    let name = read_identifier(&chars, &mut index);
+                    if name.is_empty() {
+                        return Err(ExpressionError::Parse(format!(
+                            "json path '{path}' expects field name after '.'"
+                        )));
+                    }
+                    segments.push(PathSegment::Field(name));
+                }
+            }
+            '[' => {
+                let close = chars[index..]
+                    .iter()
+                    .position(|ch| *ch == ']')
+                    .map(|offset| index + offset)
+                    .ok_or_else(|| {
+                        ExpressionError::Parse(format!("json path '{path}' has unterminated '['"))
+                    })?;
+                let inner = chars[index + 1..close]
+                    .iter()
+                    .collect::<String>()
+                    .trim()
+                    .to_owned();
+                segments.push(parse_bracket(&inner, path)?);
+                index = close + 1;
+            }
+            '*' => {
+                index += 1;
+                segments.push(PathSegment::Wildcard);
+            }
+            _ => {
+                let name = read_identifier(&chars, &mut index);
+                if name.is_empty() {
+                    return Err(ExpressionError::Parse(format!(
+                        "json path '{path}' has unexpected character '{}'",
+                        chars[index]
+                    )));
+                }
+                segments.push(PathSegment::Field(name));
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn read_identifier(chars: &[char], index: &mut usize) -> String {
+    let start = *index;
+    while *index < chars.len() && !matches!(chars[*index], '.' | '[' | ']') {
+        *index += 1;
+    }
+    chars[start..*index].iter().collect()
+}
+
+fn parse_bracket(inner: &str, path: &str) -> Result<PathSegment, ExpressionError> {
+    if inner == "*" {
+        return Ok(PathSegment::Wildcard);
+    }
+
+    if (inner.starts_with('\'') && inner.ends_with('\'')
+        || inner.starts_with('"') && inner.ends_with('"'))
+        && inner.len() >= 2
+    {
+        return Ok(PathSegment::Field(inner[1..inner.len() - 1].to_owned()));
+    }
+
+    if let Some((start, end)) = inner.split_once(':') {
+        let parse_bound = |raw: &str| -> Result<Option<i64>, ExpressionError> {
+            let raw = raw.trim();
+            if raw.is_empty() {
+                return Ok(None);
+            }
+            raw.parse::<i64>().map(Some).map_err(|err| {
+                ExpressionError::Parse(format!(
+                    "json path '{path}' has invalid slice bound '{raw}': {err}"
+                ))
+            })
+        };
+        return Ok(PathSegment::Slice(parse_bound(start)?, parse_bound(end)?));
+    }
+
+    inner.parse::<i64>().map(PathSegment::Index).map_err(|err| {
+        ExpressionError::Parse(format!(
+            "json path '{path}' has invalid index '{inner}': {err}"
+        ))
+    })
+}
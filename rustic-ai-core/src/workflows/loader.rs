@@ -1,3 +1,4 @@
+use super::contracts;
 use super::registry::WorkflowRegistry;
 use super::types::{ConditionClause, ConditionGroup, WorkflowDefinition, WorkflowStepKind};
 use crate::config::schema::WorkflowsConfig;
@@ -839,6 +840,80 @@ impl WorkflowLoader {
                     }
                 }
             }
+
+            if step.kind == WorkflowStepKind::Transform {
+                let has_body = ["input", "operations", "template"]
+                    .iter()
+                    .any(|key| step.config.get(*key).is_some());
+                if !has_body {
+                    return Err(Error::Validation(format!(
+                        "workflow '{}' transform step '{}' must define config.input, config.operations, or config.template",
+                        workflow.name, step.id
+                    )));
+                }
+
+                if let Some(operations) = step.config.get("operations") {
+                    let operations = operations.as_array().ok_or_else(|| {
+                        Error::Validation(format!(
+                            "workflow '{}' transform step '{}' config.operations must be an array",
+                            workflow.name, step.id
+                        ))
+                    })?;
+                    for (index, operation) in operations.iter().enumerate() {
+                        let op = operation
+                            .get("op")
+                            .and_then(|value| value.as_str())
+                            .ok_or_else(|| {
+                                Error::Validation(format!(
+                                    "workflow '{}' transform step '{}' operations[{}] missing string 'op'",
+                                    workflow.name, step.id, index
+                                ))
+                            })?;
+                        let required: &[&str] = match op {
+                            "set" | "remove" | "project" => &["path"],
+                            "filter" => &["expression"],
+                            "group" => &["by"],
+                            "map" | "sort" => &[],
+                            other => {
+                                return Err(Error::Validation(format!(
+                                    "workflow '{}' transform step '{}' operations[{}] has unsupported op '{}'; expected set|remove|project|map|filter|group|sort",
+                                    workflow.name, step.id, index, other
+                                )));
+                            }
+                        };
+                        for field in required {
+                            if operation
+                                .get(*field)
+                                .and_then(|value| value.as_str())
+                                .is_none()
+                            {
+                                return Err(Error::Validation(format!(
+                                    "workflow '{}' transform step '{}' operations[{}] ({}) missing string '{}'",
+                                    workflow.name, step.id, index, op, field
+                                )));
+                            }
+                        }
+                        if op == "map"
+                            && operation.get("template").is_none()
+                            && operation.get("expression").is_none()
+                        {
+                            return Err(Error::Validation(format!(
+                                "workflow '{}' transform step '{}' operations[{}] (map) requires 'template' or 'expression'",
+                                workflow.name, step.id, index
+                            )));
+                        }
+                        if let Some(order) = operation.get("order").and_then(|value| value.as_str())
+                        {
+                            if !matches!(order, "asc" | "desc") {
+                                return Err(Error::Validation(format!(
+                                    "workflow '{}' transform step '{}' operations[{}] has unsupported order '{}'; expected asc|desc",
+                                    workflow.name, step.id, index, order
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
         }
 
         for step in &workflow.steps {
@@ -931,9 +1006,55 @@ impl WorkflowLoader {
             }
         }
 
+        Self::validate_contracts(workflow)
+    }
+
+    fn validate_contracts(workflow: &WorkflowDefinition) -> Result<()> {
+        if workflow.name.contains('@') {
+            return Err(Error::Validation(format!(
+                "workflow '{}' name must not contain '@' (reserved for name@version references)",
+                workflow.name
+            )));
+        }
+        if let Some(schema) = &workflow.input_schema {
+            contracts::check_schema(schema, &format!("workflow '{}' input", workflow.name))?;
+        }
+        for (name, parameter) in &workflow.parameters {
+            let subject = format!("workflow '{}' parameter '{}'", workflow.name, name);
+            if let Some(schema) = &parameter.schema {
+                contracts::check_schema(schema, &subject)?;
+                if let Some(default) = &parameter.default {
+                    contracts::validate_against(schema, default, &format!("{subject} default"))?;
+                }
+            }
+        }
+        for (name, contract) in &workflow.outputs {
+            let subject = format!("workflow '{}' output '{}'", workflow.name, name);
+            if contract.from.trim().is_empty() {
+                return Err(Error::Validation(format!("{subject} must define 'from'")));
+            }
+            if let Some(schema) = &contract.schema {
+                contracts::check_schema(schema, &subject)?;
+            }
+        }
         Ok(())
     }
 
+    /// Parses a single workflow file against the definition schema without the
+    /// structural checks applied by [`WorkflowLoader::load`].
+    pub fn parse_definition_file(path: &Path) -> Result<WorkflowDefinition> {
+        Self::parse_file(path)
+    }
+
+    /// Runs the load-time structural checks for one definition.
+    pub fn check_definition(
+        workflow: &WorkflowDefinition,
+        file: &Path,
+        config: &WorkflowsConfig,
+    ) -> Result<()> {
+        Self::validate(workflow, file, config)
+    }
+
     pub fn load(config: &WorkflowsConfig, work_dir: &Path) -> Result<WorkflowRegistry> {
         let mut registry = WorkflowRegistry::new();
         let files = Self::discover_files(config, work_dir);
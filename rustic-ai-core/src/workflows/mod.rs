@@ -1,20 +1,32 @@
 mod config_bridge;
+pub mod contracts;
 pub mod executor;
 pub mod expressions;
+pub mod graph;
+pub mod json_path;
 pub mod loader;
 pub mod registry;
+pub mod runs;
 pub mod trigger;
 pub mod types;
+pub mod validation;
 
 pub use config_bridge::build_workflow_executor_config;
 pub use executor::{
-    WorkflowExecutionResult, WorkflowExecutor, WorkflowExecutorConfig, WorkflowRunRequest,
+    WorkflowCheckpoint, WorkflowDryRunFixtures, WorkflowExecutionResult, WorkflowExecutor,
+    WorkflowExecutorConfig, WorkflowResumePoint, WorkflowRunRequest,
 };
+pub use graph::{build_workflow_graph, WorkflowGraph};
 pub use loader::WorkflowLoader;
 pub use registry::WorkflowRegistry;
+pub use runs::{WorkflowRunRecord, WorkflowRunStatus, WorkflowRunStore};
 pub use trigger::{WorkflowTriggerEngine, WorkflowTriggerMatch, WorkflowTriggerReason};
 pub use types::{
     ConditionClause, ConditionGroup, ConditionOperator, LogicalOperator, NullHandlingMode,
-    WorkflowDefinition, WorkflowEntrypoint, WorkflowExecutionConfig, WorkflowStep,
-    WorkflowStepKind, WorkflowTriggerConfig,
+    WorkflowDefinition, WorkflowEntrypoint, WorkflowExecutionConfig, WorkflowOutputContract,
+    WorkflowParameter, WorkflowStep, WorkflowStepKind, WorkflowTriggerConfig,
+};
+pub use validation::{
+    validate_workflow, WorkflowIssue, WorkflowIssueSeverity, WorkflowValidationContext,
+    WorkflowValidationReport,
 };
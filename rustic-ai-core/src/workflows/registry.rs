@@ -1,9 +1,11 @@
 use super::types::WorkflowDefinition;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-#[derive(Default, Clone)]
+/// Workflows keyed by name, each holding every loaded version sorted oldest first.
+#[derive(Debug, Default, Clone)]
 pub struct WorkflowRegistry {
-    workflows: HashMap<String, WorkflowDefinition>,
+    workflows: HashMap<String, Vec<WorkflowDefinition>>,
 }
 
 impl WorkflowRegistry {
@@ -11,12 +13,29 @@ impl WorkflowRegistry {
         Self::default()
     }
 
+    /// Splits `name@version` into its parts; a bare name has no version.
+    pub fn parse_reference(reference: &str) -> (&str, Option<&str>) {
+        match reference.split_once('@') {
+            Some((name, version)) => (name.trim(), Some(version.trim())),
+            None => (reference.trim(), None),
+        }
+    }
+
     pub fn register(&mut self, workflow: WorkflowDefinition) {
-        self.workflows.insert(workflow.name.clone(), workflow);
+        let versions = self.workflows.entry(workflow.name.clone()).or_default();
+        versions.retain(|existing| existing.version != workflow.version);
+        versions.push(workflow);
+        versions.sort_by(|left, right| compare_versions(&left.version, &right.version));
     }
 
-    pub fn get(&self, name: &str) -> Option<&WorkflowDefinition> {
-        self.workflows.get(name)
+    /// Resolves `name` (latest version) or `name@version` (exact version).
+    pub fn get(&self, reference: &str) -> Option<&WorkflowDefinition> {
+        let (name, version) = Self::parse_reference(reference);
+        let versions = self.workflows.get(name)?;
+        match version {
+            Some(version) => versions.iter().find(|workflow| workflow.version == version),
+            None => versions.last(),
+        }
     }
 
     pub fn list(&self) -> Vec<String> {
@@ -24,4 +43,39 @@ impl WorkflowRegistry {
         names.sort();
         names
     }
+
+    pub fn versions(&self, name: &str) -> Vec<String> {
+        self.workflows
+            .get(name)
+            .map(|versions| {
+                versions
+                    .iter()
+                    .map(|workflow| workflow.version.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Orders dotted versions numerically per component (`1.10.0` > `1.9.2`), falling
+/// back to string comparison for non-numeric components.
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let mut left_parts = left.split(['.', '-']);
+    let mut right_parts = right.split(['.', '-']);
+    loop {
+        match (left_parts.next(), right_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(left), Some(right)) => {
+                let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+                    (Ok(left), Ok(right)) => left.cmp(&right),
+                    _ => left.cmp(right),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::executor::WorkflowResumePoint;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRunRecord {
+    pub id: String,
+    pub workflow: String,
+    pub entrypoint: String,
+    pub status: WorkflowRunStatus,
+    pub input: Value,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub steps_executed: usize,
+    /// Step that was running when the run stopped; resume restarts here.
+    pub last_step: Option<String>,
+    /// Outputs visible to `last_step` (final outputs once the run succeeded).
+    pub outputs: BTreeMap<String, Value>,
+    pub error: Option<String>,
+    pub resumed_from: Option<String>,
+}
+
+impl WorkflowRunRecord {
+    pub fn new(workflow: &str, entrypoint: &str, input: Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            workflow: workflow.to_owned(),
+            entrypoint: entrypoint.to_owned(),
+            status: WorkflowRunStatus::Running,
+            input,
+            started_at: Utc::now(),
+            finished_at: None,
+            steps_executed: 0,
+            last_step: None,
+            outputs: BTreeMap::new(),
+            error: None,
+            resumed_from: None,
+        }
+    }
+
+    /// Starts a new record that continues `previous` from its last step.
+    pub fn resumed(previous: &WorkflowRunRecord) -> Self {
+        let mut record = Self::new(
+            &previous.workflow,
+            &previous.entrypoint,
+            previous.input.clone(),
+        );
+        record.resumed_from = Some(previous.id.clone());
+        record
+    }
+
+    pub fn resume_point(&self) -> Option<WorkflowResumePoint> {
+        if self.status != WorkflowRunStatus::Failed {
+            return None;
+        }
+        self.last_step.as_ref().map(|step_id| WorkflowResumePoint {
+            step_id: step_id.clone(),
+            outputs: self.outputs.clone(),
+        })
+    }
+}
+
+/// File-backed history of headless workflow runs, one JSON document per run.
+#[derive(Debug, Clone)]
+pub struct WorkflowRunStore {
+    root: PathBuf,
+}
+
+impl WorkflowRunStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn record_path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || !id.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-') {
+            return Err(Error::Validation(format!("invalid workflow run id '{id}'")));
+        }
+        Ok(self.root.join(format!("{id}.json")))
+    }
+
+    pub fn save(&self, record: &WorkflowRunRecord) -> Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+        let path = self.record_path(&record.id)?;
+        let serialized = serde_json::to_string_pretty(record)?;
+        std::fs::write(&path, serialized).map_err(|err| {
+            Error::Storage(format!(
+                "failed to write workflow run '{}': {err}",
+                path.display()
+            ))
+        })
+    }
+
+    pub fn load(&self, id: &str) -> Result<WorkflowRunRecord> {
+        let path = self.record_path(id)?;
+        if !path.is_file() {
+            return Err(Error::NotFound(format!("workflow run '{id}'")));
+        }
+        let raw = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&raw).map_err(|err| {
+            Error::Storage(format!(
+                "failed to parse workflow run '{}': {err}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Lists runs newest first, optionally filtered by workflow name.
+    pub fn list(&self, workflow: Option<&str>, limit: usize) -> Result<Vec<WorkflowRunRecord>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(raw) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(record) = serde_json::from_str::<WorkflowRunRecord>(&raw) else {
+                continue;
+            };
+            if workflow.is_some_and(|name| record.workflow != name) {
+                continue;
+            }
+            records.push(record);
+        }
+
+        records.sort_by_key(|record| std::cmp::Reverse(record.started_at));
+        records.truncate(limit);
+        Ok(records)
+    }
+}
@@ -3,12 +3,21 @@ use cron::Schedule;
 use std::collections::HashSet;
 use std::str::FromStr;
 
+use crate::watcher::{compile_globs, FileChangeSet};
 use crate::workflows::registry::WorkflowRegistry;
 
 #[derive(Debug, Clone)]
 pub enum WorkflowTriggerReason {
-    Event { event_name: String },
-    Cron { expression: String },
+    Event {
+        event_name: String,
+    },
+    Cron {
+        expression: String,
+    },
+    /// `paths` are the changed paths matched by the entrypoint's `file_changes` globs.
+    FileChange {
+        paths: Vec<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +54,9 @@ impl WorkflowTriggerEngine {
             let Some(workflow) = workflows.get(&workflow_name) else {
                 continue;
             };
+            if workflow.template {
+                continue;
+            }
 
             for (entrypoint_name, entrypoint) in &workflow.entrypoints {
                 for expr in &entrypoint.triggers.cron {
@@ -86,6 +98,9 @@ impl WorkflowTriggerEngine {
             let Some(workflow) = workflows.get(&workflow_name) else {
                 continue;
             };
+            if workflow.template {
+                continue;
+            }
             for (entrypoint_name, entrypoint) in &workflow.entrypoints {
                 if entrypoint
                     .triggers
@@ -106,4 +121,45 @@ impl WorkflowTriggerEngine {
 
         due
     }
+
+    pub fn for_file_changes(
+        workflows: &WorkflowRegistry,
+        changes: &FileChangeSet,
+    ) -> Vec<WorkflowTriggerMatch> {
+        if changes.is_empty() {
+            return Vec::new();
+        }
+
+        let mut due = Vec::new();
+        for workflow_name in workflows.list() {
+            let Some(workflow) = workflows.get(&workflow_name) else {
+                continue;
+            };
+            if workflow.template {
+                continue;
+            }
+            for (entrypoint_name, entrypoint) in &workflow.entrypoints {
+                if entrypoint.triggers.file_changes.is_empty() {
+                    continue;
+                }
+                let Ok(globs) = compile_globs(&entrypoint.triggers.file_changes) else {
+                    continue;
+                };
+                let paths = changes
+                    .paths()
+                    .filter(|path| globs.is_match(path.as_str()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !paths.is_empty() {
+                    due.push(WorkflowTriggerMatch {
+                        workflow_name: workflow.name.clone(),
+                        entrypoint: entrypoint_name.clone(),
+                        reason: WorkflowTriggerReason::FileChange { paths },
+                    });
+                }
+            }
+        }
+
+        due
+    }
 }
@@ -8,7 +8,15 @@ pub struct WorkflowDefinition {
     pub name: String,
     pub description: String,
     pub version: String,
+    /// Templates are only instantiated by `workflow` steps; triggers never start them.
+    pub template: bool,
     pub timeout_seconds: Option<u64>,
+    /// JSON Schema the run input must satisfy on entry.
+    pub input_schema: Option<Value>,
+    /// Typed parameters bound from the calling step's `config.params`, exposed as `params.*`.
+    pub parameters: BTreeMap<String, WorkflowParameter>,
+    /// Named outputs resolved from the final run outputs and checked on exit.
+    pub outputs: BTreeMap<String, WorkflowOutputContract>,
     pub execution: WorkflowExecutionConfig,
     pub entrypoints: BTreeMap<String, WorkflowEntrypoint>,
     pub steps: Vec<WorkflowStep>,
@@ -20,7 +28,11 @@ impl Default for WorkflowDefinition {
             name: String::new(),
             description: String::new(),
             version: "0.1.0".to_owned(),
+            template: false,
             timeout_seconds: None,
+            input_schema: None,
+            parameters: BTreeMap::new(),
+            outputs: BTreeMap::new(),
             execution: WorkflowExecutionConfig::default(),
             entrypoints: BTreeMap::new(),
             steps: Vec::new(),
@@ -28,6 +40,36 @@ impl Default for WorkflowDefinition {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WorkflowParameter {
+    pub description: String,
+    pub schema: Option<Value>,
+    pub default: Option<Value>,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowOutputContract {
+    /// Output key (`step.<id>.result`, a named step output) or dotted path into it.
+    pub from: String,
+    pub description: String,
+    pub schema: Option<Value>,
+    pub required: bool,
+}
+
+impl Default for WorkflowOutputContract {
+    fn default() -> Self {
+        Self {
+            from: String::new(),
+            description: String::new(),
+            schema: None,
+            required: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct WorkflowExecutionConfig {
@@ -78,6 +120,9 @@ pub struct WorkflowTriggerConfig {
     pub cron: Vec<String>,
     pub events: Vec<String>,
     pub webhooks: Vec<String>,
+    /// Globs relative to the workspace root, e.g. `src/**/*.rs`; matched against
+    /// debounced file-watcher batches.
+    pub file_changes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,6 +168,7 @@ pub enum WorkflowStepKind {
     Loop,
     Merge,
     Switch,
+    Transform,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
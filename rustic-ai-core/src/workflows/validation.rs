@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use super::executor::WorkflowExecutor;
+use super::expressions::{validate_expression, EvaluationOptions};
+use super::graph::step_targets;
+use super::registry::WorkflowRegistry;
+use super::types::{WorkflowDefinition, WorkflowStep, WorkflowStepKind};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowIssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowIssue {
+    pub severity: WorkflowIssueSeverity,
+    pub step_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorkflowValidationReport {
+    pub workflow: String,
+    pub issues: Vec<WorkflowIssue>,
+}
+
+impl WorkflowValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == WorkflowIssueSeverity::Error)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == WorkflowIssueSeverity::Warning)
+            .count()
+    }
+
+    fn error(&mut self, step_id: Option<&str>, message: String) {
+        self.push(WorkflowIssueSeverity::Error, step_id, message);
+    }
+
+    fn warning(&mut self, step_id: Option<&str>, message: String) {
+        self.push(WorkflowIssueSeverity::Warning, step_id, message);
+    }
+
+    fn push(&mut self, severity: WorkflowIssueSeverity, step_id: Option<&str>, message: String) {
+        self.issues.push(WorkflowIssue {
+            severity,
+            step_id: step_id.map(ToOwned::to_owned),
+            message,
+        });
+    }
+}
+
+/// Names known to the runtime. `None` skips the corresponding reference check.
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowValidationContext<'a> {
+    pub tools: Option<BTreeSet<String>>,
+    pub agents: Option<BTreeSet<String>>,
+    pub skills: Option<BTreeSet<String>>,
+    pub workflows: Option<&'a WorkflowRegistry>,
+    pub expression_options: EvaluationOptions,
+}
+
+/// Config keys whose string values are names, paths or regexes rather than
+/// templates, so they are never parsed as expressions.
+const NON_EXPRESSION_KEYS: [&str; 12] = [
+    "tool",
+    "skill",
+    "agent",
+    "workflow",
+    "entrypoint",
+    "pattern",
+    "flags",
+    "target",
+    "default",
+    "expression_error_mode",
+    "pattern_priority",
+    "order",
+];
+
+/// Statically checks `workflow` without running it: routing targets, entrypoints,
+/// reachability, expression syntax and tool/agent/skill/workflow references.
+pub fn validate_workflow(
+    workflow: &WorkflowDefinition,
+    context: &WorkflowValidationContext<'_>,
+) -> WorkflowValidationReport {
+    let mut report = WorkflowValidationReport {
+        workflow: workflow.name.clone(),
+        issues: Vec::new(),
+    };
+    let expression_options = EvaluationOptions {
+        max_length: workflow
+            .execution
+            .expression_max_length
+            .unwrap_or(context.expression_options.max_length),
+        max_depth: workflow
+            .execution
+            .expression_max_depth
+            .unwrap_or(context.expression_options.max_depth),
+    };
+
+    let mut ids = HashSet::new();
+    for step in &workflow.steps {
+        if step.id.trim().is_empty() {
+            report.error(None, "step with empty id".to_owned());
+        } else if !ids.insert(step.id.as_str()) {
+            report.error(Some(&step.id), format!("duplicate step id '{}'", step.id));
+        }
+    }
+
+    if workflow.entrypoints.is_empty() {
+        report.error(None, "workflow defines no entrypoints".to_owned());
+    }
+    if workflow.template {
+        for (name, entrypoint) in &workflow.entrypoints {
+            let triggers = &entrypoint.triggers;
+            if !(triggers.cron.is_empty()
+                && triggers.events.is_empty()
+                && triggers.webhooks.is_empty()
+                && triggers.file_changes.is_empty())
+            {
+                report.warning(
+                    None,
+                    format!("template entrypoint '{name}' declares triggers, which never fire for templates"),
+                );
+            }
+        }
+    }
+    for (name, entrypoint) in &workflow.entrypoints {
+        for pattern in &entrypoint.triggers.file_changes {
+            if let Err(err) = globset::Glob::new(pattern) {
+                report.error(
+                    None,
+                    format!("entrypoint '{name}' has invalid file_changes glob '{pattern}': {err}"),
+                );
+            }
+        }
+        if !ids.contains(entrypoint.step.as_str()) {
+            report.error(
+                None,
+                format!(
+                    "entrypoint '{}' references unknown step '{}'",
+                    name, entrypoint.step
+                ),
+            );
+        }
+    }
+
+    for step in &workflow.steps {
+        for (label, target) in step_targets(step) {
+            if !ids.contains(target.as_str()) {
+                report.error(
+                    Some(&step.id),
+                    format!("{label} references unknown step '{target}'"),
+                );
+            }
+        }
+        check_references(step, context, &mut report);
+        check_expressions(step, expression_options, &mut report);
+    }
+
+    for step_id in unreachable_steps(workflow) {
+        report.warning(
+            Some(&step_id),
+            "step is unreachable from every entrypoint".to_owned(),
+        );
+    }
+
+    report
+}
+
+fn unreachable_steps(workflow: &WorkflowDefinition) -> Vec<String> {
+    let mut reached = HashSet::<String>::new();
+    let mut queue = workflow
+        .entrypoints
+        .values()
+        .map(|entrypoint| entrypoint.step.clone())
+        .collect::<VecDeque<_>>();
+
+    while let Some(current) = queue.pop_front() {
+        if !reached.insert(current.clone()) {
+            continue;
+        }
+        if let Some(step) = workflow.steps.iter().find(|step| step.id == current) {
+            queue.extend(step_targets(step).into_iter().map(|(_, target)| target));
+        }
+    }
+
+    workflow
+        .steps
+        .iter()
+        .filter(|step| !reached.contains(&step.id))
+        .map(|step| step.id.clone())
+        .collect()
+}
+
+fn check_references(
+    step: &WorkflowStep,
+    context: &WorkflowValidationContext<'_>,
+    report: &mut WorkflowValidationReport,
+) {
+    let (key, known) = match step.kind {
+        WorkflowStepKind::Tool => ("tool", context.tools.as_ref()),
+        WorkflowStepKind::Skill => ("skill", context.skills.as_ref()),
+        WorkflowStepKind::Agent => ("agent", context.agents.as_ref()),
+        WorkflowStepKind::Workflow => {
+            check_nested_workflow(step, context.workflows, report);
+            return;
+        }
+        _ => return,
+    };
+
+    let name = step
+        .config
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    match (name, known) {
+        (None, _) if step.kind != WorkflowStepKind::Agent => {
+            report.error(Some(&step.id), format!("missing config.{key}"));
+        }
+        (Some(name), Some(known)) if !known.contains(name) => {
+            report.error(Some(&step.id), format!("unknown {key} '{name}'"));
+        }
+        _ => {}
+    }
+}
+
+fn check_nested_workflow(
+    step: &WorkflowStep,
+    workflows: Option<&WorkflowRegistry>,
+    report: &mut WorkflowValidationReport,
+) {
+    let Some(name) = step.config.get("workflow").and_then(Value::as_str) else {
+        report.error(Some(&step.id), "missing config.workflow".to_owned());
+        return;
+    };
+    let Some(workflows) = workflows else {
+        return;
+    };
+    let Some(nested) = workflows.get(name) else {
+        report.error(Some(&step.id), format!("unknown workflow '{name}'"));
+        return;
+    };
+    let entrypoint = step
+        .config
+        .get("entrypoint")
+        .and_then(Value::as_str)
+        .unwrap_or("start");
+    if !nested.entrypoints.contains_key(entrypoint) {
+        report.error(
+            Some(&step.id),
+            format!("workflow '{name}' has no entrypoint '{entrypoint}'"),
+        );
+    }
+
+    let params = step.config.get("params").and_then(Value::as_object);
+    for key in params.into_iter().flat_map(|params| params.keys()) {
+        if !nested.parameters.contains_key(key) {
+            report.error(
+                Some(&step.id),
+                format!("workflow '{name}' has no parameter '{key}'"),
+            );
+        }
+    }
+    for (key, parameter) in &nested.parameters {
+        let provided = params.is_some_and(|params| params.contains_key(key));
+        if parameter.required && parameter.default.is_none() && !provided {
+            report.error(
+                Some(&step.id),
+                format!("workflow '{name}' requires parameter '{key}'"),
+            );
+        }
+    }
+}
+
+fn check_expressions(
+    step: &WorkflowStep,
+    options: EvaluationOptions,
+    report: &mut WorkflowValidationReport,
+) {
+    let mut failures = Vec::new();
+    collect_expression_failures(&step.config, None, options, &mut failures);
+    if failures.is_empty() {
+        return;
+    }
+
+    // Steps that swallow expression errors at runtime only warrant a warning.
+    let lenient = matches!(
+        step.config
+            .get("expression_error_mode")
+            .and_then(Value::as_str),
+        Some("null" | "literal")
+    );
+    for failure in failures {
+        if lenient {
+            report.warning(Some(&step.id), failure);
+        } else {
+            report.error(Some(&step.id), failure);
+        }
+    }
+}
+
+fn collect_expression_failures(
+    value: &Value,
+    key: Option<&str>,
+    options: EvaluationOptions,
+    failures: &mut Vec<String>,
+) {
+    match value {
+        Value::String(text) => {
+            if key.is_some_and(|key| NON_EXPRESSION_KEYS.contains(&key)) {
+                return;
+            }
+            // Condition expressions and transform `expression`/`by` are evaluated raw.
+            let expression = if matches!(key, Some("expression" | "by")) {
+                text.trim()
+            } else if WorkflowExecutor::is_expression_candidate(text) {
+                WorkflowExecutor::expression_from_template(text)
+            } else {
+                return;
+            };
+            if let Err(err) = validate_expression(expression, options) {
+                failures.push(format!("invalid expression '{expression}': {err}"));
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_expression_failures(item, key, options, failures);
+            }
+        }
+        Value::Object(map) => {
+            for (child_key, item) in map {
+                if matches!(child_key.as_str(), "cases" | "outputs") {
+                    continue;
+                }
+                collect_expression_failures(item, Some(child_key), options, failures);
+            }
+        }
+        _ => {}
+    }
+}